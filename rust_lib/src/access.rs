@@ -0,0 +1,223 @@
+//! Per-row access tracking for usage-aware eviction and cold data reports.
+//!
+//! When enabled, every label returned by a search counts as a hit. Hits are
+//! buffered in memory and flushed into a sidecar Lance table (`<table>__access`,
+//! one row per label with its hit count and last access time) once enough labels
+//! are pending or enough time has passed, so searches never write to the
+//! vector table itself. Flushes triggered by searches run in the background.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, Int64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Flush once this many distinct labels are buffered.
+pub const FLUSH_MAX_PENDING: usize = 4096;
+/// Flush buffered hits at least this often while searches keep arriving.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Hit count and last access time (milliseconds since the Unix epoch) of one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Access {
+    pub hits: i64,
+    pub last_access_ms: i64,
+}
+
+impl Access {
+    /// Combine two records of the same label.
+    pub fn merge(self, other: Access) -> Access {
+        Access {
+            hits: self.hits + other.hits,
+            last_access_ms: self.last_access_ms.max(other.last_access_ms),
+        }
+    }
+}
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+pub fn sidecar_table_name(table_name: &str) -> String {
+    format!("{}__access", table_name)
+}
+
+pub fn sidecar_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("label", DataType::Int64, false),
+        Field::new("hits", DataType::Int64, false),
+        Field::new("last_access_ms", DataType::Int64, false),
+    ]))
+}
+
+pub fn to_record_batch(accesses: &HashMap<i64, Access>) -> Result<RecordBatch> {
+    let mut rows: Vec<(&i64, &Access)> = accesses.iter().collect();
+    rows.sort_by_key(|(label, _)| **label);
+    Ok(RecordBatch::try_new(sidecar_schema(), vec![
+        Arc::new(Int64Array::from(rows.iter().map(|(l, _)| **l).collect::<Vec<_>>())),
+        Arc::new(Int64Array::from(rows.iter().map(|(_, a)| a.hits).collect::<Vec<_>>())),
+        Arc::new(Int64Array::from(rows.iter().map(|(_, a)| a.last_access_ms).collect::<Vec<_>>())),
+    ])?)
+}
+
+/// Fold a sidecar batch into `into`.
+pub fn read_batch(batch: &RecordBatch, into: &mut HashMap<i64, Access>) -> Result<()> {
+    let column = |name: &str| -> Result<&Int64Array> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+            .ok_or_else(|| anyhow!("access table column {} missing or not Int64", name))
+    };
+    let (labels, hits, last) = (column("label")?, column("hits")?, column("last_access_ms")?);
+    for i in 0..batch.num_rows() {
+        let access = Access {
+            hits: hits.value(i),
+            last_access_ms: last.value(i),
+        };
+        into.entry(labels.value(i))
+            .and_modify(|a| *a = a.merge(access))
+            .or_insert(access);
+    }
+    Ok(())
+}
+
+struct Pending {
+    hits: HashMap<i64, Access>,
+    last_flush: Instant,
+}
+
+/// In-memory hit buffer of one handle.
+pub struct AccessTracker {
+    enabled: AtomicBool,
+    pending: Mutex<Pending>,
+    /// Held by a flush, which reads and rewrites the recorded counts.
+    flushing: Mutex<()>,
+}
+
+impl AccessTracker {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            pending: Mutex::new(Pending {
+                hits: HashMap::new(),
+                last_flush: Instant::now(),
+            }),
+            flushing: Mutex::new(()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Count one hit per label. Returns true when a flush is due.
+    pub fn record(&self, labels: &[i64]) -> bool {
+        if !self.enabled() || labels.is_empty() {
+            return false;
+        }
+        let now = now_ms();
+        let Ok(mut pending) = self.pending.lock() else {
+            return false;
+        };
+        for &label in labels {
+            let hit = Access {
+                hits: 1,
+                last_access_ms: now,
+            };
+            pending
+                .hits
+                .entry(label)
+                .and_modify(|a| *a = a.merge(hit))
+                .or_insert(hit);
+        }
+        pending.hits.len() >= FLUSH_MAX_PENDING || pending.last_flush.elapsed() >= FLUSH_INTERVAL
+    }
+
+    /// Buffered hits, without draining them.
+    pub fn snapshot(&self) -> HashMap<i64, Access> {
+        self.pending.lock().map(|p| p.hits.clone()).unwrap_or_default()
+    }
+
+    /// Serialize flushes: hold the guard from [`AccessTracker::take`] until the
+    /// flushed hits are written.
+    pub fn lock_flush(&self) -> MutexGuard<'_, ()> {
+        self.flushing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drain the buffer for a flush.
+    pub fn take(&self) -> HashMap<i64, Access> {
+        match self.pending.lock() {
+            Ok(mut pending) => {
+                pending.last_flush = Instant::now();
+                std::mem::take(&mut pending.hits)
+            }
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Put back hits whose flush failed, so they are retried with the next one.
+    pub fn restore(&self, hits: HashMap<i64, Access>) {
+        if let Ok(mut pending) = self.pending.lock() {
+            for (label, access) in hits {
+                pending
+                    .hits
+                    .entry(label)
+                    .and_modify(|a| *a = a.merge(access))
+                    .or_insert(access);
+            }
+        }
+    }
+
+    /// Forget buffered hits for deleted labels.
+    pub fn forget(&self, labels: &[i64]) {
+        if let Ok(mut pending) = self.pending.lock() {
+            for label in labels {
+                pending.hits.remove(label);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_take() {
+        let tracker = AccessTracker::new(false);
+        assert!(!tracker.record(&[1]));
+        assert!(tracker.snapshot().is_empty());
+
+        tracker.set_enabled(true);
+        tracker.record(&[1, 2]);
+        tracker.record(&[1]);
+        let hits = tracker.take();
+        assert_eq!(hits[&1].hits, 2);
+        assert_eq!(hits[&2].hits, 1);
+        assert!(tracker.snapshot().is_empty());
+
+        tracker.restore(hits);
+        tracker.forget(&[2]);
+        assert_eq!(tracker.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let mut accesses = HashMap::new();
+        accesses.insert(7, Access { hits: 3, last_access_ms: 100 });
+        let batch = to_record_batch(&accesses).unwrap();
+
+        let mut merged = HashMap::new();
+        merged.insert(7, Access { hits: 1, last_access_ms: 200 });
+        read_batch(&batch, &mut merged).unwrap();
+        assert_eq!(merged[&7], Access { hits: 4, last_access_ms: 200 });
+    }
+}
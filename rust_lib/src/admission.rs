@@ -0,0 +1,294 @@
+//! Per-handle admission control for heavy operations.
+//!
+//! Searches and maintenance work (index builds, compaction) each draw permits
+//! from their own semaphore. Callers queue for a permit up to a timeout, so a
+//! burst of analytics queries cannot starve ingestion or exhaust memory.
+//!
+//! Searches run in the interactive lane; maintenance runs in the background lane
+//! and calls [`AdmissionControl::yield_to_interactive`] between batches, pausing
+//! (for a bounded time) while searches are in flight.
+
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+use crate::runtime;
+
+/// Operation classes subject to admission control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpClass {
+    Search,
+    /// Index builds and compaction.
+    Maintenance,
+}
+
+/// Scheduling lane of an operation class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Interactive,
+    Background,
+}
+
+impl OpClass {
+    pub fn priority(self) -> Priority {
+        match self {
+            OpClass::Search => Priority::Interactive,
+            OpClass::Maintenance => Priority::Background,
+        }
+    }
+}
+
+/// Concurrency limits for a single handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdmissionLimits {
+    pub max_concurrent_searches: usize,
+    pub max_concurrent_maintenance: usize,
+    /// How long a caller may queue for a permit. Zero waits indefinitely.
+    pub queue_timeout: Duration,
+    /// Longest a background task pauses at one yield point while searches run.
+    pub max_background_yield: Duration,
+}
+
+impl Default for AdmissionLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent_searches: 16,
+            max_concurrent_maintenance: 1,
+            queue_timeout: Duration::from_secs(30),
+            max_background_yield: Duration::from_millis(250),
+        }
+    }
+}
+
+struct Semaphores {
+    limits: AdmissionLimits,
+    searches: Arc<Semaphore>,
+    maintenance: Arc<Semaphore>,
+}
+
+impl Semaphores {
+    fn new(limits: AdmissionLimits) -> Self {
+        Self {
+            limits,
+            searches: Arc::new(Semaphore::new(limits.max_concurrent_searches)),
+            maintenance: Arc::new(Semaphore::new(limits.max_concurrent_maintenance)),
+        }
+    }
+}
+
+/// Counts in-flight interactive operations and wakes yielding background work
+/// when the count drops to zero.
+#[derive(Default)]
+struct InteractiveTracker {
+    active: AtomicUsize,
+    idle: Notify,
+}
+
+/// A granted admission. Releases the semaphore permit (and the interactive slot) on drop.
+pub struct AdmissionPermit {
+    _permit: OwnedSemaphorePermit,
+    interactive: Option<Arc<InteractiveTracker>>,
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        if let Some(tracker) = &self.interactive {
+            if tracker.active.fetch_sub(1, Ordering::AcqRel) == 1 {
+                tracker.idle.notify_waiters();
+            }
+        }
+    }
+}
+
+/// Holds the semaphores for one handle. Permits are released on drop.
+pub struct AdmissionControl {
+    inner: RwLock<Semaphores>,
+    interactive: Arc<InteractiveTracker>,
+}
+
+impl AdmissionControl {
+    pub fn new(limits: AdmissionLimits) -> Self {
+        Self {
+            inner: RwLock::new(Semaphores::new(limits)),
+            interactive: Arc::new(InteractiveTracker::default()),
+        }
+    }
+
+    /// Number of interactive operations currently holding permits.
+    pub fn interactive_in_flight(&self) -> usize {
+        self.interactive.active.load(Ordering::Acquire)
+    }
+
+    /// Pause background work while interactive operations are in flight, for at most
+    /// `max_background_yield`. Call between batches of long-running maintenance.
+    pub fn yield_to_interactive(&self) {
+        let max_wait = self.limits().max_background_yield;
+        let deadline = Instant::now() + max_wait;
+        loop {
+            // Create the waiter before checking the count so a concurrent release isn't missed.
+            let idle = self.interactive.idle.notified();
+            if self.interactive_in_flight() == 0 {
+                return;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return;
+            }
+            if runtime::block_on(tokio::time::timeout(remaining, idle)).is_err() {
+                return;
+            }
+        }
+    }
+
+    pub fn limits(&self) -> AdmissionLimits {
+        self.inner.read().map(|s| s.limits).unwrap_or_default()
+    }
+
+    /// Replace the limits. Operations already holding permits finish under the old limits.
+    pub fn set_limits(&self, limits: AdmissionLimits) -> Result<()> {
+        if limits.max_concurrent_searches == 0 || limits.max_concurrent_maintenance == 0 {
+            return Err(anyhow!("concurrency limits must be at least 1"));
+        }
+        let mut inner = self
+            .inner
+            .write()
+            .map_err(|_| anyhow!("admission lock poisoned"))?;
+        *inner = Semaphores::new(limits);
+        Ok(())
+    }
+
+    /// Block until a permit for `class` is available or the queue timeout elapses.
+    pub fn acquire(&self, class: OpClass) -> Result<AdmissionPermit> {
+        let (semaphore, limit, timeout) = {
+            let inner = self
+                .inner
+                .read()
+                .map_err(|_| anyhow!("admission lock poisoned"))?;
+            match class {
+                OpClass::Search => (
+                    inner.searches.clone(),
+                    inner.limits.max_concurrent_searches,
+                    inner.limits.queue_timeout,
+                ),
+                OpClass::Maintenance => (
+                    inner.maintenance.clone(),
+                    inner.limits.max_concurrent_maintenance,
+                    inner.limits.queue_timeout,
+                ),
+            }
+        };
+
+        let permit = if timeout.is_zero() {
+            runtime::block_on(semaphore.acquire_owned())
+        } else {
+            runtime::block_on(tokio::time::timeout(timeout, semaphore.acquire_owned())).map_err(
+                |_| {
+                    anyhow!(
+                        "{:?} admission timed out after {}ms ({} operations in flight)",
+                        class,
+                        timeout.as_millis(),
+                        limit
+                    )
+                },
+            )?
+        };
+        let permit = permit.map_err(|_| anyhow!("admission semaphore closed"))?;
+
+        let interactive = match class.priority() {
+            Priority::Interactive => {
+                self.interactive.active.fetch_add(1, Ordering::AcqRel);
+                Some(self.interactive.clone())
+            }
+            Priority::Background => None,
+        };
+        Ok(AdmissionPermit {
+            _permit: permit,
+            interactive,
+        })
+    }
+}
+
+impl Default for AdmissionControl {
+    fn default() -> Self {
+        Self::new(AdmissionLimits::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_times_out_when_saturated() {
+        let control = AdmissionControl::new(AdmissionLimits {
+            max_concurrent_searches: 1,
+            max_concurrent_maintenance: 1,
+            queue_timeout: Duration::from_millis(20),
+            ..AdmissionLimits::default()
+        });
+
+        let held = control.acquire(OpClass::Maintenance).unwrap();
+        let err = control.acquire(OpClass::Maintenance).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+
+        // Other classes are unaffected
+        let _search = control.acquire(OpClass::Search).unwrap();
+
+        drop(held);
+        assert!(control.acquire(OpClass::Maintenance).is_ok());
+    }
+
+    #[test]
+    fn test_background_yields_until_search_completes() {
+        let control = Arc::new(AdmissionControl::new(AdmissionLimits {
+            max_background_yield: Duration::from_secs(5),
+            ..AdmissionLimits::default()
+        }));
+
+        // No interactive work: yield returns immediately
+        let start = Instant::now();
+        control.yield_to_interactive();
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        let search = control.acquire(OpClass::Search).unwrap();
+        assert_eq!(control.interactive_in_flight(), 1);
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            drop(search);
+        });
+
+        let start = Instant::now();
+        control.yield_to_interactive();
+        let waited = start.elapsed();
+        release.join().unwrap();
+        assert!(waited >= Duration::from_millis(40));
+        assert!(waited < Duration::from_secs(5));
+        assert_eq!(control.interactive_in_flight(), 0);
+    }
+
+    #[test]
+    fn test_background_yield_is_bounded() {
+        let control = AdmissionControl::new(AdmissionLimits {
+            max_background_yield: Duration::from_millis(30),
+            ..AdmissionLimits::default()
+        });
+        let _search = control.acquire(OpClass::Search).unwrap();
+
+        let start = Instant::now();
+        control.yield_to_interactive();
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_set_limits_rejects_zero() {
+        let control = AdmissionControl::default();
+        let limits = AdmissionLimits {
+            max_concurrent_searches: 0,
+            ..AdmissionLimits::default()
+        };
+        assert!(control.set_limits(limits).is_err());
+        assert_eq!(control.limits(), AdmissionLimits::default());
+    }
+}
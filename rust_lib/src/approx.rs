@@ -0,0 +1,266 @@
+//! Approximate per-group statistics with bounded memory.
+//!
+//! [`GroupStatsBuilder`] counts rows per value of a metadata column, optionally with
+//! the distribution of distances to a query vector in each group. At most
+//! `max_groups` groups are tracked individually; rows of groups seen after that fold
+//! into a single "other" bucket. The number of distinct values is estimated with a
+//! HyperLogLog sketch, so it stays meaningful when the groups overflow.
+
+use anyhow::Result;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use arrow_array::{Array, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+/// log2 of the HyperLogLog register count: 4096 registers, ~1.6% standard error.
+const HLL_PRECISION: u32 = 12;
+
+/// HyperLogLog distinct-count sketch (Flajolet et al.).
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_PRECISION],
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let register = (hash >> (64 - HLL_PRECISION)) as usize;
+        // Position of the first set bit in the remaining bits, 1-based
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.registers[register] = self.registers[register].max(rank);
+    }
+
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        // Linear counting is more accurate while many registers are still empty
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+/// Rows and distance distribution of one group.
+#[derive(Debug, Clone, Default)]
+pub struct GroupStats {
+    pub rows: u64,
+    pub distances: u64,
+    pub min_distance: f64,
+    pub max_distance: f64,
+    pub sum_distance: f64,
+}
+
+impl GroupStats {
+    fn observe(&mut self, distance: Option<f32>) {
+        self.rows += 1;
+        if let Some(d) = distance.map(f64::from) {
+            if self.distances == 0 {
+                self.min_distance = d;
+                self.max_distance = d;
+            } else {
+                self.min_distance = self.min_distance.min(d);
+                self.max_distance = self.max_distance.max(d);
+            }
+            self.sum_distance += d;
+            self.distances += 1;
+        }
+    }
+
+    pub fn mean_distance(&self) -> Option<f64> {
+        (self.distances > 0).then(|| self.sum_distance / self.distances as f64)
+    }
+}
+
+/// Per-group statistics over (a sample of) the table.
+#[derive(Debug, Clone)]
+pub struct ApproxStats {
+    /// Tracked groups by value (`None` for null), largest first.
+    pub groups: Vec<(Option<String>, GroupStats)>,
+    /// Rows of groups beyond `max_groups`.
+    pub other: GroupStats,
+    /// Distinct values seen: exact when no group overflowed, otherwise estimated.
+    /// Under sampling it only counts values present in the sample.
+    pub distinct_groups: f64,
+    /// Fraction of rows read; row counts are scaled up by its inverse.
+    pub fraction: f64,
+}
+
+impl ApproxStats {
+    /// Rows of (group_value, other, row_count, min_distance, avg_distance, max_distance).
+    /// The "other" bucket, when any rows fell into it, is the last row and has a null
+    /// group_value. Distance columns are null for groups without distances.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let mut rows: Vec<(Option<&str>, bool, &GroupStats)> =
+            self.groups.iter().map(|(value, stats)| (value.as_deref(), false, stats)).collect();
+        if self.other.rows > 0 {
+            rows.push((None, true, &self.other));
+        }
+        let distance = |f: fn(&GroupStats) -> f64| -> Float64Array {
+            rows.iter().map(|r| (r.2.distances > 0).then(|| f(r.2))).collect()
+        };
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("group_value", DataType::Utf8, true),
+            Field::new("other", DataType::Boolean, false),
+            Field::new("row_count", DataType::Int64, false),
+            Field::new("min_distance", DataType::Float64, true),
+            Field::new("avg_distance", DataType::Float64, true),
+            Field::new("max_distance", DataType::Float64, true),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(rows.iter().map(|r| r.0).collect::<StringArray>()),
+            Arc::new(rows.iter().map(|r| Some(r.1)).collect::<BooleanArray>()),
+            Arc::new(Int64Array::from(
+                rows.iter()
+                    .map(|r| (r.2.rows as f64 / self.fraction).round() as i64)
+                    .collect::<Vec<_>>(),
+            )),
+            Arc::new(distance(|s| s.min_distance)),
+            Arc::new(distance(|s| s.mean_distance().unwrap_or_default())),
+            Arc::new(distance(|s| s.max_distance)),
+        ])?)
+    }
+}
+
+/// Accumulates [`ApproxStats`] across result batches.
+pub struct GroupStatsBuilder {
+    max_groups: usize,
+    groups: HashMap<Option<String>, GroupStats>,
+    other: GroupStats,
+    overflowed: bool,
+    sketch: HyperLogLog,
+}
+
+impl GroupStatsBuilder {
+    pub fn new(max_groups: usize) -> Self {
+        Self {
+            max_groups,
+            groups: HashMap::new(),
+            other: GroupStats::default(),
+            overflowed: false,
+            sketch: HyperLogLog::default(),
+        }
+    }
+
+    /// Fold in one batch of group values, with each row's distance when known.
+    pub fn update(&mut self, values: &dyn Array, distances: Option<&[f32]>) -> Result<()> {
+        let formatter = ArrayFormatter::try_new(values, &FormatOptions::default())?;
+        for i in 0..values.len() {
+            let key = (!values.is_null(i)).then(|| formatter.value(i).to_string());
+            self.sketch.insert(&key);
+            let distance = distances.map(|d| d[i]);
+            match self.groups.get_mut(&key) {
+                Some(stats) => stats.observe(distance),
+                None if self.groups.len() < self.max_groups => {
+                    self.groups.entry(key).or_default().observe(distance);
+                }
+                None => {
+                    self.overflowed = true;
+                    self.other.observe(distance);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self, fraction: f64) -> ApproxStats {
+        let distinct_groups = if self.overflowed {
+            self.sketch.estimate().max((self.groups.len() + 1) as f64)
+        } else {
+            self.groups.len() as f64
+        };
+        let mut groups: Vec<_> = self.groups.into_iter().collect();
+        groups.sort_by(|a, b| b.1.rows.cmp(&a.1.rows).then_with(|| a.0.cmp(&b.0)));
+        ApproxStats {
+            groups,
+            other: self.other,
+            distinct_groups,
+            fraction,
+        }
+    }
+}
+
+/// `m` of `items` drawn uniformly at random without replacement (a partial
+/// Fisher-Yates shuffle driven by splitmix64 from `seed`), in ascending order.
+/// Every item is equally likely to be drawn, whatever its position.
+pub fn sample<T: Ord>(mut items: Vec<T>, m: usize, seed: u64) -> Vec<T> {
+    let m = m.min(items.len());
+    let mut state = seed;
+    for i in 0..m {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // Multiply-shift maps z onto i..len without a modulo
+        let span = (items.len() - i) as u128;
+        let j = i + ((z as u128 * span) >> 64) as usize;
+        items.swap(i, j);
+    }
+    items.truncate(m);
+    items.sort_unstable();
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_stats_overflow_and_sketch() {
+        let mut builder = GroupStatsBuilder::new(2);
+        let values = Int64Array::from(vec![Some(1), Some(1), Some(2), None, Some(3), Some(1)]);
+        builder
+            .update(&values, Some(&[0.5, 1.5, 2.0, 3.0, 4.0, 1.0]))
+            .unwrap();
+        let stats = builder.finish(0.5);
+        assert_eq!(stats.groups[0].0.as_deref(), Some("1"));
+        assert_eq!(stats.groups[0].1.rows, 3);
+        assert_eq!(stats.groups[0].1.mean_distance(), Some(1.0));
+        assert_eq!(stats.other.rows, 2);
+        assert!(stats.distinct_groups >= 3.0);
+
+        let batch = stats.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        let counts = batch.column(2).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(counts.value(0), 6);
+
+        let mut sketch = HyperLogLog::default();
+        (0..10_000).for_each(|i| sketch.insert(&i));
+        assert!((sketch.estimate() - 10_000.0).abs() < 500.0);
+    }
+
+    #[test]
+    fn test_sample_is_uniform() {
+        let drawn = sample((0..100).collect::<Vec<u64>>(), 10, 7);
+        assert_eq!(drawn.len(), 10);
+        assert!(drawn.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample(vec![1, 2, 3], 5, 7), vec![1, 2, 3]);
+
+        // Across seeds, the first and second half of the items are drawn alike
+        let mut hits = [0usize; 2];
+        for seed in 0..2000 {
+            for item in sample((0..100).collect::<Vec<usize>>(), 10, seed) {
+                hits[item / 50] += 1;
+            }
+        }
+        assert!(hits[0].abs_diff(hits[1]) < 1000, "{:?}", hits);
+    }
+}
@@ -0,0 +1,165 @@
+//! Incremental backups of a table's dataset files.
+//!
+//! Lance never rewrites a file: data files, deletion files and index directories
+//! have unique names, and each version adds one manifest. A backup is therefore a
+//! copy of the dataset directory that later backups extend. [`backup`] copies the
+//! manifests of the versions after `since_version`, the data and deletion files
+//! those versions reference that `since_version` did not, and the index files the
+//! destination lacks. Chained backups into one destination keep every backed-up
+//! version: the destination is itself a Lance dataset that can be checked out (and
+//! restored) at any of them.
+//!
+//! Files are copied before the manifests that reference them, oldest version first,
+//! so an interrupted backup leaves the destination readable at the versions it
+//! completed.
+
+use anyhow::{anyhow, Result};
+use futures_util::TryStreamExt;
+use lance::dataset::Dataset;
+use lance::io::{ObjectStore, ObjectStoreParams, ObjectStoreRegistry};
+use object_store::path::Path;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// What one backup copied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackupReport {
+    /// Version the backup started after; `None` for a full backup.
+    pub since_version: Option<u64>,
+    /// Latest version now in the destination.
+    pub version: u64,
+    /// Manifests copied.
+    pub versions: usize,
+    /// Files copied, manifests included.
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Copy `source` (at its checked-out version) into the dataset directory `dest`,
+/// skipping what a backup up to `since_version` already holds. With a
+/// `since_version`, `dest` must contain that version.
+pub async fn backup(
+    source: &Dataset,
+    source_params: &ObjectStoreParams,
+    dest: &str,
+    dest_params: &ObjectStoreParams,
+    since_version: Option<u64>,
+) -> Result<BackupReport> {
+    let registry = Arc::new(ObjectStoreRegistry::default());
+    let (from_store, from_base) =
+        ObjectStore::from_uri_and_params(registry.clone(), source.uri(), source_params).await?;
+    let (to_store, to_base) = ObjectStore::from_uri_and_params(registry, dest, dest_params).await?;
+    let scheme = source.manifest_naming_scheme;
+    let latest = source.version().version;
+
+    let mut copied: HashSet<String> = HashSet::new();
+    if let Some(since) = since_version {
+        if since > latest {
+            return Err(anyhow!("version {} is newer than the table (version {})", since, latest));
+        }
+        let manifest = relative(&scheme.manifest_path(&Path::default(), since));
+        if !to_store.exists(&join(&to_base, &manifest)).await? {
+            return Err(anyhow!("{} holds no backup of version {}; back that version up first", dest, since));
+        }
+        copied.extend(referenced_files(&source.checkout_version(since).await?));
+    }
+    let mut versions: Vec<u64> = source
+        .versions()
+        .await?
+        .into_iter()
+        .map(|v| v.version)
+        .filter(|v| since_version.map_or(true, |since| *v > since) && *v <= latest)
+        .collect();
+    versions.sort_unstable();
+
+    let mut report = BackupReport {
+        since_version,
+        version: since_version.unwrap_or_default(),
+        ..Default::default()
+    };
+    // Index directories are never listed in the manifests Lance exposes, so copy
+    // whichever the destination lacks
+    let existing: HashSet<String> = list_relative(&to_store, &to_base, "_indices").await?.into_iter().collect();
+    for file in list_relative(&from_store, &from_base, "_indices").await? {
+        if !existing.contains(&file) {
+            report.bytes += copy(&from_store, &from_base, &to_store, &to_base, &file).await?;
+            report.files += 1;
+        }
+    }
+    for version in versions {
+        let dataset = source.checkout_version(version).await?;
+        for file in referenced_files(&dataset) {
+            if copied.insert(file.clone()) {
+                report.bytes += copy(&from_store, &from_base, &to_store, &to_base, &file).await?;
+                report.files += 1;
+            }
+        }
+        let manifest = relative(&scheme.manifest_path(&Path::default(), version));
+        report.bytes += copy(&from_store, &from_base, &to_store, &to_base, &manifest).await?;
+        report.files += 1;
+        report.versions += 1;
+        report.version = version;
+    }
+    // Datasets with the V1 naming scheme also keep a copy of the latest manifest
+    if report.versions > 0 && from_store.exists(&join(&from_base, "_latest.manifest")).await? {
+        let manifest = relative(&scheme.manifest_path(&Path::default(), report.version));
+        let bytes = from_store.inner.get(&join(&from_base, &manifest)).await?.bytes().await?;
+        to_store.inner.put(&join(&to_base, "_latest.manifest"), bytes.into()).await?;
+    }
+    Ok(report)
+}
+
+/// Data and deletion files of `dataset`'s version, relative to its root.
+pub(crate) fn referenced_files(dataset: &Dataset) -> Vec<String> {
+    let mut files = Vec::new();
+    for fragment in dataset.manifest().fragments.iter() {
+        files.extend(fragment.files.iter().map(|f| format!("data/{}", f.path)));
+        if let Some(deletion) = &fragment.deletion_file {
+            files.push(format!(
+                "_deletions/{}-{}-{}.{}",
+                fragment.id,
+                deletion.read_version,
+                deletion.id,
+                deletion.file_type.suffix()
+            ));
+        }
+    }
+    files
+}
+
+/// Files under `dir` of the dataset at `base`, relative to `base`.
+async fn list_relative(store: &ObjectStore, base: &Path, dir: &str) -> Result<Vec<String>> {
+    let prefix = join(base, dir);
+    let objects: Vec<_> = store.inner.list(Some(&prefix)).try_collect().await?;
+    Ok(objects
+        .into_iter()
+        .filter_map(|meta| meta.location.prefix_match(base).map(|parts| relative(&Path::from_iter(parts))))
+        .collect())
+}
+
+/// Copy `file` (relative to the dataset roots). Returns its size.
+async fn copy(from: &ObjectStore, from_base: &Path, to: &ObjectStore, to_base: &Path, file: &str) -> Result<u64> {
+    let bytes = from.inner.get(&join(from_base, file)).await?.bytes().await?;
+    let size = bytes.len() as u64;
+    to.inner.put(&join(to_base, file), bytes.into()).await?;
+    Ok(size)
+}
+
+pub(crate) fn join(base: &Path, relative: &str) -> Path {
+    Path::from_iter(base.parts().chain(Path::from(relative).parts()))
+}
+
+fn relative(path: &Path) -> String {
+    path.as_ref().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        assert_eq!(join(&Path::from("db/t.lance"), "data/a.lance").as_ref(), "db/t.lance/data/a.lance");
+        assert_eq!(join(&Path::default(), "_versions/1.manifest").as_ref(), "_versions/1.manifest");
+    }
+}
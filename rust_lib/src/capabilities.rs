@@ -0,0 +1,258 @@
+//! What an existing Lance table offers to the index layer.
+//!
+//! Tables written by other tools often lack the `label` key or the `vector` column
+//! this extension relies on, or type them differently. [`TableCapabilities`] lists
+//! the key, the FixedSizeList columns with their dims and the indices present, plus
+//! the problems that keep the table from opening as an index, so callers can tell
+//! users which open mode fits instead of failing on the first missing piece.
+
+use anyhow::Result;
+use arrow_array::{BooleanArray, Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// A FixedSizeList column usable as vectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorColumn {
+    pub name: String,
+    pub dimension: usize,
+    pub item_type: DataType,
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexInfo {
+    pub name: String,
+    pub index_type: String,
+    pub columns: Vec<String>,
+}
+
+/// An index of an opened table with its coverage (see `LanceIndex::list_indices`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStatus {
+    pub info: IndexInfo,
+    pub indexed_rows: u64,
+    /// Rows appended since the index was last built or optimized.
+    pub unindexed_rows: u64,
+    /// Distance type of a vector index, when this extension built it.
+    pub metric: Option<String>,
+}
+
+impl IndexStatus {
+    /// `statuses` as rows of (name, index_type, columns, indexed_rows, unindexed_rows, metric).
+    pub fn to_record_batch(statuses: &[IndexStatus]) -> Result<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("index_type", DataType::Utf8, false),
+            Field::new("columns", DataType::Utf8, false),
+            Field::new("indexed_rows", DataType::Int64, false),
+            Field::new("unindexed_rows", DataType::Int64, false),
+            Field::new("metric", DataType::Utf8, true),
+        ]));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from_iter_values(statuses.iter().map(|s| s.info.name.as_str()))),
+                Arc::new(StringArray::from_iter_values(statuses.iter().map(|s| s.info.index_type.as_str()))),
+                Arc::new(StringArray::from_iter_values(statuses.iter().map(|s| s.info.columns.join(",")))),
+                Arc::new(Int64Array::from_iter_values(statuses.iter().map(|s| s.indexed_rows as i64))),
+                Arc::new(Int64Array::from_iter_values(statuses.iter().map(|s| s.unindexed_rows as i64))),
+                Arc::new(StringArray::from_iter(statuses.iter().map(|s| s.metric.as_deref()))),
+            ],
+        )?)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableCapabilities {
+    /// The `label` column, if the table has one.
+    pub label: Option<Field>,
+    /// Rows whose label is NULL; only counted for nullable labels.
+    pub null_labels: u64,
+    pub vector_columns: Vec<VectorColumn>,
+    pub indices: Vec<IndexInfo>,
+}
+
+impl TableCapabilities {
+    /// Key and vector columns of `schema`; indices and NULL labels are filled in by
+    /// the caller.
+    pub fn from_schema(schema: &Schema) -> Self {
+        let vector_columns = schema
+            .fields()
+            .iter()
+            .filter_map(|f| match f.data_type() {
+                DataType::FixedSizeList(item, dim) => Some(VectorColumn {
+                    name: f.name().clone(),
+                    dimension: *dim as usize,
+                    item_type: item.data_type().clone(),
+                    nullable: f.is_nullable(),
+                }),
+                _ => None,
+            })
+            .collect();
+        Self {
+            label: schema.field_with_name("label").ok().cloned(),
+            null_labels: 0,
+            vector_columns,
+            indices: Vec::new(),
+        }
+    }
+
+    /// The `vector` column, the one searches and appends use.
+    pub fn vector(&self) -> Option<&VectorColumn> {
+        self.vector_columns.iter().find(|c| c.name == "vector")
+    }
+
+    /// Why the table cannot be opened as an index; empty when it can.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match &self.label {
+            None => problems.push("no label column (an Int64 row key)".to_string()),
+            Some(label) if label.data_type() != &DataType::Int64 => {
+                problems.push(format!("label column is {}, expected Int64", label.data_type()))
+            }
+            Some(_) if self.null_labels > 0 => {
+                problems.push(format!("label column has {} NULL labels", self.null_labels))
+            }
+            Some(_) => {}
+        }
+        match (self.vector(), self.vector_columns.as_slice()) {
+            (Some(vector), _) if vector.dimension == 0 => problems.push("vector column has dimension 0".to_string()),
+            (Some(_), _) => {}
+            (None, []) => problems.push("no FixedSizeList vector column".to_string()),
+            (None, columns) => problems.push(format!(
+                "no column named vector (FixedSizeList columns: {})",
+                columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+        problems
+    }
+
+    /// The report as rows of (kind, name, data_type, nullable, dimension, detail):
+    /// a `label` row if present, a `vector` row per FixedSizeList column (data_type
+    /// is the element type), an `index` row per index (data_type is the index type,
+    /// detail its columns) and a `problem` row per entry of [`Self::problems`].
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        type Row = (&'static str, String, Option<String>, Option<bool>, Option<i32>, Option<String>);
+        let mut rows: Vec<Row> = Vec::new();
+        if let Some(label) = &self.label {
+            let detail = (self.null_labels > 0).then(|| format!("{} NULL labels", self.null_labels));
+            let data_type = Some(label.data_type().to_string());
+            rows.push(("label", label.name().clone(), data_type, Some(label.is_nullable()), None, detail));
+        }
+        for c in &self.vector_columns {
+            let data_type = Some(c.item_type.to_string());
+            rows.push(("vector", c.name.clone(), data_type, Some(c.nullable), Some(c.dimension as i32), None));
+        }
+        for index in &self.indices {
+            let columns = Some(index.columns.join(","));
+            rows.push(("index", index.name.clone(), Some(index.index_type.clone()), None, None, columns));
+        }
+        for problem in self.problems() {
+            rows.push(("problem", String::new(), None, None, None, Some(problem)));
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("kind", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("data_type", DataType::Utf8, true),
+            Field::new("nullable", DataType::Boolean, true),
+            Field::new("dimension", DataType::Int32, true),
+            Field::new("detail", DataType::Utf8, true),
+        ]));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.1.as_str()))),
+                Arc::new(StringArray::from_iter(rows.iter().map(|r| r.2.as_deref()))),
+                Arc::new(BooleanArray::from_iter(rows.iter().map(|r| r.3))),
+                Arc::new(Int32Array::from_iter(rows.iter().map(|r| r.4))),
+                Arc::new(StringArray::from_iter(rows.iter().map(|r| r.5.as_deref()))),
+            ],
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+
+    fn vector_type(dim: i32) -> DataType {
+        DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim)
+    }
+
+    #[test]
+    fn test_problems() {
+        let index_table = Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", vector_type(4), true),
+            Field::new("vector_pca", vector_type(2), true),
+        ]);
+        let caps = TableCapabilities::from_schema(&index_table);
+        assert!(caps.problems().is_empty());
+        assert_eq!(caps.vector().unwrap().dimension, 4);
+        assert_eq!(caps.vector_columns.len(), 2);
+
+        let foreign = Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("embedding", vector_type(8), true),
+        ]);
+        let caps = TableCapabilities::from_schema(&foreign);
+        assert_eq!(
+            caps.problems(),
+            vec!["no label column (an Int64 row key)", "no column named vector (FixedSizeList columns: embedding)"]
+        );
+
+        let keyed = Schema::new(vec![Field::new("label", DataType::Utf8, true)]);
+        let mut caps = TableCapabilities::from_schema(&keyed);
+        assert_eq!(caps.problems(), vec!["label column is Utf8, expected Int64", "no FixedSizeList vector column"]);
+        caps.label = Some(Field::new("label", DataType::Int64, true));
+        caps.null_labels = 3;
+        assert_eq!(caps.problems()[0], "label column has 3 NULL labels");
+    }
+
+    #[test]
+    fn test_record_batch() {
+        let schema = Schema::new(vec![
+            Field::new("label", DataType::Int64, true),
+            Field::new("embedding", vector_type(8), false),
+        ]);
+        let mut caps = TableCapabilities::from_schema(&schema);
+        caps.indices.push(IndexInfo {
+            name: "embedding_idx".to_string(),
+            index_type: "IVF_PQ".to_string(),
+            columns: vec!["embedding".to_string()],
+        });
+        let batch = caps.to_record_batch().unwrap();
+        let kinds = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(kinds.iter().flatten().collect::<Vec<_>>(), vec!["label", "vector", "index", "problem"]);
+        let nullable = batch.column(3).as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert!(nullable.value(0));
+        let dims = batch.column(4).as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!((dims.value(1), dims.is_null(2)), (8, true));
+        let detail = batch.column(5).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(detail.value(2), "embedding");
+    }
+
+    #[test]
+    fn test_index_status_batch() {
+        let status = IndexStatus {
+            info: IndexInfo {
+                name: "vector_next_idx".to_string(),
+                index_type: "IVF_FLAT".to_string(),
+                columns: vec!["vector_next".to_string()],
+            },
+            indexed_rows: 90,
+            unindexed_rows: 10,
+            metric: None,
+        };
+        let batch = IndexStatus::to_record_batch(&[status]).unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        let unindexed = batch.column(4).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(unindexed.value(0), 10);
+        assert!(batch.column(5).is_null(0));
+        assert_eq!(IndexStatus::to_record_batch(&[]).unwrap().num_rows(), 0);
+    }
+}
@@ -0,0 +1,355 @@
+//! Cached conversion of appended Arrow columns to the table schema.
+//!
+//! Columns arriving through the Arrow C interface usually match the table schema
+//! except for nested field names (DuckDB names the FixedSizeList child differently).
+//! A [`CastPlan`] decides once per incoming schema which incoming column fills each
+//! table column (see [`ColumnMatching`]) and whether it passes through, only needs its
+//! type relabeled (same layout, zero-copy), or needs a real cast. [`CastPlanCache`]
+//! keeps the plan of the last incoming schema so repeated appends of the same shape
+//! skip the resolution.
+
+use anyhow::{anyhow, Result};
+use arrow::compute::{can_cast_types, cast};
+use arrow_array::{make_array, new_null_array, Array, ArrayRef};
+use arrow_schema::{DataType, Fields, SchemaRef};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::constraints::Violation;
+
+/// How incoming columns are matched to the table's columns after the label.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnMatching {
+    /// Incoming column i fills table column i + 1, whatever its name.
+    #[default]
+    Positional,
+    /// By name; an unknown or missing column fails the batch.
+    Strict,
+    /// By name; missing columns are NULL and unknown ones are ignored.
+    Lenient,
+}
+
+impl ColumnMatching {
+    pub fn parse(mode: &str) -> Result<Self> {
+        match mode {
+            "positional" => Ok(ColumnMatching::Positional),
+            "strict" => Ok(ColumnMatching::Strict),
+            "lenient" => Ok(ColumnMatching::Lenient),
+            other => Err(anyhow!("invalid column matching mode '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for ColumnMatching {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnMatching::Positional => write!(f, "positional"),
+            ColumnMatching::Strict => write!(f, "strict"),
+            ColumnMatching::Lenient => write!(f, "lenient"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    Passthrough,
+    /// Same physical layout; only nested field names differ.
+    Relabel,
+    Cast,
+    /// No incoming column; filled with NULLs.
+    Nulls,
+}
+
+/// Conversion of incoming columns to the target's columns after the first (the
+/// label, which the caller supplies).
+pub struct CastPlan {
+    source: Fields,
+    matching: ColumnMatching,
+    target: SchemaRef,
+    /// Per target column after the label: the incoming column it is read from.
+    sources: Vec<Option<usize>>,
+    conversions: Vec<Conversion>,
+}
+
+impl CastPlan {
+    pub fn new(source: &Fields, target: &SchemaRef, matching: ColumnMatching) -> Result<Self> {
+        let targets = &target.fields()[1..];
+        let sources = match matching {
+            ColumnMatching::Positional => {
+                if source.len() != targets.len() {
+                    return Err(anyhow!(
+                        "expected {} columns, got {}",
+                        targets.len(),
+                        source.len()
+                    ));
+                }
+                (0..targets.len()).map(Some).collect()
+            }
+            ColumnMatching::Strict | ColumnMatching::Lenient => Self::match_names(source, target, matching)?,
+        };
+        let conversions = sources
+            .iter()
+            .zip(targets)
+            .map(|(index, to)| {
+                let Some(index) = index else {
+                    return Ok(Conversion::Nulls);
+                };
+                let (from, to) = (source[*index].data_type(), to.data_type());
+                if from == to {
+                    Ok(Conversion::Passthrough)
+                } else if from.equals_datatype(to) {
+                    Ok(Conversion::Relabel)
+                } else if can_cast_types(from, to) {
+                    Ok(Conversion::Cast)
+                } else {
+                    Err(anyhow!("cast column {} failed: cannot convert {} to {}", index, from, to))
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            source: source.clone(),
+            matching,
+            target: target.clone(),
+            sources,
+            conversions,
+        })
+    }
+
+    /// The incoming column of each target column after the label, matched by name.
+    fn match_names(source: &Fields, target: &SchemaRef, matching: ColumnMatching) -> Result<Vec<Option<usize>>> {
+        let label = target.field(0).name();
+        let mut seen = HashSet::new();
+        for field in source.iter() {
+            if !seen.insert(field.name()) {
+                return Err(anyhow!("duplicate column {}", field.name()));
+            }
+            if field.name() == label {
+                return Err(anyhow!("column {} is assigned by the index", label));
+            }
+            if matching == ColumnMatching::Strict && target.field_with_name(field.name()).is_err() {
+                return Err(anyhow!("unknown column {}", field.name()));
+            }
+        }
+        target.fields()[1..]
+            .iter()
+            .map(|field| match source.find(field.name()) {
+                Some((index, _)) => Ok(Some(index)),
+                None if matching == ColumnMatching::Strict => Err(anyhow!("missing column {}", field.name())),
+                None if !field.is_nullable() => Err(anyhow!("missing column {} is not nullable", field.name())),
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Convert `columns` (of the plan's source fields) to the target's columns after
+    /// the label.
+    pub fn apply(&self, columns: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        let num_rows = columns.first().map_or(0, |c| c.len());
+        self.sources
+            .iter()
+            .zip(&self.conversions)
+            .zip(&self.target.fields()[1..])
+            .map(|((index, conversion), field)| {
+                let Some(index) = *index else {
+                    return Ok(new_null_array(field.data_type(), num_rows));
+                };
+                let column = &columns[index];
+                match conversion {
+                    Conversion::Passthrough => Ok(column.clone()),
+                    Conversion::Relabel => {
+                        let data = column.to_data().into_builder().data_type(field.data_type().clone()).build()?;
+                        Ok(make_array(data))
+                    }
+                    Conversion::Cast => cast(column, field.data_type())
+                        .map_err(|e| anyhow!("cast column {} failed: {}", index, e)),
+                    Conversion::Nulls => Ok(new_null_array(field.data_type(), num_rows)),
+                }
+            })
+            .collect()
+    }
+
+    /// Rows a cast turned to NULL although the incoming value was set: the value does
+    /// not convert (e.g. a list of the wrong length for a FixedSizeList column).
+    /// `converted` is the output of [`CastPlan::apply`] for `columns`.
+    pub fn failed_rows(&self, columns: &[ArrayRef], converted: &[ArrayRef]) -> Vec<Violation> {
+        let mut failed = Vec::new();
+        for (i, conversion) in self.conversions.iter().enumerate() {
+            let Some(source) = self.sources[i].map(|index| &columns[index]) else {
+                continue;
+            };
+            if *conversion != Conversion::Cast || converted[i].null_count() == source.null_count() {
+                continue;
+            }
+            let field = &self.target.fields()[i + 1];
+            let reason = match field.data_type() {
+                DataType::FixedSizeList(_, size) => format!("column {}: expected {} values", field.name(), size),
+                other => format!("column {}: cannot convert value to {}", field.name(), other),
+            };
+            for row in 0..source.len() {
+                if source.is_valid(row) && converted[i].is_null(row) {
+                    failed.push(Violation {
+                        row,
+                        reason: reason.clone(),
+                    });
+                }
+            }
+        }
+        failed.sort_by_key(|v| v.row);
+        failed
+    }
+
+    /// Number of columns that need a real cast.
+    pub fn casts(&self) -> usize {
+        self.conversions.iter().filter(|c| **c == Conversion::Cast).count()
+    }
+}
+
+/// The plan for the most recent incoming schema.
+pub struct CastPlanCache {
+    target: SchemaRef,
+    last: Mutex<Option<Arc<CastPlan>>>,
+}
+
+impl CastPlanCache {
+    pub fn new(target: SchemaRef) -> Self {
+        Self {
+            target,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// The plan for `source`, reused when it equals the previous incoming schema and
+    /// `matching` is unchanged.
+    pub fn plan(&self, source: &Fields, matching: ColumnMatching) -> Result<Arc<CastPlan>> {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(plan) = last.as_ref().filter(|p| &p.source == source && p.matching == matching) {
+            return Ok(plan.clone());
+        }
+        let plan = Arc::new(CastPlan::new(source, &self.target, matching)?);
+        *last = Some(plan.clone());
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::types::Float32Type;
+    use arrow_array::{FixedSizeListArray, Float32Array, Int32Array, ListArray, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+
+    #[test]
+    fn test_cast_plan_relabels_and_caches() {
+        let item = |name: &str| Arc::new(Field::new(name, DataType::Float32, true));
+        let target = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", DataType::FixedSizeList(item("item"), 2), true),
+            Field::new("n", DataType::Int64, true),
+        ]));
+        let vectors = FixedSizeListArray::new(
+            item("element"),
+            2,
+            Arc::new(Float32Array::from(vec![1.0, 2.0, 3.0, 4.0])),
+            None,
+        );
+        let source = Fields::from(vec![
+            Field::new("vector", vectors.data_type().clone(), true),
+            Field::new("n", DataType::Int32, true),
+        ]);
+
+        let cache = CastPlanCache::new(target.clone());
+        let plan = cache.plan(&source, ColumnMatching::Positional).unwrap();
+        assert_eq!(plan.conversions, vec![Conversion::Relabel, Conversion::Cast]);
+        assert_eq!(plan.casts(), 1);
+        assert!(Arc::ptr_eq(&plan, &cache.plan(&source, ColumnMatching::Positional).unwrap()));
+
+        let vectors: ArrayRef = Arc::new(vectors);
+        let out = plan.apply(&[vectors.clone(), Arc::new(Int32Array::from(vec![5, 6]))]).unwrap();
+        assert_eq!(out[0].data_type(), target.field(1).data_type());
+        let values = |a: &ArrayRef| a.to_data().child_data()[0].buffers()[0].as_ptr();
+        assert_eq!(values(&out[0]), values(&vectors));
+        assert_eq!(out[1].data_type(), &DataType::Int64);
+
+        assert!(cache
+            .plan(&Fields::from(vec![Field::new("vector", DataType::Utf8, true)]), ColumnMatching::Positional)
+            .is_err());
+    }
+
+    #[test]
+    fn test_failed_rows() {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let target = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", DataType::FixedSizeList(item, 2), true),
+            Field::new("n", DataType::Int32, true),
+        ]));
+        let vectors: ArrayRef = Arc::new(ListArray::from_iter_primitive::<Float32Type, _, _>(vec![
+            Some(vec![Some(1.0), Some(2.0)]),
+            Some(vec![Some(3.0)]),
+            None,
+        ]));
+        let n: ArrayRef = Arc::new(StringArray::from(vec![Some("1"), Some("2"), Some("x")]));
+        let source = Fields::from(vec![
+            Field::new("vector", vectors.data_type().clone(), true),
+            Field::new("n", DataType::Utf8, true),
+        ]);
+
+        let plan = CastPlan::new(&source, &target, ColumnMatching::Positional).unwrap();
+        let columns = [vectors, n];
+        let out = plan.apply(&columns).unwrap();
+        let failed = plan.failed_rows(&columns, &out);
+        assert_eq!(failed.len(), 2);
+        assert_eq!((failed[0].row, failed[0].reason.as_str()), (1, "column vector: expected 2 values"));
+        assert_eq!((failed[1].row, failed[1].reason.as_str()), (2, "column n: cannot convert value to Int32"));
+    }
+
+    #[test]
+    fn test_name_matching() {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let target = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("n", DataType::Int64, true),
+            Field::new("tag", DataType::Utf8, true),
+        ]));
+        let vectors: ArrayRef = Arc::new(FixedSizeListArray::new(
+            item,
+            2,
+            Arc::new(Float32Array::from(vec![1.0, 2.0, 3.0, 4.0])),
+            None,
+        ));
+        let n: ArrayRef = Arc::new(Int32Array::from(vec![5, 6]));
+        let extra: ArrayRef = Arc::new(StringArray::from(vec!["x", "y"]));
+        // Reordered, with `tag` missing and an unknown `extra`
+        let source = Fields::from(vec![
+            Field::new("n", DataType::Int32, true),
+            Field::new("extra", DataType::Utf8, true),
+            Field::new("vector", vectors.data_type().clone(), true),
+        ]);
+        let columns = [n, extra, vectors.clone()];
+
+        let lenient = CastPlan::new(&source, &target, ColumnMatching::Lenient).unwrap();
+        let out = lenient.apply(&columns).unwrap();
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].to_data(), vectors.to_data());
+        assert_eq!(out[1].data_type(), &DataType::Int64);
+        assert_eq!(out[2].null_count(), 2);
+
+        let err = CastPlan::new(&source, &target, ColumnMatching::Strict).err().unwrap();
+        assert_eq!(err.to_string(), "unknown column extra");
+        let known = Fields::from(vec![source[0].clone(), source[2].clone()]);
+        let err = CastPlan::new(&known, &target, ColumnMatching::Strict).err().unwrap();
+        assert_eq!(err.to_string(), "missing column tag");
+        // Positional matching pairs n with vector
+        assert!(CastPlan::new(&source, &target, ColumnMatching::Positional).is_err());
+
+        let cache = CastPlanCache::new(target);
+        let plan = cache.plan(&source, ColumnMatching::Lenient).unwrap();
+        // The same shape under another mode is planned again
+        assert!(cache.plan(&source, ColumnMatching::Strict).is_err());
+        assert!(Arc::ptr_eq(&plan, &cache.plan(&source, ColumnMatching::Lenient).unwrap()));
+        assert_eq!(ColumnMatching::parse("lenient").unwrap(), ColumnMatching::Lenient);
+        assert!(ColumnMatching::parse("by_name").is_err());
+    }
+}
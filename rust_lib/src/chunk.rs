@@ -0,0 +1,278 @@
+//! Text chunking for long-text ingestion.
+//!
+//! A chunking stage is stored per table in the metadata in its text form, e.g.
+//! `column=body, parent=doc_id, unit=tokens, size=256, overlap=32, embedder=minilm`.
+//! Documents ingested through `LanceIndex::add_chunked` are split into windows of
+//! `size` units of `column`, each overlapping the previous by `overlap` units. Every
+//! window becomes its own row, embedded by the embedder registered under
+//! `embedder`, and the rows of one document share the label of its first chunk in
+//! the `parent` column.
+//!
+//! Tokens are whitespace-separated words, an approximation of model tokens that
+//! needs no tokenizer. Chunks are slices of the original text, so whitespace
+//! inside a chunk is preserved.
+
+use anyhow::{anyhow, Result};
+use arrow::array::AsArray;
+use arrow::compute::take_record_batch;
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Schema};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Embeds texts: receives the texts and the table dimension, returns
+/// `texts.len() * dimension` floats, one vector per text.
+pub type Embedder = Arc<dyn Fn(&[&str], usize) -> Result<Vec<f32>> + Send + Sync>;
+
+static EMBEDDERS: RwLock<Option<HashMap<String, Embedder>>> = RwLock::new(None);
+
+/// Register (or replace) a process-wide embedder.
+pub fn register_embedder(name: &str, embedder: Embedder) -> Result<()> {
+    let mut registry = EMBEDDERS
+        .write()
+        .map_err(|_| anyhow!("embedder registry lock poisoned"))?;
+    registry
+        .get_or_insert_with(HashMap::new)
+        .insert(name.to_string(), embedder);
+    Ok(())
+}
+
+pub fn embedder(name: &str) -> Result<Embedder> {
+    let registry = EMBEDDERS
+        .read()
+        .map_err(|_| anyhow!("embedder registry lock poisoned"))?;
+    registry
+        .as_ref()
+        .and_then(|r| r.get(name))
+        .cloned()
+        .ok_or_else(|| anyhow!("embedder '{}' is not registered", name))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkUnit {
+    Chars,
+    Tokens,
+}
+
+impl fmt::Display for ChunkUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkUnit::Chars => write!(f, "chars"),
+            ChunkUnit::Tokens => write!(f, "tokens"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunking {
+    /// Text column that is split.
+    pub column: String,
+    /// Int64 column receiving the label of each document's first chunk.
+    pub parent: String,
+    pub unit: ChunkUnit,
+    pub size: usize,
+    pub overlap: usize,
+    /// Name of the registered [`Embedder`] that embeds the chunks.
+    pub embedder: String,
+}
+
+impl Chunking {
+    /// Parse the text form. `column`, `parent`, `size` and `embedder` are required;
+    /// `unit` defaults to `chars` and `overlap` to 0.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (mut column, mut parent, mut size, mut embedder) = (None, None, None, None);
+        let mut unit = ChunkUnit::Chars;
+        let mut overlap = 0;
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("chunking setting '{}' must be key=value", part))?;
+            let (key, value) = (key.trim(), value.trim());
+            let count = || -> Result<usize> {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("{} must be a non-negative integer, got '{}'", key, value))
+            };
+            match key {
+                "column" => column = Some(value.to_string()),
+                "parent" => parent = Some(value.to_string()),
+                "size" => size = Some(count()?),
+                "overlap" => overlap = count()?,
+                "embedder" => embedder = Some(value.to_string()),
+                "unit" => {
+                    unit = match value {
+                        "chars" => ChunkUnit::Chars,
+                        "tokens" => ChunkUnit::Tokens,
+                        _ => return Err(anyhow!("unit must be 'chars' or 'tokens', got '{}'", value)),
+                    }
+                }
+                _ => return Err(anyhow!("unknown chunking setting '{}'", key)),
+            }
+        }
+        let required = |value: Option<String>, key: &str| {
+            value
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| anyhow!("chunking requires {}=...", key))
+        };
+        let chunking = Chunking {
+            column: required(column, "column")?,
+            parent: required(parent, "parent")?,
+            unit,
+            size: size.ok_or_else(|| anyhow!("chunking requires size=..."))?,
+            overlap,
+            embedder: required(embedder, "embedder")?,
+        };
+        if chunking.size == 0 {
+            return Err(anyhow!("chunk size must be positive"));
+        }
+        if chunking.overlap >= chunking.size {
+            return Err(anyhow!(
+                "chunk overlap {} must be smaller than the size {}",
+                chunking.overlap,
+                chunking.size
+            ));
+        }
+        Ok(chunking)
+    }
+
+    /// Check the columns against a table schema.
+    pub fn validate(&self, schema: &Schema) -> Result<()> {
+        let column = schema
+            .field_with_name(&self.column)
+            .map_err(|_| anyhow!("chunked column '{}' not found", self.column))?;
+        if column.data_type() != &DataType::Utf8 {
+            return Err(anyhow!("chunked column '{}' must be VARCHAR, got {}", self.column, column.data_type()));
+        }
+        let parent = schema
+            .field_with_name(&self.parent)
+            .map_err(|_| anyhow!("parent column '{}' not found", self.parent))?;
+        if parent.data_type() != &DataType::Int64 {
+            return Err(anyhow!("parent column '{}' must be BIGINT, got {}", self.parent, parent.data_type()));
+        }
+        Ok(())
+    }
+
+    /// Chunks of `text`. Text that fits in one window (including empty text) is a
+    /// single chunk.
+    pub fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        // Byte spans of the units
+        let spans: Vec<(usize, usize)> = match self.unit {
+            ChunkUnit::Chars => text.char_indices().map(|(i, c)| (i, i + c.len_utf8())).collect(),
+            ChunkUnit::Tokens => text
+                .split_whitespace()
+                .map(|word| {
+                    let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                    (start, start + word.len())
+                })
+                .collect(),
+        };
+        if spans.len() <= self.size {
+            return vec![text];
+        }
+        let step = self.size - self.overlap;
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = (start + self.size).min(spans.len());
+            chunks.push(&text[spans[start].0..spans[end - 1].1]);
+            if end == spans.len() {
+                return chunks;
+            }
+            start += step;
+        }
+    }
+
+    /// One row per chunk of `batch`: rows are repeated per chunk with `column`
+    /// replaced by the chunk text. Also returns the source row of each chunk.
+    pub fn expand(&self, batch: &RecordBatch) -> Result<(RecordBatch, Vec<u32>)> {
+        let (index, _) = batch
+            .schema()
+            .column_with_name(&self.column)
+            .ok_or_else(|| anyhow!("chunked column '{}' not found", self.column))?;
+        let texts = batch
+            .column(index)
+            .as_string_opt::<i32>()
+            .ok_or_else(|| anyhow!("chunked column '{}' must be VARCHAR", self.column))?;
+
+        let mut source_rows = Vec::new();
+        let mut chunks: Vec<Option<&str>> = Vec::new();
+        for row in 0..batch.num_rows() {
+            if texts.is_null(row) {
+                source_rows.push(row as u32);
+                chunks.push(None);
+                continue;
+            }
+            for chunk in self.split(texts.value(row)) {
+                source_rows.push(row as u32);
+                chunks.push(Some(chunk));
+            }
+        }
+
+        let expanded = take_record_batch(batch, &UInt32Array::from(source_rows.clone()))?;
+        let mut columns: Vec<ArrayRef> = expanded.columns().to_vec();
+        columns[index] = Arc::new(StringArray::from(chunks));
+        Ok((RecordBatch::try_new(expanded.schema(), columns)?, source_rows))
+    }
+}
+
+impl fmt::Display for Chunking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "column={}, parent={}, unit={}, size={}, overlap={}, embedder={}",
+            self.column, self.parent, self.unit, self.size, self.overlap, self.embedder
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_schema::Field;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let chunking = Chunking::parse("column=body, parent=doc, unit=tokens, size=4, overlap=1, embedder=e").unwrap();
+        assert_eq!(chunking.unit, ChunkUnit::Tokens);
+        assert_eq!(Chunking::parse(&chunking.to_string()).unwrap(), chunking);
+
+        assert!(Chunking::parse("column=body, parent=doc, size=4").is_err());
+        assert!(Chunking::parse("column=body, parent=doc, size=4, overlap=4, embedder=e").is_err());
+        assert!(Chunking::parse("column=body, parent=doc, size=4, unit=bytes, embedder=e").is_err());
+    }
+
+    #[test]
+    fn test_split_windows() {
+        let tokens = Chunking::parse("column=t, parent=p, unit=tokens, size=3, overlap=1, embedder=e").unwrap();
+        assert_eq!(tokens.split("a b  c d e"), vec!["a b  c", "c d e"]);
+        assert_eq!(tokens.split("a b"), vec!["a b"]);
+        assert_eq!(tokens.split(""), vec![""]);
+
+        let chars = Chunking::parse("column=t, parent=p, size=4, overlap=2, embedder=e").unwrap();
+        assert_eq!(chars.split("héllo!"), vec!["héll", "llo!"]);
+    }
+
+    #[test]
+    fn test_expand_repeats_rows() {
+        let chunking = Chunking::parse("column=t, parent=p, unit=tokens, size=2, embedder=e").unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("t", DataType::Utf8, true),
+            Field::new("id", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("a b c"), None])),
+                Arc::new(arrow_array::Int64Array::from(vec![7, 8])),
+            ],
+        )
+        .unwrap();
+        let (expanded, rows) = chunking.expand(&batch).unwrap();
+        assert_eq!(rows, vec![0, 0, 1]);
+        let texts = expanded.column(0).as_string::<i32>();
+        assert_eq!((texts.value(0), texts.value(1), texts.is_null(2)), ("a b", "c", true));
+        let ids = expanded.column(1).as_primitive::<arrow::datatypes::Int64Type>();
+        assert_eq!(ids.values().to_vec(), vec![7, 7, 8]);
+    }
+}
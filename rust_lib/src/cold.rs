@@ -0,0 +1,355 @@
+//! Reduced-precision storage of cold rows.
+//!
+//! A cold storage policy is stored in the table metadata in its text form, e.g.
+//! `column=created_at, after_days=30, precision=f16`. Compaction moves the rows whose
+//! time column is older than `after_days` into a sidecar Lance table
+//! (`<table>__cold`) that keeps every column but stores `vector` narrower, and
+//! deletes them from the table, whose next compaction drops their Float32 vectors
+//! from disk. Recent rows keep full precision. The column may be a timestamp or an
+//! Int64 of milliseconds since the Unix epoch.
+//!
+//! - `f16`: two bytes per element. Rows with an element beyond the f16 range stay
+//!   in the table.
+//! - `int8`: one byte per element, scaled per row by its largest magnitude, which is
+//!   kept in a `vector_scale` column.
+//!
+//! Cold rows are not indexed: `search` ranks the ones matching its filter exactly,
+//! widening them as it scans, and merges them with the index hits. `get_vector` and
+//! `count` include them and deletes by label remove them; other reads see only the
+//! table.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, ArrayRef, FixedSizeListArray, Float32Array, Int8Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::ttl;
+use crate::vector_storage::{self, VectorStorage};
+
+/// Per-row scale of int8 cold vectors.
+pub const VECTOR_SCALE: &str = "vector_scale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    F16,
+    Int8,
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Precision::F16 => write!(f, "f16"),
+            Precision::Int8 => write!(f, "int8"),
+        }
+    }
+}
+
+impl Precision {
+    /// The precision cold vectors are stored at in sidecar `schema`.
+    pub fn of_schema(schema: &Schema) -> Option<Self> {
+        match schema.field_with_name("vector").map(Field::data_type) {
+            Ok(DataType::FixedSizeList(item, _)) => match item.data_type() {
+                DataType::Float16 => Some(Precision::F16),
+                DataType::Int8 => Some(Precision::Int8),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether `vector` can be stored at this precision.
+    pub fn fits(self, vector: &[f32]) -> bool {
+        match self {
+            Precision::F16 => vector.iter().all(|v| v.abs() <= 65504.0),
+            Precision::Int8 => vector.iter().all(|v| v.is_finite()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColdStorage {
+    /// Timestamp or Int64 epoch-milliseconds column rows are aged by.
+    pub column: String,
+    pub after_days: u64,
+    pub precision: Precision,
+}
+
+impl ColdStorage {
+    /// Parse the text form. `column` and `after_days` are required; `precision`
+    /// defaults to `f16`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (mut column, mut after_days) = (None, None);
+        let mut precision = Precision::F16;
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("cold storage setting '{}' must be key=value", part))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "column" => column = Some(value.to_string()),
+                "after_days" => {
+                    after_days = Some(
+                        value
+                            .parse::<u64>()
+                            .map_err(|_| anyhow!("after_days must be a non-negative integer, got '{}'", value))?,
+                    )
+                }
+                "precision" => {
+                    precision = match value {
+                        "f16" => Precision::F16,
+                        "int8" => Precision::Int8,
+                        _ => return Err(anyhow!("precision must be 'f16' or 'int8', got '{}'", value)),
+                    }
+                }
+                _ => return Err(anyhow!("unknown cold storage setting '{}'", key)),
+            }
+        }
+        Ok(Self {
+            column: column.ok_or_else(|| anyhow!("cold storage needs a column"))?,
+            after_days: after_days.ok_or_else(|| anyhow!("cold storage needs after_days"))?,
+            precision,
+        })
+    }
+
+    /// Check that the time column exists and has a usable type.
+    pub fn validate(&self, schema: &Schema) -> Result<()> {
+        let field = schema
+            .field_with_name(&self.column)
+            .map_err(|_| anyhow!("cold storage column '{}' not found", self.column))?;
+        match field.data_type() {
+            DataType::Timestamp(_, _) | DataType::Int64 => Ok(()),
+            other => Err(anyhow!(
+                "cold storage column {} must be a timestamp or Int64 epoch milliseconds, got {}",
+                self.column,
+                other
+            )),
+        }
+    }
+
+    /// Epoch milliseconds before which rows are cold at `now_ms`.
+    pub fn cutoff_ms(&self, now_ms: i64) -> i64 {
+        now_ms.saturating_sub((self.after_days.min(i64::MAX as u64 / 86_400_000) * 86_400_000) as i64)
+    }
+
+    /// Lance SQL predicate for rows whose time column is before `cutoff_ms`.
+    pub fn predicate(&self, column_type: &DataType, cutoff_ms: i64) -> String {
+        ttl::compare_ms(&self.column, column_type, "<", cutoff_ms)
+    }
+}
+
+impl fmt::Display for ColdStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "column={}, after_days={}, precision={}",
+            self.column, self.after_days, self.precision
+        )
+    }
+}
+
+pub fn sidecar_table_name(table_name: &str) -> String {
+    format!("{}__cold", table_name)
+}
+
+/// The sidecar schema for cold rows of a table with `schema`: `vector` narrowed to
+/// `precision`, plus [`VECTOR_SCALE`] for int8.
+pub fn sidecar_schema(schema: &Schema, precision: Precision) -> Result<SchemaRef> {
+    let mut fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| match field.data_type() {
+            DataType::FixedSizeList(_, size) if field.name() == "vector" => {
+                let element = match precision {
+                    Precision::F16 => DataType::Float16,
+                    Precision::Int8 => DataType::Int8,
+                };
+                let item = Arc::new(Field::new("item", element, true));
+                field.as_ref().clone().with_data_type(DataType::FixedSizeList(item, *size))
+            }
+            _ => field.as_ref().clone(),
+        })
+        .collect();
+    if !fields.iter().any(|f| f.name() == "vector") {
+        return Err(anyhow!("cold storage needs a vector column"));
+    }
+    if precision == Precision::Int8 {
+        fields.push(Field::new(VECTOR_SCALE, DataType::Float32, false));
+    }
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+/// `batch`, read from the table, converted to sidecar schema `cold`. Fails if the
+/// table has a column the sidecar lacks, so moving never drops data.
+pub fn narrow(batch: &RecordBatch, cold: &SchemaRef) -> Result<RecordBatch> {
+    let precision = Precision::of_schema(cold).ok_or_else(|| anyhow!("cold table has no narrow vector column"))?;
+    if let Some(missing) = batch.schema().fields().iter().find(|f| cold.field_with_name(f.name()).is_err()) {
+        return Err(anyhow!("column {} is missing from the cold table", missing.name()));
+    }
+    let vectors = batch
+        .column_by_name("vector")
+        .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+        .ok_or_else(|| anyhow!("missing vector column"))?
+        .clone();
+    let (narrowed, scales) = match precision {
+        Precision::F16 => (vector_storage::narrow(vectors, VectorStorage::Float16)?, None),
+        Precision::Int8 => {
+            let (narrowed, scales) = quantize(&vectors)?;
+            (narrowed, Some(scales))
+        }
+    };
+    let columns = cold
+        .fields()
+        .iter()
+        .map(|field| match field.name().as_str() {
+            "vector" => Ok(Arc::new(narrowed.clone()) as ArrayRef),
+            VECTOR_SCALE if precision == Precision::Int8 => {
+                scales.clone().ok_or_else(|| anyhow!("missing int8 scales"))
+            }
+            name => batch
+                .column_by_name(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("column {} is missing from the table", name)),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(cold.clone(), columns)?)
+}
+
+/// Int8 codes of `vectors`, each row scaled by its largest magnitude, and the
+/// per-row scales.
+fn quantize(vectors: &FixedSizeListArray) -> Result<(FixedSizeListArray, ArrayRef)> {
+    let values = vectors
+        .values()
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .ok_or_else(|| anyhow!("vector values not Float32"))?;
+    let size = vectors.value_length() as usize;
+    let mut codes = Vec::with_capacity(vectors.len() * size);
+    let mut scales = Vec::with_capacity(vectors.len());
+    for row in 0..vectors.len() {
+        let start = vectors.value_offset(row) as usize;
+        let vector = &values.values()[start..start + size];
+        let scale = vector.iter().fold(0.0f32, |m, v| m.max(v.abs())) / 127.0;
+        codes.extend(vector.iter().map(|v| if scale > 0.0 { (v / scale).round() as i8 } else { 0 }));
+        scales.push(scale);
+    }
+    let item = Arc::new(Field::new("item", DataType::Int8, true));
+    let codes = FixedSizeListArray::try_new(
+        item,
+        size as i32,
+        Arc::new(Int8Array::from(codes)),
+        vectors.nulls().cloned(),
+    )?;
+    Ok((codes, Arc::new(Float32Array::from(scales))))
+}
+
+/// The Float32 vectors of `batch`, read from the sidecar.
+pub fn widen(batch: &RecordBatch) -> Result<FixedSizeListArray> {
+    let vectors = batch
+        .column_by_name("vector")
+        .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+        .ok_or_else(|| anyhow!("missing vector column"))?;
+    let Some(codes) = vectors.values().as_any().downcast_ref::<Int8Array>() else {
+        return Ok(vector_storage::widen(vectors)?.into_owned());
+    };
+    let scales = batch
+        .column_by_name(VECTOR_SCALE)
+        .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
+        .ok_or_else(|| anyhow!("missing {} column", VECTOR_SCALE))?;
+    let size = vectors.value_length() as usize;
+    let mut values = Vec::with_capacity(vectors.len() * size);
+    for row in 0..vectors.len() {
+        let start = vectors.value_offset(row) as usize;
+        let scale = scales.value(row);
+        values.extend(codes.values()[start..start + size].iter().map(|c| *c as f32 * scale));
+    }
+    let item = Arc::new(Field::new("item", DataType::Float32, true));
+    Ok(FixedSizeListArray::try_new(
+        item,
+        size as i32,
+        Arc::new(Float32Array::from(values)),
+        vectors.nulls().cloned(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+    use arrow_schema::TimeUnit;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let policy = ColdStorage::parse("column=created_at, after_days=30, precision=int8").unwrap();
+        assert_eq!(policy.precision, Precision::Int8);
+        assert_eq!(ColdStorage::parse(&policy.to_string()).unwrap(), policy);
+        assert_eq!(ColdStorage::parse("column=ts, after_days=7").unwrap().precision, Precision::F16);
+
+        assert!(ColdStorage::parse("after_days=7").is_err());
+        assert!(ColdStorage::parse("column=ts").is_err());
+        assert!(ColdStorage::parse("column=ts, after_days=7, precision=f8").is_err());
+
+        let schema = Schema::new(vec![
+            Field::new("ts", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        assert!(policy.validate(&schema).is_err());
+        assert!(ColdStorage::parse("column=name, after_days=1").unwrap().validate(&schema).is_err());
+        assert!(ColdStorage::parse("column=ts, after_days=1").unwrap().validate(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_predicate() {
+        let policy = ColdStorage::parse("column=ts, after_days=1").unwrap();
+        assert_eq!(policy.cutoff_ms(86_400_005), 5);
+        assert_eq!(policy.predicate(&DataType::Int64, 5), "ts < 5");
+        assert_eq!(
+            policy.predicate(&DataType::Timestamp(TimeUnit::Millisecond, None), 1000),
+            "ts < TIMESTAMP '1970-01-01 00:00:01.000'"
+        );
+    }
+
+    fn rows(values: Vec<f32>) -> RecordBatch {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), false),
+        ]));
+        let labels = Int64Array::from_iter_values(0..values.len() as i64 / 2);
+        let vectors = FixedSizeListArray::new(item, 2, Arc::new(Float32Array::from(values)), None);
+        RecordBatch::try_new(schema, vec![Arc::new(labels), Arc::new(vectors)]).unwrap()
+    }
+
+    fn widened(batch: &RecordBatch) -> Vec<f32> {
+        let vectors = widen(batch).unwrap();
+        vectors.values().as_any().downcast_ref::<Float32Array>().unwrap().values().to_vec()
+    }
+
+    #[test]
+    fn test_narrow_and_widen() {
+        let batch = rows(vec![1.0, 0.3, -0.5, 0.0]);
+
+        let cold = sidecar_schema(&batch.schema(), Precision::F16).unwrap();
+        assert_eq!(Precision::of_schema(&cold), Some(Precision::F16));
+        let half = narrow(&batch, &cold).unwrap();
+        assert_eq!(half.column(0).as_ref(), batch.column(0).as_ref());
+        let values = widened(&half);
+        assert_eq!(values[0], 1.0);
+        assert!((values[1] - 0.3).abs() < 1.0e-3);
+
+        let cold = sidecar_schema(&batch.schema(), Precision::Int8).unwrap();
+        assert_eq!(cold.field(2).name(), VECTOR_SCALE);
+        let codes = narrow(&batch, &cold).unwrap();
+        let values = widened(&codes);
+        assert_eq!(values[0], 1.0);
+        assert!((values[1] - 38.0 / 127.0).abs() < 1.0e-6);
+        assert_eq!(&values[2..], &[-0.5, 0.0]);
+
+        assert!(Precision::F16.fits(&[65504.0, -1.0]));
+        assert!(!Precision::F16.fits(&[1.0e5, 0.0]));
+
+        // A column the sidecar lacks is never dropped
+        let narrow_schema = Arc::new(Schema::new(vec![cold.field(1).clone(), cold.field(2).clone()]));
+        assert!(narrow(&batch, &narrow_schema).unwrap_err().to_string().contains("label"));
+    }
+}
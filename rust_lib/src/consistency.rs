@@ -0,0 +1,86 @@
+//! Per-query read consistency.
+//!
+//! A handle normally catches up with commits made through other handles in this
+//! process before each read, so a session reads its own writes. A query can ask for
+//! more or less than that:
+//!
+//! - `latest`: check out the latest table version first, picking up commits from
+//!   other processes too; costs a manifest read per query.
+//! - `session`: the default; read snapshots stay on their pinned version.
+//! - `eventual`: read whatever version the handle already has, skipping the catch-up.
+//!   Cheapest, but may miss writes made since the handle last synced.
+
+use anyhow::{anyhow, Result};
+use std::cell::Cell;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Consistency {
+    Latest,
+    #[default]
+    Session,
+    Eventual,
+}
+
+impl FromStr for Consistency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "latest" | "strong" => Ok(Consistency::Latest),
+            "" | "session" => Ok(Consistency::Session),
+            "eventual" => Ok(Consistency::Eventual),
+            other => Err(anyhow!("unknown consistency '{}' (expected latest, session or eventual)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Consistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Consistency::Latest => "latest",
+            Consistency::Session => "session",
+            Consistency::Eventual => "eventual",
+        })
+    }
+}
+
+thread_local! {
+    static EVENTUAL: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `read` with the catch-up before table reads skipped on this thread.
+pub fn eventual<T>(read: impl FnOnce() -> T) -> T {
+    let outer = EVENTUAL.with(|e| e.replace(true));
+    let result = read();
+    EVENTUAL.with(|e| e.set(outer));
+    result
+}
+
+/// Whether the current thread is inside [`eventual`].
+pub fn is_eventual() -> bool {
+    EVENTUAL.with(Cell::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("LATEST".parse::<Consistency>().unwrap(), Consistency::Latest);
+        assert_eq!("".parse::<Consistency>().unwrap(), Consistency::Session);
+        assert_eq!(" eventual ".parse::<Consistency>().unwrap(), Consistency::Eventual);
+        assert!("linearizable".parse::<Consistency>().is_err());
+        assert_eq!(Consistency::Eventual.to_string(), "eventual");
+    }
+
+    #[test]
+    fn test_eventual_scope() {
+        assert!(!is_eventual());
+        let nested = eventual(|| eventual(is_eventual) && is_eventual());
+        assert!(nested);
+        assert!(!is_eventual());
+    }
+}
@@ -0,0 +1,338 @@
+//! Per-column constraints checked when rows are added.
+//!
+//! Constraints are stored in the table metadata in their text form, a
+//! `;`-separated list of rules plus an optional violation mode, e.g.
+//! `author not null; category in ('news', 'blog'); score between 0 and 1; on_violation=skip`:
+//!
+//! - `col not null`: the value must not be NULL.
+//! - `col in (a, b, ...)`: the value, as text, must be one of the listed values.
+//! - `col between lo and hi`, `col >= lo`, `col <= hi`: numeric range, bounds included.
+//!
+//! As with SQL `CHECK`, a NULL passes every rule but `not null`. `on_violation`
+//! decides what happens to a batch with violating rows: `error` (the default)
+//! rejects the whole batch, `skip` drops the violating rows and `dead_letter` drops
+//! them into the table's rejects sidecar (see [`crate::rejects`]).
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, Float64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Schema};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    NotNull,
+    InSet(Vec<String>),
+    Range { min: Option<f64>, max: Option<f64> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub column: String,
+    pub rule: Rule,
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.rule {
+            Rule::NotNull => write!(f, "{} not null", self.column),
+            Rule::InSet(values) => {
+                let quoted: Vec<String> = values.iter().map(|v| format!("'{}'", v)).collect();
+                write!(f, "{} in ({})", self.column, quoted.join(", "))
+            }
+            Rule::Range { min: Some(min), max: Some(max) } => {
+                write!(f, "{} between {} and {}", self.column, min, max)
+            }
+            Rule::Range { min: Some(min), max: None } => write!(f, "{} >= {}", self.column, min),
+            Rule::Range { min: None, max: Some(max) } => write!(f, "{} <= {}", self.column, max),
+            Rule::Range { min: None, max: None } => write!(f, "{} between -inf and inf", self.column),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnViolation {
+    #[default]
+    Error,
+    Skip,
+    DeadLetter,
+}
+
+impl OnViolation {
+    pub fn parse(mode: &str) -> Result<Self> {
+        match mode {
+            "error" => Ok(OnViolation::Error),
+            "skip" => Ok(OnViolation::Skip),
+            "dead_letter" => Ok(OnViolation::DeadLetter),
+            other => Err(anyhow!("invalid on_violation mode '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for OnViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnViolation::Error => write!(f, "error"),
+            OnViolation::Skip => write!(f, "skip"),
+            OnViolation::DeadLetter => write!(f, "dead_letter"),
+        }
+    }
+}
+
+/// A row that broke a constraint: its index in the checked batch and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub row: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraints {
+    pub rules: Vec<Constraint>,
+    pub on_violation: OnViolation,
+}
+
+impl Constraints {
+    /// Parse the text form. At least one rule is required.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        let mut on_violation = OnViolation::default();
+        for part in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            if let Some(mode) = part.strip_prefix("on_violation") {
+                let mode = mode
+                    .trim()
+                    .strip_prefix('=')
+                    .ok_or_else(|| anyhow!("on_violation must be on_violation=<mode>"))?;
+                on_violation = OnViolation::parse(mode.trim())?;
+                continue;
+            }
+            rules.push(parse_rule(part)?);
+        }
+        if rules.is_empty() {
+            return Err(anyhow!("constraints have no rules"));
+        }
+        Ok(Self { rules, on_violation })
+    }
+
+    /// Check that every constrained column exists in `schema` with a type its rule
+    /// can read.
+    pub fn validate(&self, schema: &Schema) -> Result<()> {
+        for constraint in &self.rules {
+            let field = schema
+                .field_with_name(&constraint.column)
+                .map_err(|_| anyhow!("constrained column '{}' not found", constraint.column))?;
+            if matches!(field.name().as_str(), "label" | "vector") {
+                return Err(anyhow!("column '{}' cannot be constrained", field.name()));
+            }
+            let readable = match constraint.rule {
+                Rule::NotNull => true,
+                Rule::InSet(_) => arrow::compute::can_cast_types(field.data_type(), &DataType::Utf8),
+                Rule::Range { .. } => field.data_type().is_numeric(),
+            };
+            if !readable {
+                return Err(anyhow!(
+                    "constraint '{}' does not apply to column of type {}",
+                    constraint,
+                    field.data_type()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The rows of `batch` that break a rule, in row order, each with the first rule
+    /// it breaks.
+    pub fn check(&self, batch: &RecordBatch) -> Result<Vec<Violation>> {
+        let mut reasons: Vec<Option<String>> = vec![None; batch.num_rows()];
+        for constraint in &self.rules {
+            let column = batch
+                .column_by_name(&constraint.column)
+                .ok_or_else(|| anyhow!("constrained column '{}' missing from batch", constraint.column))?;
+            let name = &constraint.column;
+            match &constraint.rule {
+                Rule::NotNull => {
+                    for (row, reason) in reasons.iter_mut().enumerate() {
+                        if reason.is_none() && column.is_null(row) {
+                            *reason = Some(format!("{} is NULL", name));
+                        }
+                    }
+                }
+                Rule::InSet(values) => {
+                    let text = arrow::compute::cast(column, &DataType::Utf8)?;
+                    let text = text
+                        .as_any()
+                        .downcast_ref::<StringArray>()
+                        .ok_or_else(|| anyhow!("column '{}' did not cast to text", name))?;
+                    for (row, reason) in reasons.iter_mut().enumerate() {
+                        if reason.is_none() && text.is_valid(row) && !values.iter().any(|v| v == text.value(row)) {
+                            *reason = Some(format!("{} '{}' violates '{}'", name, text.value(row), constraint));
+                        }
+                    }
+                }
+                Rule::Range { min, max } => {
+                    let numbers = arrow::compute::cast(column, &DataType::Float64)?;
+                    let numbers = numbers
+                        .as_any()
+                        .downcast_ref::<Float64Array>()
+                        .ok_or_else(|| anyhow!("column '{}' did not cast to a number", name))?;
+                    for (row, reason) in reasons.iter_mut().enumerate() {
+                        if reason.is_some() || numbers.is_null(row) {
+                            continue;
+                        }
+                        let value = numbers.value(row);
+                        // NaN is outside every range
+                        let in_range = min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max);
+                        if !in_range {
+                            *reason = Some(format!("{} {} violates '{}'", name, value, constraint));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(reasons
+            .into_iter()
+            .enumerate()
+            .filter_map(|(row, reason)| reason.map(|reason| Violation { row, reason }))
+            .collect())
+    }
+}
+
+/// Returned (wrapped in `anyhow::Error`) when a batch breaks a constraint under
+/// `on_violation=error`. FFI entry points report it with a distinct return code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolated {
+    /// The first violating row.
+    pub first: Violation,
+    /// Violating rows in the batch.
+    pub rows: usize,
+}
+
+impl fmt::Display for ConstraintViolated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint violated in row {} ({} rows in all): {}",
+            self.first.row, self.rows, self.first.reason
+        )
+    }
+}
+
+impl std::error::Error for ConstraintViolated {}
+
+impl fmt::Display for Constraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, constraint) in self.rules.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", constraint)?;
+        }
+        if self.on_violation != OnViolation::Error {
+            write!(f, "; on_violation={}", self.on_violation)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_rule(part: &str) -> Result<Constraint> {
+    let invalid = || anyhow!("invalid constraint '{}'", part);
+    let number = |text: &str| -> Result<f64> {
+        text.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| !n.is_nan())
+            .ok_or_else(|| anyhow!("constraint bound '{}' is not a number", text.trim()))
+    };
+    let constraint = |column: &str, rule: Rule| -> Result<Constraint> {
+        let column = column.trim();
+        if column.is_empty() || column.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        Ok(Constraint { column: column.to_string(), rule })
+    };
+
+    if let Some(column) = part.strip_suffix("not null") {
+        return constraint(column, Rule::NotNull);
+    }
+    if let Some((column, values)) = part.split_once(" in ") {
+        let values = values
+            .trim()
+            .strip_prefix('(')
+            .and_then(|v| v.strip_suffix(')'))
+            .ok_or_else(invalid)?;
+        let values: Vec<String> = values
+            .split(',')
+            .map(|v| {
+                let v = v.trim();
+                v.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')).unwrap_or(v).to_string()
+            })
+            .collect();
+        if values.iter().any(String::is_empty) {
+            return Err(invalid());
+        }
+        return constraint(column, Rule::InSet(values));
+    }
+    if let Some((column, bounds)) = part.split_once(" between ") {
+        let (min, max) = bounds.split_once(" and ").ok_or_else(invalid)?;
+        let (min, max) = (number(min)?, number(max)?);
+        if min > max {
+            return Err(anyhow!("constraint '{}' has an empty range", part));
+        }
+        return constraint(column, Rule::Range { min: Some(min), max: Some(max) });
+    }
+    if let Some((column, min)) = part.split_once(">=") {
+        return constraint(column, Rule::Range { min: Some(number(min)?), max: None });
+    }
+    if let Some((column, max)) = part.split_once("<=") {
+        return constraint(column, Rule::Range { min: None, max: Some(number(max)?) });
+    }
+    Err(invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Int64Array, StringArray};
+    use arrow_schema::Field;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let spec = "author not null; category in ('news', 'blog'); score between 0 and 1; rank >= 1; on_violation=skip";
+        let constraints = Constraints::parse(spec).unwrap();
+        assert_eq!(constraints.rules.len(), 4);
+        assert_eq!(constraints.on_violation, OnViolation::Skip);
+        assert_eq!(constraints.to_string(), spec);
+        assert_eq!(Constraints::parse(&constraints.to_string()).unwrap(), constraints);
+
+        assert!(Constraints::parse("on_violation=skip").is_err());
+        assert!(Constraints::parse("score between 2 and 1").is_err());
+        assert!(Constraints::parse("score between a and 1").is_err());
+        assert!(Constraints::parse("category in news").is_err());
+        assert!(Constraints::parse("score > 1").is_err());
+        assert!(Constraints::parse("author not null; on_violation=drop").is_err());
+    }
+
+    #[test]
+    fn test_check_reports_first_violation_per_row() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("category", DataType::Utf8, true),
+            Field::new("score", DataType::Int64, true),
+        ]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![
+            Arc::new(StringArray::from(vec![Some("news"), None, Some("spam"), Some("blog")])),
+            Arc::new(Int64Array::from(vec![Some(5), Some(50), Some(500), None])),
+        ])
+        .unwrap();
+
+        let constraints = Constraints::parse("category not null; category in (news, blog); score <= 100").unwrap();
+        constraints.validate(&schema).unwrap();
+        let violations = constraints.check(&batch).unwrap();
+        let rows: Vec<usize> = violations.iter().map(|v| v.row).collect();
+        assert_eq!(rows, vec![1, 2]);
+        assert_eq!(violations[0].reason, "category is NULL");
+        assert!(violations[1].reason.starts_with("category 'spam'"));
+
+        assert!(Constraints::parse("missing not null").unwrap().validate(&schema).is_err());
+        assert!(Constraints::parse("category >= 1").unwrap().validate(&schema).is_err());
+    }
+}
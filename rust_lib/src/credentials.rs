@@ -0,0 +1,150 @@
+//! Host-supplied credentials for tables on S3.
+//!
+//! Temporary credentials (e.g. from STS) expire while a handle stays open. Once a
+//! callback is [`register`]ed, tables on `s3://` opened or created afterwards take
+//! their credentials from it instead of the storage options, and ask again shortly
+//! before the previous ones expire, so rotating credentials reach long-lived handles
+//! without reopening them. A callback registered later is used by those tables from
+//! their next refresh.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use object_store::aws::{AwsCredential, AwsCredentialProvider};
+use object_store::CredentialProvider;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+use crate::access;
+
+/// Credentials are fetched again this long before they expire.
+pub const REFRESH_MARGIN_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub key_id: String,
+    pub secret_key: String,
+    pub token: Option<String>,
+    /// Epoch milliseconds; `None` never expires.
+    pub expires_at_ms: Option<i64>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("key_id", &self.key_id)
+            .field("expires_at_ms", &self.expires_at_ms)
+            .finish_non_exhaustive()
+    }
+}
+
+pub type CredentialFn = Arc<dyn Fn() -> Result<Credentials> + Send + Sync>;
+
+static CALLBACK: RwLock<Option<CredentialFn>> = RwLock::new(None);
+
+/// Use `callback` for tables on S3 opened afterwards; `None` goes back to the
+/// storage options for them.
+pub fn register(callback: Option<CredentialFn>) {
+    *CALLBACK.write().unwrap_or_else(|e| e.into_inner()) = callback;
+}
+
+/// Credential provider for the table at `uri`, when it is on S3 and a callback is
+/// registered.
+pub fn provider(uri: &str) -> Option<AwsCredentialProvider> {
+    let scheme = uri.split_once("://")?.0;
+    if !matches!(scheme, "s3" | "s3a" | "s3+ddb") {
+        return None;
+    }
+    CALLBACK.read().unwrap_or_else(|e| e.into_inner()).as_ref()?;
+    Some(Arc::new(CallbackProvider::default()))
+}
+
+/// Whether credentials expiring at `expires_at_ms` must be fetched again at `now_ms`.
+fn needs_refresh(expires_at_ms: Option<i64>, now_ms: i64) -> bool {
+    expires_at_ms.is_some_and(|expiry| expiry.saturating_sub(REFRESH_MARGIN_MS) <= now_ms)
+}
+
+#[derive(Debug, Default)]
+struct CallbackProvider {
+    /// The last credentials fetched, with their expiry.
+    cached: Mutex<Option<(Arc<AwsCredential>, Option<i64>)>>,
+}
+
+#[async_trait]
+impl CredentialProvider for CallbackProvider {
+    type Credential = AwsCredential;
+
+    async fn get_credential(&self) -> object_store::Result<Arc<AwsCredential>> {
+        // Held across the fetch so concurrent requests share one callback call
+        let mut cached = self.cached.lock().await;
+        if let Some((credential, expires_at_ms)) = cached.as_ref() {
+            if !needs_refresh(*expires_at_ms, access::now_ms()) {
+                return Ok(credential.clone());
+            }
+        }
+        let callback = CALLBACK
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .ok_or_else(|| anyhow!("credential provider was unregistered"));
+        let fetched = callback.and_then(|callback| callback()).map_err(|e| object_store::Error::Generic {
+            store: "S3",
+            source: format!("credential callback failed: {}", e).into(),
+        })?;
+        let credential = Arc::new(AwsCredential {
+            key_id: fetched.key_id,
+            secret_key: fetched.secret_key,
+            token: fetched.token,
+        });
+        *cached = Some((credential.clone(), fetched.expires_at_ms));
+        Ok(credential)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_needs_refresh() {
+        assert!(!needs_refresh(None, i64::MAX));
+        assert!(!needs_refresh(Some(REFRESH_MARGIN_MS + 1), 0));
+        assert!(needs_refresh(Some(REFRESH_MARGIN_MS), 0));
+        assert!(needs_refresh(Some(i64::MIN), 0));
+    }
+
+    #[test]
+    fn test_provider_refreshes_expiring_credentials() {
+        assert!(provider("s3://bucket/db").is_none());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        register(Some(Arc::new(move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Credentials {
+                key_id: format!("key{}", n),
+                secret_key: "secret".to_string(),
+                token: Some("token".to_string()),
+                // The first credentials are about to expire, the next ones last
+                expires_at_ms: Some(if n == 0 { access::now_ms() } else { access::now_ms() + 3_600_000 }),
+            })
+        })));
+        assert!(provider("file:///tmp/db").is_none());
+        assert!(provider("/tmp/db").is_none());
+        let s3 = provider("s3://bucket/db").unwrap();
+
+        crate::runtime::block_on(async {
+            assert_eq!(s3.get_credential().await.unwrap().key_id, "key0");
+            assert_eq!(s3.get_credential().await.unwrap().key_id, "key1");
+            assert_eq!(s3.get_credential().await.unwrap().key_id, "key1");
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        register(Some(Arc::new(|| Err(anyhow!("expired session")))));
+        let failing = provider("s3://bucket/db").unwrap();
+        let err = crate::runtime::block_on(failing.get_credential()).unwrap_err().to_string();
+        assert!(err.contains("expired session"));
+        register(None);
+    }
+}
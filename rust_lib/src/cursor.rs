@@ -0,0 +1,82 @@
+//! Result cursors for streaming large search results across FFI in chunks.
+
+use anyhow::{anyhow, Result};
+use arrow_array::RecordBatch;
+use futures_util::TryStreamExt;
+use lancedb::arrow::SendableRecordBatchStream;
+
+use crate::admission::AdmissionPermit;
+use crate::lance_manager::LanceIndex;
+use crate::runtime;
+
+/// Streams (label, distance) pairs from a search without materializing the full result.
+///
+/// At most one Lance result batch is buffered at a time; each `next_into` call copies
+/// up to the caller's buffer capacity and pulls further batches on demand.
+pub struct SearchCursor {
+    stream: SendableRecordBatchStream,
+    current: Option<RecordBatch>,
+    offset: usize,
+    exhausted: bool,
+    _permit: AdmissionPermit,
+}
+
+impl SearchCursor {
+    pub(crate) fn new(stream: SendableRecordBatchStream, permit: AdmissionPermit) -> Self {
+        Self {
+            stream,
+            current: None,
+            offset: 0,
+            exhausted: false,
+            _permit: permit,
+        }
+    }
+
+    /// Fill `labels`/`distances` with the next results. Returns the number written;
+    /// 0 means the cursor is exhausted.
+    pub fn next_into(&mut self, labels: &mut [i64], distances: &mut [f32]) -> Result<usize> {
+        let capacity = labels.len().min(distances.len());
+        let mut written = 0;
+
+        while written < capacity {
+            let batch = match &self.current {
+                Some(batch) if self.offset < batch.num_rows() => batch,
+                _ => {
+                    if !self.advance()? {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            let (label_col, dist_col) = LanceIndex::label_distance_columns(batch)?;
+            let take = (batch.num_rows() - self.offset).min(capacity - written);
+            let range = self.offset..self.offset + take;
+            labels[written..written + take].copy_from_slice(&label_col.values()[range.clone()]);
+            distances[written..written + take].copy_from_slice(&dist_col.values()[range]);
+            self.offset += take;
+            written += take;
+        }
+
+        Ok(written)
+    }
+
+    /// Load the next non-empty batch. Returns false once the stream is drained.
+    fn advance(&mut self) -> Result<bool> {
+        if self.exhausted {
+            return Ok(false);
+        }
+        self.current = None;
+        self.offset = 0;
+        while let Some(batch) = runtime::block_on(self.stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            if batch.num_rows() > 0 {
+                self.current = Some(batch);
+                return Ok(true);
+            }
+        }
+        self.exhausted = true;
+        Ok(false)
+    }
+}
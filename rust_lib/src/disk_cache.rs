@@ -0,0 +1,498 @@
+//! Local disk cache of table files read from object storage.
+//!
+//! Lance never rewrites a data, deletion or index file once it is committed: new
+//! table versions add files next to the old ones. Those files can therefore be
+//! cached on local disk by path, across sessions, while manifests and everything
+//! else are always read from the store so new versions are seen. Tables opened from
+//! object storage (any URI whose scheme is not `file`) after [`configure`] read
+//! through the cache.
+//!
+//! A read that misses is answered by the store with just the range it asked for,
+//! while the whole file is downloaded to disk in the background, once however many
+//! reads miss on it meanwhile; each fragment's data file is thus cached as a unit.
+//! File I/O runs on the blocking pool. Once the cache exceeds its size, the least
+//! recently read files are evicted.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use lance::io::WrappingObjectStore;
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOpts, PutOptions, PutPayload, PutResult,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::UNIX_EPOCH;
+
+use crate::runtime;
+
+static CACHE: RwLock<Option<Arc<DiskCache>>> = RwLock::new(None);
+
+static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+/// Cache files of tables opened from now on in `dir`, up to `max_bytes`; `None`
+/// stops caching for them. Files left in `dir` by an earlier session are reused.
+pub fn configure(config: Option<(&str, u64)>) -> Result<()> {
+    let cache = config
+        .map(|(dir, max_bytes)| DiskCache::open(dir, max_bytes).map(Arc::new))
+        .transpose()?;
+    *CACHE.write().unwrap_or_else(|e| e.into_inner()) = cache;
+    Ok(())
+}
+
+/// Figures of the configured cache, if any.
+pub fn stats() -> Option<DiskCacheStats> {
+    let cache = CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()?;
+    let state = cache.state.lock().unwrap_or_else(|e| e.into_inner());
+    Some(DiskCacheStats {
+        max_bytes: cache.max_bytes,
+        bytes: state.bytes,
+        files: state.files.len() as u64,
+        hits: state.hits,
+        misses: state.misses,
+    })
+}
+
+/// Store wrapper routing reads of the table at `uri` through the cache, when it is
+/// remote and a cache is configured.
+pub fn wrapper(uri: &str) -> Option<Arc<dyn WrappingObjectStore>> {
+    if !uri.split_once("://").is_some_and(|(scheme, _)| scheme != "file") {
+        return None;
+    }
+    let cache = CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()?;
+    Some(Arc::new(CacheWrapper(cache)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskCacheStats {
+    pub max_bytes: u64,
+    pub bytes: u64,
+    pub files: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// Cached file name -> (size, tick of its last read).
+    files: HashMap<String, (u64, u64)>,
+    bytes: u64,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    state: Mutex<CacheState>,
+    /// Cached file names being downloaded.
+    fills: Mutex<HashSet<String>>,
+}
+
+impl DiskCache {
+    fn open(dir: &str, max_bytes: u64) -> Result<Self> {
+        if max_bytes == 0 {
+            return Err(anyhow!("disk cache size must be positive"));
+        }
+        std::fs::create_dir_all(dir).map_err(|e| anyhow!("cannot create cache directory {}: {}", dir, e))?;
+        // Files of earlier sessions count as used in modification order
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !meta.is_file() {
+                continue;
+            }
+            if name.ends_with(".tmp") {
+                let _ = std::fs::remove_file(entry.path());
+                continue;
+            }
+            found.push((meta.modified().ok(), name, meta.len()));
+        }
+        found.sort();
+
+        let cache = Self {
+            dir: PathBuf::from(dir),
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+            fills: Mutex::new(HashSet::new()),
+        };
+        {
+            let mut state = cache.state.lock().unwrap_or_else(|e| e.into_inner());
+            for (_, name, size) in found {
+                state.tick += 1;
+                let tick = state.tick;
+                state.files.insert(name, (size, tick));
+                state.bytes += size;
+            }
+            cache.evict(&mut state);
+        }
+        Ok(cache)
+    }
+
+    /// Local path of the cached file `name`, marked as read; `None` on a miss.
+    fn lookup(&self, name: &str) -> Option<PathBuf> {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = &mut *guard;
+        state.tick += 1;
+        match state.files.get_mut(name) {
+            Some(entry) => {
+                entry.1 = state.tick;
+                state.hits += 1;
+                Some(self.dir.join(name))
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Claim the download of `name`; false if it is already being downloaded.
+    fn begin_fill(&self, name: &str) -> bool {
+        self.fills.lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_string())
+    }
+
+    fn end_fill(&self, name: &str) {
+        self.fills.lock().unwrap_or_else(|e| e.into_inner()).remove(name);
+    }
+
+    /// A fresh path to download `name` to before [`DiskCache::insert`].
+    fn temp_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}.tmp", name, NEXT_TEMP.fetch_add(1, Ordering::Relaxed)))
+    }
+
+    /// Move the downloaded `temp` of `size` bytes in as `name`, evicting the least
+    /// recently read files to make room.
+    fn insert(&self, name: &str, temp: &PathBuf, size: u64) -> std::io::Result<()> {
+        std::fs::rename(temp, self.dir.join(name))?;
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some((old, _)) = state.files.insert(name.to_string(), (size, tick)) {
+            state.bytes -= old;
+        }
+        state.bytes += size;
+        self.evict(&mut state);
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((size, _)) = state.files.remove(name) {
+            state.bytes -= size;
+            let _ = std::fs::remove_file(self.dir.join(name));
+        }
+    }
+
+    fn evict(&self, state: &mut CacheState) {
+        while state.bytes > self.max_bytes {
+            let Some(name) = state.files.iter().min_by_key(|(_, (_, used))| *used).map(|(name, _)| name.clone())
+            else {
+                break;
+            };
+            if let Some((size, _)) = state.files.remove(&name) {
+                state.bytes -= size;
+            }
+            // A reader that already looked the file up falls back to the store
+            let _ = std::fs::remove_file(self.dir.join(&name));
+        }
+    }
+}
+
+/// Cache file name of `location` in the store described by `store`: a 64-bit
+/// FNV-1a hash, stable across builds so later sessions find the file.
+fn cache_key(store: &str, location: &Path) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in store.bytes().chain([0]).chain(location.as_ref().bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Whether `location` is a file Lance never rewrites.
+fn immutable(location: &Path) -> bool {
+    location.parts().any(|part| matches!(part.as_ref(), "data" | "_indices" | "_deletions"))
+}
+
+/// The byte range `range` selects in an object of `len` bytes.
+fn resolve(range: Option<&GetRange>, len: usize) -> Range<usize> {
+    match range {
+        None => 0..len,
+        Some(GetRange::Bounded(r)) => r.start.min(len)..r.end.min(len),
+        Some(GetRange::Offset(offset)) => (*offset).min(len)..len,
+        Some(GetRange::Suffix(n)) => len.saturating_sub(*n)..len,
+    }
+}
+
+fn single_chunk(data: Bytes) -> GetResultPayload {
+    GetResultPayload::Stream(stream::once(async move { Ok(data) }).boxed())
+}
+
+#[derive(Debug)]
+struct CacheWrapper(Arc<DiskCache>);
+
+impl WrappingObjectStore for CacheWrapper {
+    fn wrap(&self, original: Arc<dyn ObjectStore>) -> Arc<dyn ObjectStore> {
+        Arc::new(CachedStore {
+            store: original.to_string(),
+            inner: original,
+            cache: self.0.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CachedStore {
+    /// Description of `inner`, which tells stores (e.g. buckets) apart in cache keys.
+    store: String,
+    inner: Arc<dyn ObjectStore>,
+    cache: Arc<DiskCache>,
+}
+
+impl fmt::Display for CachedStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DiskCache({})", self.inner)
+    }
+}
+
+impl CachedStore {
+    /// Serve a read of `range` of `location` (or its metadata, for `head`) from the
+    /// cached file at `path`.
+    fn read_local(path: &PathBuf, location: &Path, range: Option<&GetRange>, head: bool) -> std::io::Result<GetResult> {
+        let mut file = File::open(path)?;
+        let meta = file.metadata()?;
+        let len = meta.len() as usize;
+        let range = resolve(range, len);
+        let mut data = vec![0; if head { 0 } else { range.len() }];
+        file.seek(SeekFrom::Start(range.start as u64))?;
+        file.read_exact(&mut data)?;
+        let modified_ms = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as i64);
+        Ok(GetResult {
+            payload: single_chunk(Bytes::from(data)),
+            meta: ObjectMeta {
+                location: location.clone(),
+                last_modified: chrono::DateTime::from_timestamp_millis(modified_ms).unwrap_or_default(),
+                size: len,
+                e_tag: None,
+                version: None,
+            },
+            range,
+            attributes: Default::default(),
+        })
+    }
+
+    /// Download `location` into the cache as `name`, then release its claim.
+    async fn fill(cache: Arc<DiskCache>, inner: Arc<dyn ObjectStore>, location: Path, name: String) {
+        let temp = cache.temp_path(&name);
+        // Best effort: a failed download or a full disk only costs the caching
+        if Self::download(&cache, inner.as_ref(), &location, &name, &temp).await.is_err() {
+            let _ = runtime::run_blocking(move || std::fs::remove_file(temp)).await;
+        }
+        cache.end_fill(&name);
+    }
+
+    async fn download(
+        cache: &Arc<DiskCache>,
+        inner: &dyn ObjectStore,
+        location: &Path,
+        name: &str,
+        temp: &PathBuf,
+    ) -> Result<()> {
+        let result = inner.get(location).await?;
+        let size = result.meta.size as u64;
+        // Files larger than the whole cache are not stored
+        if size > cache.max_bytes {
+            return Ok(());
+        }
+        let mut chunks = result.into_stream();
+        let path = temp.clone();
+        let mut file = runtime::run_blocking(move || File::create(path)).await?;
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            file = runtime::run_blocking(move || {
+                let mut file = file;
+                file.write_all(&chunk).map(|_| file)
+            })
+            .await?;
+        }
+        drop(file);
+        let (cache, name, temp) = (cache.clone(), name.to_string(), temp.clone());
+        runtime::run_blocking(move || cache.insert(&name, &temp, size)).await?;
+        Ok(())
+    }
+
+    /// Drop the cached copy of `location`, if any.
+    async fn forget(&self, location: &Path) {
+        let (cache, name) = (self.cache.clone(), cache_key(&self.store, location));
+        runtime::run_blocking(move || cache.remove(&name)).await;
+    }
+}
+
+#[async_trait]
+impl ObjectStore for CachedStore {
+    async fn put_opts(&self, location: &Path, payload: PutPayload, opts: PutOptions) -> object_store::Result<PutResult> {
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> object_store::Result<GetResult> {
+        let conditional = options.if_match.is_some()
+            || options.if_none_match.is_some()
+            || options.if_modified_since.is_some()
+            || options.if_unmodified_since.is_some()
+            || options.version.is_some();
+        if conditional || !immutable(location) {
+            return self.inner.get_opts(location, options).await;
+        }
+        let name = cache_key(&self.store, location);
+        if let Some(path) = self.cache.lookup(&name) {
+            let (cached, range, head) = (location.clone(), options.range.clone(), options.head);
+            match runtime::run_blocking(move || Self::read_local(&path, &cached, range.as_ref(), head)).await {
+                Ok(result) => return Ok(result),
+                // Evicted or damaged since the lookup
+                Err(_) => self.forget(location).await,
+            }
+        }
+        if !options.head && self.cache.begin_fill(&name) {
+            runtime::spawn(Self::fill(self.cache.clone(), self.inner.clone(), location.clone(), name));
+        }
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.forget(location).await;
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.forget(from).await;
+        self.inner.rename(from, to).await
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.forget(from).await;
+        self.inner.rename_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+    use std::time::Duration;
+
+    /// Wait for the background downloads of `cache` to finish.
+    async fn filled(cache: &DiskCache) {
+        while !cache.fills.lock().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[test]
+    fn test_cached_reads_and_eviction() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("cache").to_string_lossy().into_owned();
+        let cache = Arc::new(DiskCache::open(&dir, 10).unwrap());
+        let inner: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let store = CacheWrapper(cache.clone()).wrap(inner.clone());
+
+        crate::runtime::block_on(async {
+            let a = Path::from("t.lance/data/a.lance");
+            let b = Path::from("t.lance/data/b.lance");
+            let manifest = Path::from("t.lance/_versions/1.manifest");
+            for path in [&a, &b, &manifest] {
+                inner.put(path, PutPayload::from_static(b"0123456")).await.unwrap();
+            }
+
+            // A miss is served from the store while the file downloads in the background
+            assert_eq!(store.get_range(&a, 2..5).await.unwrap(), Bytes::from_static(b"234"));
+            filled(&cache).await;
+            // Served from disk even once the store lost the file
+            inner.delete(&a).await.unwrap();
+            assert_eq!(store.get_range(&a, 1..3).await.unwrap(), Bytes::from_static(b"12"));
+            assert_eq!(store.head(&a).await.unwrap().size, 7);
+
+            // Manifests are never cached
+            store.get(&manifest).await.unwrap();
+            inner.delete(&manifest).await.unwrap();
+            assert!(store.get(&manifest).await.is_err());
+
+            // A file already being downloaded is not downloaded again
+            let key = cache_key(&inner.to_string(), &b);
+            assert!(cache.begin_fill(&key));
+            assert_eq!(store.get_range(&b, 0..2).await.unwrap(), Bytes::from_static(b"01"));
+            assert!(!cache.begin_fill(&key));
+            cache.end_fill(&key);
+            assert!(cache.lookup(&key).is_none());
+
+            // b does not fit next to a, so the least recently read file goes
+            assert_eq!(store.get(&b).await.unwrap().bytes().await.unwrap().len(), 7);
+            filled(&cache).await;
+            assert!(store.get(&a).await.is_err());
+            filled(&cache).await;
+        });
+        let stats = cache.state.lock().unwrap();
+        assert_eq!((stats.files.len(), stats.bytes), (1, 7));
+
+        drop(stats);
+        // A new session finds the cached file
+        let reopened = DiskCache::open(&dir, 10).unwrap();
+        assert_eq!(reopened.state.lock().unwrap().bytes, 7);
+    }
+
+    #[test]
+    fn test_resolve_and_key() {
+        assert_eq!(resolve(None, 10), 0..10);
+        assert_eq!(resolve(Some(&GetRange::Bounded(2..20)), 10), 2..10);
+        assert_eq!(resolve(Some(&GetRange::Offset(4)), 10), 4..10);
+        assert_eq!(resolve(Some(&GetRange::Suffix(3)), 10), 7..10);
+
+        let path = Path::from("t.lance/data/a.lance");
+        assert!(immutable(&path));
+        assert!(!immutable(&Path::from("t.lance/_versions/1.manifest")));
+        assert_eq!(cache_key("s3://a", &path), cache_key("s3://a", &path));
+        assert_ne!(cache_key("s3://a", &path), cache_key("s3://b", &path));
+    }
+}
@@ -0,0 +1,424 @@
+//! Exact distances with the same semantics as Lance's distance types, used when
+//! re-scoring candidates outside of a Lance query.
+//!
+//! The inner loops run on SIMD kernels picked once per process by runtime feature
+//! detection: AVX2+FMA on x86_64, NEON on aarch64, and otherwise a scalar loop with
+//! independent accumulators the compiler can vectorize.
+//!
+//! Hosts can also register custom metrics (e.g. weighted L2) by name. They are only
+//! used on exact paths such as rescoring, never by the ANN index.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Distance between two vectors of equal length; smaller is closer.
+pub type CustomMetric = Arc<dyn Fn(&[f32], &[f32]) -> f32 + Send + Sync>;
+
+static CUSTOM_METRICS: RwLock<Option<HashMap<String, CustomMetric>>> = RwLock::new(None);
+
+/// Register (or replace) a process-wide custom metric. Built-in metric names are
+/// reserved.
+pub fn register_metric(name: &str, metric: CustomMetric) -> Result<()> {
+    if canonical_metric(name).is_ok() {
+        return Err(anyhow!("'{}' is a built-in metric", name));
+    }
+    let mut registry = CUSTOM_METRICS
+        .write()
+        .map_err(|_| anyhow!("metric registry lock poisoned"))?;
+    registry
+        .get_or_insert_with(HashMap::new)
+        .insert(name.to_string(), metric);
+    Ok(())
+}
+
+/// A metric resolved once for repeated exact distance computations.
+#[derive(Clone)]
+pub enum Metric {
+    Builtin(&'static str),
+    Custom(CustomMetric),
+}
+
+impl Metric {
+    /// A built-in metric (any alias) or a registered custom metric.
+    pub fn resolve(name: &str) -> Result<Self> {
+        if let Ok(builtin) = canonical_metric(name) {
+            return Ok(Self::Builtin(builtin));
+        }
+        let registry = CUSTOM_METRICS
+            .read()
+            .map_err(|_| anyhow!("metric registry lock poisoned"))?;
+        registry
+            .as_ref()
+            .and_then(|r| r.get(name))
+            .cloned()
+            .map(Self::Custom)
+            .ok_or_else(|| anyhow!("unsupported metric '{}'", name))
+    }
+
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> Result<f32> {
+        match self {
+            Self::Builtin(metric) => distance(metric, a, b),
+            Self::Custom(metric) => {
+                if a.len() != b.len() {
+                    return Err(anyhow!("dimension mismatch: {} vs {}", a.len(), b.len()));
+                }
+                Ok(metric(a, b))
+            }
+        }
+    }
+}
+
+/// Distance between `a` and `b` under `metric` ("l2", "cosine", "dot"/"ip").
+/// Smaller is closer for every metric, as in Lance.
+pub fn distance(metric: &str, a: &[f32], b: &[f32]) -> Result<f32> {
+    if a.len() != b.len() {
+        return Err(anyhow!("dimension mismatch: {} vs {}", a.len(), b.len()));
+    }
+    let kernels = &*KERNELS;
+    Ok(match metric {
+        // Lance reports squared L2
+        "l2" => (kernels.l2)(a, b),
+        "cosine" => {
+            let (dot, na, nb) = (kernels.cosine)(a, b);
+            let denom = (na * nb).sqrt();
+            if denom == 0.0 {
+                1.0
+            } else {
+                1.0 - dot / denom
+            }
+        }
+        "dot" | "ip" => 1.0 - (kernels.dot)(a, b),
+        other => return Err(anyhow!("unsupported metric '{}'", other)),
+    })
+}
+
+/// Check per-dimension weights for a `dimension`-wide vector: finite, non-negative
+/// and not all zero. A zero weight masks its dimension out.
+pub fn check_weights(weights: &[f32], dimension: usize) -> Result<()> {
+    if weights.len() != dimension {
+        return Err(anyhow!(
+            "expected {} dimension weights, got {}",
+            dimension,
+            weights.len()
+        ));
+    }
+    if let Some(w) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+        return Err(anyhow!("dimension weights must be finite and non-negative, got {}", w));
+    }
+    if weights.iter().all(|w| *w == 0.0) {
+        return Err(anyhow!("dimension weights mask every dimension"));
+    }
+    Ok(())
+}
+
+/// `vector` scaled by the square root of `weights`, so any metric over two weighed
+/// vectors is its weighted form: L2 becomes `sum(w * (a - b)^2)`, dot becomes
+/// `sum(w * a * b)`, and cosine compares the weighted vectors.
+pub fn weigh(vector: &[f32], weights: &[f32]) -> Vec<f32> {
+    vector.iter().zip(weights).map(|(x, w)| x * w.sqrt()).collect()
+}
+
+/// `a + b`, component-wise.
+pub fn add(a: &[f32], b: &[f32]) -> Result<Vec<f32>> {
+    check_same_dimension(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x + y).collect())
+}
+
+/// `a - b`, component-wise.
+pub fn subtract(a: &[f32], b: &[f32]) -> Result<Vec<f32>> {
+    check_same_dimension(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x - y).collect())
+}
+
+/// Component-wise mean, accumulated in `f64` like the table centroid.
+pub fn average(vectors: &[&[f32]]) -> Result<Vec<f32>> {
+    let first = vectors.first().ok_or_else(|| anyhow!("cannot average zero vectors"))?;
+    let mut sum = vec![0f64; first.len()];
+    for vector in vectors {
+        check_same_dimension(first, vector)?;
+        sum.iter_mut().zip(*vector).for_each(|(s, x)| *s += *x as f64);
+    }
+    Ok(sum.iter().map(|s| (s / vectors.len() as f64) as f32).collect())
+}
+
+/// `a·b` on the same kernel the "dot" metric uses.
+pub fn dot(a: &[f32], b: &[f32]) -> Result<f32> {
+    check_same_dimension(a, b)?;
+    Ok((KERNELS.dot)(a, b))
+}
+
+/// `vector` scaled to unit L2 norm; a zero vector stays zero. This is the rule
+/// the `normalize` query transform step applies.
+pub fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = (KERNELS.dot)(vector, vector).sqrt();
+    if norm > 0.0 {
+        vector.iter().map(|x| x / norm).collect()
+    } else {
+        vector.to_vec()
+    }
+}
+
+fn check_same_dimension(a: &[f32], b: &[f32]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(anyhow!("dimension mismatch: {} vs {}", a.len(), b.len()));
+    }
+    Ok(())
+}
+
+/// Instruction set the distance kernels use: "avx2", "neon" or "scalar".
+pub fn simd_level() -> &'static str {
+    KERNELS.level
+}
+
+/// Inner-loop kernels over equal-length slices.
+struct Kernels {
+    level: &'static str,
+    dot: fn(&[f32], &[f32]) -> f32,
+    /// Squared L2.
+    l2: fn(&[f32], &[f32]) -> f32,
+    /// (a·b, |a|², |b|²) in one pass.
+    cosine: fn(&[f32], &[f32]) -> (f32, f32, f32),
+}
+
+const SCALAR: Kernels = Kernels {
+    level: "scalar",
+    dot: scalar::dot,
+    l2: scalar::l2,
+    cosine: scalar::cosine,
+};
+
+static KERNELS: LazyLock<Kernels> = LazyLock::new(|| {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+        return Kernels {
+            level: "avx2",
+            // SAFETY: the CPU supports the features the kernels are compiled for.
+            dot: |a, b| unsafe { avx2::dot(a, b) },
+            l2: |a, b| unsafe { avx2::l2(a, b) },
+            cosine: |a, b| unsafe { avx2::cosine(a, b) },
+        };
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        return Kernels {
+            level: "neon",
+            // SAFETY: the CPU supports the features the kernels are compiled for.
+            dot: |a, b| unsafe { neon::dot(a, b) },
+            l2: |a, b| unsafe { neon::l2(a, b) },
+            cosine: |a, b| unsafe { neon::cosine(a, b) },
+        };
+    }
+    SCALAR
+});
+
+mod scalar {
+    /// Independent accumulators, so the loop vectorizes without fast-math.
+    const LANES: usize = 8;
+
+    pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let mut acc = [0f32; LANES];
+        let (ca, cb) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+        let tail: f32 = ca.remainder().iter().zip(cb.remainder()).map(|(x, y)| x * y).sum();
+        for (x, y) in ca.zip(cb) {
+            for ((acc, x), y) in acc.iter_mut().zip(x).zip(y) {
+                *acc += x * y;
+            }
+        }
+        acc.iter().sum::<f32>() + tail
+    }
+
+    pub fn l2(a: &[f32], b: &[f32]) -> f32 {
+        let mut acc = [0f32; LANES];
+        let (ca, cb) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+        let tail: f32 = ca.remainder().iter().zip(cb.remainder()).map(|(x, y)| (x - y) * (x - y)).sum();
+        for (x, y) in ca.zip(cb) {
+            for ((acc, x), y) in acc.iter_mut().zip(x).zip(y) {
+                *acc += (x - y) * (x - y);
+            }
+        }
+        acc.iter().sum::<f32>() + tail
+    }
+
+    pub fn cosine(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+        (dot(a, b), dot(a, a), dot(b, b))
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn hsum(v: __m256) -> f32 {
+        let sum = _mm_add_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1));
+        let sum = _mm_add_ps(sum, _mm_movehl_ps(sum, sum));
+        let sum = _mm_add_ss(sum, _mm_shuffle_ps(sum, sum, 1));
+        _mm_cvtss_f32(sum)
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() - a.len() % 8;
+        let mut acc = _mm256_setzero_ps();
+        for i in (0..n).step_by(8) {
+            acc = _mm256_fmadd_ps(_mm256_loadu_ps(a.as_ptr().add(i)), _mm256_loadu_ps(b.as_ptr().add(i)), acc);
+        }
+        hsum(acc) + super::scalar::dot(&a[n..], &b[n..])
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn l2(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() - a.len() % 8;
+        let mut acc = _mm256_setzero_ps();
+        for i in (0..n).step_by(8) {
+            let d = _mm256_sub_ps(_mm256_loadu_ps(a.as_ptr().add(i)), _mm256_loadu_ps(b.as_ptr().add(i)));
+            acc = _mm256_fmadd_ps(d, d, acc);
+        }
+        hsum(acc) + super::scalar::l2(&a[n..], &b[n..])
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn cosine(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+        let n = a.len() - a.len() % 8;
+        let (mut dot, mut na, mut nb) = (_mm256_setzero_ps(), _mm256_setzero_ps(), _mm256_setzero_ps());
+        for i in (0..n).step_by(8) {
+            let x = _mm256_loadu_ps(a.as_ptr().add(i));
+            let y = _mm256_loadu_ps(b.as_ptr().add(i));
+            dot = _mm256_fmadd_ps(x, y, dot);
+            na = _mm256_fmadd_ps(x, x, na);
+            nb = _mm256_fmadd_ps(y, y, nb);
+        }
+        let (td, ta, tb) = super::scalar::cosine(&a[n..], &b[n..]);
+        (hsum(dot) + td, hsum(na) + ta, hsum(nb) + tb)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() - a.len() % 4;
+        let mut acc = vdupq_n_f32(0.0);
+        for i in (0..n).step_by(4) {
+            acc = vfmaq_f32(acc, vld1q_f32(a.as_ptr().add(i)), vld1q_f32(b.as_ptr().add(i)));
+        }
+        vaddvq_f32(acc) + super::scalar::dot(&a[n..], &b[n..])
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn l2(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len() - a.len() % 4;
+        let mut acc = vdupq_n_f32(0.0);
+        for i in (0..n).step_by(4) {
+            let d = vsubq_f32(vld1q_f32(a.as_ptr().add(i)), vld1q_f32(b.as_ptr().add(i)));
+            acc = vfmaq_f32(acc, d, d);
+        }
+        vaddvq_f32(acc) + super::scalar::l2(&a[n..], &b[n..])
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn cosine(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+        let n = a.len() - a.len() % 4;
+        let (mut dot, mut na, mut nb) = (vdupq_n_f32(0.0), vdupq_n_f32(0.0), vdupq_n_f32(0.0));
+        for i in (0..n).step_by(4) {
+            let x = vld1q_f32(a.as_ptr().add(i));
+            let y = vld1q_f32(b.as_ptr().add(i));
+            dot = vfmaq_f32(dot, x, y);
+            na = vfmaq_f32(na, x, x);
+            nb = vfmaq_f32(nb, y, y);
+        }
+        let (td, ta, tb) = super::scalar::cosine(&a[n..], &b[n..]);
+        (vaddvq_f32(dot) + td, vaddvq_f32(na) + ta, vaddvq_f32(nb) + tb)
+    }
+}
+
+/// Canonical name of a metric ("ip" is an alias of "dot").
+pub fn canonical_metric(metric: &str) -> Result<&'static str> {
+    match metric.trim().to_ascii_lowercase().as_str() {
+        "l2" => Ok("l2"),
+        "cosine" => Ok("cosine"),
+        "dot" | "ip" => Ok("dot"),
+        other => Err(anyhow!("unsupported metric '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        assert_eq!(distance("l2", &[0.0, 0.0], &[3.0, 4.0]).unwrap(), 25.0);
+        assert!(distance("cosine", &[1.0, 0.0], &[2.0, 0.0]).unwrap().abs() < 1e-6);
+        assert_eq!(distance("dot", &[1.0, 2.0], &[3.0, 4.0]).unwrap(), -10.0);
+        assert!(distance("l2", &[1.0], &[1.0, 2.0]).is_err());
+        assert!(distance("hamming", &[1.0], &[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_simd_kernels_match_scalar() {
+        let kernels = &*KERNELS;
+        for len in [0, 1, 7, 8, 9, 31, 128, 131] {
+            let a: Vec<f32> = (0..len).map(|i| (i as f32 * 0.37).sin()).collect();
+            let b: Vec<f32> = (0..len).map(|i| (i as f32 * 0.11).cos()).collect();
+            let close = |x: f32, y: f32| (x - y).abs() <= 1e-4 * (1.0 + y.abs());
+            assert!(close((kernels.dot)(&a, &b), scalar::dot(&a, &b)), "{} dot len {}", kernels.level, len);
+            assert!(close((kernels.l2)(&a, &b), scalar::l2(&a, &b)), "{} l2 len {}", kernels.level, len);
+            let (d, na, nb) = (kernels.cosine)(&a, &b);
+            let (sd, sna, snb) = scalar::cosine(&a, &b);
+            assert!(close(d, sd) && close(na, sna) && close(nb, snb), "{} cosine len {}", kernels.level, len);
+        }
+    }
+
+    #[test]
+    fn test_custom_metric_registry() {
+        assert!(Metric::resolve("test_weighted").is_err());
+        register_metric("test_weighted", Arc::new(|a, b| {
+            a.iter().zip(b).enumerate().map(|(i, (x, y))| (i + 1) as f32 * (x - y).powi(2)).sum()
+        }))
+        .unwrap();
+        let weighted = Metric::resolve("test_weighted").unwrap();
+        assert_eq!(weighted.distance(&[0.0, 0.0], &[1.0, 1.0]).unwrap(), 3.0);
+        assert!(weighted.distance(&[0.0], &[1.0, 1.0]).is_err());
+
+        assert!(register_metric("ip", Arc::new(|_, _| 0.0)).is_err());
+        assert!(matches!(Metric::resolve("IP").unwrap(), Metric::Builtin("dot")));
+    }
+
+    #[test]
+    fn test_weighted_distance() {
+        let weights = [4.0, 0.0];
+        assert!(check_weights(&weights, 2).is_ok());
+        let (a, b) = (weigh(&[0.0, 0.0], &weights), weigh(&[1.0, 5.0], &weights));
+        assert_eq!(distance("l2", &a, &b).unwrap(), 4.0);
+
+        assert!(check_weights(&[1.0], 2).is_err());
+        assert!(check_weights(&[1.0, -1.0], 2).is_err());
+        assert!(check_weights(&[f32::NAN, 1.0], 2).is_err());
+        assert!(check_weights(&[0.0, 0.0], 2).is_err());
+    }
+
+    #[test]
+    fn test_vector_arithmetic() {
+        assert_eq!(add(&[1.0, 2.0], &[3.0, 4.0]).unwrap(), vec![4.0, 6.0]);
+        assert_eq!(subtract(&[1.0, 2.0], &[3.0, 4.0]).unwrap(), vec![-2.0, -2.0]);
+        assert_eq!(average(&[&[1.0, 2.0], &[3.0, 6.0]]).unwrap(), vec![2.0, 4.0]);
+        assert_eq!(dot(&[1.0, 2.0], &[3.0, 4.0]).unwrap(), 11.0);
+        assert_eq!(normalize(&[3.0, 4.0]), vec![0.6, 0.8]);
+        assert_eq!(normalize(&[0.0, 0.0]), vec![0.0, 0.0]);
+
+        assert!(add(&[1.0], &[1.0, 2.0]).is_err());
+        assert!(average(&[&[1.0], &[1.0, 2.0]]).is_err());
+        assert!(average(&[]).is_err());
+    }
+
+    #[test]
+    fn test_canonical_metric() {
+        assert_eq!(canonical_metric("IP").unwrap(), "dot");
+        assert_eq!(canonical_metric(" cosine ").unwrap(), "cosine");
+        assert!(canonical_metric("hamming").is_err());
+    }
+}
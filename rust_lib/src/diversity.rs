@@ -0,0 +1,76 @@
+//! Diversity of a search's hits.
+//!
+//! A/B tests of retrieval track how varied the hits are as well as how close. A
+//! [`Diversity`] summarizes one result set: the mean distance between every pair of
+//! hits, in the handle's exact metric over the stored vectors, and optionally the
+//! number of distinct non-NULL values of a column among the hits (how many sources
+//! or categories they span). The hits' vectors are read inside the search, so
+//! callers get the metrics without fetching them.
+
+use anyhow::Result;
+use arrow::row::{RowConverter, SortField};
+use arrow_array::{Array, ArrayRef};
+use std::collections::HashSet;
+
+use crate::distance::Metric;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Diversity {
+    /// Mean distance over all pairs of hits; `None` with fewer than two hits.
+    pub mean_pairwise_distance: Option<f64>,
+    /// Distinct non-NULL values of the chosen column among the hits; `None` when no
+    /// column was chosen.
+    pub distinct_values: Option<usize>,
+}
+
+/// Mean `metric` distance over all pairs of `vectors`; `None` with fewer than two.
+pub fn mean_pairwise_distance(vectors: &[&[f32]], metric: &Metric) -> Result<Option<f64>> {
+    if vectors.len() < 2 {
+        return Ok(None);
+    }
+    let mut sum = 0.0f64;
+    for (i, a) in vectors.iter().enumerate() {
+        for b in &vectors[i + 1..] {
+            sum += metric.distance(a, b)? as f64;
+        }
+    }
+    let pairs = vectors.len() * (vectors.len() - 1) / 2;
+    Ok(Some(sum / pairs as f64))
+}
+
+/// Distinct non-NULL values of `column`.
+pub fn distinct_values(column: &ArrayRef) -> Result<usize> {
+    let converter = RowConverter::new(vec![SortField::new(column.data_type().clone())])?;
+    let rows = converter.convert_columns(&[column.clone()])?;
+    let distinct: HashSet<_> = (0..column.len())
+        .filter(|i| column.is_valid(*i))
+        .map(|i| rows.row(i))
+        .collect();
+    Ok(distinct.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::StringArray;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_mean_pairwise_distance() {
+        let l2 = Metric::resolve("l2").unwrap();
+        let vectors: [&[f32]; 3] = [&[0.0, 0.0], &[1.0, 0.0], &[0.0, 1.0]];
+        // Squared l2: 1 + 1 + 2 over three pairs
+        let mean = mean_pairwise_distance(&vectors, &l2).unwrap().unwrap();
+        assert!((mean - 4.0 / 3.0).abs() < 1e-6, "{}", mean);
+        assert_eq!(mean_pairwise_distance(&vectors[..1], &l2).unwrap(), None);
+        assert!(mean_pairwise_distance(&[&[0.0, 0.0], &[1.0]], &l2).is_err());
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let column: ArrayRef = Arc::new(StringArray::from(vec![Some("news"), None, Some("blog"), Some("news")]));
+        assert_eq!(distinct_values(&column).unwrap(), 2);
+        let empty: ArrayRef = Arc::new(StringArray::from(Vec::<Option<&str>>::new()));
+        assert_eq!(distinct_values(&empty).unwrap(), 0);
+    }
+}
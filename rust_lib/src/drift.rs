@@ -0,0 +1,188 @@
+//! Running embedding statistics for drift detection.
+//!
+//! Each handle keeps a centroid and norm distribution, folded in on its own appends
+//! and recomputed from a scan after deletes or commits by other handles. A snapshot
+//! can be tagged as a baseline in the table metadata; comparing the current stats
+//! against it shows when newly ingested vectors come from a different embedding
+//! space (e.g. a re-embedded corpus mixed with the old one).
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, FixedSizeListArray, Float32Array, Float64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorStats {
+    pub count: u64,
+    /// Per-dimension sum; the centroid is `sum / count`.
+    pub sum: Vec<f64>,
+    pub norm_sum: f64,
+    pub norm_sq_sum: f64,
+}
+
+impl VectorStats {
+    pub fn new(dimension: usize) -> Self {
+        Self {
+            count: 0,
+            sum: vec![0.0; dimension],
+            norm_sum: 0.0,
+            norm_sq_sum: 0.0,
+        }
+    }
+
+    pub fn observe(&mut self, v: &[f32]) {
+        let mut norm_sq = 0f64;
+        for (s, &x) in self.sum.iter_mut().zip(v) {
+            *s += x as f64;
+            norm_sq += (x as f64) * (x as f64);
+        }
+        self.count += 1;
+        self.norm_sum += norm_sq.sqrt();
+        self.norm_sq_sum += norm_sq;
+    }
+
+    /// Fold every non-null vector of a FixedSizeList<Float32> column.
+    pub fn observe_array(&mut self, vectors: &FixedSizeListArray) -> Result<()> {
+        let values = vectors
+            .values()
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .ok_or_else(|| anyhow!("vector values not Float32"))?;
+        let dim = vectors.value_length() as usize;
+        for i in 0..vectors.len() {
+            if vectors.is_valid(i) {
+                // `values()` is already sliced to this array's window
+                let start = i * dim;
+                self.observe(&values.values()[start..start + dim]);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn centroid(&self) -> Vec<f64> {
+        let n = self.count.max(1) as f64;
+        self.sum.iter().map(|s| s / n).collect()
+    }
+
+    pub fn norm_mean(&self) -> f64 {
+        self.norm_sum / self.count.max(1) as f64
+    }
+
+    /// Population standard deviation of vector norms.
+    pub fn norm_std(&self) -> f64 {
+        let n = self.count.max(1) as f64;
+        let mean = self.norm_sum / n;
+        (self.norm_sq_sum / n - mean * mean).max(0.0).sqrt()
+    }
+
+    /// Metadata encoding: `count;norm_sum;norm_sq_sum;s0,s1,...`.
+    pub fn encode(&self) -> String {
+        let sum: Vec<String> = self.sum.iter().map(|s| s.to_string()).collect();
+        format!("{};{};{};{}", self.count, self.norm_sum, self.norm_sq_sum, sum.join(","))
+    }
+
+    pub fn decode(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.splitn(4, ';').collect();
+        if parts.len() != 4 {
+            return Err(anyhow!("malformed vector statistics"));
+        }
+        let bad = |_: std::num::ParseFloatError| anyhow!("malformed vector statistics");
+        Ok(Self {
+            count: parts[0].parse().map_err(|_| anyhow!("malformed vector statistics"))?,
+            norm_sum: parts[1].parse().map_err(bad)?,
+            norm_sq_sum: parts[2].parse().map_err(bad)?,
+            sum: parts[3]
+                .split(',')
+                .map(|x| x.parse::<f64>().map_err(bad))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Current statistics compared against a tagged baseline.
+#[derive(Debug, Clone)]
+pub struct DriftReport {
+    pub baseline: VectorStats,
+    pub current: VectorStats,
+}
+
+impl DriftReport {
+    /// L2 distance between the baseline and current centroids.
+    pub fn centroid_shift(&self) -> f64 {
+        self.baseline
+            .centroid()
+            .iter()
+            .zip(self.current.centroid())
+            .map(|(b, c)| (b - c) * (b - c))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Cosine similarity between the baseline and current centroids.
+    pub fn centroid_cosine(&self) -> f64 {
+        let (b, c) = (self.baseline.centroid(), self.current.centroid());
+        let dot: f64 = b.iter().zip(&c).map(|(x, y)| x * y).sum();
+        let nb = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let nc = c.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if nb == 0.0 || nc == 0.0 {
+            0.0
+        } else {
+            dot / (nb * nc)
+        }
+    }
+
+    /// Rows of (metric, baseline, current).
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let norm = |v: Vec<f64>| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let rows: Vec<(&str, f64, f64)> = vec![
+            ("count", self.baseline.count as f64, self.current.count as f64),
+            ("norm_mean", self.baseline.norm_mean(), self.current.norm_mean()),
+            ("norm_std", self.baseline.norm_std(), self.current.norm_std()),
+            ("centroid_norm", norm(self.baseline.centroid()), norm(self.current.centroid())),
+            ("centroid_shift", 0.0, self.centroid_shift()),
+            ("centroid_cosine", 1.0, self.centroid_cosine()),
+        ];
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("metric", DataType::Utf8, false),
+            Field::new("baseline", DataType::Float64, false),
+            Field::new("current", DataType::Float64, false),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from(rows.iter().map(|r| r.0).collect::<Vec<_>>())),
+            Arc::new(Float64Array::from(rows.iter().map(|r| r.1).collect::<Vec<_>>())),
+            Arc::new(Float64Array::from(rows.iter().map(|r| r.2).collect::<Vec<_>>())),
+        ])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_and_encoding() {
+        let mut stats = VectorStats::new(2);
+        stats.observe(&[3.0, 4.0]);
+        stats.observe(&[0.0, 0.0]);
+        assert_eq!(stats.centroid(), vec![1.5, 2.0]);
+        assert_eq!(stats.norm_mean(), 2.5);
+        assert_eq!(stats.norm_std(), 2.5);
+        assert_eq!(VectorStats::decode(&stats.encode()).unwrap(), stats);
+        assert!(VectorStats::decode("1;2;3").is_err());
+    }
+
+    #[test]
+    fn test_report_detects_shift() {
+        let mut baseline = VectorStats::new(2);
+        baseline.observe(&[1.0, 0.0]);
+        let mut current = baseline.clone();
+        current.observe(&[-1.0, 2.0]);
+
+        let report = DriftReport { baseline, current };
+        // Centroid moves from (1, 0) to (0, 1)
+        assert!((report.centroid_shift() - 2f64.sqrt()).abs() < 1e-9);
+        assert!(report.centroid_cosine().abs() < 1e-9);
+        assert_eq!(report.to_record_batch().unwrap().num_rows(), 6);
+    }
+}
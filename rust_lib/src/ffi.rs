@@ -2,25 +2,165 @@
 
 use std::ffi::{CStr, c_char, c_void};
 use std::slice;
+use std::sync::Arc;
 
+use arrow::buffer::{Buffer, ScalarBuffer};
 use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
-use crate::lance_manager::LanceIndex;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow_array::{
+    Array, BinaryArray, Float32Array, Int64Array, RecordBatch, RecordBatchIterator, StructArray, UInt32Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use crate::admission::AdmissionLimits;
+use crate::capabilities::IndexStatus;
+use crate::cast_plan::ColumnMatching;
+use crate::chunk::{self, Chunking};
+use crate::cold::ColdStorage;
+use crate::consistency::Consistency;
+use crate::constraints::{ConstraintViolated, Constraints, OnViolation};
+use crate::credentials::{self, Credentials};
+use crate::cursor::SearchCursor;
+use crate::disk_cache;
+use crate::fusion::Fusion;
+use crate::distance;
+use crate::handles;
+use crate::index_params::{
+    BuildLimits, HitBudget, IndexStaleness, MergeIndexing, ScalarIndexType, VectorIndexParams, VectorIndexType,
+    AUTO_REFINE,
+};
+use crate::label_bitmap::{self, BitmapOp};
+use crate::lance_manager::{DistanceRange, LanceIndex, RowStatus};
+use crate::maintenance::MaintenancePlan;
+use crate::metrics::{self, Op};
+use crate::pipeline::{self, Pipeline};
+use crate::projection::{self, ColumnLayout};
+use crate::query_log::QueryLog;
+use crate::quota::{Quota, QuotaExceeded};
+use crate::read_limit::{self, ReadLimits};
+use crate::rejects;
+use crate::replication::ManifestBlob;
+use crate::runtime;
+use crate::scratch;
+use crate::sort::OrderBy;
+use crate::storage_options;
+use crate::task::{self, TaskStatus};
+use crate::upsert::UpsertOptions;
+use crate::vector_storage::VectorStorage;
 
 pub type LanceHandlePtr = *mut c_void;
+pub type LanceCursorPtr = *mut c_void;
 
 // ========================================
 // Helpers
 // ========================================
 
+/// Export a RecordBatch as an Arrow C Data Interface struct array.
+/// The caller takes ownership of both structs and must release them.
+unsafe fn export_batch(
+    batch: RecordBatch,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+) -> anyhow::Result<()> {
+    if out_schema.is_null() || out_array.is_null() {
+        return Err(anyhow::anyhow!("null output arrow schema/array"));
+    }
+    let data = StructArray::from(batch).into_data();
+    let (array, schema) = arrow::ffi::to_ffi(&data)?;
+    std::ptr::write(out_array as *mut FFI_ArrowArray, array);
+    std::ptr::write(out_schema as *mut FFI_ArrowSchema, schema);
+    Ok(())
+}
+
+/// Export `batch` as an Arrow C stream of zero-copy slices of at most `batch_rows`
+/// rows. The caller takes ownership of the stream and must release it.
+unsafe fn export_stream(batch: RecordBatch, batch_rows: usize, out_stream: *mut c_void) {
+    let schema = batch.schema();
+    let slices: Vec<Result<RecordBatch, ArrowError>> = (0..batch.num_rows())
+        .step_by(batch_rows)
+        .map(|offset| Ok(batch.slice(offset, batch_rows.min(batch.num_rows() - offset))))
+        .collect();
+    let reader = RecordBatchIterator::new(slices, schema);
+    std::ptr::write(out_stream as *mut FFI_ArrowArrayStream, FFI_ArrowArrayStream::new(Box::new(reader)));
+}
+
+/// Export (old_label, new_label) `pairs` as an Arrow C stream into `out_stream`, in
+/// batches of up to `batch_rows` rows built as the consumer reads them.
+unsafe fn export_label_pairs(pairs: Vec<(i64, i64)>, batch_rows: usize, out_stream: *mut c_void) {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("old_label", DataType::Int64, false),
+        Field::new("new_label", DataType::Int64, false),
+    ]));
+    let batch_schema = schema.clone();
+    let batches = (0..pairs.len()).step_by(batch_rows).map(move |offset| {
+        let chunk = &pairs[offset..pairs.len().min(offset + batch_rows)];
+        RecordBatch::try_new(batch_schema.clone(), vec![
+            Arc::new(Int64Array::from_iter_values(chunk.iter().map(|(old, _)| *old))),
+            Arc::new(Int64Array::from_iter_values(chunk.iter().map(|(_, new)| *new))),
+        ])
+    });
+    let reader = RecordBatchIterator::new(batches, schema);
+    std::ptr::write(out_stream as *mut FFI_ArrowArrayStream, FFI_ArrowArrayStream::new(Box::new(reader)));
+}
+
+/// Rows per batch of the label mapping stream exported by `lance_detached_merge`.
+const MERGE_MAPPING_BATCH_ROWS: usize = 65536;
+
+/// Rows per batch of the stream exported by `lance_detached_scan`.
+const SCAN_BATCH_ROWS: usize = 8192;
+
+/// Rows per batch of the edge stream exported by `lance_detached_knn_graph`.
+const KNN_GRAPH_BATCH_ROWS: usize = 65536;
+
+/// Rows per batch of the address stream exported by `lance_detached_row_addresses`.
+const ROW_ADDRESS_BATCH_ROWS: usize = 65536;
+
+/// Rows per batch of the chunk stream exported by `lance_detached_add_chunked_arrow`.
+const CHUNK_MAPPING_BATCH_ROWS: usize = 65536;
+
+/// Split a comma-separated column list, ignoring blanks.
+fn split_columns(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Refine factor argument: negative values select auto refine.
+fn refine_factor_arg(refine_factor: i32) -> usize {
+    if refine_factor < 0 {
+        AUTO_REFINE
+    } else {
+        refine_factor_arg(refine_factor)
+    }
+}
+
 unsafe fn write_err(err_buf: *mut c_char, err_buf_len: i32, msg: &str) {
-    if err_buf.is_null() || err_buf_len <= 0 {
+    write_c_str(err_buf, err_buf_len, msg);
+}
+
+/// Return code for a failed append: -2 when a quota rejected it, -3 when a row
+/// broke a constraint, -1 otherwise.
+fn append_error_code(e: &anyhow::Error) -> i32 {
+    if e.downcast_ref::<QuotaExceeded>().is_some() {
+        -2
+    } else if e.downcast_ref::<ConstraintViolated>().is_some() {
+        -3
+    } else {
+        -1
+    }
+}
+
+/// Copy `s` into a caller-provided buffer, truncating and NUL-terminating.
+unsafe fn write_c_str(buf: *mut c_char, buf_len: i32, s: &str) {
+    if buf.is_null() || buf_len <= 0 {
         return;
     }
-    let max = (err_buf_len - 1) as usize;
-    let bytes = msg.as_bytes();
+    let max = (buf_len - 1) as usize;
+    let bytes = s.as_bytes();
     let copy_len = bytes.len().min(max);
-    std::ptr::copy_nonoverlapping(bytes.as_ptr(), err_buf as *mut u8, copy_len);
-    *err_buf.add(copy_len) = 0;
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf as *mut u8, copy_len);
+    *buf.add(copy_len) = 0;
 }
 
 unsafe fn c_str_to_string(ptr: *const c_char) -> String {
@@ -34,12 +174,15 @@ unsafe fn c_str_to_string(ptr: *const c_char) -> String {
 // Create / Open / Free
 // ========================================
 
+/// Create a vector-only Lance dataset. `storage` (null or empty for float32) is the
+/// element type vectors are stored with (see `crate::vector_storage`).
 #[no_mangle]
 pub unsafe extern "C" fn lance_create_detached(
     db_path: *const c_char,
     dimension: i32,
     metric: *const c_char,
     table_name: *const c_char,
+    storage: *const c_char,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> LanceHandlePtr {
@@ -47,7 +190,10 @@ pub unsafe extern "C" fn lance_create_detached(
     let metric_str = c_str_to_string(metric);
     let table_name_str = c_str_to_string(table_name);
 
-    match LanceIndex::create(&db_path_str, dimension as usize, &metric_str, &table_name_str) {
+    let result = VectorStorage::parse(&c_str_to_string(storage)).and_then(|storage| {
+        LanceIndex::create_with_storage(&db_path_str, dimension as usize, &metric_str, &table_name_str, storage)
+    });
+    match result {
         Ok(index) => Box::into_raw(Box::new(index)) as LanceHandlePtr,
         Err(e) => {
             write_err(err_buf, err_buf_len, &format!("create failed: {}", e));
@@ -88,6 +234,56 @@ pub unsafe extern "C" fn lance_create_detached_from_arrow(
     }
 }
 
+/// `lance_create_detached_from_arrow` with the comma-separated `vector_columns`
+/// (null or empty for just `vector`) declared as the FixedSizeList columns that
+/// hold vectors; the first must be `vector` (see `LanceIndex::create_from_arrow_with_vectors`).
+/// They are stored as `storage`, as for `lance_create_detached`.
+#[no_mangle]
+pub unsafe extern "C" fn lance_create_detached_from_arrow_with_vectors(
+    db_path: *const c_char,
+    arrow_schema: *mut c_void,
+    metric: *const c_char,
+    table_name: *const c_char,
+    vector_columns: *const c_char,
+    storage: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> LanceHandlePtr {
+    let db_path_str = c_str_to_string(db_path);
+    let metric_str = c_str_to_string(metric);
+    let table_name_str = c_str_to_string(table_name);
+    let vector_columns = split_columns(&c_str_to_string(vector_columns));
+    let storage = match VectorStorage::parse(&c_str_to_string(storage)) {
+        Ok(storage) => storage,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_from_arrow failed: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+
+    if arrow_schema.is_null() {
+        write_err(err_buf, err_buf_len, "null arrow schema");
+        return std::ptr::null_mut();
+    }
+
+    let schema_ptr = arrow_schema as *mut FFI_ArrowSchema;
+
+    match LanceIndex::create_from_arrow_with_vectors(
+        &db_path_str,
+        schema_ptr,
+        &metric_str,
+        &table_name_str,
+        &vector_columns,
+        storage,
+    ) {
+        Ok(index) => Box::into_raw(Box::new(index)) as LanceHandlePtr,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_from_arrow failed: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
 /// Open an existing Lance dataset, deriving schema from the table.
 #[no_mangle]
 pub unsafe extern "C" fn lance_open_detached(
@@ -110,6 +306,84 @@ pub unsafe extern "C" fn lance_open_detached(
     }
 }
 
+/// Open an existing Lance dataset through a handle restricted to rows matching
+/// `scope` (a Lance SQL predicate, e.g. `tenant_id = 42`). Every search, scan,
+/// count and delete on the handle is AND-ed with it.
+#[no_mangle]
+pub unsafe extern "C" fn lance_open_detached_scoped(
+    db_path: *const c_char,
+    table_name: *const c_char,
+    metric: *const c_char,
+    scope: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> LanceHandlePtr {
+    let db_path_str = c_str_to_string(db_path);
+    let table_name_str = c_str_to_string(table_name);
+    let metric_str = c_str_to_string(metric);
+    let scope_str = c_str_to_string(scope);
+
+    match LanceIndex::open_scoped(&db_path_str, &table_name_str, &metric_str, &scope_str) {
+        Ok(index) => Box::into_raw(Box::new(index)) as LanceHandlePtr,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("open failed: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Export what a table offers to the index layer (see `LanceIndex::inspect`) as
+/// rows of (kind, name, data_type, nullable, dimension, detail). Tables with
+/// `problem` rows fail `lance_open_detached`. Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_inspect_table(
+    db_path: *const c_char,
+    table_name: *const c_char,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    let db_path_str = c_str_to_string(db_path);
+    let table_name_str = c_str_to_string(table_name);
+
+    let result = LanceIndex::inspect(&db_path_str, &table_name_str).and_then(|capabilities| {
+        let batch = capabilities.to_record_batch()?;
+        let rows = batch.num_rows() as i64;
+        export_batch(batch, out_schema, out_array).map(|_| rows)
+    });
+    match result {
+        Ok(rows) => rows,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("inspect failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Rename a table (and its access sidecar) in a local database. Handles open on
+/// the table must be freed first. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_rename_table(
+    db_path: *const c_char,
+    old_name: *const c_char,
+    new_name: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let db_path_str = c_str_to_string(db_path);
+    let old_name_str = c_str_to_string(old_name);
+    let new_name_str = c_str_to_string(new_name);
+
+    match LanceIndex::rename_table(&db_path_str, &old_name_str, &new_name_str) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("rename_table failed: {}", e));
+            -1
+        }
+    }
+}
+
 /// Check if the index has extra columns beyond label + vector.
 #[no_mangle]
 pub unsafe extern "C" fn lance_detached_has_extra_columns(
@@ -134,6 +408,19 @@ pub unsafe extern "C" fn lance_detached_dimension(
     h.dimension() as i32
 }
 
+/// Whether a query of `dim` dimensions can be searched (after the query transform).
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_accepts_query_dim(
+    handle: LanceHandlePtr,
+    dim: i32,
+) -> i32 {
+    if handle.is_null() || dim < 0 {
+        return 0;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    h.accepts_query_dim(dim as usize) as i32
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn lance_free_detached(handle: LanceHandlePtr) {
     if !handle.is_null() {
@@ -141,15 +428,52 @@ pub unsafe extern "C" fn lance_free_detached(handle: LanceHandlePtr) {
     }
 }
 
+/// List the handles open in this process (see `crate::handles`), exported as rows of
+/// (handle_id, path, table_name, read_only, buffered_rows, opened_ms, last_operation_ms).
+/// Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_list_handles(
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let result = handles::to_record_batch(&handles::list()).and_then(|batch| {
+        let rows = batch.num_rows();
+        export_batch(batch, out_schema, out_array).map(|_| rows)
+    });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("list_handles failed: {}", e));
+            -1
+        }
+    }
+}
+
 /// Add a batch of rows via Arrow C Data Interface.
 /// `arrow_schema` and `arrow_array` are pointers to ArrowSchema/ArrowArray structs.
-/// Fills `out_labels` with assigned labels. Returns count or -1 on error.
+/// Fills `out_labels` with assigned labels, -1 for rows dropped by validation (see
+/// `lance_detached_set_on_invalid`) or by the table's constraints, and the optional
+/// `out_rejected` with the number of dropped rows. Returns count, -2 if a quota
+/// rejected the rows, -3 if a row broke a constraint with `on_violation=error`, or
+/// -1 on other errors.
+/// A non-null `out_status` selects partial-failure semantics (see
+/// `LanceIndex::add_batch_arrow_rows`): rows failing validation or a constraint never
+/// fail the batch, and `out_status` receives a code per row, 0 (added), 1 (added by
+/// an earlier call with the same token) or 2 (invalid, label -1).
+/// A non-null `token` makes the call idempotent: a retry with the same token adds
+/// nothing and returns the labels of the first call (see `crate::idempotency`).
 #[no_mangle]
 pub unsafe extern "C" fn lance_detached_add_batch_arrow(
     handle: LanceHandlePtr,
     arrow_schema: *mut c_void,
     arrow_array: *mut c_void,
+    model: *const c_char,
+    token: *const c_char,
     out_labels: *mut i64,
+    out_rejected: *mut i64,
+    out_status: *mut u8,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -162,18 +486,189 @@ pub unsafe extern "C" fn lance_detached_add_batch_arrow(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("add_batch_arrow failed: {}", e));
+        return -1;
+    }
     let schema_ptr = arrow_schema as *mut FFI_ArrowSchema;
     let array_ptr = arrow_array as *mut FFI_ArrowArray;
+    let token = (!token.is_null()).then(|| c_str_to_string(token));
 
-    match h.add_batch_arrow(schema_ptr, array_ptr) {
-        Ok(labels) => {
-            for (i, label) in labels.iter().enumerate() {
+    let result = metrics::observe(Op::Add, || {
+        if out_status.is_null() {
+            h.add_batch_arrow_with_token(schema_ptr, array_ptr, token.as_deref())
+                .map(|labels| labels.into_iter().map(|l| (l, RowStatus::Ok)).collect())
+        } else {
+            h.add_batch_arrow_rows(schema_ptr, array_ptr, token.as_deref())
+        }
+    });
+    match result {
+        Ok(rows) => {
+            metrics::add_rows(Op::Add, rows.len() as u64);
+            for (i, (label, status)) in rows.iter().enumerate() {
                 *out_labels.add(i) = *label;
+                if !out_status.is_null() {
+                    *out_status.add(i) = status.code();
+                }
             }
-            labels.len() as i32
+            if !out_rejected.is_null() {
+                *out_rejected = rows.iter().filter(|(l, _)| *l < 0).count() as i64;
+            }
+            rows.len() as i32
         }
         Err(e) => {
             write_err(err_buf, err_buf_len, &format!("add_batch_arrow failed: {}", e));
+            append_error_code(&e)
+        }
+    }
+}
+
+/// Insert or replace rows from an Arrow C batch (see `LanceIndex::upsert_batch_arrow`),
+/// matched on `label` when `key` is null or on the column `key`. `options`, if not
+/// null, sets the conflict policy in text form, e.g.
+/// `on_conflict=keep_newest(updated_at); duplicates=collapse` (see `upsert`). Fills
+/// `out_labels` (one per row) with each row's label, -1 for rows dropped by
+/// validation, and the optional `out_inserted`, `out_updated`, `out_skipped` and
+/// `out_collapsed` with the row counts. Returns the row count, -2 if a quota rejected
+/// the rows, -3 if a row broke a constraint with `on_violation=error`, or -1 on other
+/// errors.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_upsert_arrow(
+    handle: LanceHandlePtr,
+    arrow_schema: *mut c_void,
+    arrow_array: *mut c_void,
+    key: *const c_char,
+    options: *const c_char,
+    out_labels: *mut i64,
+    out_inserted: *mut i64,
+    out_updated: *mut i64,
+    out_skipped: *mut i64,
+    out_collapsed: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if arrow_schema.is_null() || arrow_array.is_null() {
+        write_err(err_buf, err_buf_len, "null arrow schema/array");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let key = (!key.is_null()).then(|| c_str_to_string(key));
+    let options = if options.is_null() { String::new() } else { c_str_to_string(options) };
+    let result = metrics::observe(Op::Add, || {
+        h.upsert_batch_arrow(
+            arrow_schema as *mut FFI_ArrowSchema,
+            arrow_array as *mut FFI_ArrowArray,
+            key.as_deref(),
+            &UpsertOptions::parse(&options)?,
+        )
+    });
+    match result {
+        Ok(report) => {
+            metrics::add_rows(Op::Add, report.labels.len() as u64);
+            for (i, label) in report.labels.iter().enumerate() {
+                *out_labels.add(i) = *label;
+            }
+            if !out_inserted.is_null() {
+                *out_inserted = report.inserted as i64;
+            }
+            if !out_updated.is_null() {
+                *out_updated = report.updated as i64;
+            }
+            if !out_skipped.is_null() {
+                *out_skipped = report.skipped as i64;
+            }
+            if !out_collapsed.is_null() {
+                *out_collapsed = report.collapsed as i64;
+            }
+            report.labels.len() as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("upsert_arrow failed: {}", e));
+            append_error_code(&e)
+        }
+    }
+}
+
+/// Delete every row whose key is not in the Arrow C batch of one key column (see
+/// `LanceIndex::retain_only`), in one table version. An empty batch deletes every
+/// row only with a non-zero `allow_empty`. Returns the number of rows deleted or -1
+/// on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_retain_only(
+    handle: LanceHandlePtr,
+    arrow_schema: *mut c_void,
+    arrow_array: *mut c_void,
+    allow_empty: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if arrow_schema.is_null() || arrow_array.is_null() {
+        write_err(err_buf, err_buf_len, "null arrow schema/array");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = metrics::observe(Op::Delete, || {
+        h.retain_only(arrow_schema as *mut FFI_ArrowSchema, arrow_array as *mut FFI_ArrowArray, allow_empty != 0)
+    });
+    match result {
+        Ok(deleted) => {
+            metrics::add_rows(Op::Delete, deleted as u64);
+            deleted as i64
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("retain_only failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Set columns of the rows matching `predicate` (see `LanceIndex::update_where`):
+/// column `columns[i]` to the SQL expression `exprs[i]`, for `count` assignments.
+/// Non-zero `privileged` lets the predicate and expressions read sensitive columns.
+/// Returns the number of rows updated or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_update_where(
+    handle: LanceHandlePtr,
+    predicate: *const c_char,
+    columns: *const *const c_char,
+    exprs: *const *const c_char,
+    count: i32,
+    privileged: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() || predicate.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or predicate");
+        return -1;
+    }
+    if count > 0 && (columns.is_null() || exprs.is_null()) {
+        write_err(err_buf, err_buf_len, "null columns or expressions");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let (columns, exprs): (Vec<String>, Vec<String>) = if count <= 0 {
+        (Vec::new(), Vec::new())
+    } else {
+        (
+            slice::from_raw_parts(columns, count as usize).iter().map(|c| c_str_to_string(*c)).collect(),
+            slice::from_raw_parts(exprs, count as usize).iter().map(|e| c_str_to_string(*e)).collect(),
+        )
+    };
+    let assignments: Vec<(&str, &str)> =
+        columns.iter().map(String::as_str).zip(exprs.iter().map(String::as_str)).collect();
+    match h.update_where(&c_str_to_string(predicate), &assignments, privileged != 0) {
+        Ok(updated) => updated as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("update_where failed: {}", e));
             -1
         }
     }
@@ -181,19 +676,34 @@ pub unsafe extern "C" fn lance_detached_add_batch_arrow(
 
 /// Merge live rows from source index into target index (all in Rust).
 /// `live_source_labels` are the labels in source that are not tombstoned.
-/// Fills `out_old_labels` and `out_new_labels` with the mapping.
-/// Returns count of merged rows or -1 on error.
+/// Exports the label mapping as an Arrow C stream of (old_label, new_label) batches
+/// into `out_stream`, which the caller must release.
+/// Columns are reconciled by name; a non-zero `strict` rejects any schema difference.
+/// The vector index coverage after the merge (see `lance_detached_index_staleness`)
+/// goes to the optional `out_indexed_rows`, `out_unindexed_rows` and
+/// `out_retrain_recommended`.
+/// A non-null `token` makes the call idempotent: a retry with the same token merges
+/// nothing and exports the mapping of the first call.
+/// Returns count of merged rows, -2 if a quota rejected them, or -1 on error.
 #[no_mangle]
 pub unsafe extern "C" fn lance_detached_merge(
     target_handle: LanceHandlePtr,
     source_handle: LanceHandlePtr,
     live_source_labels: *const i64,
     live_count: i32,
-    out_old_labels: *mut i64,
-    out_new_labels: *mut i64,
+    strict: i32,
+    token: *const c_char,
+    out_stream: *mut c_void,
+    out_indexed_rows: *mut i64,
+    out_unindexed_rows: *mut i64,
+    out_retrain_recommended: *mut i32,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
+    if out_stream.is_null() {
+        write_err(err_buf, err_buf_len, "null output arrow stream");
+        return -1;
+    }
     if target_handle.is_null() || source_handle.is_null() {
         write_err(err_buf, err_buf_len, "null handle");
         return -1;
@@ -205,57 +715,78 @@ pub unsafe extern "C" fn lance_detached_merge(
     } else {
         &[]
     };
+    let token = (!token.is_null()).then(|| c_str_to_string(token));
 
-    match target.merge_from(source, live_labels) {
-        Ok(mapping) => {
-            for (i, (old_label, new_label)) in mapping.iter().enumerate() {
-                *out_old_labels.add(i) = *old_label;
-                *out_new_labels.add(i) = *new_label;
-            }
-            mapping.len() as i32
+    match metrics::observe(Op::Merge, || {
+        target.merge_from_with_token(source, live_labels, strict != 0, token.as_deref())
+    }) {
+        Ok(report) => {
+            let n = report.mapping.len();
+            metrics::add_rows(Op::Merge, n as u64);
+            write_staleness(report.staleness, out_indexed_rows, out_unindexed_rows, out_retrain_recommended);
+            export_label_pairs(report.mapping, MERGE_MAPPING_BATCH_ROWS, out_stream);
+            n as i32
         }
         Err(e) => {
             write_err(err_buf, err_buf_len, &format!("merge failed: {}", e));
-            -1
+            append_error_code(&e)
         }
     }
 }
 
-// ========================================
-// Add vectors
-// ========================================
+/// Write index coverage to whichever out-pointers are non-null: row counts are -1
+/// without a vector index.
+unsafe fn write_staleness(
+    staleness: Option<IndexStaleness>,
+    out_indexed_rows: *mut i64,
+    out_unindexed_rows: *mut i64,
+    out_retrain_recommended: *mut i32,
+) {
+    let (indexed, unindexed) = staleness.map_or((-1, -1), |s| (s.indexed_rows as i64, s.unindexed_rows as i64));
+    if !out_indexed_rows.is_null() {
+        *out_indexed_rows = indexed;
+    }
+    if !out_unindexed_rows.is_null() {
+        *out_unindexed_rows = unindexed;
+    }
+    if !out_retrain_recommended.is_null() {
+        *out_retrain_recommended = staleness.is_some_and(|s| s.retrain_recommended()) as i32;
+    }
+}
 
+/// What later merges into this handle do about the rows they append unindexed:
+/// 0 leaves them for the next optimize, 1 adds them to the index before the merge
+/// returns, 2 adds them in the background. Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_add(
+pub unsafe extern "C" fn lance_detached_set_merge_indexing(
     handle: LanceHandlePtr,
-    vector: *const f32,
-    dimension: i32,
+    mode: i32,
     err_buf: *mut c_char,
     err_buf_len: i32,
-) -> i64 {
+) -> i32 {
     if handle.is_null() {
         write_err(err_buf, err_buf_len, "null handle");
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    let vec_slice = slice::from_raw_parts(vector, dimension as usize);
-
-    match h.add_vector(vec_slice) {
-        Ok(label) => label,
+    match MergeIndexing::from_ffi(mode).and_then(|mode| h.set_merge_indexing(mode)) {
+        Ok(()) => 0,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("add failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("set_merge_indexing failed: {}", e));
             -1
         }
     }
 }
 
+/// Vector index coverage: indexed and unindexed (brute-force searched) row counts,
+/// both -1 without a vector index, to the optional out-pointers. Returns 1 when the
+/// table outgrew the index enough that a rebuild is recommended, 0 otherwise, or
+/// -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_add_batch(
+pub unsafe extern "C" fn lance_detached_index_staleness(
     handle: LanceHandlePtr,
-    vectors: *const f32,
-    num: i32,
-    dim: i32,
-    out_labels: *mut i64,
+    out_indexed_rows: *mut i64,
+    out_unindexed_rows: *mut i64,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -264,44 +795,24 @@ pub unsafe extern "C" fn lance_detached_add_batch(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    debug_assert_eq!(
-        dim as usize,
-        h.dimension(),
-        "C++ passed dim={} but LanceIndex has dimension={}",
-        dim,
-        h.dimension()
-    );
-    let total_floats = num as usize * h.dimension();
-    let vec_slice = slice::from_raw_parts(vectors, total_floats);
-
-    match h.add_batch(vec_slice, num as usize) {
-        Ok(labels) => {
-            for (i, label) in labels.iter().enumerate() {
-                *out_labels.add(i) = *label;
-            }
-            labels.len() as i32
+    match h.index_staleness() {
+        Ok(staleness) => {
+            write_staleness(staleness, out_indexed_rows, out_unindexed_rows, std::ptr::null_mut());
+            staleness.is_some_and(|s| s.retrain_recommended()) as i32
         }
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("add_batch failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("index_staleness failed: {}", e));
             -1
         }
     }
 }
 
-// ========================================
-// Search
-// ========================================
-
+/// Rebuild the table's recorded label watermark from a scan and write the max label
+/// (-1 when none was ever assigned) to `out_max_label`. Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_search(
+pub unsafe extern "C" fn lance_detached_repair_label_watermark(
     handle: LanceHandlePtr,
-    query: *const f32,
-    dim: i32,
-    k: i32,
-    nprobes: i32,
-    refine_factor: i32,
-    out_labels: *mut i64,
-    out_distances: *mut f32,
+    out_max_label: *mut i64,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -310,52 +821,4147 @@ pub unsafe extern "C" fn lance_detached_search(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    let query_slice = slice::from_raw_parts(query, dim as usize);
-
-    match h.search(query_slice, k as usize, nprobes as usize, refine_factor as usize) {
-        Ok(results) => {
+    match h.repair_label_watermark() {
+        Ok(max_label) => {
+            if !out_max_label.is_null() {
+                *out_max_label = max_label;
+            }
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("repair_label_watermark failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Add vectors
+// ========================================
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_add(
+    handle: LanceHandlePtr,
+    vector: *const f32,
+    dimension: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let vec_slice = slice::from_raw_parts(vector, dimension as usize);
+
+    match metrics::observe(Op::Add, || h.add_vector(vec_slice)) {
+        Ok(label) => {
+            metrics::add_rows(Op::Add, 1);
+            label
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("add failed: {}", e));
+            append_error_code(&e) as i64
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_add_batch(
+    handle: LanceHandlePtr,
+    vectors: *const f32,
+    num: i32,
+    dim: i32,
+    out_labels: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    debug_assert_eq!(
+        dim as usize,
+        h.dimension(),
+        "C++ passed dim={} but LanceIndex has dimension={}",
+        dim,
+        h.dimension()
+    );
+    let total_floats = num as usize * h.dimension();
+    let vec_slice = slice::from_raw_parts(vectors, total_floats);
+
+    match metrics::observe(Op::Add, || h.add_batch(vec_slice, num as usize)) {
+        Ok(labels) => {
+            metrics::add_rows(Op::Add, labels.len() as u64);
+            for (i, label) in labels.iter().enumerate() {
+                *out_labels.add(i) = *label;
+            }
+            labels.len() as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("add_batch failed: {}", e));
+            append_error_code(&e)
+        }
+    }
+}
+
+/// Called once when Rust no longer needs a buffer handed over by the caller.
+pub type LanceReleaseFn = unsafe extern "C" fn(release_ctx: *mut c_void);
+
+/// Releases a caller-owned buffer when the last Arrow array over it is dropped.
+struct CallerBuffer {
+    release: LanceReleaseFn,
+    // Raw pointers are not Send; the caller guarantees release may run on any thread.
+    release_ctx: usize,
+}
+
+impl Drop for CallerBuffer {
+    fn drop(&mut self) {
+        unsafe { (self.release)(self.release_ctx as *mut c_void) }
+    }
+}
+
+/// As `lance_detached_add_batch`, but the vectors are appended without a copy: Rust
+/// takes ownership of the `num * dim` floats at `vectors` (4-byte aligned) and calls
+/// `release(release_ctx)` exactly once when done with them — on any thread, possibly
+/// after this returns, and also when the call fails (but not if `release` is null).
+/// Returns count or -1/-2 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_add_batch_owned(
+    handle: LanceHandlePtr,
+    vectors: *const f32,
+    num: i32,
+    dim: i32,
+    release: Option<LanceReleaseFn>,
+    release_ctx: *mut c_void,
+    out_labels: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let Some(release) = release else {
+        write_err(err_buf, err_buf_len, "null release callback");
+        return -1;
+    };
+    let owner = Arc::new(CallerBuffer {
+        release,
+        release_ctx: release_ctx as usize,
+    });
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    if dim as usize != h.dimension() {
+        write_err(
+            err_buf,
+            err_buf_len,
+            &format!("dimension mismatch: expected {}, got {}", h.dimension(), dim),
+        );
+        return -1;
+    }
+    if num < 0 {
+        write_err(err_buf, err_buf_len, "negative vector count");
+        return -1;
+    }
+    let Some(ptr) = std::ptr::NonNull::new(vectors as *mut u8) else {
+        write_err(err_buf, err_buf_len, "null vectors");
+        return -1;
+    };
+    if vectors.align_offset(std::mem::align_of::<f32>()) != 0 {
+        write_err(err_buf, err_buf_len, "vectors are not 4-byte aligned");
+        return -1;
+    }
+    let total_floats = num as usize * h.dimension();
+    let buffer = Buffer::from_custom_allocation(ptr, total_floats * std::mem::size_of::<f32>(), owner);
+    let values = ScalarBuffer::new(buffer, 0, total_floats);
+
+    match metrics::observe(Op::Add, || h.add_batch_buffer(values, num as usize)) {
+        Ok(labels) => {
+            metrics::add_rows(Op::Add, labels.len() as u64);
+            std::ptr::copy_nonoverlapping(labels.as_ptr(), out_labels, labels.len());
+            labels.len() as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("add_batch failed: {}", e));
+            append_error_code(&e)
+        }
+    }
+}
+
+/// Buffer appends on this handle until `lance_detached_end_append_session`, writing
+/// consecutive small appends as one. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_begin_append_session(
+    handle: LanceHandlePtr,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.begin_append_session() {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("begin_append_session failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Write the appends buffered by the session and close it. Returns 0, or -1 on
+/// error (-2 when a quota rejected the buffered rows).
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_end_append_session(
+    handle: LanceHandlePtr,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::Add, || h.end_append_session()) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("end_append_session failed: {}", e));
+            append_error_code(&e)
+        }
+    }
+}
+
+// ========================================
+// Search
+// ========================================
+
+/// `refine_factor` throughout this section: 0 keeps the index's quantized
+/// distances, a positive value rescores `k * refine_factor` candidates, and a
+/// negative value picks the factor from the index's compression ratio and `k`
+/// (see `lance_detached_refine_plan`).
+///
+/// `ef` > 0 sets how many candidates an HNSW index explores (at least `k`); 0 keeps
+/// Lance's default. Weighted searches ignore it.
+///
+/// `privileged` != 0 lets `predicate` read sensitive columns, and the dedup and
+/// parent searches group by one; otherwise either fails the search.
+///
+/// `weights`, if not null, holds `weights_len` per-dimension weights (one per
+/// stored dimension) that rescore the hits with the weighted metric;
+/// `weight_query` != 0 also weighs the query before the ANN search.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    ef: i32,
+    predicate: *const c_char,
+    model: *const c_char,
+    weights: *const f32,
+    weights_len: i32,
+    weight_query: i32,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+        return -1;
+    }
+    let weights = (!weights.is_null()).then(|| slice::from_raw_parts(weights, weights_len.max(0) as usize));
+
+    match metrics::observe(Op::Search, || match weights {
+        Some(weights) => h.search_weighted(
+            query_slice,
+            weights,
+            weight_query != 0,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        ),
+        None => h.search(
+            query_slice,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            ef.max(0) as usize,
+            predicate.as_deref(),
+            privileged != 0,
+        ),
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            scratch::recycle(results);
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// `lance_detached_search` that also reports the diversity of the hits (see
+/// `LanceIndex::search_with_diversity`): `out_mean_pairwise` gets their mean pairwise
+/// distance (NaN with fewer than two hits), and with `column` (null for none)
+/// `out_distinct` gets its count of distinct non-NULL values among them (-1 without
+/// a column). Sensitive columns need a non-zero `privileged`. Returns the hit count
+/// or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_diversity(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    ef: i32,
+    predicate: *const c_char,
+    model: *const c_char,
+    column: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    out_mean_pairwise: *mut f64,
+    out_distinct: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    let column = (!column.is_null()).then(|| c_str_to_string(column));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search_diversity failed: {}", e));
+        return -1;
+    }
+
+    match metrics::observe(Op::Search, || {
+        h.search_with_diversity(
+            query_slice,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            ef.max(0) as usize,
+            predicate.as_deref(),
+            column.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok((results, diversity)) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            *out_mean_pairwise = diversity.mean_pairwise_distance.unwrap_or(f64::NAN);
+            *out_distinct = diversity.distinct_values.map_or(-1, |n| n as i64);
+            scratch::recycle(results);
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_diversity failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Run `num_queries` searches over the row-major `queries` (`num_queries * dim`
+/// values) with up to `concurrency` in flight (0 for the default; see
+/// `LanceIndex::search_batch`). The output buffers hold `num_queries * k` entries;
+/// hits are written grouped by query, nearest first, with the query's index in
+/// `out_query_idx`. Returns the total hit count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_batch(
+    handle: LanceHandlePtr,
+    queries: *const f32,
+    dim: i32,
+    num_queries: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    concurrency: i32,
+    privileged: i32,
+    out_query_idx: *mut i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || (queries.is_null() && num_queries > 0) {
+        write_err(err_buf, err_buf_len, "null handle or queries");
+        return -1;
+    }
+    if dim <= 0 || num_queries < 0 {
+        write_err(err_buf, err_buf_len, "search batch failed: invalid dimension or query count");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice: &[f32] = if num_queries == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(queries, dim as usize * num_queries as usize)
+    };
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+
+    match metrics::observe(Op::Search, || {
+        h.search_batch(
+            query_slice,
+            num_queries as usize,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            concurrency.max(0) as usize,
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (query_idx, label, dist)) in results.into_iter().enumerate() {
+                *out_query_idx.add(i) = query_idx as i32;
+                *out_labels.add(i) = label;
+                *out_distances.add(i) = dist;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search batch failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// `lance_detached_search` at the read `consistency` `latest`, `session` (also for
+/// null) or `eventual` (see `crate::consistency`). Returns the hit count or -1 on
+/// error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_consistent(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    model: *const c_char,
+    consistency: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || query.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or query");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    let consistency = match c_str_to_string(consistency).parse::<Consistency>() {
+        Ok(consistency) => consistency,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            return -1;
+        }
+    };
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+        return -1;
+    }
+
+    match metrics::observe(Op::Search, || {
+        h.search_with_consistency(
+            query_slice,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            consistency,
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            scratch::recycle(results);
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Search for at most `k` neighbors with `lower_bound <= distance < upper_bound`
+/// (see `LanceIndex::search_range`); a NaN bound leaves that side open, but one
+/// bound is required. Fills `out_labels`/`out_distances` nearest first and returns
+/// the hit count (fewer than `k` means every neighbor in range was found) or -1 on
+/// error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_range(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    lower_bound: f32,
+    upper_bound: f32,
+    predicate: *const c_char,
+    model: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || query.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or query");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search_range failed: {}", e));
+        return -1;
+    }
+    let range = DistanceRange {
+        lower: (!lower_bound.is_nan()).then_some(lower_bound),
+        upper: (!upper_bound.is_nan()).then_some(upper_bound),
+    };
+
+    match metrics::observe(Op::Search, || {
+        h.search_range(
+            query_slice,
+            range,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            scratch::recycle(results);
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_range failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Rows per batch Lance produces for search results on this handle; 0 restores
+/// Lance's default. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_read_batch_size(
+    handle: LanceHandlePtr,
+    rows: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if rows < 0 {
+        write_err(err_buf, err_buf_len, "read batch size must not be negative");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    h.set_read_batch_size(rows as usize);
+    0
+}
+
+/// Report the refine factor a search of `k` rows runs with for `refine_factor`
+/// (negative = auto) and how many candidates it rescores with exact distances.
+/// `out_compression` receives the vector index's compression ratio, or 0 when no
+/// index was built by this extension. Returns 1 when the factor was chosen by auto
+/// refine, 0 otherwise, or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_refine_plan(
+    handle: LanceHandlePtr,
+    refine_factor: i32,
+    k: i32,
+    out_factor: *mut i64,
+    out_rescored: *mut i64,
+    out_compression: *mut f64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if k <= 0 {
+        write_err(err_buf, err_buf_len, "k must be positive");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let plan = h.refine_plan(refine_factor_arg(refine_factor), k as usize);
+    if !out_factor.is_null() {
+        *out_factor = plan.factor as i64;
+    }
+    if !out_rescored.is_null() {
+        *out_rescored = plan.rescored as i64;
+    }
+    if !out_compression.is_null() {
+        *out_compression = plan.compression.unwrap_or(0.0);
+    }
+    plan.auto as i32
+}
+
+/// Set the budget filtered searches spend to return k hits when more rows match:
+/// nprobes escalation cap, largest matching row count searched exactly, and largest
+/// over-fetch multiple for host-side filtering (0 disables each). Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_hit_budget(
+    handle: LanceHandlePtr,
+    max_nprobes: i32,
+    max_exact_rows: i64,
+    max_overfetch: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if max_nprobes < 0 || max_exact_rows < 0 || max_overfetch < 0 {
+        write_err(err_buf, err_buf_len, "hit budget must not be negative");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let budget = HitBudget {
+        max_nprobes: max_nprobes as usize,
+        max_exact_rows: max_exact_rows as u64,
+        max_overfetch: max_overfetch as usize,
+    };
+    match h.set_hit_budget(budget) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_hit_budget failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Read the handle's hit budget (see `lance_detached_set_hit_budget`). Returns 0 or
+/// -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_hit_budget(
+    handle: LanceHandlePtr,
+    out_max_nprobes: *mut i32,
+    out_max_exact_rows: *mut i64,
+    out_max_overfetch: *mut i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let budget = h.hit_budget();
+    if !out_max_nprobes.is_null() {
+        *out_max_nprobes = budget.max_nprobes.min(i32::MAX as usize) as i32;
+    }
+    if !out_max_exact_rows.is_null() {
+        *out_max_exact_rows = budget.max_exact_rows.min(i64::MAX as u64) as i64;
+    }
+    if !out_max_overfetch.is_null() {
+        *out_max_overfetch = budget.max_overfetch.min(i32::MAX as usize) as i32;
+    }
+    0
+}
+
+/// Search with the query moved away from `negative_count` negative vectors
+/// (flattened at `negatives`, `dim` values each) by `weight`; otherwise as
+/// `lance_detached_search`. Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_with_negatives(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    negatives: *const f32,
+    negative_count: i32,
+    weight: f32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    model: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let negatives = if negatives.is_null() || negative_count <= 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(negatives, negative_count as usize * dim as usize)
+    };
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+        return -1;
+    }
+
+    match metrics::observe(Op::Search, || {
+        h.search_with_negatives(
+            query_slice,
+            negatives,
+            weight,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Search keeping only the best hit per distinct value of `dedup_column` (see
+/// `LanceIndex::search_dedup`); otherwise as `lance_detached_search`. Returns the
+/// number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_dedup(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    dedup_column: *const c_char,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    model: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if dedup_column.is_null() {
+        write_err(err_buf, err_buf_len, "null dedup column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let dedup_column = c_str_to_string(dedup_column);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+        return -1;
+    }
+
+    match metrics::observe(Op::Search, || {
+        h.search_dedup(
+            query_slice,
+            k as usize,
+            &dedup_column,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Hybrid vector + full-text search (see `LanceIndex::hybrid_search`). `fusion` is
+/// 0 (reciprocal rank fusion with constant `rrf_k`, non-positive for the default),
+/// 1 (linear combination of normalized scores, `vector_weight` of the vector side)
+/// or 2 (max normalized score). A null `text_column` searches every full-text
+/// indexed column. Writes labels and fused scores, highest first. Returns the
+/// number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_hybrid_search(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    text: *const c_char,
+    text_column: *const c_char,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    fusion: i32,
+    rrf_k: f32,
+    vector_weight: f32,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_scores: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || text.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or text");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let text = c_str_to_string(text);
+    let text_column = (!text_column.is_null()).then(|| c_str_to_string(text_column));
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+
+    match Fusion::from_ffi(fusion, rrf_k, vector_weight).and_then(|fusion| {
+        metrics::observe(Op::Search, || {
+            h.hybrid_search(
+                query_slice,
+                &text,
+                text_column.as_deref(),
+                k as usize,
+                nprobes as usize,
+                refine_factor_arg(refine_factor),
+                predicate.as_deref(),
+                fusion,
+                privileged != 0,
+            )
+        })
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, score)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_scores.add(i) = *score;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("hybrid_search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Build a full-text index on a string column (see `LanceIndex::create_fts_index`).
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_fts_index(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.create_fts_index(&c_str_to_string(column)) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_fts_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Build a scalar index on a metadata column (see `LanceIndex::create_scalar_index`);
+/// `index_type` is 0 for BTree, 1 for Bitmap, 2 for LabelList. Returns 0 or -1 on
+/// error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_scalar_index(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    index_type: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = ScalarIndexType::from_i32(index_type)
+        .and_then(|index_type| h.create_scalar_index(&c_str_to_string(column), index_type));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_scalar_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Parent-document search (see `LanceIndex::search_parents`). A null `parent_column`
+/// uses the parent column of the chunking stage. Writes each parent with its best
+/// chunk's label and distance. Returns the number of parents or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_parents(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    parent_column: *const c_char,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    model: *const c_char,
+    privileged: i32,
+    out_parents: *mut i64,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let parent_column = (!parent_column.is_null()).then(|| c_str_to_string(parent_column));
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let model = (!model.is_null()).then(|| c_str_to_string(model));
+    if let Err(e) = h.check_embedding_model(model.as_deref()) {
+        write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+        return -1;
+    }
+
+    match metrics::observe(Op::Search, || {
+        h.search_parents(
+            query_slice,
+            k as usize,
+            parent_column.as_deref(),
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, hit) in results.iter().enumerate() {
+                *out_parents.add(i) = hit.parent;
+                *out_labels.add(i) = hit.label;
+                *out_distances.add(i) = hit.distance;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Approximate search probing a `fraction` of the `nprobes` partitions (see
+/// `LanceIndex::search_sampled`). Also writes each result's estimated rank in the full
+/// table to `out_estimated_ranks` and the fraction actually probed to `out_fraction`.
+/// Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_sampled(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    fraction: f64,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    out_estimated_ranks: *mut f64,
+    out_fraction: *mut f64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+
+    match metrics::observe(Op::Search, || {
+        h.search_sampled(
+            query_slice,
+            k as usize,
+            fraction,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok(sampled) => {
+            let n = sampled.results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in sampled.results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+                *out_estimated_ranks.add(i) = sampled.estimated_rank(i);
+            }
+            if !out_fraction.is_null() {
+                *out_fraction = sampled.fraction;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_sampled failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Multi-hop search (see `LanceIndex::search_expand`). Writes up to
+/// `k * (hops + 1)` results to the output arrays, ranked by score.
+/// Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_expand(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    hops: i32,
+    per_hop: i32,
+    decay: f64,
+    nprobes: i32,
+    refine_factor: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    out_hops: *mut i32,
+    out_scores: *mut f64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+
+    match metrics::observe(Op::Search, || {
+        h.search_expand(
+            query_slice,
+            k.max(0) as usize,
+            hops.max(0) as usize,
+            per_hop.max(0) as usize,
+            decay,
+            nprobes.max(0) as usize,
+            refine_factor_arg(refine_factor),
+            None,
+            false,
+        )
+    }) {
+        Ok(hits) => {
+            let n = hits.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, hit) in hits.iter().enumerate() {
+                *out_labels.add(i) = hit.label;
+                *out_distances.add(i) = hit.distance;
+                *out_hops.add(i) = hit.hop as i32;
+                *out_scores.add(i) = hit.score;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_expand failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Search with the centroid of the stored vectors of `label_count` labels, moved away
+/// from the centroid of `negative_count` negative labels by `weight`. The labels
+/// themselves are excluded from the results. Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_like_labels(
+    handle: LanceHandlePtr,
+    labels: *const i64,
+    label_count: i32,
+    negative_labels: *const i64,
+    negative_count: i32,
+    weight: f32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let labels = if labels.is_null() || label_count <= 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(labels, label_count as usize)
+    };
+    let negative_labels = if negative_labels.is_null() || negative_count <= 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(negative_labels, negative_count as usize)
+    };
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+
+    match metrics::observe(Op::Search, || {
+        h.search_like_labels(
+            labels,
+            negative_labels,
+            weight,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_like_labels failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Rank the `label_count` labels at `labels` by exact distance to `query` and write
+/// the k nearest to `out_labels`/`out_distances` (capacity k). Returns the number of
+/// results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_within(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    labels: *const i64,
+    label_count: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let labels = if labels.is_null() || label_count <= 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(labels, label_count as usize)
+    };
+
+    match metrics::observe(Op::Search, || h.search_within(query_slice, k.max(0) as usize, labels)) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_within failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Read the rows among `label_count` labels (all rows when 0) matching `predicate`
+/// (null or empty for none) so their fragments are cached locally before heavy
+/// queries. Returns the number of rows read or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_prefetch(
+    handle: LanceHandlePtr,
+    labels: *const i64,
+    label_count: i32,
+    predicate: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let labels = if labels.is_null() || label_count <= 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(labels, label_count as usize)
+    };
+    let predicate_str = c_str_to_string(predicate);
+    let filter = (!predicate_str.is_empty()).then_some(predicate_str.as_str());
+
+    match h.prefetch(labels, filter) {
+        Ok(rows) => rows as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("prefetch failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Compute the k-NN graph over `sample` source rows (all rows when 0), exported as an
+/// Arrow C stream of (src_label, dst_label, distance) into `out_stream`, which the
+/// caller must release. Returns the edge count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_knn_graph(
+    handle: LanceHandlePtr,
+    k: i32,
+    sample: i64,
+    nprobes: i32,
+    refine_factor: i32,
+    out_stream: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() || out_stream.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or output stream");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::Search, || {
+        h.knn_graph(
+            k.max(0) as usize,
+            sample.max(0) as usize,
+            nprobes.max(0) as usize,
+            refine_factor_arg(refine_factor),
+        )
+    }) {
+        Ok(batch) => {
+            let n = batch.num_rows();
+            metrics::add_rows(Op::Search, n as u64);
+            export_stream(batch, KNN_GRAPH_BATCH_ROWS, out_stream);
+            n as i64
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("knn_graph failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Export the (label, _rowid, fragment_id) of every row as an Arrow C stream into
+/// `out_stream`, which the caller must release. Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_row_addresses(
+    handle: LanceHandlePtr,
+    out_stream: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() || out_stream.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or output stream");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.row_addresses() {
+        Ok(batch) => {
+            let n = batch.num_rows();
+            export_stream(batch, ROW_ADDRESS_BATCH_ROWS, out_stream);
+            n as i64
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("row_addresses failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Open a streaming search cursor for large k. Results are pulled in chunks with
+/// `lance_search_cursor_next`; free with `lance_search_cursor_free`.
+/// Returns null on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_cursor_open(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> LanceCursorPtr {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return std::ptr::null_mut();
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+
+    match metrics::observe(Op::Search, || {
+        h.search_cursor(query_slice, k as usize, nprobes as usize, refine_factor_arg(refine_factor))
+    }) {
+        Ok(cursor) => Box::into_raw(Box::new(cursor)) as LanceCursorPtr,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_cursor_open failed: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Fill up to `capacity` results into `out_labels`/`out_distances`.
+/// Returns the number written (0 when exhausted) or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_search_cursor_next(
+    cursor: LanceCursorPtr,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    capacity: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if cursor.is_null() {
+        write_err(err_buf, err_buf_len, "null cursor");
+        return -1;
+    }
+    if capacity <= 0 {
+        return 0;
+    }
+    let c = &mut *(cursor as *mut SearchCursor);
+    let labels = slice::from_raw_parts_mut(out_labels, capacity as usize);
+    let distances = slice::from_raw_parts_mut(out_distances, capacity as usize);
+
+    match c.next_into(labels, distances) {
+        Ok(n) => {
+            metrics::add_rows(Op::Search, n as u64);
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_cursor_next failed: {}", e));
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_search_cursor_free(cursor: LanceCursorPtr) {
+    if !cursor.is_null() {
+        drop(Box::from_raw(cursor as *mut SearchCursor));
+    }
+}
+
+// ========================================
+// Count / Delete
+// ========================================
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_count(
+    handle: LanceHandlePtr,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.count() {
+        Ok(n) => n as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("count failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Check that the handle's store is reachable and its table manifest readable.
+/// Writes the latest table version and the round trip in milliseconds (either may be
+/// null). After a connection error the handle reopens its table before its next
+/// operation. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_ping(
+    handle: LanceHandlePtr,
+    out_version: *mut i64,
+    out_elapsed_ms: *mut f64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.ping() {
+        Ok(ping) => {
+            if !out_version.is_null() {
+                *out_version = ping.version as i64;
+            }
+            if !out_elapsed_ms.is_null() {
+                *out_elapsed_ms = ping.elapsed_ms;
+            }
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("ping failed: {}", e));
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_delete(
+    handle: LanceHandlePtr,
+    label: i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::Delete, || h.delete(label)) {
+        Ok(()) => {
+            metrics::add_rows(Op::Delete, 1);
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("delete failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// A non-null `token` makes the call idempotent: a retry with the same token is a
+/// no-op.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_delete_batch(
+    handle: LanceHandlePtr,
+    labels: *const i64,
+    count: i32,
+    token: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let label_slice = slice::from_raw_parts(labels, count as usize);
+    let token = (!token.is_null()).then(|| c_str_to_string(token));
+    match metrics::observe(Op::Delete, || h.delete_batch_with_token(label_slice, token.as_deref())) {
+        Ok(()) => {
+            metrics::add_rows(Op::Delete, label_slice.len() as u64);
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("delete_batch failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Relabel rows in one table version (see `LanceIndex::remap_labels`). The mapping
+/// is an Arrow struct of Int64 `old_label` and `new_label` columns; takes ownership
+/// of `arrow_array`. Returns the number of rows remapped, or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_remap_labels(
+    handle: LanceHandlePtr,
+    arrow_schema: *mut c_void,
+    arrow_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if arrow_schema.is_null() || arrow_array.is_null() {
+        write_err(err_buf, err_buf_len, "null arrow schema/array");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.remap_labels_arrow(arrow_schema as *mut FFI_ArrowSchema, arrow_array as *mut FFI_ArrowArray) {
+        Ok(n) => n as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("remap_labels failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Schema migration
+// ========================================
+
+/// Import the desired schema of a migration: data columns, without the label.
+unsafe fn import_target_schema(target_schema: *mut c_void) -> anyhow::Result<Schema> {
+    if target_schema.is_null() {
+        return Err(anyhow::anyhow!("null target schema"));
+    }
+    Schema::try_from(&*(target_schema as *const FFI_ArrowSchema))
+        .map_err(|e| anyhow::anyhow!("FFI schema import failed: {}", e))
+}
+
+/// Export the migration plan turning the table schema into `target_schema` (borrowed)
+/// as (step, column, from_type, to_type) rows. Returns the number of steps or -1 on
+/// error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_schema_diff(
+    handle: LanceHandlePtr,
+    target_schema: *mut c_void,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = import_target_schema(target_schema)
+        .and_then(|target| h.schema_diff(&target))
+        .and_then(|plan| {
+            let steps = plan.steps.len() as i64;
+            export_batch(plan.to_record_batch()?, out_schema, out_array).map(|_| steps)
+        });
+    match result {
+        Ok(steps) => steps,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("schema_diff failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Migrate the table to `target_schema` (borrowed) by applying the plan
+/// `lance_detached_schema_diff` reports. Reopen handles on the table afterwards.
+/// Returns the number of steps applied or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_apply_migration(
+    handle: LanceHandlePtr,
+    target_schema: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = import_target_schema(target_schema)
+        .and_then(|target| h.schema_diff(&target))
+        .and_then(|plan| h.apply_migration(&plan));
+    match result {
+        Ok(steps) => steps as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("apply_migration failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// ANN Index / Compact
+// ========================================
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_index(
+    handle: LanceHandlePtr,
+    num_partitions: i32,
+    num_sub_vectors: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::IndexBuild, || {
+        h.create_ann_index(num_partitions as u32, num_sub_vectors as u32)
+    }) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_hnsw_index(
+    handle: LanceHandlePtr,
+    m: i32,
+    ef_construction: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::IndexBuild, || h.create_hnsw_index(m as u32, ef_construction as u32)) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_hnsw_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Create (or replace) an IVF_FLAT index; `num_partitions` <= 0 uses the LanceDB
+/// default. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_ivf_flat_index(
+    handle: LanceHandlePtr,
+    num_partitions: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::IndexBuild, || h.create_ivf_flat_index(num_partitions.max(0) as u32)) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_ivf_flat_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+unsafe fn vector_index_params(
+    num_partitions: i32,
+    num_sub_vectors: i32,
+    m: i32,
+    ef_construction: i32,
+    sample_rate: i32,
+    metric: *const c_char,
+) -> VectorIndexParams {
+    VectorIndexParams {
+        num_partitions: num_partitions.max(0) as u32,
+        num_sub_vectors: num_sub_vectors.max(0) as u32,
+        m: m.max(0) as u32,
+        ef_construction: ef_construction.max(0) as u32,
+        sample_rate: sample_rate.max(0) as u32,
+        metric: Some(c_str_to_string(metric)).filter(|m| !m.is_empty()),
+    }
+}
+
+/// Create (or replace) the vector index. `index_type` is a [`VectorIndexType`]
+/// value (0 = IVF_PQ, 1 = IVF_HNSW_SQ, 3 = IVF_FLAT); parameters <= 0 use the
+/// LanceDB defaults and those that do not apply to the type are ignored.
+/// `metric` overrides the handle's distance type (null/empty keeps it).
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_vector_index(
+    handle: LanceHandlePtr,
+    index_type: i32,
+    num_partitions: i32,
+    num_sub_vectors: i32,
+    m: i32,
+    ef_construction: i32,
+    sample_rate: i32,
+    metric: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let params = vector_index_params(num_partitions, num_sub_vectors, m, ef_construction, sample_rate, metric);
+    let result = VectorIndexType::from_i32(index_type)
+        .and_then(|kind| metrics::observe(Op::IndexBuild, || h.create_vector_index(kind, &params)));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_vector_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Rebuild the vector index in the background (same parameters as
+/// `lance_detached_create_vector_index`); the current index serves searches until
+/// the new one is committed. With `wait` != 0, blocks until the rebuild finishes and
+/// fails if it did. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_rebuild_index(
+    handle: LanceHandlePtr,
+    index_type: i32,
+    num_partitions: i32,
+    num_sub_vectors: i32,
+    m: i32,
+    ef_construction: i32,
+    sample_rate: i32,
+    metric: *const c_char,
+    wait: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let params = vector_index_params(num_partitions, num_sub_vectors, m, ef_construction, sample_rate, metric);
+    let result = VectorIndexType::from_i32(index_type)
+        .and_then(|kind| h.rebuild_index(kind, &params, wait != 0))
+        .and_then(|status| match status.error {
+            Some(error) if wait != 0 => Err(anyhow::anyhow!(error)),
+            _ => Ok(()),
+        });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("rebuild_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Status of the latest rebuild, exported as one row
+/// (state, index_type, started_ms, elapsed_ms, error, phase). Returns 1 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_rebuild_status(
+    handle: LanceHandlePtr,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h
+        .rebuild_status()
+        .to_record_batch()
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("rebuild_status failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Open a read-only handle pinned to the table version `handle` currently sees,
+/// writing that version to `out_version` (nullable). Searches and scans through it
+/// observe one dataset while ingestion continues. Returns the new handle (release
+/// with `lance_detached_release_snapshot`), or null on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_begin_read_snapshot(
+    handle: LanceHandlePtr,
+    out_version: *mut u64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> LanceHandlePtr {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return std::ptr::null_mut();
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.begin_read_snapshot() {
+        Ok(snapshot) => {
+            if !out_version.is_null() {
+                *out_version = snapshot.snapshot_version().unwrap_or_default();
+            }
+            Box::into_raw(Box::new(snapshot)) as LanceHandlePtr
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("begin_read_snapshot failed: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Free a handle from `lance_detached_begin_read_snapshot`. Live handles are
+/// refused and left open. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_release_snapshot(
+    snapshot: LanceHandlePtr,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if snapshot.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if (*(snapshot as *mut LanceIndex)).snapshot_version().is_none() {
+        write_err(err_buf, err_buf_len, "release_snapshot failed: handle is not a read snapshot");
+        return -1;
+    }
+    let snapshot = *Box::from_raw(snapshot as *mut LanceIndex);
+    match snapshot.release_snapshot() {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("release_snapshot failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Create the staging sibling of a table, copying every live row (labels kept)
+/// when `copy_rows` is non-zero. Returns a new handle (free with
+/// `lance_free_detached`, or pass to `lance_detached_promote_staging`), or null on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_staging(
+    handle: LanceHandlePtr,
+    copy_rows: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> LanceHandlePtr {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return std::ptr::null_mut();
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.create_staging(copy_rows != 0) {
+        Ok(staging) => Box::into_raw(Box::new(staging)) as LanceHandlePtr,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_staging failed: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Swap a staging table in as the live table; `handle` is reopened on it in place.
+/// Always takes ownership of `staging_handle`, even on error. Writes the tag the
+/// previous version of the live table was kept under to `out_retired`.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_promote_staging(
+    handle: LanceHandlePtr,
+    staging_handle: LanceHandlePtr,
+    out_retired: *mut c_char,
+    out_retired_len: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if staging_handle.is_null() {
+        write_err(err_buf, err_buf_len, "null staging handle");
+        return -1;
+    }
+    let staging = *Box::from_raw(staging_handle as *mut LanceIndex);
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &mut *(handle as *mut LanceIndex);
+    match h.promote_staging(staging) {
+        Ok(retired) => {
+            write_c_str(out_retired, out_retired_len, &retired);
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("promote_staging failed: {}", e));
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_compact(
+    handle: LanceHandlePtr,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::Compact, || h.compact()) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("compact failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Run a maintenance plan such as `optimize; compact(16); prune(7); verify` (see
+/// `crate::maintenance`). Exports one row of (step, status, detail, elapsed_ms) per
+/// step into `out_schema`/`out_array`; a failed step is reported there, not as an
+/// error. Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_run_maintenance(
+    handle: LanceHandlePtr,
+    plan: *const c_char,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || plan.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or plan");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = MaintenancePlan::parse(&c_str_to_string(plan))
+        .and_then(|plan| metrics::observe(Op::Compact, || h.run_maintenance(&plan)))
+        .and_then(|report| report.to_record_batch())
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("run_maintenance failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Get vector
+// ========================================
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_get_vector(
+    handle: LanceHandlePtr,
+    label: i64,
+    out_vec: *mut f32,
+    capacity: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.get_vector(label) {
+        Ok(vec) => {
+            let dim = vec.len();
+            if dim > capacity as usize {
+                write_err(err_buf, err_buf_len, "output buffer too small");
+                return -1;
+            }
+            std::ptr::copy_nonoverlapping(vec.as_ptr(), out_vec, dim);
+            dim as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("get_vector failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Bulk vector export
+// ========================================
+
+/// Export all vectors from a detached index.
+/// Returns count of vectors. Fills out_labels and out_vectors (caller-allocated).
+/// out_vectors must hold count * dimension floats.
+/// Call with null out_labels/out_vectors to get the count first.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_get_all_vectors(
+    handle: LanceHandlePtr,
+    out_labels: *mut i64,
+    out_vectors: *mut f32,
+    out_count: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match read_limit::limited(|| h.get_all_vectors()) {
+        Ok((labels, vectors)) => {
+            let count = labels.len();
+            if !out_count.is_null() {
+                *out_count = count as i64;
+            }
+            if !out_labels.is_null() && !out_vectors.is_null() {
+                std::ptr::copy_nonoverlapping(labels.as_ptr(), out_labels, count);
+                std::ptr::copy_nonoverlapping(vectors.as_ptr(), out_vectors, vectors.len());
+            }
+            count as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("get_all_vectors failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Disk usage
+// ========================================
+
+/// Report on-disk bytes by component (data, deletions, indices, manifests, total)
+/// of the current version and, when `include_columns` is non-zero, bytes per column.
+/// Exports rows of (component, column, bytes) into `out_schema`/`out_array`.
+/// Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_disk_usage(
+    handle: LanceHandlePtr,
+    include_columns: i32,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h
+        .disk_usage(include_columns != 0)
+        .and_then(|usage| usage.to_record_batch())
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("disk_usage failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Back the table up into the dataset directory `dest` (see
+/// `LanceIndex::backup_incremental`), copying only what came after
+/// `since_version`; a negative `since_version` backs up every version. Writes the
+/// latest version backed up, the files copied and their bytes into the `out_*`
+/// pointers. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_backup(
+    handle: LanceHandlePtr,
+    dest: *const c_char,
+    since_version: i64,
+    out_version: *mut i64,
+    out_files: *mut i64,
+    out_bytes: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || dest.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or dest");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let since_version = (since_version >= 0).then_some(since_version as u64);
+    match h.backup_incremental(&c_str_to_string(dest), since_version) {
+        Ok(report) => {
+            if !out_version.is_null() {
+                *out_version = report.version as i64;
+            }
+            if !out_files.is_null() {
+                *out_files = report.files as i64;
+            }
+            if !out_bytes.is_null() {
+                *out_bytes = report.bytes as i64;
+            }
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("backup failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Export the labels of the rows `handle` sees as a roaring bitmap (see
+/// `LanceIndex::label_bitmap`), as one row (version, labels, bitmap): the table
+/// version read, the label count and the serialized bitmap in a Binary column.
+/// Returns 1 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_label_bitmap(
+    handle: LanceHandlePtr,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h.label_bitmap().and_then(|(bitmap, version)| {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("version", DataType::Int64, false),
+            Field::new("labels", DataType::Int64, false),
+            Field::new("bitmap", DataType::Binary, false),
+        ]));
+        let bytes = label_bitmap::encode(&bitmap)?;
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![version as i64])),
+                Arc::new(Int64Array::from(vec![bitmap.len() as i64])),
+                Arc::new(BinaryArray::from_vec(vec![bytes.as_slice()])),
+            ],
+        )?;
+        export_batch(batch, out_schema, out_array)
+    });
+    match result {
+        Ok(()) => 1,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("label_bitmap failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Combine the serialized label bitmaps `a` and `b` with `op` (0 = and, 1 = or,
+/// 2 = labels of `a` not in `b`, 3 = xor) into one row (labels, bitmap): the label
+/// count and the serialized result. Returns 1 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_label_bitmap_combine(
+    a: *const u8,
+    a_len: i64,
+    b: *const u8,
+    b_len: i64,
+    op: i32,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if a.is_null() || b.is_null() || a_len < 0 || b_len < 0 {
+        write_err(err_buf, err_buf_len, "null or invalid bitmap");
+        return -1;
+    }
+    let (a, b) = (slice::from_raw_parts(a, a_len as usize), slice::from_raw_parts(b, b_len as usize));
+    let result = BitmapOp::from_ffi(op).and_then(|op| {
+        let combined = op.apply(&label_bitmap::decode(a)?, &label_bitmap::decode(b)?);
+        let bytes = label_bitmap::encode(&combined)?;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("labels", DataType::Int64, false),
+            Field::new("bitmap", DataType::Binary, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![combined.len() as i64])),
+                Arc::new(BinaryArray::from_vec(vec![bytes.as_slice()])),
+            ],
+        )?;
+        export_batch(batch, out_schema, out_array)
+    });
+    match result {
+        Ok(()) => 1,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("label_bitmap_combine failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Export the labels of the serialized label bitmap `bitmap`, ascending, as rows
+/// (label). Returns the label count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_label_bitmap_labels(
+    bitmap: *const u8,
+    bitmap_len: i64,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if bitmap.is_null() || bitmap_len < 0 {
+        write_err(err_buf, err_buf_len, "null or invalid bitmap");
+        return -1;
+    }
+    let bytes = slice::from_raw_parts(bitmap, bitmap_len as usize);
+    let result = label_bitmap::decode(bytes).and_then(|bitmap| label_bitmap::labels(&bitmap)).and_then(|labels| {
+        let count = labels.len() as i64;
+        let schema = Arc::new(Schema::new(vec![Field::new("label", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(labels))])?;
+        export_batch(batch, out_schema, out_array)?;
+        Ok(count)
+    });
+    match result {
+        Ok(count) => count,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("label_bitmap_labels failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Storage-format report of the table (see `LanceIndex::format_info`), exported as one
+/// row (version, writer, library, storage_format, file_versions, reader_flags,
+/// writer_flags, can_read, can_write, written_by_newer, problems). Returns 1 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_format_info(
+    handle: LanceHandlePtr,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h
+        .format_info()
+        .and_then(|info| info.to_record_batch())
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("format_info failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Export the manifest of the version `handle` sees for replicas (see
+/// `LanceIndex::export_manifest`), as one row (version, manifest) with the blob in a
+/// Binary column. Returns 1 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_export_manifest(
+    handle: LanceHandlePtr,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h.export_manifest().and_then(|blob| {
+        let version = ManifestBlob::decode(&blob)?.version as i64;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("version", DataType::Int64, false),
+            Field::new("manifest", DataType::Binary, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int64Array::from(vec![version])), Arc::new(BinaryArray::from_vec(vec![blob.as_slice()]))],
+        )?;
+        export_batch(batch, out_schema, out_array)
+    });
+    match result {
+        Ok(()) => 1,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("export_manifest failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Move `handle`'s view to the version in the `blob_len` bytes of `blob`, exported
+/// by `lance_detached_export_manifest` on the writer (see
+/// `LanceIndex::import_manifest`). The handle becomes a read-only replica. Returns
+/// the version or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_import_manifest(
+    handle: LanceHandlePtr,
+    blob: *const u8,
+    blob_len: i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() || blob.is_null() || blob_len < 0 {
+        write_err(err_buf, err_buf_len, "null handle or blob");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.import_manifest(slice::from_raw_parts(blob, blob_len as usize)) {
+        Ok(version) => version as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("import_manifest failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Compute min/max/null-count for `column`, exported as a single-row Arrow batch
+/// with columns (min, max, null_count, row_count); min/max keep the column type.
+/// Sensitive columns need a non-zero `privileged`. Returns 1 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_column_stats(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    privileged: i32,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let column = c_str_to_string(column);
+    let result = h
+        .column_stats(&column, privileged != 0)
+        .and_then(|stats| stats.to_record_batch())
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("column_stats failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Per-group row counts over `column` (see `LanceIndex::approx_stats`), exported as
+/// an Arrow batch with columns (group_value, other, row_count, min_distance,
+/// avg_distance, max_distance). `query` may be null for counts only. Writes the
+/// distinct-value estimate to `out_distinct_groups`. Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_approx_stats(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    query: *const f32,
+    dim: i32,
+    sample_fraction: f64,
+    max_groups: i32,
+    privileged: i32,
+    out_distinct_groups: *mut f64,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let column = c_str_to_string(column);
+    let query = (!query.is_null()).then(|| slice::from_raw_parts(query, dim.max(0) as usize));
+    let result = h
+        .approx_stats(&column, query, sample_fraction, max_groups.max(0) as usize, privileged != 0)
+        .and_then(|stats| {
+            if !out_distinct_groups.is_null() {
+                *out_distinct_groups = stats.distinct_groups;
+            }
+            stats.to_record_batch()
+        })
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("approx_stats failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Mark the comma-separated `columns` as sensitive, replacing the previous list
+/// (empty clears it). Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_sensitive_columns(
+    handle: LanceHandlePtr,
+    columns: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.set_sensitive_columns(&split_columns(&c_str_to_string(columns))) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_sensitive_columns failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Scan the comma-separated `columns` (every column the caller may see when
+/// empty) of rows matching `filter` (null for all), exported as an Arrow C stream
+/// into `out_stream`, which the caller must release. Sensitive columns need a
+/// non-zero `privileged`. With `order_by` (null for scan order), rows are sorted
+/// by that column, descending when `descending` is non-zero, nulls last; a
+/// non-negative `limit` keeps only the first rows, which bounds the sort's memory.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_scan(
+    handle: LanceHandlePtr,
+    columns: *const c_char,
+    filter: *const c_char,
+    privileged: i32,
+    order_by: *const c_char,
+    descending: i32,
+    limit: i64,
+    out_stream: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || out_stream.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or output stream");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let columns = split_columns(&c_str_to_string(columns));
+    let filter = (!filter.is_null()).then(|| c_str_to_string(filter));
+    let limit = (limit >= 0).then_some(limit as usize);
+    if !order_by.is_null() {
+        let order = OrderBy {
+            column: c_str_to_string(order_by),
+            descending: descending != 0,
+        };
+        return match h.scan_ordered(&columns, filter.as_deref(), privileged != 0, &order, limit, SCAN_BATCH_ROWS) {
+            Ok(reader) => {
+                std::ptr::write(out_stream as *mut FFI_ArrowArrayStream, FFI_ArrowArrayStream::new(reader));
+                0
+            }
+            Err(e) => {
+                write_err(err_buf, err_buf_len, &format!("scan failed: {}", e));
+                -1
+            }
+        };
+    }
+    match read_limit::limited(|| h.scan(&columns, filter.as_deref(), privileged != 0)) {
+        Ok(batch) => {
+            let rows = limit.map_or(batch.num_rows(), |limit| limit.min(batch.num_rows()));
+            export_stream(batch.slice(0, rows), SCAN_BATCH_ROWS, out_stream);
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("scan failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Scan for UPDATE/DELETE planning (see `LanceIndex::scan_with_labels`): like
+/// `lance_detached_scan` without ordering, with each row's `label` as the first
+/// column. Writes the table version read into `out_version`, to pass back to
+/// `lance_detached_apply_changes`. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_scan_with_labels(
+    handle: LanceHandlePtr,
+    columns: *const c_char,
+    filter: *const c_char,
+    privileged: i32,
+    out_version: *mut u64,
+    out_stream: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || out_stream.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or output stream");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let columns = split_columns(&c_str_to_string(columns));
+    let filter = (!filter.is_null()).then(|| c_str_to_string(filter));
+    match read_limit::limited(|| h.scan_with_labels(&columns, filter.as_deref(), privileged != 0)) {
+        Ok((batch, version)) => {
+            if !out_version.is_null() {
+                *out_version = version;
+            }
+            export_stream(batch, SCAN_BATCH_ROWS, out_stream);
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("scan failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Apply planned DELETE/UPDATE changes in one table version (see
+/// `LanceIndex::apply_changes`): delete the `delete_count` labels of `deletes` and
+/// write the columns of the optional Arrow batch `arrow_schema`/`arrow_array` (a
+/// `label` column plus the columns to set) over the rows it labels. Takes ownership
+/// of `arrow_array`, like `lance_detached_add_batch_arrow`. A negative
+/// `expected_version` skips the version check. Writes the row counts into
+/// `out_deleted` and `out_updated`. Returns the committed version or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_apply_changes(
+    handle: LanceHandlePtr,
+    deletes: *const i64,
+    delete_count: i32,
+    arrow_schema: *mut c_void,
+    arrow_array: *mut c_void,
+    expected_version: i64,
+    out_deleted: *mut i64,
+    out_updated: *mut i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() || (deletes.is_null() && delete_count > 0) {
+        write_err(err_buf, err_buf_len, "null handle or deletes");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let deletes = if delete_count > 0 { slice::from_raw_parts(deletes, delete_count as usize) } else { &[] };
+    let updates = if arrow_schema.is_null() || arrow_array.is_null() {
+        None
+    } else {
+        // Take ownership of the array; C++ keeps owning the schema
+        let ffi_array = std::mem::replace(&mut *(arrow_array as *mut FFI_ArrowArray), FFI_ArrowArray::empty());
+        match arrow::ffi::from_ffi(ffi_array, &*(arrow_schema as *const FFI_ArrowSchema)) {
+            Ok(data) => Some(RecordBatch::from(StructArray::from(data))),
+            Err(e) => {
+                write_err(err_buf, err_buf_len, &format!("apply_changes failed: Arrow FFI import failed: {}", e));
+                return -1;
+            }
+        }
+    };
+    let expected_version = (expected_version >= 0).then_some(expected_version as u64);
+
+    match h.apply_changes(deletes, updates.as_ref(), expected_version) {
+        Ok(applied) => {
+            if !out_deleted.is_null() {
+                *out_deleted = applied.deleted as i64;
+            }
+            if !out_updated.is_null() {
+                *out_updated = applied.updated as i64;
+            }
+            applied.version as i64
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("apply_changes failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Layout of the comma-separated result `columns` (every column the caller may see
+/// when empty) for `lance_detached_search_into`: per column, its `BufferType` code
+/// into `out_types` and its width in bytes per row (0 for strings) into
+/// `out_widths`, for at most `max_columns` columns. Returns the column count, which
+/// may exceed `max_columns`, or -1 on error (e.g. a type without a buffer layout).
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_result_layout(
+    handle: LanceHandlePtr,
+    columns: *const c_char,
+    privileged: i32,
+    out_types: *mut i32,
+    out_widths: *mut i32,
+    max_columns: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let columns = split_columns(&c_str_to_string(columns));
+    let layouts = h
+        .result_fields(&columns, privileged != 0)
+        .and_then(|fields| fields.iter().map(ColumnLayout::of).collect::<anyhow::Result<Vec<_>>>());
+    match layouts {
+        Ok(layouts) => {
+            for (i, layout) in layouts.iter().take(max_columns.max(0) as usize).enumerate() {
+                *out_types.add(i) = layout.buffer_type as i32;
+                *out_widths.add(i) = layout.width as i32;
+            }
+            layouts.len() as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("result_layout failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// `lance_detached_search`, also copying the result `columns` (as negotiated with
+/// `lance_detached_result_layout`) into caller-allocated buffers instead of an
+/// Arrow stream. Each of the `column_count` columns has:
+/// - `values[i]` with room for `value_capacities[i]` bytes: k times the width, or
+///   the string bytes for strings; `out_value_lengths[i]` receives the bytes written
+/// - `offsets[i]`: k + 1 `i32` string offsets into `values[i]` (strings only)
+/// - `validity[i]`: one byte per row, 1 valid and 0 null (may be null)
+///
+/// Nothing is written when a buffer is too small. Returns the row count or -1 on
+/// error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_into(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    columns: *const c_char,
+    privileged: i32,
+    values: *const *mut u8,
+    value_capacities: *const i64,
+    offsets: *const *mut i32,
+    validity: *const *mut u8,
+    out_value_lengths: *mut i64,
+    column_count: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let columns = split_columns(&c_str_to_string(columns));
+
+    let result = metrics::observe(Op::Search, || {
+        let (hits, batch) = h.search_rows(
+            query_slice,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            &columns,
+            privileged != 0,
+        )?;
+        if batch.num_columns() != column_count as usize {
+            return Err(anyhow::anyhow!(
+                "{} result columns, {} buffers given",
+                batch.num_columns(),
+                column_count
+            ));
+        }
+        let schema = batch.schema();
+        let mut encoded = Vec::with_capacity(batch.num_columns());
+        for (i, field) in schema.fields().iter().enumerate() {
+            let column = projection::encode(batch.column(i).as_ref(), ColumnLayout::of(field)?)?;
+            let capacity = *value_capacities.add(i);
+            if column.values.len() as i64 > capacity {
+                return Err(anyhow::anyhow!(
+                    "buffer for column '{}' holds {} bytes, {} needed",
+                    field.name(),
+                    capacity,
+                    column.values.len()
+                ));
+            }
+            if column.offsets.is_some() && (*offsets.add(i)).is_null() {
+                return Err(anyhow::anyhow!("string column '{}' needs an offsets buffer", field.name()));
+            }
+            encoded.push(column);
+        }
+        Ok((hits, encoded))
+    });
+    match result {
+        Ok((hits, encoded)) => {
+            let n = hits.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in hits.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            for (i, column) in encoded.iter().enumerate() {
+                std::ptr::copy_nonoverlapping(column.values.as_ptr(), *values.add(i), column.values.len());
+                *out_value_lengths.add(i) = column.values.len() as i64;
+                if let Some(ends) = &column.offsets {
+                    std::ptr::copy_nonoverlapping(ends.as_ptr(), *offsets.add(i), ends.len());
+                }
+                let valid = *validity.add(i);
+                if !valid.is_null() {
+                    std::ptr::copy_nonoverlapping(column.validity.as_ptr(), valid, column.validity.len());
+                }
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_into failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// `lance_detached_search` with the result `columns` (comma-separated; every
+/// column the caller may see when empty) of each hit, exported as an Arrow C
+/// stream of (label, distance, columns...) in hit order into `out_stream`, which
+/// the caller must release (see `LanceIndex::search_with_columns`). Sensitive
+/// columns need a non-zero `privileged`. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_with_columns(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    columns: *const c_char,
+    privileged: i32,
+    out_stream: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || out_stream.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or output stream");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let columns = split_columns(&c_str_to_string(columns));
+
+    let result = metrics::observe(Op::Search, || {
+        h.search_with_columns(
+            query_slice,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            predicate.as_deref(),
+            &columns,
+            privileged != 0,
+        )
+    });
+    match result {
+        Ok(reader) => {
+            std::ptr::write(out_stream as *mut FFI_ArrowArrayStream, FFI_ArrowArrayStream::new(reader));
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_with_columns failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Rewrite the table sorted by `column` into fragments of `rows_per_fragment` rows.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_cluster_by(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    rows_per_fragment: i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let column = c_str_to_string(column);
+    let rows_per_fragment = rows_per_fragment.max(0) as usize;
+    match metrics::observe(Op::Compact, || h.cluster_by(&column, rows_per_fragment)) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("cluster_by failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Count fragments matching `predicate` versus the total. Writes the clustering
+/// column (empty if the table is not clustered) to `out_cluster_by`.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_pruning_stats(
+    handle: LanceHandlePtr,
+    predicate: *const c_char,
+    out_total_fragments: *mut i64,
+    out_matching_fragments: *mut i64,
+    out_cluster_by: *mut c_char,
+    out_cluster_by_len: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || predicate.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or predicate");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let predicate = c_str_to_string(predicate);
+    match h.pruning_stats(&predicate) {
+        Ok(stats) => {
+            *out_total_fragments = stats.total_fragments as i64;
+            *out_matching_fragments = stats.matching_fragments as i64;
+            write_c_str(out_cluster_by, out_cluster_by_len, stats.cluster_by.as_deref().unwrap_or(""));
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("pruning_stats failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Retrieval pipelines
+// ========================================
+
+/// Reranker callback: fill `out_scores[0..n]` for the candidates (smaller ranks
+/// first) and return 0, or return non-zero to fail the search.
+pub type LanceRerankFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    query: *const f32,
+    dim: i32,
+    labels: *const i64,
+    distances: *const f32,
+    n: i32,
+    out_scores: *mut f32,
+) -> i32;
+
+/// Custom distance callback: the distance between `a` and `b` (`dim` values each);
+/// smaller is closer.
+pub type LanceDistanceFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    a: *const f32,
+    b: *const f32,
+    dim: i32,
+) -> f32;
+
+/// Set the table's retrieval pipeline, e.g. "coarse(10) | rescore | rerank(name)".
+/// Null or empty `spec` removes it. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_pipeline(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = c_str_to_string(spec);
+    let result = if spec.trim().is_empty() {
+        h.set_pipeline(None)
+    } else {
+        Pipeline::parse(&spec).and_then(|p| h.set_pipeline(Some(p)))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_pipeline failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Register a process-wide reranker for `rerank(name)` pipeline stages.
+/// `user_data` must stay valid, and the callback thread-safe, for the process lifetime.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_register_reranker(
+    name: *const c_char,
+    callback: Option<LanceRerankFn>,
+    user_data: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let callback = match callback {
+        Some(cb) if !name.is_null() => cb,
+        _ => {
+            write_err(err_buf, err_buf_len, "null name or callback");
+            return -1;
+        }
+    };
+    let name = c_str_to_string(name);
+    // Raw pointers are not Send; the caller guarantees the data is shareable.
+    let user_data = user_data as usize;
+    let reranker: pipeline::Reranker = std::sync::Arc::new(move |query, labels, distances| {
+        let mut scores = vec![0f32; labels.len()];
+        let rc = callback(
+            user_data as *mut c_void,
+            query.as_ptr(),
+            query.len() as i32,
+            labels.as_ptr(),
+            distances.as_ptr(),
+            labels.len() as i32,
+            scores.as_mut_ptr(),
+        );
+        if rc != 0 {
+            return Err(anyhow::anyhow!("reranker callback returned {}", rc));
+        }
+        Ok(scores)
+    });
+    match pipeline::register_reranker(&name, reranker) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("register_reranker failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Register a process-wide custom metric for exact and rescoring paths (see
+/// `lance_detached_set_rescore_metric`); the ANN index never uses it. `user_data`
+/// must stay valid, and the callback thread-safe, for the process lifetime.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_register_distance(
+    name: *const c_char,
+    callback: Option<LanceDistanceFn>,
+    user_data: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let callback = match callback {
+        Some(cb) if !name.is_null() => cb,
+        _ => {
+            write_err(err_buf, err_buf_len, "null name or callback");
+            return -1;
+        }
+    };
+    let name = c_str_to_string(name);
+    // Raw pointers are not Send; the caller guarantees the data is shareable.
+    let user_data = user_data as usize;
+    let metric: distance::CustomMetric = Arc::new(move |a, b| {
+        callback(user_data as *mut c_void, a.as_ptr(), b.as_ptr(), a.len() as i32)
+    });
+    match distance::register_metric(&name, metric) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("register_distance failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Chunked ingestion
+// ========================================
+
+/// Embedder callback: embed the `n` texts (`texts[i]` is `text_lens[i]` UTF-8 bytes,
+/// not NUL-terminated) into `out_vectors` (`n * dim` floats) and return 0, or
+/// return non-zero to fail the ingestion.
+pub type LanceEmbedFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    texts: *const *const c_char,
+    text_lens: *const i32,
+    n: i32,
+    dim: i32,
+    out_vectors: *mut f32,
+) -> i32;
+
+/// Register a process-wide embedder for chunking stages (`embedder=name`).
+/// `user_data` must stay valid, and the callback thread-safe, for the process lifetime.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_register_embedder(
+    name: *const c_char,
+    callback: Option<LanceEmbedFn>,
+    user_data: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let callback = match callback {
+        Some(cb) if !name.is_null() => cb,
+        _ => {
+            write_err(err_buf, err_buf_len, "null name or callback");
+            return -1;
+        }
+    };
+    let name = c_str_to_string(name);
+    // Raw pointers are not Send; the caller guarantees the data is shareable.
+    let user_data = user_data as usize;
+    let embedder: chunk::Embedder = Arc::new(move |texts, dim| {
+        let pointers: Vec<*const c_char> = texts.iter().map(|t| t.as_ptr() as *const c_char).collect();
+        let lens: Vec<i32> = texts.iter().map(|t| t.len() as i32).collect();
+        let mut vectors = vec![0f32; texts.len() * dim];
+        let rc = callback(
+            user_data as *mut c_void,
+            pointers.as_ptr(),
+            lens.as_ptr(),
+            texts.len() as i32,
+            dim as i32,
+            vectors.as_mut_ptr(),
+        );
+        if rc != 0 {
+            return Err(anyhow::anyhow!("embedder callback returned {}", rc));
+        }
+        Ok(vectors)
+    });
+    match chunk::register_embedder(&name, embedder) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("register_embedder failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Set the table's chunking stage, e.g. "column=body, parent=doc_id, unit=tokens,
+/// size=256, overlap=32, embedder=name". Null or empty `spec` removes it.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_chunking(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = c_str_to_string(spec);
+    let result = if spec.trim().is_empty() {
+        h.set_chunking(None)
+    } else {
+        Chunking::parse(&spec).and_then(|c| h.set_chunking(Some(c)))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_chunking failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Set the table's ingest constraints, e.g. "author not null; score between 0 and 1;
+/// on_violation=skip" (see `crate::constraints`). Null or empty `spec` removes them.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_constraints(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = c_str_to_string(spec);
+    let result = if spec.trim().is_empty() {
+        h.set_constraints(None)
+    } else {
+        Constraints::parse(&spec).and_then(|c| h.set_constraints(Some(c)))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_constraints failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Set what `lance_detached_add_batch_arrow` does with rows that fail validation:
+/// "error" (the default), "skip" or "dead_letter" (see `LanceIndex::set_on_invalid`).
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_on_invalid(
+    handle: LanceHandlePtr,
+    mode: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let mode = c_str_to_string(mode);
+    match OnViolation::parse(mode.trim()).and_then(|m| h.set_on_invalid(m)) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_on_invalid failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Set how `lance_detached_add_batch_arrow` matches incoming columns to the table's:
+/// "positional" (the default), "strict" or "lenient" (see
+/// `LanceIndex::set_column_matching`). Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_column_matching(
+    handle: LanceHandlePtr,
+    mode: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let mode = c_str_to_string(mode);
+    match ColumnMatching::parse(mode.trim()).and_then(|m| h.set_column_matching(m)) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_column_matching failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Export the rows captured in the table's rejects sidecar (see `crate::rejects`)
+/// as rows of (rejected_ms, source_row, reason, row). Returns the row count or -1
+/// on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_rejects(
+    handle: LanceHandlePtr,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h.rejects().and_then(|rejected| rejects::to_record_batch(&rejected)).and_then(|batch| {
+        let rows = batch.num_rows();
+        export_batch(batch, out_schema, out_array).map(|_| rows)
+    });
+    match result {
+        Ok(rows) => rows as i64,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("rejects failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Add documents via the Arrow C Data Interface through the table's chunking
+/// stage: each row becomes one embedded row per chunk (see
+/// `LanceIndex::add_chunked`). Takes ownership of `arrow_array`, like
+/// `lance_detached_add_batch_arrow`. Exports (source_row, label) per chunk as an
+/// Arrow C stream into `out_stream`, which the caller must release.
+/// Returns the number of chunks, -2 if a quota rejected them, or -1 on other errors.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_add_chunked_arrow(
+    handle: LanceHandlePtr,
+    arrow_schema: *mut c_void,
+    arrow_array: *mut c_void,
+    out_stream: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if arrow_schema.is_null() || arrow_array.is_null() {
+        write_err(err_buf, err_buf_len, "null arrow schema/array");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    // Take ownership of the array; C++ keeps owning the schema
+    let ffi_array = std::mem::replace(&mut *(arrow_array as *mut FFI_ArrowArray), FFI_ArrowArray::empty());
+    let batch = match arrow::ffi::from_ffi(ffi_array, &*(arrow_schema as *const FFI_ArrowSchema)) {
+        Ok(data) => RecordBatch::from(StructArray::from(data)),
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("add_chunked failed: Arrow FFI import failed: {}", e));
+            return -1;
+        }
+    };
+
+    match metrics::observe(Op::Add, || h.add_chunked(&batch)) {
+        Ok(chunks) => {
+            let n = chunks.len();
+            metrics::add_rows(Op::Add, n as u64);
+            let (rows, labels): (Vec<u32>, Vec<i64>) = chunks.into_iter().unzip();
+            let batch = RecordBatch::try_new(
+                Arc::new(Schema::new(vec![
+                    Field::new("source_row", DataType::UInt32, false),
+                    Field::new("label", DataType::Int64, false),
+                ])),
+                vec![Arc::new(UInt32Array::from(rows)), Arc::new(Int64Array::from(labels))],
+            );
+            match batch {
+                Ok(batch) => {
+                    export_stream(batch, CHUNK_MAPPING_BATCH_ROWS, out_stream);
+                    n as i64
+                }
+                Err(e) => {
+                    write_err(err_buf, err_buf_len, &format!("add_chunked failed: {}", e));
+                    -1
+                }
+            }
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("add_chunked failed: {}", e));
+            append_error_code(&e) as i64
+        }
+    }
+}
+
+// ========================================
+// Vector math
+// ========================================
+//
+// Kernels behind the SQL vector arithmetic functions, shared with the search
+// paths so results match them exactly. Vectors are `dim` floats each; outputs
+// must hold `dim` floats. Each returns 0 or -1 on error.
+
+/// Write `result` (a `dim`-wide vector) to `out`.
+unsafe fn write_vector(
+    result: anyhow::Result<Vec<f32>>,
+    out: *mut f32,
+    what: &str,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    match result {
+        Ok(v) => {
+            slice::from_raw_parts_mut(out, v.len()).copy_from_slice(&v);
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("{} failed: {}", what, e));
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_vector_add(
+    a: *const f32,
+    b: *const f32,
+    dim: i32,
+    out: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let (a, b) = (slice::from_raw_parts(a, dim as usize), slice::from_raw_parts(b, dim as usize));
+    write_vector(distance::add(a, b), out, "vector_add", err_buf, err_buf_len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lance_vector_subtract(
+    a: *const f32,
+    b: *const f32,
+    dim: i32,
+    out: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let (a, b) = (slice::from_raw_parts(a, dim as usize), slice::from_raw_parts(b, dim as usize));
+    write_vector(distance::subtract(a, b), out, "vector_subtract", err_buf, err_buf_len)
+}
+
+/// Mean of the `count` vectors at `vectors` (flattened).
+#[no_mangle]
+pub unsafe extern "C" fn lance_vector_average(
+    vectors: *const f32,
+    count: i32,
+    dim: i32,
+    out: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let flat = slice::from_raw_parts(vectors, count.max(0) as usize * dim as usize);
+    let rows: Vec<&[f32]> = flat.chunks_exact(dim.max(1) as usize).collect();
+    write_vector(distance::average(&rows), out, "vector_average", err_buf, err_buf_len)
+}
+
+/// Unit-length `vector`; a zero vector stays zero.
+#[no_mangle]
+pub unsafe extern "C" fn lance_vector_normalize(
+    vector: *const f32,
+    dim: i32,
+    out: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let vector = slice::from_raw_parts(vector, dim as usize);
+    write_vector(Ok(distance::normalize(vector)), out, "vector_normalize", err_buf, err_buf_len)
+}
+
+/// `a·b` into `out`.
+#[no_mangle]
+pub unsafe extern "C" fn lance_vector_dot(
+    a: *const f32,
+    b: *const f32,
+    dim: i32,
+    out: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let (a, b) = (slice::from_raw_parts(a, dim as usize), slice::from_raw_parts(b, dim as usize));
+    match distance::dot(a, b) {
+        Ok(dot) => {
+            *out = dot;
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("vector_dot failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Compute exact distances (search_within, PCA and pipeline rescoring, expansion
+/// hops) with `metric`, a built-in or registered custom metric. Null or empty
+/// `metric` restores the handle's metric. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_rescore_metric(
+    handle: LanceHandlePtr,
+    metric: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let metric = (!metric.is_null()).then(|| c_str_to_string(metric));
+    match h.set_rescore_metric(metric.as_deref().filter(|m| !m.trim().is_empty())) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_rescore_metric failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Set the query transform, e.g. "slice(0,256) | center | normalize". Null or empty
+/// `spec` removes it. `mean` (may be null) supplies the `center` mean; otherwise the
+/// mean of the stored vectors is used. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_query_transform(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    mean: *const f32,
+    mean_len: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = c_str_to_string(spec);
+    let spec = (!spec.trim().is_empty()).then_some(spec.as_str());
+    let mean = (!mean.is_null() && mean_len > 0)
+        .then(|| slice::from_raw_parts(mean, mean_len as usize).to_vec());
+    match h.set_query_transform(spec, mean) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_query_transform failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Cold storage
+// ========================================
+
+/// Configure the table's cold vector storage policy, e.g.
+/// "column=created_at, after_days=30, precision=f16" (see `crate::cold`), applied
+/// by compaction. Null or empty `spec` removes it. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_cold_storage(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = c_str_to_string(spec);
+    let result = if spec.trim().is_empty() {
+        h.set_cold_storage(None)
+    } else {
+        ColdStorage::parse(&spec).and_then(|p| h.set_cold_storage(Some(p)))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_cold_storage failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Quotas
+// ========================================
+
+/// Configure the table quota, e.g. "max_rows=1000, on_exceed=evict_oldest(ts)".
+/// Null or empty `spec` removes it. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_quota(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = c_str_to_string(spec);
+    let result = if spec.trim().is_empty() {
+        h.set_quota(None)
+    } else {
+        Quota::parse(&spec).and_then(|q| h.set_quota(Some(q)))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_quota failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Access tracking
+// ========================================
+
+/// Enable (1) or disable (0) search hit tracking. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_access_tracking(
+    handle: LanceHandlePtr,
+    enabled: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.set_access_tracking(enabled != 0) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_access_tracking failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Write buffered search hits to the access table. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_flush_access_stats(
+    handle: LanceHandlePtr,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.flush_access_stats() {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("flush_access_stats failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Rows not searched within `idle_ms`, exported as an Arrow batch with columns
+/// (label, hits, last_access_ms); never-accessed rows have 0 for both counters.
+/// Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_cold_rows(
+    handle: LanceHandlePtr,
+    idle_ms: i64,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h.cold_rows(idle_ms).and_then(|batch| {
+        let rows = batch.num_rows();
+        export_batch(batch, out_schema, out_array).map(|_| rows)
+    });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("cold_rows failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Query log
+// ========================================
+
+/// Log every search for offline evaluation, e.g. "tag=baseline, vectors=full". An
+/// empty or NULL spec stops logging. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_query_log(
+    handle: LanceHandlePtr,
+    spec: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let spec = if spec.is_null() { String::new() } else { c_str_to_string(spec) };
+    let result = if spec.trim().is_empty() {
+        h.set_query_log(None)
+    } else {
+        QueryLog::parse(&spec).and_then(|log| h.set_query_log(Some(log)))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_query_log failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Re-run the queries logged under `tag` and export their drift as an Arrow batch
+/// with columns (logged_ms, query_hash, logged, returned, overlap, top_changed).
+/// Entries logged without their vector are not replayed. Returns the row count or
+/// -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_replay_queries(
+    handle: LanceHandlePtr,
+    tag: *const c_char,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || tag.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or tag");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let tag = c_str_to_string(tag);
+    let result = h
+        .replay_queries(&tag)
+        .and_then(|report| report.to_record_batch())
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("replay_queries failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Row expiry
+// ========================================
+
+/// Enable (1) or disable (0) hiding rows past their `expires_at` from searches.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_row_ttl(
+    handle: LanceHandlePtr,
+    enabled: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.set_row_ttl(enabled != 0) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_row_ttl failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Dimensionality reduction (PCA)
+// ========================================
+
+/// Fit a PCA projection to `target_dims` on the first `sample` vectors and write the
+/// `vector_pca` column. `index_type` >= 0 (a [`VectorIndexType`] value) also builds
+/// that index on the reduced column; -1 builds none. Writes the fraction of variance
+/// kept to `out_explained_variance`. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_train_pca(
+    handle: LanceHandlePtr,
+    target_dims: i32,
+    sample: i64,
+    index_type: i32,
+    out_explained_variance: *mut f64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = (if index_type < 0 {
+        Ok(None)
+    } else {
+        VectorIndexType::from_i32(index_type).map(Some)
+    })
+    .and_then(|index| {
+        metrics::observe(Op::IndexBuild, || {
+            h.train_pca(target_dims.max(0) as usize, sample.max(0) as usize, index)
+        })
+    });
+    match result {
+        Ok(pca) => {
+            if !out_explained_variance.is_null() {
+                *out_explained_variance = pca.explained_variance;
+            }
+            0
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("train_pca failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Search in the PCA-reduced space and rescore the `k * rescore_factor` candidates
+/// on the full vectors. Writes up to k results to `out_labels`/`out_distances`.
+/// Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_pca(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    rescore_factor: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let query_slice = slice::from_raw_parts(query, dim as usize);
+
+    match metrics::observe(Op::Search, || {
+        h.search_pca(
+            query_slice,
+            k.max(0) as usize,
+            nprobes.max(0) as usize,
+            rescore_factor.max(1) as usize,
+            None,
+            false,
+        )
+    }) {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search_pca failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Rotation (OPQ)
+// ========================================
+
+/// Train a rotation for `num_sub_vectors` PQ sub-vectors on the first `sample`
+/// vectors and rewrite the table rotated. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_train_rotation(
+    handle: LanceHandlePtr,
+    num_sub_vectors: i32,
+    sample: i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match metrics::observe(Op::IndexBuild, || {
+        h.train_rotation(num_sub_vectors.max(0) as usize, sample.max(0) as usize)
+    }) {
+        Ok(_) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("train_rotation failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Writer lease
+// ========================================
+
+/// Require writers to hold an advisory lease expiring `ttl_secs` after their last
+/// write, taking it for this process; 0 removes the requirement.
+/// Returns 0 or -1 on error (e.g. another process holds the lease).
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_writer_lease(
+    handle: LanceHandlePtr,
+    ttl_secs: i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    if ttl_secs < 0 {
+        write_err(err_buf, err_buf_len, "ttl_secs must not be negative");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.set_writer_lease(ttl_secs as u64) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_writer_lease failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Embedding model guard
+// ========================================
+
+/// Record the embedding model id for the table; an empty `model` clears it.
+/// Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_embedding_model(
+    handle: LanceHandlePtr,
+    model: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let model = c_str_to_string(model);
+    match h.set_embedding_model(Some(model.as_str())) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_embedding_model failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Quantize the table's int8 vectors over `min..=max` (see `crate::quantize`); the
+/// table must be empty. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_set_int8_range(
+    handle: LanceHandlePtr,
+    min: f32,
+    max: f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.set_int8_range(min, max) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_int8_range failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Re-embedding
+// ========================================
+
+/// Start re-embedding the table with `new_dim`-dimensional vectors (see
+/// `crate::reembed`). Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_reembed_begin(
+    handle: LanceHandlePtr,
+    new_dim: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    match h.reembed_begin(new_dim.max(0) as usize) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("reembed_begin failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Write the new vectors (`num` rows of `dim` floats, `dim` the re-embed dimension)
+/// of rows `labels`. Returns the number of rows written or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_reembed_write(
+    handle: LanceHandlePtr,
+    labels: *const i64,
+    vectors: *const f32,
+    num: i32,
+    dim: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || (num > 0 && (labels.is_null() || vectors.is_null())) {
+        write_err(err_buf, err_buf_len, "null handle, labels or vectors");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let num = num.max(0) as usize;
+    let (labels, vectors) = if num == 0 {
+        (&[][..], &[][..])
+    } else {
+        (slice::from_raw_parts(labels, num), slice::from_raw_parts(vectors, num * dim.max(0) as usize))
+    };
+    match h.reembed_write(labels, vectors) {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("reembed_write failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Create (or replace) a vector index on `column` ("vector", or "vector_next" during
+/// a re-embed); parameters as for `lance_detached_create_vector_index`. Returns 0 or
+/// -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_create_vector_index_on(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    index_type: i32,
+    num_partitions: i32,
+    num_sub_vectors: i32,
+    m: i32,
+    ef_construction: i32,
+    sample_rate: i32,
+    metric: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let column = c_str_to_string(column);
+    let params = vector_index_params(num_partitions, num_sub_vectors, m, ef_construction, sample_rate, metric);
+    let result = VectorIndexType::from_i32(index_type)
+        .and_then(|kind| metrics::observe(Op::IndexBuild, || h.create_vector_index_on(&column, kind, &params)));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("create_vector_index failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Search vector column `column` ("vector", or "vector_next" during a re-embed, with
+/// queries of the re-embed dimension). Writes up to k results to
+/// `out_labels`/`out_distances`. Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_column(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    ef: i32,
+    predicate: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let column = c_str_to_string(column);
+    let query_slice = slice::from_raw_parts(query, dim.max(0) as usize);
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let result = metrics::observe(Op::Search, || {
+        h.search_column(
+            &column,
+            query_slice,
+            k as usize,
+            nprobes as usize,
+            refine_factor_arg(refine_factor),
+            ef.max(0) as usize,
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    });
+    match result {
+        Ok(results) => {
+            let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
+            for (i, (label, dist)) in results.iter().enumerate() {
+                *out_labels.add(i) = *label;
+                *out_distances.add(i) = *dist;
+            }
+            scratch::recycle(results);
+            n as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Search multivector column `column` with `num_values` flattened query values, any
+/// number of vectors of the column's dimension, ranking rows by the sum over query
+/// vectors of the distance to the row's closest vector. Writes up to k results to
+/// `out_labels`/`out_distances`. Returns the number of results or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_multivector(
+    handle: LanceHandlePtr,
+    column: *const c_char,
+    queries: *const f32,
+    num_values: i32,
+    k: i32,
+    nprobes: i32,
+    predicate: *const c_char,
+    privileged: i32,
+    out_labels: *mut i64,
+    out_distances: *mut f32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || column.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or column");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let column = c_str_to_string(column);
+    let queries = if queries.is_null() {
+        &[][..]
+    } else {
+        slice::from_raw_parts(queries, num_values.max(0) as usize)
+    };
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    let result = metrics::observe(Op::Search, || {
+        h.search_multivector(
+            &column,
+            queries,
+            k.max(0) as usize,
+            nprobes as usize,
+            predicate.as_deref(),
+            privileged != 0,
+        )
+    });
+    match result {
+        Ok(results) => {
             let n = results.len();
+            metrics::add_rows(Op::Search, n as u64);
             for (i, (label, dist)) in results.iter().enumerate() {
                 *out_labels.add(i) = *label;
                 *out_distances.add(i) = *dist;
             }
+            scratch::recycle(results);
             n as i32
         }
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("search failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("multivector search failed: {}", e));
             -1
         }
     }
 }
 
-// ========================================
-// Count / Delete
-// ========================================
-
+/// Export every index of the table with its coverage as rows of (name, index_type,
+/// columns, indexed_rows, unindexed_rows, metric). Returns the number of rows or -1
+/// on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_count(
+pub unsafe extern "C" fn lance_detached_list_indices(
     handle: LanceHandlePtr,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
     err_buf: *mut c_char,
     err_buf_len: i32,
-) -> i64 {
+) -> i32 {
     if handle.is_null() {
         write_err(err_buf, err_buf_len, "null handle");
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    match h.count() {
-        Ok(n) => n as i64,
+    let result = h
+        .list_indices()
+        .and_then(|statuses| IndexStatus::to_record_batch(&statuses))
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("count failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("list_indices failed: {}", e));
             -1
         }
     }
 }
 
+/// Swap the new vectors in and drop the old ones, recording `model` (empty or NULL
+/// clears the recorded model). Reopen handles afterwards. Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_delete(
+pub unsafe extern "C" fn lance_detached_reembed_finish(
     handle: LanceHandlePtr,
-    label: i64,
+    model: *const c_char,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -364,47 +4970,87 @@ pub unsafe extern "C" fn lance_detached_delete(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    match h.delete(label) {
+    let model = c_str_to_string(model);
+    match h.reembed_finish(Some(model.as_str())) {
         Ok(()) => 0,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("delete failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("reembed_finish failed: {}", e));
             -1
         }
     }
 }
 
+// ========================================
+// Drift detection
+// ========================================
+
+/// Snapshot the running vector statistics as baseline `tag`. Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_delete_batch(
+pub unsafe extern "C" fn lance_detached_tag_drift_baseline(
     handle: LanceHandlePtr,
-    labels: *const i64,
-    count: i32,
+    tag: *const c_char,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
-    if handle.is_null() {
-        write_err(err_buf, err_buf_len, "null handle");
+    if handle.is_null() || tag.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or tag");
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    let label_slice = slice::from_raw_parts(labels, count as usize);
-    match h.delete_batch(label_slice) {
+    match h.tag_drift_baseline(&c_str_to_string(tag)) {
         Ok(()) => 0,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("delete_batch failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("tag_drift_baseline failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Compare current statistics against baseline `tag`, exported as an Arrow batch
+/// with columns (metric, baseline, current). Returns the row count or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_drift_report(
+    handle: LanceHandlePtr,
+    tag: *const c_char,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() || tag.is_null() {
+        write_err(err_buf, err_buf_len, "null handle or tag");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    let result = h
+        .drift_report(&c_str_to_string(tag))
+        .and_then(|report| report.to_record_batch())
+        .and_then(|batch| {
+            let rows = batch.num_rows();
+            export_batch(batch, out_schema, out_array).map(|_| rows)
+        });
+    match result {
+        Ok(rows) => rows as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("drift_report failed: {}", e));
             -1
         }
     }
 }
 
 // ========================================
-// ANN Index / Compact
+// Admission control
 // ========================================
 
+/// Set per-handle concurrency limits. Non-positive limits keep the current value;
+/// `queue_timeout_ms` < 0 keeps the current timeout and 0 waits indefinitely.
+/// Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_create_index(
+pub unsafe extern "C" fn lance_detached_set_admission_limits(
     handle: LanceHandlePtr,
-    num_partitions: i32,
-    num_sub_vectors: i32,
+    max_concurrent_searches: i32,
+    max_concurrent_index_builds: i32,
+    queue_timeout_ms: i64,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -413,20 +5059,41 @@ pub unsafe extern "C" fn lance_detached_create_index(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    match h.create_ann_index(num_partitions as u32, num_sub_vectors as u32) {
+    let current = h.admission_limits();
+    let limits = AdmissionLimits {
+        max_concurrent_searches: if max_concurrent_searches > 0 {
+            max_concurrent_searches as usize
+        } else {
+            current.max_concurrent_searches
+        },
+        max_concurrent_maintenance: if max_concurrent_index_builds > 0 {
+            max_concurrent_index_builds as usize
+        } else {
+            current.max_concurrent_maintenance
+        },
+        queue_timeout: if queue_timeout_ms >= 0 {
+            std::time::Duration::from_millis(queue_timeout_ms as u64)
+        } else {
+            current.queue_timeout
+        },
+        ..current
+    };
+    match h.set_admission_limits(limits) {
         Ok(()) => 0,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("create_index failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("set_admission_limits failed: {}", e));
             -1
         }
     }
 }
 
+/// Set how long background maintenance (index builds, compaction, merges) may pause
+/// at each yield point while searches are in flight. 0 disables yielding.
+/// Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_create_hnsw_index(
+pub unsafe extern "C" fn lance_detached_set_background_yield(
     handle: LanceHandlePtr,
-    m: i32,
-    ef_construction: i32,
+    max_yield_ms: i64,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -435,18 +5102,26 @@ pub unsafe extern "C" fn lance_detached_create_hnsw_index(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    match h.create_hnsw_index(m as u32, ef_construction as u32) {
+    let limits = AdmissionLimits {
+        max_background_yield: std::time::Duration::from_millis(max_yield_ms.max(0) as u64),
+        ..h.admission_limits()
+    };
+    match h.set_admission_limits(limits) {
         Ok(()) => 0,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("create_hnsw_index failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("set_background_yield failed: {}", e));
             -1
         }
     }
 }
 
+/// Cap the training sample of vector index builds on this handle to
+/// `max_memory_bytes`; 0 removes the cap. Build threads are bounded process-wide by
+/// the CPU pool size (see [`crate::runtime`]). Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_compact(
+pub unsafe extern "C" fn lance_detached_set_index_build_limits(
     handle: LanceHandlePtr,
+    max_memory_bytes: i64,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -455,25 +5130,123 @@ pub unsafe extern "C" fn lance_detached_compact(
         return -1;
     }
     let h = &*(handle as *mut LanceIndex);
-    match h.compact() {
+    let limits = BuildLimits {
+        max_memory_bytes: max_memory_bytes.max(0) as u64,
+    };
+    match h.set_build_limits(limits) {
         Ok(()) => 0,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("compact failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("set_index_build_limits failed: {}", e));
             -1
         }
     }
 }
 
 // ========================================
-// Get vector
+// Asynchronous calls
 // ========================================
 
+/// Completion callback of `lance_detached_search_async`: `n` results at `labels`
+/// and `distances`, valid only for the duration of the call. On failure `n` is -1
+/// and `error` holds the message (null otherwise).
+pub type LanceSearchDoneFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    labels: *const i64,
+    distances: *const f32,
+    n: i32,
+    error: *const c_char,
+);
+
+/// Completion callback of `lance_detached_add_batch_async`: the `n` assigned labels,
+/// valid only for the duration of the call. On failure `n` is -1, or -2 when a quota
+/// rejected the append, and `error` holds the message (null otherwise).
+pub type LanceAddDoneFn =
+    unsafe extern "C" fn(user_data: *mut c_void, labels: *const i64, n: i32, error: *const c_char);
+
+/// Pass an error message to a completion callback as a NUL-terminated string.
+fn error_c_string(context: &str, e: &anyhow::Error) -> std::ffi::CString {
+    let msg = format!("{} failed: {}", context, e).replace('\0', " ");
+    std::ffi::CString::new(msg).unwrap_or_default()
+}
+
+/// Submit a search to the Lance runtime and return immediately; `callback` runs on a
+/// runtime thread when it completes. Arguments are as `lance_detached_search` (the
+/// query and predicate are copied). The handle must stay open until the callback has
+/// run. Returns 0 if the search was submitted or -1 on error, in which case the
+/// callback is not called.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_get_vector(
+pub unsafe extern "C" fn lance_detached_search_async(
     handle: LanceHandlePtr,
-    label: i64,
-    out_vec: *mut f32,
-    capacity: i32,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    privileged: i32,
+    callback: Option<LanceSearchDoneFn>,
+    user_data: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let Some(callback) = callback else {
+        write_err(err_buf, err_buf_len, "null callback");
+        return -1;
+    };
+    let query = slice::from_raw_parts(query, dim as usize).to_vec();
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    // Raw pointers are not Send; the caller keeps both valid until the callback.
+    let (handle, user_data) = (handle as usize, user_data as usize);
+    runtime::spawn_blocking(move || {
+        let h = &*(handle as *mut LanceIndex);
+        let result = metrics::observe(Op::Search, || {
+            h.search(
+                &query,
+                k as usize,
+                nprobes as usize,
+                refine_factor_arg(refine_factor),
+                0,
+                predicate.as_deref(),
+                privileged != 0,
+            )
+        });
+        match result {
+            Ok(results) => {
+                metrics::add_rows(Op::Search, results.len() as u64);
+                let (labels, distances): (Vec<i64>, Vec<f32>) = results.into_iter().unzip();
+                callback(
+                    user_data as *mut c_void,
+                    labels.as_ptr(),
+                    distances.as_ptr(),
+                    labels.len() as i32,
+                    std::ptr::null(),
+                );
+            }
+            Err(e) => {
+                let msg = error_c_string("search", &e);
+                callback(user_data as *mut c_void, std::ptr::null(), std::ptr::null(), -1, msg.as_ptr());
+            }
+        }
+    });
+    0
+}
+
+/// Submit an append of `num` vectors to the Lance runtime and return immediately;
+/// `callback` runs on a runtime thread when it completes. `vectors` is not copied:
+/// it and the handle must stay valid until the callback has run. Returns 0 if the
+/// append was submitted or -1 on error, in which case the callback is not called.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_add_batch_async(
+    handle: LanceHandlePtr,
+    vectors: *const f32,
+    num: i32,
+    dim: i32,
+    callback: Option<LanceAddDoneFn>,
+    user_data: *mut c_void,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
@@ -481,61 +5254,491 @@ pub unsafe extern "C" fn lance_detached_get_vector(
         write_err(err_buf, err_buf_len, "null handle");
         return -1;
     }
+    let Some(callback) = callback else {
+        write_err(err_buf, err_buf_len, "null callback");
+        return -1;
+    };
     let h = &*(handle as *mut LanceIndex);
-    match h.get_vector(label) {
-        Ok(vec) => {
-            let dim = vec.len();
-            if dim > capacity as usize {
-                write_err(err_buf, err_buf_len, "output buffer too small");
-                return -1;
+    if dim as usize != h.dimension() {
+        write_err(
+            err_buf,
+            err_buf_len,
+            &format!("dimension mismatch: expected {}, got {}", h.dimension(), dim),
+        );
+        return -1;
+    }
+    let (handle, vectors, user_data) = (handle as usize, vectors as usize, user_data as usize);
+    runtime::spawn_blocking(move || {
+        let h = &*(handle as *mut LanceIndex);
+        let vec_slice = slice::from_raw_parts(vectors as *const f32, num as usize * h.dimension());
+        match metrics::observe(Op::Add, || h.add_batch(vec_slice, num as usize)) {
+            Ok(labels) => {
+                metrics::add_rows(Op::Add, labels.len() as u64);
+                callback(user_data as *mut c_void, labels.as_ptr(), labels.len() as i32, std::ptr::null());
+            }
+            Err(e) => {
+                let msg = error_c_string("add_batch", &e);
+                callback(user_data as *mut c_void, std::ptr::null(), append_error_code(&e), msg.as_ptr());
             }
-            std::ptr::copy_nonoverlapping(vec.as_ptr(), out_vec, dim);
-            dim as i32
         }
+    });
+    0
+}
+
+// ========================================
+// Task handles
+// ========================================
+
+/// `lance_task_poll` results.
+const TASK_PENDING: i32 = 0;
+const TASK_READY: i32 = 1;
+const TASK_ERROR: i32 = 2;
+
+/// Submit a search to the Lance runtime and return its task id at once; poll it with
+/// `lance_task_poll` and fetch the (label, distance) rows with `lance_task_take_result`.
+/// Arguments are as `lance_detached_search` (the query and predicate are copied). The
+/// handle must stay open until the task finishes or is discarded. Returns the task id
+/// or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_search_submit(
+    handle: LanceHandlePtr,
+    query: *const f32,
+    dim: i32,
+    k: i32,
+    nprobes: i32,
+    refine_factor: i32,
+    predicate: *const c_char,
+    privileged: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let query = slice::from_raw_parts(query, dim as usize).to_vec();
+    let predicate = (!predicate.is_null()).then(|| c_str_to_string(predicate));
+    // Raw pointers are not Send; the caller keeps the handle open until the task is
+    // finished or discarded.
+    let handle = handle as usize;
+    task::submit(move || {
+        let h = &*(handle as *mut LanceIndex);
+        let results = metrics::observe(Op::Search, || {
+            h.search(
+                &query,
+                k as usize,
+                nprobes as usize,
+                refine_factor_arg(refine_factor),
+                0,
+                predicate.as_deref(),
+                privileged != 0,
+            )
+        })?;
+        metrics::add_rows(Op::Search, results.len() as u64);
+        let (labels, distances): (Vec<i64>, Vec<f32>) = results.into_iter().unzip();
+        Ok(RecordBatch::try_from_iter([
+            ("label", Arc::new(Int64Array::from(labels)) as _),
+            ("distance", Arc::new(Float32Array::from(distances)) as _),
+        ])?)
+    }) as i64
+}
+
+/// Submit an append of `num` vectors (copied) to the Lance runtime and return its
+/// task id at once; the task result has one `label` row per vector. The handle must
+/// stay open until the task finishes or is discarded. Returns the task id or -1 on
+/// error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_detached_add_batch_submit(
+    handle: LanceHandlePtr,
+    vectors: *const f32,
+    num: i32,
+    dim: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i64 {
+    if handle.is_null() {
+        write_err(err_buf, err_buf_len, "null handle");
+        return -1;
+    }
+    let h = &*(handle as *mut LanceIndex);
+    if dim as usize != h.dimension() {
+        write_err(
+            err_buf,
+            err_buf_len,
+            &format!("dimension mismatch: expected {}, got {}", h.dimension(), dim),
+        );
+        return -1;
+    }
+    let vectors = slice::from_raw_parts(vectors, num as usize * h.dimension()).to_vec();
+    let handle = handle as usize;
+    task::submit(move || {
+        let h = &*(handle as *mut LanceIndex);
+        let labels = metrics::observe(Op::Add, || h.add_batch(&vectors, num as usize))?;
+        metrics::add_rows(Op::Add, labels.len() as u64);
+        Ok(RecordBatch::try_from_iter([("label", Arc::new(Int64Array::from(labels)) as _)])?)
+    }) as i64
+}
+
+/// Task status: 0 pending, 1 ready, 2 failed (the error is reported by
+/// `lance_task_take_result`), -1 for an unknown id.
+#[no_mangle]
+pub extern "C" fn lance_task_poll(task_id: i64) -> i32 {
+    match task::poll(task_id as u64) {
+        Some(TaskStatus::Pending) => TASK_PENDING,
+        Some(TaskStatus::Ready) => TASK_READY,
+        Some(TaskStatus::Error) => TASK_ERROR,
+        None => -1,
+    }
+}
+
+/// Export a finished task's result as an Arrow struct array and forget the task.
+/// Returns the number of rows, or -1 on error: the task failed (its error is written
+/// to `err_buf`), is still pending, or is unknown. Only a pending task survives the call.
+#[no_mangle]
+pub unsafe extern "C" fn lance_task_take_result(
+    task_id: i64,
+    out_schema: *mut c_void,
+    out_array: *mut c_void,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let result = task::take_result(task_id as u64).and_then(|batch| {
+        let rows = batch.num_rows() as i32;
+        export_batch(batch, out_schema, out_array)?;
+        Ok(rows)
+    });
+    match result {
+        Ok(rows) => rows,
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("get_vector failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("{}", e));
             -1
         }
     }
 }
 
+/// Forget a task without taking its result. A running task is waited for and its
+/// result dropped, so the handle it ran on may be closed once this returns.
+#[no_mangle]
+pub extern "C" fn lance_task_discard(task_id: i64) {
+    task::discard(task_id as u64);
+}
+
 // ========================================
-// Bulk vector export
+// Runtime configuration
 // ========================================
 
-/// Export all vectors from a detached index.
-/// Returns count of vectors. Fills out_labels and out_vectors (caller-allocated).
-/// out_vectors must hold count * dimension floats.
-/// Call with null out_labels/out_vectors to get the count first.
+/// Set the I/O and CPU pool thread counts (0 keeps the current count) and write the effective
+/// counts to `out_io_threads`/`out_cpu_threads` (either may be null). Must run before
+/// any other Lance call to take effect; afterwards only the current configuration is
+/// accepted. Returns 0 or -1 on error.
 #[no_mangle]
-pub unsafe extern "C" fn lance_detached_get_all_vectors(
-    handle: LanceHandlePtr,
-    out_labels: *mut i64,
-    out_vectors: *mut f32,
-    out_count: *mut i64,
+pub unsafe extern "C" fn lance_runtime_configure(
+    io_threads: i32,
+    cpu_threads: i32,
+    out_io_threads: *mut i32,
+    out_cpu_threads: *mut i32,
     err_buf: *mut c_char,
     err_buf_len: i32,
 ) -> i32 {
-    if handle.is_null() {
-        write_err(err_buf, err_buf_len, "null handle");
+    if io_threads < 0 || cpu_threads < 0 {
+        write_err(err_buf, err_buf_len, "thread counts must not be negative");
         return -1;
     }
-    let h = &*(handle as *mut LanceIndex);
-    match h.get_all_vectors() {
-        Ok((labels, vectors)) => {
-            let count = labels.len();
-            if !out_count.is_null() {
-                *out_count = count as i64;
+    match runtime::configure(io_threads as usize, cpu_threads as usize) {
+        Ok(config) => {
+            if !out_io_threads.is_null() {
+                *out_io_threads = config.io_threads as i32;
             }
-            if !out_labels.is_null() && !out_vectors.is_null() {
-                std::ptr::copy_nonoverlapping(labels.as_ptr(), out_labels, count);
-                std::ptr::copy_nonoverlapping(vectors.as_ptr(), out_vectors, vectors.len());
+            if !out_cpu_threads.is_null() {
+                *out_cpu_threads = config.cpu_threads as i32;
             }
-            count as i32
+            0
         }
         Err(e) => {
-            write_err(err_buf, err_buf_len, &format!("get_all_vectors failed: {}", e));
+            write_err(err_buf, err_buf_len, &format!("runtime_configure failed: {}", e));
+            -1
+        }
+    }
+}
+
+// ========================================
+// Read limits
+// ========================================
+
+/// Set the rows and bytes one call collecting a whole result (`get_all_vectors`, an
+/// unordered scan) may read before it fails (see `crate::read_limit`): 0 lifts a
+/// limit, a negative value keeps the current one. Writes the effective limits to
+/// `out_max_rows`/`out_max_bytes` (either may be null). Returns 0.
+#[no_mangle]
+pub unsafe extern "C" fn lance_read_limits_configure(
+    max_rows: i64,
+    max_bytes: i64,
+    out_max_rows: *mut i64,
+    out_max_bytes: *mut i64,
+) -> i32 {
+    let current = read_limit::limits();
+    let limits = ReadLimits {
+        max_rows: if max_rows < 0 { current.max_rows } else { max_rows as u64 },
+        max_bytes: if max_bytes < 0 { current.max_bytes } else { max_bytes as u64 },
+    };
+    read_limit::configure(limits);
+    if !out_max_rows.is_null() {
+        *out_max_rows = limits.max_rows as i64;
+    }
+    if !out_max_bytes.is_null() {
+        *out_max_bytes = limits.max_bytes as i64;
+    }
+    0
+}
+
+// ========================================
+// Storage options
+// ========================================
+
+/// Replace the default storage options of connections opened afterwards with
+/// `count` `key=value` strings; 0 clears them. Returns the number of options set or
+/// -1 on error, leaving the defaults unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn lance_set_default_storage_options(
+    pairs: *const *const c_char,
+    count: i32,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let pairs: Vec<String> = if pairs.is_null() || count <= 0 {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(pairs, count as usize).iter().map(|p| c_str_to_string(*p)).collect()
+    };
+    match storage_options::parse_pairs(&pairs) {
+        Ok(options) => {
+            storage_options::set_defaults(options);
+            storage_options::default_keys().len() as i32
+        }
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("set_default_storage_options failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Credential callback: write NUL-terminated strings into `key_id`, `secret_key` and
+/// `token` (each buffer holds the given length; an empty token means none), set
+/// `expires_at_ms` to the epoch-millisecond expiry (0 if the credentials do not
+/// expire) and return 0, or return non-zero on failure.
+pub type LanceCredentialFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    key_id: *mut c_char,
+    key_id_len: i32,
+    secret_key: *mut c_char,
+    secret_key_len: i32,
+    token: *mut c_char,
+    token_len: i32,
+    expires_at_ms: *mut i64,
+) -> i32;
+
+/// Buffer sizes offered to a credential callback; STS session tokens run to a few KB.
+const CREDENTIAL_KEY_LEN: usize = 256;
+const CREDENTIAL_TOKEN_LEN: usize = 8192;
+
+/// Register the process-wide credential callback for tables on S3 opened or created
+/// afterwards (a null callback unregisters it). It is called on first use and again
+/// shortly before the returned credentials expire. `user_data` must stay valid, and
+/// the callback thread-safe, while registered. Returns 0.
+#[no_mangle]
+pub unsafe extern "C" fn lance_register_credential_provider(
+    callback: Option<LanceCredentialFn>,
+    user_data: *mut c_void,
+    _err_buf: *mut c_char,
+    _err_buf_len: i32,
+) -> i32 {
+    let Some(callback) = callback else {
+        credentials::register(None);
+        return 0;
+    };
+    // Raw pointers are not Send; the caller guarantees the data is shareable.
+    let user_data = user_data as usize;
+    credentials::register(Some(Arc::new(move || {
+        let mut key_id = vec![0u8; CREDENTIAL_KEY_LEN];
+        let mut secret_key = vec![0u8; CREDENTIAL_KEY_LEN];
+        let mut token = vec![0u8; CREDENTIAL_TOKEN_LEN];
+        let mut expires_at_ms = 0i64;
+        let rc = callback(
+            user_data as *mut c_void,
+            key_id.as_mut_ptr() as *mut c_char,
+            key_id.len() as i32,
+            secret_key.as_mut_ptr() as *mut c_char,
+            secret_key.len() as i32,
+            token.as_mut_ptr() as *mut c_char,
+            token.len() as i32,
+            &mut expires_at_ms,
+        );
+        if rc != 0 {
+            return Err(anyhow::anyhow!("credential callback returned {}", rc));
+        }
+        let text = |mut buf: Vec<u8>| {
+            // Unterminated output is cut at the buffer end
+            let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+            buf.truncate(len);
+            String::from_utf8(buf).map_err(|_| anyhow::anyhow!("credential callback returned invalid UTF-8"))
+        };
+        let token = text(token)?;
+        Ok(Credentials {
+            key_id: text(key_id)?,
+            secret_key: text(secret_key)?,
+            token: (!token.is_empty()).then_some(token),
+            expires_at_ms: (expires_at_ms != 0).then_some(expires_at_ms),
+        })
+    })));
+    0
+}
+
+// ========================================
+// Disk cache
+// ========================================
+
+/// Cache data and index files of tables opened from object storage afterwards in the
+/// local directory `dir`, up to `max_bytes`. A null or empty `dir` stops caching for
+/// tables opened afterwards. Returns 0 or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn lance_disk_cache_configure(
+    dir: *const c_char,
+    max_bytes: i64,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let dir_str = c_str_to_string(dir);
+    let config = if dir_str.is_empty() {
+        None
+    } else if max_bytes <= 0 {
+        write_err(err_buf, err_buf_len, "disk cache size must be positive");
+        return -1;
+    } else {
+        Some((dir_str.as_str(), max_bytes as u64))
+    };
+    match disk_cache::configure(config) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("disk_cache_configure failed: {}", e));
+            -1
+        }
+    }
+}
+
+/// Write the disk cache figures to the out-parameters (each may be null). Returns 1,
+/// or 0 without touching them when no cache is configured.
+#[no_mangle]
+pub unsafe extern "C" fn lance_disk_cache_stats(
+    out_max_bytes: *mut i64,
+    out_bytes: *mut i64,
+    out_files: *mut i64,
+    out_hits: *mut i64,
+    out_misses: *mut i64,
+) -> i32 {
+    let Some(stats) = disk_cache::stats() else {
+        return 0;
+    };
+    for (out, value) in [
+        (out_max_bytes, stats.max_bytes),
+        (out_bytes, stats.bytes),
+        (out_files, stats.files),
+        (out_hits, stats.hits),
+        (out_misses, stats.misses),
+    ] {
+        if !out.is_null() {
+            *out = value as i64;
+        }
+    }
+    1
+}
+
+// ========================================
+// Metrics
+// ========================================
+
+/// Start the embedded Prometheus endpoint on `bind_addr` (e.g. "0.0.0.0:9464").
+/// Returns the bound port or -1 on error. Only available with the `prometheus` feature.
+#[cfg(feature = "prometheus")]
+#[no_mangle]
+pub unsafe extern "C" fn lance_metrics_start_exporter(
+    bind_addr: *const c_char,
+    err_buf: *mut c_char,
+    err_buf_len: i32,
+) -> i32 {
+    let bind_addr_str = c_str_to_string(bind_addr);
+    match metrics::start_exporter(&bind_addr_str) {
+        Ok(addr) => addr.port() as i32,
+        Err(e) => {
+            write_err(err_buf, err_buf_len, &format!("metrics exporter failed: {}", e));
             -1
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::ffi_stream::ArrowArrayStreamReader;
+
+    fn read_pairs(stream: FFI_ArrowArrayStream) -> Vec<Vec<(i64, i64)>> {
+        ArrowArrayStreamReader::try_new(stream)
+            .unwrap()
+            .map(|batch| {
+                let batch = batch.unwrap();
+                let column = |i: usize| batch.column(i).as_any().downcast_ref::<Int64Array>().unwrap().clone();
+                column(0).values().iter().copied().zip(column(1).values().iter().copied()).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_exports_the_label_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test_ffi_merge.lance");
+        let db_path = db_path.to_str().unwrap();
+
+        // Three appends, so the source is read back as several batches
+        let source = LanceIndex::create(db_path, 2, "l2", "source").unwrap();
+        for i in 0..3 {
+            source.add_batch(&[i as f32, 0.0, i as f32, 1.0], 2).unwrap();
+        }
+        let target = LanceIndex::create(db_path, 2, "l2", "target").unwrap();
+        target.add_vector(&[9.0, 9.0]).unwrap();
+
+        let live = [0i64, 2, 3, 5];
+        let mut stream = FFI_ArrowArrayStream::empty();
+        let mut err = [0 as c_char; 256];
+        let merged = unsafe {
+            lance_detached_merge(
+                &target as *const LanceIndex as LanceHandlePtr,
+                &source as *const LanceIndex as LanceHandlePtr,
+                live.as_ptr(),
+                live.len() as i32,
+                0,
+                std::ptr::null(),
+                &mut stream as *mut FFI_ArrowArrayStream as *mut c_void,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                err.as_mut_ptr(),
+                err.len() as i32,
+            )
+        };
+        assert_eq!(merged, 4);
+        let mut mapping: Vec<(i64, i64)> = read_pairs(stream).into_iter().flatten().collect();
+        mapping.sort();
+        assert_eq!(mapping.iter().map(|(old, _)| *old).collect::<Vec<_>>(), live);
+        let mut new_labels: Vec<i64> = mapping.iter().map(|(_, new)| *new).collect();
+        new_labels.sort();
+        assert_eq!(new_labels, vec![1, 2, 3, 4]);
+        assert_eq!(target.count().unwrap(), 5);
+
+        // The mapping is exported in batches of the requested size
+        let mut stream = FFI_ArrowArrayStream::empty();
+        let pairs: Vec<(i64, i64)> = (0..5).map(|i| (i, i + 10)).collect();
+        unsafe { export_label_pairs(pairs.clone(), 2, &mut stream as *mut FFI_ArrowArrayStream as *mut c_void) };
+        let batches = read_pairs(stream);
+        assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(batches.concat(), pairs);
+    }
+}
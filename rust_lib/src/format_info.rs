@@ -0,0 +1,181 @@
+//! Storage-format compatibility of a table with this build.
+//!
+//! A table written by a newer Lance may use feature flags or data file versions this
+//! build does not know. Reading such a table fails cleanly, but an older writer could
+//! commit fragments or manifests the newer features rely on being absent. [`FormatInfo`]
+//! reports what the table's manifest declares and whether this build can safely write
+//! it; handles opened on a table that fails the check refuse writes.
+
+use anyhow::Result;
+use arrow_array::{BooleanArray, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance::table::format::{Fragment, Manifest};
+use lance_table::feature_flags::{can_read_dataset, can_write_dataset};
+use lance_table::format::WriterVersion;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatInfo {
+    /// Manifest (table) version the report is for.
+    pub version: u64,
+    /// Library that wrote the manifest, e.g. `lance 0.22.0`, if recorded.
+    pub writer: Option<String>,
+    /// The Lance library of this build.
+    pub library: String,
+    /// Declared data storage format, e.g. `lance 2.0`.
+    pub storage_format: String,
+    /// Distinct `major.minor` versions of the table's data files, ascending.
+    pub file_versions: Vec<String>,
+    pub reader_flags: u64,
+    pub writer_flags: u64,
+    pub can_read: bool,
+    pub can_write: bool,
+    /// The writer is a newer Lance than this build. Informational: newer writers
+    /// usually produce tables older readers handle, which the flags decide.
+    pub written_by_newer: bool,
+    /// Why the table cannot be read or written, if it cannot.
+    pub problems: Vec<String>,
+}
+
+impl FormatInfo {
+    pub fn from_manifest(manifest: &Manifest) -> Self {
+        let library = WriterVersion::default();
+        let mut problems = Vec::new();
+
+        let can_read = can_read_dataset(manifest.reader_feature_flags);
+        if !can_read {
+            problems.push(format!("unknown reader feature flags {}", manifest.reader_feature_flags));
+        }
+        let mut can_write = can_write_dataset(manifest.writer_feature_flags);
+        if !can_write {
+            problems.push(format!("unknown writer feature flags {}", manifest.writer_feature_flags));
+        }
+        let format = &manifest.data_storage_format;
+        if let Err(e) = format.lance_file_version() {
+            can_write = false;
+            problems.push(format!("unsupported storage format version {}: {}", format.version, e));
+        }
+        if let Err(e) = Fragment::try_infer_version(&manifest.fragments) {
+            can_write = false;
+            problems.push(format!("data files: {}", e));
+        }
+
+        let file_versions: BTreeSet<(u32, u32)> = manifest
+            .fragments
+            .iter()
+            .flat_map(|fragment| &fragment.files)
+            .map(|file| (file.file_major_version, file.file_minor_version))
+            .collect();
+        let written_by_newer = match (manifest.writer_version.as_ref().and_then(|w| w.semver()), library.semver()) {
+            (Some(writer), Some(ours)) => (writer.0, writer.1, writer.2) > (ours.0, ours.1, ours.2),
+            _ => false,
+        };
+
+        Self {
+            version: manifest.version,
+            writer: manifest
+                .writer_version
+                .as_ref()
+                .map(|w| format!("{} {}", w.library, w.version)),
+            library: format!("{} {}", library.library, library.version),
+            storage_format: format!("{} {}", format.file_format, format.version),
+            file_versions: file_versions
+                .into_iter()
+                .map(|(major, minor)| format!("{}.{}", major, minor))
+                .collect(),
+            reader_flags: manifest.reader_feature_flags,
+            writer_flags: manifest.writer_feature_flags,
+            can_read,
+            can_write,
+            written_by_newer,
+            problems,
+        }
+    }
+
+    /// Why writes must be refused, if they must.
+    pub fn write_block(&self) -> Option<String> {
+        if self.can_write {
+            return None;
+        }
+        let writer = self.writer.as_deref().unwrap_or("an unknown writer");
+        Some(format!(
+            "table was written by {} with features {} does not support ({})",
+            writer,
+            self.library,
+            self.problems.join("; ")
+        ))
+    }
+
+    /// One row: (version, writer, library, storage_format, file_versions,
+    /// reader_flags, writer_flags, can_read, can_write, written_by_newer, problems).
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("version", DataType::Int64, false),
+            Field::new("writer", DataType::Utf8, true),
+            Field::new("library", DataType::Utf8, false),
+            Field::new("storage_format", DataType::Utf8, false),
+            Field::new("file_versions", DataType::Utf8, false),
+            Field::new("reader_flags", DataType::Int64, false),
+            Field::new("writer_flags", DataType::Int64, false),
+            Field::new("can_read", DataType::Boolean, false),
+            Field::new("can_write", DataType::Boolean, false),
+            Field::new("written_by_newer", DataType::Boolean, false),
+            Field::new("problems", DataType::Utf8, true),
+        ]));
+        let problems = (!self.problems.is_empty()).then(|| self.problems.join("; "));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(vec![self.version as i64])),
+            Arc::new(StringArray::from(vec![self.writer.as_deref()])),
+            Arc::new(StringArray::from(vec![self.library.as_str()])),
+            Arc::new(StringArray::from(vec![self.storage_format.as_str()])),
+            Arc::new(StringArray::from(vec![self.file_versions.join(",")])),
+            Arc::new(Int64Array::from(vec![self.reader_flags as i64])),
+            Arc::new(Int64Array::from(vec![self.writer_flags as i64])),
+            Arc::new(BooleanArray::from(vec![self.can_read])),
+            Arc::new(BooleanArray::from(vec![self.can_write])),
+            Arc::new(BooleanArray::from(vec![self.written_by_newer])),
+            Arc::new(StringArray::from(vec![problems])),
+        ])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lance_table::feature_flags::FLAG_UNKNOWN;
+
+    fn info(writer_flags: u64, writer: &str) -> FormatInfo {
+        FormatInfo {
+            version: 3,
+            writer: Some(format!("lance {}", writer)),
+            library: "lance 0.22.0".to_string(),
+            storage_format: "lance 2.0".to_string(),
+            file_versions: vec!["2.0".to_string()],
+            reader_flags: 0,
+            writer_flags,
+            can_read: true,
+            can_write: can_write_dataset(writer_flags),
+            written_by_newer: false,
+            problems: if can_write_dataset(writer_flags) {
+                Vec::new()
+            } else {
+                vec![format!("unknown writer feature flags {}", writer_flags)]
+            },
+        }
+    }
+
+    #[test]
+    fn test_write_block() {
+        assert_eq!(info(1, "0.22.0").write_block(), None);
+        let block = info(FLAG_UNKNOWN, "9.0.0").write_block().unwrap();
+        assert!(block.contains("lance 9.0.0"), "{}", block);
+        assert!(block.contains("unknown writer feature flags 16"), "{}", block);
+
+        let batch = info(FLAG_UNKNOWN, "9.0.0").to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), 11);
+        assert!(batch.column(10).is_valid(0));
+        assert!(info(1, "0.22.0").to_record_batch().unwrap().column(10).is_null(0));
+    }
+}
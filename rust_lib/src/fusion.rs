@@ -0,0 +1,156 @@
+//! Fusion of vector and full-text hits for hybrid search.
+//!
+//! The vector side ranks by distance (lower is better) and the full-text side by
+//! BM25 score (higher is better), on unrelated scales. A [`Fusion`] turns both hit
+//! lists into one ranking with a fused score, higher is better:
+//!
+//! - `Rrf`: reciprocal rank fusion, `1 / (k + rank)` summed over both lists; only
+//!   ranks matter, so it needs no tuning beyond `k`.
+//! - `Linear`: both sides min-max normalized to [0, 1] (distances inverted), then
+//!   `vector_weight * vector + (1 - vector_weight) * text`.
+//! - `MaxScore`: the larger of the two normalized scores.
+//!
+//! A hit missing from one list scores 0 on that side.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Default `k` of reciprocal rank fusion.
+pub const RRF_K: f32 = 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fusion {
+    Rrf { k: f32 },
+    Linear { vector_weight: f32 },
+    MaxScore,
+}
+
+impl Default for Fusion {
+    fn default() -> Self {
+        Fusion::Rrf { k: RRF_K }
+    }
+}
+
+impl Fusion {
+    /// The strategy for an FFI `kind` (0 = RRF, 1 = linear, 2 = max score). A
+    /// non-positive `rrf_k` selects [`RRF_K`]; `vector_weight` is only read by the
+    /// linear strategy and must lie in [0, 1].
+    pub fn from_ffi(kind: i32, rrf_k: f32, vector_weight: f32) -> Result<Self> {
+        match kind {
+            0 if rrf_k.is_nan() => Err(anyhow!("invalid RRF k {}", rrf_k)),
+            0 => Ok(Fusion::Rrf { k: if rrf_k > 0.0 { rrf_k } else { RRF_K } }),
+            1 if !(0.0..=1.0).contains(&vector_weight) => {
+                Err(anyhow!("vector weight must be between 0 and 1, got {}", vector_weight))
+            }
+            1 => Ok(Fusion::Linear { vector_weight }),
+            2 => Ok(Fusion::MaxScore),
+            other => Err(anyhow!("unknown fusion strategy {}", other)),
+        }
+    }
+
+    /// Fuse `vector_hits` (label, distance; nearest first) and `text_hits` (label,
+    /// score; best first) into the `k` best (label, fused score), best first. Ties
+    /// keep the label order of the vector hits, then the text hits.
+    pub fn fuse(&self, vector_hits: &[(i64, f32)], text_hits: &[(i64, f32)], k: usize) -> Vec<(i64, f32)> {
+        let mut order: Vec<i64> = Vec::with_capacity(vector_hits.len() + text_hits.len());
+        let mut scores: HashMap<i64, (f32, f32)> = HashMap::new();
+        let vector_scores = match self {
+            Fusion::Rrf { k } => reciprocal_ranks(vector_hits, *k),
+            _ => normalize(vector_hits, true),
+        };
+        let text_scores = match self {
+            Fusion::Rrf { k } => reciprocal_ranks(text_hits, *k),
+            _ => normalize(text_hits, false),
+        };
+        for (label, score) in vector_scores {
+            let entry = scores.entry(label).or_insert_with(|| {
+                order.push(label);
+                (0.0, 0.0)
+            });
+            entry.0 = entry.0.max(score);
+        }
+        for (label, score) in text_scores {
+            let entry = scores.entry(label).or_insert_with(|| {
+                order.push(label);
+                (0.0, 0.0)
+            });
+            entry.1 = entry.1.max(score);
+        }
+
+        let mut fused: Vec<(i64, f32)> = order
+            .into_iter()
+            .map(|label| {
+                let (vector, text) = scores[&label];
+                let score = match self {
+                    Fusion::Rrf { .. } => vector + text,
+                    Fusion::Linear { vector_weight } => vector_weight * vector + (1.0 - vector_weight) * text,
+                    Fusion::MaxScore => vector.max(text),
+                };
+                (label, score)
+            })
+            .collect();
+        // Stable, so ties keep first-seen order
+        fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+        fused.truncate(k);
+        fused
+    }
+}
+
+/// `1 / (k + rank)` per hit, rank counted from 1.
+fn reciprocal_ranks(hits: &[(i64, f32)], k: f32) -> Vec<(i64, f32)> {
+    hits.iter()
+        .enumerate()
+        .map(|(i, (label, _))| (*label, 1.0 / (k + (i + 1) as f32)))
+        .collect()
+}
+
+/// Min-max normalize to [0, 1], best 1; `lower_is_better` for distances. A list of
+/// equal values normalizes to 1.
+fn normalize(hits: &[(i64, f32)], lower_is_better: bool) -> Vec<(i64, f32)> {
+    let (min, max) = hits
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), (_, v)| (min.min(*v), max.max(*v)));
+    let range = max - min;
+    hits.iter()
+        .map(|(label, v)| {
+            let score = if range > 0.0 { (v - min) / range } else { 1.0 };
+            (*label, if lower_is_better && range > 0.0 { 1.0 - score } else { score })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies() {
+        let vector_hits = [(1, 0.0), (2, 0.5), (3, 1.0)];
+        let text_hits = [(3, 8.0), (4, 4.0), (1, 2.0)];
+
+        // Labels 1 and 3 appear in both lists
+        let rrf = Fusion::Rrf { k: 1.0 }.fuse(&vector_hits, &text_hits, 10);
+        let labels: Vec<i64> = rrf.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec![1, 3, 2, 4]);
+        assert!((rrf[0].1 - (1.0 / 2.0 + 1.0 / 4.0)).abs() < 1e-6);
+
+        let semantic = Fusion::Linear { vector_weight: 1.0 }.fuse(&vector_hits, &text_hits, 2);
+        assert_eq!(semantic, vec![(1, 1.0), (2, 0.5)]);
+        let lexical = Fusion::Linear { vector_weight: 0.0 }.fuse(&vector_hits, &text_hits, 2);
+        assert_eq!(lexical, vec![(3, 1.0), (4, 1.0 / 3.0)]);
+
+        let max = Fusion::MaxScore.fuse(&vector_hits, &text_hits, 10);
+        assert_eq!(max[..2], [(1, 1.0), (3, 1.0)]);
+        assert!(Fusion::MaxScore.fuse(&[], &[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_from_ffi() {
+        assert_eq!(Fusion::from_ffi(0, 0.0, 0.0).unwrap(), Fusion::default());
+        assert_eq!(Fusion::from_ffi(0, 10.0, 0.0).unwrap(), Fusion::Rrf { k: 10.0 });
+        assert_eq!(Fusion::from_ffi(1, 0.0, 0.7).unwrap(), Fusion::Linear { vector_weight: 0.7 });
+        assert_eq!(Fusion::from_ffi(2, 0.0, 5.0).unwrap(), Fusion::MaxScore);
+        assert!(Fusion::from_ffi(1, 0.0, 1.5).is_err());
+        assert!(Fusion::from_ffi(3, 0.0, 0.0).is_err());
+    }
+}
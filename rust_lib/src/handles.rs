@@ -0,0 +1,164 @@
+//! Registry of the handles open in this process, for diagnostics.
+//!
+//! Every `LanceIndex` registers a [`HandleEntry`] when it is created and keeps the
+//! only strong reference to it, so an entry disappears with its handle. A handle
+//! that stays listed long after its last operation was probably leaked by the host.
+
+use anyhow::Result;
+use arrow_array::{BooleanArray, Int64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+
+use crate::access::now_ms;
+
+static HANDLES: LazyLock<Mutex<BTreeMap<u64, Weak<HandleEntry>>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Live statistics of one handle, updated by the handle itself.
+#[derive(Debug)]
+pub struct HandleEntry {
+    id: u64,
+    path: String,
+    table_name: String,
+    opened_ms: i64,
+    read_only: AtomicBool,
+    buffered_rows: AtomicUsize,
+    last_operation_ms: AtomicI64,
+}
+
+impl HandleEntry {
+    /// Record an operation on the handle now.
+    pub fn touch(&self) {
+        self.last_operation_ms.store(now_ms(), Ordering::Relaxed);
+    }
+
+    pub fn set_read_only(&self) {
+        self.read_only.store(true, Ordering::Relaxed);
+    }
+
+    pub fn set_buffered_rows(&self, rows: usize) {
+        self.buffered_rows.store(rows, Ordering::Relaxed);
+    }
+
+    pub fn buffered_rows(&self) -> usize {
+        self.buffered_rows.load(Ordering::Relaxed)
+    }
+
+    /// Database the handle was opened on, as given.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Handles other than this one open for writing on the same table.
+    pub fn other_writers(&self) -> usize {
+        list()
+            .iter()
+            .filter(|h| h.id != self.id && !h.read_only && h.path == self.path && h.table_name == self.table_name)
+            .count()
+    }
+}
+
+/// A point-in-time copy of a [`HandleEntry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandleInfo {
+    pub id: u64,
+    pub path: String,
+    pub table_name: String,
+    pub read_only: bool,
+    /// Rows held by an open append session, not yet written.
+    pub buffered_rows: usize,
+    pub opened_ms: i64,
+    pub last_operation_ms: i64,
+}
+
+/// Register a handle on `table_name` in the database at `db_path`. The handle owns
+/// the returned entry; dropping it unregisters the handle.
+pub fn register(db_path: &str, table_name: &str) -> Arc<HandleEntry> {
+    let now = now_ms();
+    let entry = Arc::new(HandleEntry {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        path: db_path.to_string(),
+        table_name: table_name.to_string(),
+        opened_ms: now,
+        read_only: AtomicBool::new(false),
+        buffered_rows: AtomicUsize::new(0),
+        last_operation_ms: AtomicI64::new(now),
+    });
+    let mut handles = HANDLES.lock().unwrap_or_else(|e| e.into_inner());
+    handles.retain(|_, h| h.strong_count() > 0);
+    handles.insert(entry.id, Arc::downgrade(&entry));
+    entry
+}
+
+/// The handles open in this process, oldest first.
+pub fn list() -> Vec<HandleInfo> {
+    let mut handles = HANDLES.lock().unwrap_or_else(|e| e.into_inner());
+    handles.retain(|_, h| h.strong_count() > 0);
+    handles
+        .values()
+        .filter_map(Weak::upgrade)
+        .map(|h| HandleInfo {
+            id: h.id,
+            path: h.path.clone(),
+            table_name: h.table_name.clone(),
+            read_only: h.read_only.load(Ordering::Relaxed),
+            buffered_rows: h.buffered_rows.load(Ordering::Relaxed),
+            opened_ms: h.opened_ms,
+            last_operation_ms: h.last_operation_ms.load(Ordering::Relaxed),
+        })
+        .collect()
+}
+
+/// Rows of (handle_id, path, table_name, read_only, buffered_rows, opened_ms,
+/// last_operation_ms).
+pub fn to_record_batch(handles: &[HandleInfo]) -> Result<RecordBatch> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("handle_id", DataType::UInt64, false),
+        Field::new("path", DataType::Utf8, false),
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("read_only", DataType::Boolean, false),
+        Field::new("buffered_rows", DataType::Int64, false),
+        Field::new("opened_ms", DataType::Int64, false),
+        Field::new("last_operation_ms", DataType::Int64, false),
+    ]));
+    Ok(RecordBatch::try_new(schema, vec![
+        Arc::new(UInt64Array::from_iter_values(handles.iter().map(|h| h.id))),
+        Arc::new(StringArray::from_iter_values(handles.iter().map(|h| h.path.as_str()))),
+        Arc::new(StringArray::from_iter_values(handles.iter().map(|h| h.table_name.as_str()))),
+        Arc::new(BooleanArray::from(handles.iter().map(|h| h.read_only).collect::<Vec<_>>())),
+        Arc::new(Int64Array::from_iter_values(handles.iter().map(|h| h.buffered_rows as i64))),
+        Arc::new(Int64Array::from_iter_values(handles.iter().map(|h| h.opened_ms))),
+        Arc::new(Int64Array::from_iter_values(handles.iter().map(|h| h.last_operation_ms))),
+    ])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_follow_their_handles() {
+        let a = register("/tmp/test_handles_db", "test_handles_a");
+        let b = register("/tmp/test_handles_db", "test_handles_b");
+        b.set_read_only();
+        b.set_buffered_rows(3);
+
+        let listed = |name: &str| list().into_iter().find(|h| h.table_name == name);
+        assert!(!listed("test_handles_a").unwrap().read_only);
+        let info = listed("test_handles_b").unwrap();
+        assert_eq!((info.read_only, info.buffered_rows), (true, 3));
+        assert!(info.id > a.id);
+        let a2 = register("/tmp/test_handles_db", "test_handles_a");
+        assert_eq!((a.other_writers(), b.other_writers()), (1, 0));
+        a2.set_read_only();
+        assert_eq!(a.other_writers(), 0);
+
+        drop(b);
+        assert!(listed("test_handles_b").is_none());
+        assert_eq!(to_record_batch(&[listed("test_handles_a").unwrap()]).unwrap().num_rows(), 1);
+    }
+}
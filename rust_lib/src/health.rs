@@ -0,0 +1,73 @@
+//! Reachability of a handle's table.
+//!
+//! A handle on a network-backed dataset can outlive the store connection it was
+//! opened with (a reset connection, an expired DNS entry). Errors of that class are
+//! recognised by [`is_connection_error`]; the handle then reopens its table, either
+//! before retrying a read or before its next operation (see `LanceIndex::ping`).
+
+use std::io::ErrorKind;
+
+/// Outcome of a successful ping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ping {
+    /// Latest version of the table, read from its manifest.
+    pub version: u64,
+    pub elapsed_ms: f64,
+}
+
+/// Fragments of error messages that mean the store could not be reached, for
+/// errors that only survive as text through the Lance and object store layers.
+const CONNECTION_MESSAGES: &[&str] = &[
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "connection aborted",
+    "broken pipe",
+    "timed out",
+    "dns error",
+    "error trying to connect",
+    "error sending request",
+    "network is unreachable",
+];
+
+/// Whether `error` means the store could not be reached, so reopening the table may
+/// succeed where the failed operation did not.
+pub fn is_connection_error(error: &anyhow::Error) -> bool {
+    for cause in error.chain() {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::TimedOut
+            ) {
+                return true;
+            }
+        }
+    }
+    let message = format!("{:#}", error).to_lowercase();
+    CONNECTION_MESSAGES.iter().any(|m| message.contains(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_is_connection_error() {
+        let reset = anyhow::Error::new(std::io::Error::new(ErrorKind::ConnectionReset, "peer"));
+        assert!(is_connection_error(&reset.context("search failed")));
+        assert!(is_connection_error(&anyhow!(
+            "Generic S3 error: error sending request for url (https://bucket/t.lance/_versions)"
+        )));
+        assert!(is_connection_error(&anyhow!("operation Timed Out after 30s")));
+
+        assert!(!is_connection_error(&anyhow!("Table 'vectors' was not found")));
+        let missing = anyhow::Error::new(std::io::Error::new(ErrorKind::NotFound, "t.lance"));
+        assert!(!is_connection_error(&missing));
+    }
+}
@@ -0,0 +1,258 @@
+//! Idempotency tokens for retried mutations.
+//!
+//! A caller that cannot tell whether an add, delete or merge went through (e.g. the
+//! FFI call failed after the commit) retries it with the same token. Tokens live in
+//! a sidecar Lance table (`<table>__tokens`) and are kept for
+//! [`TOKEN_RETENTION_MS`]:
+//!
+//! 1. Before the mutation the token is claimed: a row with a fresh claim id and no
+//!    record is inserted with a merge keyed on the token. Concurrent inserts of the
+//!    same token can both land, so the claim committed first (lowest row id) wins and
+//!    the others back off.
+//! 2. After the mutation commits, a second row with the same claim id carries the
+//!    [`TokenRecord`] of the labels it produced. A retry of a recorded token is a
+//!    no-op that returns the recorded labels.
+//!
+//! A mutation that fails releases its claim. A claim that is never recorded, because
+//! the process died or the record could not be written after the commit, leaves the
+//! token [`TokenState::Pending`]: retries with it fail until it expires rather than
+//! apply the mutation a second time.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, Int64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// How long a token is remembered after its mutation.
+pub const TOKEN_RETENTION_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Longest accepted token.
+pub const MAX_TOKEN_LEN: usize = 128;
+
+/// Tokens are quoted into sidecar filters, so they are limited to `[A-Za-z0-9_.-]`.
+pub fn validate_token(token: &str) -> Result<()> {
+    if token.is_empty() || token.len() > MAX_TOKEN_LEN {
+        return Err(anyhow!("idempotency token must be 1 to {} characters", MAX_TOKEN_LEN));
+    }
+    if !token.bytes().all(|b| b.is_ascii_alphanumeric() || b"_.-".contains(&b)) {
+        return Err(anyhow!("idempotency token '{}' may only contain letters, digits, '_', '.' and '-'", token));
+    }
+    Ok(())
+}
+
+static NEXT_CLAIM: AtomicU64 = AtomicU64::new(0);
+
+pub fn sidecar_table_name(table_name: &str) -> String {
+    format!("{}__tokens", table_name)
+}
+
+pub fn sidecar_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("token", DataType::Utf8, false),
+        Field::new("claim", DataType::Utf8, false),
+        Field::new("expires_ms", DataType::Int64, false),
+        Field::new("record", DataType::Utf8, true),
+    ]))
+}
+
+/// A claim id unique across processes.
+pub fn new_claim_id(now_ms: i64) -> String {
+    format!("{}-{}-{}", std::process::id(), now_ms, NEXT_CLAIM.fetch_add(1, Ordering::Relaxed))
+}
+
+/// A sidecar row: a claim of `token` when `record` is `None`, else its record.
+pub fn to_record_batch(token: &str, claim: &str, expires_ms: i64, record: Option<&TokenRecord>) -> Result<RecordBatch> {
+    Ok(RecordBatch::try_new(sidecar_schema(), vec![
+        Arc::new(StringArray::from(vec![token])),
+        Arc::new(StringArray::from(vec![claim])),
+        Arc::new(Int64Array::from(vec![expires_ms])),
+        Arc::new(StringArray::from(vec![record.map(|r| r.to_string())])),
+    ])?)
+}
+
+/// Where a token stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenState {
+    /// Claimed by `claim`, whose mutation has not been recorded.
+    Pending { claim: String },
+    Recorded(TokenRecord),
+}
+
+/// A sidecar row of one token, read with its row id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRow {
+    pub row_id: u64,
+    pub claim: String,
+    pub record: Option<String>,
+}
+
+/// Append the rows of a sidecar batch read with `_rowid` to `rows`.
+pub fn read_batch(batch: &RecordBatch, rows: &mut Vec<TokenRow>) -> Result<()> {
+    let text = |name: &str| -> Result<&StringArray> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .ok_or_else(|| anyhow!("tokens table column {} missing or not VARCHAR", name))
+    };
+    let (claims, records) = (text("claim")?, text("record")?);
+    let row_ids = batch
+        .column_by_name("_rowid")
+        .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+        .ok_or_else(|| anyhow!("tokens table read without row ids"))?;
+    for i in 0..batch.num_rows() {
+        rows.push(TokenRow {
+            row_id: row_ids.value(i),
+            claim: claims.value(i).to_string(),
+            record: records.is_valid(i).then(|| records.value(i).to_string()),
+        });
+    }
+    Ok(())
+}
+
+/// The state of a token from its unexpired rows: the first committed claim wins,
+/// and is recorded once a row with its claim id carries a record.
+pub fn resolve(rows: &[TokenRow]) -> Result<Option<TokenState>> {
+    let Some(winner) = rows.iter().filter(|r| r.record.is_none()).min_by_key(|r| r.row_id) else {
+        return Ok(None);
+    };
+    match rows.iter().find(|r| r.claim == winner.claim && r.record.is_some()) {
+        Some(row) => Ok(Some(TokenState::Recorded(TokenRecord::decode(row.record.as_deref().unwrap_or_default())?))),
+        None => Ok(Some(TokenState::Pending {
+            claim: winner.claim.clone(),
+        })),
+    }
+}
+
+/// `len` consecutive (from, to) pairs starting at (`from`, `to`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelRun {
+    pub from: i64,
+    pub to: i64,
+    pub len: u64,
+}
+
+/// What a recorded mutation produced: (row index, label) pairs for adds,
+/// (old label, new label) pairs for merges, nothing for deletes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRecord {
+    pub expires_ms: i64,
+    pub runs: Vec<LabelRun>,
+}
+
+impl TokenRecord {
+    /// Record `pairs`, run-length encoded: labels assigned in one call are
+    /// consecutive, so a record usually holds a single run.
+    pub fn new(expires_ms: i64, pairs: impl IntoIterator<Item = (i64, i64)>) -> Self {
+        let mut runs: Vec<LabelRun> = Vec::new();
+        for (from, to) in pairs {
+            match runs.last_mut() {
+                Some(run) if run.from + run.len as i64 == from && run.to + run.len as i64 == to => run.len += 1,
+                _ => runs.push(LabelRun { from, to, len: 1 }),
+            }
+        }
+        Self { expires_ms, runs }
+    }
+
+    pub fn pairs(&self) -> Vec<(i64, i64)> {
+        self.runs
+            .iter()
+            .flat_map(|run| (0..run.len as i64).map(move |i| (run.from + i, run.to + i)))
+            .collect()
+    }
+
+    pub fn len(&self) -> u64 {
+        self.runs.iter().map(|run| run.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Parse the text form `expires_ms;from:to:len,...`.
+    pub fn decode(text: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid idempotency record '{}'", text);
+        let (expires, runs) = text.split_once(';').ok_or_else(invalid)?;
+        let expires_ms = expires.parse().map_err(|_| invalid())?;
+        let runs = runs
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .map(|run| {
+                let mut parts = run.split(':');
+                let mut next = || parts.next().ok_or_else(invalid);
+                let (from, to, len) = (next()?, next()?, next()?);
+                Ok(LabelRun {
+                    from: from.parse().map_err(|_| invalid())?,
+                    to: to.parse().map_err(|_| invalid())?,
+                    len: len.parse().map_err(|_| invalid())?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { expires_ms, runs })
+    }
+}
+
+impl fmt::Display for TokenRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{};", self.expires_ms)?;
+        for (i, run) in self.runs.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{}:{}", run.from, run.to, run.len)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_round_trip() {
+        let record = TokenRecord::new(1000, vec![(0, 10), (1, 11), (2, 12), (5, 20)]);
+        assert_eq!(record.runs.len(), 2);
+        assert_eq!(record.len(), 4);
+        assert_eq!(record.to_string(), "1000;0:10:3,5:20:1");
+        assert_eq!(TokenRecord::decode(&record.to_string()).unwrap(), record);
+        assert_eq!(record.pairs(), vec![(0, 10), (1, 11), (2, 12), (5, 20)]);
+
+        let empty = TokenRecord::new(7, Vec::new());
+        assert_eq!(TokenRecord::decode(&empty.to_string()).unwrap(), empty);
+        assert!(TokenRecord::decode("7;1:2").is_err());
+    }
+
+    #[test]
+    fn test_resolve_first_claim_wins() {
+        let row = |row_id: u64, claim: &str, record: Option<&str>| TokenRow {
+            row_id,
+            claim: claim.to_string(),
+            record: record.map(str::to_string),
+        };
+        assert_eq!(resolve(&[]).unwrap(), None);
+        // A record without its claim row (the claim expired) does not count
+        assert_eq!(resolve(&[row(1, "a", Some("9;0:0:1"))]).unwrap(), None);
+
+        let racing = [row(7, "b", None), row(3, "a", None)];
+        assert_eq!(resolve(&racing).unwrap(), Some(TokenState::Pending { claim: "a".to_string() }));
+
+        let recorded = [row(7, "b", None), row(3, "a", None), row(9, "a", Some("9;0:4:2"))];
+        assert_eq!(
+            resolve(&recorded).unwrap(),
+            Some(TokenState::Recorded(TokenRecord::new(9, vec![(0, 4), (1, 5)])))
+        );
+        // The losing claim's record is ignored
+        let lost = [row(3, "a", None), row(7, "b", None), row(9, "b", Some("9;"))];
+        assert_eq!(resolve(&lost).unwrap(), Some(TokenState::Pending { claim: "a".to_string() }));
+    }
+
+    #[test]
+    fn test_validate_token() {
+        assert!(validate_token("batch-2024.01_7").is_ok());
+        assert!(validate_token("").is_err());
+        assert!(validate_token("a:b").is_err());
+        assert!(validate_token(&"x".repeat(MAX_TOKEN_LEN + 1)).is_err());
+    }
+}
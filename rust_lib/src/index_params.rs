@@ -0,0 +1,337 @@
+//! Vector index types and build parameters shared by the FFI entry points.
+
+use anyhow::{anyhow, Result};
+
+/// LanceDB's default training sample per partition.
+pub const DEFAULT_SAMPLE_RATE: u32 = 256;
+
+/// Centroids trained by the PQ/SQ quantizers (8-bit codes).
+const QUANTIZER_CENTROIDS: u64 = 256;
+
+/// `refine_factor` asking the search to pick one from the index's compression
+/// ratio and k (see [`auto_refine_factor`]). Negative values map to it over the FFI.
+pub const AUTO_REFINE: usize = usize::MAX;
+
+/// Upper bound of the refine factor chosen by [`auto_refine_factor`].
+pub const MAX_AUTO_REFINE: usize = 50;
+
+/// Refine factor for a search of `k` rows against an index storing vectors at
+/// `compression` times less than raw f32. Exact indexes (or none) need no rescoring;
+/// otherwise the candidate pool grows with the square root of the compression and
+/// doubles for small k, where one misranked neighbor costs the most recall.
+pub fn auto_refine_factor(compression: Option<f64>, k: usize) -> usize {
+    let Some(ratio) = compression.filter(|r| *r > 1.0) else {
+        return 0;
+    };
+    let mut factor = ratio.sqrt().ceil() as usize;
+    if k < 10 {
+        factor *= 2;
+    }
+    factor.clamp(1, MAX_AUTO_REFINE)
+}
+
+/// How a search resolves its refine factor, for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefinePlan {
+    /// Refine factor the search runs with (0 = quantized distances are final).
+    pub factor: usize,
+    /// Candidates fetched from the index and rescored with exact distances.
+    pub rescored: usize,
+    /// Compression ratio of the vector index, when one was built by this extension.
+    pub compression: Option<f64>,
+    /// Whether `factor` was chosen by auto refine.
+    pub auto: bool,
+}
+
+/// LanceDB's default PQ sub-vector count for `dimension`.
+fn default_num_sub_vectors(dimension: usize) -> usize {
+    if dimension % 16 == 0 {
+        dimension / 16
+    } else if dimension % 8 == 0 {
+        dimension / 8
+    } else {
+        1
+    }
+}
+
+/// Vector index kinds. Discriminants are the values used over the FFI. Value 2 is
+/// reserved for IVF_SQ, which the bundled Lance cannot build without HNSW; it fails
+/// as unsupported rather than unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum VectorIndexType {
+    IvfPq = 0,
+    IvfHnswSq = 1,
+    /// IVF partitions over the raw vectors: exact distances within the probed
+    /// partitions, at full vector size in the index.
+    IvfFlat = 3,
+}
+
+impl VectorIndexType {
+    pub fn from_i32(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(Self::IvfPq),
+            1 => Ok(Self::IvfHnswSq),
+            2 => Err(anyhow!(
+                "unsupported index type IVF_SQ: the bundled Lance builds SQ only with HNSW (IVF_HNSW_SQ)"
+            )),
+            3 => Ok(Self::IvfFlat),
+            other => Err(anyhow!("unknown vector index type {}", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IvfPq => "IVF_PQ",
+            Self::IvfHnswSq => "IVF_HNSW_SQ",
+            Self::IvfFlat => "IVF_FLAT",
+        }
+    }
+}
+
+/// Scalar index kinds for filter columns. Discriminants are the values used over
+/// the FFI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ScalarIndexType {
+    /// Sorted index for mostly distinct values; equality and range filters.
+    BTree = 0,
+    /// One bitmap per distinct value, for low-cardinality columns.
+    Bitmap = 1,
+    /// Bitmap over the elements of list columns, for `array_has_any`/`array_has_all`.
+    LabelList = 2,
+}
+
+impl ScalarIndexType {
+    pub fn from_i32(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(Self::BTree),
+            1 => Ok(Self::Bitmap),
+            2 => Ok(Self::LabelList),
+            other => Err(anyhow!("unknown scalar index type {}", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BTree => "BTREE",
+            Self::Bitmap => "BITMAP",
+            Self::LabelList => "LABEL_LIST",
+        }
+    }
+}
+
+/// Build parameters. Zero leaves a parameter at the LanceDB default; parameters
+/// that do not apply to the chosen index type are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorIndexParams {
+    pub num_partitions: u32,
+    /// IVF_PQ only.
+    pub num_sub_vectors: u32,
+    /// HNSW edges per node.
+    pub m: u32,
+    pub ef_construction: u32,
+    /// Training rows sampled per partition (k-means and quantizer training).
+    pub sample_rate: u32,
+    /// Distance type to build with instead of the handle's metric (e.g. a cosine
+    /// index over normalized vectors in a table opened with "l2").
+    pub metric: Option<String>,
+}
+
+impl VectorIndexParams {
+    /// Raw f32 bytes per quantized code byte of a `kind` index over `dimension`-wide
+    /// vectors built with these parameters.
+    pub fn compression_ratio(&self, kind: VectorIndexType, dimension: usize) -> f64 {
+        let raw_bytes = (dimension * std::mem::size_of::<f32>()) as f64;
+        match kind {
+            // One 8-bit code per sub-vector
+            VectorIndexType::IvfPq => {
+                let sub_vectors = match self.num_sub_vectors {
+                    0 => default_num_sub_vectors(dimension),
+                    n => n as usize,
+                };
+                raw_bytes / sub_vectors.max(1) as f64
+            }
+            // One 8-bit code per dimension
+            VectorIndexType::IvfHnswSq => raw_bytes / dimension.max(1) as f64,
+            // Raw vectors; nothing to refine
+            VectorIndexType::IvfFlat => 1.0,
+        }
+    }
+
+    /// Lower `sample_rate` so the k-means training sample of a `rows` x `dimension`
+    /// table fits in `max_bytes` (0 = no cap). Errors when even one sample per
+    /// partition does not fit.
+    pub fn fit_training_memory(&mut self, dimension: usize, rows: u64, max_bytes: u64) -> Result<()> {
+        if max_bytes == 0 {
+            return Ok(());
+        }
+        // LanceDB defaults to sqrt(rows) partitions; quantizers train 256 centroids.
+        let partitions = match self.num_partitions {
+            0 => (rows as f64).sqrt().ceil() as u64,
+            n => n as u64,
+        }
+        .max(QUANTIZER_CENTROIDS);
+        let bytes_per_sample = partitions * dimension as u64 * std::mem::size_of::<f32>() as u64;
+        let affordable = max_bytes / bytes_per_sample.max(1);
+        if affordable == 0 {
+            return Err(anyhow!(
+                "index build memory limit of {} bytes is below the {} bytes needed to train {} partitions",
+                max_bytes,
+                bytes_per_sample,
+                partitions
+            ));
+        }
+        let requested = match self.sample_rate {
+            0 => DEFAULT_SAMPLE_RATE,
+            n => n,
+        };
+        if (requested as u64) > affordable {
+            self.sample_rate = affordable as u32;
+        }
+        Ok(())
+    }
+}
+
+/// Cost budget for filtered searches that return fewer than k hits although more
+/// rows match. 0 disables a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitBudget {
+    /// Largest nprobes a short filtered search escalates to, doubling each round.
+    pub max_nprobes: usize,
+    /// Most matching rows searched exactly (bypassing the vector index) when the
+    /// index still comes up short.
+    pub max_exact_rows: u64,
+    /// Largest multiple of k a search over-fetches when the host filters its results
+    /// further (predicates that could not be pushed down).
+    pub max_overfetch: usize,
+}
+
+impl Default for HitBudget {
+    fn default() -> Self {
+        Self {
+            max_nprobes: 160,
+            max_exact_rows: 100_000,
+            max_overfetch: 8,
+        }
+    }
+}
+
+/// Resource caps applied to index builds on one handle. 0 means no cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildLimits {
+    /// Budget for the in-memory training sample, enforced by shrinking `sample_rate`.
+    pub max_memory_bytes: u64,
+}
+
+/// Rows a vector index may grow to, as a multiple of the rows it was trained on,
+/// before its partitions no longer fit the data and a rebuild is recommended.
+pub const RETRAIN_GROWTH: u64 = 2;
+
+/// What a merge does about the rows it appends unindexed. Discriminants are the
+/// values used over the FFI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum MergeIndexing {
+    /// Leave the new rows unindexed (searched by brute force) until the next optimize.
+    #[default]
+    None = 0,
+    /// Add the new rows to the index before the merge returns.
+    Optimize = 1,
+    /// Add the new rows to the index on the runtime after the merge returns.
+    Background = 2,
+}
+
+impl MergeIndexing {
+    pub fn from_ffi(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Optimize),
+            2 => Ok(Self::Background),
+            other => Err(anyhow!("unknown merge indexing mode {}", other)),
+        }
+    }
+}
+
+/// How much of the table the vector index covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStaleness {
+    pub indexed_rows: u64,
+    /// Rows appended since the index was last built or optimized; searches scan
+    /// them by brute force.
+    pub unindexed_rows: u64,
+}
+
+impl IndexStaleness {
+    /// Whether the table outgrew the index by [`RETRAIN_GROWTH`], so optimizing
+    /// (which keeps the trained partitions) is no longer enough for good recall.
+    pub fn retrain_recommended(&self) -> bool {
+        self.indexed_rows > 0
+            && self.indexed_rows + self.unindexed_rows >= self.indexed_rows.saturating_mul(RETRAIN_GROWTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_values_round_trip() {
+        use VectorIndexType::*;
+        for kind in [IvfPq, IvfHnswSq, IvfFlat] {
+            assert_eq!(VectorIndexType::from_i32(kind as i32).unwrap(), kind);
+        }
+        let err = VectorIndexType::from_i32(2).unwrap_err().to_string();
+        assert!(err.contains("unsupported index type IVF_SQ"), "{}", err);
+        assert!(VectorIndexType::from_i32(9).is_err());
+        for kind in [ScalarIndexType::BTree, ScalarIndexType::Bitmap, ScalarIndexType::LabelList] {
+            assert_eq!(ScalarIndexType::from_i32(kind as i32).unwrap(), kind);
+        }
+        assert!(ScalarIndexType::from_i32(3).is_err());
+    }
+
+    #[test]
+    fn test_fit_training_memory() {
+        let mut params = VectorIndexParams {
+            num_partitions: 1000,
+            ..Default::default()
+        };
+        // 1000 partitions x 4 dims x 4 bytes = 16000 bytes per sample
+        params.fit_training_memory(4, 1_000_000, 16_000 * 10).unwrap();
+        assert_eq!(params.sample_rate, 10);
+
+        // Already within budget: unchanged
+        params.fit_training_memory(4, 1_000_000, 16_000 * 100).unwrap();
+        assert_eq!(params.sample_rate, 10);
+
+        assert!(params.fit_training_memory(4, 1_000_000, 100).is_err());
+    }
+
+    #[test]
+    fn test_auto_refine_factor() {
+        let params = VectorIndexParams::default();
+        // 128 dims default to 8 sub-vectors: 512 raw bytes per 8 code bytes
+        let pq = params.compression_ratio(VectorIndexType::IvfPq, 128);
+        assert_eq!(pq, 64.0);
+        assert_eq!(params.compression_ratio(VectorIndexType::IvfHnswSq, 128), 4.0);
+        assert_eq!(params.compression_ratio(VectorIndexType::IvfFlat, 128), 1.0);
+
+        assert_eq!(auto_refine_factor(None, 10), 0);
+        assert_eq!(auto_refine_factor(Some(1.0), 10), 0);
+        assert_eq!(auto_refine_factor(Some(pq), 10), 8);
+        assert_eq!(auto_refine_factor(Some(pq), 1), 16);
+        assert_eq!(auto_refine_factor(Some(4.0), 100), 2);
+        assert_eq!(auto_refine_factor(Some(1.0e6), 1), MAX_AUTO_REFINE);
+    }
+
+    #[test]
+    fn test_retrain_recommended() {
+        let staleness = |indexed_rows, unindexed_rows| IndexStaleness { indexed_rows, unindexed_rows };
+        assert!(!staleness(1000, 0).retrain_recommended());
+        assert!(!staleness(1000, 999).retrain_recommended());
+        assert!(staleness(1000, 1000).retrain_recommended());
+        assert!(!staleness(0, 500).retrain_recommended());
+        assert_eq!(MergeIndexing::from_ffi(2).unwrap(), MergeIndexing::Background);
+        assert!(MergeIndexing::from_ffi(3).is_err());
+    }
+}
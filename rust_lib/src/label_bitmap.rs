@@ -0,0 +1,96 @@
+//! Compressed bitmaps of labels.
+//!
+//! [`LanceIndex::label_bitmap`](crate::lance_manager::LanceIndex::label_bitmap)
+//! exports the labels of a table's rows as a roaring bitmap, a few bits per label for
+//! the dense label ranges tables have, so callers can check references and compare
+//! versions (which labels were deleted, which were added) with set operations on
+//! bitmaps rather than per-label queries. Bitmaps are exchanged in the portable
+//! serialization of `RoaringTreemap`, readable by other roaring implementations with
+//! 64-bit support.
+
+use anyhow::{anyhow, Result};
+use roaring::RoaringTreemap;
+
+/// Set operation combining two bitmaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitmapOp {
+    And,
+    Or,
+    /// Labels of the first bitmap missing from the second.
+    AndNot,
+    Xor,
+}
+
+impl BitmapOp {
+    /// The operation for an FFI `op` (0 = and, 1 = or, 2 = and-not, 3 = xor).
+    pub fn from_ffi(op: i32) -> Result<Self> {
+        match op {
+            0 => Ok(BitmapOp::And),
+            1 => Ok(BitmapOp::Or),
+            2 => Ok(BitmapOp::AndNot),
+            3 => Ok(BitmapOp::Xor),
+            other => Err(anyhow!("unknown bitmap operation {}", other)),
+        }
+    }
+
+    pub fn apply(&self, a: &RoaringTreemap, b: &RoaringTreemap) -> RoaringTreemap {
+        match self {
+            BitmapOp::And => a & b,
+            BitmapOp::Or => a | b,
+            BitmapOp::AndNot => a - b,
+            BitmapOp::Xor => a ^ b,
+        }
+    }
+}
+
+/// Add `labels` to `bitmap`; labels are never negative.
+pub fn insert(bitmap: &mut RoaringTreemap, labels: &[i64]) -> Result<()> {
+    for &label in labels {
+        let label = u64::try_from(label).map_err(|_| anyhow!("negative label {}", label))?;
+        bitmap.insert(label);
+    }
+    Ok(())
+}
+
+pub fn encode(bitmap: &RoaringTreemap) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(bitmap.serialized_size());
+    bitmap.serialize_into(&mut out)?;
+    Ok(out)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<RoaringTreemap> {
+    RoaringTreemap::deserialize_from(bytes).map_err(|e| anyhow!("invalid label bitmap: {}", e))
+}
+
+/// The labels of `bitmap`, ascending.
+pub fn labels(bitmap: &RoaringTreemap) -> Result<Vec<i64>> {
+    bitmap
+        .iter()
+        .map(|label| i64::try_from(label).map_err(|_| anyhow!("label {} out of range", label)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ops_round_trip() {
+        let mut before = RoaringTreemap::new();
+        insert(&mut before, &[0, 1, 2, 3, 1 << 40]).unwrap();
+        let mut after = RoaringTreemap::new();
+        insert(&mut after, &[1, 3, 4]).unwrap();
+        assert!(insert(&mut after, &[-1]).is_err());
+
+        let decoded = decode(&encode(&before).unwrap()).unwrap();
+        assert_eq!(decoded, before);
+        assert!(decode(&[1, 2, 3]).is_err());
+
+        let deleted = BitmapOp::from_ffi(2).unwrap().apply(&before, &after);
+        assert_eq!(labels(&deleted).unwrap(), vec![0, 2, 1 << 40]);
+        assert_eq!(labels(&BitmapOp::And.apply(&before, &after)).unwrap(), vec![1, 3]);
+        assert_eq!(BitmapOp::Or.apply(&before, &after).len(), 6);
+        assert_eq!(labels(&BitmapOp::Xor.apply(&before, &after)).unwrap(), vec![0, 2, 4, 1 << 40]);
+        assert!(BitmapOp::from_ffi(4).is_err());
+    }
+}
@@ -2,58 +2,610 @@
 
 use anyhow::{anyhow, Result};
 use arrow_array::{
-    Array, ArrayRef, Float32Array, Int64Array, RecordBatch, RecordBatchIterator,
-    FixedSizeListArray, StructArray,
+    Array, ArrayRef, BooleanArray, Float32Array, Int64Array, RecordBatch, RecordBatchIterator,
+    RecordBatchReader, FixedSizeListArray, StringArray, StructArray, UInt32Array, UInt64Array,
 };
-use arrow_schema::{DataType, Field, Schema};
-use arrow::compute::cast;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+use arrow::buffer::{Buffer, ScalarBuffer};
+use arrow::compute::concat_batches;
 use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::row::{OwnedRow, RowConverter, SortField};
 use futures_util::TryStreamExt;
-use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::index::scalar::FullTextSearchQuery;
+use lancedb::query::{ExecutableQuery, QueryBase, QueryExecutionOptions, Select, VectorQuery};
+use lance::dataset::builder::DatasetBuilder;
+use lance::dataset::{Dataset, ReadParams, WriteParams};
+use lance::io::ObjectStoreParams;
+use lancedb::table::WriteOptions;
 use lancedb::{Connection, Table as LanceTable};
-use std::sync::atomic::{AtomicI64, Ordering};
-use std::sync::Arc;
+use roaring::RoaringTreemap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
+use crate::access::{self, Access, AccessTracker};
+use crate::admission::{AdmissionControl, AdmissionLimits, OpClass};
+use crate::approx::{self, ApproxStats, GroupStatsBuilder};
+use crate::backup::{self, BackupReport};
+use crate::capabilities::{IndexInfo, IndexStatus, TableCapabilities};
+use crate::cast_plan::{CastPlanCache, ColumnMatching};
+use crate::chunk::{self, Chunking};
+use crate::cold::{self, ColdStorage};
+use crate::consistency::{self, Consistency};
+use crate::constraints::{ConstraintViolated, Constraints, OnViolation, Violation};
+use crate::credentials;
+use crate::cursor::SearchCursor;
+use crate::disk_cache;
+use crate::distance;
+use crate::diversity::{self, Diversity};
+use crate::drift::{DriftReport, VectorStats};
+use crate::format_info::FormatInfo;
+use crate::fusion::Fusion;
+use crate::handles::{self, HandleEntry};
+use crate::health::{self, Ping};
+use crate::idempotency::{self, TokenRecord, TokenState};
+use crate::index_params::{
+    self, BuildLimits, HitBudget, IndexStaleness, MergeIndexing, RefinePlan, ScalarIndexType, VectorIndexParams,
+    VectorIndexType, AUTO_REFINE,
+};
+use crate::label_bitmap;
+use crate::lease::{self, WriterLease};
+use crate::maintenance::{MaintenancePlan, MaintenanceReport, MaintenanceStep, StepReport, StepStatus};
+use crate::metadata;
+use crate::migration::{MigrationPlan, MigrationStep};
+use crate::multivector;
+use crate::pca::{self, Pca};
+use crate::pipeline::{self, Pipeline, Stage};
+use crate::quantize::Int8Quantizer;
+use crate::query_log::{self, LoggedQuery, QueryDrift, QueryLog, QueryLogger, ReplayReport};
+use crate::quota::{Quota, QuotaPolicy, QuotaUsage};
+use crate::read_limit::ReadBudget;
+use crate::rebuild::{RebuildPhase, RebuildState, RebuildStatus, RebuildTracker};
+use crate::reconcile::SchemaReconciler;
+use crate::reembed;
+use crate::rejects::{self, Reject};
+use crate::replication::{self, ManifestBlob};
+use crate::rotation::Rotation;
 use crate::runtime;
+use crate::scratch;
+use crate::sort::{OrderBy, ScanSorter};
+use crate::staging;
+use crate::stats::{ColumnStats, ColumnStatsBuilder, PruningStats};
+use crate::storage_options;
+use crate::transform::{self, QueryTransform};
+use crate::ttl;
+use crate::upsert::{self, OnConflict, UpsertOptions};
+use crate::vector_storage::{self, VectorStorage};
+use crate::watch::{self, TableWatch};
+
+/// Outcome of [`LanceIndex::apply_changes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedChanges {
+    pub deleted: usize,
+    pub updated: usize,
+    /// Table version the changes were committed in.
+    pub version: u64,
+}
+
+/// Outcome of [`LanceIndex::upsert_batch_arrow`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpsertReport {
+    /// Label of each incoming row, -1 for rows dropped by validation. Rows of a
+    /// collapsed key and rows the conflict policy skipped report their key's label.
+    pub labels: Vec<i64>,
+    pub inserted: usize,
+    pub updated: usize,
+    /// Rows whose stored row the conflict policy kept.
+    pub skipped: usize,
+    /// Rows dropped because another row of the batch stood for their key.
+    pub collapsed: usize,
+}
+
+/// On-disk footprint of a Lance table, split by file kind.
+#[derive(Debug, Default, Clone)]
+pub struct DiskUsage {
+    pub data_bytes: u64,
+    pub deletion_bytes: u64,
+    pub index_bytes: u64,
+    pub manifest_bytes: u64,
+    /// Bytes per column, from the column sizes in the data file metadata.
+    /// Only populated when requested, since it reads every data file's footer.
+    pub columns: Vec<(String, u64)>,
+}
+
+impl DiskUsage {
+    pub fn total_bytes(&self) -> u64 {
+        self.data_bytes + self.deletion_bytes + self.index_bytes + self.manifest_bytes
+    }
+
+    /// Flatten into rows of (component, column, bytes) for export over FFI.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let mut components: Vec<&str> = vec!["data", "deletions", "indices", "manifests", "total"];
+        let mut columns: Vec<Option<&str>> = vec![None; components.len()];
+        let mut bytes: Vec<i64> = vec![
+            self.data_bytes as i64,
+            self.deletion_bytes as i64,
+            self.index_bytes as i64,
+            self.manifest_bytes as i64,
+            self.total_bytes() as i64,
+        ];
+        for (name, col_bytes) in &self.columns {
+            components.push("column");
+            columns.push(Some(name.as_str()));
+            bytes.push(*col_bytes as i64);
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("component", DataType::Utf8, false),
+            Field::new("column", DataType::Utf8, true),
+            Field::new("bytes", DataType::Int64, false),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from(components)),
+            Arc::new(StringArray::from(columns)),
+            Arc::new(Int64Array::from(bytes)),
+        ])?)
+    }
+}
+
+/// Most labels sent in one `label IN (...)` prefilter by `search_within`.
+pub const SEARCH_WITHIN_CHUNK: usize = 1024;
+
+/// Initial over-fetch multiple of `search_dedup`, and the growth factor of each
+/// widened retry.
+pub const DEDUP_OVERFETCH: usize = 4;
+
+/// Candidates `search_weighted` fetches per requested hit before rescoring them
+/// with the dimension weights.
+pub const WEIGHTED_OVERFETCH: usize = 4;
+
+/// Label residue classes used by `knn_graph`; a sample is a window of them.
+pub const SAMPLE_BUCKETS: u64 = 1000;
+
+/// Sampled rows `approx_stats` reads per take.
+const SAMPLE_TAKE_ROWS: usize = 8192;
+
+/// Rows buffered by an append session before they are written as one append.
+pub const COALESCE_MAX_ROWS: usize = 262_144;
+
+/// Candidates each side of `hybrid_search` fetches per requested hit before fusion.
+pub const HYBRID_OVERFETCH: usize = 4;
+
+/// Queries `search_batch` runs at once when the caller leaves it open.
+pub const SEARCH_BATCH_CONCURRENCY: usize = 8;
+
+/// Largest vector dimension `create_from_arrow` accepts.
+pub const MAX_DIMENSION: usize = 65_536;
+
+/// Outcome of `merge_from`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeReport {
+    /// (old_label, new_label) of every merged row, for the caller to update its mappings.
+    pub mapping: Vec<(i64, i64)>,
+    /// Vector index coverage once the merge (and, with [`MergeIndexing::Optimize`],
+    /// the index update) finished; `None` without a vector index.
+    pub staleness: Option<IndexStaleness>,
+    /// Whether an index update was scheduled in the background.
+    pub optimize_scheduled: bool,
+}
+
+/// Outcome of one incoming row of [`LanceIndex::add_batch_arrow_rows`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowStatus {
+    /// Added under a new label.
+    Ok,
+    /// Added by an earlier call with the same idempotency token, under the label
+    /// assigned then.
+    Duplicate,
+    /// Dropped by validation or a constraint; it has no label.
+    Invalid,
+}
+
+impl RowStatus {
+    /// Status of a row given its label (-1 when dropped), `added` for the others.
+    fn of(label: i64, added: RowStatus) -> Self {
+        if label < 0 {
+            RowStatus::Invalid
+        } else {
+            added
+        }
+    }
+
+    /// Code reported through the FFI.
+    pub fn code(self) -> u8 {
+        match self {
+            RowStatus::Ok => 0,
+            RowStatus::Duplicate => 1,
+            RowStatus::Invalid => 2,
+        }
+    }
+}
+
+/// Results of a sampled search. Every figure derived from it is an estimate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampledSearch {
+    pub results: Vec<(i64, f32)>,
+    /// Fraction of the partitions probed (the request rounded to whole partitions);
+    /// 1 when the table has no vector index to probe.
+    pub fraction: f64,
+}
+
+impl SampledSearch {
+    /// Estimated rank of the i-th result (0-based) in the full table.
+    pub fn estimated_rank(&self, i: usize) -> f64 {
+        (i + 1) as f64 / self.fraction
+    }
+}
+
+/// A row reached by [`LanceIndex::search_expand`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpandedHit {
+    pub label: i64,
+    /// Distance to the query.
+    pub distance: f32,
+    /// Neighbor hops from the initial hits (0 for the hits themselves).
+    pub hop: u32,
+    /// `decay^hop * exp(-distance)`; results are ranked by it, highest first.
+    pub score: f64,
+}
+
+impl ExpandedHit {
+    /// `ln(score)`, which does not underflow for large distances.
+    fn log_score(distance: f32, hop: u32, decay: f64) -> f64 {
+        hop as f64 * decay.ln() - distance as f64
+    }
+}
+
+/// A parent document found by [`LanceIndex::search_parents`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParentHit {
+    pub parent: i64,
+    /// Label of the parent's nearest chunk.
+    pub label: i64,
+    /// Distance of that chunk to the query.
+    pub distance: f32,
+}
+
+/// Distances a range search keeps: `lower <= distance < upper`, either bound
+/// optional. Distances are those searches report, in the table's metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DistanceRange {
+    pub lower: Option<f32>,
+    pub upper: Option<f32>,
+}
+
+impl DistanceRange {
+    fn validate(&self) -> Result<()> {
+        match (self.lower, self.upper) {
+            (None, None) => Err(anyhow!("distance range needs a lower or an upper bound")),
+            (Some(b), _) | (_, Some(b)) if b.is_nan() => Err(anyhow!("distance bound is NaN")),
+            (Some(lower), Some(upper)) if lower > upper => {
+                Err(anyhow!("distance range is empty: lower bound {} exceeds upper bound {}", lower, upper))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn contains(&self, distance: f32) -> bool {
+        self.lower.map_or(true, |lower| distance >= lower) && self.upper.map_or(true, |upper| distance < upper)
+    }
+}
+
+/// AND two optional Lance SQL predicates.
+fn and_filters(a: Option<&str>, b: Option<&str>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(format!("({}) AND ({})", a, b)),
+        (a, b) => a.or(b).map(str::to_string),
+    }
+}
+
+/// The columns of `schema` that the Lance SQL expression `sql` names. Identifiers
+/// are matched case-insensitively unless quoted with `"` or backticks; string
+/// literals are skipped. Names that are not columns (functions, keywords) are
+/// ignored, so the result may over-approximate but never misses a column.
+fn referenced_columns<'a>(sql: &str, schema: &'a Schema) -> Vec<&'a str> {
+    let mut names: Vec<(String, bool)> = Vec::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // '' escapes a quote inside a literal
+                while let Some(c) = chars.next() {
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+            }
+            '"' | '`' => {
+                let name: String = chars.by_ref().take_while(|q| *q != c).collect();
+                names.push((name, true));
+            }
+            c if c.is_ascii_digit() => {
+                while chars.next_if(|d| d.is_alphanumeric() || *d == '.' || *d == '_').is_some() {}
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(n) = chars.next_if(|n| n.is_alphanumeric() || *n == '_') {
+                    name.push(n);
+                }
+                names.push((name, false));
+            }
+            _ => {}
+        }
+    }
+    schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .filter(|column| {
+            names
+                .iter()
+                .any(|(name, quoted)| if *quoted { name == column } else { name.eq_ignore_ascii_case(column) })
+        })
+        .collect()
+}
+
+/// Highest label in the table's fragments with ids below `next_fragment`. Lance
+/// gives new fragments ever higher ids, so bringing it up to date only reads the
+/// fragments written since, whatever else changed. Labels of deleted rows stay
+/// counted once seen, so they are not handed out again.
+///
+/// Only an overwrite (see [`LanceIndex::promote_staging`]) numbers fragments from
+/// zero again. `newest_file`, the first data file of fragment `next_fragment - 1`,
+/// tells such a table apart, and its fragments are read again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LabelWatermark {
+    max_label: i64,
+    next_fragment: u64,
+    newest_file: Option<String>,
+}
+
+impl LabelWatermark {
+    const EMPTY: Self = Self {
+        max_label: -1,
+        next_fragment: 0,
+        newest_file: None,
+    };
+
+    /// Parse the `max_label@next_fragment[@newest_file]` form kept in the table
+    /// metadata.
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.splitn(3, '@');
+        Some(Self {
+            max_label: parts.next()?.parse().ok()?,
+            next_fragment: parts.next()?.parse().ok()?,
+            newest_file: parts.next().map(str::to_string),
+        })
+    }
+
+    /// Whether the fragments the watermark covers were replaced by an overwrite.
+    fn overwritten(&self, fragments: &[lance_table::format::Fragment]) -> bool {
+        let Some(file) = &self.newest_file else {
+            return false;
+        };
+        match fragments.iter().find(|f| f.id + 1 == self.next_fragment) {
+            Some(newest) => newest.files.first().map(|f| &f.path) != Some(file),
+            // Gone without newer fragments: deleted, or overwritten by fewer
+            None => fragments.iter().all(|f| f.id < self.next_fragment),
+        }
+    }
+}
+
+impl fmt::Display for LabelWatermark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.max_label, self.next_fragment)?;
+        match &self.newest_file {
+            Some(file) => write!(f, "@{}", file),
+            None => Ok(()),
+        }
+    }
+}
 
 /// Core LanceDB index handle.
 pub struct LanceIndex {
-    #[allow(dead_code)]
     connection: Connection,
-    table: Option<LanceTable>,
+    /// Replaced when the handle reconnects (see [`crate::health`]).
+    table: RwLock<Option<LanceTable>>,
     table_name: String,
     dimension: usize,
     metric: String,
     next_label: AtomicI64,
+    /// Labels found in the table so far, raised when other writers append.
+    label_watermark: Mutex<LabelWatermark>,
+    /// Highest label the table metadata is known to record.
+    recorded_label: AtomicI64,
     schema: Arc<Schema>,
+    /// Conversion of `add_batch_arrow` columns to `schema`, by incoming schema.
+    cast_plans: CastPlanCache,
+    admission: AdmissionControl,
+    /// Retrieval pipeline applied by `search`, cached from the table metadata.
+    pipeline: RwLock<Option<Pipeline>>,
+    /// Query preprocessing applied by `search`, cached from the table metadata.
+    query_transform: RwLock<Option<QueryTransform>>,
+    /// Running centroid/norm statistics; `None` until first needed or after deletes.
+    vector_stats: Mutex<Option<VectorStats>>,
+    /// Bumped on every recompute of `vector_stats`, so an append can tell whether
+    /// the statistics were rebuilt while it committed.
+    vector_stats_epoch: AtomicU64,
+    /// Embedding model id the table's vectors were produced by, if declared.
+    embedding_model: RwLock<Option<String>>,
+    /// Dimension recorded with the embedding model, checked against the vectors
+    /// whenever a caller declares a model.
+    embedding_dim: RwLock<Option<usize>>,
+    quota: RwLock<Option<Quota>>,
+    /// Policy moving old rows to reduced-precision storage, cached from the table
+    /// metadata.
+    cold_storage: RwLock<Option<ColdStorage>>,
+    /// Set when the cold sidecar table may hold rows; searches then merge its hits.
+    cold_rows: AtomicBool,
+    /// Chunking stage applied by `add_chunked`, cached from the table metadata.
+    chunking: RwLock<Option<Chunking>>,
+    /// Ingest constraints checked by `add_batch_arrow`, cached from the table metadata.
+    constraints: RwLock<Option<Constraints>>,
+    /// What `add_batch_arrow` does with rows that fail validation, cached from the
+    /// table metadata.
+    on_invalid: RwLock<OnViolation>,
+    /// How `add_batch_arrow` matches incoming columns to `schema`, cached from the
+    /// table metadata.
+    column_matching: RwLock<ColumnMatching>,
+    /// Distance type of the vector index, cached from the table metadata.
+    index_metric: Arc<RwLock<Option<String>>>,
+    /// Compression ratio of the vector index, cached from the table metadata.
+    index_compression: Arc<RwLock<Option<f64>>>,
+    /// Background index rebuild state.
+    rebuild: Arc<RebuildTracker>,
+    build_limits: RwLock<BuildLimits>,
+    hit_budget: RwLock<HitBudget>,
+    merge_indexing: RwLock<MergeIndexing>,
+    /// Appends buffered by an open append session; `None` outside a session.
+    pending_appends: Mutex<Option<Vec<RecordBatch>>>,
+    /// Held from an append's quota check to its commit, so concurrent appends through
+    /// the handle cannot overshoot the quota together. Caches the usage checked against.
+    append_lock: Mutex<Option<QuotaUsage>>,
+    /// Rows per search result batch; 0 for Lance's default.
+    read_batch_size: AtomicUsize,
+    /// Metric used instead of `metric` on exact and rescoring paths (built-in or
+    /// registered custom metric); the ANN index keeps using `metric`.
+    rescore_metric: RwLock<Option<String>>,
+    /// Type of the `expires_at` column while row expiry filtering is on.
+    row_ttl: RwLock<Option<DataType>>,
+    /// Columns withheld from scans and stats unless the caller is privileged,
+    /// cached from the table metadata.
+    sensitive_columns: RwLock<Vec<String>>,
+    /// Mandatory filter (e.g. a tenant) AND-ed into every read, count and delete.
+    /// Fixed when the handle is opened; see [`LanceIndex::open_scoped`].
+    scope: Option<String>,
+    /// Buffered search hits, flushed to the access sidecar table.
+    access: Arc<AccessTracker>,
+    /// Query log setting, cached from the table metadata, and buffered entries.
+    query_log: QueryLogger,
+    /// PCA projection kept in the `vector_pca` column, cached from the table metadata.
+    pca: RwLock<Option<Arc<Pca>>>,
+    /// Rotation applied to stored vectors and queries, cached from the table metadata.
+    rotation: RwLock<Option<Arc<Rotation>>>,
+    /// Scale and offset of int8 vector storage, cached from the table metadata; `None`
+    /// until fitted or on other storages.
+    quantizer: RwLock<Option<Int8Quantizer>>,
+    /// Dimension of the `vector_next` column while a re-embed is in progress, cached
+    /// from the table metadata.
+    reembed: RwLock<Option<usize>>,
+    /// Writer lease TTL while writers must hold the lease, cached from the table metadata.
+    lease_ttl_ms: RwLock<Option<i64>>,
+    /// The lease, once this handle has written (see [`crate::lease`]).
+    lease: Mutex<Option<Arc<WriterLease>>>,
+    /// Commit counter shared with other handles on the same table in this process.
+    watch: Arc<TableWatch>,
+    /// Generation of `watch` this handle's table view last caught up with.
+    synced: AtomicU64,
+    /// Set when an operation failed with a connection error; the next operation
+    /// reopens the table first.
+    reconnect_needed: AtomicBool,
+    /// Table version a read snapshot is pinned to; `None` for live handles.
+    /// See [`LanceIndex::begin_read_snapshot`].
+    snapshot_version: Option<u64>,
+    /// Why writes are refused when the table uses storage features this build does
+    /// not support; checked on open. See [`crate::format_info`].
+    format_block: Option<String>,
+    /// Version the handle's view was moved to by the last imported manifest; 0 until
+    /// one is imported. See [`LanceIndex::import_manifest`].
+    imported_version: AtomicU64,
+    /// This handle's entry in the process-wide handle registry.
+    handle_entry: Arc<HandleEntry>,
 }
 
 impl LanceIndex {
     /// Create a new Lance dataset at the given path (vector-only).
     pub fn create(db_path: &str, dimension: usize, metric: &str, table_name: &str) -> Result<Self> {
-        let connection = runtime::block_on(lancedb::connect(db_path).execute())?;
+        Self::create_with_storage(db_path, dimension, metric, table_name, VectorStorage::Float32)
+    }
+
+    /// [`LanceIndex::create`] storing vectors as `storage` (see [`crate::vector_storage`]).
+    pub fn create_with_storage(
+        db_path: &str,
+        dimension: usize,
+        metric: &str,
+        table_name: &str,
+        storage: VectorStorage,
+    ) -> Result<Self> {
+        let connection = storage_options::connect(db_path, &[])?;
 
-        let schema = Self::build_vector_schema(dimension);
+        let schema = Self::build_vector_schema(dimension, storage);
         let table_name = table_name.to_string();
 
         // Create empty table with schema (drop existing if present)
         let empty_batch = Self::empty_batch_from_schema(&schema)?;
         let batches = RecordBatchIterator::new(vec![Ok(empty_batch)], schema.clone());
         let _ = runtime::block_on(connection.drop_table(&table_name));
-        let table = runtime::block_on(
-            connection
-                .create_table(&table_name, Box::new(batches))
-                .execute(),
-        )?;
+        let _ = runtime::block_on(connection.drop_table(&access::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&rejects::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&query_log::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&idempotency::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&cold::sidecar_table_name(&table_name)));
+        let table = Self::create_table(&connection, &table_name, Box::new(batches))?;
+        // Siblings still hold the dropped table; make them reload
+        let watch = watch::watch(db_path, &table_name);
+        watch.bump();
+        let synced = watch.generation();
+        let handle_entry = handles::register(db_path, &table_name);
 
         Ok(Self {
             connection,
-            table: Some(table),
+            table: RwLock::new(Some(table)),
             table_name,
             dimension,
             metric: metric.to_string(),
             next_label: AtomicI64::new(0),
+            label_watermark: Mutex::new(LabelWatermark::EMPTY),
+            recorded_label: AtomicI64::new(-1),
+            cast_plans: CastPlanCache::new(vector_storage::ingest_schema(&schema)),
             schema,
+            admission: AdmissionControl::default(),
+            pipeline: RwLock::new(None),
+            query_transform: RwLock::new(None),
+            vector_stats: Mutex::new(None),
+            vector_stats_epoch: AtomicU64::new(0),
+            embedding_model: RwLock::new(None),
+            embedding_dim: RwLock::new(None),
+            quota: RwLock::new(None),
+            cold_storage: RwLock::new(None),
+            cold_rows: AtomicBool::new(false),
+            chunking: RwLock::new(None),
+            constraints: RwLock::new(None),
+            on_invalid: RwLock::new(OnViolation::default()),
+            column_matching: RwLock::new(ColumnMatching::default()),
+            index_metric: Arc::new(RwLock::new(None)),
+            index_compression: Arc::new(RwLock::new(None)),
+            rebuild: Arc::new(RebuildTracker::default()),
+            build_limits: RwLock::new(BuildLimits::default()),
+            hit_budget: RwLock::new(HitBudget::default()),
+            merge_indexing: RwLock::new(MergeIndexing::default()),
+            pending_appends: Mutex::new(None),
+            append_lock: Mutex::new(None),
+            read_batch_size: AtomicUsize::new(0),
+            rescore_metric: RwLock::new(None),
+            row_ttl: RwLock::new(None),
+            sensitive_columns: RwLock::new(Vec::new()),
+            scope: None,
+            snapshot_version: None,
+            format_block: None,
+            access: Arc::new(AccessTracker::new(false)),
+            query_log: QueryLogger::new(None),
+            pca: RwLock::new(None),
+            rotation: RwLock::new(None),
+            quantizer: RwLock::new(None),
+            reembed: RwLock::new(None),
+            lease_ttl_ms: RwLock::new(None),
+            lease: Mutex::new(None),
+            watch,
+            synced: AtomicU64::new(synced),
+            reconnect_needed: AtomicBool::new(false),
+            imported_version: AtomicU64::new(0),
+            handle_entry,
         })
     }
 
@@ -69,105 +621,541 @@ impl LanceIndex {
         ffi_schema_ptr: *mut FFI_ArrowSchema,
         metric: &str,
         table_name: &str,
+    ) -> Result<Self> {
+        Self::create_from_arrow_with_vectors(db_path, ffi_schema_ptr, metric, table_name, &[], VectorStorage::Float32)
+    }
+
+    /// [`LanceIndex::create_from_arrow`] with the FixedSizeList columns that hold
+    /// vectors declared (see [`LanceIndex::table_schema_from_arrow`]), stored as
+    /// `storage`; empty declares just the `vector` column.
+    ///
+    /// # Safety
+    /// Caller must pass a valid pointer to an Arrow C Data Interface ArrowSchema struct.
+    pub unsafe fn create_from_arrow_with_vectors(
+        db_path: &str,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        metric: &str,
+        table_name: &str,
+        vector_columns: &[String],
+        storage: VectorStorage,
     ) -> Result<Self> {
         // Import schema from FFI (borrows, does not consume)
         let ffi_schema = &*ffi_schema_ptr;
         let imported_schema = Schema::try_from(ffi_schema)
             .map_err(|e| anyhow!("FFI schema import failed: {}", e))?;
-
-        // Find vector dimension from FixedSizeList column
-        let mut dimension = 0usize;
-        for field in imported_schema.fields() {
-            if let DataType::FixedSizeList(_, dim) = field.data_type() {
-                dimension = *dim as usize;
-                break;
-            }
-        }
-        if dimension == 0 {
-            return Err(anyhow!("no FixedSizeList column found in schema"));
-        }
-
-        // Build table schema: prepend label column, then imported fields
-        let mut table_fields: Vec<Arc<Field>> = vec![Arc::new(Field::new("label", DataType::Int64, false))];
-        for field in imported_schema.fields() {
-            // Rename vector column's child field to "item" (DuckDB uses "")
-            if let DataType::FixedSizeList(_, dim) = field.data_type() {
-                let fixed_field = Field::new(
-                    field.name(),
-                    DataType::FixedSizeList(
-                        Arc::new(Field::new("item", DataType::Float32, true)),
-                        *dim,
-                    ),
-                    field.is_nullable(),
-                );
-                table_fields.push(Arc::new(fixed_field));
-            } else {
-                table_fields.push(Arc::new(field.as_ref().clone()));
-            }
-        }
-        let table_schema = Arc::new(Schema::new(table_fields));
+        let (table_schema, dimension) = Self::table_schema_from_arrow(&imported_schema, vector_columns, storage)?;
 
         // Create empty batch
         let empty_batch = Self::empty_batch_from_schema(&table_schema)?;
 
         // Create table
-        let connection = runtime::block_on(lancedb::connect(db_path).execute())?;
+        let connection = storage_options::connect(db_path, &[])?;
         let table_name = table_name.to_string();
         let _ = runtime::block_on(connection.drop_table(&table_name));
+        let _ = runtime::block_on(connection.drop_table(&access::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&rejects::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&query_log::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&idempotency::sidecar_table_name(&table_name)));
+        let _ = runtime::block_on(connection.drop_table(&cold::sidecar_table_name(&table_name)));
         let batches = RecordBatchIterator::new(vec![Ok(empty_batch)], table_schema.clone());
-        let table = runtime::block_on(
-            connection
-                .create_table(&table_name, Box::new(batches))
-                .execute(),
-        )?;
+        let table = Self::create_table(&connection, &table_name, Box::new(batches))?;
+        // Siblings still hold the dropped table; make them reload
+        let watch = watch::watch(db_path, &table_name);
+        watch.bump();
+        let synced = watch.generation();
+        let handle_entry = handles::register(db_path, &table_name);
 
         Ok(Self {
             connection,
-            table: Some(table),
+            table: RwLock::new(Some(table)),
             table_name,
             dimension,
             metric: metric.to_string(),
             next_label: AtomicI64::new(0),
+            label_watermark: Mutex::new(LabelWatermark::EMPTY),
+            recorded_label: AtomicI64::new(-1),
+            cast_plans: CastPlanCache::new(vector_storage::ingest_schema(&table_schema)),
             schema: table_schema,
+            admission: AdmissionControl::default(),
+            pipeline: RwLock::new(None),
+            query_transform: RwLock::new(None),
+            vector_stats: Mutex::new(None),
+            vector_stats_epoch: AtomicU64::new(0),
+            embedding_model: RwLock::new(None),
+            embedding_dim: RwLock::new(None),
+            quota: RwLock::new(None),
+            cold_storage: RwLock::new(None),
+            cold_rows: AtomicBool::new(false),
+            chunking: RwLock::new(None),
+            constraints: RwLock::new(None),
+            on_invalid: RwLock::new(OnViolation::default()),
+            column_matching: RwLock::new(ColumnMatching::default()),
+            index_metric: Arc::new(RwLock::new(None)),
+            index_compression: Arc::new(RwLock::new(None)),
+            rebuild: Arc::new(RebuildTracker::default()),
+            build_limits: RwLock::new(BuildLimits::default()),
+            hit_budget: RwLock::new(HitBudget::default()),
+            merge_indexing: RwLock::new(MergeIndexing::default()),
+            pending_appends: Mutex::new(None),
+            append_lock: Mutex::new(None),
+            read_batch_size: AtomicUsize::new(0),
+            rescore_metric: RwLock::new(None),
+            row_ttl: RwLock::new(None),
+            sensitive_columns: RwLock::new(Vec::new()),
+            scope: None,
+            snapshot_version: None,
+            format_block: None,
+            access: Arc::new(AccessTracker::new(false)),
+            query_log: QueryLogger::new(None),
+            pca: RwLock::new(None),
+            rotation: RwLock::new(None),
+            quantizer: RwLock::new(None),
+            reembed: RwLock::new(None),
+            lease_ttl_ms: RwLock::new(None),
+            lease: Mutex::new(None),
+            watch,
+            synced: AtomicU64::new(synced),
+            reconnect_needed: AtomicBool::new(false),
+            imported_version: AtomicU64::new(0),
+            handle_entry,
+        })
+    }
+
+    /// Object store parameters for the table at `uri`: the registered credential
+    /// provider when it is on S3 (see [`crate::credentials`]), and with `cached` the
+    /// local disk cache when it is remote (see [`crate::disk_cache`]).
+    fn store_params(uri: &str, cached: bool) -> Option<ObjectStoreParams> {
+        let aws_credentials = credentials::provider(uri);
+        let object_store_wrapper = if cached { disk_cache::wrapper(uri) } else { None };
+        if aws_credentials.is_none() && object_store_wrapper.is_none() {
+            return None;
+        }
+        Some(ObjectStoreParams {
+            aws_credentials,
+            object_store_wrapper,
+            ..Default::default()
         })
     }
 
+    /// Open table `name` with the store parameters of its database.
+    fn open_table(connection: &Connection, name: &str) -> Result<LanceTable> {
+        let mut builder = connection.open_table(name);
+        if let Some(store_options) = Self::store_params(connection.uri(), true) {
+            builder = builder.lance_read_params(ReadParams {
+                store_options: Some(store_options),
+                ..Default::default()
+            });
+        }
+        Ok(runtime::block_on(builder.execute())?)
+    }
+
+    /// Create table `name` from `batches` with the store parameters of its database.
+    /// Tables created on remote storage read through the disk cache once reopened.
+    fn create_table(
+        connection: &Connection,
+        name: &str,
+        batches: Box<dyn RecordBatchReader + Send>,
+    ) -> Result<LanceTable> {
+        let mut builder = connection.create_table(name, batches);
+        if let Some(store_params) = Self::store_params(connection.uri(), false) {
+            builder = builder.write_options(WriteOptions {
+                lance_write_params: Some(WriteParams {
+                    store_params: Some(store_params),
+                    ..Default::default()
+                }),
+            });
+        }
+        Ok(runtime::block_on(builder.execute())?)
+    }
+
     /// Reopen an existing Lance dataset, deriving schema from the table.
     pub fn open(db_path: &str, table_name: &str, metric: &str) -> Result<Self> {
-        let connection = runtime::block_on(lancedb::connect(db_path).execute())?;
+        let connection = storage_options::connect(db_path, &[])?;
         let table_name_str = table_name.to_string();
-        let table = runtime::block_on(connection.open_table(&table_name_str).execute())?;
+        let table = Self::open_table(&connection, &table_name_str)?;
+        let watch = watch::watch(db_path, &table_name_str);
+        let handle_entry = handles::register(db_path, &table_name_str);
 
         // Derive schema from the Lance table
         let table_schema = Self::read_table_schema(&table)?;
 
-        // Derive dimension from the vector column
-        let mut dimension = 0usize;
-        for field in table_schema.fields() {
-            if let DataType::FixedSizeList(_, dim) = field.data_type() {
-                dimension = *dim as usize;
-                break;
-            }
-        }
-        if dimension == 0 {
-            // Fallback: might be a vector-only table with known schema
-            return Err(anyhow!("cannot determine dimension from table schema"));
+        // Derive dimension from the vector column; foreign tables may lack the key or
+        // vector column entirely (see `LanceIndex::inspect` for the full report)
+        let capabilities = TableCapabilities::from_schema(&table_schema);
+        let problems = capabilities.problems();
+        let Some(vector) = capabilities.vector().filter(|_| problems.is_empty()) else {
+            return Err(anyhow!("table {} is not a Lance index table: {}", table_name, problems.join("; ")));
+        };
+        let dimension = vector.dimension;
+
+        // Use MAX(label)+1, not count_rows() — count is wrong after deletes. The
+        // recorded watermark spares reading the fragments it covers.
+        let recorded = Self::recorded_label_watermark(&table)?;
+        let watermark = Self::advance_label_watermark(&table, recorded.as_ref().unwrap_or(&LabelWatermark::EMPTY))?;
+        let mut recorded_label = recorded.as_ref().map_or(-1, |w| w.max_label);
+        // Best effort: a read-only store still opens, and reads the fragments next time
+        if Some(&watermark) != recorded.as_ref() && Self::record_label_watermark(&table, &watermark).is_ok() {
+            recorded_label = watermark.max_label;
         }
 
-        // Use MAX(label)+1, not count_rows() — count is wrong after deletes.
-        let next_label = Self::query_max_label(&table)? + 1;
+        let pipeline = metadata::get(&table, metadata::PIPELINE)?
+            .map(|spec| Pipeline::parse(&spec))
+            .transpose()?;
+        let query_transform = Self::read_query_transform(&table)?;
+        let embedding_model = metadata::get(&table, metadata::EMBEDDING_MODEL)?;
+        let embedding_dim = metadata::get(&table, metadata::EMBEDDING_DIM)?
+            .map(|dim| dim.parse::<usize>().map_err(|_| anyhow!("invalid recorded embedding dimension '{}'", dim)))
+            .transpose()?;
+        let quota = metadata::get(&table, metadata::QUOTA)?
+            .map(|spec| Quota::parse(&spec))
+            .transpose()?;
+        let cold_storage = metadata::get(&table, metadata::COLD_STORAGE)?
+            .map(|spec| ColdStorage::parse(&spec))
+            .transpose()?;
+        let cold_rows = cold_storage.is_some() || {
+            let names = runtime::block_on(connection.table_names().execute())?;
+            names.contains(&cold::sidecar_table_name(&table_name_str))
+        };
+        let chunking = metadata::get(&table, metadata::CHUNKING)?
+            .map(|spec| Chunking::parse(&spec))
+            .transpose()?;
+        let constraints = metadata::get(&table, metadata::CONSTRAINTS)?
+            .map(|spec| Constraints::parse(&spec))
+            .transpose()?;
+        let on_invalid = metadata::get(&table, metadata::ON_INVALID)?
+            .map(|mode| OnViolation::parse(&mode))
+            .transpose()?
+            .unwrap_or_default();
+        let column_matching = metadata::get(&table, metadata::COLUMN_MATCHING)?
+            .map(|mode| ColumnMatching::parse(&mode))
+            .transpose()?
+            .unwrap_or_default();
+        let access_tracking = metadata::get(&table, metadata::ACCESS_TRACKING)?.is_some();
+        let query_log = metadata::get(&table, metadata::QUERY_LOG)?
+            .map(|spec| QueryLog::parse(&spec))
+            .transpose()?;
+        let index_metric = metadata::get(&table, metadata::INDEX_METRIC)?;
+        let index_compression = metadata::get(&table, metadata::INDEX_COMPRESSION)?.and_then(|r| r.parse::<f64>().ok());
+        let sensitive_columns = metadata::get(&table, metadata::SENSITIVE_COLUMNS)?
+            .map(|list| list.split(',').map(str::to_string).collect())
+            .unwrap_or_default();
+        let pca = metadata::get(&table, metadata::PCA)?
+            .map(|spec| Pca::decode(&spec))
+            .transpose()?
+            .map(Arc::new);
+        let rotation = metadata::get(&table, metadata::ROTATION)?
+            .map(|spec| Rotation::decode(&spec))
+            .transpose()?
+            .map(Arc::new);
+        let quantizer = metadata::get(&table, metadata::INT8_QUANTIZATION)?
+            .map(|spec| Int8Quantizer::decode(&spec))
+            .transpose()?;
+        let reembed = metadata::get(&table, metadata::REEMBED)?
+            .map(|dim| reembed::parse_dim(&dim))
+            .transpose()?;
+        // The projection and re-embed columns are filled in separately, never supplied
+        // by appends
+        let table_schema = Arc::new(Schema::new_with_metadata(
+            table_schema
+                .fields()
+                .iter()
+                .filter(|f| f.name() != pca::PCA_COLUMN && f.name() != reembed::NEXT_COLUMN)
+                .cloned()
+                .collect::<Vec<_>>(),
+            table_schema.metadata().clone(),
+        ));
+        let lease_ttl_ms = metadata::get(&table, metadata::WRITER_LEASE)?
+            .map(|secs| {
+                secs.parse::<i64>()
+                    .map(|secs| secs * 1000)
+                    .map_err(|_| anyhow!("invalid writer lease TTL '{}'", secs))
+            })
+            .transpose()?;
+        let row_ttl = match metadata::get(&table, metadata::ROW_TTL)? {
+            Some(_) => Some(ttl::expiry_type(&table_schema)?),
+            None => None,
+        };
+        let format_block = Self::read_format_info(&table)?.write_block();
 
         Ok(Self {
             connection,
-            table: Some(table),
+            table: RwLock::new(Some(table)),
             table_name: table_name_str,
             dimension,
             metric: metric.to_string(),
-            next_label: AtomicI64::new(next_label),
+            next_label: AtomicI64::new(watermark.max_label + 1),
+            label_watermark: Mutex::new(watermark),
+            recorded_label: AtomicI64::new(recorded_label),
+            cast_plans: CastPlanCache::new(vector_storage::ingest_schema(&table_schema)),
             schema: table_schema,
+            admission: AdmissionControl::default(),
+            pipeline: RwLock::new(pipeline),
+            query_transform: RwLock::new(query_transform),
+            vector_stats: Mutex::new(None),
+            vector_stats_epoch: AtomicU64::new(0),
+            embedding_model: RwLock::new(embedding_model),
+            embedding_dim: RwLock::new(embedding_dim),
+            quota: RwLock::new(quota),
+            cold_storage: RwLock::new(cold_storage),
+            cold_rows: AtomicBool::new(cold_rows),
+            chunking: RwLock::new(chunking),
+            constraints: RwLock::new(constraints),
+            on_invalid: RwLock::new(on_invalid),
+            column_matching: RwLock::new(column_matching),
+            index_metric: Arc::new(RwLock::new(index_metric)),
+            index_compression: Arc::new(RwLock::new(index_compression)),
+            rebuild: Arc::new(RebuildTracker::default()),
+            build_limits: RwLock::new(BuildLimits::default()),
+            hit_budget: RwLock::new(HitBudget::default()),
+            merge_indexing: RwLock::new(MergeIndexing::default()),
+            pending_appends: Mutex::new(None),
+            append_lock: Mutex::new(None),
+            read_batch_size: AtomicUsize::new(0),
+            rescore_metric: RwLock::new(None),
+            row_ttl: RwLock::new(row_ttl),
+            sensitive_columns: RwLock::new(sensitive_columns),
+            scope: None,
+            snapshot_version: None,
+            format_block,
+            access: Arc::new(AccessTracker::new(access_tracking)),
+            query_log: QueryLogger::new(query_log),
+            pca: RwLock::new(pca),
+            rotation: RwLock::new(rotation),
+            quantizer: RwLock::new(quantizer),
+            reembed: RwLock::new(reembed),
+            lease_ttl_ms: RwLock::new(lease_ttl_ms),
+            lease: Mutex::new(None),
+            synced: AtomicU64::new(watch.generation()),
+            reconnect_needed: AtomicBool::new(false),
+            imported_version: AtomicU64::new(0),
+            watch,
+            handle_entry,
         })
     }
 
+    /// Open a handle that only sees rows matching `scope` (a Lance SQL predicate such
+    /// as `tenant_id = 42`). The scope is AND-ed into every search, scan, count and
+    /// delete made through the handle and cannot be changed or removed later.
+    pub fn open_scoped(db_path: &str, table_name: &str, metric: &str, scope: &str) -> Result<Self> {
+        if scope.trim().is_empty() {
+            return Err(anyhow!("scope filter must not be empty"));
+        }
+        let mut index = Self::open(db_path, table_name, metric)?;
+        // Reject filters Lance cannot evaluate now rather than on first use
+        runtime::block_on(index.get_table()?.count_rows(Some(scope.to_string())))
+            .map_err(|e| anyhow!("invalid scope filter '{}': {}", scope, e))?;
+        index.scope = Some(scope.to_string());
+        Ok(index)
+    }
+
+    /// Report what table `table_name` offers to the index layer (key, vector columns
+    /// and dims, indices) and why it cannot be opened as an index, if it cannot,
+    /// without opening it as one.
+    pub fn inspect(db_path: &str, table_name: &str) -> Result<TableCapabilities> {
+        let connection = storage_options::connect(db_path, &[])?;
+        let table = Self::open_table(&connection, table_name)?;
+        let mut capabilities = TableCapabilities::from_schema(&Self::read_table_schema(&table)?);
+        if capabilities.label.as_ref().is_some_and(|label| label.is_nullable()) {
+            capabilities.null_labels = runtime::block_on(table.count_rows(Some("label IS NULL".to_string())))? as u64;
+        }
+        capabilities.indices = runtime::block_on(table.list_indices())?
+            .into_iter()
+            .map(|index| IndexInfo {
+                name: index.name,
+                index_type: index.index_type.to_string(),
+                columns: index.columns,
+            })
+            .collect();
+        Ok(capabilities)
+    }
+
+    /// The handle's mandatory filter, if it was opened with one.
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// Refuse whole-table operations on scoped handles, which would reach rows
+    /// outside the scope.
+    fn require_unscoped(&self, what: &str) -> Result<()> {
+        match &self.scope {
+            Some(scope) => Err(anyhow!("{} is not allowed on a handle scoped to '{}'", what, scope)),
+            None => Ok(()),
+        }
+    }
+
+    /// Refuse operations that change how rows are stored while other handles in this
+    /// process, which would keep writing rows the old way, are open for writing.
+    fn require_sole_writer(&self, what: &str) -> Result<()> {
+        match self.handle_entry.other_writers() {
+            0 => Ok(()),
+            n => Err(anyhow!("{} needs the only writable handle on {}; {} others are open", what, self.table_name, n)),
+        }
+    }
+
+    /// Element type the `vector` column is stored with.
+    pub fn vector_storage(&self) -> VectorStorage {
+        VectorStorage::of_schema(&self.schema)
+    }
+
+    /// Refuse operations that rewrite stored vectors as Float32 on tables storing
+    /// them narrower.
+    fn require_float32_vectors(&self, what: &str) -> Result<()> {
+        match self.vector_storage() {
+            VectorStorage::Float32 => Ok(()),
+            storage => Err(anyhow!("{} needs float32 vector storage; {} stores {}", what, self.table_name, storage)),
+        }
+    }
+
+    /// Scale and offset int8 vectors are stored with, once fitted or set.
+    pub fn int8_quantizer(&self) -> Option<Int8Quantizer> {
+        self.quantizer.read().ok().and_then(|q| *q)
+    }
+
+    /// Quantize the table's int8 vectors over `min..=max` (see [`crate::quantize`]).
+    /// Required before the first rows arrive, and only allowed while the table is
+    /// empty. Persisted in the table metadata.
+    pub fn set_int8_range(&self, min: f32, max: f32) -> Result<()> {
+        self.require_unscoped("set_int8_range")?;
+        if self.vector_storage() != VectorStorage::Int8 {
+            return Err(anyhow!("{} stores {} vectors, not int8", self.table_name, self.vector_storage()));
+        }
+        let quantizer = Int8Quantizer::for_range(min, max)?;
+        self.flush_appends()?;
+        let table = self.get_table()?;
+        if runtime::block_on(table.count_rows(None))? > 0 {
+            return Err(anyhow!("the int8 range of {} can only be set while it is empty", self.table_name));
+        }
+        metadata::set(&table, metadata::INT8_QUANTIZATION, Some(&quantizer.encode()))?;
+        *self.quantizer.write().map_err(|_| anyhow!("quantizer lock poisoned"))? = Some(quantizer);
+        Ok(())
+    }
+
+    /// The int8 quantizer set with [`LanceIndex::set_int8_range`].
+    fn require_quantizer(&self) -> Result<Int8Quantizer> {
+        if let Some(quantizer) = self.int8_quantizer() {
+            return Ok(quantizer);
+        }
+        // Another handle may have set it since this one opened
+        let quantizer = metadata::get(&self.get_table()?, metadata::INT8_QUANTIZATION)?
+            .map(|spec| Int8Quantizer::decode(&spec))
+            .transpose()?
+            .ok_or_else(|| anyhow!("{} stores int8 vectors but has no int8 range; set one first", self.table_name))?;
+        *self.quantizer.write().map_err(|_| anyhow!("quantizer lock poisoned"))? = Some(quantizer);
+        Ok(quantizer)
+    }
+
+    /// Quantize Float32 `vectors` to int8, adding the rows outside the int8 range to
+    /// `invalid` instead of failing.
+    fn quantize_checked(&self, vectors: &FixedSizeListArray, invalid: &mut Vec<Violation>) -> Result<ArrayRef> {
+        let quantizer = self.require_quantizer()?;
+        invalid.extend(quantizer.check_range(vectors)?);
+        Ok(Arc::new(quantizer.quantize_saturating(vectors)?))
+    }
+
+    /// Narrow Float32 `vectors` to the table's storage, quantizing int8.
+    fn narrow_vectors(&self, vectors: FixedSizeListArray) -> Result<FixedSizeListArray> {
+        match self.vector_storage() {
+            VectorStorage::Int8 => self.require_quantizer()?.quantize(&vectors),
+            storage => vector_storage::narrow(vectors, storage),
+        }
+    }
+
+    /// Stored `vectors` with Float32 elements, widened or dequantized.
+    fn widen_vectors<'a>(&self, vectors: &'a FixedSizeListArray) -> Result<Cow<'a, FixedSizeListArray>> {
+        if vectors.value_type() != DataType::Int8 {
+            return vector_storage::widen(vectors);
+        }
+        let quantizer = self
+            .int8_quantizer()
+            .ok_or_else(|| anyhow!("{} has int8 vectors but no recorded quantization", self.table_name))?;
+        Ok(Cow::Owned(quantizer.dequantize(vectors)?))
+    }
+
+    /// `column` as Float32 vectors of the table's dimension.
+    fn float_vectors(&self, column: &ArrayRef) -> Result<FixedSizeListArray> {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        arrow::compute::cast(column, &DataType::FixedSizeList(item, self.dimension as i32))?
+            .as_any()
+            .downcast_ref::<FixedSizeListArray>()
+            .cloned()
+            .ok_or_else(|| anyhow!("vector not FixedSizeList"))
+    }
+
+    /// Rows of converted incoming columns (the table's columns after the label) whose
+    /// multivectors cannot be searched.
+    fn check_multivectors(&self, values: &[ArrayRef]) -> Vec<Violation> {
+        self.schema
+            .fields()
+            .iter()
+            .skip(1)
+            .zip(values)
+            .filter(|(field, _)| multivector::dimension(field.data_type()).is_some())
+            .flat_map(|(field, column)| multivector::check_rows(field.name(), column))
+            .collect()
+    }
+
+    /// Quantize the vector column of converted incoming columns (the table's columns
+    /// after the label) on int8 tables, where they arrive as Float32. Rows outside the
+    /// int8 range are added to `invalid`.
+    fn quantize_incoming(&self, mut values: Vec<ArrayRef>, invalid: &mut Vec<Violation>) -> Result<Vec<ArrayRef>> {
+        if self.vector_storage() != VectorStorage::Int8 {
+            return Ok(values);
+        }
+        let index = self.schema.index_of("vector")? - 1;
+        let vectors = self.float_vectors(&values[index])?;
+        values[index] = self.quantize_checked(&vectors, invalid)?;
+        Ok(values)
+    }
+
+    /// Refuse fitting vector transforms while a re-embed is replacing the vectors.
+    fn require_no_reembed(&self, what: &str) -> Result<()> {
+        match self.reembed_dimension() {
+            Some(dim) => {
+                Err(anyhow!("{} is not allowed while {} is re-embedded to dimension {}", what, self.table_name, dim))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// `predicate` restricted to the handle's scope.
+    fn scoped(&self, predicate: Option<&str>) -> Option<String> {
+        and_filters(self.scope.as_deref(), predicate)
+    }
+
+    /// Open a read-only handle pinned to the table version this handle currently
+    /// sees, so several searches and scans observe one dataset while appends
+    /// continue. Scope and per-handle search settings carry over; table settings
+    /// are read from the latest version. Writes through the snapshot fail.
+    /// Pass it to [`LanceIndex::release_snapshot`] when done.
+    pub fn begin_read_snapshot(&self) -> Result<LanceIndex> {
+        let table = self.get_table()?;
+        let version = runtime::block_on(table.version())?;
+        let mut snapshot = Self::open(self.connection.uri(), &self.table_name, &self.metric)?;
+        runtime::block_on(snapshot.get_table()?.checkout(version))?;
+        snapshot.snapshot_version = Some(version);
+        snapshot.handle_entry.set_read_only();
+        snapshot.scope = self.scope.clone();
+        snapshot.set_hit_budget(self.hit_budget())?;
+        snapshot.set_read_batch_size(self.read_batch_size());
+        let rescore_metric = self.rescore_metric.read().ok().and_then(|m| m.clone());
+        snapshot.set_rescore_metric(rescore_metric.as_deref())?;
+        Ok(snapshot)
+    }
+
+    /// Close a handle from [`LanceIndex::begin_read_snapshot`]. Errors for live
+    /// handles, which are closed all the same.
+    pub fn release_snapshot(self) -> Result<()> {
+        if self.snapshot_version.is_none() {
+            return Err(anyhow!("{} is not a read snapshot", self.table_name));
+        }
+        Ok(())
+    }
+
+    /// Version a read snapshot is pinned to; `None` for live handles.
+    pub fn snapshot_version(&self) -> Option<u64> {
+        self.snapshot_version
+    }
+
     /// Read the schema from a Lance table via its metadata (no data query needed).
     fn read_table_schema(table: &LanceTable) -> Result<Arc<Schema>> {
         Ok(runtime::block_on(table.schema())?)
@@ -193,58 +1181,271 @@ impl LanceIndex {
         &self.metric
     }
 
-    /// Clone the table handle. LanceTable is Arc-based (O(1) clone).
-    fn get_table(&self) -> Result<LanceTable> {
-        self.table
-            .as_ref()
-            .ok_or_else(|| anyhow!("table not open"))
-            .cloned()
+    /// Current concurrency limits for searches and maintenance on this handle.
+    pub fn admission_limits(&self) -> AdmissionLimits {
+        self.admission.limits()
     }
 
-    /// Add a single vector. Returns the assigned label.
-    pub fn add_vector(&self, vector: &[f32]) -> Result<i64> {
-        if vector.len() != self.dimension {
-            return Err(anyhow!(
-                "expected dimension {}, got {}",
-                self.dimension,
-                vector.len()
-            ));
-        }
+    /// Replace the concurrency limits for this handle.
+    pub fn set_admission_limits(&self, limits: AdmissionLimits) -> Result<()> {
+        self.admission.set_limits(limits)
+    }
 
-        let label = self.next_label.fetch_add(1, Ordering::Relaxed);
-        let batch = self.make_batch(&[label], &[vector])?;
+    /// Resource caps applied to vector index builds on this handle.
+    pub fn build_limits(&self) -> BuildLimits {
+        self.build_limits.read().map(|l| *l).unwrap_or_default()
+    }
 
-        let table = self.get_table()?;
-        let batches = RecordBatchIterator::new(vec![Ok(batch)], self.schema.clone());
-        runtime::block_on(table.add(Box::new(batches)).execute())?;
+    /// Replace the index build caps. Builds already running keep the old caps.
+    pub fn set_build_limits(&self, limits: BuildLimits) -> Result<()> {
+        *self
+            .build_limits
+            .write()
+            .map_err(|_| anyhow!("build limits lock poisoned"))? = limits;
+        Ok(())
+    }
 
-        Ok(label)
+    pub fn hit_budget(&self) -> HitBudget {
+        self.hit_budget.read().map(|b| *b).unwrap_or_default()
     }
 
-    /// Add a batch of contiguous vectors. Returns labels.
-    ///
-    /// `vectors` must be a flat contiguous array: [v0_d0, v0_d1, ..., v1_d0, v1_d1, ...].
-    pub fn add_batch(&self, vectors: &[f32], num_vectors: usize) -> Result<Vec<i64>> {
-        if vectors.len() != num_vectors * self.dimension {
-            return Err(anyhow!("vector data size mismatch"));
+    /// Replace the budget filtered searches spend to return k hits.
+    pub fn set_hit_budget(&self, budget: HitBudget) -> Result<()> {
+        *self
+            .hit_budget
+            .write()
+            .map_err(|_| anyhow!("hit budget lock poisoned"))? = budget;
+        Ok(())
+    }
+
+    /// What `merge_from` does about the rows it appends unindexed.
+    pub fn merge_indexing(&self) -> MergeIndexing {
+        self.merge_indexing.read().map(|m| *m).unwrap_or_default()
+    }
+
+    pub fn set_merge_indexing(&self, indexing: MergeIndexing) -> Result<()> {
+        *self
+            .merge_indexing
+            .write()
+            .map_err(|_| anyhow!("merge indexing lock poisoned"))? = indexing;
+        Ok(())
+    }
+
+    /// Use `metric` (a built-in or registered custom metric) instead of the handle's
+    /// metric for exact distances: `search_within`, PCA and pipeline rescoring, and
+    /// expansion hops. `None` restores the handle's metric.
+    pub fn set_rescore_metric(&self, metric: Option<&str>) -> Result<()> {
+        if let Some(metric) = metric {
+            distance::Metric::resolve(metric)?;
         }
+        *self
+            .rescore_metric
+            .write()
+            .map_err(|_| anyhow!("rescore metric lock poisoned"))? = metric.map(str::to_string);
+        Ok(())
+    }
 
-        let start_label = self.next_label.fetch_add(num_vectors as i64, Ordering::Relaxed);
-        let labels: Vec<i64> = (start_label..start_label + num_vectors as i64).collect();
+    /// The metric exact distances are computed with.
+    fn exact_metric(&self) -> Result<distance::Metric> {
+        let name = self.rescore_metric.read().ok().and_then(|m| m.clone());
+        distance::Metric::resolve(name.as_deref().unwrap_or(&self.metric))
+    }
 
-        let batch = self.make_batch_contiguous(&labels, vectors)?;
+    /// Rows per batch Lance produces for search results on this handle; 0 uses
+    /// Lance's default.
+    pub fn set_read_batch_size(&self, rows: usize) {
+        self.read_batch_size.store(rows, Ordering::Relaxed);
+    }
 
-        let table = self.get_table()?;
-        let batches = RecordBatchIterator::new(vec![Ok(batch)], self.schema.clone());
-        runtime::block_on(table.add(Box::new(batches)).execute())?;
+    pub fn read_batch_size(&self) -> usize {
+        self.read_batch_size.load(Ordering::Relaxed)
+    }
 
-        Ok(labels)
+    fn read_options(&self) -> QueryExecutionOptions {
+        let mut options = QueryExecutionOptions::default();
+        let rows = self.read_batch_size();
+        if rows > 0 {
+            options.max_batch_length = rows.min(u32::MAX as usize) as u32;
+        }
+        options
     }
 
-    /// Add a batch of rows via Arrow C Data Interface (multi-column path).
+    /// Retrieval pipeline applied by `search`, if one is configured.
+    pub fn pipeline(&self) -> Option<Pipeline> {
+        self.pipeline.read().ok().and_then(|p| p.clone())
+    }
+
+    /// Configure (or, with `None`, remove) the table's retrieval pipeline.
+    /// Persisted in the table metadata, so it applies to every handle opened later.
+    pub fn set_pipeline(&self, pipeline: Option<Pipeline>) -> Result<()> {
+        let spec = pipeline.as_ref().map(|p| p.to_string());
+        metadata::set(&self.get_table()?, metadata::PIPELINE, spec.as_deref())?;
+        *self
+            .pipeline
+            .write()
+            .map_err(|_| anyhow!("pipeline lock poisoned"))? = pipeline;
+        Ok(())
+    }
+
+    /// Clone the table handle. LanceTable is Arc-based (O(1) clone).
     ///
-    /// The incoming Arrow struct has columns matching the table schema minus the label column.
-    /// Labels are auto-generated. Returns assigned labels.
+    /// Catches up with commits made through sibling handles first, so every
+    /// operation sees writes made earlier in this process.
+    fn get_table(&self) -> Result<LanceTable> {
+        if self.reconnect_needed.swap(false, Ordering::AcqRel) {
+            if let Err(e) = self.reconnect() {
+                self.reconnect_needed.store(true, Ordering::Release);
+                return Err(e);
+            }
+        }
+        let table = self
+            .table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .ok_or_else(|| anyhow!("table not open"))
+            .cloned()?;
+        self.sync(&table)?;
+        self.handle_entry.touch();
+        Ok(table)
+    }
+
+    /// Reopen the table over a fresh store connection, keeping a read snapshot on
+    /// its pinned version and a replica on its imported one.
+    fn reconnect(&self) -> Result<()> {
+        let table = Self::open_table(&self.connection, &self.table_name)?;
+        if let Some(version) = self.snapshot_version.or(self.imported_version()) {
+            runtime::block_on(table.checkout(version))?;
+        }
+        *self.table.write().unwrap_or_else(|e| e.into_inner()) = Some(table);
+        Ok(())
+    }
+
+    /// Run the read `op`; after a connection error, reopen the table and run it once
+    /// more.
+    fn with_reconnect<T>(&self, op: impl Fn() -> Result<T>) -> Result<T> {
+        match op() {
+            Err(e) if health::is_connection_error(&e) => {
+                self.reconnect().map_err(|_| e)?;
+                op()
+            }
+            result => result,
+        }
+    }
+
+    /// Pass on the result of the write that produced it, making the next operation
+    /// reconnect first after a connection error. Writes are not retried: the commit
+    /// may have landed before the connection failed.
+    fn note_failure<T>(&self, result: Result<T>) -> Result<T> {
+        if let Err(e) = &result {
+            if health::is_connection_error(e) {
+                self.reconnect_needed.store(true, Ordering::Release);
+            }
+        }
+        result
+    }
+
+    /// Check that the store is reachable and the table manifest readable, by opening
+    /// the table afresh. After a connection error the handle reconnects before its
+    /// next operation.
+    pub fn ping(&self) -> Result<Ping> {
+        let start = Instant::now();
+        let result = Self::open_table(&self.connection, &self.table_name)
+            .and_then(|table| Ok(runtime::block_on(table.version())?));
+        let version = self.note_failure(result)?;
+        Ok(Ping {
+            version,
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        })
+    }
+
+    /// Check out the latest version if another handle committed since the last sync.
+    /// Read snapshots stay on their pinned version, replicas on their imported one,
+    /// and reads at [`Consistency::Eventual`] skip the check.
+    ///
+    /// Per-handle caches of table settings (pipeline, quota, ...) are not reloaded;
+    /// only data, indices and the label watermark are.
+    fn sync(&self, table: &LanceTable) -> Result<()> {
+        if self.snapshot_version.is_some() || self.imported_version().is_some() || consistency::is_eventual() {
+            return Ok(());
+        }
+        let generation = self.watch.generation();
+        if self.synced.load(Ordering::Acquire) >= generation {
+            return Ok(());
+        }
+        self.catch_up(table, generation)
+    }
+
+    /// Check out the latest version and pick up what other writers changed, then
+    /// mark the handle synced with `generation`.
+    fn catch_up(&self, table: &LanceTable, generation: u64) -> Result<()> {
+        runtime::block_on(table.checkout_latest())?;
+        // A sibling may have assigned labels past ours
+        let max_label = self.catch_up_labels(table)?;
+        self.next_label.fetch_max(max_label + 1, Ordering::SeqCst);
+        // try_lock: a recompute holding the lock (which reads through here) already
+        // sees the latest rows
+        if let Ok(mut stats) = self.vector_stats.try_lock() {
+            *stats = None;
+        }
+        self.synced.fetch_max(generation, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Tell sibling handles that this handle committed to the table.
+    fn committed(&self) {
+        self.watch.bump();
+    }
+
+    /// Add a single vector. Returns the assigned label.
+    pub fn add_vector(&self, vector: &[f32]) -> Result<i64> {
+        if vector.len() != self.dimension {
+            return Err(anyhow!(
+                "expected dimension {}, got {}",
+                self.dimension,
+                vector.len()
+            ));
+        }
+
+        let label = self.next_label.fetch_add(1, Ordering::Relaxed);
+        let batch = self.with_rotation(self.make_batch(&[label], &[vector])?)?;
+
+        self.append_or_buffer(batch)?;
+
+        Ok(label)
+    }
+
+    /// Add a batch of contiguous vectors. Returns labels.
+    ///
+    /// `vectors` must be a flat contiguous array: [v0_d0, v0_d1, ..., v1_d0, v1_d1, ...].
+    pub fn add_batch(&self, vectors: &[f32], num_vectors: usize) -> Result<Vec<i64>> {
+        self.add_batch_buffer(ScalarBuffer::from(Buffer::from_slice_ref(vectors)), num_vectors)
+    }
+
+    /// Add a batch of contiguous vectors held in an Arrow buffer, which becomes the
+    /// vector column without another copy (unless a rotation is trained). Returns labels.
+    pub fn add_batch_buffer(&self, vectors: ScalarBuffer<f32>, num_vectors: usize) -> Result<Vec<i64>> {
+        if vectors.len() != num_vectors * self.dimension {
+            return Err(anyhow!("vector data size mismatch"));
+        }
+
+        let start_label = self.next_label.fetch_add(num_vectors as i64, Ordering::Relaxed);
+        let labels: Vec<i64> = (start_label..start_label + num_vectors as i64).collect();
+
+        let batch = self.with_rotation(self.make_batch_contiguous(&labels, vectors)?)?;
+
+        self.append_or_buffer(batch)?;
+
+        Ok(labels)
+    }
+
+    /// Add a batch of rows via Arrow C Data Interface (multi-column path).
+    ///
+    /// The incoming Arrow struct has columns matching the table schema minus the label column,
+    /// by position or by name (see [`LanceIndex::set_column_matching`]).
+    /// Labels are auto-generated. Returns assigned labels, one per incoming row; rows
+    /// dropped by validation (see [`LanceIndex::set_on_invalid`]) or by the table's
+    /// constraints (see [`LanceIndex::set_constraints`]) get -1.
     ///
     /// # Safety
     /// Caller must pass valid pointers to Arrow C Data Interface structs.
@@ -252,6 +1453,18 @@ impl LanceIndex {
         &self,
         ffi_schema_ptr: *mut FFI_ArrowSchema,
         ffi_array_ptr: *mut FFI_ArrowArray,
+    ) -> Result<Vec<i64>> {
+        self.append_arrow(ffi_schema_ptr, ffi_array_ptr, false)
+    }
+
+    /// [`LanceIndex::add_batch_arrow`]; with `partial`, rows that fail validation or
+    /// a constraint under an `error` mode are dropped like under `skip` instead of
+    /// failing the batch.
+    unsafe fn append_arrow(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+        partial: bool,
     ) -> Result<Vec<i64>> {
         // Take ownership of the ArrowArray, leaving an empty one in C++ to prevent double-free
         let ffi_array = std::mem::replace(&mut *ffi_array_ptr, FFI_ArrowArray::empty());
@@ -268,49 +1481,363 @@ impl LanceIndex {
             return Ok(vec![]);
         }
 
+        // Convert to the table schema types (e.g., FixedSizeList child field name may differ)
+        let plan = self.cast_plans.plan(struct_array.fields(), self.column_matching())?;
+        let values = plan.apply(struct_array.columns())?;
+        let mut invalid = plan.failed_rows(struct_array.columns(), &values);
+        let vector_column = self.schema.index_of("vector")? - 1;
+        if let Some(vectors) = values[vector_column].as_any().downcast_ref::<FixedSizeListArray>() {
+            invalid.extend(rejects::check_vectors(vectors));
+        }
+        invalid.extend(self.check_multivectors(&values));
+        let values = self.quantize_incoming(values, &mut invalid)?;
+        let (values, rejected) = self.reject_rows(values, invalid, partial)?;
+        let accepted = num_rows - rejected.len();
+        if accepted == 0 {
+            return Ok(vec![-1; num_rows]);
+        }
+
         // Generate labels
-        let start_label = self.next_label.fetch_add(num_rows as i64, Ordering::Relaxed);
-        let labels: Vec<i64> = (start_label..start_label + num_rows as i64).collect();
-        let label_array = Int64Array::from(labels.clone());
+        let start_label = self.next_label.fetch_add(accepted as i64, Ordering::Relaxed);
+        let label_array = Int64Array::from_iter_values(start_label..start_label + accepted as i64);
+        let mut labels = label_array.values().to_vec();
 
         // Build columns: [label, vector, extra1, extra2, ...]
-        // Use cast to match the table schema types (e.g., FixedSizeList child field name may differ)
-        let mut columns: Vec<ArrayRef> = Vec::with_capacity(1 + struct_array.num_columns());
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(1 + values.len());
         columns.push(Arc::new(label_array));
-        for i in 0..struct_array.num_columns() {
-            let col = struct_array.column(i);
-            let target_type = self.schema.field(i + 1).data_type();
-            if col.data_type() == target_type {
-                columns.push(col.clone());
+        columns.extend(values);
+
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)
+            .map_err(|e| anyhow!("RecordBatch schema mismatch: {}", e))?;
+        let batch = self.with_rotation(batch)?;
+
+        self.append_or_buffer(batch)?;
+
+        for row in rejected {
+            labels.insert(row, -1);
+        }
+        Ok(labels)
+    }
+
+    /// Drop the rows of an incoming batch (its non-label columns) that failed
+    /// validation, per the table's `on_invalid` mode, or break the table's
+    /// constraints, per their `on_violation` mode. Returns the columns of the rows to
+    /// append and the indices of the rows dropped, in order. Each dropped row is
+    /// dead-lettered at most once, with its first reason. With `partial`, `error`
+    /// modes act as `skip`.
+    fn reject_rows(
+        &self,
+        values: Vec<ArrayRef>,
+        mut invalid: Vec<Violation>,
+        partial: bool,
+    ) -> Result<(Vec<ArrayRef>, Vec<usize>)> {
+        let mode = |mode: OnViolation| match mode {
+            OnViolation::Error if partial => OnViolation::Skip,
+            mode => mode,
+        };
+        let constraints = self.constraints();
+        if invalid.is_empty() && constraints.is_none() {
+            return Ok((values, vec![]));
+        }
+        // Failed casts leave NULLs in columns that may be declared non-nullable.
+        let fields: Vec<Field> = self
+            .schema
+            .fields()
+            .iter()
+            .filter(|f| f.name() != "label")
+            .map(|f| f.as_ref().clone().with_nullable(true))
+            .collect();
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), values)
+            .map_err(|e| anyhow!("RecordBatch schema mismatch: {}", e))?;
+
+        invalid.sort_by_key(|v| v.row);
+        invalid.dedup_by_key(|v| v.row);
+        let on_invalid = mode(self.on_invalid());
+        if let (Some(first), OnViolation::Error) = (invalid.first(), on_invalid) {
+            return Err(anyhow!(
+                "invalid row {} ({} rows in all): {}",
+                first.row,
+                invalid.len(),
+                first.reason
+            ));
+        }
+        let mut keep = vec![true; batch.num_rows()];
+        let mut dead_letter = Vec::new();
+        for violation in invalid {
+            keep[violation.row] = false;
+            if on_invalid == OnViolation::DeadLetter {
+                dead_letter.push(violation);
+            }
+        }
+
+        if let Some(constraints) = constraints {
+            let violations: Vec<Violation> =
+                constraints.check(&batch)?.into_iter().filter(|v| keep[v.row]).collect();
+            let on_violation = mode(constraints.on_violation);
+            if let (Some(first), OnViolation::Error) = (violations.first(), on_violation) {
+                return Err(ConstraintViolated {
+                    first: first.clone(),
+                    rows: violations.len(),
+                }
+                .into());
+            }
+            for violation in violations {
+                keep[violation.row] = false;
+                if on_violation == OnViolation::DeadLetter {
+                    dead_letter.push(violation);
+                }
+            }
+        }
+
+        if !dead_letter.is_empty() {
+            dead_letter.sort_by_key(|v| v.row);
+            let now = access::now_ms();
+            let rejected = dead_letter
+                .iter()
+                .map(|v| {
+                    Ok(Reject {
+                        rejected_ms: now,
+                        source_row: v.row as i64,
+                        reason: v.reason.clone(),
+                        row: rejects::format_row(&batch, v.row)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.write_rejects(&rejected)?;
+        }
+        let rejected: Vec<usize> = keep.iter().enumerate().filter(|(_, k)| !**k).map(|(row, _)| row).collect();
+        if rejected.is_empty() {
+            return Ok((batch.columns().to_vec(), rejected));
+        }
+        let kept = arrow::compute::filter_record_batch(&batch, &BooleanArray::from(keep))?;
+        Ok((kept.columns().to_vec(), rejected))
+    }
+
+    /// What `add_batch_arrow` does with rows that fail validation.
+    pub fn on_invalid(&self) -> OnViolation {
+        self.on_invalid.read().map(|m| *m).unwrap_or_default()
+    }
+
+    /// Set what `add_batch_arrow` does with rows that fail validation: a NULL vector,
+    /// NULL, NaN or infinite vector elements, or a value that does not convert to its
+    /// column's type. `error` (the default) fails the batch, `skip` drops the rows and
+    /// `dead_letter` moves them to the rejects table. Persisted in the table metadata.
+    pub fn set_on_invalid(&self, mode: OnViolation) -> Result<()> {
+        let value = (mode != OnViolation::Error).then(|| mode.to_string());
+        metadata::set(&self.get_table()?, metadata::ON_INVALID, value.as_deref())?;
+        *self.on_invalid.write().map_err(|_| anyhow!("on_invalid lock poisoned"))? = mode;
+        Ok(())
+    }
+
+    /// How `add_batch_arrow` matches incoming columns to the table's.
+    pub fn column_matching(&self) -> ColumnMatching {
+        self.column_matching.read().map(|m| *m).unwrap_or_default()
+    }
+
+    /// Set how `add_batch_arrow` matches incoming columns to the table's columns
+    /// after the label: `positional` (the default) by position, `strict` by name
+    /// with unknown or missing columns failing the batch, `lenient` by name with
+    /// missing nullable columns filled with NULLs and unknown ones ignored.
+    /// Persisted in the table metadata.
+    pub fn set_column_matching(&self, matching: ColumnMatching) -> Result<()> {
+        let value = (matching != ColumnMatching::Positional).then(|| matching.to_string());
+        metadata::set(&self.get_table()?, metadata::COLUMN_MATCHING, value.as_deref())?;
+        *self
+            .column_matching
+            .write()
+            .map_err(|_| anyhow!("column_matching lock poisoned"))? = matching;
+        Ok(())
+    }
+
+    /// Ingest constraints checked by `add_batch_arrow`, if any are configured.
+    pub fn constraints(&self) -> Option<Constraints> {
+        self.constraints.read().ok().and_then(|c| c.clone())
+    }
+
+    /// Configure (or, with `None`, remove) the ingest constraints. Persisted in the
+    /// table metadata; rows already in the table are not checked.
+    pub fn set_constraints(&self, constraints: Option<Constraints>) -> Result<()> {
+        if let Some(constraints) = &constraints {
+            constraints.validate(&self.schema)?;
+        }
+        let spec = constraints.as_ref().map(|c| c.to_string());
+        metadata::set(&self.get_table()?, metadata::CONSTRAINTS, spec.as_deref())?;
+        *self.constraints.write().map_err(|_| anyhow!("constraints lock poisoned"))? = constraints;
+        Ok(())
+    }
+
+    /// Append rows to the rejects sidecar table.
+    fn write_rejects(&self, rejected: &[Reject]) -> Result<()> {
+        let sidecar = self
+            .sidecar_table(&rejects::sidecar_table_name(&self.table_name), rejects::sidecar_schema(), true)?
+            .ok_or_else(|| anyhow!("rejects table unavailable"))?;
+        let batch = rejects::to_record_batch(rejected)?;
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], rejects::sidecar_schema());
+        runtime::block_on(sidecar.add(Box::new(reader)).execute())?;
+        Ok(())
+    }
+
+    /// Rows captured in the rejects sidecar table, oldest first.
+    pub fn rejects(&self) -> Result<Vec<Reject>> {
+        let name = rejects::sidecar_table_name(&self.table_name);
+        let Some(sidecar) = self.sidecar_table(&name, rejects::sidecar_schema(), false)? else {
+            return Ok(vec![]);
+        };
+        let stream = runtime::block_on(sidecar.query().execute())?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let mut rejected = Vec::new();
+        for batch in &batches {
+            let int64 = |name: &str| -> Result<&Int64Array> {
+                batch
+                    .column_by_name(name)
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("rejects table column {} missing or not Int64", name))
+            };
+            let text = |name: &str| -> Result<&StringArray> {
+                batch
+                    .column_by_name(name)
+                    .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                    .ok_or_else(|| anyhow!("rejects table column {} missing or not VARCHAR", name))
+            };
+            let (times, rows, reasons, values) =
+                (int64("rejected_ms")?, int64("source_row")?, text("reason")?, text("row")?);
+            for i in 0..batch.num_rows() {
+                rejected.push(Reject {
+                    rejected_ms: times.value(i),
+                    source_row: rows.value(i),
+                    reason: reasons.value(i).to_string(),
+                    row: values.value(i).to_string(),
+                });
+            }
+        }
+        rejected.sort_by_key(|r| (r.rejected_ms, r.source_row));
+        Ok(rejected)
+    }
+
+    /// Split each row of `batch` into chunks with the table's chunking stage, embed
+    /// the chunks and append one row per chunk. `batch` holds the table's columns
+    /// except `label`, `vector` and the parent column; nullable columns may be left
+    /// out. Returns the source row and label of every chunk, in order. The label of
+    /// a document's first chunk is the parent id all its chunks share.
+    pub fn add_chunked(&self, batch: &RecordBatch) -> Result<Vec<(u32, i64)>> {
+        let chunking = self
+            .chunking()
+            .ok_or_else(|| anyhow!("no chunking stage is configured for {}", self.table_name))?;
+        let embed = chunk::embedder(&chunking.embedder)?;
+        let (expanded, source_rows) = chunking.expand(batch)?;
+        let num_rows = expanded.num_rows();
+        if num_rows == 0 {
+            return Ok(vec![]);
+        }
+
+        let texts = expanded
+            .column_by_name(&chunking.column)
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .ok_or_else(|| anyhow!("chunked column '{}' must be VARCHAR", chunking.column))?;
+        if let Some(row) = (0..num_rows).find(|i| texts.is_null(*i)) {
+            return Err(anyhow!("chunked column '{}' is NULL in row {}", chunking.column, source_rows[row]));
+        }
+        let chunk_texts: Vec<&str> = (0..num_rows).map(|i| texts.value(i)).collect();
+        let vectors = embed(&chunk_texts, self.dimension)?;
+        if vectors.len() != num_rows * self.dimension {
+            return Err(anyhow!(
+                "embedder '{}' returned {} values for {} chunks of dimension {}",
+                chunking.embedder,
+                vectors.len(),
+                num_rows,
+                self.dimension
+            ));
+        }
+
+        let vectors = Float32Array::from(vectors);
+
+        let start_label = self.next_label.fetch_add(num_rows as i64, Ordering::Relaxed);
+        let labels: Vec<i64> = (start_label..start_label + num_rows as i64).collect();
+        let mut parents = Vec::with_capacity(num_rows);
+        for (i, row) in source_rows.iter().enumerate() {
+            if i == 0 || source_rows[i - 1] != *row {
+                parents.push(labels[i]);
             } else {
-                // Cast to match schema (handles FixedSizeList child field name differences)
-                let casted = cast(col.as_ref(), target_type)
-                    .map_err(|e| anyhow!("cast column {} failed: {}", i, e))?;
-                columns.push(casted);
+                parents.push(parents[i - 1]);
             }
         }
 
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(self.schema.fields().len());
+        for field in self.schema.fields() {
+            let column: ArrayRef = match (field.name().as_str(), field.data_type()) {
+                ("label", _) => Arc::new(Int64Array::from(labels.clone())),
+                ("vector", DataType::FixedSizeList(_, dim)) => {
+                    Arc::new(self.narrow_vectors(Self::make_fixed_size_list(vectors.clone(), *dim))?)
+                }
+                (name, _) if name == chunking.parent => Arc::new(Int64Array::from(parents.clone())),
+                (name, data_type) => match expanded.column_by_name(name) {
+                    Some(column) => arrow::compute::cast(column, data_type)?,
+                    None if field.is_nullable() => arrow_array::new_null_array(data_type, num_rows),
+                    None => return Err(anyhow!("chunked rows are missing required column '{}'", name)),
+                },
+            };
+            columns.push(column);
+        }
         let batch = RecordBatch::try_new(self.schema.clone(), columns)
             .map_err(|e| anyhow!("RecordBatch schema mismatch: {}", e))?;
+        let batch = self.with_rotation(batch)?;
+        self.append_or_buffer(batch)?;
 
-        let table = self.get_table()?;
-        let batches = RecordBatchIterator::new(vec![Ok(batch)], self.schema.clone());
-        runtime::block_on(table.add(Box::new(batches)).execute())?;
+        Ok(source_rows.into_iter().zip(labels).collect())
+    }
 
-        Ok(labels)
+    /// Chunking stage applied by `add_chunked`, if one is configured.
+    pub fn chunking(&self) -> Option<Chunking> {
+        self.chunking.read().ok().and_then(|c| c.clone())
+    }
+
+    /// Configure (or, with `None`, remove) the chunking stage. Persisted in the
+    /// table metadata. The embedder is looked up when rows are added, so it may be
+    /// registered later.
+    pub fn set_chunking(&self, chunking: Option<Chunking>) -> Result<()> {
+        if let Some(chunking) = &chunking {
+            chunking.validate(&self.schema)?;
+        }
+        let spec = chunking.as_ref().map(|c| c.to_string());
+        metadata::set(&self.get_table()?, metadata::CHUNKING, spec.as_deref())?;
+        *self.chunking.write().map_err(|_| anyhow!("chunking lock poisoned"))? = chunking;
+        Ok(())
     }
 
     /// Merge live rows from source into self. All done in Rust, no extra FFI round-trip.
     ///
     /// `live_source_labels` are labels in the source that should be copied (not tombstoned).
-    /// Returns Vec<(old_label, new_label)> for the caller to update its mappings.
+    /// Columns are matched by name so an older-format source can be merged: differing
+    /// types are cast, missing nullable columns are filled with nulls and extra source
+    /// columns are dropped. With `strict`, any schema difference is an error instead.
+    ///
+    /// Merged rows start out unindexed; the handle's [`MergeIndexing`] decides whether
+    /// they are added to the vector index now, in the background, or left for the
+    /// next optimize. The report says how stale the index is afterwards.
     pub fn merge_from(
         &self,
         source: &LanceIndex,
         live_source_labels: &[i64],
-    ) -> Result<Vec<(i64, i64)>> {
+        strict: bool,
+    ) -> Result<MergeReport> {
+        // Checked up front so a strict merge fails before any rows are read
+        let reconciler = SchemaReconciler::new(&source.schema, &self.schema, strict)?;
+        // Rows are copied as stored, so both sides must store the same space
+        if source.rotation() != self.rotation() {
+            return Err(anyhow!("cannot merge tables with different rotations"));
+        }
+        let storages = (source.vector_storage(), self.vector_storage());
+        let int8 = storages.0 == VectorStorage::Int8 || storages.1 == VectorStorage::Int8;
+        if int8 && (storages.0 != storages.1 || source.int8_quantizer() != self.int8_quantizer()) {
+            return Err(anyhow!("cannot merge int8 vectors quantized differently"));
+        }
         if live_source_labels.is_empty() {
-            return Ok(vec![]);
+            return Ok(MergeReport {
+                mapping: Vec::new(),
+                staleness: self.index_staleness()?,
+                optimize_scheduled: false,
+            });
         }
 
         let source_table = source.get_table()?;
@@ -322,35 +1849,24 @@ impl LanceIndex {
             .collect::<Vec<_>>()
             .join(", ");
         let predicate = format!("label IN ({})", csv);
+        let predicate = source.scoped(Some(&predicate)).unwrap_or(predicate);
 
-        let results = runtime::block_on(
+        let mut results = runtime::block_on(
             source_table
                 .query()
                 .only_if(predicate)
                 .execute(),
         )?;
 
-        // Collect all batches from source
-        let source_batches: Vec<RecordBatch> = runtime::block_on(async {
-            let mut batches = Vec::new();
-            let mut stream = results;
-            while let Some(batch) = stream
-                .try_next()
-                .await
-                .map_err(|e| anyhow!("stream error: {}", e))?
-            {
-                batches.push(batch);
-            }
-            Ok::<_, anyhow::Error>(batches)
-        })?;
-
-        let mut label_mapping = Vec::new();
+        let mut label_mapping = Vec::with_capacity(live_source_labels.len());
         let table = self.get_table()?;
 
-        for batch in &source_batches {
+        // Each source batch is appended as it is read
+        while let Some(batch) = runtime::block_on(results.try_next()).map_err(|e| anyhow!("stream error: {}", e))? {
             if batch.num_rows() == 0 {
                 continue;
             }
+            self.admission.yield_to_interactive();
 
             // Extract old labels
             let old_label_col = batch
@@ -372,497 +1888,9441 @@ impl LanceIndex {
                 label_mapping.push((old_labels.value(i), new_labels[i]));
             }
 
-            // Build new batch: replace label column with new labels, keep everything else
-            let mut columns: Vec<ArrayRef> = Vec::with_capacity(batch.num_columns());
-            columns.push(Arc::new(new_label_array));
-            for i in 1..batch.num_columns() {
-                columns.push(batch.column(i).clone());
-            }
+            // Build new batch: replace label column with new labels, reconcile the rest
+            let new_batch = reconciler.apply(&batch, Arc::new(new_label_array))?;
 
-            let new_batch = RecordBatch::try_new(self.schema.clone(), columns)
-                .map_err(|e| anyhow!("merge batch schema mismatch: {}", e))?;
+            self.append_batch(&table, new_batch)?;
+        }
 
-            let batches_iter =
-                RecordBatchIterator::new(vec![Ok(new_batch)], self.schema.clone());
-            runtime::block_on(table.add(Box::new(batches_iter)).execute())?;
+        let mut optimize_scheduled = false;
+        match self.merge_indexing() {
+            MergeIndexing::None => {}
+            MergeIndexing::Optimize => {
+                if self.index_staleness()?.is_some() {
+                    self.optimize_indices()?;
+                }
+            }
+            MergeIndexing::Background => {
+                if self.index_staleness()?.is_some() {
+                    self.optimize_indices_in_background()?;
+                    optimize_scheduled = true;
+                }
+            }
         }
+        Ok(MergeReport {
+            mapping: label_mapping,
+            staleness: self.index_staleness()?,
+            optimize_scheduled,
+        })
+    }
 
-        Ok(label_mapping)
+    /// Coverage of the vector index, or `None` when the table has none.
+    pub fn index_staleness(&self) -> Result<Option<IndexStaleness>> {
+        let table = self.get_table()?;
+        let indices = runtime::block_on(table.list_indices())?;
+        let Some(index) = indices.iter().find(|i| i.columns.iter().any(|c| c == "vector")) else {
+            return Ok(None);
+        };
+        Ok(runtime::block_on(table.index_stats(&index.name))?.map(|stats| IndexStaleness {
+            indexed_rows: stats.num_indexed_rows as u64,
+            unindexed_rows: stats.num_unindexed_rows as u64,
+        }))
     }
 
-    /// Search for k nearest neighbors.
-    pub fn search(
-        &self,
-        query: &[f32],
-        k: usize,
-        nprobes: usize,
-        refine_factor: usize,
-    ) -> Result<Vec<(i64, f32)>> {
-        if query.len() != self.dimension {
-            return Err(anyhow!(
-                "expected query dimension {}, got {}",
-                self.dimension,
-                query.len()
-            ));
-        }
+    /// Build (or rebuild) a full-text index on the string column `column`, which
+    /// `hybrid_search` needs for its text side.
+    pub fn create_fts_index(&self, column: &str) -> Result<()> {
+        use lancedb::index::{scalar::FtsIndexBuilder, Index};
 
+        match self.schema.field_with_name(column).map(|f| f.data_type()) {
+            Ok(DataType::Utf8 | DataType::LargeUtf8) => {}
+            Ok(other) => return Err(anyhow!("cannot full-text index column '{}' of type {}", column, other)),
+            Err(_) => return Err(anyhow!("column '{}' not found", column)),
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
         let table = self.get_table()?;
-
-        let results = runtime::block_on(
+        runtime::block_on(
             table
-                .vector_search(query)
-                .map_err(|e| anyhow!("search setup: {}", e))?
-                .limit(k)
-                .nprobes(nprobes)
-                .refine_factor(refine_factor as u32)
+                .create_index(&[column], Index::FTS(FtsIndexBuilder::default()))
+                .replace(true)
                 .execute(),
         )?;
+        self.committed();
+        Ok(())
+    }
 
-        let mut output = Vec::with_capacity(k);
-
-        runtime::block_on(async {
-            let mut stream = results;
-            while let Some(batch) = stream.try_next().await
-                .map_err(|e| anyhow!("stream error: {}", e))? {
-                let label_col = batch
-                    .column_by_name("label")
-                    .ok_or_else(|| anyhow!("missing label column"))?;
-                let labels = label_col
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .ok_or_else(|| anyhow!("label column not Int64"))?;
-
-                let dist_col = batch
-                    .column_by_name("_distance")
-                    .ok_or_else(|| anyhow!("missing _distance column"))?;
-                let distances = dist_col
-                    .as_any()
-                    .downcast_ref::<Float32Array>()
-                    .ok_or_else(|| anyhow!("distance column not Float32"))?;
-
-                for i in 0..batch.num_rows() {
-                    output.push((labels.value(i), distances.value(i)));
-                }
-            }
-            Ok::<(), anyhow::Error>(())
-        })?;
+    /// Build (or rebuild) a scalar index on the metadata column `column`, so filters
+    /// on it (`language = 'rust'`, ranges, list membership) are answered from the
+    /// index instead of a full scan, also as the prefilter of vector searches.
+    /// `LabelList` takes list columns, the other kinds primitive ones.
+    pub fn create_scalar_index(&self, column: &str, index_type: ScalarIndexType) -> Result<()> {
+        use lancedb::index::scalar::{BTreeIndexBuilder, BitmapIndexBuilder, LabelListIndexBuilder};
+        use lancedb::index::Index;
 
-        Ok(output)
+        let data_type = self
+            .schema
+            .field_with_name(column)
+            .map_err(|_| anyhow!("column '{}' not found", column))?
+            .data_type();
+        let is_list = matches!(data_type, DataType::List(_) | DataType::LargeList(_));
+        let supported = match index_type {
+            ScalarIndexType::LabelList => is_list,
+            ScalarIndexType::BTree | ScalarIndexType::Bitmap => !data_type.is_nested(),
+        };
+        if !supported {
+            return Err(anyhow!(
+                "cannot build a {} index on column '{}' of type {}",
+                index_type.name(),
+                column,
+                data_type
+            ));
+        }
+        let index = match index_type {
+            ScalarIndexType::BTree => Index::BTree(BTreeIndexBuilder::default()),
+            ScalarIndexType::Bitmap => Index::Bitmap(BitmapIndexBuilder::default()),
+            ScalarIndexType::LabelList => Index::LabelList(LabelListIndexBuilder::default()),
+        };
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        runtime::block_on(table.create_index(&[column], index).replace(true).execute())?;
+        self.committed();
+        Ok(())
     }
 
-    /// Delete a vector by label.
-    pub fn delete(&self, label: i64) -> Result<()> {
+    /// Add unindexed rows to the existing indices, keeping their trained partitions.
+    pub fn optimize_indices(&self) -> Result<()> {
+        use lancedb::table::{OptimizeAction, OptimizeOptions};
+
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
         let table = self.get_table()?;
-        runtime::block_on(table.delete(&format!("label = {}", label)))?;
+        runtime::block_on(table.optimize(OptimizeAction::Index(OptimizeOptions::default())))?;
+        self.committed();
         Ok(())
     }
 
-    /// Delete multiple vectors by label in a single operation.
-    pub fn delete_batch(&self, labels: &[i64]) -> Result<()> {
-        if labels.is_empty() {
-            return Ok(());
-        }
-        let table = self.get_table()?;
+    /// `optimize_indices` on the runtime's blocking pool. Failures are dropped: the
+    /// rows stay unindexed until the next optimize.
+    fn optimize_indices_in_background(&self) -> Result<()> {
+        use lancedb::table::{OptimizeAction, OptimizeOptions};
 
-        let csv: String = labels.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
-        let predicate = format!("label IN ({})", csv);
-        runtime::block_on(table.delete(&predicate))?;
+        self.require_writer()?;
+        let (table, watch) = (self.get_table()?, self.watch.clone());
+        runtime::spawn_blocking(move || {
+            if runtime::block_on(table.optimize(OptimizeAction::Index(OptimizeOptions::default()))).is_ok() {
+                watch.bump();
+            }
+        });
         Ok(())
     }
 
-    /// Count vectors.
-    pub fn count(&self) -> Result<u64> {
+    /// Create the hidden staging sibling of this table (see [`crate::staging`]),
+    /// replacing any previous one.
+    ///
+    /// The staging table has the same schema and settings, minus the vector index
+    /// and clustering state. With `copy_rows`, every live row is copied with its
+    /// label unchanged, so label mappings held by the caller remain valid after
+    /// promotion.
+    pub fn create_staging(&self, copy_rows: bool) -> Result<LanceIndex> {
+        self.require_unscoped("staging")?;
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
         let table = self.get_table()?;
-        let count = runtime::block_on(table.count_rows(None))?;
-        Ok(count as u64)
-    }
+        let live_dir = self.local_dataset_dir("staging")?;
+        let db_dir = live_dir
+            .parent()
+            .ok_or_else(|| anyhow!("dataset {} has no parent directory", live_dir.display()))?;
+        let name = staging::staging_table_name(&self.table_name);
 
-    /// Create an ANN index (IVF_PQ).
-    ///
-    /// Pass 0 for num_partitions or num_sub_vectors to use LanceDB defaults.
-    pub fn create_ann_index(
-        &self,
-        num_partitions: u32,
-        num_sub_vectors: u32,
-    ) -> Result<()> {
-        let table = self.get_table()?;
+        let current = Self::read_table_schema(&table)?;
+        let mut settings = current.metadata().clone();
+        // The label watermark counts the live table's fragments, not the staging table's
+        let cleared = [
+            metadata::INDEX_METRIC,
+            metadata::INDEX_COMPRESSION,
+            metadata::CLUSTER_BY,
+            metadata::LABEL_WATERMARK,
+        ];
+        for key in cleared {
+            settings.remove(&metadata::full_key(key));
+        }
+        let schema = Arc::new(Schema::new_with_metadata(current.fields().clone(), settings));
+        let empty_batch = Self::empty_batch_from_schema(&schema)?;
+        let batches = RecordBatchIterator::new(vec![Ok(empty_batch)], schema.clone());
+        let _ = runtime::block_on(self.connection.drop_table(&name));
+        Self::create_table(&self.connection, &name, Box::new(batches))?;
 
-        use lancedb::index::vector::IvfPqIndexBuilder;
-        use lancedb::index::Index;
+        let db_path = db_dir.to_string_lossy();
+        let staging = Self::open(&db_path, &name, &self.metric)?;
+        staging.set_build_limits(self.build_limits())?;
+        if copy_rows {
+            let staging_table = staging.get_table()?;
+            let mut stream = runtime::block_on(table.query().execute())?;
+            while let Some(batch) = runtime::block_on(stream.try_next())
+                .map_err(|e| anyhow!("stream error: {}", e))?
+            {
+                if batch.num_rows() == 0 {
+                    continue;
+                }
+                self.admission.yield_to_interactive();
+                staging.append_batch(&staging_table, batch)?;
+            }
+            staging
+                .next_label
+                .store(self.next_label.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        Ok(staging)
+    }
 
-        let distance_type = match self.metric.as_str() {
-            "cosine" => lancedb::DistanceType::Cosine,
-            "dot" | "ip" => lancedb::DistanceType::Dot,
-            _ => lancedb::DistanceType::L2,
-        };
+    /// Swap `staging` (from [`LanceIndex::create_staging`]) in as this table and
+    /// reopen this handle on it; the staging table is dropped. The swap is committed
+    /// on this table (see [`crate::staging`]): its current version is tagged under
+    /// the returned name, one commit replaces its rows with the staging rows, and a
+    /// second carries over the staging indices, so searches in between run without
+    /// an index. If the second commit fails, the tagged version is restored.
+    pub fn promote_staging(&mut self, staging: LanceIndex) -> Result<String> {
+        use lance::dataset::transaction::{Operation, Transaction};
+        use lance::dataset::CommitBuilder;
+        use lance_index::DatasetIndexExt;
 
-        let mut builder = IvfPqIndexBuilder::default().distance_type(distance_type);
-        if num_partitions > 0 {
-            builder = builder.num_partitions(num_partitions);
+        self.require_unscoped("staging")?;
+        if staging.table_name != staging::staging_table_name(&self.table_name) {
+            return Err(anyhow!(
+                "{} is not the staging table of {}",
+                staging.table_name,
+                self.table_name
+            ));
+        }
+        if staging.dimension != self.dimension {
+            return Err(anyhow!(
+                "staging dimension {} does not match live dimension {}",
+                staging.dimension,
+                self.dimension
+            ));
         }
-        if num_sub_vectors > 0 {
-            builder = builder.num_sub_vectors(num_sub_vectors);
+        if self.rebuild_status().state == RebuildState::Running
+            || staging.rebuild_status().state == RebuildState::Running
+        {
+            return Err(anyhow!("cannot promote while an index rebuild is running"));
         }
 
-        runtime::block_on(
-            table
-                .create_index(&["vector"], Index::IvfPq(builder))
-                .replace(true)
-                .execute(),
-        )?;
+        let permit = self.admission.acquire(OpClass::Maintenance)?;
+        let live_dir = self.local_dataset_dir("staging")?;
+        let staging_dir = staging.local_dataset_dir("staging")?;
+        let db_dir = live_dir
+            .parent()
+            .ok_or_else(|| anyhow!("dataset {} has no parent directory", live_dir.display()))?
+            .to_path_buf();
+        let staging_name = staging.table_name.clone();
+        let staging_uri = staging.get_table()?.dataset_uri().to_string();
+        drop(staging);
+
+        let retired = staging::retired_tag_name(access::now_ms());
+        let uri = self.get_table()?.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        runtime::block_on(async {
+            let mut live = Self::load_dataset(&uri, &params, None).await?;
+            let staged = Self::load_dataset(&staging_uri, &params, None).await?;
+            let version = live.manifest().version;
+            live.tags.create(&retired, version).await?;
+            staging::link_dataset_files(&staging_dir, &live_dir)?;
 
+            let overwrite = Operation::Overwrite {
+                fragments: staged.get_fragments().iter().map(|f| f.metadata().clone()).collect(),
+                schema: staged.schema().clone(),
+                config_upsert_values: None,
+            };
+            let swapped = CommitBuilder::new(Arc::new(live))
+                .execute(Transaction::new(version, overwrite, None, None))
+                .await?;
+            let indices = staged.load_indices().await?.as_ref().clone();
+            if !indices.is_empty() {
+                let create = Operation::CreateIndex {
+                    new_indices: indices,
+                    removed_indices: Vec::new(),
+                };
+                let carried = CommitBuilder::new(Arc::new(swapped.clone()))
+                    .execute(Transaction::new(swapped.manifest().version, create, None, None))
+                    .await;
+                if let Err(e) = carried {
+                    // Serve the previous rows with their index rather than the new ones without
+                    let mut previous = swapped.checkout_version(retired.as_str()).await?;
+                    previous.restore().await?;
+                    return Err(anyhow!("promote {} failed: {} (restored tag {})", staging_name, e, retired));
+                }
+            }
+            Ok::<_, anyhow::Error>(())
+        })?;
+        self.committed();
+        let _ = runtime::block_on(self.connection.drop_table(&staging_name));
+
+        // Handle-level settings are not stored with the table; carry them over
+        let admission_limits = self.admission_limits();
+        let build_limits = self.build_limits();
+        drop(permit);
+        *self = Self::open(&db_dir.to_string_lossy(), &self.table_name, &self.metric)?;
+        self.set_admission_limits(admission_limits)?;
+        self.set_build_limits(build_limits)?;
+        Ok(retired)
+    }
+
+    /// Rename a table in the database at `db_path`, together with its sidecar tables.
+    /// Handles open on the table must be closed first and reopened under the new
+    /// name. Only local databases are supported.
+    pub fn rename_table(db_path: &str, old_name: &str, new_name: &str) -> Result<()> {
+        if new_name.is_empty() || new_name.contains(['/', '\\']) {
+            return Err(anyhow!("invalid table name '{}'", new_name));
+        }
+        let local = db_path.strip_prefix("file://").unwrap_or(db_path);
+        if local.contains("://") {
+            return Err(anyhow!("rename_table is only available for local databases, got {}", db_path));
+        }
+        let db_dir = Path::new(local);
+        staging::rename_table_dir(db_dir, old_name, new_name)?;
+
+        let sidecars = [
+            access::sidecar_table_name,
+            rejects::sidecar_table_name,
+            query_log::sidecar_table_name,
+            idempotency::sidecar_table_name,
+            cold::sidecar_table_name,
+        ];
+        for sidecar_table_name in sidecars {
+            let old_sidecar = sidecar_table_name(old_name);
+            if staging::table_dir(db_dir, &old_sidecar).is_dir() {
+                staging::rename_table_dir(db_dir, &old_sidecar, &sidecar_table_name(new_name))?;
+            }
+        }
         Ok(())
     }
 
-    /// Create an ANN index (IVF_HNSW_SQ).
-    ///
-    /// `m` is the number of edges per node in the HNSW graph.
-    /// `ef_construction` is the search width during index build.
-    pub fn create_hnsw_index(
-        &self,
-        m: u32,
-        ef_construction: u32,
-    ) -> Result<()> {
+    /// Local directory of this table's dataset. `what` names the operation that
+    /// needs it, for the error on remote datasets.
+    fn local_dataset_dir(&self, what: &str) -> Result<PathBuf> {
         let table = self.get_table()?;
+        let uri = table
+            .as_native()
+            .map(|t| t.uri().to_string())
+            .ok_or_else(|| anyhow!("{} requires a native Lance table", what))?;
+        let local = uri.strip_prefix("file://").unwrap_or(&uri);
+        if local.contains("://") {
+            return Err(anyhow!("{} is only available for local datasets, got {}", what, uri));
+        }
+        Ok(PathBuf::from(local))
+    }
 
-        use lancedb::index::vector::IvfHnswSqIndexBuilder;
-        use lancedb::index::Index;
+    /// Start buffering appends so that consecutive small ones are written as a single
+    /// append (one table version) of up to [`COALESCE_MAX_ROWS`] rows. Labels are still
+    /// assigned immediately, but buffered rows are not visible to reads, and quota
+    /// rejections surface, only when they are written. A no-op if a session is open.
+    pub fn begin_append_session(&self) -> Result<()> {
+        let mut pending = self
+            .pending_appends
+            .lock()
+            .map_err(|_| anyhow!("pending appends lock poisoned"))?;
+        pending.get_or_insert_with(Vec::new);
+        Ok(())
+    }
 
-        let distance_type = match self.metric.as_str() {
-            "cosine" => lancedb::DistanceType::Cosine,
-            "dot" | "ip" => lancedb::DistanceType::Dot,
-            _ => lancedb::DistanceType::L2,
+    /// Write the rows buffered by the append session, if any. Reports rows another
+    /// handle on the table lost when it was dropped with a session open.
+    pub fn flush_appends(&self) -> Result<()> {
+        self.report_lost_appends()?;
+        let batches = match self
+            .pending_appends
+            .lock()
+            .map_err(|_| anyhow!("pending appends lock poisoned"))?
+            .as_mut()
+        {
+            Some(batches) => std::mem::take(batches),
+            None => return Ok(()),
+        };
+        self.append_coalesced(batches)
+    }
+
+    /// Flush and close the append session; later appends are written directly.
+    /// Reports rows another handle on the table lost when it was dropped with a
+    /// session open.
+    pub fn end_append_session(&self) -> Result<()> {
+        self.report_lost_appends()?;
+        self.close_append_session()
+    }
+
+    fn close_append_session(&self) -> Result<()> {
+        let batches = self
+            .pending_appends
+            .lock()
+            .map_err(|_| anyhow!("pending appends lock poisoned"))?
+            .take()
+            .unwrap_or_default();
+        self.append_coalesced(batches)
+    }
+
+    /// Fail with the loss recorded when a handle on the table was dropped and could
+    /// not write its buffered rows; the rows' labels were handed out as if written.
+    fn report_lost_appends(&self) -> Result<()> {
+        match watch::take_lost_appends(self.handle_entry.path(), &self.table_name) {
+            Some(message) => Err(anyhow!(message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Append `batch`, or buffer it while an append session is open.
+    fn append_or_buffer(&self, batch: RecordBatch) -> Result<()> {
+        self.report_lost_appends()?;
+        let mut pending = self
+            .pending_appends
+            .lock()
+            .map_err(|_| anyhow!("pending appends lock poisoned"))?;
+        let Some(batches) = pending.as_mut() else {
+            drop(pending);
+            return self.append_batch(&self.get_table()?, batch);
+        };
+        batches.push(batch);
+        let buffered = batches.iter().map(RecordBatch::num_rows).sum::<usize>();
+        self.handle_entry.set_buffered_rows(buffered);
+        if buffered < COALESCE_MAX_ROWS {
+            return Ok(());
+        }
+        let batches = std::mem::take(batches);
+        drop(pending);
+        self.append_coalesced(batches)
+    }
+
+    /// Write buffered batches as one append.
+    fn append_coalesced(&self, batches: Vec<RecordBatch>) -> Result<()> {
+        self.handle_entry.set_buffered_rows(0);
+        let Some(first) = batches.first() else {
+            return Ok(());
         };
+        let batch = concat_batches(&first.schema(), &batches)?;
+        self.append_batch(&self.get_table()?, batch)
+    }
 
-        let mut builder = IvfHnswSqIndexBuilder::default()
-            .distance_type(distance_type);
-        if m > 0 {
-            builder = builder.num_edges(m);
+    /// Append `batch`, folding its vectors into the running statistics and
+    /// enforcing the table quota.
+    ///
+    /// The stats lock is not held across the commit; if the statistics were
+    /// recomputed meanwhile (they may or may not include the batch) they are dropped
+    /// instead of folded, so no row is counted twice.
+    fn append_batch(&self, table: &LanceTable, batch: RecordBatch) -> Result<()> {
+        self.require_writer()?;
+        let batch = self.with_projection(batch)?;
+        let quota = self.quota();
+        let mut usage = self.append_lock.lock().map_err(|_| anyhow!("append lock poisoned"))?;
+        if let Some(quota) = quota.as_ref().filter(|q| q.policy == QuotaPolicy::Reject) {
+            self.check_quota(quota, &mut usage, table, &batch)?;
+        }
+        let epoch = self.vector_stats_epoch.load(Ordering::Acquire);
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let added = runtime::block_on(table.add(Box::new(reader)).execute());
+        self.note_failure(added.map_err(anyhow::Error::from))?;
+        self.committed();
+        {
+            let mut stats = self
+                .vector_stats
+                .lock()
+                .map_err(|_| anyhow!("vector stats lock poisoned"))?;
+            if self.vector_stats_epoch.load(Ordering::Acquire) != epoch {
+                *stats = None;
+            } else if let (Some(stats), Some(vectors)) = (stats.as_mut(), Self::vector_column(&batch)) {
+                // Statistics describe vectors as ingested, before any rotation
+                match self.rotation() {
+                    Some(rotation) => stats.observe_array(&rotation.map_array(vectors, Rotation::invert)?)?,
+                    None => stats.observe_array(&self.widen_vectors(vectors)?)?,
+                }
+            }
         }
-        if ef_construction > 0 {
-            builder = builder.ef_construction(ef_construction);
+        Self::advance_quota_usage(&mut usage, table, &batch);
+        if let Some(quota) = quota.as_ref().filter(|q| q.policy != QuotaPolicy::Reject) {
+            self.enforce_quota_by_eviction(quota, &mut usage, table)?;
         }
+        Ok(())
+    }
 
-        runtime::block_on(
-            table
-                .create_index(&["vector"], Index::IvfHnswSq(builder))
-                .replace(true)
-                .execute(),
-        )?;
+    /// Row/size quota, if one is configured.
+    pub fn quota(&self) -> Option<Quota> {
+        self.quota.read().ok().and_then(|q| q.clone())
+    }
 
+    /// Configure (or, with `None`, remove) the quota. Persisted in the table metadata.
+    ///
+    /// The quota applies to later appends; rows already over it are left in place.
+    pub fn set_quota(&self, quota: Option<Quota>) -> Result<()> {
+        self.require_unscoped("set_quota")?;
+        if let Some(quota) = &quota {
+            match &quota.policy {
+                QuotaPolicy::EvictOldest { column } => {
+                    self.schema
+                        .field_with_name(column)
+                        .map_err(|_| anyhow!("eviction column '{}' not found", column))?;
+                }
+                QuotaPolicy::EvictLru if !self.access.enabled() => {
+                    return Err(anyhow!("evict_lru requires access tracking to be enabled"));
+                }
+                _ => {}
+            }
+            if quota.max_bytes.is_some() {
+                // Byte quotas measure the data files; fail now if they cannot be listed
+                self.disk_usage(false)?;
+            }
+        }
+        let spec = quota.as_ref().map(|q| q.to_string());
+        metadata::set(&self.get_table()?, metadata::QUOTA, spec.as_deref())?;
+        let mut usage = self.append_lock.lock().map_err(|_| anyhow!("append lock poisoned"))?;
+        // The cached usage may lack the bytes a new limit needs
+        *usage = None;
+        *self.quota.write().map_err(|_| anyhow!("quota lock poisoned"))? = quota;
         Ok(())
     }
 
-    /// Compact the dataset (optimize storage).
-    pub fn compact(&self) -> Result<()> {
+    /// Cold storage policy, if one is configured.
+    pub fn cold_storage(&self) -> Option<ColdStorage> {
+        self.cold_storage.read().ok().and_then(|c| c.clone())
+    }
+
+    /// Configure (or, with `None`, remove) the cold storage policy (see
+    /// [`crate::cold`]). Persisted in the table metadata and applied by compaction.
+    /// Rows already moved stay cold, at the precision they were moved with, which a
+    /// new policy cannot change.
+    pub fn set_cold_storage(&self, policy: Option<ColdStorage>) -> Result<()> {
+        self.require_unscoped("set_cold_storage")?;
+        if let Some(policy) = &policy {
+            self.require_float32_vectors("cold storage")?;
+            self.require_no_reembed("cold storage")?;
+            policy.validate(&self.schema)?;
+            let stored = self
+                .cold_table(false)?
+                .map(|sidecar| Self::read_table_schema(&sidecar))
+                .transpose()?
+                .and_then(|schema| cold::Precision::of_schema(&schema));
+            if let Some(stored) = stored.filter(|p| *p != policy.precision) {
+                return Err(anyhow!("cold rows of {} are already stored at {} precision", self.table_name, stored));
+            }
+        }
+        let spec = policy.as_ref().map(|p| p.to_string());
+        metadata::set(&self.get_table()?, metadata::COLD_STORAGE, spec.as_deref())?;
+        if policy.is_some() {
+            self.cold_rows.store(true, Ordering::Release);
+        }
+        *self.cold_storage.write().map_err(|_| anyhow!("cold storage lock poisoned"))? = policy;
+        Ok(())
+    }
+
+    /// The cold sidecar table, created on first use if `create` is set.
+    fn cold_table(&self, create: bool) -> Result<Option<LanceTable>> {
+        let precision = self.cold_storage().map_or(cold::Precision::F16, |p| p.precision);
+        let schema = cold::sidecar_schema(&self.schema, precision)?;
+        self.sidecar_table(&cold::sidecar_table_name(&self.table_name), schema, create)
+    }
+
+    /// Move the rows that turned cold under the policy into the cold sidecar, at its
+    /// reduced precision, and delete them from the table. Returns the rows moved.
+    fn move_cold_rows(&self) -> Result<usize> {
+        let Some(policy) = self.cold_storage() else {
+            return Ok(0);
+        };
+        self.require_no_reembed("moving cold rows")?;
         let table = self.get_table()?;
-        runtime::block_on(table.optimize(lancedb::table::OptimizeAction::All)).map(|_| ())?;
+        let column_type = self.schema.field_with_name(&policy.column)?.data_type().clone();
+        let predicate = policy.predicate(&column_type, policy.cutoff_ms(access::now_ms()));
+        let stream = runtime::block_on(table.query().only_if(predicate).execute())?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let sidecar = self.cold_table(true)?.ok_or_else(|| anyhow!("cold table not created"))?;
+        let cold_schema = Self::read_table_schema(&sidecar)?;
+        let mut moved = Vec::new();
+        let mut labels = Vec::new();
+        for batch in batches.iter().filter(|b| b.num_rows() > 0) {
+            let vectors = Self::vector_column(batch).ok_or_else(|| anyhow!("missing vector column"))?;
+            let values = vectors
+                .values()
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .ok_or_else(|| anyhow!("vector values not Float32"))?;
+            let size = vectors.value_length() as usize;
+            // Rows the precision cannot hold stay in the table
+            let fits: BooleanArray = (0..batch.num_rows())
+                .map(|row| {
+                    let start = vectors.value_offset(row) as usize;
+                    Some(policy.precision.fits(&values.values()[start..start + size]))
+                })
+                .collect();
+            let batch = arrow::compute::filter_record_batch(batch, &fits)?;
+            let batch_labels = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("missing label column"))?;
+            labels.extend(batch_labels.values().iter().copied());
+            moved.push(cold::narrow(&batch, &cold_schema)?);
+        }
+        if labels.is_empty() {
+            return Ok(0);
+        }
+        // Upsert by label: a move interrupted before its delete is redone, not duplicated
+        let reader = RecordBatchIterator::new(moved.into_iter().map(Ok), cold_schema);
+        let mut upsert = sidecar.merge_insert(&["label"]);
+        upsert.when_matched_update_all(None).when_not_matched_insert_all();
+        runtime::block_on(upsert.execute(Box::new(reader)))?;
+        self.cold_rows.store(true, Ordering::Release);
+
+        self.cover_labels(&table)?;
+        let csv: String = labels.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
+        runtime::block_on(table.delete(&format!("label IN ({})", csv)))?;
+        self.committed();
+        self.invalidate_vector_stats();
+        Ok(labels.len())
+    }
+
+    /// Merge the fragments the moves left in the cold sidecar.
+    fn compact_cold_table(&self) -> Result<()> {
+        use lancedb::table::{CompactionOptions, OptimizeAction};
+
+        if let Some(sidecar) = self.cold_table(false)? {
+            runtime::block_on(sidecar.optimize(OptimizeAction::Compact {
+                options: CompactionOptions::default(),
+                remap_options: None,
+            }))?;
+        }
         Ok(())
     }
 
-    /// Get a vector by label.
-    pub fn get_vector(&self, label: i64) -> Result<Vec<f32>> {
+    /// Migration plan turning the live table schema into `target`, the data columns
+    /// without the label (see [`crate::migration`]).
+    pub fn schema_diff(&self, target: &Schema) -> Result<MigrationPlan> {
         let table = self.get_table()?;
+        MigrationPlan::diff(&Self::read_table_schema(&table)?, target)
+    }
 
-        let results = runtime::block_on(
-            table
-                .query()
-                .only_if(format!("label = {}", label))
-                .execute(),
-        )?;
+    /// Apply `plan`, made by [`LanceIndex::schema_diff`]: casts and nullability
+    /// changes, then additions, then drops, each kind one commit. Every step is
+    /// checked first: the plan fails without changes when the table schema changed
+    /// since the diff, or when it drops or casts a column a table setting reads.
+    /// Drops come last, so a failed step leaves every column in place. Handles keep
+    /// the schema they were opened with, so reopen them to use the migrated columns.
+    /// Returns the number of steps applied.
+    pub fn apply_migration(&self, plan: &MigrationPlan) -> Result<usize> {
+        use lance::dataset::ColumnAlteration;
+        use lancedb::table::NewColumnTransform;
 
-        let batches: Vec<RecordBatch> = runtime::block_on(async {
-            let mut batches = Vec::new();
-            let mut stream = results;
-            loop {
-                match stream.try_next().await {
-                    Ok(Some(batch)) => batches.push(batch),
-                    Ok(None) => break,
-                    Err(e) => return Err(anyhow!("stream error: {}", e)),
-                }
+        self.require_unscoped("apply_migration")?;
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let schema = Self::read_table_schema(&table)?;
+        plan.check(&schema)?;
+        plan.check_references(&Self::column_references(&table, &schema)?)?;
+
+        // A column may be both cast and made nullable; Lance takes one alteration per column
+        let mut alterations: Vec<ColumnAlteration> = Vec::new();
+        for step in &plan.steps {
+            let (column, cast_to) = match step {
+                MigrationStep::Cast { column, to, .. } => (column, Some(to)),
+                MigrationStep::SetNullable { column, .. } => (column, None),
+                _ => continue,
+            };
+            let alteration = match alterations.iter().position(|a| &a.path == column) {
+                Some(i) => alterations.remove(i),
+                None => ColumnAlteration::new(column.clone()),
+            };
+            alterations.push(match cast_to {
+                Some(to) => alteration.cast_to(to.clone()),
+                None => alteration.set_nullable(true),
+            });
+        }
+        let adds: Vec<Field> = plan
+            .steps
+            .iter()
+            .filter_map(|s| match s {
+                MigrationStep::Add { field } => Some(field.clone()),
+                _ => None,
+            })
+            .collect();
+        let drops: Vec<&str> = plan
+            .steps
+            .iter()
+            .filter(|s| matches!(s, MigrationStep::Drop { .. }))
+            .map(MigrationStep::column)
+            .collect();
+
+        if !alterations.is_empty() {
+            runtime::block_on(table.alter_columns(&alterations))?;
+            self.committed();
+        }
+        if !adds.is_empty() {
+            let schema = Arc::new(Schema::new(adds));
+            runtime::block_on(table.add_columns(NewColumnTransform::AllNulls(schema), None))?;
+            self.committed();
+        }
+        if !drops.is_empty() {
+            runtime::block_on(table.drop_columns(&drops))?;
+            self.committed();
+        }
+        Ok(plan.steps.len())
+    }
+
+    /// The columns `table`'s settings read, each with the setting, from the latest
+    /// metadata rather than this handle's copy.
+    fn column_references(table: &LanceTable, schema: &Schema) -> Result<Vec<(String, &'static str)>> {
+        let mut references = Vec::new();
+        if metadata::get(table, metadata::ROW_TTL)?.is_some() {
+            references.push((ttl::EXPIRES_AT.to_string(), "row_ttl"));
+        }
+        if let Some(spec) = metadata::get(table, metadata::CONSTRAINTS)? {
+            let constraints = Constraints::parse(&spec)?;
+            references.extend(constraints.rules.into_iter().map(|rule| (rule.column, "constraints")));
+        }
+        if let Some(list) = metadata::get(table, metadata::SENSITIVE_COLUMNS)? {
+            references.extend(list.split(',').map(|column| (column.to_string(), "sensitive_columns")));
+        }
+        if let Some(spec) = metadata::get(table, metadata::CHUNKING)? {
+            let chunking = Chunking::parse(&spec)?;
+            references.push((chunking.column, "chunking"));
+            references.push((chunking.parent, "chunking"));
+        }
+        if let Some(spec) = metadata::get(table, metadata::QUOTA)? {
+            if let QuotaPolicy::EvictOldest { column } = Quota::parse(&spec)?.policy {
+                references.push((column, "quota"));
             }
-            Ok(batches)
-        })?;
+        }
+        if let Some(column) = metadata::get(table, metadata::CLUSTER_BY)? {
+            references.push((column, "cluster_by"));
+        }
+        if metadata::get(table, metadata::PCA)?.is_some() {
+            references.push((pca::PCA_COLUMN.to_string(), "pca"));
+        }
+        if metadata::get(table, metadata::REEMBED)?.is_some() {
+            references.push((reembed::NEXT_COLUMN.to_string(), "reembed"));
+        }
+        references.extend(multivector::columns(schema).into_iter().map(|(column, _)| (column, "multivector")));
+        Ok(references)
+    }
+
+    /// Fail with [`QuotaExceeded`](crate::quota::QuotaExceeded) if appending `batch`
+    /// would exceed `quota`. Incoming bytes are estimated from the batch's Arrow memory
+    /// size. Call with the append lock held, passing its usage.
+    fn check_quota(
+        &self,
+        quota: &Quota,
+        cached: &mut Option<QuotaUsage>,
+        table: &LanceTable,
+        batch: &RecordBatch,
+    ) -> Result<()> {
+        let usage = self.quota_usage(quota, cached, table)?;
+        quota.check(&usage, batch.num_rows() as u64, batch.get_array_memory_size() as u64)?;
+        Ok(())
+    }
+
+    /// The usage `quota` is checked against: `cached` while the table is still at its
+    /// version, else measured afresh and cached.
+    fn quota_usage(&self, quota: &Quota, cached: &mut Option<QuotaUsage>, table: &LanceTable) -> Result<QuotaUsage> {
+        let version = runtime::block_on(table.version())?;
+        if let Some(usage) = cached.filter(|u| u.version == version) {
+            return Ok(usage);
+        }
+        let data_bytes = match quota.max_bytes {
+            Some(_) => self.disk_usage(false)?.data_bytes,
+            None => 0,
+        };
+        let usage = QuotaUsage {
+            version,
+            rows: self.count()?,
+            data_bytes,
+        };
+        *cached = Some(usage);
+        Ok(usage)
+    }
+
+    /// Advance the cached usage past `batch`, just appended to `table`.
+    fn advance_quota_usage(cached: &mut Option<QuotaUsage>, table: &LanceTable, batch: &RecordBatch) {
+        let Some(usage) = cached.take() else {
+            return;
+        };
+        if let Ok(version) = runtime::block_on(table.version()) {
+            *cached = usage.appended(version, batch.num_rows() as u64, batch.get_array_memory_size() as u64);
+        }
+    }
+
+    /// Delete rows chosen by the quota's eviction policy until the table fits.
+    /// Deferred until `evict_batch` rows are over, then done in a single delete.
+    fn enforce_quota_by_eviction(
+        &self,
+        quota: &Quota,
+        cached: &mut Option<QuotaUsage>,
+        table: &LanceTable,
+    ) -> Result<()> {
+        let usage = self.quota_usage(quota, cached, table)?;
+        let excess = quota.rows_to_evict(usage.rows, usage.data_bytes);
+        if excess > 0 {
+            let labels = match &quota.policy {
+                QuotaPolicy::EvictOldest { column } => self.oldest_labels(column, excess as usize)?,
+                QuotaPolicy::EvictLru => self.least_recently_used_labels(excess as usize)?,
+                QuotaPolicy::Reject => return Ok(()),
+            };
+            self.delete_batch(&labels)?;
+        }
+        Ok(())
+    }
+
+    /// Labels of the `n` least recently searched rows.
+    fn least_recently_used_labels(&self, n: usize) -> Result<Vec<i64>> {
+        let accesses = self.access_stats()?;
+        let mut labels = self.all_labels()?;
+        labels.sort_by_key(|l| (accesses.get(l).map_or(i64::MIN, |a| a.last_access_ms), *l));
+        labels.truncate(n);
+        Ok(labels)
+    }
 
+    fn all_labels(&self) -> Result<Vec<i64>> {
+        let table = self.get_table()?;
+        let mut query = table.query().select(Select::columns(&["label"]));
+        if let Some(scope) = &self.scope {
+            query = query.only_if(scope.clone());
+        }
+        let stream = runtime::block_on(query.execute())?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let mut labels = Vec::new();
         for batch in &batches {
-            if batch.num_rows() > 0 {
-                let vec_col = batch
-                    .column_by_name("vector")
-                    .ok_or_else(|| anyhow!("missing vector column"))?;
-                let list_array = vec_col
-                    .as_any()
-                    .downcast_ref::<FixedSizeListArray>()
-                    .ok_or_else(|| anyhow!("vector not FixedSizeList"))?;
-                let values = list_array
-                    .value(0);
-                let float_array = values
-                    .as_any()
-                    .downcast_ref::<Float32Array>()
-                    .ok_or_else(|| anyhow!("vector values not Float32"))?;
-                return Ok(float_array.values().to_vec());
+            let col = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("label column not Int64"))?;
+            labels.extend_from_slice(col.values());
+        }
+        Ok(labels)
+    }
+
+    /// Enable or disable search hit tracking. Persisted in the table metadata;
+    /// disabling flushes the buffered hits but keeps the recorded ones.
+    pub fn set_access_tracking(&self, enabled: bool) -> Result<()> {
+        self.require_unscoped("set_access_tracking")?;
+        if !enabled {
+            if let Some(QuotaPolicy::EvictLru) = self.quota().map(|q| q.policy) {
+                return Err(anyhow!("the table quota evicts by access; change it first"));
             }
+            self.flush_access_stats()?;
         }
+        metadata::set(&self.get_table()?, metadata::ACCESS_TRACKING, enabled.then_some("on"))?;
+        self.access.set_enabled(enabled);
+        Ok(())
+    }
 
-        Err(anyhow!("label {} not found", label))
+    pub fn access_tracking(&self) -> bool {
+        self.access.enabled()
     }
 
-    /// Get all vectors as a flat contiguous f32 array and their labels.
-    /// Returns (labels, flat_vectors) where flat_vectors has length labels.len() * dimension.
-    pub fn get_all_vectors(&self) -> Result<(Vec<i64>, Vec<f32>)> {
-        let table = self.get_table()?;
+    /// Log every `search` under `log`'s tag (see [`crate::query_log`]), or stop
+    /// logging. Persisted in the table metadata; buffered entries are flushed first,
+    /// under the tag they were logged with.
+    pub fn set_query_log(&self, log: Option<QueryLog>) -> Result<()> {
+        self.require_unscoped("set_query_log")?;
+        self.flush_query_log()?;
+        let spec = log.as_ref().map(|l| l.to_string());
+        metadata::set(&self.get_table()?, metadata::QUERY_LOG, spec.as_deref())?;
+        self.query_log.set_config(log);
+        Ok(())
+    }
+
+    pub fn query_log(&self) -> Option<QueryLog> {
+        self.query_log.config()
+    }
+
+    /// Append buffered query log entries to the sidecar table. On failure the entries
+    /// go back into the buffer, which keeps at most [`query_log::MAX_BUFFERED`] of
+    /// the newest entries while flushes fail.
+    pub fn flush_query_log(&self) -> Result<()> {
+        let pending = self.query_log.take();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let flush = || -> Result<()> {
+            let name = query_log::sidecar_table_name(&self.table_name);
+            let sidecar = self
+                .sidecar_table(&name, query_log::sidecar_schema(), true)?
+                .ok_or_else(|| anyhow!("query log table unavailable"))?;
+            let batch = query_log::to_record_batch(&pending)?;
+            let reader = RecordBatchIterator::new(vec![Ok(batch)], query_log::sidecar_schema());
+            runtime::block_on(sidecar.add(Box::new(reader)).execute())?;
+            Ok(())
+        };
+        flush().inspect_err(|_| self.query_log.restore(pending.clone()))
+    }
+
+    /// Logged and buffered queries, all or those of `tag`, oldest first. The log
+    /// covers every handle's searches, so scoped handles cannot read it.
+    pub fn logged_queries(&self, tag: Option<&str>) -> Result<Vec<LoggedQuery>> {
+        self.require_unscoped("logged_queries")?;
+        let mut entries = Vec::new();
+        let name = query_log::sidecar_table_name(&self.table_name);
+        if let Some(sidecar) = self.sidecar_table(&name, query_log::sidecar_schema(), false)? {
+            let mut query = sidecar.query();
+            if let Some(tag) = tag {
+                query = query.only_if(format!("tag = '{}'", tag.replace('\'', "''")));
+            }
+            let stream = runtime::block_on(query.execute())?;
+            let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            for batch in &batches {
+                query_log::read_batch(batch, &mut entries)?;
+            }
+        }
+        entries.extend(self.query_log.snapshot().into_iter().filter(|e| tag.is_none_or(|tag| e.tag == tag)));
+        entries.sort_by_key(|e| e.logged_ms);
+        Ok(entries)
+    }
+
+    /// Re-run the queries logged under `tag` against the table as it is now, with
+    /// their logged filter and search parameters, and compare each result set with
+    /// the logged one. Replayed searches are not logged.
+    pub fn replay_queries(&self, tag: &str) -> Result<ReplayReport> {
+        self.require_unscoped("replay_queries")?;
+        let mut report = ReplayReport {
+            tag: tag.to_string(),
+            queries: Vec::new(),
+            skipped: 0,
+        };
+        for entry in self.logged_queries(Some(tag))? {
+            let Some(query) = &entry.query else {
+                report.skipped += 1;
+                continue;
+            };
+            let results = self
+                .search_unlogged(
+                    query,
+                    entry.k as usize,
+                    entry.nprobes as usize,
+                    entry.refine_factor as usize,
+                    entry.ef as usize,
+                    entry.filter.as_deref(),
+                    // The filter was checked when the logged search ran
+                    true,
+                )
+                .map_err(|e| anyhow!("replaying query {} logged at {}: {}", entry.query_hash, entry.logged_ms, e))?;
+            let labels: Vec<i64> = results.iter().map(|(label, _)| *label).collect();
+            report.queries.push(QueryDrift::new(&entry, &labels));
+        }
+        Ok(report)
+    }
+
+    /// Require writers to hold an advisory lease on the table (see [`crate::lease`]),
+    /// expiring `ttl_secs` after their last write; 0 removes the requirement.
+    /// Enabling takes the lease for this process. Persisted in the table metadata.
+    pub fn set_writer_lease(&self, ttl_secs: u64) -> Result<()> {
+        let path = self.lease_path()?;
+        let ttl_ms = (ttl_secs > 0).then(|| ttl_secs as i64 * 1000);
+        let held = match ttl_ms {
+            Some(ttl_ms) => Some(WriterLease::acquire(&path, ttl_ms)?),
+            None => {
+                lease::check_not_held(&path)?;
+                None
+            }
+        };
+        let value = ttl_secs.to_string();
+        metadata::set(&self.get_table()?, metadata::WRITER_LEASE, ttl_ms.map(|_| value.as_str()))?;
+        *self
+            .lease_ttl_ms
+            .write()
+            .map_err(|_| anyhow!("writer lease lock poisoned"))? = ttl_ms;
+        *self.lease.lock().map_err(|_| anyhow!("writer lease lock poisoned"))? = held;
+        Ok(())
+    }
+
+    /// Writer lease TTL in seconds, if writers must hold the lease.
+    pub fn writer_lease(&self) -> Option<u64> {
+        self.lease_ttl_ms.read().ok().and_then(|t| *t).map(|ms| (ms / 1000) as u64)
+    }
+
+    fn lease_path(&self) -> Result<PathBuf> {
+        let dir = self.local_dataset_dir("writer lease")?;
+        let db_dir = dir.parent().ok_or_else(|| anyhow!("table directory has no parent"))?;
+        Ok(lease::lock_path(db_dir, &self.table_name))
+    }
+
+    /// Take or extend the writer lease before a write. Tables without a lease
+    /// configured still refuse writes while another process holds one.
+    fn require_writer(&self) -> Result<()> {
+        if let Some(version) = self.snapshot_version {
+            return Err(anyhow!("read snapshot of {} at version {} is read-only", self.table_name, version));
+        }
+        if let Some(version) = self.imported_version() {
+            return Err(anyhow!("replica of {} at version {} is read-only", self.table_name, version));
+        }
+        if let Some(block) = &self.format_block {
+            return Err(anyhow!("cannot write to {}: {}", self.table_name, block));
+        }
+        let ttl_ms = self.lease_ttl_ms.read().ok().and_then(|t| *t);
+        // Remote tables have no lock file
+        let Ok(path) = self.lease_path() else {
+            return Ok(());
+        };
+        let result = match ttl_ms {
+            None => lease::check_not_held(&path),
+            Some(ttl_ms) => {
+                let mut held = self.lease.lock().map_err(|_| anyhow!("writer lease lock poisoned"))?;
+                match held.as_ref() {
+                    Some(lease) => lease.renew(),
+                    None => WriterLease::acquire(&path, ttl_ms).map(|lease| *held = Some(lease)),
+                }
+            }
+        };
+        result.map_err(|e| anyhow!("cannot write to {}: {}", self.table_name, e))
+    }
+
+    /// Enable or disable hiding expired rows from searches (see [`crate::ttl`]).
+    /// Enabling requires an `expires_at` column. Persisted in the table metadata.
+    pub fn set_row_ttl(&self, enabled: bool) -> Result<()> {
+        self.require_unscoped("set_row_ttl")?;
+        let expiry = if enabled {
+            Some(ttl::expiry_type(&self.schema)?)
+        } else {
+            None
+        };
+        metadata::set(&self.get_table()?, metadata::ROW_TTL, enabled.then_some("on"))?;
+        *self
+            .row_ttl
+            .write()
+            .map_err(|_| anyhow!("row ttl lock poisoned"))? = expiry;
+        Ok(())
+    }
+
+    pub fn row_ttl(&self) -> bool {
+        self.row_ttl.read().map(|t| t.is_some()).unwrap_or(false)
+    }
+
+    /// `filter` restricted to the handle's scope, with the row expiry predicate
+    /// added when row expiry is on.
+    fn live_filter(&self, filter: Option<&str>) -> Option<String> {
+        let expiry = self
+            .row_ttl
+            .read()
+            .ok()
+            .and_then(|t| t.clone())
+            .map(|expiry| ttl::live_predicate(&expiry, access::now_ms()));
+        and_filters(self.scoped(filter).as_deref(), expiry.as_deref())
+    }
+
+    /// The access sidecar table, created on first use if `create` is set.
+    fn access_table(&self, create: bool) -> Result<Option<LanceTable>> {
+        self.sidecar_table(&access::sidecar_table_name(&self.table_name), access::sidecar_schema(), create)
+    }
+
+    /// Open the sidecar table `name`, creating it empty with `schema` if `create`.
+    fn sidecar_table(&self, name: &str, schema: Arc<Schema>, create: bool) -> Result<Option<LanceTable>> {
+        Self::open_sidecar(&self.connection, name, schema, create)
+    }
+
+    fn open_sidecar(
+        connection: &Connection,
+        name: &str,
+        schema: Arc<Schema>,
+        create: bool,
+    ) -> Result<Option<LanceTable>> {
+        let names = runtime::block_on(connection.table_names().execute())?;
+        if names.iter().any(|n| n == name) {
+            return Ok(Some(Self::open_table(connection, name)?));
+        }
+        if !create {
+            return Ok(None);
+        }
+        let empty = Self::empty_batch_from_schema(&schema)?;
+        let reader = RecordBatchIterator::new(vec![Ok(empty)], schema);
+        Ok(Some(Self::create_table(connection, name, Box::new(reader))?))
+    }
+
+    /// Write buffered hits into the sidecar table, adding to the recorded counts.
+    /// On failure the hits go back into the buffer.
+    pub fn flush_access_stats(&self) -> Result<()> {
+        Self::flush_access(&self.connection, &self.table_name, &self.access)
+    }
+
+    /// [`LanceIndex::flush_access_stats`] on the runtime's blocking pool, so searches
+    /// and drops do not wait for the sidecar write.
+    fn flush_access_in_background(&self) {
+        let (connection, table_name, access) = (self.connection.clone(), self.table_name.clone(), self.access.clone());
+        runtime::spawn_blocking(move || {
+            // A failed flush keeps the hits buffered for the next one
+            let _ = Self::flush_access(&connection, &table_name, &access);
+        });
+    }
+
+    fn flush_access(connection: &Connection, table_name: &str, access: &AccessTracker) -> Result<()> {
+        let _flushing = access.lock_flush();
+        let pending = access.take();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let flush = || -> Result<()> {
+            let name = access::sidecar_table_name(table_name);
+            let sidecar = Self::open_sidecar(connection, &name, access::sidecar_schema(), true)?
+                .ok_or_else(|| anyhow!("access table unavailable"))?;
+            let csv = pending.keys().map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
+            let stream = runtime::block_on(
+                sidecar.query().only_if(format!("label IN ({})", csv)).execute(),
+            )?;
+            let existing: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            let mut merged = pending.clone();
+            for batch in &existing {
+                access::read_batch(batch, &mut merged)?;
+            }
+
+            let batch = access::to_record_batch(&merged)?;
+            let reader = RecordBatchIterator::new(vec![Ok(batch)], access::sidecar_schema());
+            let mut upsert = sidecar.merge_insert(&["label"]);
+            upsert.when_matched_update_all(None).when_not_matched_insert_all();
+            runtime::block_on(upsert.execute(Box::new(reader)))?;
+            Ok(())
+        };
+        flush().inspect_err(|_| access.restore(pending.clone()))
+    }
+
+    /// Recorded and buffered hits per label.
+    pub fn access_stats(&self) -> Result<HashMap<i64, Access>> {
+        let mut accesses = self.access.snapshot();
+        if let Some(sidecar) = self.access_table(false)? {
+            let stream = runtime::block_on(sidecar.query().execute())?;
+            let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            for batch in &batches {
+                access::read_batch(batch, &mut accesses)?;
+            }
+        }
+        Ok(accesses)
+    }
+
+    /// Rows not searched within the last `idle_ms` milliseconds, least recent first.
+    ///
+    /// Returns (label, hits, last_access_ms); never-accessed rows have 0 hits and
+    /// last_access_ms 0.
+    pub fn cold_rows(&self, idle_ms: i64) -> Result<RecordBatch> {
+        let cutoff = access::now_ms() - idle_ms;
+        let accesses = self.access_stats()?;
+        let mut cold: HashMap<i64, Access> = HashMap::new();
+        for label in self.all_labels()? {
+            let access = accesses.get(&label).copied().unwrap_or(Access {
+                hits: 0,
+                last_access_ms: 0,
+            });
+            if access.last_access_ms < cutoff {
+                cold.insert(label, access);
+            }
+        }
+        let batch = access::to_record_batch(&cold)?;
+        let last = batch
+            .column_by_name("last_access_ms")
+            .ok_or_else(|| anyhow!("missing last_access_ms column"))?;
+        let order = arrow::compute::sort_to_indices(last, None, None)?;
+        Ok(arrow::compute::take_record_batch(&batch, &order)?)
+    }
+
+    /// Labels of the `n` rows with the smallest values of `column` (nulls last).
+    fn oldest_labels(&self, column: &str, n: usize) -> Result<Vec<i64>> {
+        use arrow::compute::{concat_batches, sort_to_indices, SortOptions};
+
+        let table = self.get_table()?;
+        let columns = if column == "label" { vec!["label"] } else { vec!["label", column] };
+        let mut query = table.query().select(Select::columns(&columns));
+        if let Some(scope) = &self.scope {
+            query = query.only_if(scope.clone());
+        }
+        let stream = runtime::block_on(query.execute())?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let Some(first) = batches.first() else {
+            return Ok(vec![]);
+        };
+        let all = concat_batches(&first.schema(), &batches)?;
+        let order = sort_to_indices(
+            all.column_by_name(column)
+                .ok_or_else(|| anyhow!("missing {} column", column))?,
+            Some(SortOptions {
+                descending: false,
+                nulls_first: false,
+            }),
+            Some(n),
+        )?;
+        let labels = all
+            .column_by_name("label")
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+            .ok_or_else(|| anyhow!("label column not Int64"))?;
+        Ok(order.values().iter().map(|&i| labels.value(i as usize)).collect())
+    }
+
+    fn vector_column(batch: &RecordBatch) -> Option<&FixedSizeListArray> {
+        batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+    }
+
+    /// Deleted vectors cannot be subtracted, so the next read recomputes from a scan.
+    fn invalidate_vector_stats(&self) {
+        if let Ok(mut stats) = self.vector_stats.lock() {
+            *stats = None;
+        }
+    }
+
+    /// Current centroid and norm statistics, maintained incrementally on ingest.
+    pub fn vector_stats(&self) -> Result<VectorStats> {
+        let mut stats = self
+            .vector_stats
+            .lock()
+            .map_err(|_| anyhow!("vector stats lock poisoned"))?;
+        if stats.is_none() {
+            let (_, vectors) = self.get_all_vectors()?;
+            let mut fresh = VectorStats::new(self.dimension);
+            vectors
+                .chunks_exact(self.dimension)
+                .for_each(|v| fresh.observe(v));
+            *stats = Some(fresh);
+            self.vector_stats_epoch.fetch_add(1, Ordering::AcqRel);
+        }
+        Ok(stats.clone().unwrap_or_else(|| VectorStats::new(self.dimension)))
+    }
+
+    /// Snapshot the current statistics as baseline `tag` in the table metadata.
+    pub fn tag_drift_baseline(&self, tag: &str) -> Result<()> {
+        if tag.is_empty() {
+            return Err(anyhow!("baseline tag must not be empty"));
+        }
+        let stats = self.vector_stats()?;
+        metadata::set(
+            &self.get_table()?,
+            &format!("{}{}", metadata::DRIFT_BASELINE_PREFIX, tag),
+            Some(&stats.encode()),
+        )
+    }
+
+    /// Compare the current statistics against baseline `tag`.
+    pub fn drift_report(&self, baseline_tag: &str) -> Result<DriftReport> {
+        let key = format!("{}{}", metadata::DRIFT_BASELINE_PREFIX, baseline_tag);
+        let baseline = metadata::get(&self.get_table()?, &key)?
+            .ok_or_else(|| anyhow!("no drift baseline tagged '{}'", baseline_tag))?;
+        Ok(DriftReport {
+            baseline: VectorStats::decode(&baseline)?,
+            current: self.vector_stats()?,
+        })
+    }
+
+    /// Embedding model id recorded for this table, if any.
+    pub fn embedding_model(&self) -> Option<String> {
+        self.embedding_model.read().ok().and_then(|m| m.clone())
+    }
+
+    /// Record (or, with `None`, clear) the embedding model id together with the
+    /// table dimension. Persisted in the table metadata.
+    pub fn set_embedding_model(&self, model: Option<&str>) -> Result<()> {
+        let model = model.map(str::trim).filter(|m| !m.is_empty());
+        let table = self.get_table()?;
+        let dim = model.map(|_| self.dimension.to_string());
+        metadata::set_all(&table, &[
+            (metadata::EMBEDDING_MODEL, model),
+            (metadata::EMBEDDING_DIM, dim.as_deref()),
+        ])?;
+        *self
+            .embedding_model
+            .write()
+            .map_err(|_| anyhow!("embedding model lock poisoned"))? = model.map(str::to_string);
+        *self
+            .embedding_dim
+            .write()
+            .map_err(|_| anyhow!("embedding dim lock poisoned"))? = model.map(|_| self.dimension);
+        Ok(())
+    }
+
+    /// Start re-embedding the table with `new_dim`-dimensional vectors (see
+    /// [`crate::reembed`]): adds the `vector_next` column, NULL for every row, stored
+    /// like `vector`. Refused while a PCA projection, rotation or query transform is
+    /// configured, since those are fitted to the current vectors.
+    pub fn reembed_begin(&self, new_dim: usize) -> Result<()> {
+        use lancedb::table::NewColumnTransform;
+
+        self.require_unscoped("reembed_begin")?;
+        if new_dim > MAX_DIMENSION {
+            return Err(anyhow!("re-embed dimension {} exceeds the maximum of {}", new_dim, MAX_DIMENSION));
+        }
+        if self.vector_storage() == VectorStorage::Int8 {
+            return Err(anyhow!("{} stores int8 vectors, which cannot be re-embedded", self.table_name));
+        }
+        let field = reembed::next_field(new_dim, self.vector_storage())?;
+        let fitted = [
+            ("a PCA projection", self.pca().is_some()),
+            ("a rotation", self.rotation().is_some()),
+            ("a query transform", self.query_transform().is_some()),
+            ("cold storage", self.cold_rows.load(Ordering::Acquire)),
+        ];
+        if let Some((what, _)) = fitted.iter().find(|(_, set)| *set) {
+            return Err(anyhow!("cannot re-embed {}: {} is fitted to its current vectors", self.table_name, what));
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        if let Some(dim) = metadata::get(&table, metadata::REEMBED)? {
+            return Err(anyhow!("a re-embed to dimension {} is already in progress on {}", dim, self.table_name));
+        }
+
+        // A column left by a re-embed that failed before recording itself
+        if Self::read_table_schema(&table)?.column_with_name(reembed::NEXT_COLUMN).is_some() {
+            runtime::block_on(table.drop_columns(&[reembed::NEXT_COLUMN]))?;
+        }
+        let schema = Arc::new(Schema::new(vec![field]));
+        runtime::block_on(table.add_columns(NewColumnTransform::AllNulls(schema), None))?;
+        metadata::set(&table, metadata::REEMBED_INDEX_METRIC, None)?;
+        metadata::set(&table, metadata::REEMBED_INDEX_COMPRESSION, None)?;
+        metadata::set(&table, metadata::REEMBED, Some(&new_dim.to_string()))?;
+        *self.reembed.write().map_err(|_| anyhow!("reembed lock poisoned"))? = Some(new_dim);
+        self.committed();
+        Ok(())
+    }
+
+    /// Dimension of the re-embed in progress, if any, cached when the handle was
+    /// opened or the re-embed began.
+    pub fn reembed_dimension(&self) -> Option<usize> {
+        self.reembed.read().ok().and_then(|r| *r)
+    }
+
+    /// Dimension of the re-embed in progress on `table`, read from its metadata.
+    fn current_reembed(&self, table: &LanceTable) -> Result<usize> {
+        let dim = metadata::get(table, metadata::REEMBED)?
+            .map(|dim| reembed::parse_dim(&dim))
+            .transpose()?
+            .ok_or_else(|| anyhow!("no re-embed in progress on {}", self.table_name))?;
+        *self.reembed.write().map_err(|_| anyhow!("reembed lock poisoned"))? = Some(dim);
+        Ok(dim)
+    }
+
+    /// Write the new vectors of the rows `labels` (flattened, `labels.len()` times the
+    /// re-embed dimension) during a re-embed. Rows may be written again; the last
+    /// write stands. Returns the rows written.
+    pub fn reembed_write(&self, labels: &[i64], vectors: &[f32]) -> Result<usize> {
+        self.require_unscoped("reembed_write")?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let dim = self.current_reembed(&table)?;
+        if vectors.len() != labels.len() * dim {
+            return Err(anyhow!(
+                "expected {} values for {} vectors of dimension {}, got {}",
+                labels.len() * dim,
+                labels.len(),
+                dim,
+                vectors.len()
+            ));
+        }
+        if labels.is_empty() {
+            return Ok(0);
+        }
+        let mut rows: HashMap<i64, u32> = HashMap::with_capacity(labels.len());
+        for (row, label) in labels.iter().enumerate() {
+            if rows.insert(*label, row as u32).is_some() {
+                return Err(anyhow!("label {} is written twice", label));
+            }
+        }
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let incoming = FixedSizeListArray::new(item, dim as i32, Arc::new(Float32Array::from(vectors.to_vec())), None);
+        if let Some(invalid) = rejects::check_vectors(&incoming).first() {
+            return Err(anyhow!("new vector of label {}: {}", labels[invalid.row], invalid.reason));
+        }
+        let incoming = vector_storage::narrow(incoming, self.vector_storage())?;
+
+        let mut sorted = labels.to_vec();
+        sorted.sort_unstable();
+        let mut batches = Vec::new();
+        for chunk in sorted.chunks(SEARCH_WITHIN_CHUNK) {
+            let label_list: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let predicate = format!("label IN ({})", label_list.join(", "));
+            let stream = runtime::block_on(table.query().only_if(predicate).execute())?;
+            let stored: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            for batch in stored.into_iter().filter(|b| b.num_rows() > 0) {
+                let stored_labels = batch
+                    .column_by_name("label")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("label column is not Int64"))?;
+                let take = UInt32Array::from_iter_values(stored_labels.values().iter().map(|label| rows[label]));
+                let index = batch.schema().index_of(reembed::NEXT_COLUMN)?;
+                let mut columns = batch.columns().to_vec();
+                columns[index] = arrow::compute::take(&incoming, &take, None)?;
+                batches.push(RecordBatch::try_new(batch.schema(), columns)?);
+            }
+        }
+        let found: usize = batches.iter().map(|b| b.num_rows()).sum();
+        if found != labels.len() {
+            return Err(anyhow!("{} of {} labels to re-embed not found", labels.len() - found, labels.len()));
+        }
+
+        let schema = batches[0].schema();
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let mut update = table.merge_insert(&["label"]);
+        update.when_matched_update_all(None);
+        self.note_failure(runtime::block_on(update.execute(Box::new(reader))).map_err(anyhow::Error::from))?;
+        self.committed();
+        Ok(found)
+    }
+
+    /// Finish the re-embed: in one commit, `vector_next` becomes `vector` and the
+    /// old vectors and their index are dropped. Records `model` as the embedding
+    /// model (with the new dimension), or clears the recorded one. Fails while any
+    /// row has no new vector. Handles keep the dimension they were opened with, so
+    /// reopen them, this one included, to use the new vectors.
+    pub fn reembed_finish(&self, model: Option<&str>) -> Result<()> {
+        use lance::dataset::transaction::{Operation, Transaction};
+        use lance::dataset::CommitBuilder;
+
+        self.require_unscoped("reembed_finish")?;
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let dim = self.current_reembed(&table)?;
+        let missing = runtime::block_on(table.count_rows(Some(format!("{} IS NULL", reembed::NEXT_COLUMN))))?;
+        if missing > 0 {
+            return Err(anyhow!("{} rows of {} have no new vector yet", missing, self.table_name));
+        }
+
+        // Renames and drops are schema-only (a Lance projection), so the swap is one
+        // projection: the new column takes the old one's place and name, and the old
+        // column's index goes with it
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let dataset = runtime::block_on(Self::load_dataset(&uri, &params, None))?;
+        let mut schema = dataset.schema().clone();
+        let next = schema
+            .fields
+            .iter()
+            .position(|f| f.name == reembed::NEXT_COLUMN)
+            .ok_or_else(|| anyhow!("{} has no {} column", self.table_name, reembed::NEXT_COLUMN))?;
+        let mut vectors = schema.fields.remove(next);
+        let current = schema
+            .fields
+            .iter()
+            .position(|f| f.name == "vector")
+            .ok_or_else(|| anyhow!("{} has no vector column", self.table_name))?;
+        vectors.name = "vector".to_string();
+        schema.fields[current] = vectors;
+
+        // The new column's index settings, if it has an index, become the table's
+        let model = model.map(str::trim).filter(|m| !m.is_empty());
+        let mut take = |key: &str| schema.metadata.remove(&metadata::full_key(key)).filter(|v| !v.is_empty());
+        take(metadata::REEMBED);
+        let index_metric = take(metadata::REEMBED_INDEX_METRIC);
+        let index_compression = take(metadata::REEMBED_INDEX_COMPRESSION);
+        let recorded = [
+            (metadata::INDEX_METRIC, index_metric.clone()),
+            (metadata::INDEX_COMPRESSION, index_compression.clone()),
+            (metadata::EMBEDDING_MODEL, model.map(str::to_string)),
+            (metadata::EMBEDDING_DIM, model.map(|_| dim.to_string())),
+        ];
+        for (key, value) in recorded {
+            match value {
+                Some(value) => schema.metadata.insert(metadata::full_key(key), value),
+                None => schema.metadata.remove(&metadata::full_key(key)),
+            };
+        }
+        let transaction = Transaction::new(dataset.manifest().version, Operation::Project { schema }, None, None);
+        runtime::block_on(CommitBuilder::new(Arc::new(dataset)).execute(transaction))?;
+
+        *self.reembed.write().map_err(|_| anyhow!("reembed lock poisoned"))? = None;
+        *self.index_metric.write().map_err(|_| anyhow!("index metric lock poisoned"))? = index_metric;
+        *self
+            .index_compression
+            .write()
+            .map_err(|_| anyhow!("index compression lock poisoned"))? = index_compression.and_then(|r| r.parse().ok());
+        *self
+            .embedding_model
+            .write()
+            .map_err(|_| anyhow!("embedding model lock poisoned"))? = model.map(str::to_string);
+        *self
+            .embedding_dim
+            .write()
+            .map_err(|_| anyhow!("embedding dim lock poisoned"))? = model.map(|_| dim);
+        self.committed();
+        self.invalidate_vector_stats();
+        Ok(())
+    }
+
+    /// Mark `columns` as sensitive (replacing the previous list): scans and column
+    /// statistics leave them out unless the caller passes the privileged flag. The
+    /// label and vector columns cannot be marked. Persisted in the table metadata.
+    pub fn set_sensitive_columns(&self, columns: &[String]) -> Result<()> {
+        self.require_unscoped("set_sensitive_columns")?;
+        let mut columns = columns.to_vec();
+        columns.sort();
+        columns.dedup();
+        for column in &columns {
+            let field = self
+                .schema
+                .field_with_name(column)
+                .map_err(|_| anyhow!("column '{}' not found", column))?;
+            if column == "label" || matches!(field.data_type(), DataType::FixedSizeList(_, _)) {
+                return Err(anyhow!("column '{}' cannot be marked sensitive", column));
+            }
+        }
+        let list = columns.join(",");
+        metadata::set(
+            &self.get_table()?,
+            metadata::SENSITIVE_COLUMNS,
+            (!list.is_empty()).then_some(list.as_str()),
+        )?;
+        *self
+            .sensitive_columns
+            .write()
+            .map_err(|_| anyhow!("sensitive columns lock poisoned"))? = columns;
+        Ok(())
+    }
+
+    pub fn sensitive_columns(&self) -> Vec<String> {
+        self.sensitive_columns.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Fail if any of `columns` is sensitive and the caller is not privileged.
+    fn check_exportable(&self, columns: &[&str], privileged: bool) -> Result<()> {
+        if privileged {
+            return Ok(());
+        }
+        let sensitive = self.sensitive_columns();
+        match columns.iter().find(|c| sensitive.iter().any(|s| s == *c)) {
+            Some(column) => Err(anyhow!("column '{}' is sensitive", column)),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail if `filter` reads a sensitive column and the caller is not privileged,
+    /// since which rows match would reveal its values.
+    fn check_filter(&self, filter: Option<&str>, privileged: bool) -> Result<()> {
+        let Some(filter) = filter.filter(|_| !privileged) else {
+            return Ok(());
+        };
+        self.check_exportable(&referenced_columns(filter, &self.schema), privileged)
+            .map_err(|e| anyhow!("filter '{}' not allowed: {}", filter, e))
+    }
+
+    /// `columns` checked to exist and be exportable, or every column the caller may
+    /// see when empty.
+    fn exportable_columns(&self, columns: &[String], privileged: bool) -> Result<Vec<String>> {
+        if columns.is_empty() {
+            let sensitive = if privileged { Vec::new() } else { self.sensitive_columns() };
+            return Ok(self
+                .schema
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .filter(|name| !sensitive.contains(name))
+                .collect());
+        }
+        let names: Vec<&str> = columns.iter().map(String::as_str).collect();
+        for name in &names {
+            self.schema
+                .field_with_name(name)
+                .map_err(|_| anyhow!("column '{}' not found", name))?;
+        }
+        self.check_exportable(&names, privileged)?;
+        Ok(columns.to_vec())
+    }
+
+    /// Read `columns` (all columns the caller may see when empty) of the rows
+    /// matching `filter`. The handle's scope and row expiry apply. Unless
+    /// `privileged`, the filter may not read sensitive columns either.
+    pub fn scan(&self, columns: &[String], filter: Option<&str>, privileged: bool) -> Result<RecordBatch> {
+        use arrow::compute::concat_batches;
+
+        let columns = self.exportable_columns(columns, privileged)?;
+        self.check_filter(filter, privileged)?;
+        let schema = Arc::new(self.schema.project(
+            &columns
+                .iter()
+                .map(|c| self.schema.index_of(c))
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+        )?);
+
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let batches = self.with_reconnect(|| {
+            let table = self.get_table()?;
+            let mut query = table.query().select(Select::columns(&columns));
+            if let Some(filter) = self.live_filter(filter) {
+                query = query.only_if(filter);
+            }
+            let mut stream = runtime::block_on(query.execute())?;
+            let mut budget = ReadBudget::new("scan");
+            runtime::block_on(async {
+                let mut batches = Vec::new();
+                while let Some(batch) = stream.try_next().await.map_err(|e| anyhow!("stream error: {}", e))? {
+                    budget.charge_batch(&batch)?;
+                    batches.push(batch);
+                }
+                Ok::<Vec<RecordBatch>, anyhow::Error>(batches)
+            })
+        })?;
+        let batch = concat_batches(&schema, &batches)?;
+        match self.rotation() {
+            Some(rotation) => Self::map_vectors(batch, |v| rotation.map_array(v, Rotation::invert)),
+            None => Ok(batch),
+        }
+    }
+
+    /// `scan` with the `label` of every row as the first column, whether or not
+    /// `columns` names it, and the table version read. Labels survive compaction and
+    /// updates, unlike Lance row ids, so they identify the rows to pass to
+    /// [`LanceIndex::apply_changes`], with the version as its `expected_version`.
+    pub fn scan_with_labels(
+        &self,
+        columns: &[String],
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<(RecordBatch, u64)> {
+        let mut selected = vec!["label".to_string()];
+        selected.extend(self.exportable_columns(columns, privileged)?.into_iter().filter(|c| c != "label"));
+        // Read before the scan: a commit in between makes the version stale, which
+        // apply_changes rejects, rather than newer than the rows read
+        let version = runtime::block_on(self.get_table()?.version())?;
+        Ok((self.scan(&selected, filter, privileged)?, version))
+    }
+
+    /// Read every column of the rows among `labels` (all rows when empty) that match
+    /// `filter` and discard them, so the fragments holding those rows are in the local
+    /// disk cache (see [`crate::disk_cache`]) and the OS page cache before a heavy query
+    /// needs them. Labels are read as `label IN (...)` scans of at most
+    /// [`SEARCH_WITHIN_CHUNK`] labels each. Returns the number of rows read, so the
+    /// filter may not read sensitive columns.
+    pub fn prefetch(&self, labels: &[i64], filter: Option<&str>) -> Result<u64> {
+        self.check_filter(filter, false)?;
+        let mut labels = labels.to_vec();
+        labels.sort_unstable();
+        labels.dedup();
+        let mut predicates: Vec<Option<String>> = labels
+            .chunks(SEARCH_WITHIN_CHUNK)
+            .map(|chunk| {
+                let label_list: Vec<String> = chunk.iter().map(i64::to_string).collect();
+                Some(format!("label IN ({})", label_list.join(", ")))
+            })
+            .collect();
+        if predicates.is_empty() {
+            predicates.push(None);
+        }
+
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let table = self.get_table()?;
+        let mut rows = 0;
+        for predicate in predicates {
+            let mut query = table.query();
+            if let Some(filter) = self.live_filter(and_filters(predicate.as_deref(), filter).as_deref()) {
+                query = query.only_if(filter);
+            }
+            let stream = runtime::block_on(query.execute())?;
+            rows += runtime::block_on(stream.try_fold(0u64, |n, batch| async move { Ok(n + batch.num_rows() as u64) }))
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+        }
+        Ok(rows)
+    }
+
+    /// `scan` ordered by `order` and cut to `limit` rows, as a reader of at most
+    /// `batch_rows` rows per batch. Rows are sorted in Rust while the scan streams
+    /// (see [`crate::sort`]), so a small limit needs memory for about one batch plus the
+    /// limit; the order column need not be among `columns`.
+    pub fn scan_ordered(
+        &self,
+        columns: &[String],
+        filter: Option<&str>,
+        privileged: bool,
+        order: &OrderBy,
+        limit: Option<usize>,
+        batch_rows: usize,
+    ) -> Result<Box<dyn RecordBatchReader + Send>> {
+        let columns = self.exportable_columns(columns, privileged)?;
+        self.exportable_columns(std::slice::from_ref(&order.column), privileged)?;
+        self.check_filter(filter, privileged)?;
+        let mut selected = columns.clone();
+        if !selected.contains(&order.column) {
+            selected.push(order.column.clone());
+        }
+        let schema = Arc::new(self.schema.project(
+            &selected
+                .iter()
+                .map(|c| self.schema.index_of(c))
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+        )?);
+        let mut sorter = ScanSorter::new(schema.clone(), order.clone(), limit)?;
+
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let table = self.get_table()?;
+        let mut query = table.query().select(Select::columns(&selected));
+        if let Some(filter) = self.live_filter(filter) {
+            query = query.only_if(filter);
+        }
+        let rotation = self.rotation();
+        let mut stream = runtime::block_on(query.execute())?;
+        while let Some(batch) = runtime::block_on(stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            let batch = match &rotation {
+                Some(rotation) => Self::map_vectors(batch, |v| rotation.map_array(v, Rotation::invert))?,
+                None => batch,
+            };
+            sorter.push(batch)?;
+        }
+        let sorted = sorter.finish(batch_rows)?;
+        if selected.len() == columns.len() {
+            return Ok(sorted);
+        }
+        // Drop the order column, which was only read to sort by
+        let projection: Vec<usize> = (0..columns.len()).collect();
+        let output = Arc::new(schema.project(&projection)?);
+        let batches = sorted.map(move |batch| batch.and_then(|b| b.project(&projection)));
+        Ok(Box::new(RecordBatchIterator::new(batches, output)))
+    }
+
+    /// Fields of the columns `search_rows` returns for `columns` (see `scan`).
+    pub fn result_fields(&self, columns: &[String], privileged: bool) -> Result<Vec<Field>> {
+        self.exportable_columns(columns, privileged)?
+            .iter()
+            .map(|c| Ok(self.schema.field_with_name(c)?.clone()))
+            .collect()
+    }
+
+    /// `search` plus `columns` of each hit (see `scan`), in hit order. Hits whose row
+    /// was deleted before its columns were read are dropped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_rows(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        columns: &[String],
+        privileged: bool,
+    ) -> Result<(Vec<(i64, f32)>, RecordBatch)> {
+        let mut columns = self.exportable_columns(columns, privileged)?;
+        let hits = self.search(query, k, nprobes, refine_factor, 0, filter, privileged)?;
+        let with_label = !columns.iter().any(|c| c == "label");
+        if with_label {
+            columns.push("label".to_string());
+        }
+        let mut rows = Vec::new();
+        for chunk in hits.chunks(SEARCH_WITHIN_CHUNK) {
+            let labels: Vec<String> = chunk.iter().map(|(label, _)| label.to_string()).collect();
+            rows.push(self.scan(&columns, Some(&format!("label IN ({})", labels.join(", "))), true)?);
+        }
+        let schema = Arc::new(self.schema.project(
+            &columns
+                .iter()
+                .map(|c| self.schema.index_of(c))
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+        )?);
+        let batch = concat_batches(&schema, &rows)?;
+
+        let found = batch
+            .column_by_name("label")
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+            .ok_or_else(|| anyhow!("missing label column"))?;
+        let position: HashMap<i64, u64> = found.values().iter().enumerate().map(|(i, l)| (*l, i as u64)).collect();
+        let (hits, order): (Vec<(i64, f32)>, Vec<u64>) = hits
+            .iter()
+            .filter_map(|hit| position.get(&hit.0).map(|i| (*hit, *i)))
+            .unzip();
+        let mut batch = arrow::compute::take_record_batch(&batch, &UInt64Array::from(order))?;
+        if with_label {
+            batch.remove_column(batch.num_columns() - 1);
+        }
+        Ok((hits, batch))
+    }
+
+    /// `search` plus the [`Diversity`] of its hits: their mean pairwise distance in the
+    /// exact metric, and with `column` the number of distinct non-NULL values of that
+    /// column among them (see [`crate::diversity`]). Hits deleted before their vectors
+    /// were read do not count.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_with_diversity(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+        column: Option<&str>,
+        privileged: bool,
+    ) -> Result<(Vec<(i64, f32)>, Diversity)> {
+        if let Some(column) = column {
+            self.exportable_columns(&[column.to_string()], privileged)?;
+        }
+        let hits = self.search(query, k, nprobes, refine_factor, ef, filter, privileged)?;
+        let labels: Vec<i64> = hits.iter().map(|(label, _)| *label).collect();
+
+        let vectors = self.vectors_for_labels(&labels)?;
+        let slices: Vec<&[f32]> = vectors.iter().map(|(_, v)| v.as_slice()).collect();
+        let metric = self.exact_metric()?;
+        let mut report = Diversity {
+            mean_pairwise_distance: diversity::mean_pairwise_distance(&slices, &metric)?,
+            distinct_values: None,
+        };
+        if let Some(column) = column {
+            let mut values: Vec<ArrayRef> = Vec::new();
+            self.scan_labels(&labels, column, |_, array| {
+                values.push(array.clone());
+                Ok(())
+            })?;
+            let values: Vec<&dyn Array> = values.iter().map(|a| a.as_ref()).collect();
+            let column = if values.is_empty() {
+                arrow_array::new_empty_array(self.schema.field_with_name(column)?.data_type())
+            } else {
+                arrow::compute::concat(&values)?
+            };
+            report.distinct_values = Some(diversity::distinct_values(&column)?);
+        }
+        Ok((hits, report))
+    }
+
+    /// `search` with `columns` of each hit (see `scan`), as a reader of batches of
+    /// (label, distance, columns...) in hit order. Without a pipeline the k-NN query
+    /// selects the columns itself and its batches are handed on as Lance produced
+    /// them; a pipeline search, or a filtered search that comes back short of k
+    /// hits and must be refilled, reads the columns afterwards like `search_rows`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_with_columns(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        columns: &[String],
+        privileged: bool,
+    ) -> Result<Box<dyn RecordBatchReader + Send>> {
+        let columns: Vec<String> =
+            self.exportable_columns(columns, privileged)?.into_iter().filter(|c| c != "label").collect();
+        self.check_filter(filter, privileged)?;
+        let mut fields = vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("distance", DataType::Float32, true),
+        ];
+        for column in &columns {
+            fields.push(self.schema.field_with_name(column)?.clone());
+        }
+        let schema = Arc::new(Schema::new(fields));
+
+        if self.pipeline().is_none() {
+            let prepared = self.prepare_query(query)?;
+            let mut selected = vec!["label".to_string()];
+            selected.extend(columns.iter().cloned());
+            let mut vector_query =
+                self.vector_query(&prepared, k, nprobes, refine_factor, 0)?.select(Select::columns(&selected));
+            let live = self.live_filter(filter);
+            if let Some(filter) = &live {
+                vector_query = vector_query.only_if(filter.clone());
+            }
+            let batches: Vec<RecordBatch> = {
+                let _permit = self.admission.acquire(OpClass::Search)?;
+                let stream = runtime::block_on(vector_query.execute_with_options(self.read_options()))?;
+                runtime::block_on(stream.try_collect()).map_err(|e| anyhow!("stream error: {}", e))?
+            };
+            let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+            if rows >= k || live.is_none() {
+                let rotation = self.rotation();
+                let mut output = Vec::with_capacity(batches.len());
+                let mut labels = Vec::with_capacity(rows);
+                for batch in batches {
+                    let (label_column, distances) = Self::label_distance_columns(&batch)?;
+                    labels.extend_from_slice(label_column.values());
+                    let mut values: Vec<ArrayRef> = vec![Arc::new(label_column.clone()), Arc::new(distances.clone())];
+                    values.extend(Self::named_columns(&batch, &columns)?);
+                    let batch = RecordBatch::try_new(schema.clone(), values)?;
+                    output.push(match &rotation {
+                        Some(rotation) => Self::map_vectors(batch, |v| rotation.map_array(v, Rotation::invert))?,
+                        None => batch,
+                    });
+                }
+                if self.access.enabled() {
+                    self.record_access(&labels);
+                }
+                return Ok(Box::new(RecordBatchIterator::new(output.into_iter().map(Ok), schema)));
+            }
+        }
+
+        // An empty list would read every column
+        let read = if columns.is_empty() { vec!["label".to_string()] } else { columns.clone() };
+        let (hits, batch) = self.search_rows(query, k, nprobes, refine_factor, filter, &read, privileged)?;
+        let (labels, distances): (Vec<i64>, Vec<f32>) = hits.into_iter().unzip();
+        let mut values: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(labels)),
+            Arc::new(Float32Array::from(distances)),
+        ];
+        values.extend(Self::named_columns(&batch, &columns)?);
+        let batch = RecordBatch::try_new(schema.clone(), values)?;
+        Ok(Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)))
+    }
+
+    /// The `columns` of `batch`, by name.
+    fn named_columns(batch: &RecordBatch, columns: &[String]) -> Result<Vec<ArrayRef>> {
+        columns
+            .iter()
+            .map(|c| batch.column_by_name(c).cloned().ok_or_else(|| anyhow!("missing column {}", c)))
+            .collect()
+    }
+
+    /// Verify a caller-declared model id against the recorded one, and the recorded
+    /// dimension against the table's vectors.
+    ///
+    /// Passes when the caller declares nothing or the table has no model recorded.
+    pub fn check_embedding_model(&self, declared: Option<&str>) -> Result<()> {
+        let (Some(declared), Some(stored)) = (declared, self.embedding_model()) else {
+            return Ok(());
+        };
+        if declared != stored {
+            return Err(anyhow!(
+                "embedding model mismatch: table holds '{}' vectors ({} dims), caller declared '{}'",
+                stored,
+                self.dimension,
+                declared
+            ));
+        }
+        match self.embedding_dim.read().ok().and_then(|d| *d) {
+            Some(dim) if dim != self.dimension => Err(anyhow!(
+                "embedding model '{}' was recorded with {} dims, but the table's vectors have {}; record it again",
+                stored,
+                dim,
+                self.dimension
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Query preprocessing applied by `search`, if one is configured.
+    pub fn query_transform(&self) -> Option<QueryTransform> {
+        self.query_transform.read().ok().and_then(|t| t.clone())
+    }
+
+    /// Configure (or, with `None`, remove) the query transform, e.g. "center | normalize".
+    ///
+    /// If the spec uses `center` and no `mean` is given, the mean of the stored vectors
+    /// is used. Persisted in the table metadata.
+    pub fn set_query_transform(&self, spec: Option<&str>, mean: Option<Vec<f32>>) -> Result<()> {
+        let transform = match spec {
+            Some(spec) => {
+                let uses_center = spec.split('|').any(|s| s.trim() == "center");
+                let mean = match mean {
+                    None if uses_center => Some(self.vector_mean()?),
+                    mean => mean,
+                };
+                let transform = QueryTransform::parse(spec, mean)?;
+                // Queries must leave at the table dimension. They arrive at the one the
+                // first slice or centering needs, or else at the table dimension.
+                let input = transform.input_dim()?.unwrap_or(self.dimension);
+                let output = transform.output_dim(input)?;
+                if output != self.dimension {
+                    return Err(anyhow!(
+                        "query transform '{}' produces {} dimensions, the table has {}",
+                        spec,
+                        output,
+                        self.dimension
+                    ));
+                }
+                Some(transform)
+            }
+            None => None,
+        };
+
+        let table = self.get_table()?;
+        let spec = transform.as_ref().map(|t| t.to_string());
+        let mean = transform.as_ref().and_then(|t| t.mean_to_string());
+        metadata::set_all(&table, &[
+            (metadata::QUERY_TRANSFORM, spec.as_deref()),
+            (metadata::QUERY_MEAN, mean.as_deref()),
+        ])?;
+        *self
+            .query_transform
+            .write()
+            .map_err(|_| anyhow!("query transform lock poisoned"))? = transform;
+        Ok(())
+    }
+
+    /// Whether a query of `dim` dimensions can be searched after preprocessing.
+    pub fn accepts_query_dim(&self, dim: usize) -> bool {
+        match self.query_transform() {
+            Some(t) => t.output_dim(dim).map_or(false, |d| d == self.dimension),
+            None => dim == self.dimension,
+        }
+    }
+
+    fn read_query_transform(table: &LanceTable) -> Result<Option<QueryTransform>> {
+        let Some(spec) = metadata::get(table, metadata::QUERY_TRANSFORM)? else {
+            return Ok(None);
+        };
+        let mean = metadata::get(table, metadata::QUERY_MEAN)?
+            .map(|m| QueryTransform::parse_mean(&m))
+            .transpose()?;
+        Ok(Some(QueryTransform::parse(&spec, mean)?))
+    }
+
+    /// Apply the configured query transform, if any, then the rotation.
+    fn prepare_query<'q>(&self, query: &'q [f32]) -> Result<Cow<'q, [f32]>> {
+        let query = match self.query_transform() {
+            Some(t) => Cow::Owned(t.apply(query)?),
+            None => Cow::Borrowed(query),
+        };
+        match self.rotation() {
+            Some(rotation) => Ok(Cow::Owned(rotation.apply(&query)?)),
+            None => Ok(query),
+        }
+    }
+
+    /// Element-wise mean of all stored vectors.
+    fn vector_mean(&self) -> Result<Vec<f32>> {
+        let (labels, vectors) = self.get_all_vectors()?;
+        if labels.is_empty() {
+            return Err(anyhow!("cannot compute a mean over an empty table"));
+        }
+        let mut mean = vec![0f64; self.dimension];
+        for v in vectors.chunks_exact(self.dimension) {
+            mean.iter_mut().zip(v).for_each(|(m, x)| *m += *x as f64);
+        }
+        Ok(mean.iter().map(|m| (m / labels.len() as f64) as f32).collect())
+    }
+
+    /// Search for k nearest neighbors, optionally restricted by a Lance SQL `filter`.
+    /// Unless `privileged`, the filter may not read sensitive columns (see `scan`);
+    /// the other searches take the flag in the same sense.
+    ///
+    /// `ef` sets how many candidates an HNSW index explores per query (0 keeps Lance's
+    /// default of about `k * 1.5`); larger values trade latency for recall without
+    /// rebuilding the index. IVF-only indexes ignore it.
+    ///
+    /// The configured query transform is applied first. When the table has a retrieval
+    /// pipeline configured, it replaces the plain ANN search (and `refine_factor` and `ef`).
+    pub fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        let results = self.search_unlogged(query, k, nprobes, refine_factor, ef, filter, privileged)?;
+        let labels = results.iter().map(|(label, _)| *label).collect();
+        if self.query_log.record(query, k, nprobes, refine_factor, ef, filter, labels) {
+            // A failed flush keeps the entries buffered; it must not fail the search.
+            let _ = self.flush_query_log();
+        }
+        Ok(results)
+    }
+
+    /// [`LanceIndex::search`] without a query log entry.
+    fn search_unlogged(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        let query = self.prepare_query(query)?;
+        self.with_reconnect(|| self.search_prepared(&query, k, nprobes, refine_factor, ef, filter, privileged))
+    }
+
+    /// [`LanceIndex::search`] of vector column `column`: `vector`, or during a
+    /// re-embed `vector_next`, to serve or compare the new embeddings before the
+    /// swap. Queries of the new column have the re-embed dimension and skip the
+    /// retrieval pipeline, query transform and rotation, which belong to the current
+    /// vectors; rows without a new vector yet are not found.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_column(
+        &self,
+        column: &str,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        match column {
+            "vector" => self.search(query, k, nprobes, refine_factor, ef, filter, privileged),
+            reembed::NEXT_COLUMN => {
+                self.check_filter(filter, privileged)?;
+                self.with_reconnect(|| self.reembed_search(query, k, nprobes, refine_factor, ef, filter))
+            }
+            other => Err(Self::unknown_search_column(other)),
+        }
+    }
+
+    /// ANN search of the `vector_next` column, checked against the metric of its index.
+    fn reembed_search(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        let table = self.get_table()?;
+        let dim = self.current_reembed(&table)?;
+        if query.len() != dim {
+            return Err(anyhow!("expected query dimension {} for {}, got {}", dim, reembed::NEXT_COLUMN, query.len()));
+        }
+        if let Some(index_metric) = metadata::get(&table, metadata::REEMBED_INDEX_METRIC)? {
+            if distance::canonical_metric(&self.metric)? != index_metric {
+                return Err(anyhow!(
+                    "search metric '{}' does not match the {} index, which was built with '{}'",
+                    self.metric,
+                    reembed::NEXT_COLUMN,
+                    index_metric
+                ));
+            }
+        }
+        let refine_factor = if refine_factor == AUTO_REFINE {
+            let compression = metadata::get(&table, metadata::REEMBED_INDEX_COMPRESSION)?.and_then(|r| r.parse().ok());
+            index_params::auto_refine_factor(compression, k)
+        } else {
+            refine_factor
+        };
+
+        let mut vector_query = table
+            .vector_search(query)
+            .map_err(|e| anyhow!("search setup: {}", e))?
+            .column(reembed::NEXT_COLUMN)
+            .select(Select::columns(&["label"]))
+            .limit(k)
+            .nprobes(nprobes);
+        if ef > 0 {
+            if ef < k {
+                return Err(anyhow!("ef {} is below k {}; HNSW must explore at least k candidates", ef, k));
+            }
+            vector_query = vector_query.ef(ef);
+        }
+        if refine_factor > 0 {
+            vector_query = vector_query.refine_factor(refine_factor as u32);
+        }
+        let written = format!("{} IS NOT NULL", reembed::NEXT_COLUMN);
+        if let Some(filter) = and_filters(self.live_filter(filter).as_deref(), Some(&written)) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let stream = runtime::block_on(vector_query.execute_with_options(self.read_options()))?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+
+        let mut output = scratch::results(k);
+        for batch in &batches {
+            let (labels, distances) = Self::label_distance_columns(batch)?;
+            output.extend(labels.values().iter().copied().zip(distances.values().iter().copied()));
+        }
+        Ok(output)
+    }
+
+    /// The multivector columns of the table with their dimensions.
+    pub fn multivector_columns(&self) -> Vec<(String, usize)> {
+        multivector::columns(&self.schema)
+    }
+
+    fn multivector_dimension(&self, column: &str) -> Result<usize> {
+        self.multivector_columns()
+            .into_iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, dim)| dim)
+            .ok_or_else(|| anyhow!("{} is not a multivector column of {}", column, self.table_name))
+    }
+
+    /// The `k` rows of multivector column `column` closest to the query vectors
+    /// `queries` (flattened, each of the column's dimension) under the multivector
+    /// distance (see [`multivector`]), with the handle's metric. An index on the
+    /// column is used when there is one; `nprobes` applies to it.
+    pub fn search_multivector(
+        &self,
+        column: &str,
+        queries: &[f32],
+        k: usize,
+        nprobes: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        let dim = self.multivector_dimension(column)?;
+        self.check_filter(filter, privileged)?;
+        let queries = multivector::query_vectors(queries, dim)?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        self.with_reconnect(|| self.multivector_search(column, &queries, k, nprobes, filter))
+    }
+
+    fn multivector_search(
+        &self,
+        column: &str,
+        queries: &[&[f32]],
+        k: usize,
+        nprobes: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        let table = self.get_table()?;
+        // Several query vectors on a List column form one multivector query
+        let mut vector_query = table
+            .vector_search(queries[0])
+            .map_err(|e| anyhow!("search setup: {}", e))?;
+        for query in &queries[1..] {
+            vector_query = vector_query
+                .add_query_vector(*query)
+                .map_err(|e| anyhow!("search setup: {}", e))?;
+        }
+        let mut vector_query = vector_query
+            .column(column)
+            .distance_type(Self::distance_type(distance::canonical_metric(&self.metric)?))
+            .select(Select::columns(&["label"]))
+            .limit(k)
+            .nprobes(nprobes);
+        if let Some(filter) = self.live_filter(filter) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let stream = runtime::block_on(vector_query.execute_with_options(self.read_options()))?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+
+        let mut output = scratch::results(k);
+        for batch in &batches {
+            let (labels, distances) = Self::label_distance_columns(batch)?;
+            output.extend(labels.values().iter().copied().zip(distances.values().iter().copied()));
+        }
+        Ok(output)
+    }
+
+    /// `search` for a query that is already in the stored vectors' space.
+    fn search_prepared(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        self.check_filter(filter, privileged)?;
+        let results = match self.pipeline() {
+            Some(pipeline) => self.search_pipeline(&pipeline, query, k, nprobes, filter, true),
+            None => self
+                .ann_search_filled(query, k, nprobes, refine_factor, ef, filter)
+                .and_then(|hits| self.with_cold_hits(hits, query, k, filter)),
+        }?;
+        if self.access.enabled() {
+            let labels: Vec<i64> = results.iter().map(|(label, _)| *label).collect();
+            self.record_access(&labels);
+        }
+        Ok(results)
+    }
+
+    /// [`LanceIndex::search`] for each of `num_queries` queries, flattened in
+    /// `queries`, with up to `concurrency` of them in flight at once (0 for
+    /// [`SEARCH_BATCH_CONCURRENCY`]). Returns (query index, label, distance) hits,
+    /// grouped by query in query order and nearest first within a query. The first
+    /// failing query fails the batch, naming the query.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_batch(
+        &self,
+        queries: &[f32],
+        num_queries: usize,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        concurrency: usize,
+        privileged: bool,
+    ) -> Result<Vec<(usize, i64, f32)>> {
+        if num_queries == 0 {
+            return Ok(Vec::new());
+        }
+        if queries.len() % num_queries != 0 {
+            return Err(anyhow!("{} query values do not split into {} queries", queries.len(), num_queries));
+        }
+        let dim = queries.len() / num_queries;
+        let workers = if concurrency == 0 { SEARCH_BATCH_CONCURRENCY } else { concurrency }.min(num_queries);
+
+        // Each worker blocks on the Lance runtime for one query at a time, so up to
+        // `workers` searches overlap their I/O there
+        let next = AtomicUsize::new(0);
+        let search = || -> Result<Vec<(usize, Vec<(i64, f32)>)>> {
+            let mut done = Vec::new();
+            loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= num_queries {
+                    return Ok(done);
+                }
+                let query = &queries[i * dim..(i + 1) * dim];
+                match self.search(query, k, nprobes, refine_factor, 0, filter, privileged) {
+                    Ok(hits) => done.push((i, hits)),
+                    Err(e) => {
+                        // Stop the other workers picking up queries
+                        next.store(num_queries, Ordering::Relaxed);
+                        return Err(anyhow!("query {}: {}", i, e));
+                    }
+                }
+            }
+        };
+        let finished: Vec<Result<Vec<(usize, Vec<(i64, f32)>)>>> = std::thread::scope(|scope| {
+            let running: Vec<_> = (0..workers).map(|_| scope.spawn(search)).collect();
+            running
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|_| Err(anyhow!("search worker panicked"))))
+                .collect()
+        });
+
+        let mut per_query: Vec<Vec<(i64, f32)>> = vec![Vec::new(); num_queries];
+        for worker in finished {
+            for (i, hits) in worker? {
+                per_query[i] = hits;
+            }
+        }
+        Ok(per_query
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, hits)| hits.into_iter().map(move |(label, distance)| (i, label, distance)))
+            .collect())
+    }
+
+    /// [`LanceIndex::search`] at the given read `consistency` (see
+    /// [`crate::consistency`]). `Latest` checks out the latest table version before
+    /// searching, so it also sees commits from other processes; read snapshots and
+    /// replicas refuse it, being pinned to a version.
+    pub fn search_with_consistency(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        consistency: Consistency,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        match consistency {
+            Consistency::Latest => {
+                if let Some(version) = self.snapshot_version.or(self.imported_version()) {
+                    return Err(anyhow!(
+                        "{} is pinned to version {}; latest consistency needs a live handle",
+                        self.table_name,
+                        version
+                    ));
+                }
+                let table = self.get_table()?;
+                self.note_failure(self.catch_up(&table, self.watch.generation()))?;
+                self.search(query, k, nprobes, refine_factor, 0, filter, privileged)
+            }
+            Consistency::Session => self.search(query, k, nprobes, refine_factor, 0, filter, privileged),
+            Consistency::Eventual => {
+                consistency::eventual(|| self.search(query, k, nprobes, refine_factor, 0, filter, privileged))
+            }
+        }
+    }
+
+    /// [`LanceIndex::search`] for the neighbors whose distance lies in `range`, e.g.
+    /// every row within a radius, nearest first. `k` caps the hits; the result is
+    /// complete only when fewer than `k` come back. Lance applies the range during
+    /// the search; with a retrieval pipeline configured, the pipeline's hits are
+    /// filtered instead.
+    pub fn search_range(
+        &self,
+        query: &[f32],
+        range: DistanceRange,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        range.validate()?;
+        self.check_filter(filter, privileged)?;
+        let query = self.prepare_query(query)?;
+        let results = self.with_reconnect(|| match self.pipeline() {
+            Some(pipeline) => {
+                let mut hits = self.search_pipeline(&pipeline, &query, k, nprobes, filter, true)?;
+                hits.retain(|(_, distance)| range.contains(*distance));
+                Ok(hits)
+            }
+            None => self.range_search(&query, range, k, nprobes, refine_factor, filter),
+        })?;
+        if self.access.enabled() {
+            let labels: Vec<i64> = results.iter().map(|(label, _)| *label).collect();
+            self.record_access(&labels);
+        }
+        Ok(results)
+    }
+
+    /// `ann_search` keeping the hits in `range`.
+    fn range_search(
+        &self,
+        query: &[f32],
+        range: DistanceRange,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        let mut vector_query =
+            self.vector_query(query, k, nprobes, refine_factor, 0)?.distance_range(range.lower, range.upper);
+        if let Some(filter) = self.live_filter(filter) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let stream = runtime::block_on(vector_query.execute_with_options(self.read_options()))?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+
+        let mut output = scratch::results(k);
+        for batch in &batches {
+            let (labels, distances) = Self::label_distance_columns(batch)?;
+            output.extend(labels.values().iter().copied().zip(distances.values().iter().copied()));
+        }
+        Ok(output)
+    }
+
+    /// Count a search hit on each of `labels` (see [`crate::access`]).
+    fn record_access(&self, labels: &[i64]) {
+        if self.access.record(labels) {
+            self.flush_access_in_background();
+        }
+    }
+
+    /// "More like `positive`, less like `negatives`": search with the query moved away
+    /// from the mean of the negative vectors (see [`transform::exclude_negatives`]).
+    /// `negatives` holds zero or more vectors of the query's dimension, flattened.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_with_negatives(
+        &self,
+        positive: &[f32],
+        negatives: &[f32],
+        weight: f32,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        let query = transform::exclude_negatives(positive, negatives, weight)?;
+        self.search(&query, k, nprobes, refine_factor, 0, filter, privileged)
+    }
+
+    /// Search with per-dimension `weights` (one per stored dimension; 0 masks a
+    /// dimension out), e.g. to down-weight known-noisy embedding dimensions. The ANN
+    /// index cannot weigh dimensions, so [`WEIGHTED_OVERFETCH`] times k candidates are
+    /// fetched and rescored with the weighted form of the exact metric (see
+    /// [`distance::weigh`]). With `weight_query`, the query is also weighed before the
+    /// ANN search so masked dimensions do not steer candidate selection.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_weighted(
+        &self,
+        query: &[f32],
+        weights: &[f32],
+        weight_query: bool,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        distance::check_weights(weights, self.dimension)?;
+        let query = self.prepare_query(query)?;
+        if query.len() != self.dimension {
+            return Err(anyhow!(
+                "expected query dimension {}, got {}",
+                self.dimension,
+                query.len()
+            ));
+        }
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let fetch = k.saturating_mul(WEIGHTED_OVERFETCH);
+        let candidates = if weight_query {
+            let weighed = distance::weigh(&query, weights);
+            self.search_prepared(&weighed, fetch, nprobes, refine_factor, 0, filter, privileged)?
+        } else {
+            self.search_prepared(&query, fetch, nprobes, refine_factor, 0, filter, privileged)?
+        };
+        let labels: Vec<i64> = candidates.iter().map(|(label, _)| *label).collect();
+        scratch::recycle(candidates);
+        let vectors = self.vectors_for_labels(&labels)?;
+
+        let (metric, weights) = (self.exact_metric()?, weights.to_vec());
+        let query = distance::weigh(&query, &weights);
+        let mut results: Vec<(i64, f32)> = runtime::run_cpu(move || {
+            vectors
+                .iter()
+                .map(|(label, v)| Ok((*label, metric.distance(&query, &distance::weigh(v, &weights))?)))
+                .collect::<Result<_>>()
+        })?;
+        results.sort_by(|a, b| a.1.total_cmp(&b.1));
+        results.truncate(k);
+        Ok(results)
+    }
+
+    /// Hybrid search: fuse the vector hits for `query` with the BM25 full-text hits
+    /// for `text` (over `text_column`, or every full-text indexed column) using
+    /// `fusion` (see [`crate::fusion`]). Each side fetches [`HYBRID_OVERFETCH`] times
+    /// k candidates matching `filter`. Returns the k best (label, fused score),
+    /// highest first. The text side needs a full-text index (see
+    /// [`LanceIndex::create_fts_index`]).
+    #[allow(clippy::too_many_arguments)]
+    pub fn hybrid_search(
+        &self,
+        query: &[f32],
+        text: &str,
+        text_column: Option<&str>,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        fusion: Fusion,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let fetch = k.saturating_mul(HYBRID_OVERFETCH);
+        let vector_hits = self.search(query, fetch, nprobes, refine_factor, 0, filter, privileged)?;
+        let text_hits = self.with_reconnect(|| self.text_search(text, text_column, fetch, filter))?;
+        let fused = fusion.fuse(&vector_hits, &text_hits, k);
+        scratch::recycle(vector_hits);
+        Ok(fused)
+    }
+
+    /// The `limit` best BM25 hits for `text` as (label, score), best first.
+    fn text_search(
+        &self,
+        text: &str,
+        column: Option<&str>,
+        limit: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        if let Some(column) = column {
+            match self.schema.field_with_name(column).map(|f| f.data_type()) {
+                Ok(DataType::Utf8 | DataType::LargeUtf8) => {}
+                Ok(other) => return Err(anyhow!("text column '{}' is {}, expected a string", column, other)),
+                Err(_) => return Err(anyhow!("text column '{}' not found", column)),
+            }
+        }
+        let fts = FullTextSearchQuery::new(text.to_string()).columns(column.map(|c| vec![c.to_string()]));
+        let mut text_query = self
+            .get_table()?
+            .query()
+            .full_text_search(fts)
+            .select(Select::columns(&["label"]))
+            .limit(limit);
+        if let Some(filter) = self.live_filter(filter) {
+            text_query = text_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let stream = runtime::block_on(text_query.execute_with_options(self.read_options()))?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+
+        let mut hits = Vec::new();
+        for batch in &batches {
+            let labels = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("missing label column"))?;
+            let scores = batch
+                .column_by_name("_score")
+                .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
+                .ok_or_else(|| anyhow!("missing _score column"))?;
+            hits.extend(labels.values().iter().copied().zip(scores.values().iter().copied()));
+        }
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(hits)
+    }
+
+    /// Search keeping only the best hit per distinct value of `dedup_column` (e.g. the
+    /// document id shared by its chunks), which must be exportable (see `scan`);
+    /// NULL counts as one value. Over-fetches [`DEDUP_OVERFETCH`] times k and widens
+    /// the search by the same factor until k distinct values are found or no more
+    /// rows are reachable.
+    pub fn search_dedup(
+        &self,
+        query: &[f32],
+        k: usize,
+        dedup_column: &str,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        let field = self
+            .schema
+            .field_with_name(dedup_column)
+            .map_err(|_| anyhow!("dedup column '{}' not found", dedup_column))?;
+        if dedup_column == "vector" {
+            return Err(anyhow!("cannot deduplicate by the vector column"));
+        }
+        // Which hits survive deduplication reveals whether their values are equal
+        self.check_exportable(&[dedup_column], privileged)?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let converter = RowConverter::new(vec![SortField::new(field.data_type().clone())])?;
+        let query = self.prepare_query(query)?;
+        let hits = self.search_distinct(&query, k, nprobes, refine_factor, filter, privileged, |labels| {
+            self.dedup_keys(labels, dedup_column, &converter)
+        })?;
+        Ok(hits.into_iter().map(|(label, distance, _)| (label, distance)).collect())
+    }
+
+    /// Parent-document retrieval: search the chunks and return the k best distinct
+    /// parents of `parent_column` (by default the parent column of the chunking
+    /// stage), each with its best chunk. The parent column must be exportable (see
+    /// `scan`), as its values are returned. A row with a NULL parent is its own
+    /// parent. Widens the search like [`LanceIndex::search_dedup`].
+    pub fn search_parents(
+        &self,
+        query: &[f32],
+        k: usize,
+        parent_column: Option<&str>,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<ParentHit>> {
+        let parent_column = match parent_column {
+            Some(column) => column.to_string(),
+            None => self
+                .chunking()
+                .map(|c| c.parent)
+                .ok_or_else(|| anyhow!("no parent column given and no chunking stage is configured"))?,
+        };
+        let field = self
+            .schema
+            .field_with_name(&parent_column)
+            .map_err(|_| anyhow!("parent column '{}' not found", parent_column))?;
+        if field.data_type() != &DataType::Int64 {
+            return Err(anyhow!("parent column '{}' must be BIGINT, got {}", parent_column, field.data_type()));
+        }
+        self.check_exportable(&[parent_column.as_str()], privileged)?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let query = self.prepare_query(query)?;
+        let hits = self.search_distinct(&query, k, nprobes, refine_factor, filter, privileged, |labels| {
+            self.parent_ids(labels, &parent_column)
+        })?;
+        Ok(hits
+            .into_iter()
+            .map(|(label, distance, parent)| ParentHit { parent, label, distance })
+            .collect())
+    }
+
+    /// The k nearest hits with distinct keys, each with its key. Over-fetches
+    /// [`DEDUP_OVERFETCH`] times k and widens the search by the same factor until k
+    /// distinct keys are found or no more rows are reachable. `keys` maps hit labels
+    /// to their keys; a label without a key (e.g. deleted meanwhile) is skipped.
+    fn search_distinct<K: Eq + Hash + Clone>(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+        keys: impl Fn(&[i64]) -> Result<HashMap<i64, K>>,
+    ) -> Result<Vec<(i64, f32, K)>> {
+        let rows = self.count()? as usize;
+        let mut fetch = k.saturating_mul(DEDUP_OVERFETCH);
+        loop {
+            let hits = self.search_prepared(query, fetch, nprobes, refine_factor, 0, filter, privileged)?;
+            let exhausted = hits.len() < fetch || fetch >= rows;
+            let labels: Vec<i64> = hits.iter().map(|(label, _)| *label).collect();
+            let keys = keys(&labels)?;
+
+            let mut seen = HashSet::new();
+            let mut results = Vec::with_capacity(k);
+            // Hits are nearest first, so the first hit of each key is its best
+            for &(label, distance) in &hits {
+                if let Some(key) = keys.get(&label) {
+                    if seen.insert(key.clone()) {
+                        results.push((label, distance, key.clone()));
+                        if results.len() == k {
+                            break;
+                        }
+                    }
+                }
+            }
+            scratch::recycle(hits);
+            if results.len() == k || exhausted {
+                return Ok(results);
+            }
+            fetch = fetch.saturating_mul(DEDUP_OVERFETCH).min(rows);
+        }
+    }
+
+    /// Row-encoded values of `column` for `labels`.
+    fn dedup_keys(&self, labels: &[i64], column: &str, converter: &RowConverter) -> Result<HashMap<i64, OwnedRow>> {
+        let mut keys = HashMap::with_capacity(labels.len());
+        self.scan_labels(labels, column, |batch_labels, values| {
+            let encoded = converter.convert_columns(&[values.clone()])?;
+            for (label, row) in batch_labels.values().iter().zip(encoded.iter()) {
+                keys.insert(*label, row.owned());
+            }
+            Ok(())
+        })?;
+        Ok(keys)
+    }
+
+    /// Int64 `column` of `labels`, with a NULL replaced by the row's own label.
+    fn parent_ids(&self, labels: &[i64], column: &str) -> Result<HashMap<i64, i64>> {
+        let mut parents = HashMap::with_capacity(labels.len());
+        self.scan_labels(labels, column, |batch_labels, values| {
+            let values = values
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .ok_or_else(|| anyhow!("parent column '{}' must be BIGINT", column))?;
+            for (i, label) in batch_labels.values().iter().enumerate() {
+                let parent = if values.is_null(i) { *label } else { values.value(i) };
+                parents.insert(*label, parent);
+            }
+            Ok(())
+        })?;
+        Ok(parents)
+    }
+
+    /// Visit (label, `column`) of the in-scope rows among `labels`, read as
+    /// `label IN (...)` scans of at most [`SEARCH_WITHIN_CHUNK`] labels each.
+    fn scan_labels(
+        &self,
+        labels: &[i64],
+        column: &str,
+        mut visit: impl FnMut(&Int64Array, &ArrayRef) -> Result<()>,
+    ) -> Result<()> {
+        let table = self.get_table()?;
+        let columns = if column == "label" { vec!["label"] } else { vec!["label", column] };
+        for chunk in labels.chunks(SEARCH_WITHIN_CHUNK) {
+            let label_list: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let mut predicate = format!("label IN ({})", label_list.join(", "));
+            if let Some(scope) = &self.scope {
+                predicate = format!("{} AND ({})", predicate, scope);
+            }
+            let stream = runtime::block_on(
+                table
+                    .query()
+                    .select(Select::columns(&columns))
+                    .only_if(predicate)
+                    .execute(),
+            )?;
+            let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            for batch in &batches {
+                let batch_labels = batch
+                    .column_by_name("label")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("missing label column"))?;
+                let values = batch
+                    .column_by_name(column)
+                    .ok_or_else(|| anyhow!("missing {} column", column))?;
+                visit(batch_labels, values)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Approximate search for fast exploratory estimates: probes only a `fraction`
+    /// of the `nprobes` IVF partitions a full search would, nearest first, so rows
+    /// in the others are never read. The returned [`SampledSearch`] is flagged
+    /// approximate and scales ranks by `1 / fraction`. Without a vector index every
+    /// row is searched, and the fraction reported is 1.
+    pub fn search_sampled(
+        &self,
+        query: &[f32],
+        k: usize,
+        fraction: f64,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<SampledSearch> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(anyhow!("sample_fraction must be in (0, 1], got {}", fraction));
+        }
+        let nprobes = nprobes.max(1);
+        let probes = ((nprobes as f64 * fraction).round() as usize).clamp(1, nprobes);
+        let results = self.search(query, k, probes, refine_factor, 0, filter, privileged)?;
+        let fraction = match self.index_staleness()? {
+            Some(_) => probes as f64 / nprobes as f64,
+            None => 1.0,
+        };
+        Ok(SampledSearch { results, fraction })
+    }
+
+    /// Multi-hop retrieval: the k nearest neighbors, then up to `hops` rounds that search
+    /// around each row found in the previous round for `per_hop` more. Each round keeps
+    /// the `k` best new rows, so at most `k * (hops + 1)` rows are returned, deduplicated
+    /// and ranked by [`ExpandedHit::score`]. `decay` in (0, 1] discounts each hop.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_expand(
+        &self,
+        query: &[f32],
+        k: usize,
+        hops: usize,
+        per_hop: usize,
+        decay: f64,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<ExpandedHit>> {
+        if !(decay > 0.0 && decay <= 1.0) {
+            return Err(anyhow!("decay must be in (0, 1], got {}", decay));
+        }
+        let query = self.prepare_query(query)?;
+        let metric = self.exact_metric()?;
+        let hit = |label: i64, distance: f32, hop: u32| {
+            let log_score = ExpandedHit::log_score(distance, hop, decay);
+            (log_score, ExpandedHit { label, distance, hop, score: log_score.exp() })
+        };
+
+        let mut found: HashMap<i64, (f64, ExpandedHit)> = HashMap::new();
+        let mut frontier: Vec<i64> = Vec::new();
+        for (label, distance) in self.search_prepared(&query, k, nprobes, refine_factor, 0, filter, privileged)? {
+            found.insert(label, hit(label, distance, 0));
+            frontier.push(label);
+        }
+
+        for hop in 1..=hops as u32 {
+            if frontier.is_empty() || per_hop == 0 {
+                break;
+            }
+            // Stored vectors are already in the indexed space
+            let mut candidates = HashSet::new();
+            for (_, vector) in self.vectors_for_labels(&frontier)? {
+                for (neighbor, _) in self.ann_search(&vector, per_hop + 1, nprobes, refine_factor, 0, filter)? {
+                    if !found.contains_key(&neighbor) {
+                        candidates.insert(neighbor);
+                    }
+                }
+            }
+            let candidates: Vec<i64> = candidates.into_iter().collect();
+            let mut reached = self
+                .vectors_for_labels(&candidates)?
+                .into_iter()
+                .map(|(label, v)| Ok(hit(label, metric.distance(&query, &v)?, hop)))
+                .collect::<Result<Vec<_>>>()?;
+            reached.sort_by(|a, b| b.0.total_cmp(&a.0));
+            reached.truncate(k);
+            frontier = reached.iter().map(|(_, h)| h.label).collect();
+            found.extend(reached.into_iter().map(|(log_score, h)| (h.label, (log_score, h))));
+        }
+
+        let mut results: Vec<(f64, ExpandedHit)> = found.into_values().collect();
+        results.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.label.cmp(&b.1.label)));
+        Ok(results.into_iter().map(|(_, h)| h).collect())
+    }
+
+    /// "Find items similar to this basket": search with the centroid of the stored
+    /// vectors of `labels`, moved away from the centroid of `negative_labels` by
+    /// `weight` when any are given. The basket's own rows are excluded from the results.
+    ///
+    /// Stored vectors are already in the indexed space, so the query transform is not
+    /// applied to the centroid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_like_labels(
+        &self,
+        labels: &[i64],
+        negative_labels: &[i64],
+        weight: f32,
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        if labels.is_empty() {
+            return Err(anyhow!("search_like_labels needs at least one label"));
+        }
+        self.check_filter(filter, privileged)?;
+        let centroid = transform::mean_vector(&self.stored_vectors(labels)?, self.dimension)?;
+        let query = transform::exclude_negatives(&centroid, &self.stored_vectors(negative_labels)?, weight)?;
+
+        let mut seeds = labels.to_vec();
+        seeds.sort_unstable();
+        seeds.dedup();
+        let seeds = seeds.iter().map(i64::to_string).collect::<Vec<_>>().join(", ");
+        let exclusion = format!("label NOT IN ({})", seeds);
+        let filter = match filter {
+            Some(filter) => format!("({}) AND {}", filter, exclusion),
+            None => exclusion,
+        };
+        self.search_prepared(&query, k, nprobes, refine_factor, 0, Some(&filter), true)
+    }
+
+    /// Stored vectors of `labels`, flattened in the given order. Fails if any label is missing.
+    fn stored_vectors(&self, labels: &[i64]) -> Result<Vec<f32>> {
+        let found: HashMap<i64, Vec<f32>> = self.vectors_for_labels(labels)?.into_iter().collect();
+        let missing: Vec<i64> = labels.iter().filter(|l| !found.contains_key(l)).copied().collect();
+        if !missing.is_empty() {
+            return Err(anyhow!("labels not found: {:?}", missing));
+        }
+        Ok(labels.iter().flat_map(|l| found[l].iter().copied()).collect())
+    }
+
+    /// Rank `labels` by exact distance to `query` and return the k nearest, for callers
+    /// that have already narrowed the candidates (e.g. through joins) and only need
+    /// vector ranking of that subset. Labels that do not exist are ignored.
+    ///
+    /// The allow-list is deduplicated and read as `label IN (...)` scans of at most
+    /// [`SEARCH_WITHIN_CHUNK`] labels each, so memory stays bounded by the chunk size
+    /// plus k regardless of the list length.
+    pub fn search_within(&self, query: &[f32], k: usize, labels: &[i64]) -> Result<Vec<(i64, f32)>> {
+        let mut labels = labels.to_vec();
+        labels.sort_unstable();
+        labels.dedup();
+        if labels.is_empty() || k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let query = self.prepare_query(query)?;
+        if query.len() != self.dimension {
+            return Err(anyhow!(
+                "expected query dimension {}, got {}",
+                self.dimension,
+                query.len()
+            ));
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let live = self.live_filter(None);
+        let metric = self.exact_metric()?;
+        let mut results = Vec::with_capacity(k + SEARCH_WITHIN_CHUNK);
+        for chunk in labels.chunks(SEARCH_WITHIN_CHUNK) {
+            for (label, vector) in self.vectors_where(chunk, live.as_deref())? {
+                results.push((label, metric.distance(&query, &vector)?));
+            }
+            results.sort_by(|a, b| a.1.total_cmp(&b.1));
+            results.truncate(k);
+        }
+
+        let hits: Vec<i64> = results.iter().map(|(label, _)| *label).collect();
+        self.record_access(&hits);
+        Ok(results)
+    }
+
+    /// Fit a PCA projection to `target_dims` on the first `sample` vectors, store it in
+    /// the table metadata and (re)write the `vector_pca` column for every row. Rows
+    /// appended later are projected on the way in. With `index`, also builds a vector
+    /// index of that type (default parameters, L2) on the reduced column.
+    pub fn train_pca(
+        &self,
+        target_dims: usize,
+        sample: usize,
+        index: Option<VectorIndexType>,
+    ) -> Result<Arc<Pca>> {
+        use lancedb::table::NewColumnTransform;
+
+        self.require_unscoped("train_pca")?;
+        self.require_float32_vectors("train_pca")?;
+        self.require_no_reembed("train_pca")?;
+        if sample < 2 {
+            return Err(anyhow!("PCA sample must be at least 2 rows"));
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+
+        let vectors_of = |batch: &RecordBatch| -> Result<FixedSizeListArray> {
+            batch
+                .column_by_name("vector")
+                .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                .cloned()
+                .ok_or_else(|| anyhow!("missing vector column"))
+        };
+        let stream = runtime::block_on(
+            table
+                .query()
+                .select(Select::columns(&["vector"]))
+                .limit(sample)
+                .execute(),
+        )?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let mut flat = Vec::with_capacity(sample * self.dimension);
+        for batch in &batches {
+            let vectors = vectors_of(batch)?;
+            for i in 0..vectors.len() {
+                let values = vectors.value(i);
+                let values = values
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                flat.extend_from_slice(values.values());
+            }
+        }
+        let dimension = self.dimension;
+        let fitted = Arc::new(runtime::run_cpu(move || Pca::fit(&flat, dimension, target_dims))?);
+
+        let current = Self::read_table_schema(&table)?;
+        if current.column_with_name(pca::PCA_COLUMN).is_some() {
+            runtime::block_on(table.drop_columns(&[pca::PCA_COLUMN]))?;
+        }
+        // add_columns aligns the reader with the table's rows in scan order
+        let schema = Arc::new(Schema::new(vec![fitted.field()]));
+        let mut projected = Vec::new();
+        let mut stream = runtime::block_on(table.query().select(Select::columns(&["vector"])).execute())?;
+        while let Some(batch) = runtime::block_on(stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            self.admission.yield_to_interactive();
+            let column = fitted.project_array(&vectors_of(&batch)?)?;
+            projected.push(RecordBatch::try_new(schema.clone(), vec![Arc::new(column)])?);
+        }
+        let reader = RecordBatchIterator::new(projected.into_iter().map(Ok), schema);
+        runtime::block_on(table.add_columns(NewColumnTransform::Reader(Box::new(reader)), None))?;
+        metadata::set(&table, metadata::PCA, Some(&fitted.encode()))?;
+        *self.pca.write().map_err(|_| anyhow!("pca lock poisoned"))? = Some(fitted.clone());
+        self.committed();
+
+        if let Some(kind) = index {
+            let params = VectorIndexParams {
+                metric: Some("l2".to_string()),
+                ..Default::default()
+            };
+            let (index, _) = self.vector_index_spec(kind, &params)?;
+            runtime::block_on_cpu(
+                table
+                    .create_index(&[pca::PCA_COLUMN], index)
+                    .replace(true)
+                    .execute(),
+            )?;
+            self.committed();
+        }
+        Ok(fitted)
+    }
+
+    /// The trained PCA projection, if any.
+    pub fn pca(&self) -> Option<Arc<Pca>> {
+        self.pca.read().ok().and_then(|p| p.clone())
+    }
+
+    /// `batch` with its `vector_pca` column added when a projection is trained.
+    fn with_projection(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let Some(pca) = self.pca() else {
+            return Ok(batch);
+        };
+        if batch.schema().column_with_name(pca::PCA_COLUMN).is_some() {
+            return Ok(batch);
+        }
+        let vectors = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+            .ok_or_else(|| anyhow!("missing vector column"))?;
+        let projected = pca.project_array(vectors)?;
+        let mut fields = batch.schema().fields().to_vec();
+        fields.push(Arc::new(pca.field()));
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(projected));
+        let schema = Schema::new_with_metadata(fields, batch.schema().metadata().clone());
+        Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+    }
+
+    /// Train an OPQ-style rotation for `num_sub_vectors` PQ sub-vectors on the first
+    /// `sample` vectors, store it in the table metadata and rewrite every row rotated.
+    /// Rows appended later and queries are rotated on the way in, and vectors read back
+    /// are rotated back. Distances are unchanged; rebuild the IVF_PQ index afterwards
+    /// (with the same `num_sub_vectors`) to benefit.
+    ///
+    /// Handles that have not seen the rotation would write unrotated vectors, so this
+    /// handle must be the only writable one on the table in this process; writers in
+    /// other processes are only kept out by the writer lease (see [`crate::lease`]).
+    /// The rows are replaced in one commit (see `replace_rows`) and the rotation is
+    /// recorded in the next; if recording it fails, the table is restored to the
+    /// version before the rewrite.
+    pub fn train_rotation(&self, num_sub_vectors: usize, sample: usize) -> Result<Arc<Rotation>> {
+        self.require_unscoped("train_rotation")?;
+        self.require_float32_vectors("train_rotation")?;
+        self.require_no_reembed("train_rotation")?;
+        if sample < 2 {
+            return Err(anyhow!("rotation sample must be at least 2 rows"));
+        }
+        if self.pca().is_some() {
+            return Err(anyhow!("cannot train a rotation after a PCA projection; train it first"));
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        self.require_sole_writer("train_rotation")?;
+        let table = self.get_table()?;
+        let version = runtime::block_on(table.version())?;
+        let previous = self.rotation();
+
+        let stream = runtime::block_on(
+            table
+                .query()
+                .select(Select::columns(&["vector"]))
+                .limit(sample)
+                .execute(),
+        )?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let mut flat = Vec::with_capacity(sample * self.dimension);
+        for vectors in batches.iter().filter_map(Self::vector_column) {
+            for i in 0..vectors.len() {
+                let values = vectors.value(i);
+                let values = values
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                match &previous {
+                    Some(rotation) => flat.extend(rotation.invert(values.values())?),
+                    None => flat.extend_from_slice(values.values()),
+                }
+            }
+        }
+        let dimension = self.dimension;
+        let fitted = Arc::new(runtime::run_cpu(move || Rotation::fit(&flat, dimension, num_sub_vectors))?);
+
+        let rerotate = |v: &[f32]| match &previous {
+            Some(rotation) => fitted.apply(&rotation.invert(v)?),
+            None => fitted.apply(v),
+        };
+        let rows_per_fragment = WriteParams::default().max_rows_per_file;
+        self.replace_rows(&table, version, rows_per_fragment, |batches, schema| {
+            let mut rotated = Vec::new();
+            for batch in batches {
+                let batch = batch?;
+                if batch.num_rows() > 0 {
+                    rotated.push(Self::map_vectors(batch, |v| fitted.map_array(v, |_, v| rerotate(v)))?);
+                }
+            }
+            let rows: Box<dyn RecordBatchReader + Send> =
+                Box::new(RecordBatchIterator::new(rotated.into_iter().map(Ok), schema));
+            Ok(rows)
+        })?;
+        if let Err(e) = metadata::set(&table, metadata::ROTATION, Some(&fitted.encode())) {
+            // Rotated rows without the recorded rotation would be read back rotated
+            let restored = runtime::block_on(async {
+                table.checkout(version).await?;
+                table.restore().await
+            });
+            self.committed();
+            return Err(match restored {
+                Ok(()) => anyhow!("recording the rotation failed, version {} restored: {}", version, e),
+                Err(restore_err) => anyhow!("{} (restoring version {} also failed: {})", e, version, restore_err),
+            });
+        }
+        *self.rotation.write().map_err(|_| anyhow!("rotation lock poisoned"))? = Some(fitted.clone());
+        self.committed();
+        Ok(fitted)
+    }
+
+    /// The trained rotation, if any.
+    pub fn rotation(&self) -> Option<Arc<Rotation>> {
+        self.rotation.read().ok().and_then(|r| r.clone())
+    }
+
+    /// `batch` with its vectors rotated into the stored space when a rotation is trained.
+    fn with_rotation(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        match self.rotation() {
+            Some(rotation) => Self::map_vectors(batch, |v| rotation.map_array(v, Rotation::apply)),
+            None => Ok(batch),
+        }
+    }
+
+    /// `batch` with its `vector` column, if present, replaced by `f` of it.
+    fn map_vectors(
+        batch: RecordBatch,
+        f: impl Fn(&FixedSizeListArray) -> Result<FixedSizeListArray>,
+    ) -> Result<RecordBatch> {
+        let Ok(index) = batch.schema().index_of("vector") else {
+            return Ok(batch);
+        };
+        let vectors = batch
+            .column(index)
+            .as_any()
+            .downcast_ref::<FixedSizeListArray>()
+            .ok_or_else(|| anyhow!("vector not FixedSizeList"))?;
+        let mut columns = batch.columns().to_vec();
+        columns[index] = Arc::new(f(vectors)?);
+        Ok(RecordBatch::try_new(batch.schema(), columns)?)
+    }
+
+    /// k nearest neighbors found in the PCA-reduced space, then rescored exactly:
+    /// the `k * rescore_factor` nearest by L2 on `vector_pca` are re-ranked by the
+    /// handle's exact metric (see `set_rescore_metric`) on the full vectors.
+    pub fn search_pca(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        rescore_factor: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        let pca = self
+            .pca()
+            .ok_or_else(|| anyhow!("no PCA projection trained; call train_pca first"))?;
+        self.check_filter(filter, privileged)?;
+        let query = self.prepare_query(query)?;
+        let reduced = pca.project(&query)?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let table = self.get_table()?;
+        let mut vector_query = table
+            .vector_search(reduced)
+            .map_err(|e| anyhow!("search setup: {}", e))?
+            .column(pca::PCA_COLUMN)
+            .distance_type(lancedb::DistanceType::L2)
+            .select(Select::columns(&["label"]))
+            .limit(k.saturating_mul(rescore_factor.max(1)))
+            .nprobes(nprobes);
+        if let Some(filter) = self.live_filter(filter) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let mut candidates = Vec::new();
+        let mut stream = runtime::block_on(vector_query.execute())?;
+        while let Some(batch) = runtime::block_on(stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            let (labels, _) = Self::label_distance_columns(&batch)?;
+            candidates.extend(labels.values().iter().copied());
+        }
+
+        let metric = self.exact_metric()?;
+        let mut results = Vec::with_capacity(candidates.len());
+        for chunk in candidates.chunks(SEARCH_WITHIN_CHUNK) {
+            for (label, vector) in self.vectors_where(chunk, None)? {
+                results.push((label, metric.distance(&query, &vector)?));
+            }
+        }
+        results.sort_by(|a, b| a.1.total_cmp(&b.1));
+        results.truncate(k);
+
+        let hits: Vec<i64> = results.iter().map(|(label, _)| *label).collect();
+        self.record_access(&hits);
+        Ok(results)
+    }
+
+    /// Run `pipeline` for the k nearest neighbors.
+    pub fn search_pipeline(
+        &self,
+        pipeline: &Pipeline,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        filter: Option<&str>,
+        privileged: bool,
+    ) -> Result<Vec<(i64, f32)>> {
+        self.check_filter(filter, privileged)?;
+        let mut candidates = Vec::new();
+        for stage in &pipeline.stages {
+            match stage {
+                Stage::Coarse { .. } => {
+                    candidates = self.ann_search(query, pipeline.candidates(k), nprobes, 0, 0, filter)?;
+                }
+                Stage::Rescore => {
+                    let labels: Vec<i64> = candidates.iter().map(|(label, _)| *label).collect();
+                    let vectors = self.vectors_for_labels(&labels)?;
+                    let (metric, query) = (self.exact_metric()?, query.to_vec());
+                    candidates = runtime::run_cpu(move || {
+                        vectors
+                            .iter()
+                            .map(|(label, v)| Ok((*label, metric.distance(&query, v)?)))
+                            .collect::<Result<_>>()
+                    })?;
+                }
+                Stage::Rerank { name } => {
+                    let (labels, distances): (Vec<i64>, Vec<f32>) = candidates.iter().copied().unzip();
+                    let reranker = pipeline::reranker(name)?;
+                    let query = query.to_vec();
+                    let (labels, scores) = runtime::run_cpu(move || {
+                        let scores = reranker(&query, &labels, &distances);
+                        (labels, scores)
+                    });
+                    let scores = scores?;
+                    if scores.len() != labels.len() {
+                        return Err(anyhow!(
+                            "reranker '{}' returned {} scores for {} candidates",
+                            name,
+                            scores.len(),
+                            labels.len()
+                        ));
+                    }
+                    candidates = labels.into_iter().zip(scores).collect();
+                }
+            }
+            candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+        }
+        candidates.truncate(k);
+        Ok(candidates)
+    }
+
+    /// Plain ANN search through the Lance index.
+    fn ann_search(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        if self.vector_storage() == VectorStorage::Int8 {
+            return self.int8_search(query, k, filter);
+        }
+        let mut vector_query = self.vector_query(query, k, nprobes, refine_factor, ef)?;
+        if let Some(filter) = self.live_filter(filter) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let results = runtime::block_on(vector_query.execute_with_options(self.read_options()))?;
+
+        let mut output = scratch::results(k);
+
+        runtime::block_on(async {
+            let mut stream = results;
+            while let Some(batch) = stream.try_next().await
+                .map_err(|e| anyhow!("stream error: {}", e))? {
+                let (labels, distances) = Self::label_distance_columns(&batch)?;
+                output.extend(labels.values().iter().copied().zip(distances.values().iter().copied()));
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+
+        Ok(output)
+    }
+
+    /// `ann_search` that, when a filter leaves it short of k hits while more rows
+    /// match, retries with doubled nprobes up to the hit budget and finally searches
+    /// the matching rows exactly if there are few enough of them.
+    fn ann_search_filled(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        let mut results = self.ann_search(query, k, nprobes, refine_factor, ef, filter)?;
+        if results.len() >= k {
+            return Ok(results);
+        }
+        let Some(predicate) = self.live_filter(filter) else {
+            return Ok(results);
+        };
+        let matching = runtime::block_on(self.get_table()?.count_rows(Some(predicate)))? as u64;
+        let wanted = (k as u64).min(matching) as usize;
+
+        let budget = self.hit_budget();
+        let mut nprobes = nprobes.max(1);
+        while results.len() < wanted && nprobes < budget.max_nprobes {
+            nprobes = (nprobes * 2).min(budget.max_nprobes);
+            scratch::recycle(results);
+            results = self.ann_search(query, k, nprobes, refine_factor, ef, filter)?;
+        }
+        if results.len() < wanted && matching <= budget.max_exact_rows {
+            scratch::recycle(results);
+            results = self.exact_filtered_search(query, k, filter)?;
+        }
+        Ok(results)
+    }
+
+    /// Exact k-NN over int8 vectors, which Lance cannot search: the rows matching
+    /// `filter` are scanned, dequantized and ranked by the search metric.
+    fn int8_search(&self, query: &[f32], k: usize, filter: Option<&str>) -> Result<Vec<(i64, f32)>> {
+        if query.len() != self.dimension {
+            return Err(anyhow!("expected query dimension {}, got {}", self.dimension, query.len()));
+        }
+        let metric = distance::Metric::resolve(&self.metric)?;
+        let table = self.get_table()?;
+        let mut select = table.query().select(Select::columns(&["label", "vector"]));
+        if let Some(filter) = self.live_filter(filter) {
+            select = select.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let mut output = scratch::results(k);
+        let mut stream = runtime::block_on(select.execute_with_options(self.read_options()))?;
+        while let Some(batch) = runtime::block_on(stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            let labels = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("missing label column"))?;
+            let vectors = Self::vector_column(&batch).ok_or_else(|| anyhow!("missing vector column"))?;
+            let vectors = self.widen_vectors(vectors)?;
+            let values = vectors
+                .values()
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .ok_or_else(|| anyhow!("vector values not Float32"))?;
+            for (i, vector) in values.values().chunks_exact(self.dimension).enumerate() {
+                output.push((labels.value(i), metric.distance(query, vector)?));
+            }
+            // Keep the buffer near k rather than the size of the table
+            if output.len() > k.saturating_mul(2).max(1024) {
+                output.sort_by(|a, b| a.1.total_cmp(&b.1));
+                output.truncate(k);
+            }
+        }
+        output.sort_by(|a, b| a.1.total_cmp(&b.1));
+        output.truncate(k);
+        Ok(output)
+    }
+
+    /// `hits` merged with the k nearest cold rows matching `filter` (see
+    /// [`crate::cold`]), which are ranked exactly as their vectors are widened.
+    fn with_cold_hits(
+        &self,
+        mut hits: Vec<(i64, f32)>,
+        query: &[f32],
+        k: usize,
+        filter: Option<&str>,
+    ) -> Result<Vec<(i64, f32)>> {
+        if !self.cold_rows.load(Ordering::Acquire) {
+            return Ok(hits);
+        }
+        let Some(sidecar) = self.cold_table(false)? else {
+            return Ok(hits);
+        };
+        let metric = distance::Metric::resolve(&self.metric)?;
+        let mut columns = vec!["label", "vector"];
+        if Self::read_table_schema(&sidecar)?.field_with_name(cold::VECTOR_SCALE).is_ok() {
+            columns.push(cold::VECTOR_SCALE);
+        }
+        let mut select = sidecar.query().select(Select::columns(&columns));
+        if let Some(filter) = self.live_filter(filter) {
+            select = select.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let mut stream = runtime::block_on(select.execute_with_options(self.read_options()))?;
+        while let Some(batch) = runtime::block_on(stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            let labels = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("missing label column"))?;
+            let vectors = cold::widen(&batch)?;
+            let values = vectors
+                .values()
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .ok_or_else(|| anyhow!("vector values not Float32"))?;
+            for (i, vector) in values.values().chunks_exact(self.dimension).enumerate() {
+                hits.push((labels.value(i), metric.distance(query, vector)?));
+            }
+            if hits.len() > k.saturating_mul(2).max(1024) {
+                hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+                hits.truncate(k);
+            }
+        }
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        // A move interrupted before its delete leaves a row in both tables
+        let mut seen = HashSet::new();
+        hits.retain(|(label, _)| seen.insert(*label));
+        hits.truncate(k);
+        Ok(hits)
+    }
+
+    /// Exact k-NN over the rows matching `filter`, bypassing the vector index.
+    fn exact_filtered_search(&self, query: &[f32], k: usize, filter: Option<&str>) -> Result<Vec<(i64, f32)>> {
+        let mut vector_query = self.vector_query(query, k, 1, 0, 0)?.bypass_vector_index();
+        if let Some(filter) = self.live_filter(filter) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let stream = runtime::block_on(vector_query.execute_with_options(self.read_options()))?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+
+        let mut output = scratch::results(k);
+        for batch in &batches {
+            let (labels, distances) = Self::label_distance_columns(batch)?;
+            output.extend(labels.values().iter().copied().zip(distances.values().iter().copied()));
+        }
+        Ok(output)
+    }
+
+    /// The k-nearest-neighbor graph over `sample` source rows (every row when 0), as
+    /// (src_label, dst_label, distance) edges found through the vector index. Sources
+    /// are sampled by label residue so they spread over the table; neighbors are drawn
+    /// from all rows, and a row is never its own neighbor.
+    pub fn knn_graph(&self, k: usize, sample: usize, nprobes: usize, refine_factor: usize) -> Result<RecordBatch> {
+        if k == 0 {
+            return Err(anyhow!("knn_graph needs k of at least 1"));
+        }
+        let table = self.get_table()?;
+        let mut sources = table.query().select(Select::columns(&["label", "vector"]));
+        let mut residue = None;
+        if sample > 0 {
+            let rows = self.count()?.max(1);
+            let window = (sample as u64 * SAMPLE_BUCKETS).div_ceil(rows);
+            if window < SAMPLE_BUCKETS {
+                residue = Some(format!("label % {} < {}", SAMPLE_BUCKETS, window));
+            }
+            sources = sources.limit(sample);
+        }
+        if let Some(filter) = self.live_filter(residue.as_deref()) {
+            sources = sources.only_if(filter);
+        }
+
+        let (mut src, mut dst, mut distances) = (Vec::new(), Vec::new(), Vec::new());
+        let mut stream = runtime::block_on(sources.execute())?;
+        while let Some(batch) = runtime::block_on(stream.try_next())
+            .map_err(|e| anyhow!("stream error: {}", e))?
+        {
+            let labels = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("missing label column"))?;
+            let vectors = Self::vector_column(&batch).ok_or_else(|| anyhow!("missing vector column"))?;
+            let vectors = self.widen_vectors(vectors)?;
+            for i in 0..batch.num_rows() {
+                let values = vectors.value(i);
+                let values = values
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                // Stored vectors are already in the indexed space
+                let neighbors = self.ann_search(values.values(), k + 1, nprobes, refine_factor, 0, None)?;
+                let label = labels.value(i);
+                for (neighbor, distance) in neighbors.into_iter().filter(|(n, _)| *n != label).take(k) {
+                    src.push(label);
+                    dst.push(neighbor);
+                    distances.push(distance);
+                }
+            }
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("src_label", DataType::Int64, false),
+            Field::new("dst_label", DataType::Int64, false),
+            Field::new("distance", DataType::Float32, false),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(src)),
+            Arc::new(Int64Array::from(dst)),
+            Arc::new(Float32Array::from(distances)),
+        ])?)
+    }
+
+    /// Open a cursor over the k nearest neighbors, delivered in caller-sized chunks.
+    ///
+    /// The cursor holds a search admission permit until it is dropped.
+    pub fn search_cursor(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+    ) -> Result<SearchCursor> {
+        let query = self.prepare_query(query)?;
+        let mut vector_query = self.vector_query(&query, k, nprobes, refine_factor, 0)?;
+        if let Some(filter) = self.live_filter(None) {
+            vector_query = vector_query.only_if(filter);
+        }
+        let permit = self.admission.acquire(OpClass::Search)?;
+        let stream = runtime::block_on(vector_query.execute())?;
+        Ok(SearchCursor::new(stream, permit))
+    }
+
+    /// Build a k-NN query against the vector column, validating the query dimension.
+    fn vector_query(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+    ) -> Result<VectorQuery> {
+        if query.len() != self.dimension {
+            return Err(anyhow!(
+                "expected query dimension {}, got {}",
+                self.dimension,
+                query.len()
+            ));
+        }
+        if self.vector_storage() == VectorStorage::Int8 {
+            return Err(anyhow!("{} stores int8 vectors, which only plain search supports", self.table_name));
+        }
+
+        self.check_index_metric()?;
+
+        let table = self.get_table()?;
+        // Results carry only label and _distance, so no other column leaves Lance
+        let vector_query = table
+            .vector_search(query)
+            .map_err(|e| anyhow!("search setup: {}", e))?
+            .column("vector")
+            .select(Select::columns(&["label"]))
+            .limit(k)
+            .nprobes(nprobes);
+        // refine_factor 0 keeps the index's quantized distances
+        let vector_query = match ef {
+            0 => vector_query,
+            ef if ef < k => return Err(anyhow!("ef {} is below k {}; HNSW must explore at least k candidates", ef, k)),
+            ef => vector_query.ef(ef),
+        };
+        let refine_factor = self.refine_plan(refine_factor, k).factor;
+        Ok(if refine_factor > 0 {
+            vector_query.refine_factor(refine_factor as u32)
+        } else {
+            vector_query
+        })
+    }
+
+    /// Borrow the label and `_distance` columns of a search result batch.
+    pub(crate) fn label_distance_columns(batch: &RecordBatch) -> Result<(&Int64Array, &Float32Array)> {
+        let labels = batch
+            .column_by_name("label")
+            .ok_or_else(|| anyhow!("missing label column"))?
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| anyhow!("label column not Int64"))?;
+        let distances = batch
+            .column_by_name("_distance")
+            .ok_or_else(|| anyhow!("missing _distance column"))?
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .ok_or_else(|| anyhow!("distance column not Float32"))?;
+        Ok((labels, distances))
+    }
+
+    /// Delete a vector by label.
+    pub fn delete(&self, label: i64) -> Result<()> {
+        self.require_writer()?;
+        let table = self.get_table()?;
+        self.cover_labels(&table)?;
+        let predicate = format!("label = {}", label);
+        let predicate = self.scoped(Some(&predicate)).unwrap_or(predicate);
+        let deleted = runtime::block_on(table.delete(&predicate));
+        self.note_failure(deleted.map_err(anyhow::Error::from))?;
+        self.delete_cold(&predicate)?;
+        self.committed();
+        self.invalidate_vector_stats();
+        self.forget_access(&[label]);
+        Ok(())
+    }
+
+    /// Delete multiple vectors by label in a single operation.
+    pub fn delete_batch(&self, labels: &[i64]) -> Result<()> {
+        if labels.is_empty() {
+            return Ok(());
+        }
+        self.require_writer()?;
+        let table = self.get_table()?;
+
+        self.cover_labels(&table)?;
+        let csv: String = labels.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
+        let predicate = format!("label IN ({})", csv);
+        let predicate = self.scoped(Some(&predicate)).unwrap_or(predicate);
+        let deleted = runtime::block_on(table.delete(&predicate));
+        self.note_failure(deleted.map_err(anyhow::Error::from))?;
+        self.delete_cold(&predicate)?;
+        self.committed();
+        self.invalidate_vector_stats();
+        self.forget_access(labels);
+        Ok(())
+    }
+
+    /// Delete the cold rows matching `predicate`, after their table rows.
+    fn delete_cold(&self, predicate: &str) -> Result<()> {
+        if !self.cold_rows.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        if let Some(sidecar) = self.cold_table(false)? {
+            let deleted = runtime::block_on(sidecar.delete(predicate));
+            self.note_failure(deleted.map_err(anyhow::Error::from))?;
+        }
+        Ok(())
+    }
+
+    /// Delete, in one table version, every in-scope row whose key is not among the
+    /// keys in the imported Arrow array: a struct with one integer or string column
+    /// naming the key column (`label` or a stored column). Stored rows are read in
+    /// batches of the label and key columns with their row addresses; rows with a
+    /// NULL key are deleted. The doomed rows are written into their fragments'
+    /// deletion files, so the commit carries no predicate however many rows it
+    /// deletes. An empty key set deletes every row and is refused unless
+    /// `allow_empty`. Returns the number of rows deleted.
+    ///
+    /// # Safety
+    /// As for [`LanceIndex::add_batch_arrow`].
+    pub unsafe fn retain_only(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+        allow_empty: bool,
+    ) -> Result<usize> {
+        self.require_writer()?;
+        let ffi_array = std::mem::replace(&mut *ffi_array_ptr, FFI_ArrowArray::empty());
+        let array_data = arrow::ffi::from_ffi(ffi_array, &*ffi_schema_ptr)
+            .map_err(|e| anyhow!("Arrow FFI import failed: {}", e))?;
+        let (fields, arrays, _) = StructArray::from(array_data).into_parts();
+        let (key, keys) = match (fields.first(), arrays.first()) {
+            (Some(field), Some(keys)) if fields.len() == 1 => (field.name().clone(), keys.clone()),
+            _ => return Err(anyhow!("retain_only takes exactly one key column, got {}", fields.len())),
+        };
+        let stored_type = match self.schema.field_with_name(&key) {
+            Ok(field) => field.data_type().clone(),
+            Err(_) => return Err(anyhow!("key column '{}' not found", key)),
+        };
+        if keys.is_empty() && !allow_empty {
+            return Err(anyhow!("retain_only with no keys would delete every row; allow an empty key set to do so"));
+        }
+        // Keys compare as literals of the stored type
+        let options = arrow::compute::CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        let keys = arrow::compute::cast_with_options(&keys, &stored_type, &options)
+            .map_err(|e| anyhow!("keys do not fit key column '{}': {}", key, e))?;
+        let retained: HashSet<String> = Self::key_literals(&key, &keys)?.into_iter().collect();
+
+        let table = self.get_table()?;
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let version = runtime::block_on(table.version())?;
+        let dataset = Arc::new(runtime::block_on(Self::load_dataset(&uri, &params, Some(version)))?);
+        let columns = if key == "label" { vec!["label"] } else { vec!["label", key.as_str()] };
+        let mut scan = dataset.scan();
+        scan.project(&columns)?.with_row_address();
+        if let Some(scope) = self.scoped(None) {
+            scan.filter(&scope)?;
+        }
+        let mut doomed = RoaringTreemap::new();
+        let mut deletes: Vec<i64> = Vec::new();
+        runtime::block_on(async {
+            let mut stream = scan.try_into_stream().await?;
+            while let Some(batch) = stream.try_next().await.map_err(|e| anyhow!("stream error: {}", e))? {
+                let labels = batch
+                    .column_by_name("label")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("label column is not Int64"))?;
+                let addresses = batch
+                    .column_by_name("_rowaddr")
+                    .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+                    .ok_or_else(|| anyhow!("missing _rowaddr column"))?;
+                let stored = batch.column_by_name(&key).ok_or_else(|| anyhow!("missing key column '{}'", key))?;
+                // NULL keys are never retained; the rest are compared as literals
+                let present = arrow::compute::filter(stored, &arrow::compute::is_not_null(stored)?)?;
+                let mut present_keys = Self::key_literals(&key, &present)?.into_iter();
+                for (i, label) in labels.values().iter().enumerate() {
+                    let kept = stored.is_valid(i) && present_keys.next().is_some_and(|k| retained.contains(&k));
+                    if !kept {
+                        deletes.push(*label);
+                        doomed.insert(addresses.value(i));
+                    }
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+        if deletes.is_empty() {
+            return Ok(0);
+        }
+        self.cover_labels(&table)?;
+
+        let deleted = runtime::block_on(Self::commit_deletions(dataset, &uri, &params, &doomed));
+        self.note_failure(deleted)?;
+        runtime::block_on(table.checkout_latest())?;
+        self.committed();
+        self.invalidate_vector_stats();
+        self.forget_access(&deletes);
+        Ok(deletes.len())
+    }
+
+    /// Add the rows at the addresses in `doomed` to the deletion files of their
+    /// fragments of `dataset`, dropping fragments left empty, and commit that as one
+    /// delete based on the dataset's version.
+    async fn commit_deletions(
+        dataset: Arc<Dataset>,
+        uri: &str,
+        params: &ObjectStoreParams,
+        doomed: &RoaringTreemap,
+    ) -> Result<()> {
+        use lance::dataset::transaction::{Operation, Transaction};
+        use lance::dataset::CommitBuilder;
+        use lance::io::{ObjectStore, ObjectStoreRegistry};
+        use lance_table::io::deletion::{read_deletion_file, write_deletion_file};
+
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let (store, base) = ObjectStore::from_uri_and_params(registry, uri, params).await?;
+        let version = dataset.manifest().version;
+        let mut updated_fragments = Vec::new();
+        let mut deleted_fragment_ids = Vec::new();
+        // Row addresses are (fragment id << 32) | offset
+        for (fragment_id, offsets) in doomed.bitmaps() {
+            let fragment = dataset
+                .get_fragment(fragment_id as usize)
+                .ok_or_else(|| anyhow!("fragment {} not found at version {}", fragment_id, version))?;
+            let mut metadata = fragment.metadata().clone();
+            let mut deletions = read_deletion_file(&base, &metadata, &store).await?.unwrap_or_default();
+            deletions.extend(offsets.iter());
+            if deletions.len() >= fragment.physical_rows().await? {
+                deleted_fragment_ids.push(metadata.id);
+                continue;
+            }
+            metadata.deletion_file = write_deletion_file(&base, metadata.id, version, &deletions, &store).await?;
+            updated_fragments.push(metadata);
+        }
+        let operation = Operation::Delete {
+            updated_fragments,
+            deleted_fragment_ids,
+            predicate: "retain_only".to_string(),
+        };
+        CommitBuilder::new(dataset)
+            .execute(Transaction::new(version, operation, None, None))
+            .await
+            .map_err(|e| anyhow!("retain_only at version {} not committed: {}", version, e))?;
+        Ok(())
+    }
+
+    /// Delete the rows labeled `deletes` and overwrite columns of the rows labeled in
+    /// `updates`, in one table version. `updates` holds a non-null Int64 `label`
+    /// column plus the columns to set, matched by name and cast to the stored types;
+    /// other columns keep their values. Updated rows are checked like appends (vector
+    /// validity and the table's constraints) and any failure rejects the whole
+    /// change. Every label must name an in-scope row, and updates may not set the
+    /// columns a scoped handle's scope names. With `expected_version`, the change is
+    /// rejected if the table moved past the version the caller planned against (see
+    /// [`LanceIndex::scan_with_labels`]). The change is committed as a transaction
+    /// based on that version, so a write that lands between the check and the commit
+    /// fails it if it deleted or updated rows in the fragments the change touches;
+    /// appends and changes to other fragments in that window are kept.
+    pub fn apply_changes(
+        &self,
+        deletes: &[i64],
+        updates: Option<&RecordBatch>,
+        expected_version: Option<u64>,
+    ) -> Result<AppliedChanges> {
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let version = runtime::block_on(table.version())?;
+        if let Some(expected) = expected_version.filter(|expected| *expected != version) {
+            return Err(anyhow!("table changed since version {} (now at version {})", expected, version));
+        }
+        let updates = updates.filter(|u| u.num_rows() > 0);
+        if deletes.is_empty() && updates.is_none() {
+            return Ok(AppliedChanges { deleted: 0, updated: 0, version });
+        }
+
+        let mut deletes = deletes.to_vec();
+        deletes.sort_unstable();
+        deletes.dedup();
+        let rows = match updates {
+            Some(updates) => Some(self.updated_rows(&table, updates, &deletes)?),
+            None => None,
+        };
+        let present = self.count_labels(&table, &deletes)?;
+        if present != deletes.len() {
+            return Err(anyhow!("{} of {} labels to delete not found", deletes.len() - present, deletes.len()));
+        }
+
+        let delete_predicate = (!deletes.is_empty()).then(|| {
+            let label_list: Vec<String> = deletes.iter().map(i64::to_string).collect();
+            let predicate = format!("label IN ({})", label_list.join(", "));
+            self.scoped(Some(&predicate)).unwrap_or(predicate)
+        });
+        if !deletes.is_empty() {
+            // A metadata-only commit, which the change based on `version` rebases over
+            self.cover_labels(&table)?;
+        }
+        let updated = rows.as_ref().map_or(0, RecordBatch::num_rows);
+        let result = self.commit_changes(&table, version, rows, delete_predicate);
+        self.note_failure(result)?;
+        self.committed();
+        self.invalidate_vector_stats();
+        self.forget_access(&deletes);
+        Ok(AppliedChanges {
+            deleted: deletes.len(),
+            updated,
+            version: runtime::block_on(table.version())?,
+        })
+    }
+
+    /// Merge `rows` over the stored rows with their labels and delete the rows
+    /// matching `delete_predicate`, in one transaction based on `version`. Lance
+    /// refuses the commit if a change since `version` deleted or rewrote rows in the
+    /// same fragments; appends and changes to other fragments are kept.
+    fn commit_changes(
+        &self,
+        table: &LanceTable,
+        version: u64,
+        rows: Option<RecordBatch>,
+        delete_predicate: Option<String>,
+    ) -> Result<()> {
+        use lance::dataset::{CommitBuilder, MergeInsertBuilder, WhenMatched, WhenNotMatched, WhenNotMatchedBySource};
+
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let dataset = Arc::new(runtime::block_on(Self::load_dataset(&uri, &params, Some(version)))?);
+        let not_committed = |e: lance::Error| anyhow!("change at version {} not committed: {}", version, e);
+        runtime::block_on(async {
+            match (rows, delete_predicate) {
+                (Some(rows), delete_predicate) => {
+                    let expected = rows.num_rows() as u64;
+                    let schema = rows.schema();
+                    let mut merge = MergeInsertBuilder::try_new(dataset.clone(), vec!["label".to_string()])?;
+                    merge.when_matched(WhenMatched::UpdateAll).when_not_matched(WhenNotMatched::DoNothing);
+                    // Deletes ride along as "not in the source": one version for both
+                    if let Some(predicate) = delete_predicate {
+                        merge.when_not_matched_by_source(WhenNotMatchedBySource::delete_if(&dataset, &predicate)?);
+                    }
+                    let reader = RecordBatchIterator::new(vec![Ok(rows)], schema);
+                    let (transaction, stats) = merge.try_build()?.execute_uncommitted(reader).await?;
+                    // Rows read after `version` may not have existed yet at it
+                    if stats.num_updated_rows != expected {
+                        return Err(anyhow!(
+                            "{} of {} labels to update not found at version {}",
+                            expected - stats.num_updated_rows.min(expected),
+                            expected,
+                            version
+                        ));
+                    }
+                    CommitBuilder::new(dataset).execute(transaction).await.map_err(not_committed)?;
+                }
+                (None, Some(predicate)) => {
+                    let mut dataset = (*dataset).clone();
+                    dataset.delete(&predicate).await.map_err(not_committed)?;
+                }
+                (None, None) => unreachable!("nothing to apply"),
+            }
+            table.checkout_latest().await?;
+            Ok(())
+        })
+    }
+
+    /// The stored rows labeled in `updates` with its columns written over them,
+    /// ready to merge back. None of the labels may be among `deletes`.
+    fn updated_rows(&self, table: &LanceTable, updates: &RecordBatch, deletes: &[i64]) -> Result<RecordBatch> {
+        let labels = updates
+            .column_by_name("label")
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+            .ok_or_else(|| anyhow!("updates need an Int64 label column"))?;
+        if labels.null_count() > 0 {
+            return Err(anyhow!("updates have NULL labels"));
+        }
+        let mut update_rows: HashMap<i64, u32> = HashMap::with_capacity(labels.len());
+        for (row, label) in labels.values().iter().enumerate() {
+            if update_rows.insert(*label, row as u32).is_some() {
+                return Err(anyhow!("label {} is updated twice", label));
+            }
+            if deletes.binary_search(label).is_ok() {
+                return Err(anyhow!("label {} is both updated and deleted", label));
+            }
+        }
+        let scope_columns = self.scope.as_deref().map_or_else(Vec::new, |s| referenced_columns(s, &self.schema));
+        for field in updates.schema().fields() {
+            if field.name() != "label" && self.schema.field_with_name(field.name()).is_err() {
+                return Err(anyhow!("column '{}' not found", field.name()));
+            }
+            if scope_columns.contains(&field.name().as_str()) {
+                return Err(anyhow!("column '{}' is in the handle's scope and cannot be updated", field.name()));
+            }
+        }
+
+        let mut sorted: Vec<i64> = labels.values().to_vec();
+        sorted.sort_unstable();
+        let columns: Vec<&str> = self.schema.fields().iter().map(|f| f.name().as_str()).collect();
+        let mut batches = Vec::new();
+        for chunk in sorted.chunks(SEARCH_WITHIN_CHUNK) {
+            let label_list: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let predicate = format!("label IN ({})", label_list.join(", "));
+            let mut query = table.query().select(Select::columns(&columns));
+            if let Some(filter) = self.scoped(Some(&predicate)) {
+                query = query.only_if(filter);
+            }
+            let stream = runtime::block_on(query.execute())?;
+            batches.extend(
+                runtime::block_on(stream.try_collect::<Vec<RecordBatch>>())
+                    .map_err(|e| anyhow!("stream error: {}", e))?,
+            );
+        }
+        let stored = concat_batches(&self.schema, &batches)?;
+        if stored.num_rows() != labels.len() {
+            return Err(anyhow!("{} of {} labels to update not found", labels.len() - stored.num_rows(), labels.len()));
+        }
+        let stored = match self.rotation() {
+            Some(rotation) => Self::map_vectors(stored, |v| rotation.map_array(v, Rotation::invert))?,
+            None => stored,
+        };
+
+        // Update rows in the order the stored rows came back
+        let stored_labels = stored
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| anyhow!("label column is not Int64"))?;
+        let take = UInt32Array::from_iter_values(stored_labels.values().iter().map(|label| update_rows[label]));
+        let mut columns = stored.columns().to_vec();
+        let mut invalid = Vec::new();
+        for (i, field) in self.schema.fields().iter().enumerate().skip(1) {
+            if let Some(column) = updates.column_by_name(field.name()) {
+                let column = arrow::compute::take(column.as_ref(), &take, None)?;
+                columns[i] = match (field.name().as_str(), self.vector_storage()) {
+                    ("vector", VectorStorage::Int8) => {
+                        // Checked before quantizing, which maps NaN to a code
+                        let vectors = self.float_vectors(&column)?;
+                        invalid.extend(rejects::check_vectors(&vectors));
+                        self.quantize_checked(&vectors, &mut invalid)?
+                    }
+                    _ => arrow::compute::cast(&column, field.data_type())
+                        .map_err(|e| anyhow!("column '{}': {}", field.name(), e))?,
+                };
+                if multivector::dimension(field.data_type()).is_some() {
+                    invalid.extend(multivector::check_rows(field.name(), &columns[i]));
+                }
+            }
+        }
+        let rows = RecordBatch::try_new(self.schema.clone(), columns)
+            .map_err(|e| anyhow!("RecordBatch schema mismatch: {}", e))?;
+
+        let vectors = rows.column_by_name("vector").and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>());
+        if let Some(vectors) = vectors {
+            invalid.extend(rejects::check_vectors(vectors));
+        }
+        if let Some(constraints) = self.constraints() {
+            invalid.extend(constraints.check(&rows)?);
+        }
+        if let Some(first) = invalid.iter().min_by_key(|v| v.row) {
+            return Err(anyhow!("update of label {} is invalid: {}", stored_labels.value(first.row), first.reason));
+        }
+        self.with_rotation(rows)
+    }
+
+    /// How many of `labels` name in-scope rows.
+    fn count_labels(&self, table: &LanceTable, labels: &[i64]) -> Result<usize> {
+        let mut count = 0;
+        for chunk in labels.chunks(SEARCH_WITHIN_CHUNK) {
+            let label_list: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let predicate = format!("label IN ({})", label_list.join(", "));
+            count += runtime::block_on(table.count_rows(self.scoped(Some(&predicate))))?;
+        }
+        Ok(count)
+    }
+
+    /// Rewrite columns of the in-scope rows matching `predicate` in one table
+    /// version, leaving vectors untouched: each `(column, expr)` of `assignments`
+    /// sets `column` to the SQL expression `expr`, evaluated against the row's stored
+    /// values (e.g. `("status", "'archived'")`). The label and vector columns cannot
+    /// be assigned, and neither can the columns a scoped handle's scope names, which
+    /// would move rows out of it. Unless `privileged`, the predicate and expressions
+    /// may not read sensitive columns. The new values must satisfy the table's
+    /// constraints; they are evaluated and checked before anything is written, and a
+    /// violation fails the whole update. Returns the number of rows updated.
+    pub fn update_where(&self, predicate: &str, assignments: &[(&str, &str)], privileged: bool) -> Result<u64> {
+        if predicate.trim().is_empty() {
+            return Err(anyhow!("update needs a predicate"));
+        }
+        if assignments.is_empty() {
+            return Err(anyhow!("update needs at least one assignment"));
+        }
+        let mut assigned = HashSet::new();
+        for (column, _) in assignments {
+            let field = self
+                .schema
+                .field_with_name(column)
+                .map_err(|_| anyhow!("column '{}' not found", column))?;
+            if *column == "label" || matches!(field.data_type(), DataType::FixedSizeList(..)) {
+                return Err(anyhow!("column '{}' cannot be updated", column));
+            }
+            if !assigned.insert(*column) {
+                return Err(anyhow!("column '{}' is assigned twice", column));
+            }
+        }
+        if let Some(scope) = &self.scope {
+            if let Some(column) = referenced_columns(scope, &self.schema).into_iter().find(|c| assigned.contains(c)) {
+                return Err(anyhow!("column '{}' is in the handle's scope '{}' and cannot be updated", column, scope));
+            }
+        }
+        let mut read = referenced_columns(predicate, &self.schema);
+        for (_, expr) in assignments {
+            read.extend(referenced_columns(expr, &self.schema));
+        }
+        self.check_exportable(&read, privileged)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let filter = self.scoped(Some(predicate)).unwrap_or_default();
+        if let Some(constraints) = self.constraints() {
+            // Evaluate the constrained columns' new values the way the update will
+            let mut preview: Vec<(&str, &str)> = Vec::new();
+            for rule in &constraints.rules {
+                let column = rule.column.as_str();
+                if preview.iter().all(|(c, _)| *c != column) {
+                    let expr = assignments.iter().find(|(c, _)| *c == column).map_or(column, |(_, e)| *e);
+                    preview.push((column, expr));
+                }
+            }
+            let query = table.query().only_if(filter.clone()).select(Select::dynamic(&preview));
+            let mut stream = runtime::block_on(query.execute())?;
+            while let Some(batch) =
+                runtime::block_on(stream.try_next()).map_err(|e| anyhow!("stream error: {}", e))?
+            {
+                if let Some(violation) = constraints.check(&batch)?.first() {
+                    return Err(anyhow!("update rejected: {}", violation.reason));
+                }
+            }
+        }
+        let mut update = table.update().only_if(filter);
+        for (column, expr) in assignments {
+            update = update.column(*column, *expr);
+        }
+        let updated = self.note_failure(runtime::block_on(update.execute()).map_err(anyhow::Error::from))?;
+        if updated > 0 {
+            self.committed();
+        }
+        Ok(updated)
+    }
+
+    /// Insert or replace rows in one table version. Rows are matched on `key`: with
+    /// `None` (or `"label"`) the batch carries a non-null Int64 `label` column and
+    /// rows with a stored label replace it, while the others are inserted under
+    /// their given labels; with a column name, rows whose key is stored replace
+    /// that row and keep its label, and the others get new labels. Replaced rows
+    /// take the batch's values for every column, so labels and fragments stay put
+    /// where delete and re-add would churn them. Rows are validated like appends.
+    /// A key column must be an integer or string column.
+    ///
+    /// `options` picks what happens to rows whose key is stored and to keys the batch
+    /// repeats (see [`crate::upsert`]); by default stored rows are replaced and
+    /// repeated keys fail the upsert.
+    ///
+    /// # Safety
+    /// As for [`LanceIndex::add_batch_arrow`].
+    pub unsafe fn upsert_batch_arrow(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+        key: Option<&str>,
+        options: &UpsertOptions,
+    ) -> Result<UpsertReport> {
+        self.require_unscoped("upsert")?;
+        self.require_writer()?;
+        let key = key.filter(|k| *k != "label");
+        let ffi_array = std::mem::replace(&mut *ffi_array_ptr, FFI_ArrowArray::empty());
+        let array_data = arrow::ffi::from_ffi(ffi_array, &*ffi_schema_ptr)
+            .map_err(|e| anyhow!("Arrow FFI import failed: {}", e))?;
+        let (fields, mut arrays, _) = StructArray::from(array_data).into_parts();
+        let num_rows = arrays.first().map_or(0, |a| a.len());
+        if num_rows == 0 {
+            return Ok(UpsertReport::default());
+        }
+
+        // Given labels are split off; the other columns are converted like an append
+        let label_index = fields.iter().position(|f| f.name() == "label");
+        let given_labels = match (label_index, key) {
+            (Some(_), Some(key)) => return Err(anyhow!("labels are assigned when upserting on key '{}'", key)),
+            (None, None) => return Err(anyhow!("upserting on label needs a label column")),
+            (Some(i), None) => {
+                let labels = arrow::compute::cast(&arrays.remove(i), &DataType::Int64)?;
+                let labels = labels
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("labels are not Int64"))?;
+                if labels.null_count() > 0 {
+                    return Err(anyhow!("upsert has NULL labels"));
+                }
+                if let Some(label) = labels.values().iter().find(|l| **l < 0) {
+                    return Err(anyhow!("label {} is negative", label));
+                }
+                Some(labels)
+            }
+            (None, Some(_)) => None,
+        };
+        let fields: Fields = fields.iter().filter(|f| f.name() != "label").cloned().collect();
+        let plan = self.cast_plans.plan(&fields, self.column_matching())?;
+        let values = plan.apply(&arrays)?;
+        let mut invalid = plan.failed_rows(&arrays, &values);
+        let vector_column = self.schema.index_of("vector")? - 1;
+        if let Some(vectors) = values[vector_column].as_any().downcast_ref::<FixedSizeListArray>() {
+            invalid.extend(rejects::check_vectors(vectors));
+        }
+        invalid.extend(self.check_multivectors(&values));
+        let values = self.quantize_incoming(values, &mut invalid)?;
+        let (values, rejected) = self.reject_rows(values, invalid, false)?;
+        let accepted = num_rows - rejected.len();
+        if accepted == 0 {
+            return Ok(UpsertReport {
+                labels: vec![-1; num_rows],
+                ..Default::default()
+            });
+        }
+
+        let table = self.get_table()?;
+        // Each accepted row's key: its given label, or its key column value as a literal
+        let (given, keys) = match (given_labels, key) {
+            (Some(given), _) => {
+                let mut keep = vec![true; num_rows];
+                for row in &rejected {
+                    keep[*row] = false;
+                }
+                let given = arrow::compute::filter(&given, &BooleanArray::from(keep))?;
+                let given = given.as_any().downcast_ref::<Int64Array>();
+                let labels = given.map(|l| l.values().to_vec()).unwrap_or_default();
+                let keys: Vec<String> = labels.iter().map(i64::to_string).collect();
+                (Some(labels), keys)
+            }
+            (None, Some(key)) => {
+                let key_index = self
+                    .schema
+                    .index_of(key)
+                    .map_err(|_| anyhow!("key column '{}' not found", key))?;
+                (None, Self::key_literals(key, &values[key_index - 1])?)
+            }
+            (None, None) => unreachable!("label upserts carry labels"),
+        };
+        let kind = if given.is_some() { "label" } else { "key" };
+        let newest = match options.on_conflict.newest_column() {
+            Some(column) => match self.schema.index_of(column) {
+                Ok(index) if index > 0 => Some((column, index - 1)),
+                _ => return Err(anyhow!("keep_newest column '{}' not found", column)),
+            },
+            None => None,
+        };
+
+        // A repeated key is refused, or collapsed to the one row that stands for it
+        let winners = upsert::winners(&keys, newest.map(|(_, index)| values[index].as_ref()))?;
+        if !options.collapse_duplicates {
+            if let Some(row) = (0..keys.len()).find(|row| winners[*row] != *row) {
+                return Err(anyhow!("{} {} appears twice in the upsert", kind, keys[row]));
+            }
+        }
+        let unique: Vec<usize> = (0..keys.len()).filter(|row| winners[*row] == *row).collect();
+
+        // The stored label of each unique row's key, if stored
+        let stored: Vec<Option<i64>> = match (&given, key) {
+            (Some(given), _) => {
+                let labels: Vec<i64> = unique.iter().map(|row| given[*row]).collect();
+                let mut found = HashSet::new();
+                self.scan_labels(&labels, "label", |labels, _| {
+                    found.extend(labels.values().iter().copied());
+                    Ok(())
+                })?;
+                labels.iter().map(|label| found.contains(label).then_some(*label)).collect()
+            }
+            (None, Some(key)) => {
+                let unique_keys: Vec<String> = unique.iter().map(|row| keys[*row].clone()).collect();
+                let existing = self.labels_by_key(&table, key, &unique_keys)?;
+                unique_keys.iter().map(|k| existing.get(k.as_str()).copied()).collect()
+            }
+            (None, None) => unreachable!("label upserts carry labels"),
+        };
+        if options.on_conflict == OnConflict::Error {
+            if let Some(i) = stored.iter().position(Option::is_some) {
+                return Err(anyhow!("{} {} is already stored", kind, keys[unique[i]]));
+            }
+        }
+        let labels: Vec<i64> = match &given {
+            Some(given) => {
+                if let Some(max) = given.iter().max() {
+                    self.next_label.fetch_max(max + 1, Ordering::SeqCst);
+                }
+                unique.iter().map(|row| given[*row]).collect()
+            }
+            None => {
+                let new_rows = stored.iter().filter(|label| label.is_none()).count() as i64;
+                let mut next = self.next_label.fetch_add(new_rows, Ordering::SeqCst);
+                stored
+                    .iter()
+                    .map(|label| {
+                        label.unwrap_or_else(|| {
+                            next += 1;
+                            next - 1
+                        })
+                    })
+                    .collect()
+            }
+        };
+
+        // Stored rows the policy keeps are not written over
+        let mut write: Vec<bool> = match options.on_conflict {
+            OnConflict::KeepExisting => stored.iter().map(Option::is_none).collect(),
+            _ => vec![true; unique.len()],
+        };
+        if let Some((column, index)) = newest {
+            let position: HashMap<i64, usize> =
+                stored.iter().enumerate().filter_map(|(i, label)| label.map(|label| (label, i))).collect();
+            let matched: Vec<i64> = position.keys().copied().collect();
+            self.scan_labels(&matched, column, |found, stored_values| {
+                let newer = upsert::comparator(values[index].as_ref(), stored_values.as_ref())?;
+                for (stored_row, label) in found.values().iter().enumerate() {
+                    if let Some(&i) = position.get(label) {
+                        write[i] = newer(unique[i], stored_row).is_ge();
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        let written: Vec<usize> = (0..unique.len()).filter(|i| write[*i]).collect();
+        let updated = written.iter().filter(|i| stored[**i].is_some()).count();
+        let inserted = written.len() - updated;
+
+        // Every accepted row reports the label of the row standing for its key
+        let mut label_of = vec![-1; keys.len()];
+        for (i, row) in unique.iter().enumerate() {
+            label_of[*row] = labels[i];
+        }
+        let mut report = UpsertReport {
+            labels: winners.iter().map(|row| label_of[*row]).collect(),
+            inserted,
+            updated,
+            skipped: unique.len() - written.len(),
+            collapsed: keys.len() - unique.len(),
+        };
+        for row in rejected {
+            report.labels.insert(row, -1);
+        }
+        if written.is_empty() {
+            return Ok(report);
+        }
+
+        let values = if written.len() == keys.len() {
+            values
+        } else {
+            let rows = UInt32Array::from(written.iter().map(|i| unique[*i] as u32).collect::<Vec<_>>());
+            values
+                .iter()
+                .map(|column| arrow::compute::take(column.as_ref(), &rows, None))
+                .collect::<std::result::Result<Vec<_>, _>>()?
+        };
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(1 + values.len());
+        columns.push(Arc::new(Int64Array::from(written.iter().map(|i| labels[*i]).collect::<Vec<_>>())));
+        columns.extend(values);
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)
+            .map_err(|e| anyhow!("RecordBatch schema mismatch: {}", e))?;
+        let batch = self.with_projection(self.with_rotation(batch)?)?;
+        let mut usage = self.append_lock.lock().map_err(|_| anyhow!("append lock poisoned"))?;
+        let mut added = None;
+        if let Some(quota) = self.quota().filter(|q| q.policy == QuotaPolicy::Reject && inserted > 0) {
+            let new_rows: BooleanArray = written.iter().map(|i| Some(stored[*i].is_none())).collect();
+            let new_rows = arrow::compute::filter_record_batch(&batch, &new_rows)?;
+            self.check_quota(&quota, &mut usage, &table, &new_rows)?;
+            added = Some(new_rows);
+        }
+
+        let schema = batch.schema();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        let mut merge = table.merge_insert(&["label"]);
+        merge.when_matched_update_all(None).when_not_matched_insert_all();
+        self.note_failure(runtime::block_on(merge.execute(Box::new(reader))).map_err(anyhow::Error::from))?;
+        self.committed();
+        if let Some(added) = &added {
+            Self::advance_quota_usage(&mut usage, &table, added);
+        }
+        drop(usage);
+        self.invalidate_vector_stats();
+        Ok(report)
+    }
+
+    /// `keys` as SQL literals, one per row. Integer and string columns only; NULL
+    /// keys are refused.
+    fn key_literals(key: &str, keys: &ArrayRef) -> Result<Vec<String>> {
+        let quoted = match keys.data_type() {
+            t if t.is_integer() => false,
+            DataType::Utf8 | DataType::LargeUtf8 => true,
+            other => return Err(anyhow!("key column '{}' is {}, expected an integer or string", key, other)),
+        };
+        if keys.null_count() > 0 {
+            return Err(anyhow!("key column '{}' has NULLs", key));
+        }
+        let text = arrow::compute::cast(keys, &DataType::Utf8)?;
+        let text = text.as_any().downcast_ref::<StringArray>().ok_or_else(|| anyhow!("key cast failed"))?;
+        Ok(text
+            .iter()
+            .flatten()
+            .map(|v| if quoted { format!("'{}'", v.replace('\'', "''")) } else { v.to_string() })
+            .collect())
+    }
+
+    /// Labels of the stored rows whose `key` is among `literals` (from
+    /// [`LanceIndex::key_literals`]), by literal.
+    fn labels_by_key(&self, table: &LanceTable, key: &str, literals: &[String]) -> Result<HashMap<String, i64>> {
+        let mut labels = HashMap::new();
+        for chunk in literals.chunks(SEARCH_WITHIN_CHUNK) {
+            let stream = runtime::block_on(
+                table
+                    .query()
+                    .select(Select::columns(&["label", key]))
+                    .only_if(format!("{} IN ({})", key, chunk.join(", ")))
+                    .execute(),
+            )?;
+            let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            for batch in &batches {
+                let found = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .ok_or_else(|| anyhow!("label column is not Int64"))?;
+                let keys = Self::key_literals(key, batch.column(1))?;
+                labels.extend(keys.into_iter().zip(found.values().iter().copied()));
+            }
+        }
+        Ok(labels)
+    }
+
+    /// Relabel rows in one table version: the row of each `(old_label, new_label)`
+    /// pair of `mapping` gets `new_label`, keeping its other columns. Every old label
+    /// must name an in-scope row, and no new label may be used by a row outside the
+    /// mapping, so labels can be swapped. Access records of the remapped rows are
+    /// dropped. Returns the number of rows remapped.
+    pub fn remap_labels(&self, mapping: &[(i64, i64)]) -> Result<usize> {
+        if mapping.is_empty() {
+            return Ok(0);
+        }
+        self.require_writer()?;
+        let mut old_labels = HashSet::new();
+        let mut new_labels = HashSet::new();
+        for &(old_label, new_label) in mapping {
+            if new_label < 0 {
+                return Err(anyhow!("new label {} is negative", new_label));
+            }
+            if !old_labels.insert(old_label) {
+                return Err(anyhow!("label {} is remapped more than once", old_label));
+            }
+            if !new_labels.insert(new_label) {
+                return Err(anyhow!("new label {} is assigned more than once", new_label));
+            }
+        }
+
+        let olds: Vec<i64> = mapping.iter().map(|(old_label, _)| *old_label).collect();
+        let mut found = HashSet::new();
+        self.scan_labels(&olds, "label", |labels, _| {
+            found.extend(labels.values().iter().copied());
+            Ok(())
+        })?;
+        if let Some(missing) = olds.iter().find(|l| !found.contains(*l)) {
+            return Err(anyhow!("label {} not found", missing));
+        }
+
+        // New labels are checked against the whole table, not just the scope
+        let table = self.get_table()?;
+        let news: Vec<i64> = mapping.iter().map(|(_, new_label)| *new_label).collect();
+        for chunk in news.chunks(SEARCH_WITHIN_CHUNK) {
+            let label_list: Vec<String> = chunk.iter().map(i64::to_string).collect();
+            let stream = runtime::block_on(
+                table
+                    .query()
+                    .select(Select::columns(&["label"]))
+                    .only_if(format!("label IN ({})", label_list.join(", ")))
+                    .execute(),
+            )?;
+            let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+                .map_err(|e| anyhow!("stream error: {}", e))?;
+            for batch in &batches {
+                let labels = batch
+                    .column_by_name("label")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("label column not Int64"))?;
+                if let Some(taken) = labels.values().iter().find(|l| !old_labels.contains(*l)) {
+                    return Err(anyhow!("new label {} is already used", taken));
+                }
+            }
+        }
+
+        let cases: String = mapping
+            .iter()
+            .map(|(old_label, new_label)| format!(" WHEN {} THEN {}", old_label, new_label))
+            .collect();
+        let label_list: Vec<String> = olds.iter().map(i64::to_string).collect();
+        runtime::block_on(
+            table
+                .update()
+                .only_if(format!("label IN ({})", label_list.join(", ")))
+                .column("label", format!("CASE label{} END", cases))
+                .execute(),
+        )?;
+        self.committed();
+
+        // Keep appends from handing out a remapped label again. Lance rewrites updated
+        // rows into new fragments, which the label watermark reads.
+        let max_label = news.iter().copied().max().unwrap_or(-1);
+        self.next_label.fetch_max(max_label + 1, Ordering::SeqCst);
+        self.forget_access(&olds);
+        Ok(mapping.len())
+    }
+
+    /// [`LanceIndex::remap_labels`] with the mapping given via the Arrow C Data
+    /// Interface, as a struct of Int64 `old_label` and `new_label` columns.
+    ///
+    /// # Safety
+    /// As for [`LanceIndex::add_batch_arrow`]; takes ownership of the array.
+    pub unsafe fn remap_labels_arrow(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+    ) -> Result<usize> {
+        let ffi_array = std::mem::replace(&mut *ffi_array_ptr, FFI_ArrowArray::empty());
+        let array_data = arrow::ffi::from_ffi(ffi_array, &*ffi_schema_ptr)
+            .map_err(|e| anyhow!("Arrow FFI import failed: {}", e))?;
+        let struct_array = StructArray::from(array_data);
+        fn column<'a>(mapping: &'a StructArray, name: &str) -> Result<&'a Int64Array> {
+            let column = mapping
+                .column_by_name(name)
+                .ok_or_else(|| anyhow!("label mapping has no {} column", name))?;
+            if column.null_count() > 0 {
+                return Err(anyhow!("label mapping column {} contains NULL", name));
+            }
+            column
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .ok_or_else(|| anyhow!("label mapping column {} is not Int64", name))
+        }
+        let (old_labels, new_labels) = (column(&struct_array, "old_label")?, column(&struct_array, "new_label")?);
+        let mapping: Vec<(i64, i64)> =
+            old_labels.values().iter().copied().zip(new_labels.values().iter().copied()).collect();
+        self.remap_labels(&mapping)
+    }
+
+    /// [`LanceIndex::add_batch_arrow`], run once per idempotency `token` (see
+    /// [`crate::idempotency`]): a retry with a recorded token adds nothing and
+    /// returns the labels assigned by the first call.
+    ///
+    /// # Safety
+    /// As for [`LanceIndex::add_batch_arrow`].
+    pub unsafe fn add_batch_arrow_with_token(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+        token: Option<&str>,
+    ) -> Result<Vec<i64>> {
+        let rows = self.ingest_arrow(ffi_schema_ptr, ffi_array_ptr, token, false)?;
+        Ok(rows.into_iter().map(|(label, _)| label).collect())
+    }
+
+    /// [`LanceIndex::add_batch_arrow_with_token`] with partial-failure semantics:
+    /// rows that fail validation or a constraint are dropped and reported as
+    /// [`RowStatus::Invalid`] whatever the table's modes (`dead_letter` still records
+    /// them), so only whole-batch failures such as a quota return an error. Returns a
+    /// (label, status) pair per incoming row; the label is -1 for invalid rows.
+    ///
+    /// # Safety
+    /// As for [`LanceIndex::add_batch_arrow`].
+    pub unsafe fn add_batch_arrow_rows(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+        token: Option<&str>,
+    ) -> Result<Vec<(i64, RowStatus)>> {
+        self.ingest_arrow(ffi_schema_ptr, ffi_array_ptr, token, true)
+    }
+
+    unsafe fn ingest_arrow(
+        &self,
+        ffi_schema_ptr: *mut FFI_ArrowSchema,
+        ffi_array_ptr: *mut FFI_ArrowArray,
+        token: Option<&str>,
+        partial: bool,
+    ) -> Result<Vec<(i64, RowStatus)>> {
+        let with_status = |labels: Vec<i64>, added: RowStatus| -> Vec<(i64, RowStatus)> {
+            labels.into_iter().map(|label| (label, RowStatus::of(label, added))).collect()
+        };
+        let Some(token) = token else {
+            return Ok(with_status(self.append_arrow(ffi_schema_ptr, ffi_array_ptr, partial)?, RowStatus::Ok));
+        };
+        self.with_token(
+            token,
+            || {
+                let labels = self.append_arrow(ffi_schema_ptr, ffi_array_ptr, partial)?;
+                let pairs = labels.iter().enumerate().map(|(i, label)| (i as i64, *label)).collect();
+                Ok((with_status(labels, RowStatus::Ok), pairs))
+            },
+            |record| {
+                // Release the rows like a first call would
+                let rows = std::mem::replace(&mut *ffi_array_ptr, FFI_ArrowArray::empty()).len() as u64;
+                if record.len() != rows {
+                    return Err(anyhow!(
+                        "idempotency token '{}' was used for {} rows, not {}",
+                        token,
+                        record.len(),
+                        rows
+                    ));
+                }
+                let labels = record.pairs().into_iter().map(|(_, label)| label).collect();
+                Ok(with_status(labels, RowStatus::Duplicate))
+            },
+        )
+    }
+
+    /// [`LanceIndex::delete_batch`], run once per idempotency `token`.
+    pub fn delete_batch_with_token(&self, labels: &[i64], token: Option<&str>) -> Result<()> {
+        let Some(token) = token else {
+            return self.delete_batch(labels);
+        };
+        self.with_token(token, || Ok((self.delete_batch(labels)?, Vec::new())), |_| Ok(()))
+    }
+
+    /// [`LanceIndex::merge_from`], run once per idempotency `token`: a retry with a
+    /// recorded token merges nothing and returns the first call's label mapping.
+    pub fn merge_from_with_token(
+        &self,
+        source: &LanceIndex,
+        live_source_labels: &[i64],
+        strict: bool,
+        token: Option<&str>,
+    ) -> Result<MergeReport> {
+        let Some(token) = token else {
+            return self.merge_from(source, live_source_labels, strict);
+        };
+        self.with_token(
+            token,
+            || {
+                let report = self.merge_from(source, live_source_labels, strict)?;
+                let pairs = report.mapping.clone();
+                Ok((report, pairs))
+            },
+            |record| {
+                Ok(MergeReport {
+                    mapping: record.pairs(),
+                    staleness: self.index_staleness()?,
+                    optimize_scheduled: false,
+                })
+            },
+        )
+    }
+
+    /// Run `mutation` once per idempotency `token` (see [`crate::idempotency`]):
+    /// claim the token, run the mutation and record the label pairs it returns. A
+    /// retry of a recorded token calls `replay` with the record instead, and a retry
+    /// of a token whose mutation was never recorded fails.
+    fn with_token<T>(
+        &self,
+        token: &str,
+        mutation: impl FnOnce() -> Result<(T, Vec<(i64, i64)>)>,
+        replay: impl FnOnce(TokenRecord) -> Result<T>,
+    ) -> Result<T> {
+        idempotency::validate_token(token)?;
+        let sidecar = self
+            .sidecar_table(&idempotency::sidecar_table_name(&self.table_name), idempotency::sidecar_schema(), true)?
+            .ok_or_else(|| anyhow!("tokens table unavailable"))?;
+        let now = access::now_ms();
+        let expires_ms = now + idempotency::TOKEN_RETENTION_MS;
+        let claim = idempotency::new_claim_id(now);
+        if Self::token_state(&sidecar, token, now)?.is_none() {
+            let expired = format!("expires_ms <= {}", now);
+            if runtime::block_on(sidecar.count_rows(Some(expired.clone())))? > 0 {
+                runtime::block_on(sidecar.delete(&expired))?;
+            }
+            let batch = idempotency::to_record_batch(token, &claim, expires_ms, None)?;
+            let reader = RecordBatchIterator::new(vec![Ok(batch)], idempotency::sidecar_schema());
+            let mut insert = sidecar.merge_insert(&["token"]);
+            insert.when_not_matched_insert_all();
+            runtime::block_on(insert.execute(Box::new(reader)))?;
+        }
+        match Self::token_state(&sidecar, token, now)? {
+            Some(TokenState::Recorded(record)) => return replay(record),
+            Some(TokenState::Pending { claim: winner }) if winner == claim => {}
+            _ => {
+                return Err(anyhow!(
+                    "an earlier call with idempotency token '{}' has not been recorded; its outcome is unknown \
+                     until it is, or until the token expires",
+                    token
+                ))
+            }
+        }
+
+        let (value, pairs) = match mutation() {
+            Ok(done) => done,
+            Err(e) => {
+                let release = format!("token = '{}' AND claim = '{}'", token, claim);
+                return Err(match runtime::block_on(sidecar.delete(&release)) {
+                    Ok(()) => e,
+                    Err(release_error) => e.context(format!(
+                        "idempotency token '{}' stays claimed until it expires: {}",
+                        token, release_error
+                    )),
+                });
+            }
+        };
+        let record = TokenRecord::new(expires_ms, pairs);
+        let recorded = idempotency::to_record_batch(token, &claim, expires_ms, Some(&record)).and_then(|batch| {
+            let reader = RecordBatchIterator::new(vec![Ok(batch)], idempotency::sidecar_schema());
+            Ok(runtime::block_on(sidecar.add(Box::new(reader)).execute())?)
+        });
+        recorded.map_err(|e| {
+            anyhow!(
+                "the mutation committed, but idempotency token '{}' could not be recorded; retries with it fail \
+                 until it expires: {}",
+                token,
+                e
+            )
+        })?;
+        Ok(value)
+    }
+
+    /// The state of `token` from its unexpired rows in the tokens sidecar table.
+    fn token_state(sidecar: &LanceTable, token: &str, now: i64) -> Result<Option<TokenState>> {
+        let filter = format!("token = '{}' AND expires_ms > {}", token, now);
+        let stream = runtime::block_on(sidecar.query().only_if(filter).with_row_id().execute())?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let mut rows = Vec::new();
+        for batch in &batches {
+            idempotency::read_batch(batch, &mut rows)?;
+        }
+        idempotency::resolve(&rows)
+    }
+
+    /// Drop access records of deleted labels. Called once the delete committed, so
+    /// it is best effort: labels are never reused, and a record left behind only
+    /// names a row that no longer exists.
+    fn forget_access(&self, labels: &[i64]) {
+        if !self.access.enabled() {
+            return;
+        }
+        self.access.forget(labels);
+        let forget = || -> Result<()> {
+            if let Some(sidecar) = self.access_table(false)? {
+                let csv = labels.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
+                runtime::block_on(sidecar.delete(&format!("label IN ({})", csv)))?;
+            }
+            Ok(())
+        };
+        let _ = forget();
+    }
+
+    /// Count vectors.
+    pub fn count(&self) -> Result<u64> {
+        self.with_reconnect(|| {
+            let table = self.get_table()?;
+            let mut count = runtime::block_on(table.count_rows(self.scope.clone()))?;
+            if self.cold_rows.load(Ordering::Acquire) {
+                if let Some(sidecar) = self.cold_table(false)? {
+                    count += runtime::block_on(sidecar.count_rows(self.scope.clone()))?;
+                }
+            }
+            Ok(count as u64)
+        })
+    }
+
+    /// Create an ANN index (IVF_PQ).
+    ///
+    /// Pass 0 for num_partitions or num_sub_vectors to use LanceDB defaults.
+    pub fn create_ann_index(
+        &self,
+        num_partitions: u32,
+        num_sub_vectors: u32,
+    ) -> Result<()> {
+        self.create_vector_index(
+            VectorIndexType::IvfPq,
+            &VectorIndexParams {
+                num_partitions,
+                num_sub_vectors,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create an ANN index (IVF_HNSW_SQ).
+    ///
+    /// `m` is the number of edges per node in the HNSW graph.
+    /// `ef_construction` is the search width during index build.
+    pub fn create_hnsw_index(
+        &self,
+        m: u32,
+        ef_construction: u32,
+    ) -> Result<()> {
+        self.create_vector_index(
+            VectorIndexType::IvfHnswSq,
+            &VectorIndexParams {
+                m,
+                ef_construction,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create an ANN index (IVF_FLAT): IVF partitions over unquantized vectors, so
+    /// the probed partitions are searched with exact distances. Full recall within
+    /// `nprobes`, at the cost of an index as large as the vectors; suits moderate
+    /// tables.
+    pub fn create_ivf_flat_index(&self, num_partitions: u32) -> Result<()> {
+        self.create_vector_index(
+            VectorIndexType::IvfFlat,
+            &VectorIndexParams {
+                num_partitions,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create (or replace) the vector index of the given type.
+    pub fn create_vector_index(&self, kind: VectorIndexType, params: &VectorIndexParams) -> Result<()> {
+        if self.vector_storage() == VectorStorage::Int8 {
+            let table = &self.table_name;
+            return Err(anyhow!("{} stores int8 vectors, which Lance cannot index; searches scan them", table));
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.admission.yield_to_interactive();
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let (index, metric) = self.vector_index_spec(kind, params)?;
+        let compression = params.compression_ratio(kind, self.dimension);
+        let result = Self::commit_vector_index(
+            &table,
+            index,
+            metric,
+            compression,
+            &self.index_metric,
+            &self.index_compression,
+            || {},
+        );
+        self.committed();
+        result
+    }
+
+    /// Create (or replace) a vector index on `column`: `vector`, as
+    /// [`LanceIndex::create_vector_index`] does, or during a re-embed `vector_next`,
+    /// once every row has its new vector, so the current and the new embeddings can
+    /// both be searched through an index while they are compared. The new column's
+    /// index becomes the table's vector index when the re-embed finishes. Multivector
+    /// columns are indexed too, under cosine only.
+    pub fn create_vector_index_on(
+        &self,
+        column: &str,
+        kind: VectorIndexType,
+        params: &VectorIndexParams,
+    ) -> Result<()> {
+        match column {
+            "vector" => return self.create_vector_index(kind, params),
+            reembed::NEXT_COLUMN => {}
+            other if self.multivector_dimension(other).is_ok() => {
+                return self.create_multivector_index(other, kind, params)
+            }
+            other => return Err(Self::unknown_search_column(other)),
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.admission.yield_to_interactive();
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let dim = self.current_reembed(&table)?;
+        let missing = runtime::block_on(table.count_rows(Some(format!("{} IS NULL", column))))?;
+        if missing > 0 {
+            return Err(anyhow!("cannot index {}: {} rows have no new vector yet", column, missing));
+        }
+        let (index, metric) = self.vector_index_spec(kind, params)?;
+        let compression = params.compression_ratio(kind, dim);
+        let built = runtime::block_on_cpu(table.create_index(&[column], index).replace(true).execute());
+        self.committed();
+        built?;
+        metadata::set(&table, metadata::REEMBED_INDEX_METRIC, Some(metric))?;
+        metadata::set(&table, metadata::REEMBED_INDEX_COMPRESSION, Some(&compression.to_string()))?;
+        Ok(())
+    }
+
+    fn create_multivector_index(
+        &self,
+        column: &str,
+        kind: VectorIndexType,
+        params: &VectorIndexParams,
+    ) -> Result<()> {
+        let (index, metric) = self.vector_index_spec(kind, params)?;
+        if metric != "cosine" {
+            return Err(anyhow!("multivector column {} can only be indexed with metric cosine, not {}", column, metric));
+        }
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.admission.yield_to_interactive();
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let built = runtime::block_on_cpu(table.create_index(&[column], index).replace(true).execute());
+        self.committed();
+        built?;
+        Ok(())
+    }
+
+    fn unknown_search_column(column: &str) -> anyhow::Error {
+        anyhow!(
+            "unknown vector column '{}' (expected vector, {} or a multivector column)",
+            column,
+            reembed::NEXT_COLUMN
+        )
+    }
+
+    /// Every index on the table with its coverage. Vector indices built by this
+    /// extension on `vector`, or during a re-embed on `vector_next`, also report the
+    /// metric they were built with, as do indices on multivector columns (cosine).
+    pub fn list_indices(&self) -> Result<Vec<IndexStatus>> {
+        let table = self.get_table()?;
+        let mut metrics = vec![
+            ("vector".to_string(), metadata::get(&table, metadata::INDEX_METRIC)?),
+            (reembed::NEXT_COLUMN.to_string(), metadata::get(&table, metadata::REEMBED_INDEX_METRIC)?),
+        ];
+        for (column, _) in self.multivector_columns() {
+            metrics.push((column, Some("cosine".to_string())));
+        }
+        let mut statuses = Vec::new();
+        for index in runtime::block_on(table.list_indices())? {
+            let stats = runtime::block_on(table.index_stats(&index.name))?;
+            let metric = metrics
+                .iter()
+                .find(|(column, _)| index.columns.iter().any(|c| c == column))
+                .and_then(|(_, metric)| metric.clone());
+            statuses.push(IndexStatus {
+                indexed_rows: stats.as_ref().map_or(0, |s| s.num_indexed_rows as u64),
+                unindexed_rows: stats.as_ref().map_or(0, |s| s.num_unindexed_rows as u64),
+                metric,
+                info: IndexInfo {
+                    name: index.name,
+                    index_type: index.index_type.to_string(),
+                    columns: index.columns,
+                },
+            });
+        }
+        Ok(statuses)
+    }
+
+    /// Train a replacement vector index on a worker thread, leaving the current index
+    /// serving searches until Lance swaps in the new one on commit.
+    ///
+    /// Parameters are validated before the worker starts. With `wait`, blocks until the
+    /// rebuild finishes; either way, returns the rebuild status.
+    pub fn rebuild_index(
+        &self,
+        kind: VectorIndexType,
+        params: &VectorIndexParams,
+        wait: bool,
+    ) -> Result<RebuildStatus> {
+        let (index, metric) = self.vector_index_spec(kind, params)?;
+        let compression = params.compression_ratio(kind, self.dimension);
+        self.require_writer()?;
+        let table = self.get_table()?;
+        if self.rebuild.status().state == RebuildState::Running {
+            return Err(anyhow!("an index rebuild is already running"));
+        }
+        let permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.rebuild.begin(kind)?;
+
+        let tracker = self.rebuild.clone();
+        let index_metric = self.index_metric.clone();
+        let index_compression = self.index_compression.clone();
+        let watch = self.watch.clone();
+        let spawned = std::thread::Builder::new()
+            .name("lance-rebuild".to_string())
+            .spawn(move || {
+                let _permit = permit;
+                tracker.finish(Self::commit_vector_index(
+                    &table,
+                    index,
+                    metric,
+                    compression,
+                    &index_metric,
+                    &index_compression,
+                    || tracker.enter(RebuildPhase::Recording),
+                ));
+                watch.bump();
+            });
+        if let Err(e) = spawned {
+            self.rebuild.finish(Err(anyhow!("failed to start rebuild worker: {}", e)));
+            return Err(anyhow!("failed to start rebuild worker: {}", e));
+        }
+
+        Ok(if wait {
+            self.rebuild.wait()
+        } else {
+            self.rebuild.status()
+        })
+    }
+
+    /// Status of the most recent `rebuild_index` on this handle.
+    pub fn rebuild_status(&self) -> RebuildStatus {
+        self.rebuild.status()
+    }
+
+    /// Validate build parameters, apply the handle's training memory cap, and translate
+    /// them to a LanceDB index definition. Returns the definition and the canonical
+    /// metric it is built with.
+    fn vector_index_spec(
+        &self,
+        kind: VectorIndexType,
+        params: &VectorIndexParams,
+    ) -> Result<(lancedb::index::Index, &'static str)> {
+        use lancedb::index::vector::{IvfFlatIndexBuilder, IvfHnswSqIndexBuilder, IvfPqIndexBuilder};
+        use lancedb::index::Index;
+
+        let metric = distance::canonical_metric(params.metric.as_deref().unwrap_or(&self.metric))?;
+        let distance_type = Self::distance_type(metric);
+
+        let limits = self.build_limits();
+        let mut params = params.clone();
+        if limits.max_memory_bytes > 0 {
+            params.fit_training_memory(self.dimension, self.count()?, limits.max_memory_bytes)?;
+        }
+
+        let index = match kind {
+            VectorIndexType::IvfPq => {
+                let mut builder = IvfPqIndexBuilder::default().distance_type(distance_type);
+                if params.num_partitions > 0 {
+                    builder = builder.num_partitions(params.num_partitions);
+                }
+                if params.num_sub_vectors > 0 {
+                    builder = builder.num_sub_vectors(params.num_sub_vectors);
+                }
+                if params.sample_rate > 0 {
+                    builder = builder.sample_rate(params.sample_rate);
+                }
+                Index::IvfPq(builder)
+            }
+            VectorIndexType::IvfHnswSq => {
+                let mut builder = IvfHnswSqIndexBuilder::default().distance_type(distance_type);
+                if params.num_partitions > 0 {
+                    builder = builder.num_partitions(params.num_partitions);
+                }
+                if params.m > 0 {
+                    builder = builder.num_edges(params.m);
+                }
+                if params.ef_construction > 0 {
+                    builder = builder.ef_construction(params.ef_construction);
+                }
+                if params.sample_rate > 0 {
+                    builder = builder.sample_rate(params.sample_rate);
+                }
+                Index::IvfHnswSq(builder)
+            }
+            VectorIndexType::IvfFlat => {
+                let mut builder = IvfFlatIndexBuilder::default().distance_type(distance_type);
+                if params.num_partitions > 0 {
+                    builder = builder.num_partitions(params.num_partitions);
+                }
+                if params.sample_rate > 0 {
+                    builder = builder.sample_rate(params.sample_rate);
+                }
+                Index::IvfFlat(builder)
+            }
+        };
+
+        Ok((index, metric))
+    }
+
+    /// Build `index` on the vector column, replacing the current one in the same commit,
+    /// and record its metric and compression ratio.
+    fn commit_vector_index(
+        table: &LanceTable,
+        index: lancedb::index::Index,
+        metric: &str,
+        compression: f64,
+        index_metric: &RwLock<Option<String>>,
+        index_compression: &RwLock<Option<f64>>,
+        trained: impl FnOnce(),
+    ) -> Result<()> {
+        runtime::block_on_cpu(
+            table
+                .create_index(&["vector"], index)
+                .replace(true)
+                .execute(),
+        )?;
+        trained();
+
+        metadata::set(table, metadata::INDEX_METRIC, Some(metric))?;
+        *index_metric
+            .write()
+            .map_err(|_| anyhow!("index metric lock poisoned"))? = Some(metric.to_string());
+        metadata::set(table, metadata::INDEX_COMPRESSION, Some(&compression.to_string()))?;
+        *index_compression
+            .write()
+            .map_err(|_| anyhow!("index compression lock poisoned"))? = Some(compression);
+        Ok(())
+    }
+
+    /// Compression ratio of the vector index, if one was built by this extension.
+    pub fn index_compression(&self) -> Option<f64> {
+        self.index_compression.read().ok().and_then(|c| *c)
+    }
+
+    /// The refine factor a search of `k` rows runs with for the requested
+    /// `refine_factor` ([`AUTO_REFINE`] picks one from the index's compression ratio),
+    /// and how many candidates it rescores.
+    pub fn refine_plan(&self, refine_factor: usize, k: usize) -> RefinePlan {
+        let compression = self.index_compression();
+        let auto = refine_factor == AUTO_REFINE;
+        let factor = if auto {
+            index_params::auto_refine_factor(compression, k)
+        } else {
+            refine_factor
+        };
+        RefinePlan {
+            factor,
+            rescored: if factor > 0 { k.saturating_mul(factor) } else { 0 },
+            compression,
+            auto,
+        }
+    }
+
+    /// Distance type the vector index was built with, if one was built by this extension.
+    pub fn index_metric(&self) -> Option<String> {
+        self.index_metric.read().ok().and_then(|m| m.clone())
+    }
+
+    /// Fail when searches with this handle's metric would not match the vector index.
+    fn check_index_metric(&self) -> Result<()> {
+        let Some(index_metric) = self.index_metric() else {
+            return Ok(());
+        };
+        if distance::canonical_metric(&self.metric)? != index_metric {
+            return Err(anyhow!(
+                "search metric '{}' does not match the vector index, which was built with '{}'; \
+                 open the table with metric '{}' or rebuild the index",
+                self.metric,
+                index_metric,
+                index_metric
+            ));
+        }
+        Ok(())
+    }
+
+    fn distance_type(metric: &str) -> lancedb::DistanceType {
+        match metric {
+            "cosine" => lancedb::DistanceType::Cosine,
+            "dot" => lancedb::DistanceType::Dot,
+            _ => lancedb::DistanceType::L2,
+        }
+    }
+
+    /// Compact the dataset (optimize storage).
+    ///
+    /// Runs compaction, version pruning, and index optimization as separate steps so
+    /// in-flight searches get priority between them. Rows that turned cold under the
+    /// table's cold storage policy are moved out first, so the compaction reclaims
+    /// their vectors.
+    pub fn compact(&self) -> Result<()> {
+        use lancedb::table::{CompactionOptions, OptimizeAction, OptimizeOptions};
+
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        if self.move_cold_rows()? > 0 {
+            self.compact_cold_table()?;
+        }
+        let table = self.get_table()?;
+        let steps = [
+            OptimizeAction::Compact {
+                options: CompactionOptions::default(),
+                remap_options: None,
+            },
+            OptimizeAction::Prune {
+                older_than: None,
+                delete_unverified: None,
+                error_if_tagged_old_versions: None,
+            },
+            OptimizeAction::Index(OptimizeOptions::default()),
+        ];
+        for step in steps {
+            self.admission.yield_to_interactive();
+            runtime::block_on(table.optimize(step))?;
+            self.committed();
+        }
+        Ok(())
+    }
+
+    /// Run a maintenance plan step by step (see [`crate::maintenance`]). A failing
+    /// step stops the plan; the report records it and skips the remaining steps.
+    pub fn run_maintenance(&self, plan: &MaintenancePlan) -> Result<MaintenanceReport> {
+        self.require_writer()?;
+        let mut report = MaintenanceReport::default();
+        let mut failed = false;
+        for &step in &plan.steps {
+            let started = Instant::now();
+            let (status, detail) = if failed {
+                (StepStatus::Skipped, "an earlier step failed".to_string())
+            } else {
+                self.admission.yield_to_interactive();
+                match self.run_maintenance_step(step) {
+                    Ok(Some(detail)) => (StepStatus::Done, detail),
+                    Ok(None) => (StepStatus::Skipped, "not needed".to_string()),
+                    Err(e) => {
+                        failed = true;
+                        (StepStatus::Failed, e.to_string())
+                    }
+                }
+            };
+            report.steps.push(StepReport {
+                step,
+                status,
+                detail,
+                elapsed: started.elapsed(),
+            });
+        }
+        Ok(report)
+    }
+
+    /// Run one step; `None` when there was nothing to do.
+    fn run_maintenance_step(&self, step: MaintenanceStep) -> Result<Option<String>> {
+        use lancedb::table::{CompactionOptions, OptimizeAction};
+
+        match step {
+            MaintenanceStep::Optimize => {
+                if !self.index_staleness()?.is_some_and(|s| s.unindexed_rows > 0) {
+                    return Ok(None);
+                }
+                self.optimize_indices()?;
+                Ok(Some("indices updated".to_string()))
+            }
+            MaintenanceStep::Compact { min_fragments } => {
+                let _permit = self.admission.acquire(OpClass::Maintenance)?;
+                let moved = self.move_cold_rows()?;
+                let cold = (moved > 0).then(|| format!("{} rows moved to cold storage", moved));
+                if moved > 0 {
+                    self.compact_cold_table()?;
+                }
+                let fragments = self.fragment_ids(None)?.len();
+                if fragments < min_fragments.max(2) {
+                    return Ok(cold);
+                }
+                let stats = runtime::block_on(self.get_table()?.optimize(OptimizeAction::Compact {
+                    options: CompactionOptions::default(),
+                    remap_options: None,
+                }))?;
+                self.committed();
+                let (removed, added) = stats
+                    .compaction
+                    .map_or((0, 0), |m| (m.fragments_removed, m.fragments_added));
+                let detail = format!("{} fragments: {} rewritten into {}", fragments, removed, added);
+                Ok(Some(match cold {
+                    Some(cold) => format!("{}; {}", detail, cold),
+                    None => detail,
+                }))
+            }
+            MaintenanceStep::Prune { older_than_days } => {
+                // Lance subtracts the age from the current time, which must not overflow
+                let older_than = i64::try_from(older_than_days)
+                    .ok()
+                    .and_then(chrono::TimeDelta::try_days)
+                    .filter(|age| {
+                        let cutoff = access::now_ms().checked_sub(age.num_milliseconds());
+                        cutoff.and_then(chrono::DateTime::from_timestamp_millis).is_some()
+                    })
+                    .ok_or_else(|| anyhow!("prune age of {} days is out of range", older_than_days))?;
+                let _permit = self.admission.acquire(OpClass::Maintenance)?;
+                let stats = runtime::block_on(self.get_table()?.optimize(OptimizeAction::Prune {
+                    older_than: Some(older_than),
+                    delete_unverified: None,
+                    error_if_tagged_old_versions: None,
+                }))?;
+                let (versions, bytes) = stats.prune.map_or((0, 0), |p| (p.old_versions, p.bytes_removed));
+                if versions == 0 {
+                    return Ok(None);
+                }
+                Ok(Some(format!("{} versions removed, {} bytes freed", versions, bytes)))
+            }
+            MaintenanceStep::Verify => self.verify_labels().map(Some),
+        }
+    }
+
+    /// Check that labels are unique and below the next label to assign, so appends
+    /// cannot collide with existing rows.
+    fn verify_labels(&self) -> Result<String> {
+        let mut labels = self.all_labels()?;
+        labels.sort_unstable();
+        if let Some(pair) = labels.windows(2).find(|w| w[0] == w[1]) {
+            return Err(anyhow!("label {} is used by more than one row", pair[0]));
+        }
+        let next_label = self.next_label.load(Ordering::SeqCst);
+        if let Some(&max_label) = labels.last() {
+            if max_label >= next_label {
+                return Err(anyhow!(
+                    "label {} is not below the next label {}; run repair_label_watermark",
+                    max_label,
+                    next_label
+                ));
+            }
+        }
+        Ok(format!("{} rows, labels consistent", labels.len()))
+    }
+
+    /// Rewrite the table sorted by `column`, `rows_per_fragment` rows per fragment.
+    ///
+    /// Rows with nearby values of `column` end up in the same fragments, so range
+    /// filters on it (e.g. a time window) match few fragments and the rest are skipped.
+    /// The rows are sorted as they stream in, spilling sorted runs to disk (see
+    /// [`crate::sort`]), and replace the old fragments in one commit based on the
+    /// version read (see `replace_rows`): rows other writers append meanwhile are
+    /// kept, and a concurrent delete or update of the rewritten rows fails the
+    /// rewrite instead of being undone. The vector indices are then updated for the
+    /// new fragments.
+    pub fn cluster_by(&self, column: &str, rows_per_fragment: usize) -> Result<()> {
+        use lancedb::table::{OptimizeAction, OptimizeOptions};
+
+        self.require_unscoped("cluster_by")?;
+        if rows_per_fragment == 0 {
+            return Err(anyhow!("rows_per_fragment must be positive"));
+        }
+        self.schema
+            .field_with_name(column)
+            .map_err(|_| anyhow!("column '{}' not found", column))?;
+
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let version = runtime::block_on(table.version())?;
+        let order = OrderBy {
+            column: column.to_string(),
+            descending: false,
+        };
+        if self.replace_rows(&table, version, rows_per_fragment, |batches, schema| {
+            let mut sorter = ScanSorter::new(schema, order, None)?;
+            for batch in batches {
+                sorter.push(batch?)?;
+            }
+            sorter.finish(rows_per_fragment)
+        })? {
+            self.admission.yield_to_interactive();
+            runtime::block_on(table.optimize(OptimizeAction::Index(OptimizeOptions::default())))?;
+        }
+
+        let result = metadata::set(&table, metadata::CLUSTER_BY, Some(column));
+        self.committed();
+        result
+    }
+
+    /// Replace the rows of the table at `version` by what `rewrite` makes of them, in
+    /// one commit based on `version`. `rewrite` gets the rows as an iterator of
+    /// batches and their schema, and returns the new rows, which are written as
+    /// fragments of at most `rows_per_fragment` rows. The commit removes the
+    /// fragments of `version`, so rows appended since then are kept, and Lance fails
+    /// it if another writer deleted or updated rows in those fragments since. Returns
+    /// false, committing nothing, when the table had no rows.
+    fn replace_rows(
+        &self,
+        table: &LanceTable,
+        version: u64,
+        rows_per_fragment: usize,
+        rewrite: impl FnOnce(
+            &mut dyn Iterator<Item = Result<RecordBatch>>,
+            SchemaRef,
+        ) -> Result<Box<dyn RecordBatchReader + Send>>,
+    ) -> Result<bool> {
+        use lance::dataset::transaction::{Operation, Transaction};
+        use lance::dataset::{CommitBuilder, InsertBuilder, WriteMode};
+
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false);
+        let dataset = Arc::new(runtime::block_on(Self::load_dataset(
+            &uri,
+            &params.clone().unwrap_or_default(),
+            Some(version),
+        ))?);
+        let removed_fragment_ids: Vec<u64> = dataset.get_fragments().iter().map(|f| f.id() as u64).collect();
+        if removed_fragment_ids.is_empty() || runtime::block_on(dataset.count_rows(None))? == 0 {
+            return Ok(false);
+        }
+
+        let schema: SchemaRef = Arc::new(dataset.schema().into());
+        let mut stream = runtime::block_on(dataset.scan().try_into_stream())?;
+        let mut batches = std::iter::from_fn(|| {
+            self.admission.yield_to_interactive();
+            runtime::block_on(stream.try_next())
+                .map_err(|e| anyhow!("stream error: {}", e))
+                .transpose()
+        });
+        let rows = rewrite(&mut batches, schema)?;
+
+        let write_params = WriteParams {
+            mode: WriteMode::Append,
+            max_rows_per_file: rows_per_fragment,
+            store_params: params,
+            ..Default::default()
+        };
+        runtime::block_on(async {
+            let written = InsertBuilder::new(dataset.clone())
+                .with_params(&write_params)
+                .execute_uncommitted_stream(rows)
+                .await?;
+            let Operation::Append { fragments } = written.operation else {
+                return Err(anyhow!("rewrite did not produce new fragments"));
+            };
+            let operation = Operation::Update {
+                removed_fragment_ids,
+                updated_fragments: Vec::new(),
+                new_fragments: fragments,
+            };
+            CommitBuilder::new(dataset)
+                .execute(Transaction::new(version, operation, None, None))
+                .await
+                .map_err(|e| anyhow!("rewrite of {} at version {} not committed: {}", self.table_name, version, e))
+        })?;
+        runtime::block_on(table.checkout_latest())?;
+        self.committed();
+        Ok(true)
+    }
+
+    /// Column the table was last clustered by, if any.
+    pub fn cluster_column(&self) -> Result<Option<String>> {
+        metadata::get(&self.get_table()?, metadata::CLUSTER_BY)
+    }
+
+    /// Count the fragments a `filter` has to touch versus the total.
+    ///
+    /// Fragment ids are taken from row addresses, so this scans the filter's columns.
+    pub fn pruning_stats(&self, filter: &str) -> Result<PruningStats> {
+        Ok(PruningStats {
+            cluster_by: self.cluster_column()?,
+            total_fragments: self.fragment_ids(None)?.len() as u64,
+            matching_fragments: self.fragment_ids(Some(filter))?.len() as u64,
+        })
+    }
+
+    /// The (label, _rowid, fragment_id) of every row in scope, in scan order, read
+    /// from the label column alone. Row addresses change when compaction or a merge
+    /// rewrites fragments, so hosts keeping their own row correspondence re-read this
+    /// afterwards instead of scanning full rows.
+    pub fn row_addresses(&self) -> Result<RecordBatch> {
+        let table = self.get_table()?;
+        let mut query = table
+            .query()
+            .select(Select::columns(&["label"]))
+            .with_row_id();
+        if let Some(scope) = &self.scope {
+            query = query.only_if(scope.clone());
+        }
+        let stream = runtime::block_on(query.execute())?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+
+        let (mut labels, mut row_ids) = (Vec::new(), Vec::new());
+        for batch in &batches {
+            let batch_labels = batch
+                .column_by_name("label")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .ok_or_else(|| anyhow!("label column not Int64"))?;
+            let batch_row_ids = batch
+                .column_by_name("_rowid")
+                .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+                .ok_or_else(|| anyhow!("_rowid column is not UInt64"))?;
+            labels.extend_from_slice(batch_labels.values());
+            row_ids.extend_from_slice(batch_row_ids.values());
+        }
+        // Row addresses are (fragment id << 32) | offset.
+        let fragments: Vec<u32> = row_ids.iter().map(|id| (id >> 32) as u32).collect();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("_rowid", DataType::UInt64, false),
+            Field::new("fragment_id", DataType::UInt32, false),
+        ]));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(labels)),
+                Arc::new(UInt64Array::from(row_ids)),
+                Arc::new(UInt32Array::from(fragments)),
+            ],
+        )?)
+    }
+
+    /// Distinct fragment ids containing rows that match `filter` (all rows when `None`).
+    fn fragment_ids(&self, filter: Option<&str>) -> Result<HashSet<u64>> {
+        let table = self.get_table()?;
+        let mut query = table
+            .query()
+            .select(Select::columns(&["label"]))
+            .with_row_id();
+        if let Some(filter) = filter {
+            query = query.only_if(filter);
+        }
+        let results = runtime::block_on(query.execute())?;
+
+        let mut fragments = HashSet::new();
+        runtime::block_on(async {
+            let mut stream = results;
+            while let Some(batch) = stream
+                .try_next()
+                .await
+                .map_err(|e| anyhow!("stream error: {}", e))?
+            {
+                let row_ids = batch
+                    .column_by_name("_rowid")
+                    .ok_or_else(|| anyhow!("missing _rowid column"))?
+                    .as_any()
+                    .downcast_ref::<UInt64Array>()
+                    .ok_or_else(|| anyhow!("_rowid column is not UInt64"))?;
+                // Row addresses are (fragment id << 32) | offset.
+                fragments.extend(row_ids.values().iter().map(|id| id >> 32));
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+        Ok(fragments)
+    }
+
+    /// Compute min/max/null-count for one column.
+    ///
+    /// Lance's per-fragment statistics are not exposed through LanceDB, so this runs a
+    /// scan projected to the single column; cost scales with that column's size only.
+    ///
+    /// Sensitive columns need `privileged`, since min/max expose values. On a scoped
+    /// handle only the rows in scope are counted.
+    pub fn column_stats(&self, column: &str, privileged: bool) -> Result<ColumnStats> {
+        let field = self
+            .schema
+            .field_with_name(column)
+            .map_err(|_| anyhow!("column '{}' not found", column))?;
+        self.check_exportable(&[column], privileged)?;
+        let table = self.get_table()?;
+
+        let mut query = table.query().select(Select::columns(&[column]));
+        if let Some(scope) = self.scoped(None) {
+            query = query.only_if(scope);
+        }
+        let results = runtime::block_on(query.execute())?;
+
+        let mut builder = ColumnStatsBuilder::new(column);
+        runtime::block_on(async {
+            let mut stream = results;
+            while let Some(batch) = stream
+                .try_next()
+                .await
+                .map_err(|e| anyhow!("stream error: {}", e))?
+            {
+                let col = batch
+                    .column_by_name(column)
+                    .ok_or_else(|| anyhow!("missing {} column", column))?;
+                builder.update(col.as_ref())?;
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+
+        builder.finish(field.data_type())
+    }
+
+    /// Row counts per value of `column` over a `fraction` of the rows, with the
+    /// distribution of distances to `query` per group when one is given. The sample
+    /// is drawn uniformly from the row ids of the rows in scope, so it does not follow
+    /// insertion order, and only the sampled rows are read. At most `max_groups`
+    /// values are tracked; the rest are counted together (see [`crate::approx`]).
+    pub fn approx_stats(
+        &self,
+        column: &str,
+        query: Option<&[f32]>,
+        fraction: f64,
+        max_groups: usize,
+        privileged: bool,
+    ) -> Result<ApproxStats> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(anyhow!("sample_fraction must be in (0, 1], got {}", fraction));
+        }
+        self.schema
+            .field_with_name(column)
+            .map_err(|_| anyhow!("column '{}' not found", column))?;
+        self.check_exportable(&[column], privileged)?;
+        let query = query.map(|q| self.prepare_query(q)).transpose()?;
+
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        let table = self.get_table()?;
+        let mut columns = vec![column];
+        if query.is_some() {
+            columns.push("vector");
+        }
+        let mut builder = GroupStatsBuilder::new(max_groups);
+        let mut fold = |batch: &RecordBatch| -> Result<()> {
+            let values = batch
+                .column_by_name(column)
+                .ok_or_else(|| anyhow!("missing {} column", column))?;
+            let distances = match &query {
+                Some(query) => {
+                    let vectors = Self::vector_column(batch).ok_or_else(|| anyhow!("missing vector column"))?;
+                    let vectors = self.widen_vectors(vectors)?;
+                    let mut distances = Vec::with_capacity(vectors.len());
+                    for i in 0..vectors.len() {
+                        let values = vectors.value(i);
+                        let values = values
+                            .as_any()
+                            .downcast_ref::<Float32Array>()
+                            .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                        distances.push(distance::distance(&self.metric, query, values.values())?);
+                    }
+                    Some(distances)
+                }
+                None => None,
+            };
+            builder.update(values.as_ref(), distances.as_deref())
+        };
+
+        if fraction >= 1.0 {
+            let mut select = table.query().select(Select::columns(&columns));
+            if let Some(filter) = self.live_filter(None) {
+                select = select.only_if(filter);
+            }
+            let mut stream = runtime::block_on(select.execute())?;
+            while let Some(batch) = runtime::block_on(stream.try_next())
+                .map_err(|e| anyhow!("stream error: {}", e))?
+            {
+                fold(&batch)?;
+            }
+            return Ok(builder.finish(1.0));
+        }
+
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let version = runtime::block_on(table.version())?;
+        let dataset = runtime::block_on(Self::load_dataset(&uri, &params, Some(version)))?;
+        let mut scan = dataset.scan();
+        scan.project(&["label"])?.with_row_id();
+        if let Some(filter) = self.live_filter(None) {
+            scan.filter(&filter)?;
+        }
+        let mut row_ids: Vec<u64> = Vec::new();
+        runtime::block_on(async {
+            let mut stream = scan.try_into_stream().await?;
+            while let Some(batch) = stream.try_next().await.map_err(|e| anyhow!("stream error: {}", e))? {
+                let ids = batch
+                    .column_by_name("_rowid")
+                    .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+                    .ok_or_else(|| anyhow!("missing _rowid column"))?;
+                row_ids.extend_from_slice(ids.values());
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+        let rows = row_ids.len();
+        if rows == 0 {
+            return Ok(builder.finish(1.0));
+        }
+
+        let size = ((fraction * rows as f64).round() as usize).clamp(1, rows);
+        let seed = std::hash::BuildHasher::hash_one(&std::collections::hash_map::RandomState::new(), access::now_ms());
+        let sampled = approx::sample(row_ids, size, seed);
+        let projection = dataset.schema().project(&columns)?;
+        for chunk in sampled.chunks(SAMPLE_TAKE_ROWS) {
+            let batch = runtime::block_on(dataset.take_rows(chunk, projection.clone()))?;
+            fold(&batch)?;
+        }
+        Ok(builder.finish(size as f64 / rows as f64))
+    }
+
+    /// Summarize on-disk bytes by data, deletion, index, and manifest files.
+    ///
+    /// Only the files the current version references are counted: its fragments'
+    /// data and deletion files, the directories of its indices, and its manifest.
+    /// Files left behind by older versions until cleanup are not. With
+    /// `include_columns`, data bytes are also split per column from the column
+    /// sizes recorded in each data file's metadata, without reading the data (legacy
+    /// v1 files record none, so their columns report 0).
+    pub fn disk_usage(&self, include_columns: bool) -> Result<DiskUsage> {
+        use lance::dataset::statistics::DatasetStatisticsExt;
+        use lance::io::{ObjectStore, ObjectStoreRegistry};
+        use lance_index::DatasetIndexExt;
+
+        let table = self.get_table()?;
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let version = runtime::block_on(table.version())?;
+        runtime::block_on(async {
+            let dataset = Arc::new(Self::load_dataset(&uri, &params, Some(version)).await?);
+            // Sizes come from the store, so object-store tables are covered too
+            let registry = Arc::new(ObjectStoreRegistry::default());
+            let (store, base) = ObjectStore::from_uri_and_params(registry, &uri, &params).await?;
+            let mut usage = DiskUsage::default();
+            for file in backup::referenced_files(&dataset) {
+                let bytes = store.size(&backup::join(&base, &file)).await? as u64;
+                if file.starts_with("_deletions/") {
+                    usage.deletion_bytes += bytes;
+                } else {
+                    usage.data_bytes += bytes;
+                }
+            }
+            for index in dataset.load_indices().await?.iter() {
+                let dir = backup::join(&base, &format!("_indices/{}", index.uuid));
+                let mut objects = store.inner.list(Some(&dir));
+                while let Some(meta) = objects.try_next().await? {
+                    usage.index_bytes += meta.size as u64;
+                }
+            }
+            let manifest = dataset.manifest_naming_scheme.manifest_path(&base, version);
+            usage.manifest_bytes = store.size(&manifest).await? as u64;
+
+            if include_columns {
+                let stats = dataset.calculate_data_stats().await?;
+                let bytes: HashMap<u32, u64> = stats.fields.iter().map(|f| (f.id, f.bytes_on_disk)).collect();
+                for field in dataset.schema().fields.iter() {
+                    // Nested columns are recorded under the ids of their child fields
+                    let mut total = 0;
+                    let mut pending = vec![field];
+                    while let Some(f) = pending.pop() {
+                        total += bytes.get(&(f.id as u32)).copied().unwrap_or(0);
+                        pending.extend(f.children.iter());
+                    }
+                    usage.columns.push((field.name.clone(), total));
+                }
+            }
+            Ok::<DiskUsage, anyhow::Error>(usage)
+        })
+    }
+
+    /// Back the table's files up into the dataset directory `dest` (a local path or
+    /// object store URI), copying only what the versions after `since_version` added
+    /// (see [`crate::backup`]); `None` backs up every version. Chain backups into
+    /// the same `dest`, passing the previous report's version, for point-in-time
+    /// restore from any backed-up version. Read snapshots back up their pinned
+    /// version.
+    pub fn backup_incremental(&self, dest: &str, since_version: Option<u64>) -> Result<BackupReport> {
+        let _permit = self.admission.acquire(OpClass::Maintenance)?;
+        let uri = self.get_table()?.dataset_uri().to_string();
+        let source_params = Self::store_params(&uri, false).unwrap_or_default();
+        let dest_params = Self::store_params(dest, false).unwrap_or_default();
+        runtime::block_on(async {
+            let source = Self::load_dataset(&uri, &source_params, self.snapshot_version).await?;
+            backup::backup(&source, &source_params, dest, &dest_params, since_version).await
+        })
+    }
+
+    /// The table's Lance dataset at `version`, the latest when `None`.
+    async fn load_dataset(uri: &str, params: &ObjectStoreParams, version: Option<u64>) -> Result<Dataset> {
+        let mut builder = DatasetBuilder::from_uri(uri).with_read_params(ReadParams {
+            store_options: Some(params.clone()),
+            ..Default::default()
+        });
+        if let Some(version) = version {
+            builder = builder.with_version(version);
+        }
+        Ok(builder.load().await?)
+    }
+
+    /// The manifest of the version this handle sees, as a blob for
+    /// [`LanceIndex::import_manifest`] on a replica.
+    /// Storage-format versions and feature flags of the table's current version, and
+    /// whether this build can write it (see [`crate::format_info`]).
+    pub fn format_info(&self) -> Result<FormatInfo> {
+        Self::read_format_info(&self.get_table()?)
+    }
+
+    fn read_format_info(table: &LanceTable) -> Result<FormatInfo> {
+        let version = runtime::block_on(table.version())?;
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let dataset = runtime::block_on(Self::load_dataset(&uri, &params, Some(version)))?;
+        Ok(FormatInfo::from_manifest(dataset.manifest()))
+    }
+
+    pub fn export_manifest(&self) -> Result<Vec<u8>> {
+        let table = self.get_table()?;
+        let version = runtime::block_on(table.version())?;
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let manifest = runtime::block_on(async {
+            let dataset = Self::load_dataset(&uri, &params, Some(version)).await?;
+            replication::read_manifest(&dataset, &params).await
+        })?;
+        Ok(ManifestBlob {
+            table_name: self.table_name.clone(),
+            version,
+            manifest,
+        }
+        .encode())
+    }
+
+    /// Move this handle's view to the version in `blob` (from
+    /// [`LanceIndex::export_manifest`] on the writer), after checking that the
+    /// manifest stored for that version is the exported one. Only the version's
+    /// manifest is read; the store is not listed. The handle becomes a read-only
+    /// replica that stays on the imported version until the next import, which must
+    /// not go back. Returns the version.
+    pub fn import_manifest(&self, blob: &[u8]) -> Result<u64> {
+        if let Some(version) = self.snapshot_version {
+            let table = &self.table_name;
+            return Err(anyhow!("read snapshot of {} at version {} cannot import manifests", table, version));
+        }
+        let blob = ManifestBlob::decode(blob)?;
+        if blob.table_name != self.table_name {
+            return Err(anyhow!("manifest blob is for table {}, not {}", blob.table_name, self.table_name));
+        }
+        if let Some(current) = self.imported_version().filter(|v| blob.version < *v) {
+            return Err(anyhow!("manifest version {} is older than the imported version {}", blob.version, current));
+        }
+        let table = self.get_table()?;
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        let stored = runtime::block_on(async {
+            let dataset = Self::load_dataset(&uri, &params, Some(blob.version)).await?;
+            replication::read_manifest(&dataset, &params).await
+        })
+        .map_err(|e| anyhow!("version {} of {} is not readable from storage: {}", blob.version, self.table_name, e))?;
+        if stored != blob.manifest {
+            return Err(anyhow!(
+                "stored manifest of {} version {} differs from the imported one",
+                self.table_name,
+                blob.version
+            ));
+        }
+        self.note_failure(runtime::block_on(table.checkout(blob.version)).map_err(Into::into))?;
+        self.imported_version.store(blob.version, Ordering::Release);
+        self.invalidate_vector_stats();
+        Ok(blob.version)
+    }
+
+    /// Version the last [`LanceIndex::import_manifest`] moved this handle to.
+    pub fn imported_version(&self) -> Option<u64> {
+        Some(self.imported_version.load(Ordering::Acquire)).filter(|v| *v > 0)
+    }
+
+    /// Get a vector by label.
+    pub fn get_vector(&self, label: i64) -> Result<Vec<f32>> {
+        let table = self.get_table()?;
+
+        let predicate = format!("label = {}", label);
+        let results = runtime::block_on(
+            table
+                .query()
+                .only_if(self.scoped(Some(&predicate)).unwrap_or(predicate))
+                .execute(),
+        )?;
+
+        let batches: Vec<RecordBatch> = runtime::block_on(async {
+            let mut batches = Vec::new();
+            let mut stream = results;
+            loop {
+                match stream.try_next().await {
+                    Ok(Some(batch)) => batches.push(batch),
+                    Ok(None) => break,
+                    Err(e) => return Err(anyhow!("stream error: {}", e)),
+                }
+            }
+            Ok(batches)
+        })?;
+
+        for batch in &batches {
+            if batch.num_rows() > 0 {
+                let vec_col = batch
+                    .column_by_name("vector")
+                    .ok_or_else(|| anyhow!("missing vector column"))?;
+                let list_array = vec_col
+                    .as_any()
+                    .downcast_ref::<FixedSizeListArray>()
+                    .ok_or_else(|| anyhow!("vector not FixedSizeList"))?;
+                let list_array = self.widen_vectors(list_array)?;
+                let values = list_array
+                    .value(0);
+                let float_array = values
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                return match self.rotation() {
+                    Some(rotation) => rotation.invert(float_array.values()),
+                    None => Ok(float_array.values().to_vec()),
+                };
+            }
+        }
+
+        let Some(vector) = self.cold_vector(label)? else {
+            return Err(anyhow!("label {} not found", label));
+        };
+        match self.rotation() {
+            Some(rotation) => rotation.invert(&vector),
+            None => Ok(vector),
+        }
+    }
+
+    /// The widened vector of cold row `label`, if it was moved to the cold sidecar.
+    fn cold_vector(&self, label: i64) -> Result<Option<Vec<f32>>> {
+        if !self.cold_rows.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        let Some(sidecar) = self.cold_table(false)? else {
+            return Ok(None);
+        };
+        let predicate = format!("label = {}", label);
+        let stream = runtime::block_on(
+            sidecar
+                .query()
+                .only_if(self.scoped(Some(&predicate)).unwrap_or(predicate))
+                .execute(),
+        )?;
+        let batches: Vec<RecordBatch> = runtime::block_on(stream.try_collect())
+            .map_err(|e| anyhow!("stream error: {}", e))?;
+        let Some(batch) = batches.iter().find(|b| b.num_rows() > 0) else {
+            return Ok(None);
+        };
+        let vectors = cold::widen(batch)?;
+        let values = vectors.value(0);
+        let values = values
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .ok_or_else(|| anyhow!("vector values not Float32"))?;
+        Ok(Some(values.values().to_vec()))
+    }
+
+    /// Fetch the vectors of `labels`. Labels that do not exist are skipped.
+    fn vectors_for_labels(&self, labels: &[i64]) -> Result<Vec<(i64, Vec<f32>)>> {
+        self.vectors_where(labels, None)
+    }
+
+    /// `vectors_for_labels`, skipping rows that do not match `filter`.
+    fn vectors_where(&self, labels: &[i64], filter: Option<&str>) -> Result<Vec<(i64, Vec<f32>)>> {
+        if labels.is_empty() {
+            return Ok(Vec::new());
+        }
+        let table = self.get_table()?;
+        let label_list: Vec<String> = labels.iter().map(|l| l.to_string()).collect();
+        let mut predicate = format!("label IN ({})", label_list.join(", "));
+        if let Some(filter) = and_filters(self.scope.as_deref(), filter) {
+            predicate = format!("{} AND ({})", predicate, filter);
+        }
+        let results = runtime::block_on(
+            table
+                .query()
+                .select(Select::columns(&["label", "vector"]))
+                .only_if(predicate)
+                .execute(),
+        )?;
+
+        let mut out = Vec::with_capacity(labels.len());
+        runtime::block_on(async {
+            let mut stream = results;
+            while let Some(batch) = stream
+                .try_next()
+                .await
+                .map_err(|e| anyhow!("stream error: {}", e))?
+            {
+                let batch_labels = batch
+                    .column_by_name("label")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("missing label column"))?;
+                let vectors = batch
+                    .column_by_name("vector")
+                    .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+                    .ok_or_else(|| anyhow!("missing vector column"))?;
+                let vectors = self.widen_vectors(vectors)?;
+                for i in 0..batch.num_rows() {
+                    let values = vectors.value(i);
+                    let values = values
+                        .as_any()
+                        .downcast_ref::<Float32Array>()
+                        .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                    out.push((batch_labels.value(i), values.values().to_vec()));
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+        Ok(out)
+    }
+
+    /// Get all vectors as a flat contiguous f32 array and their labels.
+    /// Returns (labels, flat_vectors) where flat_vectors has length labels.len() * dimension.
+    pub fn get_all_vectors(&self) -> Result<(Vec<i64>, Vec<f32>)> {
+        let table = self.get_table()?;
+
+        let mut query = table.query();
+        if let Some(scope) = &self.scope {
+            query = query.only_if(scope.clone());
+        }
+        let results = runtime::block_on(query.execute())?;
+
+        let mut all_labels = Vec::new();
+        let mut all_vectors = Vec::new();
+        let mut budget = ReadBudget::new("get_all_vectors");
+        let row_bytes = std::mem::size_of::<i64>() + self.dimension * std::mem::size_of::<f32>();
+
+        runtime::block_on(async {
+            let mut stream = results;
+            while let Some(batch) = stream.try_next().await
+                .map_err(|e| anyhow!("stream error: {}", e))? {
+                budget.charge(batch.num_rows(), batch.num_rows() * row_bytes)?;
+                let label_col = batch
+                    .column_by_name("label")
+                    .ok_or_else(|| anyhow!("missing label column"))?;
+                let labels = label_col
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .ok_or_else(|| anyhow!("label column not Int64"))?;
+
+                let vec_col = batch
+                    .column_by_name("vector")
+                    .ok_or_else(|| anyhow!("missing vector column"))?;
+                let list_array = vec_col
+                    .as_any()
+                    .downcast_ref::<FixedSizeListArray>()
+                    .ok_or_else(|| anyhow!("vector not FixedSizeList"))?;
+                let list_array = self.widen_vectors(list_array)?;
+
+                for i in 0..batch.num_rows() {
+                    all_labels.push(labels.value(i));
+                    let values = list_array.value(i);
+                    let float_array = values
+                        .as_any()
+                        .downcast_ref::<Float32Array>()
+                        .ok_or_else(|| anyhow!("vector values not Float32"))?;
+                    all_vectors.extend_from_slice(float_array.values());
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        })?;
+
+        if let Some(rotation) = self.rotation() {
+            all_vectors = all_vectors
+                .chunks_exact(self.dimension)
+                .map(|v| rotation.invert(v))
+                .collect::<Result<Vec<_>>>()?
+                .concat();
+        }
+        Ok((all_labels, all_vectors))
+    }
+
+    /// The labels of the rows this handle sees (its scope and row expiry apply) as a
+    /// roaring bitmap (see [`crate::label_bitmap`]), with the table version read.
+    /// Only the label column is scanned.
+    pub fn label_bitmap(&self) -> Result<(RoaringTreemap, u64)> {
+        let _permit = self.admission.acquire(OpClass::Search)?;
+        self.with_reconnect(|| {
+            let table = self.get_table()?;
+            let version = runtime::block_on(table.version())?;
+            let mut query = table.query().select(Select::columns(&["label"]));
+            if let Some(filter) = self.live_filter(None) {
+                query = query.only_if(filter);
+            }
+            let mut stream = runtime::block_on(query.execute())?;
+            let mut bitmap = RoaringTreemap::new();
+            runtime::block_on(async {
+                while let Some(batch) = stream.try_next().await.map_err(|e| anyhow!("stream error: {}", e))? {
+                    let labels = batch
+                        .column_by_name("label")
+                        .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                        .ok_or_else(|| anyhow!("missing label column"))?;
+                    label_bitmap::insert(&mut bitmap, labels.values())?;
+                }
+                Ok::<(), anyhow::Error>(())
+            })?;
+            Ok((bitmap, version))
+        })
+    }
+
+    // Internal helpers
+
+    /// The label watermark recorded in the table metadata, if one is recorded and
+    /// readable.
+    fn recorded_label_watermark(table: &LanceTable) -> Result<Option<LabelWatermark>> {
+        Ok(metadata::get(table, metadata::LABEL_WATERMARK)?.and_then(|value| LabelWatermark::parse(&value)))
+    }
+
+    fn record_label_watermark(table: &LanceTable, watermark: &LabelWatermark) -> Result<()> {
+        metadata::set(table, metadata::LABEL_WATERMARK, Some(&watermark.to_string()))
+    }
+
+    /// `watermark` raised by the labels in the fragments of the table's current
+    /// version that it does not cover yet.
+    fn advance_label_watermark(table: &LanceTable, watermark: &LabelWatermark) -> Result<LabelWatermark> {
+        let uri = table.dataset_uri().to_string();
+        let params = Self::store_params(&uri, false).unwrap_or_default();
+        runtime::block_on(async {
+            let version = table.version().await?;
+            let dataset = Self::load_dataset(&uri, &params, Some(version)).await?;
+            let fragments: Vec<_> = dataset.get_fragments().iter().map(|f| f.metadata().clone()).collect();
+            let from = if watermark.overwritten(&fragments) { 0 } else { watermark.next_fragment };
+            let fragments: Vec<_> = fragments.into_iter().filter(|f| f.id >= from).collect();
+            let Some(newest) = fragments.iter().max_by_key(|f| f.id) else {
+                return Ok(watermark.clone());
+            };
+            let (next_fragment, newest_file) = (newest.id + 1, newest.files.first().map(|f| f.path.clone()));
+            let mut max_label = watermark.max_label;
+            let mut scan = dataset.scan();
+            scan.with_fragments(fragments).project(&["label"])?;
+            let mut stream = scan.try_into_stream().await?;
+            while let Some(batch) = stream.try_next().await? {
+                let labels = batch
+                    .column_by_name("label")
+                    .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                    .ok_or_else(|| anyhow!("label column missing or not Int64"))?;
+                max_label = max_label.max(arrow::compute::max(labels).unwrap_or(-1));
+            }
+            Ok::<_, anyhow::Error>(LabelWatermark {
+                max_label,
+                next_fragment,
+                newest_file,
+            })
+        })
+    }
+
+    /// Bring the handle's label watermark up to the table's current version.
+    /// Returns the highest label found.
+    fn catch_up_labels(&self, table: &LanceTable) -> Result<i64> {
+        let mut watermark = self
+            .label_watermark
+            .lock()
+            .map_err(|_| anyhow!("label watermark lock poisoned"))?;
+        *watermark = Self::advance_label_watermark(table, &watermark)?;
+        Ok(watermark.max_label)
+    }
+
+    /// Record the label watermark before rows are deleted, if labels were assigned
+    /// since it was last recorded: once the rows holding the highest label are gone,
+    /// only the record keeps a later open from handing it out again. Appends do not
+    /// record it, so this costs a metadata commit only for the first delete after
+    /// new labels.
+    fn cover_labels(&self, table: &LanceTable) -> Result<()> {
+        let assigned = self.next_label.load(Ordering::SeqCst) - 1;
+        if assigned <= self.recorded_label.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        let mut watermark = self
+            .label_watermark
+            .lock()
+            .map_err(|_| anyhow!("label watermark lock poisoned"))?;
+        *watermark = Self::advance_label_watermark(table, &watermark)?;
+        watermark.max_label = watermark.max_label.max(assigned);
+        Self::record_label_watermark(table, &watermark)?;
+        self.recorded_label.fetch_max(watermark.max_label, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Rebuild the recorded label watermark from every fragment. Returns the max
+    /// label (-1 when no label was ever assigned).
+    pub fn repair_label_watermark(&self) -> Result<i64> {
+        self.require_writer()?;
+        let table = self.get_table()?;
+        let recorded = Self::recorded_label_watermark(&table)?.map_or(-1, |w| w.max_label);
+        let from_scratch = LabelWatermark {
+            max_label: recorded.max(self.next_label.load(Ordering::SeqCst) - 1),
+            ..LabelWatermark::EMPTY
+        };
+        let watermark = Self::advance_label_watermark(&table, &from_scratch)?;
+        Self::record_label_watermark(&table, &watermark)?;
+        self.committed();
+        let max_label = watermark.max_label;
+        *self
+            .label_watermark
+            .lock()
+            .map_err(|_| anyhow!("label watermark lock poisoned"))? = watermark;
+        self.recorded_label.fetch_max(max_label, Ordering::AcqRel);
+        self.next_label.fetch_max(max_label + 1, Ordering::SeqCst);
+        Ok(max_label)
+    }
+
+    /// Build schema for vector-only tables (label + vector).
+    /// The table schema for data columns `imported` (label prepended) and the
+    /// dimension of its `vector` column.
+    ///
+    /// `vector_columns` declares which FixedSizeList columns hold vectors; the first
+    /// is the one searched and must be named `vector`, and empty declares just that
+    /// column. Declared columns must have float elements and a dimension in
+    /// 1..=[`MAX_DIMENSION`]; they are stored with `storage`'s element type. Other
+    /// FixedSizeList columns keep their element type. `List<FixedSizeList<float>>`
+    /// columns are multivector columns, stored as Float32 (see [`multivector`]). A
+    /// schema without a `vector` column fails instead of one of its FixedSizeList
+    /// columns being guessed.
+    fn table_schema_from_arrow(
+        imported: &Schema,
+        vector_columns: &[String],
+        storage: VectorStorage,
+    ) -> Result<(Arc<Schema>, usize)> {
+        let fixed_size_lists: Vec<&str> = imported
+            .fields()
+            .iter()
+            .filter(|f| matches!(f.data_type(), DataType::FixedSizeList(_, _)))
+            .map(|f| f.name().as_str())
+            .collect();
+        let declared: Vec<&str> = if vector_columns.is_empty() {
+            vec!["vector"]
+        } else {
+            vector_columns.iter().map(String::as_str).collect()
+        };
+        if declared[0] != "vector" {
+            return Err(anyhow!("the first vector column must be named vector, got {}", declared[0]));
+        }
+        if storage == VectorStorage::Int8 && declared.len() > 1 {
+            return Err(anyhow!("int8 storage quantizes only the vector column; declare no other vector columns"));
+        }
+        if imported.field_with_name("vector").is_err() {
+            return Err(match fixed_size_lists.as_slice() {
+                [] => anyhow!("no FixedSizeList column found in schema"),
+                names => anyhow!(
+                    "no vector column; name the vector column vector (FixedSizeList columns: {})",
+                    names.join(", ")
+                ),
+            });
+        }
+
+        let mut dimension = 0usize;
+        for (i, name) in declared.iter().enumerate() {
+            if declared[..i].contains(name) {
+                return Err(anyhow!("vector column {} declared twice", name));
+            }
+            let field = imported
+                .field_with_name(name)
+                .map_err(|_| anyhow!("declared vector column {} is not in the schema", name))?;
+            let DataType::FixedSizeList(item, dim) = field.data_type() else {
+                return Err(anyhow!("vector column {} is {}, expected a FixedSizeList", name, field.data_type()));
+            };
+            if !item.data_type().is_floating() {
+                return Err(anyhow!("vector column {} has {} elements, expected floats", name, item.data_type()));
+            }
+            if *dim <= 0 || *dim as usize > MAX_DIMENSION {
+                return Err(anyhow!("vector column {} has dimension {}, expected 1 to {}", name, dim, MAX_DIMENSION));
+            }
+            if i == 0 {
+                dimension = *dim as usize;
+            }
+        }
+
+        // Build table schema: prepend label column, then imported fields
+        let mut table_fields: Vec<Arc<Field>> = vec![Arc::new(Field::new("label", DataType::Int64, false))];
+        for field in imported.fields() {
+            // Rename FixedSizeList child fields to "item" (DuckDB uses "")
+            if let DataType::FixedSizeList(item, dim) = field.data_type() {
+                let item = if declared.contains(&field.name().as_str()) {
+                    Field::new("item", storage.element_type(), true)
+                } else {
+                    Field::new("item", item.data_type().clone(), item.is_nullable())
+                };
+                let fixed_field =
+                    Field::new(field.name(), DataType::FixedSizeList(Arc::new(item), *dim), field.is_nullable());
+                table_fields.push(Arc::new(fixed_field));
+            } else if let Some(stored) = multivector::stored_field(field)? {
+                table_fields.push(Arc::new(stored));
+            } else {
+                table_fields.push(Arc::new(field.as_ref().clone()));
+            }
+        }
+        Ok((Arc::new(Schema::new(table_fields)), dimension))
+    }
+
+    fn build_vector_schema(dimension: usize, storage: VectorStorage) -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new(
+                "vector",
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", storage.element_type(), true)),
+                    dimension as i32,
+                ),
+                false,
+            ),
+        ]))
+    }
+
+    /// Create an empty RecordBatch from a schema by generating empty arrays for each field.
+    fn empty_batch_from_schema(schema: &Arc<Schema>) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = schema
+            .fields()
+            .iter()
+            .map(|field| Self::empty_array_for_datatype(field.data_type()))
+            .collect();
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+
+    /// Create an empty array for any Arrow DataType.
+    fn empty_array_for_datatype(dt: &DataType) -> ArrayRef {
+        use arrow_array::{BooleanArray, Int32Array, Float64Array, StringArray};
+        match dt {
+            DataType::Int64 => Arc::new(Int64Array::from(Vec::<i64>::new())),
+            DataType::Int32 => Arc::new(Int32Array::from(Vec::<i32>::new())),
+            DataType::Float32 => Arc::new(Float32Array::from(Vec::<f32>::new())),
+            DataType::Float64 => Arc::new(Float64Array::from(Vec::<f64>::new())),
+            DataType::Utf8 => Arc::new(StringArray::from(Vec::<&str>::new())),
+            DataType::Boolean => Arc::new(BooleanArray::from(Vec::<bool>::new())),
+            DataType::FixedSizeList(_, _) | DataType::List(_) => arrow_array::new_empty_array(dt),
+            _ => Arc::new(StringArray::from(Vec::<&str>::new())), // fallback
+        }
+    }
+
+    fn make_fixed_size_list(values: Float32Array, dimension: i32) -> FixedSizeListArray {
+        let field = Arc::new(Field::new("item", DataType::Float32, true));
+        let values_ref: ArrayRef = Arc::new(values);
+        FixedSizeListArray::new(field, dimension, values_ref, None)
+    }
+
+    /// Build a RecordBatch over already-contiguous flat vector data (no copy).
+    fn make_batch_contiguous(&self, labels: &[i64], flat_vectors: ScalarBuffer<f32>) -> Result<RecordBatch> {
+        let label_array = Int64Array::from_iter_values(labels.iter().copied());
+        let values = Float32Array::new(flat_vectors, None);
+        let list = Self::make_fixed_size_list(values, self.dimension as i32);
+        let list = self.narrow_vectors(list)?;
+        Ok(RecordBatch::try_new(self.schema.clone(), vec![
+            Arc::new(label_array),
+            Arc::new(list),
+        ])?)
+    }
+
+    fn make_batch(&self, labels: &[i64], vectors: &[&[f32]]) -> Result<RecordBatch> {
+        let label_array = Int64Array::from(labels.to_vec());
+        let flat_values: Vec<f32> = vectors.iter().flat_map(|v| v.iter().copied()).collect();
+        let values = Float32Array::from(flat_values);
+        let list = Self::make_fixed_size_list(values, self.dimension as i32);
+        let list = self.narrow_vectors(list)?;
+        Ok(RecordBatch::try_new(self.schema.clone(), vec![
+            Arc::new(label_array),
+            Arc::new(list),
+        ])?)
+    }
+}
+
+impl Drop for LanceIndex {
+    fn drop(&mut self) {
+        // Buffered rows have labels already; a failed flush is reported by the next
+        // write on the table. Hits are best effort and flushed in the background.
+        let buffered = self.handle_entry.buffered_rows();
+        if let Err(e) = self.close_append_session() {
+            let message = format!(
+                "{} buffered rows of {} were not written when their handle was dropped: {}",
+                buffered, self.table_name, e
+            );
+            watch::record_lost_appends(self.handle_entry.path(), &self.table_name, message);
+        }
+        if self.access.enabled() {
+            self.flush_access_in_background();
+        }
+        let _ = self.flush_query_log();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quota::QuotaExceeded;
+
+    fn temp_dir() -> tempfile::TempDir {
+        tempfile::tempdir().expect("failed to create temp dir")
+    }
+
+    #[test]
+    fn test_next_label_unique_after_deletes() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        // Insert 5 vectors with labels [0,1,2,3,4]
+        let idx = LanceIndex::create(db_path_str, 3, "l2", "vectors").unwrap();
+        for i in 0..5 {
+            let label = idx.add_vector(&[i as f32, 0.0, 0.0]).unwrap();
+            assert_eq!(label, i);
+        }
+
+        // Delete labels [1,2]
+        idx.delete(1).unwrap();
+        idx.delete(2).unwrap();
+
+        // Reopen — next insert must NOT produce label 3 (already exists)
+        drop(idx);
+        let idx2 = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let new_label = idx2.add_vector(&[99.0, 0.0, 0.0]).unwrap();
+        assert!(
+            new_label >= 5,
+            "expected label >= 5 after deletes, got {new_label}"
+        );
+    }
+
+    #[test]
+    fn test_next_label_correct_on_empty_reopen() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_empty.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        drop(idx);
+
+        let idx2 = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let label = idx2.add_vector(&[1.0, 2.0]).unwrap();
+        assert_eq!(label, 0);
+    }
+
+    #[test]
+    fn test_open_derives_schema() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_schema.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 3, "l2", "vectors").unwrap();
+        for i in 0..5 {
+            idx.add_vector(&[i as f32, 0.0, 0.0]).unwrap();
+        }
+        idx.delete(1).unwrap();
+        idx.delete(2).unwrap();
+
+        drop(idx);
+
+        let idx2 = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let new_label = idx2.add_vector(&[99.0, 0.0, 0.0]).unwrap();
+        assert!(
+            new_label >= 5,
+            "expected label >= 5 via open, got {new_label}"
+        );
+    }
+
+    #[test]
+    fn test_custom_table_name() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_tbl.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        // Two indexes in same dataset with different table names
+        let idx_a = LanceIndex::create(db_path_str, 2, "l2", "idx_a").unwrap();
+        let idx_b = LanceIndex::create(db_path_str, 2, "l2", "idx_b").unwrap();
+
+        idx_a.add_vector(&[1.0, 0.0]).unwrap();
+        idx_a.add_vector(&[2.0, 0.0]).unwrap();
+        idx_b.add_vector(&[10.0, 0.0]).unwrap();
+
+        assert_eq!(idx_a.count().unwrap(), 2);
+        assert_eq!(idx_b.count().unwrap(), 1);
+
+        // Reopen each — they stay independent
+        drop(idx_a);
+        drop(idx_b);
+        let idx_a2 = LanceIndex::open(db_path_str, "idx_a", "l2").unwrap();
+        let idx_b2 = LanceIndex::open(db_path_str, "idx_b", "l2").unwrap();
+        assert_eq!(idx_a2.count().unwrap(), 2);
+        assert_eq!(idx_b2.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_search_cursor_streams_all_results() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_cursor.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..200).map(|i| i as f32).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        let expected = idx.search(&[0.0, 1.0], 50, 20, 1, 0, None, false).unwrap();
+
+        let mut cursor = idx.search_cursor(&[0.0, 1.0], 50, 20, 1).unwrap();
+        let mut labels = [0i64; 7];
+        let mut distances = [0f32; 7];
+        let mut streamed = Vec::new();
+        loop {
+            let n = cursor.next_into(&mut labels, &mut distances).unwrap();
+            if n == 0 {
+                break;
+            }
+            assert!(n <= 7);
+            streamed.extend(labels[..n].iter().copied().zip(distances[..n].iter().copied()));
+        }
+        assert_eq!(streamed, expected);
+        assert_eq!(cursor.next_into(&mut labels, &mut distances).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_disk_usage_reports_data_and_columns() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_usage.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 4, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..400).map(|i| i as f32).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+        idx.delete(3).unwrap();
+
+        let usage = idx.disk_usage(true).unwrap();
+        assert!(usage.data_bytes > 0);
+        assert!(usage.deletion_bytes > 0);
+        assert!(usage.manifest_bytes > 0);
+        assert_eq!(usage.columns.len(), 2);
+        assert!(usage.columns.iter().all(|(_, bytes)| *bytes > 0));
+        let column_total: u64 = usage.columns.iter().map(|(_, b)| b).sum();
+        assert!(column_total <= usage.data_bytes);
+
+        // A second delete replaces the fragment's deletion file; the old one stays
+        // on disk but is no longer counted
+        idx.delete(4).unwrap();
+        let uri = idx.get_table().unwrap().dataset_uri().to_string();
+        let deletions: Vec<u64> = std::fs::read_dir(Path::new(&uri).join("_deletions"))
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .collect();
+        assert_eq!(deletions.len(), 2);
+        let usage = idx.disk_usage(false).unwrap();
+        assert!(usage.deletion_bytes < deletions.iter().sum::<u64>());
+        assert!(usage.columns.is_empty());
+
+        let batch = usage.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 5 + 2);
+    }
+
+    #[test]
+    fn test_cluster_by_prunes_fragments() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_cluster.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        // A single append lands in one fragment
+        let vectors: Vec<f32> = (0..200).map(|i| (i / 2) as f32).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+        assert_eq!(idx.cluster_column().unwrap(), None);
+
+        idx.cluster_by("label", 10).unwrap();
+        assert_eq!(idx.cluster_column().unwrap().as_deref(), Some("label"));
+        assert_eq!(idx.count().unwrap(), 100);
+
+        let stats = idx.pruning_stats("label >= 20 AND label < 30").unwrap();
+        assert_eq!(stats.total_fragments, 10);
+        assert_eq!(stats.matching_fragments, 1);
+        assert_eq!(stats.pruned_fragments(), 9);
+
+        // Filtered search only sees the window
+        let results = idx
+            .search(&[0.0, 0.0], 5, 20, 1, 0, Some("label >= 20 AND label < 30"), false)
+            .unwrap();
+        assert!(results.iter().all(|(label, _)| (20..30).contains(label)));
+    }
+
+    #[test]
+    fn test_pipeline_rescore_and_rerank() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_pipeline.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..100).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        let exact = idx.search(&[10.2, 0.0], 3, 20, 1, 0, None, false).unwrap();
+        let labels: Vec<i64> = exact.iter().map(|(l, _)| *l).collect();
+        assert_eq!(labels, vec![10, 11, 9]);
+
+        idx.set_pipeline(Some(Pipeline::parse("coarse(4) | rescore").unwrap()))
+            .unwrap();
+        let piped = idx.search(&[10.2, 0.0], 3, 20, 1, 0, None, false).unwrap();
+        assert_eq!(piped.iter().map(|(l, _)| *l).collect::<Vec<_>>(), labels);
+        assert!((piped[0].1 - 0.04).abs() < 1e-4);
+
+        // Reranker that prefers the largest label among the candidates
+        pipeline::register_reranker(
+            "test_largest_label",
+            Arc::new(|_, labels, _| Ok(labels.iter().map(|&l| -(l as f32)).collect())),
+        )
+        .unwrap();
+        idx.set_pipeline(Some(
+            Pipeline::parse("coarse(2) | rescore | rerank(test_largest_label)").unwrap(),
+        ))
+        .unwrap();
+        // Candidates are labels 10 and 11
+        let reranked = idx.search(&[10.2, 0.0], 1, 20, 1, 0, None, false).unwrap();
+        assert_eq!(reranked[0].0, 11);
+
+        // Persisted for handles opened later
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(
+            reopened.pipeline().unwrap().to_string(),
+            "coarse(2) | rescore | rerank(test_largest_label)"
+        );
+        reopened.set_pipeline(None).unwrap();
+        assert!(reopened.pipeline().is_none());
+    }
+
+    #[test]
+    fn test_staging_promote() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_staging.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let mut idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..10).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 10).unwrap();
+
+        let staging = idx.create_staging(true).unwrap();
+        assert_eq!(staging.count().unwrap(), 10);
+        // Copied rows keep their labels; new rows continue after them
+        assert_eq!(staging.add_vector(&[50.0, 0.0]).unwrap(), 10);
+        assert_eq!(idx.count().unwrap(), 10);
+
+        let sibling = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let retired = idx.promote_staging(staging).unwrap();
+        assert_eq!(idx.count().unwrap(), 11);
+        assert_eq!(idx.search(&[49.0, 0.0], 1, 1, 1, 0, None, false).unwrap()[0].0, 10);
+        // Open handles move on to the promoted rows; the staging table is gone
+        assert_eq!(sibling.count().unwrap(), 11);
+        let staging_name = staging::staging_table_name("vectors");
+        assert!(LanceIndex::open(db_path_str, &staging_name, "l2").is_err());
+
+        // The previous rows stay at the retired tag
+        let uri = idx.get_table().unwrap().dataset_uri().to_string();
+        let dataset = runtime::block_on(lance::dataset::Dataset::open(&uri)).unwrap();
+        let old = runtime::block_on(dataset.checkout_version(retired.as_str())).unwrap();
+        assert_eq!(runtime::block_on(old.count_rows(None)).unwrap(), 10);
+
+        let other = LanceIndex::create(db_path_str, 2, "l2", "other").unwrap();
+        assert!(idx.promote_staging(other).is_err());
+    }
+
+    #[test]
+    fn test_rename_table() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_rename.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 0.0, 0.0, 1.0], 2).unwrap();
+        drop(idx);
+
+        LanceIndex::rename_table(db_path_str, "vectors", "renamed").unwrap();
+        assert!(LanceIndex::open(db_path_str, "vectors", "l2").is_err());
+        let renamed = LanceIndex::open(db_path_str, "renamed", "l2").unwrap();
+        assert_eq!(renamed.count().unwrap(), 2);
+
+        assert!(LanceIndex::rename_table(db_path_str, "vectors", "other").is_err());
+        assert!(LanceIndex::rename_table(db_path_str, "renamed", "a/b").is_err());
+    }
+
+    #[test]
+    fn test_row_ttl_needs_expires_at() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_ttl.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        assert!(idx.set_row_ttl(true).is_err());
+        assert!(!idx.row_ttl());
+        idx.set_row_ttl(false).unwrap();
+        assert_eq!(idx.live_filter(Some("label > 1")).as_deref(), Some("label > 1"));
+    }
+
+    #[test]
+    fn test_row_ttl_with_zoned_expiry() {
+        use arrow_array::TimestampMillisecondArray;
+        use arrow_schema::TimeUnit;
+
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_ttl_zoned.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let zoned = DataType::Timestamp(TimeUnit::Millisecond, Some("+05:00".into()));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new(ttl::EXPIRES_AT, zoned, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        // The first row expired an hour ago; +05:00 is five hours ahead of UTC
+        let now = access::now_ms();
+        let expires = TimestampMillisecondArray::from(vec![now - 3_600_000, now + 86_400_000]).with_timezone("+05:00");
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 1.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(expires),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        idx.set_row_ttl(true).unwrap();
+        let hits = idx.search(&[0.0, 0.0], 2, 0, 0, 0, None, false).unwrap();
+        assert_eq!(hits.iter().map(|(label, _)| *label).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn test_scoped_handle() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_scope.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..10).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 10).unwrap();
+
+        assert!(LanceIndex::open_scoped(db_path_str, "vectors", "l2", "no_such_column = 1").is_err());
+        let scoped = LanceIndex::open_scoped(db_path_str, "vectors", "l2", "label >= 5").unwrap();
+        assert_eq!(scoped.count().unwrap(), 5);
+
+        // Out-of-scope rows are invisible to search, reads and deletes
+        let results = scoped.search(&[0.0, 0.0], 3, 1, 1, 0, None, false).unwrap();
+        assert_eq!(results.iter().map(|(l, _)| *l).collect::<Vec<_>>(), vec![5, 6, 7]);
+        let filtered = scoped.search(&[0.0, 0.0], 3, 1, 1, 0, Some("label < 7"), false).unwrap();
+        assert_eq!(filtered.iter().map(|(l, _)| *l).collect::<Vec<_>>(), vec![5, 6]);
+        assert!(scoped.get_vector(1).is_err());
+        scoped.delete_batch(&[1, 5]).unwrap();
+        assert_eq!(idx.count().unwrap(), 9);
+        assert_eq!(scoped.count().unwrap(), 4);
+
+        assert!(scoped.cluster_by("label", 2).is_err());
+
+        // Statistics only count rows in scope; table-wide settings are refused
+        let stats = scoped.column_stats("label", false).unwrap();
+        assert_eq!(stats.row_count, 4);
+        assert!(scoped.set_sensitive_columns(&[]).is_err());
+        assert!(scoped.set_row_ttl(false).is_err());
+        assert!(scoped.set_quota(None).is_err());
+        assert!(scoped.set_access_tracking(false).is_err());
+    }
+
+    #[test]
+    fn test_scan_and_sensitive_columns() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_scan.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..10).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 10).unwrap();
+
+        let all = idx.scan(&[], None, false).unwrap();
+        assert_eq!(all.num_rows(), 10);
+        assert_eq!(all.num_columns(), 2);
+        let labels = idx.scan(&["label".to_string()], Some("label < 3"), false).unwrap();
+        assert_eq!(labels.num_rows(), 3);
+        assert_eq!(labels.num_columns(), 1);
+        assert!(idx.scan(&["missing".to_string()], None, false).is_err());
+
+        // Keys needed to join back to the source cannot be withheld
+        assert!(idx.set_sensitive_columns(&["label".to_string()]).is_err());
+        assert!(idx.set_sensitive_columns(&["vector".to_string()]).is_err());
+        assert!(idx.set_sensitive_columns(&["missing".to_string()]).is_err());
+        idx.set_sensitive_columns(&[]).unwrap();
+        assert!(idx.sensitive_columns().is_empty());
+
+        // A sensitive column is left out of default scans, and cannot be read,
+        // filtered on or summarized without the privileged flag
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![vector, Field::new("token", DataType::Utf8, true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let notes = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "notes") }.unwrap();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(Float32Array::from(vec![0.0, 0.0, 1.0, 0.0])), None)),
+            Arc::new(StringArray::from(vec![Some("secret"), None])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { notes.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+        notes.set_sensitive_columns(&["token".to_string()]).unwrap();
+
+        let labels = ["label".to_string()];
+        let visible = notes.scan(&[], None, false).unwrap();
+        assert_eq!((visible.num_rows(), visible.num_columns()), (2, 2));
+        assert!(visible.schema().column_with_name("token").is_none());
+        assert!(notes.scan(&["token".to_string()], None, false).is_err());
+        assert!(notes.scan(&labels, Some("token = 'secret'"), false).is_err());
+        assert!(notes.scan(&labels, Some("TOKEN LIKE 's%'"), false).is_err());
+        assert!(notes.scan_with_labels(&[], Some("\"token\" IS NULL"), false).is_err());
+        assert!(notes.prefetch(&[], Some("token IS NULL")).is_err());
+        assert!(notes.column_stats("token", false).is_err());
+        // String literals that spell the column's name do not read it
+        assert_eq!(notes.scan(&labels, Some("'token' = 'token'"), false).unwrap().num_rows(), 2);
+
+        let privileged = notes.scan(&[], Some("token = 'secret'"), true).unwrap();
+        assert_eq!(privileged.num_rows(), 1);
+        assert!(privileged.schema().column_with_name("token").is_some());
+        assert_eq!(notes.column_stats("token", true).unwrap().null_count, 1);
+    }
+
+    /// A table with a sensitive string `token` and a sensitive BIGINT `doc` column.
+    fn sensitive_notes(db_path: &str) -> LanceIndex {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![
+            vector,
+            Field::new("token", DataType::Utf8, true),
+            Field::new("doc", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let notes = unsafe { LanceIndex::create_from_arrow(db_path, &mut ffi_schema, "l2", "notes") }.unwrap();
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 0.0, 2.0, 0.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(StringArray::from(vec![Some("secret"), None, Some("secret")])),
+            Arc::new(Int64Array::from(vec![7, 7, 8])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { notes.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+        notes.set_sensitive_columns(&["doc".to_string(), "token".to_string()]).unwrap();
+        notes
+    }
+
+    #[test]
+    fn test_searches_refuse_sensitive_filters() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_sensitive.lance");
+        let notes = sensitive_notes(db_path.to_str().unwrap());
+
+        // Which rows a filter lets through would reveal the column's values
+        let (q, filter) = ([0.0, 0.0], Some("token = 'secret'"));
+        let labels = ["label".to_string()];
+        let err = notes.search(&q, 3, 1, 0, 0, filter, false).unwrap_err();
+        assert!(err.to_string().contains("sensitive"), "{}", err);
+        assert!(notes.search_unlogged(&q, 3, 1, 0, 0, filter, false).is_err());
+        assert!(notes.search_prepared(&q, 3, 1, 0, 0, filter, false).is_err());
+        assert!(notes.search_rows(&q, 3, 1, 0, filter, &labels, false).is_err());
+        assert!(notes.hybrid_search(&q, "secret", None, 3, 1, 0, filter, Fusion::MaxScore, false).is_err());
+        assert!(notes.search_dedup(&q, 3, "label", 1, 0, filter, false).is_err());
+        assert!(notes.search_batch(&q, 1, 3, 1, 0, filter, 0, false).is_err());
+        assert!(notes.search_sampled(&q, 3, 1.0, 1, 0, filter, false).is_err());
+        // Nor can hits be grouped by one
+        assert!(notes.search_dedup(&q, 3, "token", 1, 0, None, false).is_err());
+
+        assert_eq!(notes.search(&q, 3, 1, 0, 0, filter, true).unwrap().len(), 2);
+        assert_eq!(notes.search_rows(&q, 3, 1, 0, filter, &labels, true).unwrap().0.len(), 2);
+        assert_eq!(notes.search_dedup(&q, 3, "token", 1, 0, None, true).unwrap().len(), 2);
+        assert_eq!(notes.search(&q, 3, 1, 0, 0, Some("label > 0"), false).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_search_with_columns_refuses_sensitive_columns() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_columns_sensitive.lance");
+        let notes = sensitive_notes(db_path.to_str().unwrap());
+
+        let q = [0.0, 0.0];
+        let token = ["token".to_string()];
+        assert!(notes.search_with_columns(&q, 3, 1, 0, None, &token, false).is_err());
+        // One hit fills k, so the k-NN query's own batches would be returned
+        assert!(notes.search_with_columns(&q, 1, 1, 0, Some("token IS NULL"), &[], false).is_err());
+
+        // Default columns leave the sensitive ones out
+        let reader = notes.search_with_columns(&q, 3, 1, 0, None, &[], false).unwrap();
+        let rows: Vec<RecordBatch> = reader.collect::<Result<_, _>>().unwrap();
+        assert!(rows.iter().all(|b| b.schema().column_with_name("token").is_none()));
+        let reader = notes.search_with_columns(&q, 3, 1, 0, Some("token IS NULL"), &token, true).unwrap();
+        let rows: Vec<RecordBatch> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows.iter().map(RecordBatch::num_rows).sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn test_search_parents_refuses_sensitive_columns() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_parents_sensitive.lance");
+        let notes = sensitive_notes(db_path.to_str().unwrap());
+
+        let q = [0.0, 0.0];
+        let err = notes.search_parents(&q, 2, Some("doc"), 1, 0, None, false).unwrap_err();
+        assert!(err.to_string().contains("sensitive"), "{}", err);
+        assert!(notes.search_parents(&q, 2, Some("label"), 1, 0, Some("token IS NULL"), false).is_err());
+
+        let parents: Vec<i64> =
+            notes.search_parents(&q, 2, Some("doc"), 1, 0, None, true).unwrap().iter().map(|h| h.parent).collect();
+        assert_eq!(parents, vec![7, 8]);
+        assert_eq!(notes.search_parents(&q, 3, Some("label"), 1, 0, None, false).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_search_within_allow_list() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_within.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..100).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        // The global nearest (10, 11) are not in the allow-list; duplicates and unknown labels are ignored
+        let within = idx.search_within(&[10.2, 0.0], 2, &[50, 3, 20, 3, 1000]).unwrap();
+        assert_eq!(within.iter().map(|(l, _)| *l).collect::<Vec<_>>(), vec![3, 20]);
+        assert!((within[0].1 - 7.2 * 7.2).abs() < 1e-3);
+
+        // Allow-lists longer than one chunk are merged into a single top k
+        let all: Vec<i64> = (0..(SEARCH_WITHIN_CHUNK as i64 + 10)).rev().collect();
+        let nearest = idx.search_within(&[99.0, 0.0], 3, &all).unwrap();
+        assert_eq!(nearest.iter().map(|(l, _)| *l).collect::<Vec<_>>(), vec![99, 98, 97]);
+
+        assert!(idx.search_within(&[1.0, 0.0], 5, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_with_negatives() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_negatives.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..100).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        // [50, 0] - 0.5 * [100, 0] = [0, 0]
+        let results = idx
+            .search_with_negatives(&[50.0, 0.0], &[100.0, 0.0], 0.5, 1, 20, 1, None, false)
+            .unwrap();
+        assert_eq!(results[0].0, 0);
+
+        assert!(idx.search_with_negatives(&[50.0, 0.0], &[1.0], 0.5, 1, 20, 1, None, false).is_err());
+    }
+
+    #[test]
+    fn test_search_like_labels() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_like_labels.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..100).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        // Centroid of 10 and 20 is 15; the basket itself is excluded
+        let results = idx.search_like_labels(&[10, 20], &[], 1.0, 3, 20, 1, None, false).unwrap();
+        assert_eq!(results[0].0, 15);
+        assert!(results.iter().all(|(l, _)| *l != 10 && *l != 20));
+
+        // 15 - 0.5 * 10 = 10, which is in the basket, so 9 and 11 come first
+        let pushed = idx.search_like_labels(&[10, 20], &[10], 0.5, 2, 20, 1, None, false).unwrap();
+        let mut labels: Vec<i64> = pushed.iter().map(|(l, _)| *l).collect();
+        labels.sort();
+        assert_eq!(labels, vec![9, 11]);
+
+        assert!(idx.search_like_labels(&[1000], &[], 1.0, 3, 20, 1, None, false).is_err());
+        assert!(idx.search_like_labels(&[], &[], 1.0, 3, 20, 1, None, false).is_err());
+    }
+
+    #[test]
+    fn test_search_sampled() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_sampled.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..2000).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 2000).unwrap();
+
+        // Without a vector index every row is searched
+        let exact = idx.search_sampled(&[500.0, 0.0], 5, 0.1, 20, 1, None, false).unwrap();
+        assert_eq!(exact.fraction, 1.0);
+        assert_eq!(exact.results[0].0, 500);
+
+        // 2 of the 20 partitions a full search probes
+        idx.create_ivf_flat_index(10).unwrap();
+        let sampled = idx.search_sampled(&[500.0, 0.0], 5, 0.1, 20, 1, None, false).unwrap();
+        assert_eq!(sampled.fraction, 0.1);
+        assert_eq!(sampled.results[0].0, 500);
+        assert_eq!(sampled.estimated_rank(0), 10.0);
+        // At least one partition is probed
+        let single = idx.search_sampled(&[500.0, 0.0], 5, 0.01, 20, 1, None, false).unwrap();
+        assert_eq!(single.fraction, 0.05);
+
+        assert!(idx.search_sampled(&[500.0, 0.0], 5, 0.0, 20, 1, None, false).is_err());
+        assert!(idx.search_sampled(&[500.0, 0.0], 5, 1.5, 20, 1, None, false).is_err());
+    }
+
+    #[test]
+    fn test_query_transform_slice_and_normalize() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_transform.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 0.0, 0.0, 1.0], 2).unwrap();
+
+        // A slice must leave queries at the table dimension
+        assert!(idx.set_query_transform(Some("slice(0,3)"), None).is_err());
+        assert!(idx.set_query_transform(Some("slice(1,1) | normalize"), None).is_err());
+
+        // Queries arrive with 3 dims; the corpus holds the normalized first two
+        let version = |idx: &LanceIndex| runtime::block_on(idx.get_table().unwrap().version()).unwrap();
+        let before = version(&idx);
+        idx.set_query_transform(Some("slice(0,2) | normalize"), None).unwrap();
+        // The spec and the (cleared) mean are written in one commit
+        assert_eq!(version(&idx), before + 1);
+        assert!(idx.accepts_query_dim(3));
+        assert!(!idx.accepts_query_dim(1));
+        let results = idx.search(&[0.0, 5.0, 7.0], 1, 20, 1, 0, None, false).unwrap();
+        assert_eq!(results[0].0, 1);
+        assert!(results[0].1.abs() < 1e-6);
+
+        // Center with the stored mean (0.5, 0.5)
+        idx.set_query_transform(Some("center"), None).unwrap();
+        let mean = idx.query_transform().unwrap().mean.unwrap();
+        assert_eq!(mean, vec![0.5, 0.5]);
+        assert!(idx.accepts_query_dim(2));
+        assert!(!idx.accepts_query_dim(3));
+
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.query_transform().unwrap().to_string(), "center");
+        reopened.set_query_transform(None, None).unwrap();
+        assert!(reopened.query_transform().is_none());
+    }
+
+    #[test]
+    fn test_quota_holds_under_concurrent_appends() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_quota_concurrent.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.set_quota(Some(Quota::parse("max_rows=4").unwrap())).unwrap();
+        let handle = &idx;
+        let accepted: Vec<i64> = std::thread::scope(|scope| {
+            let appends: Vec<_> =
+                (0..8).map(|i| scope.spawn(move || handle.add_vector(&[i as f32, 0.0]))).collect();
+            appends.into_iter().filter_map(|append| append.join().unwrap().ok()).collect()
+        });
+        assert_eq!(accepted.len(), 4);
+        assert_eq!(idx.count().unwrap(), 4);
+
+        // A delete moves the table past the cached usage, which is measured again
+        idx.delete(accepted[0]).unwrap();
+        idx.add_vector(&[9.0, 9.0]).unwrap();
+        assert!(idx.add_vector(&[9.0, 9.0]).is_err());
+        assert_eq!(idx.count().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_quota_reject_and_evict() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_quota.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.set_quota(Some(Quota::parse("max_rows=3").unwrap())).unwrap();
+        idx.add_batch(&[0.0; 6], 3).unwrap();
+        let err = idx.add_vector(&[1.0, 1.0]).unwrap_err();
+        let exceeded = err.downcast_ref::<QuotaExceeded>().unwrap();
+        assert_eq!((exceeded.max, exceeded.requested), (3, 4));
+        assert_eq!(idx.count().unwrap(), 3);
+
+        // Evicting by label drops the earliest rows instead
+        idx.set_quota(Some(Quota::parse("max_rows=3, on_exceed=evict_oldest(label)").unwrap()))
+            .unwrap();
+        idx.add_batch(&[1.0; 4], 2).unwrap();
+        assert_eq!(idx.count().unwrap(), 3);
+        // The rejected add consumed label 3
+        let (mut labels, _) = idx.get_all_vectors().unwrap();
+        labels.sort();
+        assert_eq!(labels, vec![2, 4, 5]);
+
+        // Batched retention lets the overflow build up before one delete
+        idx.set_quota(Some(Quota::parse("max_rows=3, on_exceed=evict_oldest, evict_batch=2").unwrap()))
+            .unwrap();
+        idx.add_vector(&[2.0, 2.0]).unwrap();
+        assert_eq!(idx.count().unwrap(), 4);
+        idx.add_vector(&[3.0, 3.0]).unwrap();
+        assert_eq!(idx.count().unwrap(), 3);
+
+        assert!(idx.set_quota(Some(Quota::parse("max_rows=3, on_exceed=evict_oldest(ts)").unwrap())).is_err());
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.quota().unwrap().max_rows, Some(3));
+    }
+
+    #[test]
+    fn test_access_tracking_and_lru_eviction() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_access.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 1.0, 2.0, 2.0], 3).unwrap();
+        assert!(idx
+            .set_quota(Some(Quota::parse("max_rows=3, on_exceed=evict_lru").unwrap()))
+            .is_err());
+
+        idx.set_access_tracking(true).unwrap();
+        idx.search(&[0.0, 0.0], 1, 20, 1, 0, None, false).unwrap();
+        idx.search(&[0.0, 0.0], 1, 20, 1, 0, None, false).unwrap();
+        idx.flush_access_stats().unwrap();
+        idx.search(&[2.0, 2.0], 1, 20, 1, 0, None, false).unwrap();
+
+        // Flushed and buffered hits are combined
+        let stats = idx.access_stats().unwrap();
+        assert_eq!(stats[&0].hits, 2);
+        assert_eq!(stats[&2].hits, 1);
+        let cold = idx.cold_rows(0).unwrap();
+        let cold_labels = cold.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(cold_labels.value(0), 1);
+
+        // Label 1 was never searched, so it is evicted first
+        idx.set_quota(Some(Quota::parse("max_rows=3, on_exceed=evict_lru").unwrap()))
+            .unwrap();
+        idx.add_vector(&[3.0, 3.0]).unwrap();
+        let (mut labels, _) = idx.get_all_vectors().unwrap();
+        labels.sort();
+        assert_eq!(labels, vec![0, 2, 3]);
+        assert!(!idx.access_stats().unwrap().contains_key(&1));
+        assert!(idx.set_access_tracking(false).is_err());
+        // Drop flushes in the background; flush now so the reopened handle sees the hits
+        idx.flush_access_stats().unwrap();
+        drop(idx);
+
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert!(reopened.access_tracking());
+        assert_eq!(reopened.access_stats().unwrap()[&2].hits, 1);
+    }
+
+    #[test]
+    fn test_embedding_model_guard() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_model.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        // Nothing recorded: any declaration passes
+        idx.check_embedding_model(Some("model-a")).unwrap();
+
+        idx.set_embedding_model(Some("model-a")).unwrap();
+        idx.check_embedding_model(Some("model-a")).unwrap();
+        idx.check_embedding_model(None).unwrap();
+        let err = idx.check_embedding_model(Some("model-b")).unwrap_err();
+        assert!(err.to_string().contains("mismatch"));
+        drop(idx);
+
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.embedding_model().as_deref(), Some("model-a"));
+        assert!(reopened.check_embedding_model(Some("model-b")).is_err());
+
+        // A recorded dimension the vectors do not have fails declared ingest and search
+        metadata::set(&reopened.get_table().unwrap(), metadata::EMBEDDING_DIM, Some("3")).unwrap();
+        drop(reopened);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let err = reopened.check_embedding_model(Some("model-a")).unwrap_err();
+        assert!(err.to_string().contains("recorded with 3 dims"), "{}", err);
+        reopened.check_embedding_model(None).unwrap();
+        reopened.set_embedding_model(Some("model-a")).unwrap();
+        reopened.check_embedding_model(Some("model-a")).unwrap();
+
+        metadata::set(&reopened.get_table().unwrap(), metadata::EMBEDDING_DIM, Some("two")).unwrap();
+        drop(reopened);
+        assert!(LanceIndex::open(db_path_str, "vectors", "l2").is_err());
+    }
+
+    #[test]
+    fn test_drift_report_against_baseline() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_drift.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 0.0, 1.0, 0.0], 2).unwrap();
+        idx.tag_drift_baseline("v1").unwrap();
+        assert!(idx.drift_report("v2").is_err());
+
+        // A second embedding space pointing elsewhere
+        idx.add_batch(&[0.0, 1.0, 0.0, 1.0], 2).unwrap();
+        let report = idx.drift_report("v1").unwrap();
+        assert_eq!(report.baseline.count, 2);
+        assert_eq!(report.current.count, 4);
+        assert!((report.centroid_shift() - 0.5f64.sqrt()).abs() < 1e-6);
+
+        // Deletes force a recompute from the table
+        idx.delete_batch(&[2, 3]).unwrap();
+        let report = idx.drift_report("v1").unwrap();
+        assert_eq!(report.current.count, 2);
+        assert!(report.centroid_shift() < 1e-6);
+    }
+
+    #[test]
+    fn test_vector_stats_with_concurrent_recompute() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_drift_concurrent.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let handle = &idx;
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for _ in 0..8 {
+                    handle.add_batch(&[1.0, 0.0], 1).unwrap();
+                }
+            });
+            scope.spawn(move || {
+                for _ in 0..8 {
+                    handle.invalidate_vector_stats();
+                    handle.vector_stats().unwrap();
+                }
+            });
+        });
+        // Each appended vector is counted once, however the recomputes interleaved
+        assert_eq!(idx.vector_stats().unwrap().count, 8);
+    }
+
+    #[test]
+    fn test_column_stats_on_label() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_stats.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 4, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..400).map(|i| i as f32).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        let stats = idx.column_stats("label", false).unwrap();
+        let min = stats.min.as_any().downcast_ref::<Int64Array>().unwrap();
+        let max = stats.max.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(min.value(0), 0);
+        assert_eq!(max.value(0), 99);
+        assert_eq!(stats.null_count, 0);
+        assert_eq!(stats.row_count, 100);
+
+        assert!(idx.column_stats("missing", false).is_err());
+        assert!(idx.column_stats("vector", false).is_err());
+    }
+
+    #[test]
+    fn test_sibling_handles_read_your_writes() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_siblings.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let ingest = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let search = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(search.count().unwrap(), 0);
+
+        let vectors: Vec<f32> = (0..10).flat_map(|i| [i as f32, 0.0]).collect();
+        ingest.add_batch(&vectors, 10).unwrap();
+        assert_eq!(search.count().unwrap(), 10);
+        let results = search.search(&[9.0, 0.0], 1, 1, 1, 0, None, false).unwrap();
+        assert_eq!(results[0].0, 9);
+
+        // Labels continue past the sibling's writes
+        assert_eq!(search.add_vector(&[10.0, 0.0]).unwrap(), 10);
+        search.delete(0).unwrap();
+        assert_eq!(ingest.count().unwrap(), 10);
+    }
+
+    #[test]
+    fn test_writer_lease() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_lease.lance");
+        let db_path_str = db_path.to_str().unwrap();
+        let lock = db_path.join("vectors.writer.lock");
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.set_writer_lease(60).unwrap();
+        assert_eq!(idx.writer_lease(), Some(60));
+        assert!(lock.exists());
+
+        // Handles in the same process share the lease
+        let sibling = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(sibling.writer_lease(), Some(60));
+        sibling.add_vector(&[1.0, 0.0]).unwrap();
+        drop(sibling);
+        idx.set_writer_lease(0).unwrap();
+        assert!(!lock.exists());
+
+        // A live lease from another process blocks writes even without one configured here
+        std::fs::write(&lock, format!("1:0\n{}\n", access::now_ms() + 60_000)).unwrap();
+        let err = idx.add_vector(&[2.0, 0.0]).unwrap_err();
+        assert!(err.to_string().contains("locked by another writer"));
+        assert!(idx.set_writer_lease(60).is_err());
+        std::fs::remove_file(&lock).unwrap();
+        idx.add_vector(&[2.0, 0.0]).unwrap();
+    }
+
+    #[test]
+    fn test_pca_search() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_pca.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 4, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..40)
+            .flat_map(|i| [i as f32, (i % 5) as f32, 0.01 * (i % 2) as f32, 0.0])
+            .collect();
+        idx.add_batch(&vectors, 40).unwrap();
+
+        assert!(idx.search_pca(&[1.0, 1.0, 0.0, 0.0], 1, 1, 4, None, false).is_err());
+        assert!(idx.train_pca(4, 100, None).is_err());
+        let pca = idx.train_pca(2, 100, None).unwrap();
+        assert_eq!(pca.target, 2);
+        assert!(pca.explained_variance > 0.99);
+
+        let results = idx.search_pca(&[12.0, 2.0, 0.0, 0.0], 2, 1, 4, None, false).unwrap();
+        assert_eq!(results[0], (12, 0.0));
+
+        // Rows appended after training are projected too, and reopening keeps the projection
+        let label = idx.add_vector(&[100.0, 0.0, 0.0, 0.0]).unwrap();
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.pca().unwrap().target, 2);
+        let results = reopened.search_pca(&[99.0, 0.0, 0.0, 0.0], 1, 1, 4, None, false).unwrap();
+        assert_eq!(results[0].0, label);
+        assert_eq!(reopened.search(&[99.0, 0.0, 0.0, 0.0], 1, 1, 1, 0, None, false).unwrap()[0].0, label);
+    }
+
+    #[test]
+    fn test_rotation_round_trip() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_rotation.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 4, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..20)
+            .flat_map(|i| [i as f32, i as f32 + 0.5, (i % 3) as f32, 1.0])
+            .collect();
+        idx.add_batch(&vectors, 20).unwrap();
+        assert!(idx.train_rotation(3, 100).is_err());
+        idx.train_rotation(2, 100).unwrap();
+
+        // Reads come back in the original space and searches still find exact matches
+        let v = idx.get_vector(7).unwrap();
+        assert!(v.iter().zip(&vectors[28..32]).all(|(a, b)| (a - b).abs() < 1e-4));
+        let label = idx.add_vector(&[30.0, 30.5, 0.0, 1.0]).unwrap();
+        let results = idx.search(&[30.0, 30.5, 0.0, 1.0], 1, 1, 1, 0, None, false).unwrap();
+        assert_eq!(results[0].0, label);
+        assert!(results[0].1 < 1e-3);
+
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.rotation(), idx.rotation());
+        let results = reopened.search(&[3.0, 3.5, 0.0, 1.0], 1, 1, 1, 0, None, false).unwrap();
+        assert_eq!(results[0].0, 3);
+
+        // Another writable handle would not rotate what it appends
+        let err = idx.train_rotation(2, 100).unwrap_err();
+        assert!(err.to_string().contains("only writable handle"), "{}", err);
+        drop(reopened);
+        idx.train_rotation(2, 100).unwrap();
+        assert_eq!(idx.count().unwrap(), 21);
+    }
+
+    #[test]
+    fn test_approx_stats_by_group() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_approx_stats.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..30).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 30).unwrap();
+
+        // Group by label: every value is distinct, so most overflow into "other"
+        let stats = idx.approx_stats("label", Some(&[0.0, 0.0]), 1.0, 10, false).unwrap();
+        assert_eq!(stats.groups.len(), 10);
+        assert_eq!(stats.other.rows, 20);
+        assert!((stats.distinct_groups - 30.0).abs() < 3.0);
+        let (_, zero) = stats.groups.iter().find(|(v, _)| v.as_deref() == Some("0")).unwrap();
+        assert_eq!(zero.mean_distance(), Some(0.0));
+
+        // Half the rows, drawn at random, each standing for two
+        let sampled = idx.approx_stats("label", None, 0.5, 100, false).unwrap();
+        assert_eq!(sampled.fraction, 0.5);
+        assert_eq!(sampled.groups.len(), 15);
+        assert!(sampled.groups.iter().all(|(_, stats)| stats.rows == 1));
+        let batch = sampled.to_record_batch().unwrap();
+        let counts = batch.column(2).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert!(counts.values().iter().all(|&count| count == 2));
+
+        assert!(idx.approx_stats("missing", None, 1.0, 10, false).is_err());
+        assert!(idx.approx_stats("label", None, 0.0, 10, false).is_err());
+    }
+
+    #[test]
+    fn test_knn_graph() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_knn_graph.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..20).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 20).unwrap();
+
+        let graph = idx.knn_graph(2, 0, 1, 1).unwrap();
+        assert_eq!(graph.num_rows(), 40);
+        let src = graph.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        let dst = graph.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+        for i in 0..graph.num_rows() {
+            assert_ne!(src.value(i), dst.value(i));
+            assert!((src.value(i) - dst.value(i)).abs() <= 2);
+        }
+
+        let sampled = idx.knn_graph(1, 5, 1, 1).unwrap();
+        assert!(sampled.num_rows() <= 5 && sampled.num_rows() > 0);
+        assert!(idx.knn_graph(0, 0, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_search_expand_reaches_next_cluster() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_expand.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        // A chain of points: each hop reaches one step further from the query
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..10).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 10).unwrap();
+
+        let direct = idx.search_expand(&[0.0, 0.0], 2, 0, 2, 0.5, 1, 1, None, false).unwrap();
+        assert_eq!(direct.iter().map(|h| h.label).collect::<Vec<_>>(), vec![0, 1]);
+
+        let expanded = idx.search_expand(&[0.0, 0.0], 2, 2, 2, 0.5, 1, 1, None, false).unwrap();
+        let labels: HashSet<i64> = expanded.iter().map(|h| h.label).collect();
+        assert_eq!(labels.len(), expanded.len());
+        assert!(labels.contains(&2) && labels.contains(&3));
+        assert!(expanded.len() <= 6);
+        assert!(expanded.windows(2).all(|w| w[0].score >= w[1].score));
+        let two = expanded.iter().find(|h| h.label == 2).unwrap();
+        assert_eq!((two.hop, two.distance), (1, 4.0));
+
+        assert!(idx.search_expand(&[0.0, 0.0], 2, 1, 2, 0.0, 1, 1, None, false).is_err());
+    }
+
+    #[test]
+    fn test_index_calls_on_blocking_pool() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_blocking_pool.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = Arc::new(LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let worker = Arc::clone(&idx);
+        crate::runtime::spawn_blocking(move || {
+            let labels = worker.add_batch(&[0.0, 0.0, 5.0, 5.0], 2).unwrap();
+            let hits = worker.search(&[4.0, 4.0], 1, 1, 1, 0, None, false).unwrap();
+            tx.send((labels, hits)).unwrap();
+        });
+        let (labels, hits) = rx.recv_timeout(std::time::Duration::from_secs(30)).unwrap();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(hits[0].0, labels[1]);
+    }
+
+    #[test]
+    fn test_add_batch_buffer_shares_caller_buffer() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_add_batch_buffer.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let buffer = ScalarBuffer::from(vec![1.0f32, 2.0, 3.0, 4.0]);
+        let batch = idx.make_batch_contiguous(&[0, 1], buffer.clone()).unwrap();
+        let list = batch.column(1).as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+        let values = list.values().as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(values.values().as_ptr(), buffer.as_ptr());
+
+        let labels = idx.add_batch_buffer(buffer, 2).unwrap();
+        assert_eq!(labels, vec![0, 1]);
+        assert_eq!(idx.get_vector(1).unwrap(), vec![3.0, 4.0]);
+        assert!(idx.add_batch_buffer(ScalarBuffer::from(vec![1.0f32]), 1).is_err());
+    }
+
+    #[test]
+    fn test_search_with_small_read_batches() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_read_batch_size.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..20).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 20).unwrap();
+
+        let expected = idx.search(&[3.0, 0.0], 5, 1, 1, 0, None, false).unwrap();
+        idx.set_read_batch_size(2);
+        assert_eq!(idx.read_batch_size(), 2);
+        assert_eq!(idx.search(&[3.0, 0.0], 5, 1, 1, 0, None, false).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_auto_refine_without_index() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_auto_refine.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..20).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 20).unwrap();
+
+        // Flat search is exact: auto resolves to no rescoring
+        let plan = idx.refine_plan(AUTO_REFINE, 5);
+        assert!(plan.auto);
+        assert_eq!((plan.factor, plan.rescored, plan.compression), (0, 0, None));
+        assert_eq!(idx.refine_plan(3, 5).rescored, 15);
+        assert_eq!(
+            idx.search(&[3.0, 0.0], 5, 1, AUTO_REFINE, 0, None, false).unwrap(),
+            idx.search(&[3.0, 0.0], 5, 1, 0, 0, None, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_search_dedup_widens_until_k_values() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_dedup.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("doc", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+
+        // 30 chunks of 3 documents, ten consecutive chunks each
+        let values = Float32Array::from((0..30).flat_map(|i| [i as f32, 0.0]).collect::<Vec<_>>());
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(Int64Array::from_iter_values((0..30).map(|i| i / 10))),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        // The first over-fetch (12 rows) only reaches two documents
+        let results = idx.search_dedup(&[0.0, 0.0], 3, "doc", 1, 1, None, false).unwrap();
+        let labels: Vec<i64> = results.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec![0, 10, 20]);
+
+        assert_eq!(idx.search_dedup(&[0.0, 0.0], 5, "doc", 1, 1, None, false).unwrap().len(), 3);
+        assert!(idx.search_dedup(&[0.0, 0.0], 3, "missing", 1, 1, None, false).is_err());
+    }
+
+    #[test]
+    fn test_filtered_search_fills_k() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_hit_budget.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..50).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 50).unwrap();
+
+        let filled = idx.search(&[0.0, 0.0], 5, 1, 1, 0, Some("label >= 40"), false).unwrap();
+        let labels: Vec<i64> = filled.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec![40, 41, 42, 43, 44]);
+        // Fewer matching rows than k: all of them, without an error
+        assert_eq!(idx.search(&[0.0, 0.0], 5, 1, 1, 0, Some("label >= 48"), false).unwrap().len(), 2);
+
+        let exact = idx.exact_filtered_search(&[0.0, 0.0], 5, Some("label >= 40")).unwrap();
+        assert_eq!(exact, filled);
+
+        let budget = HitBudget {
+            max_nprobes: 0,
+            ..HitBudget::default()
+        };
+        idx.set_hit_budget(budget).unwrap();
+        assert_eq!(idx.hit_budget(), budget);
+    }
+
+    #[test]
+    fn test_rescore_metric_ranks_exact_paths() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_rescore_metric.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 0.0, 0.0, 2.0], 2).unwrap();
+        let query = [1.0, 1.125];
+        assert_eq!(idx.search_within(&query, 1, &[0, 1]).unwrap()[0].0, 0);
+
+        // Only the second dimension counts
+        distance::register_metric("test_second_dim", Arc::new(|a, b| (a[1] - b[1]).abs())).unwrap();
+        idx.set_rescore_metric(Some("test_second_dim")).unwrap();
+        assert_eq!(idx.search_within(&query, 2, &[0, 1]).unwrap(), vec![(1, 0.875), (0, 1.125)]);
+
+        assert!(idx.set_rescore_metric(Some("test_unregistered")).is_err());
+        idx.set_rescore_metric(None).unwrap();
+        assert_eq!(idx.search_within(&query, 1, &[0, 1]).unwrap()[0].0, 0);
+    }
+
+    #[test]
+    fn test_search_weighted_masks_dimensions() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_weighted.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 0.0, 0.0, 10.0], 2).unwrap();
+        let query = [1.0, 10.0];
+        assert_eq!(idx.search(&query, 1, 20, 0, 0, None, false).unwrap()[0].0, 1);
+
+        // Masking the second dimension flips the ranking
+        for weight_query in [false, true] {
+            let hits = idx.search_weighted(&query, &[1.0, 0.0], weight_query, 2, 20, 0, None, false).unwrap();
+            assert_eq!(hits, vec![(0, 0.0), (1, 1.0)]);
+        }
+        let hits = idx.search_weighted(&query, &[4.0, 1.0], false, 2, 20, 0, None, false).unwrap();
+        assert_eq!(hits, vec![(1, 4.0), (0, 100.0)]);
+
+        assert!(idx.search_weighted(&query, &[1.0], false, 2, 20, 0, None, false).is_err());
+        assert!(idx.search_weighted(&query, &[0.0, 0.0], false, 2, 20, 0, None, false).is_err());
+    }
+
+    #[test]
+    fn test_search_rows_in_hit_order() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_rows.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 5.0, 0.0, 1.0, 0.0], 3).unwrap();
+
+        let columns = vec!["vector".to_string()];
+        let (hits, batch) = idx.search_rows(&[5.0, 0.0], 3, 20, 0, None, &columns, false).unwrap();
+        let labels: Vec<i64> = hits.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec![1, 2, 0]);
+        assert_eq!(batch.num_columns(), 1);
+        let vectors = batch.column(0).as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+        let first = vectors.value(0);
+        assert_eq!(first.as_any().downcast_ref::<Float32Array>().unwrap().values(), &[5.0, 0.0]);
+
+        let fields = idx.result_fields(&[], false).unwrap();
+        assert_eq!(fields.iter().map(|f| f.name().as_str()).collect::<Vec<_>>(), vec!["label", "vector"]);
+        assert!(idx.result_fields(&["missing".to_string()], false).is_err());
+    }
+
+    #[test]
+    fn test_row_addresses_follow_fragments() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_row_addresses.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0], 2).unwrap();
+        idx.add_batch(&[2.0, 0.0], 1).unwrap();
+
+        let batch = idx.row_addresses().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        let labels = column("label");
+        let labels = labels.as_any().downcast_ref::<Int64Array>().unwrap();
+        let row_ids = column("_rowid");
+        let row_ids = row_ids.as_any().downcast_ref::<UInt64Array>().unwrap();
+        let fragments = column("fragment_id");
+        let fragments = fragments.as_any().downcast_ref::<UInt32Array>().unwrap();
+        assert_eq!(labels.values(), &[0, 1, 2]);
+        // Each append writes its own fragment
+        assert_eq!(fragments.value(0), fragments.value(1));
+        assert_ne!(fragments.value(1), fragments.value(2));
+        assert_eq!(row_ids.value(2) >> 32, fragments.value(2) as u64);
+    }
+
+    #[test]
+    fn test_merge_refuses_values_the_target_type_cannot_hold() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_merge_cast.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let create = |name: &str, n_type: DataType| {
+            let schema = Schema::new(vec![
+                Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+                Field::new("n", n_type, true),
+            ]);
+            let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+            (unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", name) }.unwrap(), schema)
+        };
+        let (source, schema) = create("source", DataType::Int64);
+        let (target, _) = create("target", DataType::Int32);
+
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 0.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+            Arc::new(Int64Array::from(vec![7, 5_000_000_000])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { source.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        // 5_000_000_000 does not fit an INT32; the merge fails instead of storing NULL
+        let err = target.merge_from(&source, &[0, 1], false).unwrap_err();
+        assert!(err.to_string().contains("column n cannot be converted"), "{}", err);
+        assert_eq!(target.count().unwrap(), 0);
+
+        let report = target.merge_from(&source, &[0], false).unwrap();
+        assert_eq!(report.mapping.len(), 1);
+        let (stored, _) = target.scan_with_labels(&["n".to_string()], None, false).unwrap();
+        let n = stored.column_by_name("n").unwrap().as_any().downcast_ref::<arrow_array::Int32Array>().unwrap();
+        assert_eq!(n.values(), &[7]);
+    }
+
+    #[test]
+    fn test_merge_reports_index_staleness() {
+        let dir = temp_dir();
+        let target_path = dir.path().join("test_merge_target.lance");
+        let source_path = dir.path().join("test_merge_source.lance");
+
+        let target = LanceIndex::create(target_path.to_str().unwrap(), 2, "l2", "vectors").unwrap();
+        let source = LanceIndex::create(source_path.to_str().unwrap(), 2, "l2", "vectors").unwrap();
+        target.add_batch(&[0.0, 0.0], 1).unwrap();
+        source.add_batch(&[1.0, 0.0, 2.0, 0.0], 2).unwrap();
+
+        // Without a vector index there is nothing to optimize or report
+        target.set_merge_indexing(MergeIndexing::Background).unwrap();
+        let report = target.merge_from(&source, &[1], false).unwrap();
+        assert_eq!(report.mapping, vec![(1, 1)]);
+        assert_eq!(report.staleness, None);
+        assert!(!report.optimize_scheduled);
+        assert_eq!(target.count().unwrap(), 2);
+        assert_eq!(target.index_staleness().unwrap(), None);
+    }
+
+    #[test]
+    fn test_scan_ordered_limit() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_scan_ordered.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..5).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 5).unwrap();
+
+        let order = OrderBy { column: "label".to_string(), descending: true };
+        let columns = vec!["vector".to_string()];
+        let reader = idx.scan_ordered(&columns, Some("label < 4"), false, &order, Some(2), 1).unwrap();
+        assert_eq!(reader.schema().fields().len(), 1);
+        let firsts: Vec<f32> = reader
+            .map(|batch| {
+                let batch = batch.unwrap();
+                let vectors = batch.column(0).as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+                let first = vectors.value(0);
+                first.as_any().downcast_ref::<Float32Array>().unwrap().value(0)
+            })
+            .collect();
+        assert_eq!(firsts, vec![3.0, 2.0]);
+
+        let by_vector = OrderBy { column: "vector".to_string(), descending: false };
+        assert!(idx.scan_ordered(&[], None, false, &by_vector, None, 1).is_err());
+    }
+
+    #[test]
+    fn test_label_watermark_survives_deletes_and_repairs() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_watermark.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        for i in 0..4 {
+            idx.add_vector(&[i as f32, 0.0]).unwrap();
+        }
+        // Deleting the highest label must not make it reusable after reopen
+        idx.delete(3).unwrap();
+        drop(idx);
+        let idx = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let table = idx.get_table().unwrap();
+        let before = runtime::block_on(table.version()).unwrap();
+        assert_eq!(idx.add_vector(&[9.0, 0.0]).unwrap(), 4);
+        // Appends commit only their data
+        assert_eq!(runtime::block_on(table.version()).unwrap(), before + 1);
+
+        // Fragments past the recorded watermark are read on open
+        metadata::set(&table, metadata::LABEL_WATERMARK, Some("1@1")).unwrap();
+        drop(idx);
+        let idx = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(idx.add_vector(&[9.0, 0.0]).unwrap(), 5);
+
+        metadata::set(&idx.get_table().unwrap(), metadata::LABEL_WATERMARK, Some("garbage")).unwrap();
+        assert_eq!(idx.repair_label_watermark().unwrap(), 5);
+        let table = idx.get_table().unwrap();
+        let recorded = LanceIndex::recorded_label_watermark(&table).unwrap().unwrap();
+        assert_eq!(recorded.max_label, 5);
+    }
+
+    #[test]
+    fn test_read_snapshot_pins_version() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_snapshot.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        for i in 0..3 {
+            idx.add_vector(&[i as f32, 0.0]).unwrap();
+        }
+        let snapshot = idx.begin_read_snapshot().unwrap();
+        assert!(snapshot.snapshot_version().is_some());
+
+        // Ingestion continues on the live handle; the snapshot keeps seeing 3 rows
+        idx.add_vector(&[3.0, 0.0]).unwrap();
+        idx.delete(0).unwrap();
+        assert_eq!(snapshot.count().unwrap(), 3);
+        assert_eq!(idx.count().unwrap(), 3);
+        let hits = snapshot.search(&[0.0, 0.0], 1, 20, 1, 0, None, false).unwrap();
+        assert_eq!(hits[0].0, 0);
+
+        let err = snapshot.add_vector(&[9.0, 0.0]).unwrap_err();
+        assert!(err.to_string().contains("read-only"), "{err}");
+        snapshot.release_snapshot().unwrap();
+        assert!(idx.begin_read_snapshot().unwrap().release_snapshot().is_ok());
+    }
+
+    #[test]
+    fn test_add_chunked_shares_parent() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_chunked.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item, 2), true),
+            Field::new("body", DataType::Utf8, true),
+            Field::new("doc", DataType::Int64, true),
+            Field::new("title", DataType::Utf8, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+
+        // Embeds a chunk as (word count, 0)
+        chunk::register_embedder(
+            "test_word_count",
+            Arc::new(|texts: &[&str], dim: usize| {
+                Ok(texts
+                    .iter()
+                    .flat_map(|t| {
+                        let mut v = vec![0.0; dim];
+                        v[0] = t.split_whitespace().count() as f32;
+                        v
+                    })
+                    .collect())
+            }),
+        )
+        .unwrap();
+        let body = RecordBatch::try_from_iter(vec![(
+            "body",
+            Arc::new(StringArray::from(vec!["a b c d e", "f"])) as ArrayRef,
+        )])
+        .unwrap();
+        assert!(idx.add_chunked(&body).is_err());
+        assert!(idx.set_chunking(Some(Chunking::parse("column=title, parent=body, size=2, embedder=e").unwrap())).is_err());
+
+        let spec = "column=body, parent=doc, unit=tokens, size=2, overlap=1, embedder=test_word_count";
+        idx.set_chunking(Some(Chunking::parse(spec).unwrap())).unwrap();
+        let chunks = idx.add_chunked(&body).unwrap();
+        assert_eq!(chunks, vec![(0, 0), (0, 1), (0, 2), (0, 3), (1, 4)]);
+
+        let order = OrderBy { column: "label".to_string(), descending: false };
+        let columns = vec!["doc".to_string(), "body".to_string()];
+        let batch = idx.scan_ordered(&columns, None, false, &order, None, 16).unwrap().next().unwrap().unwrap();
+        let docs = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(docs.values().to_vec(), vec![0, 0, 0, 0, 4]);
+        let bodies = batch.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!((bodies.value(0), bodies.value(3), bodies.value(4)), ("a b", "d e", "f"));
+
+        // The stage is persisted with the table
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.chunking().unwrap().to_string(), Chunking::parse(spec).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_search_parents_returns_best_chunk_per_parent() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_parents.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("doc", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+
+        // 20 chunks of documents 100 and 101, then two rows without a parent
+        let values = Float32Array::from((0..22).flat_map(|i| [i as f32, 0.0]).collect::<Vec<_>>());
+        let docs = Int64Array::from_iter((0..22).map(|i| (i < 20).then_some(100 + i / 10)));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(docs),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        let hits = idx.search_parents(&[0.0, 0.0], 3, Some("doc"), 1, 1, None, false).unwrap();
+        let found: Vec<(i64, i64)> = hits.iter().map(|h| (h.parent, h.label)).collect();
+        assert_eq!(found, vec![(100, 0), (101, 10), (20, 20)]);
+        assert_eq!(hits[0].distance, 0.0);
+
+        // Without a column the chunking stage names it
+        assert!(idx.search_parents(&[0.0, 0.0], 3, None, 1, 1, None, false).is_err());
+        assert!(idx.search_parents(&[0.0, 0.0], 3, Some("vector"), 1, 1, None, false).is_err());
+    }
+
+    #[test]
+    fn test_run_maintenance_reports_each_step() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_maintenance.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        // Each append writes its own fragment
+        for i in 0..4 {
+            idx.add_batch(&[i as f32, 0.0], 1).unwrap();
+        }
+
+        let plan = MaintenancePlan::parse("optimize; compact(3); prune(7); verify").unwrap();
+        let report = idx.run_maintenance(&plan).unwrap();
+        let statuses: Vec<StepStatus> = report.steps.iter().map(|s| s.status).collect();
+        // No vector index to optimize and no version is a week old
+        assert_eq!(
+            statuses,
+            vec![StepStatus::Skipped, StepStatus::Done, StepStatus::Skipped, StepStatus::Done]
+        );
+        assert!(report.succeeded());
+        assert_eq!(report.steps[3].detail, "4 rows, labels consistent");
+        assert_eq!(report.to_record_batch().unwrap().num_rows(), 4);
+
+        // Compacted into a single fragment, so the threshold is no longer reached
+        let report = idx.run_maintenance(&MaintenancePlan::parse("compact(3)").unwrap()).unwrap();
+        assert_eq!(report.steps[0].status, StepStatus::Skipped);
+        assert_eq!(idx.count().unwrap(), 4);
+
+        // An age no timestamp can reach fails the step instead of panicking
+        let plan = MaintenancePlan::parse("prune(18446744073709551615); verify").unwrap();
+        let report = idx.run_maintenance(&plan).unwrap();
+        assert_eq!(report.steps[0].status, StepStatus::Failed);
+        assert!(report.steps[0].detail.contains("out of range"), "{}", report.steps[0].detail);
+        assert_eq!(report.steps[1].status, StepStatus::Skipped);
+    }
+
+    #[test]
+    fn test_handles_are_listed_until_dropped() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_handles.lance");
+        let db_path_str = db_path.to_str().unwrap();
+        let listed = || -> Vec<handles::HandleInfo> {
+            handles::list().into_iter().filter(|h| h.path == db_path_str).collect()
+        };
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.begin_append_session().unwrap();
+        idx.add_batch(&[1.0, 0.0, 2.0, 0.0], 2).unwrap();
+        assert_eq!(listed()[0].buffered_rows, 2);
+        idx.flush_appends().unwrap();
+        assert_eq!(listed()[0].buffered_rows, 0);
+
+        let snapshot = idx.begin_read_snapshot().unwrap();
+        let read_only: Vec<bool> = listed().iter().map(|h| h.read_only).collect();
+        assert_eq!(read_only, vec![false, true]);
+
+        snapshot.release_snapshot().unwrap();
+        drop(idx);
+        assert!(listed().is_empty());
+    }
+
+    #[test]
+    fn test_idempotency_tokens_make_retries_no_ops() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_idempotency.lance");
+        let source_path = dir.path().join("test_idempotency_source.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let add = |rows: usize, token: Option<&str>| -> Result<Vec<i64>> {
+            let values = Float32Array::from((0..rows).flat_map(|i| [i as f32, 0.0]).collect::<Vec<_>>());
+            let columns: Vec<ArrayRef> = vec![Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None))];
+            let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.add_batch_arrow_with_token(&mut array_schema, &mut array, token) }
+        };
+
+        assert_eq!(add(3, Some("batch-1")).unwrap(), vec![0, 1, 2]);
+        assert_eq!(add(3, Some("batch-1")).unwrap(), vec![0, 1, 2]);
+        assert_eq!(idx.count().unwrap(), 3);
+        assert!(add(2, Some("batch-1")).is_err());
+        assert!(add(1, Some("bad token")).is_err());
+        assert_eq!(add(1, None).unwrap(), vec![3]);
+
+        idx.delete_batch_with_token(&[0], Some("delete-1")).unwrap();
+        idx.delete_batch_with_token(&[0], Some("delete-1")).unwrap();
+        assert_eq!(idx.count().unwrap(), 3);
+
+        let source = LanceIndex::create(source_path.to_str().unwrap(), 2, "l2", "vectors").unwrap();
+        source.add_batch(&[5.0, 0.0, 6.0, 0.0], 2).unwrap();
+        let first = idx.merge_from_with_token(&source, &[0, 1], false, Some("merge-1")).unwrap();
+        let retry = idx.merge_from_with_token(&source, &[0, 1], false, Some("merge-1")).unwrap();
+        assert_eq!(first.mapping, vec![(0, 4), (1, 5)]);
+        assert_eq!(retry.mapping, first.mapping);
+        assert_eq!(idx.count().unwrap(), 5);
+
+        // A failed mutation releases its token
+        assert!(idx.with_token("delete-2", || Err::<((), _), _>(anyhow!("failed")), |_| Ok(())).is_err());
+        idx.delete_batch_with_token(&[2], Some("delete-2")).unwrap();
+        assert_eq!(idx.count().unwrap(), 4);
+
+        // A claim that was never recorded blocks retries instead of applying them again
+        let sidecar = idx
+            .sidecar_table(&idempotency::sidecar_table_name("vectors"), idempotency::sidecar_schema(), false)
+            .unwrap()
+            .unwrap();
+        let claim = idempotency::to_record_batch("delete-3", "crashed", access::now_ms() + 60_000, None).unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(claim)], idempotency::sidecar_schema());
+        runtime::block_on(sidecar.add(Box::new(reader)).execute()).unwrap();
+        assert!(idx.delete_batch_with_token(&[3], Some("delete-3")).is_err());
+        assert_eq!(idx.count().unwrap(), 4);
+
+        // Tokens survive reopening the table
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        reopened.delete_batch_with_token(&[1], Some("delete-1")).unwrap();
+        assert_eq!(reopened.count().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_remap_labels_validates_and_relabels() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_remap_labels.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 2.0, 0.0], 3).unwrap();
+
+        assert!(idx.remap_labels(&[(0, 5), (1, 5)]).is_err());
+        assert!(idx.remap_labels(&[(0, 5), (0, 6)]).is_err());
+        assert!(idx.remap_labels(&[(7, 8)]).is_err());
+        assert!(idx.remap_labels(&[(0, 2)]).is_err());
+
+        // Swaps are allowed; the row keeps its vector
+        assert_eq!(idx.remap_labels(&[(0, 1), (1, 0), (2, 10)]).unwrap(), 3);
+        let hits = idx.search(&[0.0, 0.0], 1, 1, 1, 0, None, false).unwrap();
+        assert_eq!(hits[0].0, 1);
+        assert_eq!(idx.verify_labels().unwrap(), "3 rows, labels consistent");
+        assert_eq!(idx.add_vector(&[3.0, 0.0]).unwrap(), 11);
+    }
+
+    #[test]
+    fn test_constraints_reject_skip_and_dead_letter() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_constraints.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("category", DataType::Utf8, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let add = |categories: Vec<Option<&str>>| -> Result<Vec<i64>> {
+            let n = categories.len();
+            let values = Float32Array::from((0..n).flat_map(|i| [i as f32, 0.0]).collect::<Vec<_>>());
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+                Arc::new(StringArray::from(categories)),
+            ];
+            let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }
+        };
+
+        assert!(idx.set_constraints(Some(Constraints::parse("vector not null").unwrap())).is_err());
+        idx.set_constraints(Some(Constraints::parse("category in (news, blog)").unwrap())).unwrap();
+        let err = add(vec![Some("news"), Some("spam")]).unwrap_err();
+        assert_eq!(err.downcast_ref::<ConstraintViolated>().unwrap().first.row, 1);
+        assert_eq!(idx.count().unwrap(), 0);
+
+        idx.set_constraints(Some(Constraints::parse("category in (news, blog); on_violation=skip").unwrap()))
+            .unwrap();
+        assert_eq!(add(vec![Some("spam"), Some("news"), None]).unwrap(), vec![-1, 0, 1]);
+        assert!(idx.rejects().unwrap().is_empty());
+
+        idx.set_constraints(Some(Constraints::parse("category not null; on_violation=dead_letter").unwrap()))
+            .unwrap();
+        assert_eq!(add(vec![Some("blog"), None]).unwrap(), vec![2, -1]);
+        let rejected = idx.rejects().unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!((rejected[0].source_row, rejected[0].reason.as_str()), (1, "category is NULL"));
+        assert!(rejected[0].row.ends_with("category=NULL"));
+        assert_eq!(idx.count().unwrap(), 3);
+
+        // Constraints are persisted with the table
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.constraints().unwrap().on_violation, OnViolation::DeadLetter);
+    }
+
+    #[test]
+    fn test_invalid_rows_error_skip_and_dead_letter() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_invalid_rows.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![vector.clone(), Field::new("n", DataType::Int32, true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        // n arrives as text, so "x" fails its cast
+        let add = |xs: Vec<f32>, ns: Vec<&str>| -> Result<Vec<i64>> {
+            let values = Float32Array::from(xs.iter().flat_map(|x| [*x, 0.0]).collect::<Vec<_>>());
+            let fields = Schema::new(vec![vector.clone(), Field::new("n", DataType::Utf8, true)]).fields().clone();
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+                Arc::new(StringArray::from(ns)),
+            ];
+            let data = StructArray::new(fields, columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }
+        };
+
+        let err = add(vec![1.0, f32::NAN], vec!["1", "2"]).unwrap_err();
+        assert!(err.to_string().contains("invalid row 1"), "{}", err);
+        assert_eq!(idx.count().unwrap(), 0);
+
+        idx.set_on_invalid(OnViolation::Skip).unwrap();
+        assert_eq!(add(vec![1.0, f32::INFINITY, 3.0], vec!["1", "2", "x"]).unwrap(), vec![0, -1, -1]);
+        assert!(idx.rejects().unwrap().is_empty());
+
+        idx.set_on_invalid(OnViolation::DeadLetter).unwrap();
+        assert_eq!(add(vec![f32::NAN, 5.0, 6.0], vec!["4", "x", "6"]).unwrap(), vec![-1, -1, 1]);
+        let rejected: Vec<(i64, String)> =
+            idx.rejects().unwrap().into_iter().map(|r| (r.source_row, r.reason)).collect();
+        assert_eq!(rejected, vec![
+            (0, "vector has NaN or infinite elements".to_string()),
+            (1, "column n: cannot convert value to Int32".to_string()),
+        ]);
+        assert_eq!(idx.count().unwrap(), 2);
+
+        // The mode is persisted with the table
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.on_invalid(), OnViolation::DeadLetter);
+    }
+
+    #[test]
+    fn test_add_batch_arrow_matches_columns_by_name() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_column_matching.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![
+            vector.clone(),
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let add = |fields: Vec<Field>, columns: Vec<ArrayRef>| -> Result<Vec<i64>> {
+            let data = StructArray::new(Schema::new(fields).fields().clone(), columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }
+        };
+        let vectors = || -> ArrayRef {
+            Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(Float32Array::from(vec![1.0, 2.0])), None))
+        };
+        let int = |v: i64| -> ArrayRef { Arc::new(Int64Array::from(vec![v])) };
+        let count = |filter: &str| {
+            runtime::block_on(idx.get_table().unwrap().count_rows(Some(filter.to_string()))).unwrap()
+        };
+        let a = Field::new("a", DataType::Int64, true);
+        let b = Field::new("b", DataType::Int64, true);
+        let extra = Field::new("extra", DataType::Int64, true);
+
+        // Positional (the default) puts b's value into a
+        assert_eq!(idx.column_matching(), ColumnMatching::Positional);
+        add(vec![vector.clone(), b.clone(), a.clone()], vec![vectors(), int(2), int(1)]).unwrap();
+        assert_eq!(count("a = 2"), 1);
+
+        idx.set_column_matching(ColumnMatching::Strict).unwrap();
+        add(vec![vector.clone(), b.clone(), a.clone()], vec![vectors(), int(2), int(1)]).unwrap();
+        assert_eq!(count("a = 1 AND b = 2"), 1);
+        let err = add(vec![vector.clone(), a.clone()], vec![vectors(), int(1)]).unwrap_err();
+        assert!(err.to_string().contains("missing column b"), "{}", err);
+        let err = add(vec![vector.clone(), a.clone(), b.clone(), extra.clone()], vec![
+            vectors(),
+            int(1),
+            int(2),
+            int(3),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown column extra"), "{}", err);
+
+        idx.set_column_matching(ColumnMatching::Lenient).unwrap();
+        add(vec![extra, a, vector], vec![int(3), int(7), vectors()]).unwrap();
+        assert_eq!(count("a = 7 AND b IS NULL"), 1);
+
+        // The mode is persisted with the table
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.column_matching(), ColumnMatching::Lenient);
+    }
+
+    #[test]
+    fn test_hybrid_search_fusion() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_hybrid.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let body = Field::new("body", DataType::Utf8, true);
+        let schema = Schema::new(vec![vector, body]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 0.0, 5.0, 5.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(StringArray::from(vec!["red apple", "green pear", "red wine"])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        assert!(idx.create_fts_index("vector").is_err());
+        idx.create_fts_index("body").unwrap();
+        let labels = |fusion: Fusion, k: usize| -> Vec<i64> {
+            let hits = idx.hybrid_search(&[0.0, 0.0], "wine", Some("body"), k, 1, 0, None, fusion, false).unwrap();
+            hits.into_iter().map(|(label, _)| label).collect()
+        };
+
+        // Semantic only: the nearest vector wins; lexical only: the matching text
+        assert_eq!(labels(Fusion::Linear { vector_weight: 1.0 }, 1), vec![0]);
+        assert_eq!(labels(Fusion::Linear { vector_weight: 0.0 }, 1), vec![2]);
+        // Row 2 is ranked by both sides, so RRF puts it above row 1
+        let rrf = labels(Fusion::default(), 3);
+        assert_eq!(rrf.len(), 3);
+        assert!(rrf.iter().position(|l| *l == 2) < rrf.iter().position(|l| *l == 1));
+        assert!(idx.hybrid_search(&[0.0, 0.0], "wine", Some("nope"), 1, 1, 0, None, Fusion::MaxScore, false).is_err());
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        use arrow_array::Int32Array;
+
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_apply_changes.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![vector, Field::new("a", DataType::Int64, true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 3.0, 0.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(Int64Array::from(vec![10, 11, 12, 13])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        let labels = unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        // The scan leads with the label even when not asked for it
+        let (batch, version) = idx.scan_with_labels(&["a".to_string()], Some("a >= 12"), false).unwrap();
+        assert_eq!(batch.schema().field(0).name(), "label");
+        assert_eq!(batch.num_rows(), 2);
+
+        // Set a on one row, delete another, in one version
+        let updates = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("a", DataType::Int32, true),
+                Field::new("label", DataType::Int64, false),
+            ])),
+            vec![Arc::new(Int32Array::from(vec![42])), Arc::new(Int64Array::from(vec![labels[2]]))],
+        )
+        .unwrap();
+        let applied = idx.apply_changes(&[labels[3]], Some(&updates), Some(version)).unwrap();
+        assert_eq!((applied.deleted, applied.updated, applied.version), (1, 1, version + 1));
+        assert_eq!(idx.count().unwrap(), 3);
+        let (batch, _) = idx.scan_with_labels(&["a".to_string(), "vector".to_string()], Some("a = 42"), false).unwrap();
+        assert_eq!(batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap().value(0), labels[2]);
+        assert_eq!(idx.get_vector(labels[2]).unwrap(), vec![2.0, 0.0]);
+
+        // Planned against a stale version, or naming missing rows
+        let err = idx.apply_changes(&[labels[0]], None, Some(version)).unwrap_err();
+        assert!(err.to_string().contains("table changed since version"), "{}", err);
+        let err = idx.apply_changes(&[labels[3]], None, None).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+        let err = idx.apply_changes(&[labels[2]], Some(&updates), None).unwrap_err();
+        assert!(err.to_string().contains("both updated and deleted"), "{}", err);
+        assert_eq!(idx.count().unwrap(), 3);
+
+        // A scoped handle cannot move rows out of its scope
+        let scoped = LanceIndex::open_scoped(db_path_str, "vectors", "l2", "a >= 11").unwrap();
+        let err = scoped.apply_changes(&[], Some(&updates), None).unwrap_err();
+        assert!(err.to_string().contains("scope"), "{}", err);
+        drop(scoped);
+
+        // A delete landing after the planned version fails a change to the same fragment
+        let (_, planned) = idx.scan_with_labels(&["a".to_string()], None, false).unwrap();
+        idx.apply_changes(&[labels[0]], None, None).unwrap();
+        let table = idx.get_table().unwrap();
+        let stale = idx.commit_changes(&table, planned, None, Some(format!("label = {}", labels[1])));
+        assert!(stale.unwrap_err().to_string().contains("not committed"));
+        assert_eq!(idx.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_backup_incremental() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_backup.lance");
+        let db_path_str = db_path.to_str().unwrap();
+        let dest = dir.path().join("backup").join("vectors.lance");
+        let dest_str = dest.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0], 2).unwrap();
+        let full = idx.backup_incremental(dest_str, None).unwrap();
+        assert!(full.versions >= 2 && full.files > full.versions, "{:?}", full);
+
+        // A stale base version is refused; the chained backup copies only the new version
+        assert!(idx.backup_incremental(dest_str, Some(full.version + 1)).is_err());
+        idx.add_batch(&[2.0, 0.0], 1).unwrap();
+        idx.delete(0).unwrap();
+        let incremental = idx.backup_incremental(dest_str, Some(full.version)).unwrap();
+        // The append, the label watermark recorded before the first delete, and the delete
+        assert_eq!(incremental.versions, 3);
+        // Their manifests, the new data file and the deletion file
+        assert_eq!(incremental.files, incremental.versions + 2);
+
+        // The backup opens at either point in time
+        let restored = runtime::block_on(lance::dataset::Dataset::open(dest_str)).unwrap();
+        assert_eq!(restored.version().version, incremental.version);
+        assert_eq!(runtime::block_on(restored.count_rows(None)).unwrap(), 2);
+        let earlier = runtime::block_on(restored.checkout_version(full.version)).unwrap();
+        assert_eq!(runtime::block_on(earlier.count_rows(None)).unwrap(), 2);
+        let labels = runtime::block_on(earlier.scan().project(&["label"]).unwrap().try_into_batch()).unwrap();
+        assert_eq!(labels.column(0).as_any().downcast_ref::<Int64Array>().unwrap().values(), &[0, 1]);
+    }
+
+    #[test]
+    fn test_search_with_columns() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_columns.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![vector, Field::new("title", DataType::Utf8, true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 3.0, 0.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(StringArray::from(vec!["a", "b", "c", "d"])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        let labels = unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        let collect = |reader: Box<dyn RecordBatchReader + Send>| {
+            let schema = reader.schema();
+            let batches: Vec<RecordBatch> = reader.collect::<std::result::Result<_, _>>().unwrap();
+            concat_batches(&schema, &batches).unwrap()
+        };
+        let title = ["title".to_string()];
+        let batch = collect(idx.search_with_columns(&[0.9, 0.0], 2, 1, 0, None, &title, false).unwrap());
+        let names: Vec<&str> = batch.schema().fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, vec!["label", "distance", "title"]);
+        let found = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(found.values(), &[labels[1], labels[0]]);
+        let titles = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!((titles.value(0), titles.value(1)), ("b", "a"));
+
+        // A filter that leaves fewer than k rows still pairs each hit with its row
+        let batch = collect(idx.search_with_columns(&[0.0, 0.0], 3, 1, 0, Some("title = 'd'"), &title, false).unwrap());
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.column(2).as_any().downcast_ref::<StringArray>().unwrap().value(0), "d");
+
+        let label = ["label".to_string()];
+        let batch = collect(idx.search_with_columns(&[0.0, 0.0], 1, 1, 0, None, &label, false).unwrap());
+        assert_eq!(batch.num_columns(), 2);
+    }
+
+    #[test]
+    fn test_upsert_batch_arrow() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_upsert.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let doc_id = Field::new("doc_id", DataType::Utf8, true);
+        let schema = Schema::new(vec![vector.clone(), doc_id.clone()]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let rows = |fields: Vec<Field>, labels: Option<Vec<i64>>, vectors: Vec<f32>, ids: Vec<&str>| {
+            let values = Float32Array::from(vectors);
+            let mut columns: Vec<ArrayRef> = Vec::new();
+            if let Some(labels) = labels {
+                columns.push(Arc::new(Int64Array::from(labels)));
+            }
+            columns.push(Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)));
+            columns.push(Arc::new(StringArray::from(ids)));
+            let data = StructArray::new(fields.into(), columns, None).into_data();
+            arrow::ffi::to_ffi(&data).unwrap()
+        };
+        let (mut array, mut array_schema) =
+            rows(vec![vector.clone(), doc_id.clone()], None, vec![0.0; 4], vec!["a", "b"]);
+        assert_eq!(unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap(), vec![0, 1]);
+
+        // Keyed on doc_id: "b" keeps its label, "c" gets the next one
+        let (mut array, mut array_schema) =
+            rows(vec![vector.clone(), doc_id.clone()], None, vec![1.0, 1.0, 2.0, 2.0], vec!["b", "c"]);
+        let defaults = UpsertOptions::default();
+        let report =
+            unsafe { idx.upsert_batch_arrow(&mut array_schema, &mut array, Some("doc_id"), &defaults) }.unwrap();
+        assert_eq!(report, UpsertReport { labels: vec![1, 2], inserted: 1, updated: 1, ..Default::default() });
+        assert_eq!(idx.count().unwrap(), 3);
+        assert_eq!(idx.get_vector(1).unwrap(), vec![1.0, 1.0]);
+
+        // Keyed on label: label 10 is inserted as given and later appends go past it
+        let fields = vec![Field::new("label", DataType::Int64, false), vector.clone(), doc_id.clone()];
+        let (mut array, mut array_schema) =
+            rows(fields.clone(), Some(vec![0, 10]), vec![3.0, 3.0, 4.0, 4.0], vec!["a", "z"]);
+        let report = unsafe { idx.upsert_batch_arrow(&mut array_schema, &mut array, None, &defaults) }.unwrap();
+        assert_eq!((report.inserted, report.updated), (1, 1));
+        assert_eq!(idx.get_vector(0).unwrap(), vec![3.0, 3.0]);
+        assert_eq!(idx.add_batch(&[5.0, 5.0], 1).unwrap(), vec![11]);
+
+        let (mut array, mut array_schema) = rows(vec![vector, doc_id], None, vec![0.0; 4], vec!["a", "a"]);
+        let err =
+            unsafe { idx.upsert_batch_arrow(&mut array_schema, &mut array, Some("doc_id"), &defaults) }.unwrap_err();
+        assert!(err.to_string().contains("appears twice"));
+        let (mut array, mut array_schema) = rows(fields, Some(vec![0, 1]), vec![0.0; 4], vec!["a", "b"]);
+        assert!(unsafe { idx.upsert_batch_arrow(&mut array_schema, &mut array, Some("doc_id"), &defaults) }.is_err());
+        assert_eq!(idx.count().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_update_where() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_update_where.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![vector, Field::new("status", DataType::Utf8, true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let values = Float32Array::from(vec![0.0, 0.0, 1.0, 0.0, 2.0, 0.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(StringArray::from(vec!["new", "new", "done"])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+
+        let updated = idx.update_where("status = 'new' AND label > 0", &[("status", "'archived'")], false).unwrap();
+        assert_eq!(updated, 1);
+        let (batch, _) = idx.scan_with_labels(&["status".to_string()], None, false).unwrap();
+        // Lance rewrites updated rows into a new fragment, so compare by label
+        let labels = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        let status = batch.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        let mut rows: Vec<(i64, &str)> = labels.values().iter().copied().zip(status.iter().flatten()).collect();
+        rows.sort_unstable();
+        assert_eq!(rows, vec![(0, "new"), (1, "archived"), (2, "done")]);
+        assert_eq!(idx.get_vector(1).unwrap(), vec![1.0, 0.0]);
+
+        assert!(idx.update_where("true", &[("vector", "vector")], false).is_err());
+        assert!(idx.update_where("true", &[("label", "label + 1")], false).is_err());
+        assert!(idx.update_where("true", &[("missing", "1")], false).is_err());
+        assert!(idx.update_where("", &[("status", "'x'")], false).is_err());
+
+        // A scoped handle cannot move rows out of its scope
+        let scoped = LanceIndex::open_scoped(db_path_str, "vectors", "l2", "status IN ('new', 'done')").unwrap();
+        let err = scoped.update_where("label = 2", &[("status", "'archived'")], false).unwrap_err();
+        assert!(err.to_string().contains("scope"), "{}", err);
+        drop(scoped);
+
+        // Sensitive columns cannot be read through the predicate or expressions
+        idx.set_sensitive_columns(&["status".to_string()]).unwrap();
+        assert!(idx.update_where("status = 'done'", &[("status", "'closed'")], false).is_err());
+        assert!(idx.update_where("label = 0", &[("status", "upper(status)")], false).is_err());
+        assert_eq!(idx.update_where("status = 'done'", &[("status", "'closed'")], true).unwrap(), 1);
+        idx.set_sensitive_columns(&[]).unwrap();
+
+        // New values are checked against the constraints before anything is written
+        idx.set_constraints(Some(Constraints::parse("status in (new, archived, closed)").unwrap())).unwrap();
+        let err = idx.update_where("label = 0", &[("status", "'bogus'")], false).unwrap_err();
+        assert!(err.to_string().contains("bogus"), "{}", err);
+        let (batch, _) = idx.scan_with_labels(&["status".to_string()], Some("status = 'bogus'"), false).unwrap();
+        assert_eq!(batch.num_rows(), 0);
+        assert_eq!(idx.update_where("label = 0", &[("status", "'archived'")], false).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_search_range() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_range.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 3.0, 0.0], 4).unwrap();
+
+        // Squared L2: the rows at 0 and 1 are within 1.5 of the origin, 2 is not
+        let within = DistanceRange { lower: None, upper: Some(1.5) };
+        let results = idx.search_range(&[0.0, 0.0], within, 10, 1, 0, None, false).unwrap();
+        assert_eq!(results.iter().map(|(l, _)| *l).collect::<Vec<_>>(), vec![0, 1]);
+        let ring = DistanceRange { lower: Some(1.0), upper: Some(5.0) };
+        let results = idx.search_range(&[0.0, 0.0], ring, 10, 1, 0, Some("label != 2"), false).unwrap();
+        assert_eq!(results, vec![(1, 1.0)]);
+        assert!(results.iter().all(|(_, d)| ring.contains(*d)));
+
+        assert!(idx.search_range(&[0.0, 0.0], DistanceRange::default(), 10, 1, 0, None, false).is_err());
+        let empty = DistanceRange { lower: Some(2.0), upper: Some(1.0) };
+        assert!(idx.search_range(&[0.0, 0.0], empty, 10, 1, 0, None, false).is_err());
+    }
+
+    #[test]
+    fn test_search_with_consistency() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_consistency.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let writer = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        writer.add_batch(&[0.0, 0.0], 1).unwrap();
+        let reader = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reader.count().unwrap(), 1);
+
+        // An eventual read skips catching up with the sibling's append; the others see it
+        writer.add_batch(&[1.0, 0.0], 1).unwrap();
+        let query = [1.0, 0.0];
+        let eventual = reader.search_with_consistency(&query, 1, 1, 0, None, Consistency::Eventual, false).unwrap();
+        assert_eq!(eventual[0].0, 0);
+        let session = reader.search_with_consistency(&query, 1, 1, 0, None, Consistency::Session, false).unwrap();
+        assert_eq!(session[0].0, 1);
+        let latest = reader.search_with_consistency(&query, 1, 1, 0, None, Consistency::Latest, false).unwrap();
+        assert_eq!(latest[0].0, 1);
+
+        let snapshot = reader.begin_read_snapshot().unwrap();
+        assert!(snapshot.search_with_consistency(&query, 1, 1, 0, None, Consistency::Latest, false).is_err());
+        let hits = snapshot.search_with_consistency(&query, 1, 1, 0, None, Consistency::Session, false).unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_replace_rows_keeps_concurrent_appends() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_replace_rows.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 2.0, 0.0], 3).unwrap();
+        let identity = |batches: &mut dyn Iterator<Item = Result<RecordBatch>>,
+                        schema: SchemaRef|
+         -> Result<Box<dyn RecordBatchReader + Send>> {
+            let batches: Vec<RecordBatch> = batches.collect::<Result<_>>()?;
+            Ok(Box::new(RecordBatchIterator::new(batches.into_iter().map(Ok), schema)))
+        };
+
+        // A row another handle appends after the version read survives the rewrite
+        let table = idx.get_table().unwrap();
+        let version = runtime::block_on(table.version()).unwrap();
+        let other = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let appended = other.add_vector(&[9.0, 0.0]).unwrap();
+        assert!(idx.replace_rows(&table, version, 2, identity).unwrap());
+        assert_eq!(idx.count().unwrap(), 4);
+        assert!(idx.get_vector(appended).is_ok());
+
+        // A row deleted since the version read is not brought back
+        let table = idx.get_table().unwrap();
+        let version = runtime::block_on(table.version()).unwrap();
+        other.delete(0).unwrap();
+        assert!(idx.replace_rows(&table, version, 2, identity).is_err());
+        assert_eq!(idx.count().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_search_with_diversity() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_diversity.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 9.0, 0.0], 4).unwrap();
+
+        // Hits (0,0), (1,0), (1,0): squared l2 of 1, 1 and 0 over three pairs
+        let (hits, report) = idx
+            .search_with_diversity(&[0.0, 0.0], 3, 1, 0, 0, None, Some("vector"), false)
+            .unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!((report.mean_pairwise_distance.unwrap() - 2.0 / 3.0).abs() < 1e-6, "{:?}", report);
+        assert_eq!(report.distinct_values, Some(2));
+
+        let (_, report) = idx.search_with_diversity(&[9.0, 0.0], 1, 1, 0, 0, None, None, false).unwrap();
+        assert_eq!(report, Diversity::default());
+        assert!(idx
+            .search_with_diversity(&[0.0, 0.0], 3, 1, 0, 0, None, Some("missing"), false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_query_log_replay() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_query_log.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let labels = idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 3.0, 0.0], 4).unwrap();
+        idx.search(&[0.0, 0.0], 2, 1, 0, 0, None, false).unwrap();
+        idx.set_query_log(Some(QueryLog::parse("tag=baseline").unwrap())).unwrap();
+        let filter = format!("label != {}", labels[2]);
+        idx.search(&[0.1, 0.0], 2, 1, 0, 0, None, false).unwrap();
+        idx.search(&[2.9, 0.0], 2, 1, 0, 0, Some(&filter), false).unwrap();
+        idx.set_query_log(Some(QueryLog::parse("tag=hashed, vectors=hash").unwrap())).unwrap();
+        idx.search(&[0.1, 0.0], 1, 1, 0, 0, None, false).unwrap();
+
+        let logged = idx.logged_queries(Some("baseline")).unwrap();
+        assert_eq!(logged.len(), 2);
+        assert_eq!(logged[0].labels, vec![labels[0], labels[1]]);
+        assert_eq!(logged[1].labels, vec![labels[3], labels[1]]);
+        assert_eq!(logged[1].filter.as_deref(), Some(filter.as_str()));
+        assert_eq!(idx.logged_queries(None).unwrap().len(), 3);
+        let report = idx.replay_queries("baseline").unwrap();
+        assert_eq!((report.queries.len(), report.changed(), report.skipped), (2, 0, 0));
+
+        // A closer row displaces a logged result; replays are not logged themselves
+        idx.add_vector(&[0.1, 0.0]).unwrap();
+        let report = idx.replay_queries("baseline").unwrap();
+        assert_eq!(report.changed(), 1);
+        assert!(report.queries[0].top_changed);
+        assert_eq!(report.queries[0].overlap, 0.5);
+        let hashed = idx.replay_queries("hashed").unwrap();
+        assert_eq!((hashed.queries.len(), hashed.skipped), (0, 1));
+        assert_eq!(idx.logged_queries(None).unwrap().len(), 3);
+
+        // Buffered entries are flushed on drop; the setting is persisted
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.query_log().unwrap().vectors, query_log::VectorCapture::Hash);
+        assert_eq!(reopened.logged_queries(None).unwrap().len(), 3);
+        reopened.set_query_log(None).unwrap();
+        reopened.search(&[0.1, 0.0], 1, 1, 0, 0, None, false).unwrap();
+        assert_eq!(reopened.logged_queries(None).unwrap().len(), 3);
+
+        // The log spans every scope, so scoped handles cannot read, replay or change it
+        let scoped = LanceIndex::open_scoped(db_path_str, "vectors", "l2", "label >= 0").unwrap();
+        assert!(scoped.logged_queries(None).is_err());
+        assert!(scoped.replay_queries("baseline").is_err());
+        assert!(scoped.set_query_log(None).is_err());
+    }
+
+    #[test]
+    fn test_multivector_search() {
+        use arrow_array::builder::{FixedSizeListBuilder, Float32Builder, ListBuilder};
+
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_multivector.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("tokens", multivector::stored_type(2), true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "cosine", "docs") }.unwrap();
+        assert_eq!(idx.multivector_columns(), vec![("tokens".to_string(), 2)]);
+        let add = |docs: &[&[[f32; 2]]]| -> Result<Vec<i64>> {
+            let values = Float32Array::from(vec![1.0; docs.len() * 2]);
+            let mut tokens = ListBuilder::new(FixedSizeListBuilder::new(Float32Builder::new(), 2));
+            for doc in docs {
+                for token in *doc {
+                    tokens.values().values().append_slice(token);
+                    tokens.values().append(true);
+                }
+                tokens.append(true);
+            }
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+                Arc::new(tokens.finish()),
+            ];
+            let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }
+        };
+        let labels = add(&[&[[1.0, 0.0], [0.0, 1.0]], &[[1.0, 0.0]], &[[0.0, 1.0], [0.7, 0.7]]]).unwrap();
+
+        // Each query vector counts its closest token: 0 + 0, 0 + 1, 0.29 + 0
+        let hits = idx.search_multivector("tokens", &[1.0, 0.0, 0.0, 1.0], 3, 1, None, false).unwrap();
+        assert_eq!(hits.iter().map(|(label, _)| *label).collect::<Vec<_>>(), vec![labels[0], labels[2], labels[1]]);
+        assert!(hits[0].1.abs() < 1e-5);
+        assert!((hits[2].1 - 1.0).abs() < 1e-5);
+        let filter = format!("label != {}", labels[0]);
+        assert_eq!(idx.search_multivector("tokens", &[1.0, 0.0], 3, 1, Some(&filter), false).unwrap().len(), 2);
+        assert!(idx.search_multivector("tokens", &[1.0, 0.0, 0.0], 3, 1, None, false).is_err());
+        assert!(idx.search_multivector("vector", &[1.0, 0.0], 3, 1, None, false).is_err());
+
+        // Rows Lance cannot rank are refused
+        let err = add(&[&[]]).unwrap_err();
+        assert!(err.to_string().contains("has no vectors"), "{}", err);
+        assert!(add(&[&[[0.0, 0.0]]]).is_err());
+        assert_eq!(idx.count().unwrap(), 3);
+
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "docs", "cosine").unwrap();
+        assert_eq!(reopened.multivector_columns(), vec![("tokens".to_string(), 2)]);
+        assert!(reopened.search_multivector("tokens", &[0.0, 1.0], 1, 1, None, false).unwrap()[0].1 < 1e-5);
+    }
+
+    #[test]
+    fn test_int8_storage() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_int8.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create_with_storage(db_path_str, 2, "l2", "vectors", VectorStorage::Int8).unwrap();
+        assert_eq!(idx.vector_storage(), VectorStorage::Int8);
+        assert!(idx.int8_quantizer().is_none());
+        let err = idx.add_vector(&[0.0, 0.0]).unwrap_err();
+        assert!(err.to_string().contains("no int8 range"), "{}", err);
+        idx.set_int8_range(-1.0, 1.0).unwrap();
+        let labels = idx.add_batch(&[1.0, 0.0, 0.0, 1.0, 0.5, -0.5, -1.0, 0.25], 4).unwrap();
+
+        // Reads dequantize to within half a step
+        let quantizer = idx.int8_quantizer().unwrap();
+        assert_eq!(quantizer, Int8Quantizer::for_range(-1.0, 1.0).unwrap());
+        let third = idx.get_vector(labels[2]).unwrap();
+        assert!((third[0] - 0.5).abs() <= quantizer.scale / 2.0 + 1e-6);
+        assert!((third[1] + 0.5).abs() <= quantizer.scale / 2.0 + 1e-6);
+        let (all_labels, all_vectors) = idx.get_all_vectors().unwrap();
+        assert_eq!((all_labels.len(), all_vectors.len()), (4, 8));
+        assert!(idx.set_int8_range(-2.0, 2.0).unwrap_err().to_string().contains("empty"));
+
+        // Searches rank the dequantized vectors exactly; Lance cannot index them
+        let hits = idx.search(&[0.0, 0.9], 2, 1, 0, 0, None, false).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].0, labels[1]);
+        let others = format!("label != {}", labels[1]);
+        assert_eq!(idx.search(&[0.0, 0.9], 10, 1, 0, 0, Some(&others), false).unwrap().len(), 3);
+        assert!(idx.create_ivf_flat_index(1).unwrap_err().to_string().contains("cannot index"));
+
+        // Elements past the range are refused, not saturated
+        let err = idx.add_vector(&[5.0, 0.0]).unwrap_err();
+        assert!(err.to_string().contains("outside the int8 range"), "{}", err);
+        assert_eq!(idx.count().unwrap(), 4);
+
+        // A sibling handle opened before the range was set picks it up
+        let empty = LanceIndex::create_with_storage(db_path_str, 2, "l2", "ranged", VectorStorage::Int8).unwrap();
+        let sibling = LanceIndex::open(db_path_str, "ranged", "l2").unwrap();
+        empty.set_int8_range(-4.0, 4.0).unwrap();
+        sibling.add_vector(&[3.0, -3.0]).unwrap();
+        assert_eq!(sibling.int8_quantizer(), Some(Int8Quantizer::for_range(-4.0, 4.0).unwrap()));
+
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.int8_quantizer(), Some(quantizer));
+        assert_eq!(reopened.get_vector(labels[2]).unwrap(), third);
+    }
+
+    #[test]
+    fn test_reembed_dual_index() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_reembed_dual.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..256).flat_map(|i| [(i % 16) as f32, (i / 16) as f32]).collect();
+        let labels = idx.add_batch(&vectors, 256).unwrap();
+        idx.create_ivf_flat_index(2).unwrap();
+        let next = reembed::NEXT_COLUMN;
+        let err = idx.search_column(next, &[0.0; 3], 1, 2, 0, 0, None, false).unwrap_err();
+        assert!(err.to_string().contains("no re-embed"));
+
+        idx.reembed_begin(3).unwrap();
+        let params = VectorIndexParams {
+            num_partitions: 2,
+            ..Default::default()
+        };
+        let err = idx.create_vector_index_on(next, VectorIndexType::IvfFlat, &params).unwrap_err();
+        assert!(err.to_string().contains("256 rows"), "{}", err);
+        let new_vectors: Vec<f32> = (0..256).flat_map(|i| [(i / 16) as f32, (i % 16) as f32, 1.0]).collect();
+        idx.reembed_write(&labels, &new_vectors).unwrap();
+        idx.create_vector_index_on(next, VectorIndexType::IvfFlat, &params).unwrap();
+
+        // Each column answers with its own embeddings
+        assert_eq!(idx.search_column("vector", &[3.0, 4.0], 1, 2, 0, 0, None, false).unwrap()[0].0, 4 * 16 + 3);
+        let hits = idx.search_column(next, &[3.0, 4.0, 1.0], 1, 2, 0, 0, None, false).unwrap();
+        assert_eq!(hits, vec![(3 * 16 + 4, 0.0)]);
+        assert!(idx.search_column(next, &[3.0, 4.0], 1, 2, 0, 0, None, false).is_err());
+        assert!(idx.search_column("vector_pca", &[3.0, 4.0], 1, 2, 0, 0, None, false).is_err());
+        let statuses = idx.list_indices().unwrap();
+        assert_eq!(statuses.len(), 2);
+        for status in &statuses {
+            assert_eq!((status.indexed_rows, status.unindexed_rows), (256, 0));
+            assert_eq!(status.metric.as_deref(), Some("l2"));
+        }
+
+        // The new column's index carries over as the vector index
+        idx.reembed_finish(None).unwrap();
+        drop(idx);
+        let idx = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let statuses = idx.list_indices().unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].info.columns, vec!["vector".to_string()]);
+        assert_eq!(idx.index_compression(), Some(1.0));
+        assert_eq!(idx.search(&[3.0, 4.0, 1.0], 1, 2, 0, 0, None, false).unwrap(), vec![(3 * 16 + 4, 0.0)]);
+    }
+
+    #[test]
+    fn test_reembed() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_reembed.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let labels = idx.add_batch(&[1.0, 0.0, 0.0, 1.0], 2).unwrap();
+        assert!(idx.reembed_write(&labels, &[0.0; 6]).unwrap_err().to_string().contains("no re-embed"));
+        idx.reembed_begin(3).unwrap();
+        assert_eq!(idx.reembed_dimension(), Some(3));
+        assert!(idx.reembed_begin(4).unwrap_err().to_string().contains("already in progress"));
+
+        // Searches keep using the old vectors until the swap
+        idx.reembed_write(&labels[..1], &[0.0, 0.0, 1.0]).unwrap();
+        assert!(idx.reembed_finish(None).unwrap_err().to_string().contains("1 rows"));
+        assert!(idx.reembed_write(&[99], &[0.0, 0.0, 1.0]).unwrap_err().to_string().contains("not found"));
+        assert!(idx.reembed_write(&labels[1..], &[f32::NAN, 0.0, 1.0]).is_err());
+        idx.reembed_write(&labels[1..], &[1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(idx.search(&[1.0, 0.0], 1, 1, 0, 0, None, false).unwrap()[0].0, labels[0]);
+
+        // A reopened handle resumes the re-embed
+        drop(idx);
+        let idx = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(idx.reembed_dimension(), Some(3));
+        assert_eq!(idx.schema.fields().len(), 2);
+        idx.reembed_finish(Some("model-v2")).unwrap();
+
+        drop(idx);
+        let idx = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(idx.dimension(), 3);
+        assert_eq!(idx.reembed_dimension(), None);
+        assert_eq!(idx.embedding_model().as_deref(), Some("model-v2"));
+        assert_eq!(idx.schema.field(1).name(), "vector");
+        assert_eq!(idx.get_vector(labels[1]).unwrap(), vec![1.0, 0.0, 0.0]);
+        assert_eq!(idx.search(&[0.0, 0.0, 1.0], 1, 1, 0, 0, None, false).unwrap()[0].0, labels[0]);
+        idx.add_vector(&[0.5, 0.5, 0.5]).unwrap();
+        assert_eq!(idx.count().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_float16_storage() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_float16.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create_with_storage(db_path_str, 2, "l2", "vectors", VectorStorage::Float16).unwrap();
+        assert_eq!(idx.vector_storage(), VectorStorage::Float16);
+        let labels = idx.add_batch(&[1.0, 0.0, 0.0, 1.0, 0.5, 0.333], 3).unwrap();
+        assert!(idx.add_batch(&[1.0e6, 0.0], 1).unwrap_err().to_string().contains("float16 range"));
+
+        // Read back widened, with f16 precision
+        assert_eq!(idx.get_vector(labels[0]).unwrap(), vec![1.0, 0.0]);
+        let third = idx.get_vector(labels[2]).unwrap();
+        assert!((third[1] - 0.333).abs() < 1e-3 && third[1] != 0.333);
+        let (all_labels, all_vectors) = idx.get_all_vectors().unwrap();
+        assert_eq!(all_labels.len(), 3);
+        assert_eq!(all_vectors.len(), 6);
+
+        // f32 queries search the f16 column
+        let hits = idx.search(&[0.0, 0.9], 1, 1, 0, 0, None, false).unwrap();
+        assert_eq!(hits[0].0, labels[1]);
+        assert!(idx.train_rotation(1, 3).unwrap_err().to_string().contains("float32 vector storage"));
+
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.vector_storage(), VectorStorage::Float16);
+        reopened.add_vector(&[2.0, 2.0]).unwrap();
+        assert_eq!(reopened.count().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_format_info() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_format_info.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 0.0, 0.0, 1.0], 2).unwrap();
+        let info = idx.format_info().unwrap();
+        assert!(info.can_read && info.can_write, "{:?}", info.problems);
+        assert!(!info.written_by_newer);
+        assert_eq!(info.writer.as_deref(), Some(info.library.as_str()));
+        assert!(info.storage_format.starts_with("lance "), "{}", info.storage_format);
+        assert_eq!(info.file_versions.len(), 1);
+        assert_eq!(info.version, runtime::block_on(idx.get_table().unwrap().version()).unwrap());
+
+        // A table this build can write opens writable
+        drop(idx);
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert!(reopened.format_block.is_none());
+        reopened.add_batch(&[1.0, 1.0], 1).unwrap();
+    }
+
+    #[test]
+    fn test_create_ivf_flat_index() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_ivf_flat.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..256).flat_map(|i| [(i % 16) as f32, (i / 16) as f32]).collect();
+        idx.add_batch(&vectors, 256).unwrap();
+        idx.create_ivf_flat_index(2).unwrap();
+
+        // Unquantized: nothing for auto refine to rescore, and probing every partition is exact
+        assert_eq!(idx.index_compression(), Some(1.0));
+        assert_eq!(idx.refine_plan(AUTO_REFINE, 5).factor, 0);
+        let hits = idx.search(&[3.0, 4.0], 1, 2, 0, 0, None, false).unwrap();
+        assert_eq!(hits, vec![(4 * 16 + 3, 0.0)]);
+    }
+
+    #[test]
+    fn test_upsert_conflict_policies() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_upsert_policies.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("doc_id", DataType::Utf8, true),
+            Field::new("updated_at", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let upsert = |ids: Vec<&str>, versions: Vec<i64>, spec: &str| {
+            let values = Float32Array::from(versions.iter().flat_map(|v| [*v as f32, 0.0]).collect::<Vec<_>>());
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+                Arc::new(StringArray::from(ids)),
+                Arc::new(Int64Array::from(versions)),
+            ];
+            let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            let options = UpsertOptions::parse(spec).unwrap();
+            unsafe { idx.upsert_batch_arrow(&mut array_schema, &mut array, Some("doc_id"), &options) }
+        };
+        upsert(vec!["a", "b"], vec![5, 5], "").unwrap();
+
+        // Older "a" loses to the stored row, newer "b" replaces it, "c" is new
+        let report = upsert(vec!["a", "b", "c"], vec![4, 6, 1], "on_conflict=keep_newest(updated_at)").unwrap();
+        assert_eq!((report.inserted, report.updated, report.skipped), (1, 1, 1));
+        assert_eq!(report.labels, vec![0, 1, 2]);
+        assert_eq!(idx.get_vector(0).unwrap(), vec![5.0, 0.0]);
+        assert_eq!(idx.get_vector(1).unwrap(), vec![6.0, 0.0]);
+
+        let report = upsert(vec!["a", "d"], vec![9, 9], "on_conflict=keep_existing").unwrap();
+        assert_eq!((report.inserted, report.updated, report.skipped), (1, 0, 1));
+        assert_eq!(idx.get_vector(0).unwrap(), vec![5.0, 0.0]);
+
+        let err = upsert(vec!["e", "b"], vec![1, 1], "on_conflict=error").unwrap_err();
+        assert!(err.to_string().contains("'b' is already stored"), "{}", err);
+        assert_eq!(idx.count().unwrap(), 4);
+
+        // A repeated key keeps its newest row wherever it sits in the batch, or the last row
+        let spec = "on_conflict=keep_newest(updated_at); duplicates=collapse";
+        let report = upsert(vec!["e", "e", "e"], vec![3, 8, 2], spec).unwrap();
+        assert_eq!((report.inserted, report.collapsed), (1, 2));
+        assert_eq!(report.labels, vec![4, 4, 4]);
+        assert_eq!(idx.get_vector(4).unwrap(), vec![8.0, 0.0]);
+        upsert(vec!["e", "e"], vec![10, 7], "duplicates=collapse").unwrap();
+        assert_eq!(idx.get_vector(4).unwrap(), vec![7.0, 0.0]);
+        assert!(upsert(vec!["e", "e"], vec![1, 2], "").unwrap_err().to_string().contains("appears twice"));
+        assert!(upsert(vec!["f"], vec![1], "on_conflict=keep_newest(missing)").is_err());
+        assert_eq!(idx.count().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_search_ef() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_ef.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..20).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 20).unwrap();
+
+        // Without an HNSW index ef changes nothing
+        let expected = idx.search(&[3.0, 0.0], 5, 1, 0, 0, None, false).unwrap();
+        assert_eq!(idx.search(&[3.0, 0.0], 5, 1, 0, 64, None, false).unwrap(), expected);
+        let err = idx.search(&[3.0, 0.0], 5, 1, 0, 4, None, false).unwrap_err();
+        assert!(err.to_string().contains("ef 4 is below k 5"), "{}", err);
+    }
+
+    #[test]
+    fn test_retain_only() {
+        use arrow_array::StringArray;
+
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_retain_only.lance");
+        let db_path_str = db_path.to_str().unwrap();
 
-        let results = runtime::block_on(
-            table
-                .query()
-                .execute(),
-        )?;
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![vector, Field::new("doc", DataType::Utf8, true)]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let add = |docs: Vec<Option<&str>>| {
+            let values = Float32Array::from((0..docs.len() * 2).map(|i| i as f32).collect::<Vec<_>>());
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+                Arc::new(StringArray::from(docs)),
+            ];
+            let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+            let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
+        };
+        add(vec![Some("a"), Some("b"), Some("c"), Some("d"), Some("it's"), None]);
+        add(vec![Some("e"), Some("f")]);
 
-        let mut all_labels = Vec::new();
-        let mut all_vectors = Vec::new();
+        let retain = |name: &str, keys: ArrayRef, allow_empty: bool| {
+            let fields = Fields::from(vec![Field::new(name, keys.data_type().clone(), true)]);
+            let data = StructArray::new(fields, vec![keys], None).into_data();
+            let (mut array, mut schema) = arrow::ffi::to_ffi(&data).unwrap();
+            unsafe { idx.retain_only(&mut schema, &mut array, allow_empty) }
+        };
 
-        runtime::block_on(async {
-            let mut stream = results;
-            while let Some(batch) = stream.try_next().await
-                .map_err(|e| anyhow!("stream error: {}", e))? {
-                let label_col = batch
-                    .column_by_name("label")
-                    .ok_or_else(|| anyhow!("missing label column"))?;
-                let labels = label_col
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .ok_or_else(|| anyhow!("label column not Int64"))?;
+        // Keys absent from the table are ignored; the NULL-keyed row goes, and so
+        // does the second fragment as a whole
+        let deleted = retain("doc", Arc::new(StringArray::from(vec!["b", "d", "it's", "zzz"])), false).unwrap();
+        assert_eq!(deleted, 5);
+        assert_eq!(idx.count().unwrap(), 3);
+        assert_eq!(idx.fragment_ids(None).unwrap().len(), 1);
+        assert_eq!(retain("doc", Arc::new(StringArray::from(vec!["b", "d", "it's"])), false).unwrap(), 0);
 
-                let vec_col = batch
-                    .column_by_name("vector")
-                    .ok_or_else(|| anyhow!("missing vector column"))?;
-                let list_array = vec_col
-                    .as_any()
-                    .downcast_ref::<FixedSizeListArray>()
-                    .ok_or_else(|| anyhow!("vector not FixedSizeList"))?;
+        // One version for the whole delete
+        let version = || runtime::block_on(idx.get_table().unwrap().version()).unwrap();
+        let before = version();
+        let deleted = retain("label", Arc::new(Int64Array::from(vec![1])), false).unwrap();
+        assert_eq!(deleted, 2);
+        assert_eq!(version(), before + 1);
+        let (labels, _) = idx.get_all_vectors().unwrap();
+        assert_eq!(labels, vec![1]);
 
-                for i in 0..batch.num_rows() {
-                    all_labels.push(labels.value(i));
-                    let values = list_array.value(i);
-                    let float_array = values
-                        .as_any()
-                        .downcast_ref::<Float32Array>()
-                        .ok_or_else(|| anyhow!("vector values not Float32"))?;
-                    all_vectors.extend_from_slice(float_array.values());
-                }
-            }
-            Ok::<(), anyhow::Error>(())
-        })?;
+        assert!(retain("missing", Arc::new(Int64Array::from(vec![1])), false).is_err());
+        assert!(retain("doc", Arc::new(StringArray::from(vec![None::<&str>])), false).is_err());
+        assert_eq!(idx.count().unwrap(), 1);
 
-        Ok((all_labels, all_vectors))
+        // An empty key set clears the table only when allowed
+        let empty = || Arc::new(Int64Array::from(Vec::<i64>::new())) as ArrayRef;
+        assert!(retain("label", empty(), false).unwrap_err().to_string().contains("every row"));
+        assert_eq!(retain("label", empty(), true).unwrap(), 1);
+        assert_eq!(idx.count().unwrap(), 0);
     }
 
-    // Internal helpers
+    #[test]
+    fn test_create_scalar_index() {
+        use arrow_array::StringArray;
 
-    /// Query MAX(label) from the table. Returns -1 if empty.
-    fn query_max_label(table: &LanceTable) -> Result<i64> {
-        let count = runtime::block_on(table.count_rows(None))?;
-        if count == 0 {
-            return Ok(-1);
-        }
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_create_scalar_index.lance");
+        let db_path_str = db_path.to_str().unwrap();
 
-        let results = runtime::block_on(table.query().execute())?;
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![
+            vector,
+            Field::new("language", DataType::Utf8, true),
+            Field::new("stars", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let values = Float32Array::from((0..16).map(|i| (i / 2) as f32).collect::<Vec<_>>());
+        let languages = ["rust", "go", "rust", "c", "rust", "go", "c", "rust"];
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(values), None)),
+            Arc::new(StringArray::from(languages.to_vec())),
+            Arc::new(Int64Array::from((0..8).collect::<Vec<i64>>())),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap();
 
-        let mut max_label: i64 = -1;
-        runtime::block_on(async {
-            let mut stream = results;
-            while let Some(batch) = stream
-                .try_next()
-                .await
-                .map_err(|e| anyhow!("stream error: {}", e))?
-            {
-                let label_col = batch
-                    .column_by_name("label")
-                    .ok_or_else(|| anyhow!("missing label column"))?;
-                let labels = label_col
-                    .as_any()
-                    .downcast_ref::<Int64Array>()
-                    .ok_or_else(|| anyhow!("label column not Int64"))?;
-                for i in 0..labels.len() {
-                    let val = labels.value(i);
-                    if val > max_label {
-                        max_label = val;
-                    }
-                }
-            }
-            Ok::<(), anyhow::Error>(())
-        })?;
+        idx.create_scalar_index("language", ScalarIndexType::Bitmap).unwrap();
+        idx.create_scalar_index("stars", ScalarIndexType::BTree).unwrap();
+        let indices = runtime::block_on(idx.get_table().unwrap().list_indices()).unwrap();
+        assert_eq!(indices.len(), 2);
 
-        Ok(max_label)
+        let hits = idx.search(&[0.0, 0.0], 10, 1, 0, 0, Some("language = 'rust' AND stars > 0"), false).unwrap();
+        let mut labels: Vec<i64> = hits.iter().map(|(label, _)| *label).collect();
+        labels.sort_unstable();
+        assert_eq!(labels, vec![2, 4, 7]);
+
+        assert!(idx.create_scalar_index("vector", ScalarIndexType::BTree).is_err());
+        assert!(idx.create_scalar_index("language", ScalarIndexType::LabelList).is_err());
+        assert!(idx.create_scalar_index("missing", ScalarIndexType::Bitmap).is_err());
     }
 
-    /// Build schema for vector-only tables (label + vector).
-    fn build_vector_schema(dimension: usize) -> Arc<Schema> {
-        Arc::new(Schema::new(vec![
-            Field::new("label", DataType::Int64, false),
-            Field::new(
-                "vector",
-                DataType::FixedSizeList(
-                    Arc::new(Field::new("item", DataType::Float32, true)),
-                    dimension as i32,
-                ),
-                false,
-            ),
-        ]))
+    #[test]
+    fn test_label_bitmap() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_label_bitmap.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..10).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 10).unwrap();
+        let (before, version) = idx.label_bitmap().unwrap();
+        assert_eq!(before.len(), 10);
+
+        idx.delete(2).unwrap();
+        idx.delete(5).unwrap();
+        let (after, later) = idx.label_bitmap().unwrap();
+        assert!(later > version);
+        let deleted = label_bitmap::BitmapOp::AndNot.apply(&before, &after);
+        assert_eq!(label_bitmap::labels(&deleted).unwrap(), vec![2, 5]);
+        assert!(after.contains(3) && !after.contains(5));
     }
 
-    /// Create an empty RecordBatch from a schema by generating empty arrays for each field.
-    fn empty_batch_from_schema(schema: &Arc<Schema>) -> Result<RecordBatch> {
-        let columns: Vec<ArrayRef> = schema
-            .fields()
-            .iter()
-            .map(|field| Self::empty_array_for_datatype(field.data_type()))
-            .collect();
-        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    #[test]
+    fn test_read_limits() {
+        use crate::read_limit::{self, ReadLimits};
+
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_read_limits.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let vectors: Vec<f32> = (0..100).flat_map(|i| [i as f32, 0.0]).collect();
+        idx.add_batch(&vectors, 100).unwrap();
+
+        read_limit::configure(ReadLimits {
+            max_rows: 50,
+            max_bytes: 0,
+        });
+        let err = read_limit::limited(|| idx.get_all_vectors()).unwrap_err();
+        assert!(err.to_string().contains("limit of 50 rows"), "{}", err);
+        assert!(read_limit::limited(|| idx.scan(&[], None, false)).is_err());
+        // A filtered scan under the limit and uncapped internal reads still work
+        let part = read_limit::limited(|| idx.scan(&["label".to_string()], Some("label < 10"), false)).unwrap();
+        assert_eq!(part.num_rows(), 10);
+        assert_eq!(idx.get_all_vectors().unwrap().0.len(), 100);
+        read_limit::configure(ReadLimits::default());
+        assert_eq!(read_limit::limited(|| idx.get_all_vectors()).unwrap().0.len(), 100);
     }
 
-    /// Create an empty array for any Arrow DataType.
-    fn empty_array_for_datatype(dt: &DataType) -> ArrayRef {
-        use arrow_array::{BooleanArray, Int32Array, Float64Array, StringArray};
-        match dt {
-            DataType::Int64 => Arc::new(Int64Array::from(Vec::<i64>::new())),
-            DataType::Int32 => Arc::new(Int32Array::from(Vec::<i32>::new())),
-            DataType::Float32 => Arc::new(Float32Array::from(Vec::<f32>::new())),
-            DataType::Float64 => Arc::new(Float64Array::from(Vec::<f64>::new())),
-            DataType::Utf8 => Arc::new(StringArray::from(Vec::<&str>::new())),
-            DataType::Boolean => Arc::new(BooleanArray::from(Vec::<bool>::new())),
-            DataType::FixedSizeList(child_field, dim) => {
-                let values = Float32Array::from(Vec::<f32>::new());
-                let list = FixedSizeListArray::new(child_field.clone(), *dim, Arc::new(values), None);
-                Arc::new(list)
-            }
-            _ => Arc::new(StringArray::from(Vec::<&str>::new())), // fallback
+    #[test]
+    fn test_search_batch() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_search_batch.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 3.0, 0.0], 4).unwrap();
+
+        let queries: Vec<f32> = (0..20).flat_map(|i| [(i % 4) as f32, 0.0]).collect();
+        let hits = idx.search_batch(&queries, 20, 2, 1, 0, None, 3, false).unwrap();
+        assert_eq!(hits.len(), 40);
+        for i in 0..20 {
+            let query_hits: Vec<_> = hits.iter().filter(|(q, _, _)| *q == i).collect();
+            assert_eq!(query_hits.len(), 2);
+            assert_eq!(query_hits[0].1, (i % 4) as i64);
+            assert_eq!(query_hits[0].2, 0.0);
         }
+        let sequential = idx.search(&queries[6..8], 2, 1, 0, 0, None, false).unwrap();
+        assert_eq!(hits[6..8].iter().map(|(_, l, d)| (*l, *d)).collect::<Vec<_>>(), sequential);
+
+        assert!(idx.search_batch(&queries[..5], 2, 2, 1, 0, None, 0, false).is_err());
+        let err = idx.search_batch(&[0.0; 6], 2, 2, 1, 0, None, 0, false).unwrap_err();
+        assert!(err.to_string().contains("query 0"), "{}", err);
+        assert!(idx.search_batch(&[], 0, 2, 1, 0, None, 0, false).unwrap().is_empty());
     }
 
-    fn make_fixed_size_list(values: Float32Array, dimension: i32) -> FixedSizeListArray {
-        let field = Arc::new(Field::new("item", DataType::Float32, true));
-        let values_ref: ArrayRef = Arc::new(values);
-        FixedSizeListArray::new(field, dimension, values_ref, None)
+    #[test]
+    fn test_manifest_replication() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_replication.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let writer = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        writer.add_batch(&[0.0, 0.0, 1.0, 0.0], 2).unwrap();
+        let first = writer.export_manifest().unwrap();
+        writer.add_batch(&[2.0, 0.0], 1).unwrap();
+
+        // The replica stays on the imported version while the writer moves on
+        let replica = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let version = replica.import_manifest(&first).unwrap();
+        assert_eq!(replica.imported_version(), Some(version));
+        assert_eq!(replica.count().unwrap(), 2);
+        writer.add_batch(&[3.0, 0.0], 1).unwrap();
+        assert_eq!(replica.count().unwrap(), 2);
+        assert!(replica.add_batch(&[4.0, 0.0], 1).unwrap_err().to_string().contains("read-only"));
+
+        let second = writer.export_manifest().unwrap();
+        assert!(replica.import_manifest(&second).unwrap() > version);
+        assert_eq!(replica.count().unwrap(), 4);
+        assert!(replica.import_manifest(&first).unwrap_err().to_string().contains("older"));
+
+        // A blob whose manifest does not match storage is refused
+        let mut tampered = ManifestBlob::decode(&second).unwrap();
+        tampered.manifest[0] ^= 0xff;
+        assert!(replica.import_manifest(&tampered.encode()).unwrap_err().to_string().contains("differs"));
+        tampered.table_name = "other".to_string();
+        assert!(replica.import_manifest(&tampered.encode()).is_err());
     }
 
-    /// Build a RecordBatch from already-contiguous flat vector data (no re-flattening needed).
-    fn make_batch_contiguous(&self, labels: &[i64], flat_vectors: &[f32]) -> Result<RecordBatch> {
-        let label_array = Int64Array::from(labels.to_vec());
-        let values = Float32Array::from(flat_vectors.to_vec());
-        let list = Self::make_fixed_size_list(values, self.dimension as i32);
-        Ok(RecordBatch::try_new(self.schema.clone(), vec![
-            Arc::new(label_array),
-            Arc::new(list),
-        ])?)
+    #[test]
+    fn test_table_schema_from_arrow_vector_columns() {
+        let vector_type = |item: DataType, dim: i32| DataType::FixedSizeList(Arc::new(Field::new("", item, true)), dim);
+        let imported = Schema::new(vec![
+            Field::new("vector", vector_type(DataType::Float32, 4), true),
+            Field::new("image_vector", vector_type(DataType::Float16, 8), true),
+            Field::new("bbox", vector_type(DataType::Int32, 4), true),
+        ]);
+
+        // Undeclared FixedSizeList columns keep their element type
+        let (schema, dimension) = LanceIndex::table_schema_from_arrow(&imported, &[], VectorStorage::Float32).unwrap();
+        assert_eq!(dimension, 4);
+        assert_eq!(schema.field(0).name(), "label");
+        let DataType::FixedSizeList(item, _) = schema.field_with_name("bbox").unwrap().data_type() else {
+            panic!("bbox is not a FixedSizeList");
+        };
+        assert_eq!((item.name().as_str(), item.data_type()), ("item", &DataType::Int32));
+
+        let declared = ["vector".to_string(), "image_vector".to_string()];
+        let (schema, _) = LanceIndex::table_schema_from_arrow(&imported, &declared, VectorStorage::Float32).unwrap();
+        let DataType::FixedSizeList(item, 8) = schema.field_with_name("image_vector").unwrap().data_type() else {
+            panic!("image_vector is not a FixedSizeList of 8");
+        };
+        assert_eq!(item.data_type(), &DataType::Float32);
+        let (schema, _) = LanceIndex::table_schema_from_arrow(&imported, &declared, VectorStorage::Float16).unwrap();
+        assert_eq!(VectorStorage::of_schema(&schema), VectorStorage::Float16);
+
+        let error = |imported: &Schema, declared: &[&str]| {
+            let declared: Vec<String> = declared.iter().map(|c| c.to_string()).collect();
+            LanceIndex::table_schema_from_arrow(imported, &declared, VectorStorage::Float32).unwrap_err().to_string()
+        };
+        assert_eq!(error(&imported, &["vector", "bbox"]), "vector column bbox has Int32 elements, expected floats");
+        assert_eq!(
+            error(&imported, &["image_vector"]),
+            "the first vector column must be named vector, got image_vector"
+        );
+        assert_eq!(error(&imported, &["vector", "vector"]), "vector column vector declared twice");
+        assert_eq!(error(&imported, &["vector", "text"]), "declared vector column text is not in the schema");
+
+        let unnamed = Schema::new(vec![
+            Field::new("a", vector_type(DataType::Float32, 4), true),
+            Field::new("b", vector_type(DataType::Float32, 4), true),
+        ]);
+        assert_eq!(
+            error(&unnamed, &[]),
+            "no vector column; name the vector column vector (FixedSizeList columns: a, b)"
+        );
+        let empty = Schema::new(vec![Field::new("vector", vector_type(DataType::Float32, 0), true)]);
+        assert_eq!(error(&empty, &[]), "vector column vector has dimension 0, expected 1 to 65536");
     }
 
-    fn make_batch(&self, labels: &[i64], vectors: &[&[f32]]) -> Result<RecordBatch> {
-        let label_array = Int64Array::from(labels.to_vec());
-        let flat_values: Vec<f32> = vectors.iter().flat_map(|v| v.iter().copied()).collect();
-        let values = Float32Array::from(flat_values);
-        let list = Self::make_fixed_size_list(values, self.dimension as i32);
-        Ok(RecordBatch::try_new(self.schema.clone(), vec![
-            Arc::new(label_array),
-            Arc::new(list),
-        ])?)
+    #[test]
+    fn test_inspect_reports_foreign_table() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_inspect.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("embedding", DataType::FixedSizeList(item.clone(), 2), true),
+        ]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![
+            Arc::new(StringArray::from(vec!["a"])),
+            Arc::new(FixedSizeListArray::new(item, 2, Arc::new(Float32Array::from(vec![1.0, 2.0])), None)),
+        ])
+        .unwrap();
+        let connection = storage_options::connect(db_path_str, &[]).unwrap();
+        let batches = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        LanceIndex::create_table(&connection, "foreign", Box::new(batches)).unwrap();
+
+        let err = LanceIndex::open(db_path_str, "foreign", "l2").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "table foreign is not a Lance index table: no label column (an Int64 row key); \
+             no column named vector (FixedSizeList columns: embedding)"
+        );
+        let capabilities = LanceIndex::inspect(db_path_str, "foreign").unwrap();
+        assert!(capabilities.label.is_none());
+        assert_eq!(capabilities.vector_columns[0].dimension, 2);
+        assert!(capabilities.indices.is_empty());
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[1.0, 2.0], 1).unwrap();
+        let capabilities = LanceIndex::inspect(db_path_str, "vectors").unwrap();
+        assert!(capabilities.problems().is_empty());
+        assert_eq!(capabilities.vector().unwrap().dimension, 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_add_batch_arrow_rows_reports_row_status() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_row_status.lance");
+        let idx = LanceIndex::create(db_path.to_str().unwrap(), 2, "l2", "vectors").unwrap();
 
-    fn temp_dir() -> tempfile::TempDir {
-        tempfile::tempdir().expect("failed to create temp dir")
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let fields = Schema::new(vec![Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true)])
+            .fields()
+            .clone();
+        let batch = |xs: &[f32]| {
+            let values = Float32Array::from(xs.iter().flat_map(|x| [*x, 0.0]).collect::<Vec<_>>());
+            let vectors: ArrayRef = Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None));
+            arrow::ffi::to_ffi(&StructArray::new(fields.clone(), vec![vectors], None).into_data()).unwrap()
+        };
+        let xs = [1.0, f32::NAN, 3.0];
+
+        // Partial-failure semantics report the invalid row despite on_invalid=error
+        let (mut array, mut schema) = batch(&xs);
+        let rows = unsafe { idx.add_batch_arrow_rows(&mut schema, &mut array, Some("load-1")) }.unwrap();
+        assert_eq!(rows, vec![(0, RowStatus::Ok), (-1, RowStatus::Invalid), (1, RowStatus::Ok)]);
+
+        // A retry reports the rows added the first time as duplicates
+        let (mut array, mut schema) = batch(&xs);
+        let rows = unsafe { idx.add_batch_arrow_rows(&mut schema, &mut array, Some("load-1")) }.unwrap();
+        assert_eq!(rows, vec![(0, RowStatus::Duplicate), (-1, RowStatus::Invalid), (1, RowStatus::Duplicate)]);
+        assert_eq!(idx.count().unwrap(), 2);
+
+        let (mut array, mut schema) = batch(&xs);
+        assert!(unsafe { idx.add_batch_arrow(&mut schema, &mut array) }.is_err());
+        assert_eq!(idx.count().unwrap(), 2);
     }
 
     #[test]
-    fn test_next_label_unique_after_deletes() {
+    fn test_prefetch_counts_rows_read() {
         let dir = temp_dir();
         let db_path = dir.path().join("test.lance");
-        let db_path_str = db_path.to_str().unwrap();
+        let idx = LanceIndex::create(db_path.to_str().unwrap(), 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0], 4).unwrap();
 
-        // Insert 5 vectors with labels [0,1,2,3,4]
-        let idx = LanceIndex::create(db_path_str, 3, "l2", "vectors").unwrap();
-        for i in 0..5 {
-            let label = idx.add_vector(&[i as f32, 0.0, 0.0]).unwrap();
-            assert_eq!(label, i);
-        }
+        assert_eq!(idx.prefetch(&[], None).unwrap(), 4);
+        assert_eq!(idx.prefetch(&[0, 2, 2, 99], None).unwrap(), 2);
+        assert_eq!(idx.prefetch(&[0, 1, 2], Some("label > 0")).unwrap(), 2);
+        assert!(idx.prefetch(&[], Some("missing_column = 1")).is_err());
+    }
 
-        // Delete labels [1,2]
-        idx.delete(1).unwrap();
-        idx.delete(2).unwrap();
+    #[test]
+    fn test_ping_and_reconnect() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test.lance");
+        let idx = LanceIndex::create(db_path.to_str().unwrap(), 2, "l2", "vectors").unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 1.0], 2).unwrap();
+        let version = idx.ping().unwrap().version;
+        assert!(version >= 2);
 
-        // Reopen — next insert must NOT produce label 3 (already exists)
-        drop(idx);
-        let idx2 = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
-        let new_label = idx2.add_vector(&[99.0, 0.0, 0.0]).unwrap();
-        assert!(
-            new_label >= 5,
-            "expected label >= 5 after deletes, got {new_label}"
-        );
+        // A failed connection makes the next operation reopen the table
+        let dropped: Result<()> = Err(anyhow::Error::new(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "connection reset by peer",
+        )));
+        assert!(idx.note_failure(dropped).is_err());
+        assert!(idx.reconnect_needed.load(Ordering::Acquire));
+        assert_eq!(idx.count().unwrap(), 2);
+        assert!(!idx.reconnect_needed.load(Ordering::Acquire));
+        assert_eq!(idx.search(&[1.0, 1.0], 1, 1, 1, 0, None, false).unwrap()[0].0, 1);
+
+        // Other errors leave the table as it is
+        assert!(idx.note_failure::<()>(Err(anyhow!("no such column"))).is_err());
+        assert!(!idx.reconnect_needed.load(Ordering::Acquire));
+        assert_eq!(idx.ping().unwrap().version, version);
     }
 
     #[test]
-    fn test_next_label_correct_on_empty_reopen() {
+    fn test_schema_migration() {
         let dir = temp_dir();
-        let db_path = dir.path().join("test_empty.lance");
+        let db_path = dir.path().join("test.lance");
         let db_path_str = db_path.to_str().unwrap();
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vector = Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true);
+        let schema = Schema::new(vec![
+            vector.clone(),
+            Field::new("n", DataType::Int32, true),
+            Field::new("old", DataType::Utf8, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "t") }.unwrap();
+        let vectors = FixedSizeListArray::new(item, 2, Arc::new(Float32Array::from(vec![1.0, 0.0, 0.0, 1.0])), None);
+        let columns: Vec<(Arc<Field>, ArrayRef)> = vec![
+            (Arc::new(vector.clone()), Arc::new(vectors)),
+            (Arc::new(Field::new("n", DataType::Int32, true)), Arc::new(arrow_array::Int32Array::from(vec![7, 8]))),
+            (Arc::new(Field::new("old", DataType::Utf8, true)), Arc::new(StringArray::from(vec!["a", "b"]))),
+        ];
+        let (mut array, mut ffi_schema) = arrow::ffi::to_ffi(&StructArray::from(columns).into_data()).unwrap();
+        unsafe { idx.add_batch_arrow(&mut ffi_schema, &mut array) }.unwrap();
 
-        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
-        drop(idx);
+        let target = Schema::new(vec![
+            vector,
+            Field::new("n", DataType::Int64, true),
+            Field::new("category", DataType::Utf8, true),
+        ]);
+        let plan = idx.schema_diff(&target).unwrap();
+        assert_eq!(plan.steps.len(), 3);
+        // A column a setting reads is not dropped, and nothing else is applied
+        idx.set_sensitive_columns(&["old".to_string()]).unwrap();
+        let err = idx.apply_migration(&plan).unwrap_err();
+        assert!(err.to_string().contains("sensitive_columns"), "{}", err);
+        assert_eq!(idx.schema_diff(&target).unwrap(), plan);
+        idx.set_sensitive_columns(&[]).unwrap();
+        assert_eq!(idx.apply_migration(&plan).unwrap(), 3);
+        // Applying again finds the table already migrated
+        assert!(idx.apply_migration(&plan).is_err());
+        assert!(idx.schema_diff(&target).unwrap().is_empty());
 
-        let idx2 = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
-        let label = idx2.add_vector(&[1.0, 2.0]).unwrap();
-        assert_eq!(label, 0);
+        let reopened = LanceIndex::open(db_path_str, "t", "l2").unwrap();
+        let batch = reopened.scan(&["n".to_string(), "category".to_string()], None, true).unwrap();
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Int64);
+        assert_eq!(batch.column(1).null_count(), 2);
+        assert!(reopened.schema.field_with_name("old").is_err());
     }
 
     #[test]
-    fn test_open_derives_schema() {
+    fn test_cold_storage_moves_old_rows() {
         let dir = temp_dir();
-        let db_path = dir.path().join("test_schema.lance");
+        let db_path = dir.path().join("test_cold_storage.lance");
         let db_path_str = db_path.to_str().unwrap();
 
-        let idx = LanceIndex::create(db_path_str, 3, "l2", "vectors").unwrap();
-        for i in 0..5 {
-            idx.add_vector(&[i as f32, 0.0, 0.0]).unwrap();
-        }
-        idx.delete(1).unwrap();
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Schema::new(vec![
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), true),
+            Field::new("ts", DataType::Int64, true),
+        ]);
+        let mut ffi_schema = FFI_ArrowSchema::try_from(&schema).unwrap();
+        let idx = unsafe { LanceIndex::create_from_arrow(db_path_str, &mut ffi_schema, "l2", "vectors") }.unwrap();
+        let now = access::now_ms();
+        let values = Float32Array::from(vec![1.0, 0.3, 1.0, 0.3, 0.0, 9.0]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(FixedSizeListArray::new(item.clone(), 2, Arc::new(values), None)),
+            Arc::new(Int64Array::from(vec![0, now, 0])),
+        ];
+        let data = StructArray::new(schema.fields().clone(), columns, None).into_data();
+        let (mut array, mut array_schema) = arrow::ffi::to_ffi(&data).unwrap();
+        assert_eq!(unsafe { idx.add_batch_arrow(&mut array_schema, &mut array) }.unwrap(), vec![0, 1, 2]);
+
+        assert!(idx.set_cold_storage(Some(ColdStorage::parse("column=missing, after_days=1").unwrap())).is_err());
+        idx.set_cold_storage(Some(ColdStorage::parse("column=ts, after_days=1, precision=int8").unwrap()))
+            .unwrap();
+        idx.compact().unwrap();
+
+        // The old rows left the table for the sidecar; the recent one kept full precision
+        let hot = runtime::block_on(idx.get_table().unwrap().count_rows(None)).unwrap();
+        assert_eq!(hot, 1);
+        assert_eq!(idx.count().unwrap(), 3);
+        let old = idx.get_vector(0).unwrap();
+        assert_eq!(old[0], 1.0);
+        assert!((old[1] - 38.0 / 127.0).abs() < 1.0e-6);
+        assert_eq!(idx.get_vector(1).unwrap(), vec![1.0, 0.3]);
+
+        // Searches merge the cold rows, ranked exactly at their reduced precision
+        let hits = idx.search(&[1.0, 0.3], 3, 0, 0, 0, None, false).unwrap();
+        let labels: Vec<i64> = hits.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec![1, 0, 2]);
+        assert_eq!(hits[0].1, 0.0);
+        assert!(hits[1].1 > 0.0);
+        let hits = idx.search(&[1.0, 0.3], 3, 0, 0, 0, Some("label != 1"), false).unwrap();
+        assert_eq!(hits.iter().map(|(label, _)| *label).collect::<Vec<_>>(), vec![0, 2]);
+
+        // Nothing is left to move, and the precision cannot change under cold rows
+        let report = idx.run_maintenance(&MaintenancePlan::parse("compact(1000)").unwrap()).unwrap();
+        assert_eq!(report.steps[0].status, StepStatus::Skipped);
+        let f16 = ColdStorage::parse("column=ts, after_days=1, precision=f16").unwrap();
+        assert!(idx.set_cold_storage(Some(f16)).unwrap_err().to_string().contains("int8 precision"));
+
         idx.delete(2).unwrap();
+        assert_eq!(idx.count().unwrap(), 2);
+        assert!(idx.get_vector(2).is_err());
 
         drop(idx);
-
-        let idx2 = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
-        let new_label = idx2.add_vector(&[99.0, 0.0, 0.0]).unwrap();
-        assert!(
-            new_label >= 5,
-            "expected label >= 5 via open, got {new_label}"
-        );
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        assert_eq!(reopened.cold_storage().unwrap().precision, cold::Precision::Int8);
+        reopened.set_cold_storage(None).unwrap();
+        drop(reopened);
+        // Removing the policy leaves the moved rows searchable
+        let reopened = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let hits = reopened.search(&[1.0, 0.3], 3, 0, 0, 0, None, false).unwrap();
+        assert_eq!(hits.iter().map(|(label, _)| *label).collect::<Vec<_>>(), vec![1, 0]);
     }
 
     #[test]
-    fn test_custom_table_name() {
+    fn test_append_session_coalesces() {
         let dir = temp_dir();
-        let db_path = dir.path().join("test_tbl.lance");
+        let db_path = dir.path().join("test_append_session.lance");
         let db_path_str = db_path.to_str().unwrap();
 
-        // Two indexes in same dataset with different table names
-        let idx_a = LanceIndex::create(db_path_str, 2, "l2", "idx_a").unwrap();
-        let idx_b = LanceIndex::create(db_path_str, 2, "l2", "idx_b").unwrap();
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        let version = |idx: &LanceIndex| runtime::block_on(idx.get_table().unwrap().version()).unwrap();
+        let before = version(&idx);
 
-        idx_a.add_vector(&[1.0, 0.0]).unwrap();
-        idx_a.add_vector(&[2.0, 0.0]).unwrap();
-        idx_b.add_vector(&[10.0, 0.0]).unwrap();
+        idx.begin_append_session().unwrap();
+        let mut labels = Vec::new();
+        for i in 0..3 {
+            labels.extend(idx.add_batch(&[i as f32, 0.0, i as f32, 1.0], 2).unwrap());
+        }
+        assert_eq!(labels, (0..6).collect::<Vec<_>>());
+        assert_eq!(idx.count().unwrap(), 0);
 
-        assert_eq!(idx_a.count().unwrap(), 2);
-        assert_eq!(idx_b.count().unwrap(), 1);
+        idx.end_append_session().unwrap();
+        assert_eq!(idx.count().unwrap(), 6);
+        assert_eq!(version(&idx), before + 1);
 
-        // Reopen each — they stay independent
-        drop(idx_a);
-        drop(idx_b);
-        let idx_a2 = LanceIndex::open(db_path_str, "idx_a", "l2").unwrap();
-        let idx_b2 = LanceIndex::open(db_path_str, "idx_b", "l2").unwrap();
-        assert_eq!(idx_a2.count().unwrap(), 2);
-        assert_eq!(idx_b2.count().unwrap(), 1);
+        idx.add_batch(&[9.0, 9.0], 1).unwrap();
+        assert_eq!(idx.count().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_dropped_session_reports_lost_appends() {
+        let dir = temp_dir();
+        let db_path = dir.path().join("test_lost_appends.lance");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let idx = LanceIndex::create(db_path_str, 2, "l2", "vectors").unwrap();
+        idx.set_quota(Some(Quota::parse("max_rows=1").unwrap())).unwrap();
+        idx.begin_append_session().unwrap();
+        idx.add_batch(&[0.0, 0.0, 1.0, 1.0], 2).unwrap();
+        // The quota rejects the flush on drop
+        drop(idx);
+
+        let idx = LanceIndex::open(db_path_str, "vectors", "l2").unwrap();
+        let err = idx.add_vector(&[2.0, 2.0]).unwrap_err().to_string();
+        assert!(err.contains("2 buffered rows of vectors were not written"), "{}", err);
+        assert!(err.contains("quota exceeded"), "{}", err);
+        // Reported once
+        idx.add_vector(&[2.0, 2.0]).unwrap();
+        assert_eq!(idx.count().unwrap(), 1);
     }
 }
@@ -0,0 +1,304 @@
+//! Advisory single-writer leases for local tables.
+//!
+//! Two processes appending to the same Lance table both succeed, but every commit
+//! races the other's and has to be retried or rebased. When a table has a writer
+//! lease configured, a handle must hold the lease before it writes. The lease is a
+//! `<table>.writer.lock` file next to the dataset naming its holder and when it
+//! expires; the holder extends it on every write, and a lease that is not extended
+//! within its TTL (e.g. the process died) can be taken over.
+//!
+//! A free lease is taken by linking a complete record into place, which fails if
+//! the file exists. Every other change (renewing, releasing, taking over an expired
+//! lease) is a compare-and-replace: the writer first creates a claim file named after
+//! the record it expects with `create_new`, so only one process can replace a given
+//! record, and checks the record is unchanged before replacing it.
+//!
+//! Leases belong to a process, not a handle: handles on one table in the same
+//! process share one, and it is released when the last of them is dropped.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::time::Duration;
+
+use crate::access::now_ms;
+
+static LEASES: LazyLock<Mutex<HashMap<PathBuf, Weak<WriterLease>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Distinguishes this process from an earlier one that reused its pid.
+static OWNER: LazyLock<String> = LazyLock::new(|| format!("{}:{}", std::process::id(), now_ms()));
+
+/// How long a claim may stand before it counts as left behind by a crashed process.
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Lock file of `table_name` in a local database directory.
+pub fn lock_path(db_dir: &Path, table_name: &str) -> PathBuf {
+    db_dir.join(format!("{}.writer.lock", table_name))
+}
+
+/// Holder and expiry recorded in a lock file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LeaseRecord {
+    owner: String,
+    expires_ms: i64,
+}
+
+impl LeaseRecord {
+    fn encode(&self) -> String {
+        format!("{}\n{}\n", self.owner, self.expires_ms)
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        let owner = lines.next()?.to_string();
+        let expires_ms = lines.next()?.parse().ok()?;
+        Some(Self { owner, expires_ms })
+    }
+}
+
+/// This process's lease on one table.
+#[derive(Debug)]
+pub struct WriterLease {
+    path: PathBuf,
+    ttl_ms: i64,
+    expires_ms: Mutex<i64>,
+}
+
+impl WriterLease {
+    /// Take the lease at `path`, or share it if this process already holds it.
+    pub fn acquire(path: &Path, ttl_ms: i64) -> Result<Arc<Self>> {
+        if ttl_ms <= 0 {
+            return Err(anyhow!("writer lease TTL must be positive"));
+        }
+        let mut leases = LEASES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = leases.get(path).and_then(Weak::upgrade) {
+            return Ok(existing);
+        }
+        leases.retain(|_, l| l.strong_count() > 0);
+
+        let lease = Arc::new(Self {
+            path: path.to_path_buf(),
+            ttl_ms,
+            expires_ms: Mutex::new(0),
+        });
+        // A second round covers a lease released between the two steps
+        for _ in 0..2 {
+            let record = lease.record(now_ms());
+            let taken = match read_record(path)? {
+                None => create_record(path, &record)?,
+                Some(held) => {
+                    check_live(&held)?;
+                    // Expired, unreadable, or left behind by this process
+                    compare_and_replace(path, &held, Some(&record))?
+                }
+            };
+            if taken {
+                *lease.expires_ms.lock().unwrap_or_else(|e| e.into_inner()) = record.expires_ms;
+                leases.insert(path.to_path_buf(), Arc::downgrade(&lease));
+                return Ok(lease);
+            }
+        }
+        Err(anyhow!("table is locked by another writer that took the lease concurrently"))
+    }
+
+    /// Confirm the lease is still ours and extend it once half of the TTL is used.
+    pub fn renew(&self) -> Result<()> {
+        let now = now_ms();
+        let mut expires = self.expires_ms.lock().unwrap_or_else(|e| e.into_inner());
+        if *expires - now > self.ttl_ms / 2 {
+            return Ok(());
+        }
+        let held = LeaseRecord {
+            owner: OWNER.clone(),
+            expires_ms: *expires,
+        };
+        let record = self.record(now);
+        if !compare_and_replace(&self.path, &held, Some(&record))? {
+            return Err(anyhow!("writer lease was lost to another writer after it expired"));
+        }
+        *expires = record.expires_ms;
+        Ok(())
+    }
+
+    fn record(&self, now: i64) -> LeaseRecord {
+        LeaseRecord {
+            owner: OWNER.clone(),
+            expires_ms: now + self.ttl_ms,
+        }
+    }
+}
+
+impl Drop for WriterLease {
+    fn drop(&mut self) {
+        let held = LeaseRecord {
+            owner: OWNER.clone(),
+            expires_ms: *self.expires_ms.lock().unwrap_or_else(|e| e.into_inner()),
+        };
+        let _ = compare_and_replace(&self.path, &held, None);
+    }
+}
+
+/// Write `record` to `path` unless the file exists. The record is linked into place
+/// complete, so readers never see it half-written. Returns whether it was written.
+fn create_record(path: &Path, record: &LeaseRecord) -> Result<bool> {
+    let tmp = path.with_extension(format!("lock.{}", std::process::id()));
+    let linked = std::fs::write(&tmp, record.encode()).and_then(|_| std::fs::hard_link(&tmp, path));
+    let _ = std::fs::remove_file(&tmp);
+    match linked {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(anyhow!("failed to write writer lease {}: {}", path.display(), e)),
+    }
+}
+
+/// Replace the record at `path` with `new` (or, with `None`, remove it) if it still
+/// is `expected`. Returns false, changing nothing, if the record changed or another
+/// process is replacing the same record.
+fn compare_and_replace(path: &Path, expected: &LeaseRecord, new: Option<&LeaseRecord>) -> Result<bool> {
+    let claim = path.with_extension(format!(
+        "lock.{}-{}.claim",
+        expected.owner.replace(':', "_"),
+        expected.expires_ms
+    ));
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&claim) {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let abandoned = std::fs::metadata(&claim)
+                .and_then(|m| m.modified())
+                .is_ok_and(|at| at.elapsed().is_ok_and(|age| age > CLAIM_TIMEOUT));
+            if abandoned {
+                let _ = std::fs::remove_file(&claim);
+            }
+            return Ok(false);
+        }
+        Err(e) => return Err(anyhow!("failed to claim writer lease {}: {}", path.display(), e)),
+    }
+    let replaced = replace_claimed(path, expected, new);
+    let _ = std::fs::remove_file(&claim);
+    replaced
+}
+
+/// [`compare_and_replace`] once the claim on `expected` is held.
+fn replace_claimed(path: &Path, expected: &LeaseRecord, new: Option<&LeaseRecord>) -> Result<bool> {
+    if read_record(path)?.as_ref() != Some(expected) {
+        return Ok(false);
+    }
+    let written = match new {
+        Some(record) => {
+            let tmp = path.with_extension(format!("lock.{}", std::process::id()));
+            std::fs::write(&tmp, record.encode()).and_then(|_| std::fs::rename(&tmp, path))
+        }
+        None => std::fs::remove_file(path),
+    };
+    written.map_err(|e| anyhow!("failed to write writer lease {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+/// Fail if another process holds a live lease at `path`. Used by writers on tables
+/// without a lease configured, so they cannot slip past one set up elsewhere.
+pub fn check_not_held(path: &Path) -> Result<()> {
+    match read_record(path)? {
+        Some(held) => check_live(&held),
+        None => Ok(()),
+    }
+}
+
+/// Fail if `held` is another process's unexpired lease.
+fn check_live(held: &LeaseRecord) -> Result<()> {
+    let now = now_ms();
+    if held.owner != *OWNER && held.expires_ms > now {
+        return Err(anyhow!(
+            "table is locked by another writer (process {}); its lease expires in {}s",
+            held.owner.split(':').next().unwrap_or("?"),
+            (held.expires_ms - now + 999) / 1000
+        ));
+    }
+    Ok(())
+}
+
+fn read_record(path: &Path) -> Result<Option<LeaseRecord>> {
+    match std::fs::read_to_string(path) {
+        // An unreadable record is treated as expired
+        Ok(text) => Ok(Some(LeaseRecord::parse(&text).unwrap_or(LeaseRecord {
+            owner: String::new(),
+            expires_ms: 0,
+        }))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow!("failed to read writer lease {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer_lease() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = lock_path(dir.path(), "vectors");
+
+        let lease = WriterLease::acquire(&path, 60_000).unwrap();
+        assert!(Arc::ptr_eq(&lease, &WriterLease::acquire(&path, 60_000).unwrap()));
+        lease.renew().unwrap();
+
+        // A live lease held by another process blocks us
+        drop(lease);
+        assert!(!path.exists());
+        let other = LeaseRecord {
+            owner: "1:0".to_string(),
+            expires_ms: now_ms() + 60_000,
+        };
+        std::fs::write(&path, other.encode()).unwrap();
+        assert!(WriterLease::acquire(&path, 60_000).is_err());
+        assert!(check_not_held(&path).is_err());
+
+        // An expired one is taken over
+        let expired = LeaseRecord {
+            expires_ms: now_ms() - 1,
+            ..other
+        };
+        std::fs::write(&path, expired.encode()).unwrap();
+        let lease = WriterLease::acquire(&path, 60_000).unwrap();
+        assert_eq!(read_record(&path).unwrap().unwrap().owner, *OWNER);
+        drop(lease);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_compare_and_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = lock_path(dir.path(), "vectors");
+        let stale = LeaseRecord {
+            owner: "1:0".to_string(),
+            expires_ms: 1,
+        };
+        assert!(create_record(&path, &stale).unwrap());
+        assert!(!create_record(&path, &stale).unwrap());
+
+        // Two takeovers of the same expired record: only the first replaces it
+        let first = LeaseRecord {
+            owner: "2:0".to_string(),
+            expires_ms: now_ms() + 60_000,
+        };
+        let second = LeaseRecord {
+            owner: "3:0".to_string(),
+            ..first.clone()
+        };
+        assert!(compare_and_replace(&path, &stale, Some(&first)).unwrap());
+        assert!(!compare_and_replace(&path, &stale, Some(&second)).unwrap());
+        assert_eq!(read_record(&path).unwrap(), Some(first.clone()));
+
+        // A takeover in progress blocks another one; an abandoned claim is cleared
+        let claim = path.with_extension("lock.2_0-".to_string() + &first.expires_ms.to_string() + ".claim");
+        let file = std::fs::File::create(&claim).unwrap();
+        assert!(!compare_and_replace(&path, &first, None).unwrap());
+        let long_ago = std::time::SystemTime::now() - 2 * CLAIM_TIMEOUT;
+        file.set_modified(long_ago).unwrap();
+        assert!(!compare_and_replace(&path, &first, None).unwrap());
+        assert!(!claim.exists());
+        assert!(compare_and_replace(&path, &first, None).unwrap());
+        assert!(!path.exists());
+    }
+}
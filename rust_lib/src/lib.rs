@@ -1,3 +1,56 @@
+pub mod access;
+pub mod admission;
+pub mod approx;
+pub mod backup;
+pub mod capabilities;
+pub mod cast_plan;
+pub mod chunk;
+pub mod cold;
+pub mod consistency;
+pub mod constraints;
+pub mod credentials;
+pub mod cursor;
+pub mod disk_cache;
+pub mod distance;
+pub mod diversity;
+pub mod drift;
 pub mod ffi;
+pub mod format_info;
+pub mod fusion;
+pub mod handles;
+pub mod health;
+pub mod idempotency;
+pub mod index_params;
+pub mod label_bitmap;
 pub mod lance_manager;
+pub mod lease;
+pub mod maintenance;
+pub mod metadata;
+pub mod metrics;
+pub mod migration;
+pub mod multivector;
+pub mod pca;
+pub mod pipeline;
+pub mod projection;
+pub mod quantize;
+pub mod query_log;
+pub mod quota;
+pub mod read_limit;
+pub mod rebuild;
+pub mod reconcile;
+pub mod reembed;
+pub mod rejects;
+pub mod replication;
+pub mod rotation;
 pub mod runtime;
+pub mod scratch;
+pub mod sort;
+pub mod staging;
+pub mod stats;
+pub mod storage_options;
+pub mod task;
+pub mod transform;
+pub mod ttl;
+pub mod upsert;
+pub mod vector_storage;
+pub mod watch;
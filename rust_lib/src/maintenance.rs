@@ -0,0 +1,187 @@
+//! Declarative maintenance plans.
+//!
+//! A plan is a `;`-separated list of steps run in order by
+//! `LanceIndex::run_maintenance`, e.g. `optimize; compact(16); prune(7); verify`:
+//!
+//! - `optimize`: add unindexed rows to the existing indices.
+//! - `compact(N)`: rewrite small fragments, only when the table spans at least `N`
+//!   fragments (default [`DEFAULT_COMPACT_FRAGMENTS`]).
+//! - `prune(D)`: remove table versions older than `D` days (default 7).
+//! - `verify`: check that labels are unique and below the next label to assign.
+//!
+//! A failing step stops the plan; the steps after it are reported as skipped.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fragment count from which `compact` without an argument rewrites the table.
+pub const DEFAULT_COMPACT_FRAGMENTS: usize = 8;
+
+/// Age in days of the versions `prune` without an argument removes.
+pub const DEFAULT_PRUNE_DAYS: u64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceStep {
+    Optimize,
+    Compact { min_fragments: usize },
+    Prune { older_than_days: u64 },
+    Verify,
+}
+
+impl fmt::Display for MaintenanceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaintenanceStep::Optimize => write!(f, "optimize"),
+            MaintenanceStep::Compact { min_fragments } => write!(f, "compact({})", min_fragments),
+            MaintenanceStep::Prune { older_than_days } => write!(f, "prune({})", older_than_days),
+            MaintenanceStep::Verify => write!(f, "verify"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenancePlan {
+    pub steps: Vec<MaintenanceStep>,
+}
+
+impl MaintenancePlan {
+    /// Parse the text form, e.g. `optimize; compact(16); prune(7); verify`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut steps = Vec::new();
+        for part in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, arg) = match part.find('(') {
+                Some(open) => {
+                    let arg = part[open + 1..]
+                        .strip_suffix(')')
+                        .ok_or_else(|| anyhow!("unterminated argument in step '{}'", part))?;
+                    (part[..open].trim(), Some(arg.trim()))
+                }
+                None => (part, None),
+            };
+            let number = |n: &str| -> Result<u64> {
+                n.parse()
+                    .map_err(|_| anyhow!("{} argument must be a non-negative integer, got '{}'", name, n))
+            };
+            let step = match (name, arg) {
+                ("optimize", None) => MaintenanceStep::Optimize,
+                ("compact", None) => MaintenanceStep::Compact {
+                    min_fragments: DEFAULT_COMPACT_FRAGMENTS,
+                },
+                ("compact", Some(n)) => MaintenanceStep::Compact {
+                    min_fragments: number(n)? as usize,
+                },
+                ("prune", None) => MaintenanceStep::Prune {
+                    older_than_days: DEFAULT_PRUNE_DAYS,
+                },
+                ("prune", Some(n)) => MaintenanceStep::Prune {
+                    older_than_days: number(n)?,
+                },
+                ("verify", None) => MaintenanceStep::Verify,
+                _ => return Err(anyhow!("invalid maintenance step '{}'", part)),
+            };
+            steps.push(step);
+        }
+        if steps.is_empty() {
+            return Err(anyhow!("maintenance plan has no steps"));
+        }
+        Ok(Self { steps })
+    }
+}
+
+impl fmt::Display for MaintenancePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepStatus {
+    Done,
+    /// Not needed (e.g. `compact` below its fragment threshold) or not run
+    /// because an earlier step failed.
+    Skipped,
+    Failed,
+}
+
+impl fmt::Display for StepStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepStatus::Done => write!(f, "done"),
+            StepStatus::Skipped => write!(f, "skipped"),
+            StepStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    pub step: MaintenanceStep,
+    pub status: StepStatus,
+    /// What the step did, why it was skipped, or the error.
+    pub detail: String,
+    pub elapsed: Duration,
+}
+
+/// Outcome of every step of a plan, in plan order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    pub steps: Vec<StepReport>,
+}
+
+impl MaintenanceReport {
+    pub fn succeeded(&self) -> bool {
+        self.steps.iter().all(|s| s.status != StepStatus::Failed)
+    }
+
+    /// Rows of (step, status, detail, elapsed_ms).
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("step", DataType::Utf8, false),
+            Field::new("status", DataType::Utf8, false),
+            Field::new("detail", DataType::Utf8, false),
+            Field::new("elapsed_ms", DataType::Int64, false),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from_iter_values(self.steps.iter().map(|s| s.step.to_string()))),
+            Arc::new(StringArray::from_iter_values(self.steps.iter().map(|s| s.status.to_string()))),
+            Arc::new(StringArray::from_iter_values(self.steps.iter().map(|s| s.detail.as_str()))),
+            Arc::new(Int64Array::from_iter_values(
+                self.steps.iter().map(|s| s.elapsed.as_millis() as i64),
+            )),
+        ])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let plan = MaintenancePlan::parse("optimize; compact(16); prune(3); verify").unwrap();
+        assert_eq!(plan.steps.len(), 4);
+        assert_eq!(MaintenancePlan::parse(&plan.to_string()).unwrap(), plan);
+
+        let defaults = MaintenancePlan::parse("compact; prune").unwrap();
+        assert_eq!(defaults.to_string(), "compact(8); prune(7)");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_plans() {
+        assert!(MaintenancePlan::parse("").is_err());
+        assert!(MaintenancePlan::parse("vacuum").is_err());
+        assert!(MaintenancePlan::parse("optimize(2)").is_err());
+        assert!(MaintenancePlan::parse("prune(-1)").is_err());
+        assert!(MaintenancePlan::parse("compact(4").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Per-table settings persisted in the Lance schema metadata.
+//!
+//! Keys are namespaced with [`KEY_PREFIX`] so they never collide with metadata
+//! written by other Lance clients. Settings travel with the dataset, so a table
+//! reopened from another process sees the same configuration.
+
+use anyhow::{anyhow, Result};
+use lancedb::Table as LanceTable;
+use std::collections::HashMap;
+
+use crate::runtime;
+
+pub const KEY_PREFIX: &str = "duckdb_lance:";
+
+/// Column the table was last physically clustered by (see `LanceIndex::cluster_by`).
+pub const CLUSTER_BY: &str = "cluster_by";
+
+/// Retrieval pipeline in its text form (see [`crate::pipeline::Pipeline`]).
+pub const PIPELINE: &str = "pipeline";
+
+/// Query preprocessing spec and the mean used by its `center` step
+/// (see [`crate::transform::QueryTransform`]).
+pub const QUERY_TRANSFORM: &str = "query_transform";
+pub const QUERY_MEAN: &str = "query_mean";
+
+/// Identifier and dimension of the embedding model the vectors came from.
+pub const EMBEDDING_MODEL: &str = "embedding_model";
+pub const EMBEDDING_DIM: &str = "embedding_dim";
+
+/// Distance type the vector index was last built with (see `LanceIndex::create_vector_index`).
+pub const INDEX_METRIC: &str = "index_metric";
+
+/// Compression ratio of the vector index's stored codes, used by auto refine
+/// (see `index_params::auto_refine_factor`).
+pub const INDEX_COMPRESSION: &str = "index_compression";
+
+/// Chunking stage in its text form (see [`crate::chunk::Chunking`]).
+pub const CHUNKING: &str = "chunking";
+
+/// Row/size quota in its text form (see [`crate::quota::Quota`]).
+pub const QUOTA: &str = "quota";
+
+/// Ingest constraints in their text form (see [`crate::constraints::Constraints`]).
+pub const CONSTRAINTS: &str = "constraints";
+
+/// What happens to rows that fail ingest validation (see
+/// [`crate::constraints::OnViolation`]); absent means `error`.
+pub const ON_INVALID: &str = "on_invalid";
+
+/// How `add_batch_arrow` matches incoming columns to the table's (see
+/// [`crate::cast_plan::ColumnMatching`]); absent means `positional`.
+pub const COLUMN_MATCHING: &str = "column_matching";
+
+/// Cold vector storage policy in its text form (see [`crate::cold::ColdStorage`]).
+pub const COLD_STORAGE: &str = "cold_storage";
+
+/// Set ("on") when search hits are recorded (see [`crate::access`]).
+pub const ACCESS_TRACKING: &str = "access_tracking";
+
+/// Query log setting in its text form (see [`crate::query_log`]).
+pub const QUERY_LOG: &str = "query_log";
+
+/// Set ("on") when searches hide rows past their `expires_at` (see [`crate::ttl`]).
+pub const ROW_TTL: &str = "row_ttl";
+
+/// Comma-separated columns withheld from unprivileged scans
+/// (see `LanceIndex::set_sensitive_columns`).
+pub const SENSITIVE_COLUMNS: &str = "sensitive_columns";
+
+/// Fitted PCA projection in its text form (see [`crate::pca::Pca::encode`]).
+pub const PCA: &str = "pca";
+
+/// Rotation applied to stored vectors and queries (see [`crate::rotation::Rotation::encode`]).
+pub const ROTATION: &str = "rotation";
+
+/// Scale and offset of int8 vector storage (see [`crate::quantize::Int8Quantizer::encode`]).
+pub const INT8_QUANTIZATION: &str = "int8_quantization";
+
+/// Dimension of the vectors being written while a re-embed is in progress (see
+/// [`crate::reembed`]).
+pub const REEMBED: &str = "reembed";
+
+/// [`INDEX_METRIC`] and [`INDEX_COMPRESSION`] of the index on the re-embed column;
+/// they become the table's when the re-embed finishes.
+pub const REEMBED_INDEX_METRIC: &str = "reembed_index_metric";
+pub const REEMBED_INDEX_COMPRESSION: &str = "reembed_index_compression";
+
+/// Writer lease TTL in seconds; set when writers must hold the lease (see [`crate::lease`]).
+pub const WRITER_LEASE: &str = "writer_lease";
+
+/// Highest label assigned in the fragments below an id, as `max_label@next_fragment`
+/// (see `LanceIndex::repair_label_watermark`).
+pub const LABEL_WATERMARK: &str = "label_watermark";
+
+/// Prefix of tagged drift baselines (see [`crate::drift::VectorStats::encode`]).
+pub const DRIFT_BASELINE_PREFIX: &str = "drift_baseline:";
+
+pub(crate) fn full_key(key: &str) -> String {
+    format!("{}{}", KEY_PREFIX, key)
+}
+
+/// Read a setting. Unset and empty values both read as `None`.
+pub fn get(table: &LanceTable, key: &str) -> Result<Option<String>> {
+    let schema = runtime::block_on(table.schema())?;
+    Ok(schema
+        .metadata()
+        .get(&full_key(key))
+        .filter(|v| !v.is_empty())
+        .cloned())
+}
+
+/// Write (or, with `None`, clear) a setting. Commits a new table version.
+pub fn set(table: &LanceTable, key: &str, value: Option<&str>) -> Result<()> {
+    set_all(table, &[(key, value)])
+}
+
+/// Write (or, with `None`, clear) several settings in one new table version, so
+/// readers never see some of them changed without the others.
+pub fn set_all(table: &LanceTable, settings: &[(&str, Option<&str>)]) -> Result<()> {
+    let native = table
+        .as_native()
+        .ok_or_else(|| anyhow!("table metadata requires a native Lance table"))?;
+
+    // Lance replaces the whole map, so merge into the current metadata first.
+    let schema = runtime::block_on(table.schema())?;
+    let mut metadata: HashMap<String, String> = schema.metadata().clone();
+    for (key, value) in settings {
+        metadata.insert(full_key(key), value.unwrap_or_default().to_string());
+    }
+    runtime::block_on(native.replace_schema_metadata(metadata))?;
+    Ok(())
+}
+
+/// Settings whose key starts with `prefix`, keyed by the rest of the key.
+pub fn get_prefixed(table: &LanceTable, prefix: &str) -> Result<HashMap<String, String>> {
+    let schema = runtime::block_on(table.schema())?;
+    let full_prefix = full_key(prefix);
+    Ok(schema
+        .metadata()
+        .iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix(&full_prefix)?.to_string(), value.clone())))
+        .filter(|(_, value)| !value.is_empty())
+        .collect())
+}
+
+/// Replace every setting whose key starts with `prefix` by `entries` (keyed by the
+/// rest of the key). Commits a new table version.
+pub fn set_prefixed(table: &LanceTable, prefix: &str, entries: &HashMap<String, String>) -> Result<()> {
+    let native = table
+        .as_native()
+        .ok_or_else(|| anyhow!("table metadata requires a native Lance table"))?;
+
+    let schema = runtime::block_on(table.schema())?;
+    let full_prefix = full_key(prefix);
+    let mut metadata: HashMap<String, String> = schema.metadata().clone();
+    metadata.retain(|key, _| !key.starts_with(&full_prefix));
+    for (key, value) in entries {
+        metadata.insert(format!("{}{}", full_prefix, key), value.clone());
+    }
+    runtime::block_on(native.replace_schema_metadata(metadata))?;
+    Ok(())
+}
@@ -0,0 +1,300 @@
+//! Process-wide operation counters and latency histograms.
+//!
+//! Collection is always on (a few relaxed atomics per FFI call). With the
+//! `prometheus` cargo feature, an embedded HTTP endpoint serves them in the
+//! Prometheus text exposition format.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Operation classes tracked by the metrics registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Search,
+    Add,
+    Delete,
+    Merge,
+    Compact,
+    IndexBuild,
+}
+
+impl Op {
+    const ALL: [Op; 6] = [Op::Search, Op::Add, Op::Delete, Op::Merge, Op::Compact, Op::IndexBuild];
+
+    fn label(self) -> &'static str {
+        match self {
+            Op::Search => "search",
+            Op::Add => "add",
+            Op::Delete => "delete",
+            Op::Merge => "merge",
+            Op::Compact => "compact",
+            Op::IndexBuild => "index_build",
+        }
+    }
+}
+
+/// Upper bounds (seconds) of the latency histogram buckets; +Inf is implicit.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0,
+];
+
+struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS.len()],
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        // Buckets are stored non-cumulatively and summed at render time.
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|&b| secs <= b) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+struct OpMetrics {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    rows: AtomicU64,
+    latency: Histogram,
+}
+
+impl OpMetrics {
+    const fn new() -> Self {
+        Self {
+            calls: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            rows: AtomicU64::new(0),
+            latency: Histogram::new(),
+        }
+    }
+}
+
+static OPS: [OpMetrics; Op::ALL.len()] = [const { OpMetrics::new() }; Op::ALL.len()];
+
+/// Run `f`, recording one call (and an error if it fails) plus its latency under `op`.
+pub fn observe<T>(op: Op, f: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let start = Instant::now();
+    let result = f();
+    let m = &OPS[op as usize];
+    m.calls.fetch_add(1, Ordering::Relaxed);
+    if result.is_err() {
+        m.errors.fetch_add(1, Ordering::Relaxed);
+    }
+    m.latency.observe(start.elapsed());
+    result
+}
+
+/// Count rows processed by `op` (vectors added, labels deleted, hits returned, ...).
+pub fn add_rows(op: Op, rows: u64) {
+    OPS[op as usize].rows.fetch_add(rows, Ordering::Relaxed);
+}
+
+/// Total calls recorded for `op`.
+pub fn calls(op: Op) -> u64 {
+    OPS[op as usize].calls.load(Ordering::Relaxed)
+}
+
+/// Render all metrics in the Prometheus text exposition format (version 0.0.4).
+pub fn render_prometheus() -> String {
+    let mut out = String::new();
+
+    let counters: [(&str, &str, fn(&OpMetrics) -> u64); 3] = [
+        ("lance_operations_total", "Operations executed", |m| m.calls.load(Ordering::Relaxed)),
+        ("lance_operation_errors_total", "Operations that returned an error", |m| {
+            m.errors.load(Ordering::Relaxed)
+        }),
+        ("lance_operation_rows_total", "Rows processed by operations", |m| {
+            m.rows.load(Ordering::Relaxed)
+        }),
+    ];
+    for (name, help, value) in counters {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        for op in Op::ALL {
+            let _ = writeln!(out, "{}{{op=\"{}\"}} {}", name, op.label(), value(&OPS[op as usize]));
+        }
+    }
+
+    let name = "lance_operation_duration_seconds";
+    let _ = writeln!(out, "# HELP {} Operation latency", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for op in Op::ALL {
+        let h = &OPS[op as usize].latency;
+        let mut cumulative = 0u64;
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&h.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{}_bucket{{op=\"{}\",le=\"{}\"}} {}", name, op.label(), bound, cumulative);
+        }
+        let count = h.count.load(Ordering::Relaxed);
+        let sum = h.sum_micros.load(Ordering::Relaxed) as f64 / 1e6;
+        let _ = writeln!(out, "{}_bucket{{op=\"{}\",le=\"+Inf\"}} {}", name, op.label(), count);
+        let _ = writeln!(out, "{}_sum{{op=\"{}\"}} {}", name, op.label(), sum);
+        let _ = writeln!(out, "{}_count{{op=\"{}\"}} {}", name, op.label(), count);
+    }
+
+    out
+}
+
+#[cfg(feature = "prometheus")]
+pub use exporter::start_exporter;
+
+#[cfg(feature = "prometheus")]
+mod exporter {
+    use anyhow::{anyhow, Result};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    static RUNNING: Mutex<Option<SocketAddr>> = Mutex::new(None);
+
+    /// Connections being served; past the cap new ones are closed unanswered.
+    static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+    /// Longest a scrape may wait on a slow or idle client before it is dropped.
+    const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Most connections served at once, so a flood of clients cannot spawn
+    /// unbounded threads.
+    pub(super) const MAX_CONNECTIONS: usize = 4;
+
+    /// Start the metrics endpoint on `bind_addr` (e.g. "127.0.0.1:9464").
+    ///
+    /// Accepts on a dedicated thread and serves each connection on its own, with
+    /// read and write timeouts, so an idle client cannot hold up other scrapes.
+    /// At most `MAX_CONNECTIONS` are served at once; further connections are
+    /// closed until one finishes. Only one exporter may run per process. Returns the bound address (useful
+    /// with port 0).
+    pub fn start_exporter(bind_addr: &str) -> Result<SocketAddr> {
+        let mut running = RUNNING.lock().map_err(|_| anyhow!("metrics exporter lock poisoned"))?;
+        if let Some(addr) = *running {
+            return Err(anyhow!("metrics exporter already running on {}", addr));
+        }
+
+        let listener = TcpListener::bind(bind_addr)?;
+        let addr = listener.local_addr()?;
+        std::thread::Builder::new()
+            .name("lance-metrics".into())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if ACTIVE.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                        ACTIVE.fetch_sub(1, Ordering::AcqRel);
+                        continue;
+                    }
+                    // A misbehaving scraper must not take the endpoint down.
+                    let spawned = std::thread::Builder::new().name("lance-metrics-conn".into()).spawn(move || {
+                        let _ = serve(stream);
+                        ACTIVE.fetch_sub(1, Ordering::AcqRel);
+                    });
+                    if spawned.is_err() {
+                        ACTIVE.fetch_sub(1, Ordering::AcqRel);
+                    }
+                }
+            })?;
+
+        *running = Some(addr);
+        Ok(addr)
+    }
+
+    fn serve(mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Drain headers up to the blank line.
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let (status, content_type, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some("/metrics")) => (
+                "200 OK",
+                "text/plain; version=0.0.4",
+                super::render_prometheus(),
+            ),
+            _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_records_calls_errors_and_latency() {
+        let before = calls(Op::Compact);
+        let _ = observe(Op::Compact, || Ok(()));
+        let _: anyhow::Result<()> = observe(Op::Compact, || Err(anyhow::anyhow!("boom")));
+        assert_eq!(calls(Op::Compact), before + 2);
+
+        let text = render_prometheus();
+        assert!(text.contains("# TYPE lance_operations_total counter"));
+        assert!(text.contains("lance_operation_errors_total{op=\"compact\"}"));
+        assert!(text.contains("lance_operation_duration_seconds_bucket{op=\"compact\",le=\"+Inf\"}"));
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn test_exporter_serves_metrics() {
+        use std::io::{Read, Write};
+
+        let addr = start_exporter("127.0.0.1:0").unwrap();
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("lance_operations_total"));
+
+        // An idle connection does not hold up the next scrape
+        let _idle = std::net::TcpStream::connect(addr).unwrap();
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(std::time::Duration::from_secs(2))).unwrap();
+        write!(stream, "GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // Past the connection cap a client is closed without a response
+        let _held: Vec<_> = (0..exporter::MAX_CONNECTIONS)
+            .map(|_| std::net::TcpStream::connect(addr).unwrap())
+            .collect();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(std::time::Duration::from_secs(2))).unwrap();
+        let _ = write!(stream, "GET /metrics HTTP/1.1\r\n\r\n");
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        assert!(response.is_empty());
+
+        assert!(start_exporter("127.0.0.1:0").is_err());
+    }
+}
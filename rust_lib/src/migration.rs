@@ -0,0 +1,320 @@
+//! Declarative schema migrations.
+//!
+//! [`MigrationPlan::diff`] compares the live table schema with a desired one, given
+//! like the schema of `create_from_arrow` (data columns, without the label), and
+//! lists the steps that turn one into the other:
+//!
+//! - `drop`: a column missing from the desired schema.
+//! - `cast`: a column whose type differs; its values are converted.
+//! - `set_nullable`: a non-nullable column that becomes nullable.
+//! - `add`: a new column, NULL for existing rows, so it must be nullable.
+//!
+//! Columns are matched by name; their order and field metadata are ignored. The
+//! label and vector columns cannot change, and a column cannot become non-nullable.
+//! The plan records the type each step expects to find, so applying a plan to a table
+//! that changed since the diff fails before any step runs. So does a plan dropping or
+//! casting a column a table setting reads (see [`MigrationPlan::check_references`]).
+//! Drops are applied last, so a plan that fails part way has not lost any data.
+
+use anyhow::{anyhow, Result};
+use arrow::compute::can_cast_types;
+use arrow_array::{RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationStep {
+    Drop { column: String, from: DataType },
+    Cast { column: String, from: DataType, to: DataType },
+    SetNullable { column: String, data_type: DataType },
+    Add { field: Field },
+}
+
+impl MigrationStep {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MigrationStep::Drop { .. } => "drop",
+            MigrationStep::Cast { .. } => "cast",
+            MigrationStep::SetNullable { .. } => "set_nullable",
+            MigrationStep::Add { .. } => "add",
+        }
+    }
+
+    pub fn column(&self) -> &str {
+        match self {
+            MigrationStep::Drop { column, .. }
+            | MigrationStep::Cast { column, .. }
+            | MigrationStep::SetNullable { column, .. } => column,
+            MigrationStep::Add { field } => field.name(),
+        }
+    }
+
+    /// Type the step expects the column to have now; `None` for new columns.
+    pub fn from_type(&self) -> Option<&DataType> {
+        match self {
+            MigrationStep::Drop { from, .. } | MigrationStep::Cast { from, .. } => Some(from),
+            MigrationStep::SetNullable { data_type, .. } => Some(data_type),
+            MigrationStep::Add { .. } => None,
+        }
+    }
+
+    /// Type of the column after the step; `None` for dropped columns.
+    pub fn to_type(&self) -> Option<&DataType> {
+        match self {
+            MigrationStep::Drop { .. } => None,
+            MigrationStep::Cast { to, .. } => Some(to),
+            MigrationStep::SetNullable { data_type, .. } => Some(data_type),
+            MigrationStep::Add { field } => Some(field.data_type()),
+        }
+    }
+}
+
+/// Steps in the order they are applied: casts, nullability, additions, then drops.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationPlan {
+    pub steps: Vec<MigrationStep>,
+}
+
+impl MigrationPlan {
+    /// Steps turning `current` (the table schema, label first) into `target`.
+    pub fn diff(current: &Schema, target: &Schema) -> Result<Self> {
+        let label = current.field(0).name();
+        let mut drops = Vec::new();
+        let mut casts = Vec::new();
+        let mut nullability = Vec::new();
+        let mut adds = Vec::new();
+
+        for field in current.fields().iter().skip(1) {
+            let column = field.name();
+            let Ok(wanted) = target.field_with_name(column) else {
+                if column == "vector" {
+                    return Err(anyhow!("column vector cannot be dropped"));
+                }
+                drops.push(MigrationStep::Drop {
+                    column: column.clone(),
+                    from: field.data_type().clone(),
+                });
+                continue;
+            };
+            if !field.data_type().equals_datatype(wanted.data_type()) {
+                if column == "vector" {
+                    return Err(anyhow!(
+                        "column vector cannot change from {} to {}",
+                        field.data_type(),
+                        wanted.data_type()
+                    ));
+                }
+                if !can_cast_types(field.data_type(), wanted.data_type()) {
+                    return Err(anyhow!(
+                        "column {} cannot be converted from {} to {}",
+                        column,
+                        field.data_type(),
+                        wanted.data_type()
+                    ));
+                }
+                casts.push(MigrationStep::Cast {
+                    column: column.clone(),
+                    from: field.data_type().clone(),
+                    to: wanted.data_type().clone(),
+                });
+            }
+            match (field.is_nullable(), wanted.is_nullable()) {
+                (true, false) => return Err(anyhow!("column {} cannot become non-nullable", column)),
+                (false, true) => nullability.push(MigrationStep::SetNullable {
+                    column: column.clone(),
+                    data_type: wanted.data_type().clone(),
+                }),
+                _ => {}
+            }
+        }
+
+        for field in target.fields() {
+            if field.name() == label {
+                if field.data_type() != current.field(0).data_type() {
+                    return Err(anyhow!("column {} cannot change", label));
+                }
+                continue;
+            }
+            if current.field_with_name(field.name()).is_ok() {
+                continue;
+            }
+            if !field.is_nullable() {
+                return Err(anyhow!("new column {} must be nullable", field.name()));
+            }
+            adds.push(MigrationStep::Add {
+                field: field.as_ref().clone().with_metadata(Default::default()),
+            });
+        }
+
+        let mut steps = casts;
+        steps.extend(nullability);
+        steps.extend(adds);
+        steps.extend(drops);
+        Ok(Self { steps })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Check that `current` is the schema the plan was made against, as far as its
+    /// steps are concerned.
+    pub fn check(&self, current: &Schema) -> Result<()> {
+        for step in &self.steps {
+            let found = current.field_with_name(step.column()).ok();
+            let stale = match (step.from_type(), found) {
+                (None, found) => found.is_some(),
+                (Some(expected), Some(field)) => !field.data_type().equals_datatype(expected),
+                (Some(_), None) => true,
+            };
+            if stale {
+                return Err(anyhow!(
+                    "table schema changed since the migration plan was made (column {}); diff again",
+                    step.column()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse a plan that drops or casts a column in `references`, the columns table
+    /// settings read, each paired with its setting.
+    pub fn check_references(&self, references: &[(String, &str)]) -> Result<()> {
+        for step in &self.steps {
+            if !matches!(step, MigrationStep::Drop { .. } | MigrationStep::Cast { .. }) {
+                continue;
+            }
+            if let Some((column, setting)) = references.iter().find(|(c, _)| c == step.column()) {
+                return Err(anyhow!(
+                    "cannot {} column {}: the table's {} setting uses it; change the setting first",
+                    step.kind(),
+                    column,
+                    setting
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The plan as (step, column, from_type, to_type) rows.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let type_name = |t: Option<&DataType>| t.map(|t| t.to_string());
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("step", DataType::Utf8, false),
+            Field::new("column", DataType::Utf8, false),
+            Field::new("from_type", DataType::Utf8, true),
+            Field::new("to_type", DataType::Utf8, true),
+        ]));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from_iter_values(self.steps.iter().map(|s| s.kind()))),
+                Arc::new(StringArray::from_iter_values(self.steps.iter().map(|s| s.column()))),
+                Arc::new(StringArray::from_iter(self.steps.iter().map(|s| type_name(s.from_type())))),
+                Arc::new(StringArray::from_iter(self.steps.iter().map(|s| type_name(s.to_type())))),
+            ],
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector_type(dim: i32, item: &str) -> DataType {
+        DataType::FixedSizeList(Arc::new(Field::new(item, DataType::Float32, true)), dim)
+    }
+
+    fn current() -> Schema {
+        Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", vector_type(2, "item"), true),
+            Field::new("ts", DataType::Int32, false),
+            Field::new("old", DataType::Utf8, true),
+        ])
+    }
+
+    #[test]
+    fn test_diff_orders_steps() {
+        let target = Schema::new(vec![
+            Field::new("category", DataType::Utf8, true),
+            Field::new("vector", vector_type(2, ""), true),
+            Field::new("ts", DataType::Int64, true),
+        ]);
+        let plan = MigrationPlan::diff(&current(), &target).unwrap();
+        let steps: Vec<(&str, &str)> = plan.steps.iter().map(|s| (s.kind(), s.column())).collect();
+        assert_eq!(
+            steps,
+            vec![("cast", "ts"), ("set_nullable", "ts"), ("add", "category"), ("drop", "old")]
+        );
+        plan.check(&current()).unwrap();
+
+        let batch = plan.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 4);
+        let to = batch.column(3).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(to.value(0), "Int64");
+        assert!(to.is_null(3));
+
+        let unchanged = Schema::new(current().fields()[1..].to_vec());
+        assert!(MigrationPlan::diff(&current(), &unchanged).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_rejects_unsupported_changes() {
+        let with = |fields: Vec<Field>| Schema::new(fields);
+        let vector = Field::new("vector", vector_type(2, "item"), true);
+        let ts = Field::new("ts", DataType::Int32, false);
+        let old = Field::new("old", DataType::Utf8, true);
+
+        let resized = with(vec![Field::new("vector", vector_type(3, "item"), true), ts.clone(), old.clone()]);
+        assert!(MigrationPlan::diff(&current(), &resized).is_err());
+        assert!(MigrationPlan::diff(&current(), &with(vec![ts.clone(), old.clone()])).is_err());
+        let required = with(vec![vector.clone(), ts.clone(), Field::new("old", DataType::Utf8, false)]);
+        assert!(MigrationPlan::diff(&current(), &required).is_err());
+        let new_required = with(vec![vector.clone(), ts.clone(), old.clone(), Field::new("n", DataType::Int64, false)]);
+        assert!(MigrationPlan::diff(&current(), &new_required).is_err());
+        let uncastable = with(vec![vector, ts, Field::new("old", vector_type(2, "item"), true)]);
+        assert!(MigrationPlan::diff(&current(), &uncastable).is_err());
+    }
+
+    #[test]
+    fn test_check_references() {
+        let target = Schema::new(vec![
+            Field::new("vector", vector_type(2, "item"), true),
+            Field::new("ts", DataType::Int64, false),
+            Field::new("old", DataType::Utf8, true),
+        ]);
+        let cast = MigrationPlan::diff(&current(), &target).unwrap();
+        let err = cast.check_references(&[("ts".to_string(), "row_ttl")]).unwrap_err();
+        assert!(err.to_string().contains("cannot cast column ts"), "{}", err);
+        cast.check_references(&[("old".to_string(), "constraints")]).unwrap();
+
+        let dropped = Schema::new(current().fields()[1..3].to_vec());
+        let drop = MigrationPlan::diff(&current(), &dropped).unwrap();
+        let err = drop.check_references(&[("old".to_string(), "constraints")]).unwrap_err();
+        assert!(err.to_string().contains("cannot drop column old"), "{}", err);
+
+        // Making a referenced column nullable is allowed
+        let nullable = Schema::new(vec![
+            Field::new("vector", vector_type(2, "item"), true),
+            Field::new("ts", DataType::Int32, true),
+            Field::new("old", DataType::Utf8, true),
+        ]);
+        let plan = MigrationPlan::diff(&current(), &nullable).unwrap();
+        plan.check_references(&[("ts".to_string(), "row_ttl")]).unwrap();
+    }
+
+    #[test]
+    fn test_check_detects_stale_plan() {
+        let target = Schema::new(vec![
+            Field::new("vector", vector_type(2, "item"), true),
+            Field::new("ts", DataType::Int64, false),
+            Field::new("n", DataType::Int64, true),
+        ]);
+        let plan = MigrationPlan::diff(&current(), &target).unwrap();
+        let mut changed = current().fields().to_vec();
+        changed.push(Arc::new(Field::new("n", DataType::Int64, true)));
+        assert!(plan.check(&Schema::new(changed)).is_err());
+        assert!(plan.check(&Schema::new(current().fields()[..3].to_vec())).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Multivector (late-interaction) columns.
+//!
+//! A `List<FixedSizeList<float>>` column holds any number of vectors per row, such as
+//! the per-token embeddings of a ColBERT-style model. Tables created from an Arrow
+//! schema store such columns with Float32 elements, and they are recognized from the
+//! table schema on open. `search_multivector` ranks rows by Lance's multivector
+//! distance: for each query vector, the distance to the row's closest vector, summed
+//! over the query vectors. Under cosine this is the number of query vectors minus the
+//! MaxSim score, so smaller is closer as for every other distance.
+//!
+//! Lance indexes multivector columns only under cosine, which leaves an all-zero
+//! vector without a distance, so such vectors are refused at ingest like NaN
+//! elements. A row needs at least one vector; NULL rows are kept but never found.
+
+use anyhow::{anyhow, Result};
+use arrow_array::cast::AsArray;
+use arrow_array::types::Float32Type;
+use arrow_array::{Array, ArrayRef};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+use crate::constraints::Violation;
+use crate::lance_manager::MAX_DIMENSION;
+
+/// The dimension of a multivector column of `data_type`, if it is one.
+pub fn dimension(data_type: &DataType) -> Option<usize> {
+    match data_type {
+        DataType::List(item) => match item.data_type() {
+            DataType::FixedSizeList(element, dim) if element.data_type().is_floating() && *dim > 0 => {
+                Some(*dim as usize)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The stored type of `dim`-dimensional multivectors.
+pub fn stored_type(dim: usize) -> DataType {
+    let element = Arc::new(Field::new("item", DataType::Float32, true));
+    let vector = Field::new("item", DataType::FixedSizeList(element, dim as i32), true);
+    DataType::List(Arc::new(vector))
+}
+
+/// `field` as stored, if it is a multivector column.
+pub fn stored_field(field: &Field) -> Result<Option<Field>> {
+    let Some(dim) = dimension(field.data_type()) else {
+        return Ok(None);
+    };
+    if dim > MAX_DIMENSION {
+        return Err(anyhow!(
+            "multivector column {} has dimension {}, expected 1 to {}",
+            field.name(),
+            dim,
+            MAX_DIMENSION
+        ));
+    }
+    Ok(Some(field.clone().with_data_type(stored_type(dim))))
+}
+
+/// The multivector columns of `schema` with their dimensions, in schema order.
+pub fn columns(schema: &Schema) -> Vec<(String, usize)> {
+    schema
+        .fields()
+        .iter()
+        .filter_map(|f| dimension(f.data_type()).map(|dim| (f.name().clone(), dim)))
+        .collect()
+}
+
+/// Split flattened `queries` into `dim`-wide query vectors.
+pub fn query_vectors(queries: &[f32], dim: usize) -> Result<Vec<&[f32]>> {
+    if queries.is_empty() || queries.len() % dim != 0 {
+        return Err(anyhow!("{} query values do not split into {}-dimensional query vectors", queries.len(), dim));
+    }
+    if queries.iter().any(|v| !v.is_finite()) {
+        return Err(anyhow!("query vectors have NaN or infinite elements"));
+    }
+    Ok(queries.chunks_exact(dim).collect())
+}
+
+/// Rows of multivector column `column` (stored type) that Lance cannot rank: empty
+/// rows, NULL, NaN or infinite elements, and all-zero vectors.
+pub fn check_rows(name: &str, column: &ArrayRef) -> Vec<Violation> {
+    let Some(lists) = column.as_list_opt::<i32>() else {
+        return Vec::new();
+    };
+    let mut invalid = Vec::new();
+    for row in 0..lists.len() {
+        if lists.is_null(row) {
+            continue;
+        }
+        let vectors = lists.value(row);
+        let vectors = vectors.as_fixed_size_list();
+        let Some(elements) = vectors.values().as_primitive_opt::<Float32Type>() else {
+            continue;
+        };
+        let values = elements.values();
+        let dim = vectors.value_length() as usize;
+        let reason = if vectors.is_empty() {
+            "has no vectors"
+        } else if vectors.null_count() > 0 || elements.null_count() > 0 || values.iter().any(|v| !v.is_finite()) {
+            "has NULL, NaN or infinite elements"
+        } else if values.chunks_exact(dim).any(|v| v.iter().all(|x| *x == 0.0)) {
+            "has an all-zero vector, which has no cosine distance"
+        } else {
+            continue;
+        };
+        invalid.push(Violation {
+            row,
+            reason: format!("multivector column {} {}", name, reason),
+        });
+    }
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::builder::{FixedSizeListBuilder, Float32Builder, ListBuilder};
+
+    fn multivectors(rows: &[Option<&[[f32; 2]]>]) -> ArrayRef {
+        let mut builder = ListBuilder::new(FixedSizeListBuilder::new(Float32Builder::new(), 2));
+        for row in rows {
+            match row {
+                Some(vectors) => {
+                    for vector in *vectors {
+                        builder.values().values().append_slice(vector);
+                        builder.values().append(true);
+                    }
+                    builder.append(true);
+                }
+                None => builder.append(false),
+            }
+        }
+        Arc::new(builder.finish())
+    }
+
+    fn list_of(element: DataType, dim: i32) -> DataType {
+        let vector = DataType::FixedSizeList(Arc::new(Field::new("", element, true)), dim);
+        DataType::List(Arc::new(Field::new("", vector, true)))
+    }
+
+    #[test]
+    fn test_detection() {
+        // DuckDB names nested fields ""; stored multivectors are Float32
+        let field = Field::new("tokens", list_of(DataType::Float64, 4), true);
+        assert_eq!(dimension(field.data_type()), Some(4));
+        let stored = stored_field(&field).unwrap().unwrap();
+        assert_eq!(stored.data_type(), &stored_type(4));
+        assert!(stored.is_nullable());
+
+        let ints = list_of(DataType::Int32, 4);
+        assert_eq!(dimension(&ints), None);
+        assert!(stored_field(&Field::new("bbox", ints, true)).unwrap().is_none());
+        let wide = Field::new("tokens", stored_type(MAX_DIMENSION + 1), true);
+        assert!(stored_field(&wide).is_err());
+
+        let schema = Schema::new(vec![Field::new("label", DataType::Int64, false), stored]);
+        assert_eq!(columns(&schema), vec![("tokens".to_string(), 4)]);
+    }
+
+    #[test]
+    fn test_query_vectors() {
+        assert_eq!(query_vectors(&[1.0, 2.0, 3.0, 4.0], 2).unwrap().len(), 2);
+        assert!(query_vectors(&[1.0, 2.0, 3.0], 2).is_err());
+        assert!(query_vectors(&[], 2).is_err());
+        assert!(query_vectors(&[f32::NAN, 1.0], 2).is_err());
+    }
+
+    #[test]
+    fn test_check_rows() {
+        let column = multivectors(&[
+            Some(&[[1.0, 0.0], [0.0, 1.0]]),
+            Some(&[]),
+            None,
+            Some(&[[f32::NAN, 0.0]]),
+            Some(&[[1.0, 1.0], [0.0, 0.0]]),
+        ]);
+        let invalid = check_rows("tokens", &column);
+        assert_eq!(invalid.iter().map(|v| v.row).collect::<Vec<_>>(), vec![1, 3, 4]);
+        assert_eq!(invalid[0].reason, "multivector column tokens has no vectors");
+    }
+}
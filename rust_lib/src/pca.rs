@@ -0,0 +1,260 @@
+//! PCA projection of the stored vectors to fewer dimensions.
+//!
+//! [`Pca::fit`] finds the `target` directions of largest variance in a sample of
+//! vectors. The fitted mean and components are persisted in the table metadata, and
+//! every row carries its projection in a `vector_pca` column, so an index over that
+//! column needs a fraction of the memory of one over the full vectors. Searches run
+//! against the reduced column and rescore the candidates with the full vectors.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, FixedSizeListArray, Float32Array};
+use arrow_schema::{DataType, Field};
+use std::sync::Arc;
+
+/// Column holding each row's projection.
+pub const PCA_COLUMN: &str = "vector_pca";
+
+/// Subspace iterations; enough for the leading directions of embedding spectra,
+/// whose eigenvalues fall off quickly.
+const ITERATIONS: usize = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pca {
+    pub dim: usize,
+    pub target: usize,
+    mean: Vec<f32>,
+    /// `target` unit rows of `dim` values, largest variance first.
+    components: Vec<f32>,
+    /// Fraction of the sample's variance kept by the projection.
+    pub explained_variance: f64,
+}
+
+impl Pca {
+    /// Fit a projection to `target` dimensions on flattened `dim`-dimensional vectors.
+    pub fn fit(vectors: &[f32], dim: usize, target: usize) -> Result<Self> {
+        if dim == 0 || vectors.len() % dim != 0 {
+            return Err(anyhow!("vector data size mismatch"));
+        }
+        if target == 0 || target >= dim {
+            return Err(anyhow!("target dimensions must be between 1 and {}, got {}", dim - 1, target));
+        }
+        let n = vectors.len() / dim;
+        if n < 2 {
+            return Err(anyhow!("PCA needs at least 2 vectors, got {}", n));
+        }
+
+        let (mean, cov) = covariance(vectors, dim);
+        let total: f64 = (0..dim).map(|i| cov[i * dim + i]).sum();
+        let axes = principal_axes(&cov, dim, target);
+        let kept: f64 = axes.iter().map(|(var, _)| var.max(0.0)).sum();
+
+        Ok(Self {
+            dim,
+            target,
+            mean: mean.iter().map(|&m| m as f32).collect(),
+            components: axes.iter().flat_map(|(_, q)| q.iter().map(|&x| x as f32)).collect(),
+            explained_variance: if total > 0.0 { (kept / total).min(1.0) } else { 1.0 },
+        })
+    }
+
+    /// Project one `dim`-dimensional vector.
+    pub fn project(&self, v: &[f32]) -> Result<Vec<f32>> {
+        if v.len() != self.dim {
+            return Err(anyhow!("expected dimension {}, got {}", self.dim, v.len()));
+        }
+        let centered: Vec<f32> = v.iter().zip(&self.mean).map(|(x, m)| x - m).collect();
+        Ok(self
+            .components
+            .chunks_exact(self.dim)
+            .map(|c| c.iter().zip(&centered).map(|(a, b)| a * b).sum())
+            .collect())
+    }
+
+    /// Project a vector column into a `target`-dimensional one. Null vectors stay null.
+    pub fn project_array(&self, vectors: &FixedSizeListArray) -> Result<FixedSizeListArray> {
+        let mut projected = Vec::with_capacity(vectors.len() * self.target);
+        for i in 0..vectors.len() {
+            if vectors.is_null(i) {
+                projected.resize(projected.len() + self.target, 0.0);
+                continue;
+            }
+            let values = vectors.value(i);
+            let values = values
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .ok_or_else(|| anyhow!("vector values must be Float32"))?;
+            projected.extend(self.project(values.values())?);
+        }
+        Ok(FixedSizeListArray::new(
+            Arc::new(Field::new("item", DataType::Float32, true)),
+            self.target as i32,
+            Arc::new(Float32Array::from(projected)),
+            vectors.nulls().cloned(),
+        ))
+    }
+
+    /// Schema field of the projection column.
+    pub fn field(&self) -> Field {
+        Field::new(
+            PCA_COLUMN,
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), self.target as i32),
+            true,
+        )
+    }
+
+    /// Metadata encoding: `dim;target;explained_variance;mean;components`, vectors
+    /// comma-separated.
+    pub fn encode(&self) -> String {
+        let join = |v: &[f32]| v.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",");
+        format!(
+            "{};{};{};{};{}",
+            self.dim,
+            self.target,
+            self.explained_variance,
+            join(&self.mean),
+            join(&self.components)
+        )
+    }
+
+    pub fn decode(s: &str) -> Result<Self> {
+        let bad = || anyhow!("malformed PCA projection");
+        let parts: Vec<&str> = s.splitn(5, ';').collect();
+        if parts.len() != 5 {
+            return Err(bad());
+        }
+        let floats = |p: &str| -> Result<Vec<f32>> { p.split(',').map(|x| x.parse().map_err(|_| bad())).collect() };
+        let pca = Self {
+            dim: parts[0].parse().map_err(|_| bad())?,
+            target: parts[1].parse().map_err(|_| bad())?,
+            explained_variance: parts[2].parse().map_err(|_| bad())?,
+            mean: floats(parts[3])?,
+            components: floats(parts[4])?,
+        };
+        if pca.mean.len() != pca.dim || pca.components.len() != pca.dim * pca.target {
+            return Err(bad());
+        }
+        Ok(pca)
+    }
+}
+
+/// Mean and covariance matrix (`dim` x `dim`, row-major) of flattened vectors.
+pub(crate) fn covariance(vectors: &[f32], dim: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = vectors.len() / dim;
+    let mut mean = vec![0.0f64; dim];
+    for v in vectors.chunks_exact(dim) {
+        mean.iter_mut().zip(v).for_each(|(m, &x)| *m += x as f64);
+    }
+    mean.iter_mut().for_each(|m| *m /= n as f64);
+
+    // Upper triangle accumulated, then mirrored
+    let mut cov = vec![0.0f64; dim * dim];
+    let mut centered = vec![0.0f64; dim];
+    for v in vectors.chunks_exact(dim) {
+        centered.iter_mut().zip(v.iter().zip(&mean)).for_each(|(c, (&x, m))| *c = x as f64 - m);
+        for i in 0..dim {
+            let ci = centered[i];
+            let row = &mut cov[i * dim..(i + 1) * dim];
+            row[i..].iter_mut().zip(&centered[i..]).for_each(|(r, c)| *r += ci * c);
+        }
+    }
+    let denom = n.saturating_sub(1).max(1) as f64;
+    for i in 0..dim {
+        for j in i..dim {
+            let c = cov[i * dim + j] / denom;
+            cov[i * dim + j] = c;
+            cov[j * dim + i] = c;
+        }
+    }
+    (mean, cov)
+}
+
+/// The `count` leading eigenpairs (variance, unit axis) of a covariance matrix,
+/// largest variance first, by subspace iteration from a deterministic start.
+pub(crate) fn principal_axes(cov: &[f64], dim: usize, count: usize) -> Vec<(f64, Vec<f64>)> {
+    let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+    let mut basis: Vec<f64> = (0..count * dim)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        })
+        .collect();
+    orthonormalize(&mut basis, dim);
+    for _ in 0..ITERATIONS {
+        basis = basis.chunks_exact(dim).flat_map(|q| mat_vec(cov, q)).collect();
+        orthonormalize(&mut basis, dim);
+    }
+
+    // Variance along each direction (Rayleigh quotient)
+    let mut axes: Vec<(f64, Vec<f64>)> = basis
+        .chunks_exact(dim)
+        .map(|q| (dot(q, &mat_vec(cov, q)), q.to_vec()))
+        .collect();
+    axes.sort_by(|a, b| b.0.total_cmp(&a.0));
+    axes
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(m: &[f64], v: &[f64]) -> Vec<f64> {
+    m.chunks_exact(v.len()).map(|row| dot(row, v)).collect()
+}
+
+/// Modified Gram-Schmidt over the `dim`-length rows of `basis`. A row that collapses
+/// (the sample spans fewer directions) is replaced by a unit axis orthogonal to the rest.
+fn orthonormalize(basis: &mut [f64], dim: usize) {
+    let rows = basis.len() / dim;
+    for i in 0..rows {
+        let mut axis = 0;
+        loop {
+            for j in 0..i {
+                let (done, rest) = basis.split_at_mut(i * dim);
+                let q = &done[j * dim..(j + 1) * dim];
+                let v = &mut rest[..dim];
+                let proj = dot(q, v);
+                v.iter_mut().zip(q).for_each(|(x, y)| *x -= proj * y);
+            }
+            let v = &mut basis[i * dim..(i + 1) * dim];
+            let norm = dot(v, v).sqrt();
+            if norm > 1e-12 || axis >= dim {
+                v.iter_mut().for_each(|x| *x /= norm.max(1e-12));
+                break;
+            }
+            v.iter_mut().enumerate().for_each(|(k, x)| *x = if k == axis { 1.0 } else { 0.0 });
+            axis += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_and_project() {
+        // Points along (1, 1, 0) with a little noise on the third axis
+        let vectors: Vec<f32> = (0..50)
+            .flat_map(|i| {
+                let t = i as f32 - 25.0;
+                [t + 1.0, t + 1.0, if i % 2 == 0 { 0.1 } else { -0.1 }]
+            })
+            .collect();
+        let pca = Pca::fit(&vectors, 3, 1).unwrap();
+        assert!(pca.explained_variance > 0.99);
+
+        // Distances along the main direction are preserved
+        let a = pca.project(&[1.0, 1.0, 0.0]).unwrap();
+        let b = pca.project(&[3.0, 3.0, 0.0]).unwrap();
+        assert!(((a[0] - b[0]).abs() - 8.0f32.sqrt()).abs() < 1e-3);
+
+        let decoded = Pca::decode(&pca.encode()).unwrap();
+        assert_eq!(decoded.project(&[2.0, 0.0, 1.0]).unwrap(), pca.project(&[2.0, 0.0, 1.0]).unwrap());
+
+        assert!(Pca::fit(&vectors, 3, 3).is_err());
+        assert!(Pca::fit(&vectors[..3], 3, 1).is_err());
+        assert!(Pca::decode("3;1;1;0,0").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Declarative multi-stage retrieval pipelines.
+//!
+//! A pipeline runs inside a single search call:
+//!
+//! 1. `coarse(N)`: ANN search over the table's quantized index (IVF_PQ, IVF_HNSW_SQ)
+//!    for `N * k` candidates, without refinement.
+//! 2. `rescore`: recompute exact f32 distances for the candidates.
+//! 3. `rerank(name)`: reorder candidates with a reranker registered under `name`.
+//!
+//! Pipelines are stored per table in the Lance schema metadata as their text form,
+//! e.g. `coarse(10) | rescore | rerank(cross_encoder)`.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Reorders candidates. Receives the query, candidate labels and their current
+/// distances; returns one score per candidate (smaller ranks first).
+pub type Reranker = Arc<dyn Fn(&[f32], &[i64], &[f32]) -> Result<Vec<f32>> + Send + Sync>;
+
+static RERANKERS: RwLock<Option<HashMap<String, Reranker>>> = RwLock::new(None);
+
+/// Register (or replace) a process-wide reranker.
+pub fn register_reranker(name: &str, reranker: Reranker) -> Result<()> {
+    let mut registry = RERANKERS
+        .write()
+        .map_err(|_| anyhow!("reranker registry lock poisoned"))?;
+    registry
+        .get_or_insert_with(HashMap::new)
+        .insert(name.to_string(), reranker);
+    Ok(())
+}
+
+pub fn reranker(name: &str) -> Result<Reranker> {
+    let registry = RERANKERS
+        .read()
+        .map_err(|_| anyhow!("reranker registry lock poisoned"))?;
+    registry
+        .as_ref()
+        .and_then(|r| r.get(name))
+        .cloned()
+        .ok_or_else(|| anyhow!("reranker '{}' is not registered", name))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stage {
+    Coarse { oversample: usize },
+    Rescore,
+    Rerank { name: String },
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Coarse { oversample } => write!(f, "coarse({})", oversample),
+            Stage::Rescore => write!(f, "rescore"),
+            Stage::Rerank { name } => write!(f, "rerank({})", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    pub stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// Parse the text form, e.g. `coarse(10) | rescore | rerank(name)`.
+    ///
+    /// The first stage must be `coarse`; `rescore` and `rerank` may each follow once,
+    /// in that order.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut stages = Vec::new();
+        for part in spec.split('|').map(str::trim) {
+            let (name, arg) = match part.find('(') {
+                Some(open) => {
+                    let arg = part[open + 1..]
+                        .strip_suffix(')')
+                        .ok_or_else(|| anyhow!("unterminated argument in stage '{}'", part))?;
+                    (part[..open].trim(), Some(arg.trim()))
+                }
+                None => (part, None),
+            };
+            let stage = match (name, arg) {
+                ("coarse", Some(n)) => {
+                    let oversample: usize = n
+                        .parse()
+                        .map_err(|_| anyhow!("coarse oversample must be an integer, got '{}'", n))?;
+                    if oversample == 0 {
+                        return Err(anyhow!("coarse oversample must be positive"));
+                    }
+                    Stage::Coarse { oversample }
+                }
+                ("coarse", None) => Stage::Coarse { oversample: 10 },
+                ("rescore", None) => Stage::Rescore,
+                ("rerank", Some(n)) if !n.is_empty() => Stage::Rerank { name: n.to_string() },
+                _ => return Err(anyhow!("invalid pipeline stage '{}'", part)),
+            };
+            stages.push(stage);
+        }
+
+        let order = |s: &Stage| match s {
+            Stage::Coarse { .. } => 0,
+            Stage::Rescore => 1,
+            Stage::Rerank { .. } => 2,
+        };
+        if !matches!(stages.first(), Some(Stage::Coarse { .. })) {
+            return Err(anyhow!("pipeline must start with a coarse stage"));
+        }
+        if stages.windows(2).any(|w| order(&w[0]) >= order(&w[1])) {
+            return Err(anyhow!("pipeline stages must be coarse, rescore, rerank in that order"));
+        }
+        Ok(Self { stages })
+    }
+
+    /// Number of candidates the coarse stage fetches for a final `k`.
+    pub fn candidates(&self, k: usize) -> usize {
+        match self.stages.first() {
+            Some(Stage::Coarse { oversample }) => k.saturating_mul(*oversample),
+            _ => k,
+        }
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            write!(f, "{}", stage)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let p = Pipeline::parse("coarse(8) | rescore | rerank(ce)").unwrap();
+        assert_eq!(p.stages.len(), 3);
+        assert_eq!(p.candidates(5), 40);
+        assert_eq!(Pipeline::parse(&p.to_string()).unwrap(), p);
+
+        assert_eq!(Pipeline::parse("coarse").unwrap().candidates(3), 30);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_specs() {
+        assert!(Pipeline::parse("rescore").is_err());
+        assert!(Pipeline::parse("coarse(0)").is_err());
+        assert!(Pipeline::parse("coarse(4) | rerank(x) | rescore").is_err());
+        assert!(Pipeline::parse("coarse(4) | rescore | rescore").is_err());
+        assert!(Pipeline::parse("coarse(4 | rescore").is_err());
+        assert!(Pipeline::parse("coarse(4) | rerank()").is_err());
+    }
+
+    #[test]
+    fn test_reranker_registry() {
+        assert!(reranker("test_missing").is_err());
+        register_reranker("test_reverse", Arc::new(|_, labels, _| {
+            Ok(labels.iter().map(|&l| -(l as f32)).collect())
+        }))
+        .unwrap();
+        let scores = reranker("test_reverse").unwrap()(&[], &[1, 2], &[0.0, 0.0]).unwrap();
+        assert_eq!(scores, vec![-1.0, -2.0]);
+    }
+}
@@ -0,0 +1,178 @@
+//! Result columns copied into caller-allocated buffers, for hosts that cannot
+//! consume an Arrow C stream.
+//!
+//! The host first asks for the [`ColumnLayout`] of each requested column, allocates
+//! buffers of that shape for k rows, and the search then writes the columns of its
+//! hits into them. Values are in native byte order; nulls are flagged in a
+//! one-byte-per-row validity buffer and leave their value slot zeroed.
+
+use anyhow::{anyhow, Result};
+use arrow::array::AsArray;
+use arrow::datatypes::{Float32Type, Float64Type, Int32Type, Int64Type};
+use arrow_array::Array;
+use arrow_schema::{DataType, Field};
+
+/// How a column is laid out in the caller's buffers.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferType {
+    Int32 = 1,
+    Int64 = 2,
+    Float32 = 3,
+    Float64 = 4,
+    /// One byte per row, 0 or 1.
+    Boolean = 5,
+    /// Concatenated UTF-8 bytes plus rows + 1 `i32` offsets.
+    Utf8 = 6,
+    /// `width / 4` consecutive `f32`s per row (a vector column).
+    Float32List = 7,
+}
+
+/// Buffer type of a column and its fixed width in bytes per row (0 for `Utf8`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnLayout {
+    pub buffer_type: BufferType,
+    pub width: usize,
+}
+
+impl ColumnLayout {
+    /// Layout of `field`, or an error for types only the Arrow stream can carry.
+    pub fn of(field: &Field) -> Result<Self> {
+        let (buffer_type, width) = match field.data_type() {
+            DataType::Int32 => (BufferType::Int32, 4),
+            DataType::Int64 => (BufferType::Int64, 8),
+            DataType::Float32 => (BufferType::Float32, 4),
+            DataType::Float64 => (BufferType::Float64, 8),
+            DataType::Boolean => (BufferType::Boolean, 1),
+            DataType::Utf8 => (BufferType::Utf8, 0),
+            DataType::FixedSizeList(item, size) if item.data_type() == &DataType::Float32 => {
+                (BufferType::Float32List, *size as usize * 4)
+            }
+            other => {
+                return Err(anyhow!(
+                    "column '{}' of type {} has no buffer layout; read it through the Arrow stream",
+                    field.name(),
+                    other
+                ))
+            }
+        };
+        Ok(Self { buffer_type, width })
+    }
+}
+
+/// One result column encoded as the buffers the host allocated.
+#[derive(Debug, Default, PartialEq)]
+pub struct EncodedColumn {
+    pub values: Vec<u8>,
+    /// `Utf8` only: rows + 1 offsets into `values`.
+    pub offsets: Option<Vec<i32>>,
+    /// One byte per row: 1 valid, 0 null.
+    pub validity: Vec<u8>,
+}
+
+/// Encode `array` with `layout` (from [`ColumnLayout::of`] on its field).
+pub fn encode(array: &dyn Array, layout: ColumnLayout) -> Result<EncodedColumn> {
+    let rows = array.len();
+    let validity = (0..rows).map(|i| array.is_valid(i) as u8).collect();
+    let mut values = Vec::with_capacity(rows * layout.width);
+    let mut offsets = None;
+    match layout.buffer_type {
+        BufferType::Int32 => {
+            fixed(&mut values, array, array.as_primitive::<Int32Type>().values(), |v| v.to_ne_bytes())
+        }
+        BufferType::Int64 => {
+            fixed(&mut values, array, array.as_primitive::<Int64Type>().values(), |v| v.to_ne_bytes())
+        }
+        BufferType::Float32 => {
+            fixed(&mut values, array, array.as_primitive::<Float32Type>().values(), |v| v.to_ne_bytes())
+        }
+        BufferType::Float64 => {
+            fixed(&mut values, array, array.as_primitive::<Float64Type>().values(), |v| v.to_ne_bytes())
+        }
+        BufferType::Boolean => {
+            let bools = array.as_boolean();
+            values.extend((0..rows).map(|i| (array.is_valid(i) && bools.value(i)) as u8));
+        }
+        BufferType::Utf8 => {
+            let strings = array.as_string::<i32>();
+            let mut ends = Vec::with_capacity(rows + 1);
+            ends.push(0);
+            for i in 0..rows {
+                if array.is_valid(i) {
+                    values.extend_from_slice(strings.value(i).as_bytes());
+                }
+                ends.push(i32::try_from(values.len()).map_err(|_| anyhow!("string column exceeds 2 GiB"))?);
+            }
+            offsets = Some(ends);
+        }
+        BufferType::Float32List => {
+            let lists = array.as_fixed_size_list();
+            for i in 0..rows {
+                if array.is_valid(i) {
+                    let row = lists.value(i);
+                    values.extend(row.as_primitive::<Float32Type>().values().iter().flat_map(|v| v.to_ne_bytes()));
+                } else {
+                    values.resize(values.len() + layout.width, 0);
+                }
+            }
+        }
+    }
+    Ok(EncodedColumn {
+        values,
+        offsets,
+        validity,
+    })
+}
+
+/// Append the native bytes of each value, zeroing null slots.
+fn fixed<T: Copy + Default, const N: usize>(
+    values: &mut Vec<u8>,
+    array: &dyn Array,
+    native: &[T],
+    bytes: impl Fn(T) -> [u8; N],
+) {
+    for (i, v) in native.iter().enumerate() {
+        values.extend(bytes(if array.is_valid(i) { *v } else { T::default() }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{FixedSizeListArray, Int64Array, StringArray};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_layouts() {
+        let layout = |t: DataType| ColumnLayout::of(&Field::new("c", t, true));
+        assert_eq!(layout(DataType::Int64).unwrap(), ColumnLayout { buffer_type: BufferType::Int64, width: 8 });
+        let vector = DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 3);
+        assert_eq!(layout(vector).unwrap().width, 12);
+        assert!(layout(DataType::Date32).is_err());
+    }
+
+    #[test]
+    fn test_encode_nulls_and_strings() {
+        let ints = Int64Array::from(vec![Some(7), None]);
+        let encoded = encode(&ints, ColumnLayout::of(&Field::new("c", DataType::Int64, true)).unwrap()).unwrap();
+        assert_eq!(encoded.validity, vec![1, 0]);
+        assert_eq!(&encoded.values[..8], &7i64.to_ne_bytes());
+        assert_eq!(&encoded.values[8..], &[0; 8]);
+
+        let strings = StringArray::from(vec![Some("ab"), None, Some("c")]);
+        let encoded = encode(&strings, ColumnLayout::of(&Field::new("c", DataType::Utf8, true)).unwrap()).unwrap();
+        assert_eq!(encoded.values, b"abc");
+        assert_eq!(encoded.offsets, Some(vec![0, 2, 2, 3]));
+        assert_eq!(encoded.validity, vec![1, 0, 1]);
+
+        let vectors = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            vec![Some(vec![Some(1.0), Some(2.0)]), None],
+            2,
+        );
+        let field = Field::new("v", vectors.data_type().clone(), true);
+        let encoded = encode(&vectors, ColumnLayout::of(&field).unwrap()).unwrap();
+        assert_eq!(encoded.values.len(), 16);
+        assert_eq!(&encoded.values[4..8], &2f32.to_ne_bytes());
+        assert_eq!(encoded.validity, vec![1, 0]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Scalar quantization of stored vectors to int8.
+//!
+//! A table created with `int8` storage keeps each vector element as one signed byte,
+//! a quarter of the bytes of Float32. Element `x` is stored as the code
+//! `q = round((x - offset) / scale)` clamped to -128..=127, and read back as
+//! `q * scale + offset`; one scale and offset cover the whole table, so they are
+//! kept in the table metadata. They come from the range set while the table is
+//! empty (`set_int8_range`, or `int8_range` on the DuckDB index); int8 tables refuse
+//! rows until it is set. Elements outside the range are never clamped: the rows
+//! holding them are invalid (see [`Int8Quantizer::check_range`]).
+//!
+//! `get_vector` and `get_all_vectors` dequantize; scans return the stored codes. The
+//! bundled Lance has no int8 distance kernels, so int8 vectors cannot be indexed and
+//! `search` ranks the matching rows exactly, dequantizing them as it scans; other
+//! search paths refuse int8 tables.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Array, FixedSizeListArray, Float32Array, Int8Array};
+use arrow_schema::{DataType, Field};
+use std::sync::Arc;
+
+use crate::constraints::Violation;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Int8Quantizer {
+    /// Width of one code step.
+    pub scale: f32,
+    /// Value of code 0.
+    pub offset: f32,
+}
+
+impl Int8Quantizer {
+    /// The quantizer spreading the 256 codes evenly over `min..=max`.
+    pub fn for_range(min: f32, max: f32) -> Result<Self> {
+        if !(min.is_finite() && max.is_finite() && min < max) {
+            return Err(anyhow!("invalid int8 range [{}, {}]", min, max));
+        }
+        let scale = (max - min) / 255.0;
+        Ok(Self {
+            scale,
+            offset: min + 128.0 * scale,
+        })
+    }
+
+    /// Smallest and largest element the codes represent.
+    pub fn range(&self) -> (f32, f32) {
+        (self.dequantize_value(i8::MIN), self.dequantize_value(i8::MAX))
+    }
+
+    /// Whether `value` rounds to a code rather than past the ends of the range.
+    pub fn in_range(&self, value: f32) -> bool {
+        (-128.0..=127.0).contains(&((value - self.offset) / self.scale).round())
+    }
+
+    /// Rows of `vectors` with finite elements outside the range. NULL, NaN and
+    /// infinite elements are left to [`crate::rejects::check_vectors`].
+    pub fn check_range(&self, vectors: &FixedSizeListArray) -> Result<Vec<Violation>> {
+        let values = float_values(vectors)?;
+        let size = vectors.value_length() as usize;
+        let (min, max) = self.range();
+        let mut invalid = Vec::new();
+        for row in (0..vectors.len()).filter(|row| vectors.is_valid(*row)) {
+            let start = vectors.value_offset(row) as usize;
+            let elements = values.values()[start..start + size].iter();
+            if elements.filter(|v| v.is_finite()).any(|v| !self.in_range(*v)) {
+                invalid.push(Violation {
+                    row,
+                    reason: format!("vector has elements outside the int8 range [{}, {}]", min, max),
+                });
+            }
+        }
+        Ok(invalid)
+    }
+
+    /// The code of `value`, saturating outside the range; callers check the range
+    /// first.
+    pub fn quantize_value(&self, value: f32) -> i8 {
+        // NaN casts to 0; such rows are rejected by ingest validation
+        ((value - self.offset) / self.scale).round().clamp(-128.0, 127.0) as i8
+    }
+
+    pub fn dequantize_value(&self, code: i8) -> f32 {
+        code as f32 * self.scale + self.offset
+    }
+
+    /// Quantize Float32 `vectors` to Int8, keeping NULLs. Fails on rows with
+    /// elements outside the range.
+    pub fn quantize(&self, vectors: &FixedSizeListArray) -> Result<FixedSizeListArray> {
+        if let Some(first) = self.check_range(vectors)?.first() {
+            return Err(anyhow!("row {}: {}", first.row, first.reason));
+        }
+        self.quantize_saturating(vectors)
+    }
+
+    /// [`Int8Quantizer::quantize`] for vectors whose rows outside the range were
+    /// already found invalid and are dropped later.
+    pub fn quantize_saturating(&self, vectors: &FixedSizeListArray) -> Result<FixedSizeListArray> {
+        let values = float_values(vectors)?;
+        let codes: Int8Array = values.iter().map(|v| v.map(|v| self.quantize_value(v))).collect();
+        let item = Arc::new(Field::new("item", DataType::Int8, true));
+        Ok(FixedSizeListArray::try_new(item, vectors.value_length(), Arc::new(codes), vectors.nulls().cloned())?)
+    }
+
+    /// Dequantize Int8 `vectors` to Float32, keeping NULLs.
+    pub fn dequantize(&self, vectors: &FixedSizeListArray) -> Result<FixedSizeListArray> {
+        let codes = vectors
+            .values()
+            .as_any()
+            .downcast_ref::<Int8Array>()
+            .ok_or_else(|| anyhow!("vectors to dequantize are {}, expected Int8", vectors.value_type()))?;
+        let values: Float32Array = codes.iter().map(|q| q.map(|q| self.dequantize_value(q))).collect();
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        Ok(FixedSizeListArray::try_new(item, vectors.value_length(), Arc::new(values), vectors.nulls().cloned())?)
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{};{}", self.scale, self.offset)
+    }
+
+    pub fn decode(s: &str) -> Result<Self> {
+        let bad = || anyhow!("malformed int8 quantization '{}'", s);
+        let (scale, offset) = s.split_once(';').ok_or_else(bad)?;
+        let quantizer = Self {
+            scale: scale.parse().map_err(|_| bad())?,
+            offset: offset.parse().map_err(|_| bad())?,
+        };
+        if !(quantizer.scale.is_finite() && quantizer.scale > 0.0 && quantizer.offset.is_finite()) {
+            return Err(bad());
+        }
+        Ok(quantizer)
+    }
+}
+
+fn float_values(vectors: &FixedSizeListArray) -> Result<&Float32Array> {
+    vectors
+        .values()
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .ok_or_else(|| anyhow!("vectors to quantize are {}, expected Float32", vectors.value_type()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_and_round_trip() {
+        let quantizer = Int8Quantizer::for_range(-1.0, 1.0).unwrap();
+        assert_eq!(quantizer.quantize_value(-1.0), -128);
+        assert_eq!(quantizer.quantize_value(1.0), 127);
+        let (min, max) = quantizer.range();
+        assert!((min + 1.0).abs() < 1e-6 && (max - 1.0).abs() < 1e-6, "[{}, {}]", min, max);
+        assert!(quantizer.in_range(1.0) && quantizer.in_range(-1.0));
+        assert!(!quantizer.in_range(1.1) && !quantizer.in_range(-1.1));
+        for value in [-1.0f32, -0.3, 0.0, 0.25, 0.999] {
+            let back = quantizer.dequantize_value(quantizer.quantize_value(value));
+            assert!((back - value).abs() <= quantizer.scale / 2.0 + 1e-6, "{} -> {}", value, back);
+        }
+
+        assert_eq!(Int8Quantizer::decode(&quantizer.encode()).unwrap(), quantizer);
+        assert!(Int8Quantizer::decode("0;1").is_err());
+        assert!(Int8Quantizer::decode("wide").is_err());
+        assert!(Int8Quantizer::for_range(1.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_quantize_arrays() {
+        let quantizer = Int8Quantizer::for_range(-1.0, 1.0).unwrap();
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let vectors = FixedSizeListArray::new(item, 2, Arc::new(Float32Array::from(vec![-1.0, 1.0, 0.5, 0.0])), None);
+        let codes = quantizer.quantize(&vectors).unwrap();
+        assert_eq!(codes.value_type(), DataType::Int8);
+        let back = quantizer.dequantize(&codes).unwrap();
+        let values = back.values().as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(values.len(), 4);
+        assert!((values.value(2) - 0.5).abs() <= quantizer.scale / 2.0 + 1e-6);
+        assert!(quantizer.dequantize(&vectors).is_err());
+
+        // Rows past the range are found, and refused unless quantized saturating
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let values = Float32Array::from(vec![Some(0.0), Some(5.0), None, Some(f32::NAN), Some(-0.5), Some(0.5)]);
+        let vectors = FixedSizeListArray::new(item, 2, Arc::new(values), None);
+        let rows: Vec<usize> = quantizer.check_range(&vectors).unwrap().iter().map(|v| v.row).collect();
+        assert_eq!(rows, vec![0]);
+        assert!(quantizer.quantize(&vectors).unwrap_err().to_string().contains("outside the int8 range"));
+        let codes = quantizer.quantize_saturating(&vectors).unwrap();
+        assert_eq!(codes.values().as_any().downcast_ref::<Int8Array>().unwrap().value(1), 127);
+    }
+}
@@ -0,0 +1,496 @@
+//! Query log for offline evaluation of retrieval quality.
+//!
+//! A query log is stored in the table metadata in its text form, e.g.
+//! `tag=baseline, vectors=full`. While one is set, every `search` appends an entry to
+//! a sidecar Lance table (`<table>__queries`): the time, the tag, a hash of the query
+//! vector, the vector itself unless `vectors=hash`, the filter, k and the search
+//! parameters, and the labels returned. Entries are buffered per handle and flushed
+//! like access hits (see [`crate::access`]), so searches do not commit one by one.
+//!
+//! `replay_queries(tag)` re-runs the logged queries of a tag against the table as it
+//! is now and reports how far each result set drifted from the logged one, to compare
+//! retrieval across index rebuilds or model changes. Entries logged with
+//! `vectors=hash` identify repeated queries without keeping them, so they cannot be
+//! replayed and are counted as skipped.
+
+use anyhow::{anyhow, Result};
+use arrow_array::builder::{Float32Builder, Int64Builder, ListBuilder};
+use arrow_array::{
+    Array, BooleanArray, Float32Array, Float64Array, Int64Array, ListArray, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use crate::access::{self, FLUSH_INTERVAL};
+
+/// Flush once this many entries are buffered.
+pub const FLUSH_MAX_PENDING: usize = 256;
+
+/// Most entries kept buffered while flushes fail; the oldest are dropped beyond it.
+pub const MAX_BUFFERED: usize = 16 * FLUSH_MAX_PENDING;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VectorCapture {
+    /// Keep the query vector, so the entry can be replayed.
+    #[default]
+    Full,
+    /// Keep only the hash of the query vector.
+    Hash,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryLog {
+    /// Tag entries are logged under and replayed by.
+    pub tag: String,
+    pub vectors: VectorCapture,
+}
+
+impl QueryLog {
+    /// Parse the text form. `tag` is required; `vectors` defaults to `full`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut tag = None;
+        let mut vectors = VectorCapture::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("query log setting '{}' must be key=value", part))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "tag" if value.is_empty() => return Err(anyhow!("query log tag must not be empty")),
+                "tag" => tag = Some(value.to_string()),
+                "vectors" => {
+                    vectors = match value {
+                        "full" => VectorCapture::Full,
+                        "hash" => VectorCapture::Hash,
+                        _ => return Err(anyhow!("vectors must be 'full' or 'hash', got '{}'", value)),
+                    }
+                }
+                _ => return Err(anyhow!("unknown query log setting '{}'", key)),
+            }
+        }
+        Ok(Self {
+            tag: tag.ok_or_else(|| anyhow!("query log needs a tag"))?,
+            vectors,
+        })
+    }
+}
+
+impl fmt::Display for QueryLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let vectors = match self.vectors {
+            VectorCapture::Full => "full",
+            VectorCapture::Hash => "hash",
+        };
+        write!(f, "tag={}, vectors={}", self.tag, vectors)
+    }
+}
+
+/// One logged search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedQuery {
+    pub logged_ms: i64,
+    pub tag: String,
+    /// [`hash_query`] of the query vector.
+    pub query_hash: String,
+    /// The query vector, unless only its hash was kept.
+    pub query: Option<Vec<f32>>,
+    pub filter: Option<String>,
+    pub k: i64,
+    pub nprobes: i64,
+    pub refine_factor: i64,
+    pub ef: i64,
+    /// Labels returned, nearest first.
+    pub labels: Vec<i64>,
+}
+
+/// Stable hash of a query vector (FNV-1a over its elements' bits), as 16 hex digits.
+pub fn hash_query(query: &[f32]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in query.iter().flat_map(|v| v.to_bits().to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+pub fn sidecar_table_name(table_name: &str) -> String {
+    format!("{}__queries", table_name)
+}
+
+pub fn sidecar_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("logged_ms", DataType::Int64, false),
+        Field::new("tag", DataType::Utf8, false),
+        Field::new("query_hash", DataType::Utf8, false),
+        Field::new_list("query", Field::new("item", DataType::Float32, true), true),
+        Field::new("filter", DataType::Utf8, true),
+        Field::new("k", DataType::Int64, false),
+        Field::new("nprobes", DataType::Int64, false),
+        Field::new("refine_factor", DataType::Int64, false),
+        Field::new("ef", DataType::Int64, false),
+        Field::new_list("labels", Field::new("item", DataType::Int64, true), false),
+    ]))
+}
+
+pub fn to_record_batch(entries: &[LoggedQuery]) -> Result<RecordBatch> {
+    let mut queries = ListBuilder::new(Float32Builder::new());
+    let mut labels = ListBuilder::new(Int64Builder::new());
+    for entry in entries {
+        match &entry.query {
+            Some(query) => {
+                queries.values().append_slice(query);
+                queries.append(true);
+            }
+            None => queries.append(false),
+        }
+        labels.values().append_slice(&entry.labels);
+        labels.append(true);
+    }
+    let int64 = |f: fn(&LoggedQuery) -> i64| Arc::new(Int64Array::from_iter_values(entries.iter().map(f)));
+    Ok(RecordBatch::try_new(sidecar_schema(), vec![
+        int64(|e| e.logged_ms),
+        Arc::new(StringArray::from_iter_values(entries.iter().map(|e| e.tag.as_str()))),
+        Arc::new(StringArray::from_iter_values(entries.iter().map(|e| e.query_hash.as_str()))),
+        Arc::new(queries.finish()),
+        Arc::new(StringArray::from_iter(entries.iter().map(|e| e.filter.as_deref()))),
+        int64(|e| e.k),
+        int64(|e| e.nprobes),
+        int64(|e| e.refine_factor),
+        int64(|e| e.ef),
+        Arc::new(labels.finish()),
+    ])?)
+}
+
+/// Append the entries of a sidecar batch to `into`.
+pub fn read_batch(batch: &RecordBatch, into: &mut Vec<LoggedQuery>) -> Result<()> {
+    let int64 = |name: &str| -> Result<&Int64Array> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+            .ok_or_else(|| anyhow!("query log column {} missing or not Int64", name))
+    };
+    let text = |name: &str| -> Result<&StringArray> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .ok_or_else(|| anyhow!("query log column {} missing or not VARCHAR", name))
+    };
+    let list = |name: &str| -> Result<&ListArray> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<ListArray>())
+            .ok_or_else(|| anyhow!("query log column {} missing or not a list", name))
+    };
+    let (times, tags, hashes, filters) = (int64("logged_ms")?, text("tag")?, text("query_hash")?, text("filter")?);
+    let (ks, nprobes, refine_factors, efs) = (int64("k")?, int64("nprobes")?, int64("refine_factor")?, int64("ef")?);
+    let (queries, labels) = (list("query")?, list("labels")?);
+    for i in 0..batch.num_rows() {
+        let query = if queries.is_null(i) {
+            None
+        } else {
+            let values = queries.value(i);
+            let values = values
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .ok_or_else(|| anyhow!("query log queries are not Float32"))?;
+            Some(values.values().to_vec())
+        };
+        let returned = labels.value(i);
+        let returned = returned
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| anyhow!("query log labels are not Int64"))?;
+        into.push(LoggedQuery {
+            logged_ms: times.value(i),
+            tag: tags.value(i).to_string(),
+            query_hash: hashes.value(i).to_string(),
+            query,
+            filter: filters.is_valid(i).then(|| filters.value(i).to_string()),
+            k: ks.value(i),
+            nprobes: nprobes.value(i),
+            refine_factor: refine_factors.value(i),
+            ef: efs.value(i),
+            labels: returned.values().to_vec(),
+        });
+    }
+    Ok(())
+}
+
+struct Pending {
+    entries: Vec<LoggedQuery>,
+    last_flush: Instant,
+    /// Entries dropped to keep the buffer within [`MAX_BUFFERED`].
+    dropped: u64,
+}
+
+impl Pending {
+    fn trim(&mut self) {
+        let excess = self.entries.len().saturating_sub(MAX_BUFFERED);
+        if excess > 0 {
+            self.entries.drain(..excess);
+            self.dropped += excess as u64;
+        }
+    }
+}
+
+/// The query log setting of one handle and its buffered entries.
+pub struct QueryLogger {
+    config: RwLock<Option<QueryLog>>,
+    pending: Mutex<Pending>,
+}
+
+impl QueryLogger {
+    pub fn new(config: Option<QueryLog>) -> Self {
+        Self {
+            config: RwLock::new(config),
+            pending: Mutex::new(Pending {
+                entries: Vec::new(),
+                last_flush: Instant::now(),
+                dropped: 0,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> Option<QueryLog> {
+        self.config.read().ok().and_then(|c| c.clone())
+    }
+
+    pub fn set_config(&self, config: Option<QueryLog>) {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+    }
+
+    /// Buffer an entry for a search of `query` returning `labels`, if logging is on.
+    /// Returns true when a flush is due.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        query: &[f32],
+        k: usize,
+        nprobes: usize,
+        refine_factor: usize,
+        ef: usize,
+        filter: Option<&str>,
+        labels: Vec<i64>,
+    ) -> bool {
+        let Some(config) = self.config() else {
+            return false;
+        };
+        let entry = LoggedQuery {
+            logged_ms: access::now_ms(),
+            tag: config.tag,
+            query_hash: hash_query(query),
+            query: (config.vectors == VectorCapture::Full).then(|| query.to_vec()),
+            filter: filter.map(str::to_string),
+            k: k as i64,
+            nprobes: nprobes as i64,
+            refine_factor: refine_factor as i64,
+            ef: ef as i64,
+            labels,
+        };
+        let Ok(mut pending) = self.pending.lock() else {
+            return false;
+        };
+        pending.entries.push(entry);
+        pending.trim();
+        pending.entries.len() >= FLUSH_MAX_PENDING || pending.last_flush.elapsed() >= FLUSH_INTERVAL
+    }
+
+    /// Buffered entries, without draining them.
+    pub fn snapshot(&self) -> Vec<LoggedQuery> {
+        self.pending.lock().map(|p| p.entries.clone()).unwrap_or_default()
+    }
+
+    /// Drain the buffer for a flush.
+    pub fn take(&self) -> Vec<LoggedQuery> {
+        match self.pending.lock() {
+            Ok(mut pending) => {
+                pending.last_flush = Instant::now();
+                std::mem::take(&mut pending.entries)
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Put back entries whose flush failed, ahead of any buffered since. Beyond
+    /// [`MAX_BUFFERED`] the oldest entries are dropped.
+    pub fn restore(&self, mut entries: Vec<LoggedQuery>) {
+        if let Ok(mut pending) = self.pending.lock() {
+            entries.append(&mut pending.entries);
+            pending.entries = entries;
+            pending.trim();
+        }
+    }
+
+    /// Entries dropped because flushes kept failing.
+    pub fn dropped(&self) -> u64 {
+        self.pending.lock().map(|p| p.dropped).unwrap_or_default()
+    }
+}
+
+/// How one replayed query's results compare with the logged ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDrift {
+    pub logged_ms: i64,
+    pub query_hash: String,
+    /// Number of labels logged and returned now.
+    pub logged: usize,
+    pub returned: usize,
+    /// Fraction of the logged labels still returned; 1 when none were logged.
+    pub overlap: f64,
+    /// The nearest result is no longer the logged one.
+    pub top_changed: bool,
+}
+
+impl QueryDrift {
+    pub fn new(entry: &LoggedQuery, current: &[i64]) -> Self {
+        let now: HashSet<i64> = current.iter().copied().collect();
+        let kept = entry.labels.iter().filter(|label| now.contains(label)).count();
+        Self {
+            logged_ms: entry.logged_ms,
+            query_hash: entry.query_hash.clone(),
+            logged: entry.labels.len(),
+            returned: current.len(),
+            overlap: if entry.labels.is_empty() {
+                1.0
+            } else {
+                kept as f64 / entry.labels.len() as f64
+            },
+            top_changed: entry.labels.first() != current.first(),
+        }
+    }
+
+    /// The result set differs from the logged one.
+    pub fn changed(&self) -> bool {
+        self.overlap < 1.0 || self.logged != self.returned || self.top_changed
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayReport {
+    pub tag: String,
+    /// Per replayed query, in log order.
+    pub queries: Vec<QueryDrift>,
+    /// Entries logged with `vectors=hash`, which cannot be replayed.
+    pub skipped: usize,
+}
+
+impl ReplayReport {
+    /// Replayed queries whose result set differs from the logged one.
+    pub fn changed(&self) -> usize {
+        self.queries.iter().filter(|q| q.changed()).count()
+    }
+
+    /// Mean overlap of the replayed queries; 1 when none were replayed.
+    pub fn mean_overlap(&self) -> f64 {
+        if self.queries.is_empty() {
+            return 1.0;
+        }
+        self.queries.iter().map(|q| q.overlap).sum::<f64>() / self.queries.len() as f64
+    }
+
+    /// One row per replayed query: (logged_ms, query_hash, logged, returned, overlap,
+    /// top_changed).
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("logged_ms", DataType::Int64, false),
+            Field::new("query_hash", DataType::Utf8, false),
+            Field::new("logged", DataType::Int64, false),
+            Field::new("returned", DataType::Int64, false),
+            Field::new("overlap", DataType::Float64, false),
+            Field::new("top_changed", DataType::Boolean, false),
+        ]));
+        let queries = &self.queries;
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from_iter_values(queries.iter().map(|q| q.logged_ms))),
+            Arc::new(StringArray::from_iter_values(queries.iter().map(|q| q.query_hash.as_str()))),
+            Arc::new(Int64Array::from_iter_values(queries.iter().map(|q| q.logged as i64))),
+            Arc::new(Int64Array::from_iter_values(queries.iter().map(|q| q.returned as i64))),
+            Arc::new(Float64Array::from_iter_values(queries.iter().map(|q| q.overlap))),
+            Arc::new(BooleanArray::from(queries.iter().map(|q| q.top_changed).collect::<Vec<_>>())),
+        ])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let log = QueryLog::parse("tag=baseline").unwrap();
+        assert_eq!(log.vectors, VectorCapture::Full);
+        assert_eq!(QueryLog::parse(&log.to_string()).unwrap(), log);
+        assert_eq!(QueryLog::parse("vectors=hash, tag=v2").unwrap().vectors, VectorCapture::Hash);
+        assert!(QueryLog::parse("vectors=hash").is_err());
+        assert!(QueryLog::parse("tag=").is_err());
+        assert!(QueryLog::parse("tag=a, vectors=some").is_err());
+    }
+
+    #[test]
+    fn test_record_and_round_trip() {
+        let logger = QueryLogger::new(None);
+        assert!(!logger.record(&[1.0, 0.0], 2, 1, 0, 0, None, vec![3, 4]));
+        assert!(logger.snapshot().is_empty());
+
+        logger.set_config(Some(QueryLog::parse("tag=a").unwrap()));
+        logger.record(&[1.0, 0.0], 2, 1, 0, 0, Some("n > 1"), vec![3, 4]);
+        logger.set_config(Some(QueryLog::parse("tag=b, vectors=hash").unwrap()));
+        logger.record(&[1.0, 0.0], 2, 1, 0, 0, None, vec![]);
+        let entries = logger.take();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].query_hash, entries[1].query_hash);
+        assert_ne!(hash_query(&[0.0, 1.0]), entries[0].query_hash);
+        assert!(entries[1].query.is_none());
+
+        let mut read = Vec::new();
+        read_batch(&to_record_batch(&entries).unwrap(), &mut read).unwrap();
+        assert_eq!(read, entries);
+        logger.restore(entries);
+        assert_eq!(logger.snapshot().len(), 2);
+
+        // Failed flushes keep at most MAX_BUFFERED entries, dropping the oldest
+        let failed = logger.take();
+        for i in 0..MAX_BUFFERED {
+            logger.record(&[i as f32, 0.0], 1, 1, 0, 0, None, vec![]);
+        }
+        logger.restore(failed);
+        let buffered = logger.snapshot();
+        assert_eq!((buffered.len(), logger.dropped()), (MAX_BUFFERED, 2));
+        assert_eq!(buffered[0].query.as_deref(), Some(&[0.0, 0.0][..]));
+    }
+
+    #[test]
+    fn test_drift() {
+        let entry = LoggedQuery {
+            logged_ms: 1,
+            tag: "a".to_string(),
+            query_hash: hash_query(&[1.0]),
+            query: Some(vec![1.0]),
+            filter: None,
+            k: 4,
+            nprobes: 1,
+            refine_factor: 0,
+            ef: 0,
+            labels: vec![1, 2, 3, 4],
+        };
+        let same = QueryDrift::new(&entry, &[1, 2, 3, 4]);
+        assert!(!same.changed());
+        let drifted = QueryDrift::new(&entry, &[2, 1, 5, 3]);
+        assert_eq!(drifted.overlap, 0.75);
+        assert!(drifted.top_changed && drifted.changed());
+
+        let report = ReplayReport {
+            tag: "a".to_string(),
+            queries: vec![same, drifted],
+            skipped: 1,
+        };
+        assert_eq!(report.changed(), 1);
+        assert_eq!(report.mean_overlap(), 0.875);
+        assert_eq!(report.to_record_batch().unwrap().num_rows(), 2);
+    }
+}
@@ -0,0 +1,256 @@
+//! Per-table row and size quotas.
+//!
+//! A quota is stored in the table metadata in its text form, e.g.
+//! `max_rows=100000, max_bytes=1073741824, on_exceed=evict_oldest(created_at)`.
+//! Appends that would exceed it either fail with [`QuotaExceeded`] (`on_exceed=reject`,
+//! the default) or delete the oldest rows by the named column after the append.
+//!
+//! Eviction doubles as a retention policy for bounded tables such as conversation
+//! memory: `max_rows=1000, on_exceed=evict_oldest, evict_batch=100` keeps the newest
+//! rows by label and lets up to 99 extra rows accumulate before deleting them in one
+//! commit, so steady ingestion does not create a table version per insert.
+
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotaPolicy {
+    Reject,
+    /// Delete the rows with the smallest values of `column` (by default `label`,
+    /// i.e. insertion order) until back under quota.
+    EvictOldest { column: String },
+    /// Delete the least recently searched rows first (never-accessed rows, oldest
+    /// label first, go before any accessed row). Requires access tracking.
+    EvictLru,
+}
+
+impl fmt::Display for QuotaPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaPolicy::Reject => write!(f, "reject"),
+            QuotaPolicy::EvictOldest { column } => write!(f, "evict_oldest({})", column),
+            QuotaPolicy::EvictLru => write!(f, "evict_lru"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quota {
+    pub max_rows: Option<u64>,
+    /// Limit on data file bytes (indices and manifests are not counted).
+    pub max_bytes: Option<u64>,
+    pub policy: QuotaPolicy,
+    /// Evict only once at least this many rows are over quota (eviction policy only).
+    pub evict_batch: u64,
+}
+
+impl Quota {
+    /// Parse the text form. At least one of `max_rows` and `max_bytes` is required.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut quota = Quota {
+            max_rows: None,
+            max_bytes: None,
+            policy: QuotaPolicy::Reject,
+            evict_batch: 1,
+        };
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("quota setting '{}' must be key=value", part))?;
+            let (key, value) = (key.trim(), value.trim());
+            let limit = || -> Result<u64> {
+                match value.parse::<u64>() {
+                    Ok(n) if n > 0 => Ok(n),
+                    _ => Err(anyhow!("{} must be a positive integer, got '{}'", key, value)),
+                }
+            };
+            match key {
+                "max_rows" => quota.max_rows = Some(limit()?),
+                "max_bytes" => quota.max_bytes = Some(limit()?),
+                "evict_batch" => quota.evict_batch = limit()?,
+                "on_exceed" => {
+                    quota.policy = match value {
+                        "reject" => QuotaPolicy::Reject,
+                        "evict_oldest" => QuotaPolicy::EvictOldest { column: "label".to_string() },
+                        "evict_lru" => QuotaPolicy::EvictLru,
+                        _ => {
+                            let column = value
+                                .strip_prefix("evict_oldest(")
+                                .and_then(|rest| rest.strip_suffix(')'))
+                                .map(str::trim)
+                                .filter(|c| !c.is_empty())
+                                .ok_or_else(|| anyhow!("invalid on_exceed policy '{}'", value))?;
+                            QuotaPolicy::EvictOldest { column: column.to_string() }
+                        }
+                    }
+                }
+                _ => return Err(anyhow!("unknown quota setting '{}'", key)),
+            }
+        }
+        if quota.max_rows.is_none() && quota.max_bytes.is_none() {
+            return Err(anyhow!("quota needs max_rows or max_bytes"));
+        }
+        if quota.evict_batch > 1 && quota.policy == QuotaPolicy::Reject {
+            return Err(anyhow!("evict_batch requires an eviction policy"));
+        }
+        Ok(quota)
+    }
+
+    /// Rows to remove so that `rows` rows taking `bytes` bytes fit the quota.
+    /// Byte overage is converted to rows at the table's average row size.
+    pub fn excess_rows(&self, rows: u64, bytes: u64) -> u64 {
+        let by_rows = self.max_rows.map_or(0, |max| rows.saturating_sub(max));
+        let by_bytes = match self.max_bytes {
+            Some(max) if bytes > max && rows > 0 => {
+                let per_row = (bytes / rows).max(1);
+                (bytes - max).div_ceil(per_row)
+            }
+            _ => 0,
+        };
+        by_rows.max(by_bytes).min(rows)
+    }
+
+    /// Fail with [`QuotaExceeded`] if adding `rows` rows taking `bytes` bytes to
+    /// `usage` would exceed the quota.
+    pub fn check(&self, usage: &QuotaUsage, rows: u64, bytes: u64) -> Result<(), QuotaExceeded> {
+        if let Some(max) = self.max_rows {
+            let requested = usage.rows + rows;
+            if requested > max {
+                return Err(QuotaExceeded { limit: "rows", max, requested });
+            }
+        }
+        if let Some(max) = self.max_bytes {
+            let requested = usage.data_bytes + bytes;
+            if requested > max {
+                return Err(QuotaExceeded { limit: "bytes", max, requested });
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows to evict now: the excess once it reaches `evict_batch`, else 0.
+    pub fn rows_to_evict(&self, rows: u64, bytes: u64) -> u64 {
+        match self.excess_rows(rows, bytes) {
+            excess if excess >= self.evict_batch => excess,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(n) = self.max_rows {
+            parts.push(format!("max_rows={}", n));
+        }
+        if let Some(n) = self.max_bytes {
+            parts.push(format!("max_bytes={}", n));
+        }
+        parts.push(format!("on_exceed={}", self.policy));
+        if self.evict_batch > 1 {
+            parts.push(format!("evict_batch={}", self.evict_batch));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Rows and data bytes of a table at one version, as last measured for a quota.
+/// Appends advance it instead of measuring again; any other commit invalidates it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub version: u64,
+    pub rows: u64,
+    /// Data file bytes; 0 when the quota has no byte limit.
+    pub data_bytes: u64,
+}
+
+impl QuotaUsage {
+    /// The usage after an append of `rows` rows taking about `bytes` bytes committed
+    /// as `version`, or `None` when other commits came in between.
+    pub fn appended(self, version: u64, rows: u64, bytes: u64) -> Option<Self> {
+        (version == self.version + 1).then_some(QuotaUsage {
+            version,
+            rows: self.rows + rows,
+            data_bytes: self.data_bytes + bytes,
+        })
+    }
+}
+
+/// Returned (wrapped in `anyhow::Error`) when an append is rejected by a quota.
+/// FFI entry points report it with a distinct return code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub limit: &'static str,
+    pub max: u64,
+    pub requested: u64,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "quota exceeded: {} would reach {}, limit is {}",
+            self.limit, self.requested, self.max
+        )
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_and_advance_usage() {
+        let quota = Quota::parse("max_rows=10, max_bytes=100").unwrap();
+        let usage = QuotaUsage { version: 4, rows: 8, data_bytes: 60 };
+        assert!(quota.check(&usage, 2, 40).is_ok());
+        let err = quota.check(&usage, 3, 0).unwrap_err();
+        assert_eq!((err.limit, err.requested), ("rows", 11));
+        assert_eq!(quota.check(&usage, 1, 41).unwrap_err().limit, "bytes");
+
+        let advanced = usage.appended(5, 2, 40).unwrap();
+        assert_eq!(advanced, QuotaUsage { version: 5, rows: 10, data_bytes: 100 });
+        // A commit in between leaves the rows unknown
+        assert_eq!(usage.appended(6, 2, 40), None);
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let q = Quota::parse("max_rows=10, on_exceed=evict_oldest(ts)").unwrap();
+        assert_eq!(q.max_rows, Some(10));
+        assert_eq!(q.policy, QuotaPolicy::EvictOldest { column: "ts".into() });
+        assert_eq!(Quota::parse(&q.to_string()).unwrap(), q);
+
+        assert!(Quota::parse("on_exceed=reject").is_err());
+        assert!(Quota::parse("max_rows=0").is_err());
+        assert!(Quota::parse("max_rows=5, on_exceed=evict_oldest()").is_err());
+        assert!(Quota::parse("max_docs=5").is_err());
+        assert!(Quota::parse("max_rows=5, evict_batch=2").is_err());
+
+        let retention = Quota::parse("max_rows=100, on_exceed=evict_oldest, evict_batch=10").unwrap();
+        assert_eq!(retention.policy, QuotaPolicy::EvictOldest { column: "label".into() });
+        assert_eq!(Quota::parse(&retention.to_string()).unwrap(), retention);
+
+        let lru = Quota::parse("max_rows=5, on_exceed=evict_lru").unwrap();
+        assert_eq!(Quota::parse(&lru.to_string()).unwrap(), lru);
+    }
+
+    #[test]
+    fn test_excess_rows() {
+        let q = Quota::parse("max_rows=10, max_bytes=1000").unwrap();
+        assert_eq!(q.excess_rows(8, 800), 0);
+        assert_eq!(q.excess_rows(12, 800), 2);
+        // 150 bytes per row, 500 over
+        assert_eq!(q.excess_rows(10, 1500), 4);
+    }
+
+    #[test]
+    fn test_rows_to_evict_waits_for_batch() {
+        let q = Quota::parse("max_rows=10, on_exceed=evict_oldest, evict_batch=5").unwrap();
+        assert_eq!(q.rows_to_evict(14, 0), 0);
+        assert_eq!(q.rows_to_evict(15, 0), 5);
+        assert_eq!(q.rows_to_evict(17, 0), 7);
+    }
+}
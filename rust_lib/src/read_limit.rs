@@ -0,0 +1,135 @@
+//! Caps on what one FFI read call may materialize.
+//!
+//! Calls like `lance_detached_get_all_vectors` or an unordered `lance_detached_scan`
+//! collect their whole result in memory before handing it over, so one of them on a
+//! table of hundreds of millions of rows can exhaust an embedded process. With
+//! [`configure`]d limits, such a call fails once it has read more rows or bytes than
+//! allowed, instead of growing until the process is killed. Streaming reads (ordered
+//! scans, search cursors) hold about one batch at a time and are not capped.
+//!
+//! Only reads inside [`limited`] are capped: the FFI entry points wrap the calls that
+//! collect a result for the caller, while internal reads (training a PCA from every
+//! vector, say) are not. Both limits default to 0, unlimited.
+
+use anyhow::{anyhow, Result};
+use arrow_array::RecordBatch;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static MAX_ROWS: AtomicU64 = AtomicU64::new(0);
+static MAX_BYTES: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static LIMITED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Rows and bytes one capped read may materialize; 0 leaves a side unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadLimits {
+    pub max_rows: u64,
+    pub max_bytes: u64,
+}
+
+/// Set the limits of capped reads started afterwards.
+pub fn configure(limits: ReadLimits) {
+    MAX_ROWS.store(limits.max_rows, Ordering::Relaxed);
+    MAX_BYTES.store(limits.max_bytes, Ordering::Relaxed);
+}
+
+pub fn limits() -> ReadLimits {
+    ReadLimits {
+        max_rows: MAX_ROWS.load(Ordering::Relaxed),
+        max_bytes: MAX_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+/// Run `read` with the reads it makes on this thread capped.
+pub fn limited<T>(read: impl FnOnce() -> T) -> T {
+    let outer = LIMITED.with(|l| l.replace(true));
+    let result = read();
+    LIMITED.with(|l| l.set(outer));
+    result
+}
+
+/// Rows and bytes collected so far by one read, checked against the limits as they
+/// grow. Unlimited outside [`limited`].
+#[derive(Debug)]
+pub struct ReadBudget {
+    what: &'static str,
+    limits: ReadLimits,
+    rows: u64,
+    bytes: u64,
+}
+
+impl ReadBudget {
+    /// A budget for the read `what` (named in the error) starting now.
+    pub fn new(what: &'static str) -> Self {
+        Self::with_limits(what, if LIMITED.with(Cell::get) { limits() } else { ReadLimits::default() })
+    }
+
+    fn with_limits(what: &'static str, limits: ReadLimits) -> Self {
+        Self {
+            what,
+            limits,
+            rows: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Count `rows` more rows taking `bytes` more bytes; fails once over a limit.
+    pub fn charge(&mut self, rows: usize, bytes: usize) -> Result<()> {
+        self.rows += rows as u64;
+        self.bytes += bytes as u64;
+        let ReadLimits { max_rows, max_bytes } = self.limits;
+        if max_rows > 0 && self.rows > max_rows {
+            return Err(self.exceeded(format!("{} rows", max_rows)));
+        }
+        if max_bytes > 0 && self.bytes > max_bytes {
+            return Err(self.exceeded(format!("{} bytes", max_bytes)));
+        }
+        Ok(())
+    }
+
+    pub fn charge_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.charge(batch.num_rows(), batch.get_array_memory_size())
+    }
+
+    fn exceeded(&self, limit: String) -> anyhow::Error {
+        anyhow!(
+            "{} would read more than the read limit of {}; read the rows as a stream (an ordered scan) or in \
+             filtered parts, or raise the limit",
+            self.what,
+            limit
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let limits = ReadLimits {
+            max_rows: 10,
+            max_bytes: 1000,
+        };
+        let mut rows = ReadBudget::with_limits("scan", limits);
+        assert!(rows.charge(10, 100).is_ok());
+        let err = rows.charge(1, 10).unwrap_err();
+        assert!(err.to_string().contains("limit of 10 rows"), "{}", err);
+
+        let mut bytes = ReadBudget::with_limits("get_all_vectors", limits);
+        assert!(bytes.charge(1, 1001).unwrap_err().to_string().contains("1000 bytes"));
+
+        let mut unlimited = ReadBudget::with_limits("scan", ReadLimits::default());
+        assert!(unlimited.charge(1_000_000, 1 << 30).is_ok());
+    }
+
+    #[test]
+    fn test_limited_scope() {
+        assert!(!LIMITED.with(Cell::get));
+        assert!(limited(|| limited(|| LIMITED.with(Cell::get)) && LIMITED.with(Cell::get)));
+        assert!(!LIMITED.with(Cell::get));
+    }
+}
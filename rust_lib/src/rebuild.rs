@@ -0,0 +1,229 @@
+//! Background vector index rebuilds.
+//!
+//! A rebuild trains the replacement index on a worker thread while the current
+//! index keeps serving searches. Lance commits the new index in a single manifest
+//! version, so readers move from the old index to the new one atomically and the
+//! table is never left unindexed while parameters change.
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::access::now_ms;
+use crate::index_params::VectorIndexType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebuildState {
+    Idle,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl RebuildState {
+    pub fn name(self) -> &'static str {
+        match self {
+            RebuildState::Idle => "idle",
+            RebuildState::Running => "running",
+            RebuildState::Succeeded => "succeeded",
+            RebuildState::Failed => "failed",
+        }
+    }
+}
+
+/// Step a running rebuild is in. Lance reports no progress from inside index
+/// training, so this is as fine-grained as a rebuild can be followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebuildPhase {
+    /// Lance is training, writing and committing the new index.
+    Training,
+    /// The index is committed; its metric and compression are being recorded.
+    Recording,
+}
+
+impl RebuildPhase {
+    pub fn name(self) -> &'static str {
+        match self {
+            RebuildPhase::Training => "training",
+            RebuildPhase::Recording => "recording",
+        }
+    }
+}
+
+/// State of the most recent rebuild of one handle. Times are milliseconds since
+/// the Unix epoch; `finished_ms` is 0 while running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebuildStatus {
+    pub state: RebuildState,
+    /// Current step while running, `None` otherwise.
+    pub phase: Option<RebuildPhase>,
+    pub index_type: Option<VectorIndexType>,
+    pub started_ms: i64,
+    pub finished_ms: i64,
+    pub error: Option<String>,
+}
+
+impl RebuildStatus {
+    /// Time spent so far (running) or in total (finished).
+    pub fn elapsed_ms(&self) -> i64 {
+        match self.state {
+            RebuildState::Idle => 0,
+            RebuildState::Running => now_ms() - self.started_ms,
+            _ => self.finished_ms - self.started_ms,
+        }
+    }
+
+    /// One row: (state, index_type, started_ms, elapsed_ms, error, phase).
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("state", DataType::Utf8, false),
+            Field::new("index_type", DataType::Utf8, true),
+            Field::new("started_ms", DataType::Int64, false),
+            Field::new("elapsed_ms", DataType::Int64, false),
+            Field::new("error", DataType::Utf8, true),
+            Field::new("phase", DataType::Utf8, true),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from(vec![self.state.name()])),
+            Arc::new(StringArray::from(vec![self.index_type.map(|t| t.name())])),
+            Arc::new(Int64Array::from(vec![self.started_ms])),
+            Arc::new(Int64Array::from(vec![self.elapsed_ms()])),
+            Arc::new(StringArray::from(vec![self.error.as_deref()])),
+            Arc::new(StringArray::from(vec![self.phase.map(|p| p.name())])),
+        ])?)
+    }
+}
+
+/// Rebuild state shared between a handle and its worker thread.
+pub struct RebuildTracker {
+    status: Mutex<RebuildStatus>,
+    finished: Condvar,
+}
+
+impl Default for RebuildTracker {
+    fn default() -> Self {
+        Self {
+            status: Mutex::new(RebuildStatus {
+                state: RebuildState::Idle,
+                phase: None,
+                index_type: None,
+                started_ms: 0,
+                finished_ms: 0,
+                error: None,
+            }),
+            finished: Condvar::new(),
+        }
+    }
+}
+
+impl RebuildTracker {
+    pub fn status(&self) -> RebuildStatus {
+        match self.status.lock() {
+            Ok(status) => status.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Mark a rebuild as started. Fails if one is already running.
+    pub fn begin(&self, index_type: VectorIndexType) -> Result<()> {
+        let mut status = self
+            .status
+            .lock()
+            .map_err(|_| anyhow!("rebuild lock poisoned"))?;
+        if status.state == RebuildState::Running {
+            return Err(anyhow!(
+                "a {} rebuild is already running",
+                status.index_type.map_or("vector index", |t| t.name())
+            ));
+        }
+        *status = RebuildStatus {
+            state: RebuildState::Running,
+            phase: Some(RebuildPhase::Training),
+            index_type: Some(index_type),
+            started_ms: now_ms(),
+            finished_ms: 0,
+            error: None,
+        };
+        Ok(())
+    }
+
+    /// Move the running rebuild on to `phase`.
+    pub fn enter(&self, phase: RebuildPhase) {
+        let mut status = match self.status.lock() {
+            Ok(status) => status,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if status.state == RebuildState::Running {
+            status.phase = Some(phase);
+        }
+    }
+
+    /// Record the outcome of the running rebuild and wake waiters.
+    pub fn finish(&self, result: Result<()>) {
+        let mut status = match self.status.lock() {
+            Ok(status) => status,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        status.finished_ms = now_ms();
+        status.phase = None;
+        match result {
+            Ok(()) => status.state = RebuildState::Succeeded,
+            Err(e) => {
+                status.state = RebuildState::Failed;
+                status.error = Some(e.to_string());
+            }
+        }
+        self.finished.notify_all();
+    }
+
+    /// Block until no rebuild is running, then return its status.
+    pub fn wait(&self) -> RebuildStatus {
+        let mut status = match self.status.lock() {
+            Ok(status) => status,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        while status.state == RebuildState::Running {
+            status = match self.finished.wait(status) {
+                Ok(status) => status,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+        status.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_running_rebuild() {
+        let tracker = Arc::new(RebuildTracker::default());
+        assert_eq!(tracker.status().state, RebuildState::Idle);
+
+        tracker.begin(VectorIndexType::IvfPq).unwrap();
+        assert!(tracker.begin(VectorIndexType::IvfPq).is_err());
+        assert_eq!(tracker.status().phase, Some(RebuildPhase::Training));
+        tracker.enter(RebuildPhase::Recording);
+        assert_eq!(tracker.status().phase, Some(RebuildPhase::Recording));
+
+        let worker = {
+            let tracker = tracker.clone();
+            std::thread::spawn(move || tracker.finish(Err(anyhow!("boom"))))
+        };
+        let status = tracker.wait();
+        worker.join().unwrap();
+        assert_eq!(status.state, RebuildState::Failed);
+        assert_eq!(status.phase, None);
+        assert_eq!(status.error.as_deref(), Some("boom"));
+
+        // A finished rebuild does not block the next one
+        tracker.begin(VectorIndexType::IvfHnswSq).unwrap();
+        tracker.finish(Ok(()));
+        assert_eq!(tracker.status().state, RebuildState::Succeeded);
+        // Only a running rebuild has a phase
+        tracker.enter(RebuildPhase::Recording);
+        assert_eq!(tracker.status().phase, None);
+    }
+}
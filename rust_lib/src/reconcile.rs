@@ -0,0 +1,161 @@
+//! Schema reconciliation for merging rows between tables of different formats.
+//!
+//! Source columns are matched to target columns by name. Matching columns are
+//! cast to the target type when they differ, target columns missing from the
+//! source are filled with nulls, and source-only columns are dropped. A value the
+//! cast cannot convert (an overflow, unparseable text) fails the batch rather than
+//! becoming null. Strict mode refuses any difference instead.
+
+use anyhow::{anyhow, Result};
+use arrow::compute::{can_cast_types, cast_with_options, CastOptions};
+use arrow_array::{new_null_array, ArrayRef, RecordBatch};
+use arrow_schema::SchemaRef;
+
+enum ColumnSource {
+    /// Source column index; cast when the types differ.
+    Column { index: usize, cast: bool },
+    Nulls,
+}
+
+/// Maps batches of one schema onto another. The target's first column (the label)
+/// is always supplied by the caller.
+pub struct SchemaReconciler {
+    target: SchemaRef,
+    columns: Vec<ColumnSource>,
+}
+
+impl SchemaReconciler {
+    pub fn new(source: &SchemaRef, target: &SchemaRef, strict: bool) -> Result<Self> {
+        let mut columns = Vec::with_capacity(target.fields().len().saturating_sub(1));
+        let mut differences = Vec::new();
+        for field in target.fields().iter().skip(1) {
+            match source.index_of(field.name()) {
+                Ok(index) => {
+                    let source_type = source.field(index).data_type();
+                    let needs_cast = source_type != field.data_type();
+                    if needs_cast {
+                        if !can_cast_types(source_type, field.data_type()) {
+                            return Err(anyhow!(
+                                "column {} cannot be converted from {} to {}",
+                                field.name(),
+                                source_type,
+                                field.data_type()
+                            ));
+                        }
+                        differences.push(format!(
+                            "column {} is {} in the source but {} in the target",
+                            field.name(),
+                            source_type,
+                            field.data_type()
+                        ));
+                    }
+                    columns.push(ColumnSource::Column { index, cast: needs_cast });
+                }
+                Err(_) => {
+                    if !field.is_nullable() {
+                        return Err(anyhow!(
+                            "column {} is missing from the source and not nullable in the target",
+                            field.name()
+                        ));
+                    }
+                    differences.push(format!("column {} is missing from the source", field.name()));
+                    columns.push(ColumnSource::Nulls);
+                }
+            }
+        }
+        for field in source.fields().iter().skip(1) {
+            if target.index_of(field.name()).is_err() {
+                differences.push(format!("column {} is not in the target", field.name()));
+            }
+        }
+        if strict && !differences.is_empty() {
+            return Err(anyhow!("schemas differ: {}", differences.join("; ")));
+        }
+        Ok(Self {
+            target: target.clone(),
+            columns,
+        })
+    }
+
+    /// Build a target-schema batch from `batch` with `labels` as the label column.
+    pub fn apply(&self, batch: &RecordBatch, labels: ArrayRef) -> Result<RecordBatch> {
+        let options = CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        let mut arrays = Vec::with_capacity(self.target.fields().len());
+        arrays.push(labels);
+        for (column, field) in self.columns.iter().zip(self.target.fields().iter().skip(1)) {
+            arrays.push(match column {
+                ColumnSource::Column { index, cast: false } => batch.column(*index).clone(),
+                ColumnSource::Column { index, cast: true } => {
+                    cast_with_options(batch.column(*index), field.data_type(), &options).map_err(|e| {
+                        anyhow!("column {} cannot be converted to {}: {}", field.name(), field.data_type(), e)
+                    })?
+                }
+                ColumnSource::Nulls => new_null_array(field.data_type(), batch.num_rows()),
+            });
+        }
+        RecordBatch::try_new(self.target.clone(), arrays)
+            .map_err(|e| anyhow!("merge batch schema mismatch: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, Int32Array, Int64Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_reconcile_columns() {
+        let source = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("n", DataType::Int32, true),
+            Field::new("legacy", DataType::Utf8, true),
+        ]));
+        let target = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("n", DataType::Int64, true),
+            Field::new("note", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(source.clone(), vec![
+            Arc::new(Int64Array::from(vec![0, 1])),
+            Arc::new(Int32Array::from(vec![5, 6])),
+            Arc::new(StringArray::from(vec!["a", "b"])),
+        ])
+        .unwrap();
+
+        assert!(SchemaReconciler::new(&source, &target, true).is_err());
+
+        let reconciler = SchemaReconciler::new(&source, &target, false).unwrap();
+        let out = reconciler.apply(&batch, Arc::new(Int64Array::from(vec![10, 11]))).unwrap();
+        assert_eq!(out.schema(), target);
+        let n = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(n.values(), &[5, 6]);
+        assert_eq!(out.column(2).null_count(), 2);
+
+        // Values the cast cannot convert fail instead of becoming null
+        let narrow = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("n", DataType::Int8, true),
+        ]));
+        let reconciler = SchemaReconciler::new(&source, &narrow, false).unwrap();
+        let wide = RecordBatch::try_new(source.clone(), vec![
+            Arc::new(Int64Array::from(vec![0, 1])),
+            Arc::new(Int32Array::from(vec![5, 300])),
+            Arc::new(StringArray::from(vec!["a", "b"])),
+        ])
+        .unwrap();
+        let err = reconciler.apply(&wide, Arc::new(Int64Array::from(vec![10, 11]))).unwrap_err();
+        assert!(err.to_string().contains("column n cannot be converted"), "{}", err);
+        assert!(reconciler.apply(&batch, Arc::new(Int64Array::from(vec![10, 11]))).is_ok());
+
+        let required = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("note", DataType::Utf8, false),
+        ]));
+        assert!(SchemaReconciler::new(&source, &required, false).is_err());
+    }
+}
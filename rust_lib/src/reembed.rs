@@ -0,0 +1,68 @@
+//! Re-embedding a table in place.
+//!
+//! Moving a table to a new embedding model changes every vector and usually the
+//! dimension, which the `vector` column's type fixes. A re-embed builds the new
+//! vectors in a [`NEXT_COLUMN`] alongside it: `reembed_begin(new_dim)` adds the
+//! column (NULL for every row), `reembed_write(labels, vectors)` fills it in any
+//! number of batches while searches keep using `vector`, and `reembed_finish()`
+//! swaps the two in one commit and drops the old vectors. The new dimension is kept
+//! in the table metadata while the re-embed is in progress, so it can be resumed
+//! from another handle or process.
+//!
+//! Rows appended during the re-embed get a NULL new vector; finishing refuses while
+//! any row lacks one.
+//!
+//! Before the swap, both columns can be indexed and searched (`create_vector_index_on`,
+//! `search_column`), so traffic can be split or compared between the two models. The
+//! new column's index survives the swap as the table's vector index.
+
+use anyhow::{anyhow, Result};
+use arrow_schema::{DataType, Field};
+use std::sync::Arc;
+
+use crate::vector_storage::VectorStorage;
+
+/// Column holding each row's new vector while a re-embed is in progress.
+pub const NEXT_COLUMN: &str = "vector_next";
+
+/// Name the old vectors take during the swap, before they are dropped.
+pub const PREVIOUS_COLUMN: &str = "vector_prev";
+
+/// The nullable [`NEXT_COLUMN`] for `dim`-dimensional vectors stored as `storage`.
+pub fn next_field(dim: usize, storage: VectorStorage) -> Result<Field> {
+    let size = i32::try_from(dim)
+        .ok()
+        .filter(|size| *size > 0)
+        .ok_or_else(|| anyhow!("invalid re-embed dimension {}", dim))?;
+    let item = Arc::new(Field::new("item", storage.element_type(), true));
+    Ok(Field::new(NEXT_COLUMN, DataType::FixedSizeList(item, size), true))
+}
+
+/// The new dimension recorded in the table metadata.
+pub fn parse_dim(value: &str) -> Result<usize> {
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|dim| *dim > 0)
+        .ok_or_else(|| anyhow!("invalid re-embed dimension '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_field() {
+        let field = next_field(3, VectorStorage::Float16).unwrap();
+        assert_eq!(field.name(), NEXT_COLUMN);
+        assert!(field.is_nullable());
+        match field.data_type() {
+            DataType::FixedSizeList(item, 3) => assert_eq!(item.data_type(), &DataType::Float16),
+            other => panic!("unexpected type {}", other),
+        }
+        assert!(next_field(0, VectorStorage::Float32).is_err());
+        assert_eq!(parse_dim("768").unwrap(), 768);
+        assert!(parse_dim("0").is_err());
+        assert!(parse_dim("wide").is_err());
+    }
+}
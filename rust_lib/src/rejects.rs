@@ -0,0 +1,128 @@
+//! Dead-letter capture of rows rejected at ingest.
+//!
+//! Rows dropped by a constraint with `on_violation=dead_letter`, or that fail
+//! validation (see [`check_vectors`] and `CastPlan::failed_rows`) while the table's
+//! `on_invalid` mode is `dead_letter`, are appended to a sidecar Lance table
+//! (`<table>__rejects`) instead of failing their batch. Each
+//! reject keeps the time, the row's index in its batch, the reason and the row
+//! itself rendered as text, so rows whose values do not fit the table schema can be
+//! kept too.
+
+use anyhow::Result;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use arrow_array::{Array, FixedSizeListArray, Float16Array, Float32Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+use crate::constraints::Violation;
+
+/// A rejected row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reject {
+    pub rejected_ms: i64,
+    /// Index of the row in the batch it arrived in.
+    pub source_row: i64,
+    pub reason: String,
+    /// `column=value` pairs of the row.
+    pub row: String,
+}
+
+pub fn sidecar_table_name(table_name: &str) -> String {
+    format!("{}__rejects", table_name)
+}
+
+pub fn sidecar_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("rejected_ms", DataType::Int64, false),
+        Field::new("source_row", DataType::Int64, false),
+        Field::new("reason", DataType::Utf8, false),
+        Field::new("row", DataType::Utf8, false),
+    ]))
+}
+
+/// Rows of a vector column that cannot be indexed: NULL vectors, NULL elements and
+/// NaN or infinite elements.
+pub fn check_vectors(vectors: &FixedSizeListArray) -> Vec<Violation> {
+    let elements = vectors.values();
+    let floats = elements.as_any().downcast_ref::<Float32Array>();
+    // Float16 storage: elements beyond its range were narrowed to infinity
+    let halves = elements.as_any().downcast_ref::<Float16Array>();
+    let size = vectors.value_length() as usize;
+    let mut invalid = Vec::new();
+    for row in 0..vectors.len() {
+        let start = vectors.value_offset(row) as usize;
+        let reason = if vectors.is_null(row) {
+            "vector is NULL"
+        } else if (start..start + size).any(|i| elements.is_null(i)) {
+            "vector has NULL elements"
+        } else if floats.is_some_and(|f| f.values()[start..start + size].iter().any(|v| !v.is_finite()))
+            || halves.is_some_and(|f| f.values()[start..start + size].iter().any(|v| !v.is_finite()))
+        {
+            "vector has NaN or infinite elements"
+        } else {
+            continue;
+        };
+        invalid.push(Violation {
+            row,
+            reason: reason.to_string(),
+        });
+    }
+    invalid
+}
+
+/// Render row `row` of `batch` as `column=value, ...`.
+pub fn format_row(batch: &RecordBatch, row: usize) -> Result<String> {
+    let options = FormatOptions::default().with_null("NULL");
+    let mut parts = Vec::with_capacity(batch.num_columns());
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let formatter = ArrayFormatter::try_new(column.as_ref(), &options)?;
+        parts.push(format!("{}={}", field.name(), formatter.value(row)));
+    }
+    Ok(parts.join(", "))
+}
+
+pub fn to_record_batch(rejects: &[Reject]) -> Result<RecordBatch> {
+    Ok(RecordBatch::try_new(sidecar_schema(), vec![
+        Arc::new(Int64Array::from_iter_values(rejects.iter().map(|r| r.rejected_ms))),
+        Arc::new(Int64Array::from_iter_values(rejects.iter().map(|r| r.source_row))),
+        Arc::new(StringArray::from_iter_values(rejects.iter().map(|r| r.reason.as_str()))),
+        Arc::new(StringArray::from_iter_values(rejects.iter().map(|r| r.row.as_str()))),
+    ])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_row() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("category", DataType::Utf8, true),
+            Field::new("score", DataType::Float32, true),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from(vec![Some("news"), None])),
+            Arc::new(Float32Array::from(vec![0.5, 1.5])),
+        ])
+        .unwrap();
+        assert_eq!(format_row(&batch, 0).unwrap(), "category=news, score=0.5");
+        assert_eq!(format_row(&batch, 1).unwrap(), "category=NULL, score=1.5");
+    }
+
+    #[test]
+    fn test_check_vectors() {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let mut values = [1.0, 2.0, f32::NAN, 0.0, 0.0, 1.0, 0.0, 0.0, f32::INFINITY, 0.0].map(Some).to_vec();
+        values[4] = None;
+        let values = Float32Array::from(values);
+        let validity = vec![true, true, true, false, true];
+        let vectors = FixedSizeListArray::new(item, 2, Arc::new(values), Some(validity.into()));
+        let invalid: Vec<(usize, String)> = check_vectors(&vectors).into_iter().map(|v| (v.row, v.reason)).collect();
+        assert_eq!(invalid, vec![
+            (1, "vector has NaN or infinite elements".to_string()),
+            (2, "vector has NULL elements".to_string()),
+            (3, "vector is NULL".to_string()),
+            (4, "vector has NaN or infinite elements".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Manifest blobs for read replicas.
+//!
+//! A read replica on another machine attaches to the object storage the writer
+//! commits to. Rather than listing the bucket to discover new versions, the writer
+//! exports the manifest of a committed version as a [`ManifestBlob`] and hands it to
+//! the replica through the catalog; the replica imports it, which checks the blob
+//! against the manifest stored for that version and then moves its view to the
+//! version in one step. A blob is a few KB: the manifest lists fragments and
+//! indices, not data.
+//!
+//! Layout: `LNCM`, a format byte, the table name (u16 length + UTF-8), the version
+//! (u64) and the manifest file bytes; integers little-endian.
+
+use anyhow::{anyhow, Result};
+use lance::dataset::Dataset;
+use lance::io::{ObjectStore, ObjectStoreParams, ObjectStoreRegistry};
+use std::sync::Arc;
+
+const MAGIC: &[u8; 4] = b"LNCM";
+const FORMAT: u8 = 1;
+
+/// One committed version of a table, as exchanged between writer and replicas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestBlob {
+    pub table_name: String,
+    pub version: u64,
+    /// The version's manifest file as stored.
+    pub manifest: Vec<u8>,
+}
+
+impl ManifestBlob {
+    pub fn encode(&self) -> Vec<u8> {
+        let name = self.table_name.as_bytes();
+        let mut out = Vec::with_capacity(MAGIC.len() + 11 + name.len() + self.manifest.len());
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT);
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(name);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.manifest);
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let rest = bytes.strip_prefix(MAGIC).ok_or_else(|| anyhow!("not a manifest blob"))?;
+        let (&format, rest) = rest.split_first().ok_or_else(|| anyhow!("truncated manifest blob"))?;
+        if format != FORMAT {
+            return Err(anyhow!("unsupported manifest blob format {}", format));
+        }
+        let (len, rest) = take::<2>(rest)?;
+        let name_len = u16::from_le_bytes(len) as usize;
+        if rest.len() < name_len {
+            return Err(anyhow!("truncated manifest blob"));
+        }
+        let (name, rest) = rest.split_at(name_len);
+        let table_name =
+            String::from_utf8(name.to_vec()).map_err(|_| anyhow!("manifest blob has an invalid table name"))?;
+        let (version, manifest) = take::<8>(rest)?;
+        if manifest.is_empty() {
+            return Err(anyhow!("manifest blob holds no manifest"));
+        }
+        Ok(Self {
+            table_name,
+            version: u64::from_le_bytes(version),
+            manifest: manifest.to_vec(),
+        })
+    }
+}
+
+fn take<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8])> {
+    if bytes.len() < N {
+        return Err(anyhow!("truncated manifest blob"));
+    }
+    let (head, rest) = bytes.split_at(N);
+    Ok((head.try_into()?, rest))
+}
+
+/// The manifest file of `dataset`'s checked-out version.
+pub async fn read_manifest(dataset: &Dataset, params: &ObjectStoreParams) -> Result<Vec<u8>> {
+    let registry = Arc::new(ObjectStoreRegistry::default());
+    let (store, base) = ObjectStore::from_uri_and_params(registry, dataset.uri(), params).await?;
+    let path = dataset.manifest_naming_scheme.manifest_path(&base, dataset.version().version);
+    Ok(store.inner.get(&path).await?.bytes().await?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let blob = ManifestBlob {
+            table_name: "docs".to_string(),
+            version: 42,
+            manifest: vec![1, 2, 3],
+        };
+        let bytes = blob.encode();
+        assert_eq!(ManifestBlob::decode(&bytes).unwrap(), blob);
+
+        assert!(ManifestBlob::decode(b"LNCX").is_err());
+        assert!(ManifestBlob::decode(&bytes[..bytes.len() - 3]).is_err());
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert!(ManifestBlob::decode(&future).unwrap_err().to_string().contains("format 2"));
+    }
+}
@@ -1,25 +1,157 @@
-//! Tokio runtime for blocking on LanceDB async operations.
+//! Tokio runtimes for LanceDB async operations and CPU-heavy work.
 //!
-//! Thread count scales with available cores (capped at 4) since DuckDB manages
-//! its own parallelism — we only need enough threads for async Lance I/O.
+//! The I/O runtime's thread count scales with available cores (capped at 4) since
+//! DuckDB manages its own parallelism — we only need enough threads for async Lance
+//! I/O. CPU-bound stages (index training, PCA/OPQ fitting, rescoring, reranking) run
+//! on a separate pool, so they cannot starve concurrent searches of I/O workers.
+//!
+//! Lance trains indices (k-means, PQ, HNSW) on a pool of its own, sized from the
+//! `LANCE_CPU_THREADS` environment variable when it first starts. The variable is
+//! only read, never set here (changing the environment of a multi-threaded host is
+//! unsound): to bound index builds, set it before the extension loads. The CPU pool
+//! defaults to the same count.
 
-use std::sync::LazyLock;
+use anyhow::{anyhow, Result};
+use std::sync::{LazyLock, OnceLock};
 use tokio::runtime::Runtime;
 
+/// Thread counts of the Lance runtimes. Fixed once either runtime has started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// Async I/O workers.
+    pub io_threads: usize,
+    /// Workers and blocking threads of the CPU pool.
+    pub cpu_threads: usize,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2);
+        let lance_threads = std::env::var(LANCE_CPU_THREADS)
+            .ok()
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|n| *n > 0);
+        Self {
+            io_threads: cores.min(4),
+            cpu_threads: lance_threads.unwrap_or(cores),
+        }
+    }
+}
+
+/// Variable Lance reads for the size of its training pool.
+const LANCE_CPU_THREADS: &str = "LANCE_CPU_THREADS";
+
+static CONFIG: OnceLock<RuntimeConfig> = OnceLock::new();
+
+/// The effective configuration; fixes the defaults if none was set.
+pub fn config() -> RuntimeConfig {
+    *CONFIG.get_or_init(RuntimeConfig::default)
+}
+
+/// Set the thread counts before first use; 0 keeps the default (or, once started,
+/// the running count). Fails once the runtimes have started with other counts.
+pub fn configure(io_threads: usize, cpu_threads: usize) -> Result<RuntimeConfig> {
+    let current = *CONFIG.get_or_init(|| {
+        let defaults = RuntimeConfig::default();
+        RuntimeConfig {
+            io_threads: if io_threads == 0 { defaults.io_threads } else { io_threads },
+            cpu_threads: if cpu_threads == 0 { defaults.cpu_threads } else { cpu_threads },
+        }
+    });
+    let conflicts = |requested: usize, running: usize| requested != 0 && requested != running;
+    if conflicts(io_threads, current.io_threads) || conflicts(cpu_threads, current.cpu_threads) {
+        return Err(anyhow!(
+            "runtime already started with io_threads={}, cpu_threads={}",
+            current.io_threads,
+            current.cpu_threads
+        ));
+    }
+    Ok(current)
+}
+
 static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
-    let threads = std::thread::available_parallelism()
-        .map(|n| n.get().min(4))
-        .unwrap_or(2);
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(config().io_threads)
+        .thread_name("lance-io")
+        .enable_all()
+        .build()
+        .expect("failed to create tokio runtime")
+});
 
+static CPU_RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    let threads = config().cpu_threads;
     tokio::runtime::Builder::new_multi_thread()
         .worker_threads(threads)
-        .thread_name("lance-io")
+        .max_blocking_threads(threads)
+        .thread_name("lance-cpu")
         .enable_all()
         .build()
         .expect("failed to create tokio runtime")
 });
 
-/// Block on an async future using the shared tokio runtime.
+/// Block on an async future using the I/O runtime.
 pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
     RUNTIME.block_on(future)
 }
+
+/// Block on a CPU-heavy future (e.g. index training) using the CPU pool.
+pub fn block_on_cpu<F: std::future::Future>(future: F) -> F::Output {
+    CPU_RUNTIME.block_on(future)
+}
+
+/// Run CPU-bound `work` on the CPU pool and wait for its result.
+pub fn run_cpu<R, F>(work: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static,
+{
+    match CPU_RUNTIME.block_on(CPU_RUNTIME.spawn_blocking(work)) {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Run blocking work — including `LanceIndex` calls, which block on the I/O runtime
+/// internally — on the I/O runtime's blocking pool without waiting for it.
+pub fn spawn_blocking<F: FnOnce() + Send + 'static>(work: F) {
+    RUNTIME.spawn_blocking(work);
+}
+
+/// Run `future` on the I/O runtime without waiting for it.
+pub fn spawn<F>(future: F)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    RUNTIME.spawn(future);
+}
+
+/// Run blocking `work` (e.g. file I/O) on the I/O runtime's blocking pool and wait
+/// for its result without blocking the calling task's worker.
+pub async fn run_blocking<R, F>(work: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static,
+{
+    match RUNTIME.spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configure_after_start_and_run_cpu() {
+        assert_eq!(run_cpu(|| 2 + 2), 4);
+        let current = config();
+        assert_eq!(configure(current.io_threads, current.cpu_threads).unwrap(), current);
+        assert_eq!(configure(0, 0).unwrap(), current);
+        assert!(configure(current.io_threads + 1, 0).is_err());
+        let thread = run_cpu(|| std::thread::current().name().map(str::to_string));
+        assert_eq!(thread.as_deref(), Some("lance-cpu"));
+    }
+}
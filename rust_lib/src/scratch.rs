@@ -0,0 +1,61 @@
+//! Per-thread reuse of search result buffers.
+//!
+//! A high-QPS search loop otherwise allocates a fresh result `Vec` per query. The
+//! search path takes a cleared buffer from the calling thread's pool, and the FFI
+//! layer hands it back once the results are copied out. Buffers that grew past
+//! [`MAX_POOLED_CAPACITY`] are dropped rather than pooled.
+
+use std::cell::RefCell;
+
+/// Buffers kept per thread.
+const MAX_POOLED: usize = 4;
+
+/// Largest capacity (in hits) a pooled buffer may keep.
+const MAX_POOLED_CAPACITY: usize = 1 << 16;
+
+thread_local! {
+    static RESULTS: RefCell<Vec<Vec<(i64, f32)>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An empty result buffer with room for at least `capacity` hits.
+pub fn results(capacity: usize) -> Vec<(i64, f32)> {
+    let mut buf = RESULTS
+        .with(|pool| pool.borrow_mut().pop())
+        .unwrap_or_default();
+    buf.reserve(capacity);
+    buf
+}
+
+/// Return a buffer from [`results`] for reuse by later searches on this thread.
+pub fn recycle(mut buf: Vec<(i64, f32)>) {
+    if buf.capacity() > MAX_POOLED_CAPACITY {
+        return;
+    }
+    buf.clear();
+    RESULTS.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < MAX_POOLED {
+            pool.push(buf);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_are_reused() {
+        let mut buf = results(8);
+        buf.push((1, 0.5));
+        let ptr = buf.as_ptr();
+        recycle(buf);
+
+        let reused = results(4);
+        assert!(reused.is_empty());
+        assert_eq!(reused.as_ptr(), ptr);
+
+        recycle(Vec::with_capacity(MAX_POOLED_CAPACITY + 1));
+        assert!(results(0).capacity() <= MAX_POOLED_CAPACITY);
+    }
+}
@@ -0,0 +1,334 @@
+//! ORDER BY for scans.
+//!
+//! LanceDB's query builder cannot sort, so rows are ordered in Rust as they stream
+//! in. With a limit only the best `limit` rows are ever kept, so `ORDER BY ts
+//! LIMIT 100` costs memory for about one batch plus 100 rows. Without one, sorted
+//! runs of [`SPILL_ROWS`] rows are spilled to Arrow IPC files in the temp directory
+//! and merged back lazily, a batch at a time, as the caller reads the result.
+
+use anyhow::{anyhow, Result};
+use arrow::compute::{concat_batches, interleave, sort_to_indices, take_record_batch, SortOptions};
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::row::{OwnedRow, RowConverter, Rows, SortField};
+use arrow_array::{Array, RecordBatch, RecordBatchIterator, RecordBatchReader};
+use arrow_schema::{ArrowError, DataType, SchemaRef};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Rows buffered before a sorted run is spilled to disk.
+pub const SPILL_ROWS: usize = 1 << 20;
+
+/// Rows per batch of a merged (spilled) result.
+const OUTPUT_BATCH_ROWS: usize = 8192;
+
+static NEXT_SPILL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Sort key of a scan. Nulls sort last in both directions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBy {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl OrderBy {
+    fn options(&self) -> SortOptions {
+        SortOptions {
+            descending: self.descending,
+            nulls_first: false,
+        }
+    }
+}
+
+/// Accumulates the batches of a scan and returns them ordered.
+pub struct ScanSorter {
+    schema: SchemaRef,
+    order: OrderBy,
+    key: usize,
+    limit: Option<usize>,
+    spill_rows: usize,
+    buffered: Vec<RecordBatch>,
+    buffered_rows: usize,
+    runs: Vec<SpillFile>,
+}
+
+impl ScanSorter {
+    /// Sorter for batches of `schema`, keeping the first `limit` rows when given.
+    pub fn new(schema: SchemaRef, order: OrderBy, limit: Option<usize>) -> Result<Self> {
+        let key = schema
+            .index_of(&order.column)
+            .map_err(|_| anyhow!("order_by column '{}' not found", order.column))?;
+        if matches!(
+            schema.field(key).data_type(),
+            DataType::FixedSizeList(..) | DataType::List(..) | DataType::Struct(..)
+        ) {
+            return Err(anyhow!("cannot order by nested column '{}'", order.column));
+        }
+        Ok(Self {
+            schema,
+            order,
+            key,
+            limit,
+            spill_rows: SPILL_ROWS,
+            buffered: Vec::new(),
+            buffered_rows: 0,
+            runs: Vec::new(),
+        })
+    }
+
+    pub fn push(&mut self, batch: RecordBatch) -> Result<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        self.buffered_rows += batch.num_rows();
+        self.buffered.push(batch);
+        match self.limit {
+            // Trim to the best `limit` rows once the buffer holds twice that
+            Some(limit) if self.buffered_rows >= limit.saturating_mul(2).max(OUTPUT_BATCH_ROWS) => {
+                let kept = self.sort_buffered(Some(limit))?;
+                self.buffered_rows = kept.num_rows();
+                self.buffered = vec![kept];
+            }
+            None if self.buffered_rows >= self.spill_rows => {
+                let run = self.sort_buffered(None)?;
+                self.runs.push(SpillFile::write(&run)?);
+                self.buffered.clear();
+                self.buffered_rows = 0;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The rows pushed so far, ordered, as a reader of at most `batch_rows` rows
+    /// per batch.
+    pub fn finish(mut self, batch_rows: usize) -> Result<Box<dyn RecordBatchReader + Send>> {
+        let last = self.sort_buffered(self.limit)?;
+        if self.runs.is_empty() {
+            let slices: Vec<Result<RecordBatch, ArrowError>> = (0..last.num_rows())
+                .step_by(batch_rows.max(1))
+                .map(|offset| Ok(last.slice(offset, batch_rows.min(last.num_rows() - offset))))
+                .collect();
+            return Ok(Box::new(RecordBatchIterator::new(slices, self.schema.clone())));
+        }
+        if last.num_rows() > 0 {
+            self.runs.push(SpillFile::write(&last)?);
+        }
+        Ok(Box::new(MergeReader::new(self.schema, &self.order, self.key, self.runs, batch_rows)?))
+    }
+
+    /// The buffered rows concatenated and ordered, truncated to `limit`.
+    fn sort_buffered(&self, limit: Option<usize>) -> Result<RecordBatch> {
+        let all = concat_batches(&self.schema, &self.buffered)?;
+        let order = sort_to_indices(all.column(self.key), Some(self.order.options()), limit)?;
+        Ok(take_record_batch(&all, &order)?)
+    }
+}
+
+/// A sorted run in the temp directory, removed on drop.
+struct SpillFile {
+    path: PathBuf,
+}
+
+impl SpillFile {
+    fn write(batch: &RecordBatch) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "lance_sort_{}_{}.arrow",
+            std::process::id(),
+            NEXT_SPILL_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let spill = Self { path };
+        let mut writer = FileWriter::try_new(File::create(&spill.path)?, &batch.schema())?;
+        for offset in (0..batch.num_rows()).step_by(OUTPUT_BATCH_ROWS) {
+            writer.write(&batch.slice(offset, OUTPUT_BATCH_ROWS.min(batch.num_rows() - offset)))?;
+        }
+        writer.finish()?;
+        Ok(spill)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// One spilled run being merged: its current batch and the sort keys of its rows.
+struct Run {
+    reader: FileReader<File>,
+    batch: RecordBatch,
+    keys: Rows,
+    row: usize,
+    _file: SpillFile,
+}
+
+/// K-way merge of sorted runs, reading one batch per run at a time.
+struct MergeReader {
+    schema: SchemaRef,
+    key: usize,
+    converter: RowConverter,
+    runs: Vec<Run>,
+    heap: BinaryHeap<Reverse<(OwnedRow, usize)>>,
+    batch_rows: usize,
+}
+
+impl MergeReader {
+    fn new(schema: SchemaRef, order: &OrderBy, key: usize, files: Vec<SpillFile>, batch_rows: usize) -> Result<Self> {
+        let field = SortField::new_with_options(schema.field(key).data_type().clone(), order.options());
+        let mut merge = Self {
+            schema,
+            key,
+            converter: RowConverter::new(vec![field])?,
+            runs: Vec::with_capacity(files.len()),
+            heap: BinaryHeap::new(),
+            batch_rows: batch_rows.max(1),
+        };
+        for file in files {
+            let mut reader = FileReader::try_new(File::open(&file.path)?, None)?;
+            if let Some(batch) = reader.next().transpose()? {
+                let keys = merge.converter.convert_columns(&[batch.column(merge.key).clone()])?;
+                merge.heap.push(Reverse((keys.row(0).owned(), merge.runs.len())));
+                merge.runs.push(Run {
+                    reader,
+                    batch,
+                    keys,
+                    row: 0,
+                    _file: file,
+                });
+            }
+        }
+        Ok(merge)
+    }
+
+    /// Move `run` to its next row, reading its next batch when needed. Returns
+    /// false once the run is exhausted.
+    fn advance(&mut self, run: usize) -> Result<bool> {
+        let state = &mut self.runs[run];
+        state.row += 1;
+        if state.row < state.batch.num_rows() {
+            return Ok(true);
+        }
+        match state.reader.next().transpose()? {
+            Some(batch) if batch.num_rows() > 0 => {
+                state.keys = self.converter.convert_columns(&[batch.column(self.key).clone()])?;
+                state.batch = batch;
+                state.row = 0;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        // Batches the picked rows come from; a run's batch is added when first used
+        let mut sources: Vec<RecordBatch> = Vec::new();
+        let mut source_of_run: Vec<Option<usize>> = vec![None; self.runs.len()];
+        let mut picks = Vec::with_capacity(self.batch_rows);
+        while picks.len() < self.batch_rows {
+            let Some(Reverse((_, run))) = self.heap.pop() else {
+                break;
+            };
+            let source = *source_of_run[run].get_or_insert_with(|| {
+                sources.push(self.runs[run].batch.clone());
+                sources.len() - 1
+            });
+            picks.push((source, self.runs[run].row));
+            if self.advance(run)? {
+                if self.runs[run].row == 0 {
+                    // A new batch of this run: later picks need a new source
+                    source_of_run[run] = None;
+                }
+                let state = &self.runs[run];
+                self.heap.push(Reverse((state.keys.row(state.row).owned(), run)));
+            }
+        }
+        if picks.is_empty() {
+            return Ok(None);
+        }
+        let columns = (0..self.schema.fields().len())
+            .map(|c| {
+                let arrays: Vec<&dyn Array> = sources.iter().map(|b| b.column(c).as_ref()).collect();
+                interleave(&arrays, &picks)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(RecordBatch::try_new(self.schema.clone(), columns)?))
+    }
+}
+
+impl Iterator for MergeReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch()
+            .map_err(|e| ArrowError::ExternalError(e.into()))
+            .transpose()
+    }
+}
+
+impl RecordBatchReader for MergeReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+    use arrow_schema::{Field, Schema};
+    use std::sync::Arc;
+
+    fn batch(values: Vec<Option<i64>>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("ts", DataType::Int64, true)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    fn collect(reader: Box<dyn RecordBatchReader + Send>) -> Vec<Option<i64>> {
+        reader
+            .flat_map(|b| {
+                let b = b.unwrap();
+                let values = b.column(0).as_any().downcast_ref::<Int64Array>().unwrap().clone();
+                values.iter().collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_top_n_keeps_limit_rows() {
+        let order = OrderBy { column: "ts".to_string(), descending: true };
+        let mut sorter = ScanSorter::new(batch(vec![]).schema(), order, Some(3)).unwrap();
+        for chunk in (0..20_000).collect::<Vec<i64>>().chunks(1000) {
+            sorter.push(batch(chunk.iter().map(|v| Some(*v)).collect())).unwrap();
+            assert!(sorter.buffered_rows < 2 * OUTPUT_BATCH_ROWS);
+        }
+        sorter.push(batch(vec![None])).unwrap();
+        assert_eq!(collect(sorter.finish(2).unwrap()), vec![Some(19_999), Some(19_998), Some(19_997)]);
+    }
+
+    #[test]
+    fn test_spilled_runs_merge_in_order() {
+        let order = OrderBy { column: "ts".to_string(), descending: false };
+        let mut sorter = ScanSorter::new(batch(vec![]).schema(), order, None).unwrap();
+        sorter.spill_rows = 4;
+        sorter.push(batch(vec![Some(5), None, Some(1), Some(9)])).unwrap();
+        sorter.push(batch(vec![Some(4), Some(2), Some(8), Some(7)])).unwrap();
+        sorter.push(batch(vec![Some(3), Some(6)])).unwrap();
+        assert_eq!(sorter.runs.len(), 2);
+
+        let expected: Vec<Option<i64>> = (1..=9).map(Some).chain([None]).collect();
+        assert_eq!(collect(sorter.finish(3).unwrap()), expected);
+
+        let vectors = Schema::new(vec![Field::new(
+            "v",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 2),
+            true,
+        )]);
+        let order = OrderBy { column: "v".to_string(), descending: false };
+        assert!(ScanSorter::new(Arc::new(vectors), order.clone(), None).is_err());
+        let order = OrderBy { column: "missing".to_string(), ..order };
+        assert!(ScanSorter::new(batch(vec![]).schema(), order, None).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Staging tables for zero-downtime rebuilds.
+//!
+//! A staging table is a hidden sibling of a live table in the same database. The
+//! caller fills and indexes it while the live table keeps serving, then promotes
+//! it. Promotion is a commit on the live table: its current version is tagged as
+//! retired, the staging files are linked into its dataset, and an overwrite makes
+//! them its rows. Open handles see the new rows like any other write, and the
+//! retired version can be checked out by its tag until it is deleted.
+//!
+//! Tables are renamed by renaming their dataset directory, since the bundled
+//! LanceDB cannot rename tables itself; this, and promotion, require a local
+//! database.
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+pub fn staging_table_name(table_name: &str) -> String {
+    format!("{}__staging", table_name)
+}
+
+/// Tag the replaced version of a live table is kept under.
+pub fn retired_tag_name(retired_ms: i64) -> String {
+    format!("retired_{}", retired_ms)
+}
+
+/// Directory of `table_name` in a local database directory.
+pub fn table_dir(db_dir: &Path, table_name: &str) -> PathBuf {
+    db_dir.join(format!("{}.lance", table_name))
+}
+
+/// Rename a table by renaming its dataset directory in `db_dir`.
+pub fn rename_table_dir(db_dir: &Path, old_name: &str, new_name: &str) -> Result<()> {
+    let from = table_dir(db_dir, old_name);
+    let to = table_dir(db_dir, new_name);
+    if !from.is_dir() {
+        return Err(anyhow!("table {} does not exist", old_name));
+    }
+    if to.exists() {
+        return Err(anyhow!("table {} already exists", new_name));
+    }
+    std::fs::rename(&from, &to).map_err(|e| anyhow!("rename {} to {} failed: {}", old_name, new_name, e))
+}
+
+/// Link (or, across filesystems, copy) the data, deletion and index files of the
+/// dataset in `from` into the dataset in `to`, so a commit on `to` can refer to
+/// them. Lance names these files uniquely, so files already present are kept.
+pub fn link_dataset_files(from: &Path, to: &Path) -> Result<()> {
+    for dir in ["data", "_deletions", "_indices"] {
+        link_tree(&from.join(dir), &to.join(dir))?;
+    }
+    Ok(())
+}
+
+fn link_tree(from: &Path, to: &Path) -> Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            link_tree(&entry.path(), &target)?;
+        } else if !target.exists() && std::fs::hard_link(entry.path(), &target).is_err() {
+            std::fs::copy(entry.path(), &target)
+                .map_err(|e| anyhow!("copy {} failed: {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_dataset_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let staging = table_dir(dir.path(), &staging_table_name("vectors"));
+        let live = table_dir(dir.path(), "vectors");
+        std::fs::create_dir_all(staging.join("data")).unwrap();
+        std::fs::create_dir_all(staging.join("_indices").join("uuid")).unwrap();
+        std::fs::create_dir_all(staging.join("_versions")).unwrap();
+        std::fs::write(staging.join("data").join("new.lance"), b"new").unwrap();
+        std::fs::write(staging.join("_indices").join("uuid").join("index.idx"), b"idx").unwrap();
+        std::fs::write(staging.join("_versions").join("1.manifest"), b"staging").unwrap();
+        std::fs::create_dir_all(live.join("data")).unwrap();
+        std::fs::write(live.join("data").join("old.lance"), b"old").unwrap();
+
+        link_dataset_files(&staging, &live).unwrap();
+        assert_eq!(std::fs::read(live.join("data").join("new.lance")).unwrap(), b"new");
+        assert_eq!(std::fs::read(live.join("data").join("old.lance")).unwrap(), b"old");
+        assert!(live.join("_indices").join("uuid").join("index.idx").exists());
+        // Versions stay the live table's own
+        assert!(!live.join("_versions").exists());
+        // Linking again keeps what is there
+        link_dataset_files(&staging, &live).unwrap();
+    }
+
+    #[test]
+    fn test_rename_table_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(table_dir(dir.path(), "vectors")).unwrap();
+        std::fs::create_dir(table_dir(dir.path(), "other")).unwrap();
+
+        // Renames never overwrite
+        assert!(rename_table_dir(dir.path(), "vectors", "other").is_err());
+        assert!(rename_table_dir(dir.path(), "missing", "renamed").is_err());
+        rename_table_dir(dir.path(), "vectors", "renamed").unwrap();
+        assert!(table_dir(dir.path(), "renamed").is_dir());
+    }
+}
@@ -0,0 +1,194 @@
+//! Column statistics used by the C++ side for filter selectivity estimation.
+
+use anyhow::{anyhow, Result};
+use arrow::array::AsArray;
+use arrow::compute::{concat, max, max_boolean, max_string, min, min_boolean, min_string};
+use arrow::datatypes::{
+    Date32Type, Date64Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimeUnit, TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+    TimestampSecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, Int64Array, PrimitiveArray, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// Min/max/null-count of one column. `min`/`max` are single-element arrays of the
+/// column's own type (null when the column has no non-null values).
+#[derive(Debug, Clone)]
+pub struct ColumnStats {
+    pub column: String,
+    pub min: ArrayRef,
+    pub max: ArrayRef,
+    pub null_count: u64,
+    pub row_count: u64,
+}
+
+impl ColumnStats {
+    /// Single-row batch of (min, max, null_count, row_count), preserving the column type.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let value_type = self.min.data_type().clone();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("min", value_type.clone(), true),
+            Field::new("max", value_type, true),
+            Field::new("null_count", DataType::Int64, false),
+            Field::new("row_count", DataType::Int64, false),
+        ]));
+        Ok(RecordBatch::try_new(schema, vec![
+            self.min.clone(),
+            self.max.clone(),
+            Arc::new(Int64Array::from(vec![self.null_count as i64])),
+            Arc::new(Int64Array::from(vec![self.row_count as i64])),
+        ])?)
+    }
+}
+
+/// How a filter maps onto the table's fragments.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PruningStats {
+    /// Column the table was last clustered by, if any.
+    pub cluster_by: Option<String>,
+    pub total_fragments: u64,
+    /// Fragments holding at least one matching row; the others need not be read.
+    pub matching_fragments: u64,
+}
+
+impl PruningStats {
+    pub fn pruned_fragments(&self) -> u64 {
+        self.total_fragments.saturating_sub(self.matching_fragments)
+    }
+}
+
+/// Accumulates statistics for one column across result batches.
+pub struct ColumnStatsBuilder {
+    column: String,
+    mins: Vec<ArrayRef>,
+    maxes: Vec<ArrayRef>,
+    null_count: u64,
+    row_count: u64,
+    data_type: Option<DataType>,
+}
+
+impl ColumnStatsBuilder {
+    pub fn new(column: &str) -> Self {
+        Self {
+            column: column.to_string(),
+            mins: Vec::new(),
+            maxes: Vec::new(),
+            null_count: 0,
+            row_count: 0,
+            data_type: None,
+        }
+    }
+
+    pub fn update(&mut self, array: &dyn Array) -> Result<()> {
+        let (batch_min, batch_max) = min_max(array)?;
+        self.mins.push(batch_min);
+        self.maxes.push(batch_max);
+        self.null_count += array.null_count() as u64;
+        self.row_count += array.len() as u64;
+        self.data_type = Some(array.data_type().clone());
+        Ok(())
+    }
+
+    /// Fold the per-batch extremes. `data_type` is used when no batches were seen.
+    pub fn finish(self, data_type: &DataType) -> Result<ColumnStats> {
+        let data_type = self.data_type.unwrap_or_else(|| data_type.clone());
+        let (min_arr, max_arr) = if self.mins.is_empty() {
+            let null = arrow_array::new_null_array(&data_type, 1);
+            (null.clone(), null)
+        } else {
+            let mins = concat(&self.mins.iter().map(|a| a.as_ref()).collect::<Vec<_>>())?;
+            let maxes = concat(&self.maxes.iter().map(|a| a.as_ref()).collect::<Vec<_>>())?;
+            (min_max(mins.as_ref())?.0, min_max(maxes.as_ref())?.1)
+        };
+        Ok(ColumnStats {
+            column: self.column,
+            min: min_arr,
+            max: max_arr,
+            null_count: self.null_count,
+            row_count: self.row_count,
+        })
+    }
+}
+
+/// Compute (min, max) of an array as single-element arrays of the same type.
+pub fn min_max(array: &dyn Array) -> Result<(ArrayRef, ArrayRef)> {
+    macro_rules! primitive {
+        ($t:ty) => {{
+            let a = array.as_primitive::<$t>();
+            let lo = PrimitiveArray::<$t>::from(vec![min(a)]).with_data_type(a.data_type().clone());
+            let hi = PrimitiveArray::<$t>::from(vec![max(a)]).with_data_type(a.data_type().clone());
+            (Arc::new(lo) as ArrayRef, Arc::new(hi) as ArrayRef)
+        }};
+    }
+
+    Ok(match array.data_type() {
+        DataType::Int8 => primitive!(Int8Type),
+        DataType::Int16 => primitive!(Int16Type),
+        DataType::Int32 => primitive!(Int32Type),
+        DataType::Int64 => primitive!(Int64Type),
+        DataType::UInt8 => primitive!(UInt8Type),
+        DataType::UInt16 => primitive!(UInt16Type),
+        DataType::UInt32 => primitive!(UInt32Type),
+        DataType::UInt64 => primitive!(UInt64Type),
+        DataType::Float32 => primitive!(Float32Type),
+        DataType::Float64 => primitive!(Float64Type),
+        DataType::Date32 => primitive!(Date32Type),
+        DataType::Date64 => primitive!(Date64Type),
+        DataType::Timestamp(TimeUnit::Second, _) => primitive!(TimestampSecondType),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => primitive!(TimestampMillisecondType),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => primitive!(TimestampMicrosecondType),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => primitive!(TimestampNanosecondType),
+        DataType::Utf8 => {
+            let a = array.as_string::<i32>();
+            (
+                Arc::new(StringArray::from(vec![min_string(a)])) as ArrayRef,
+                Arc::new(StringArray::from(vec![max_string(a)])) as ArrayRef,
+            )
+        }
+        DataType::Boolean => {
+            let a = array.as_boolean();
+            (
+                Arc::new(BooleanArray::from(vec![min_boolean(a)])) as ArrayRef,
+                Arc::new(BooleanArray::from(vec![max_boolean(a)])) as ArrayRef,
+            )
+        }
+        other => return Err(anyhow!("column statistics not supported for type {}", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int32Array;
+
+    #[test]
+    fn test_builder_folds_batches() {
+        let mut builder = ColumnStatsBuilder::new("score");
+        builder.update(&Int32Array::from(vec![Some(5), None, Some(3)])).unwrap();
+        builder.update(&Int32Array::from(vec![Some(9), Some(-1)])).unwrap();
+        builder.update(&Int32Array::from(vec![None::<i32>])).unwrap();
+        let stats = builder.finish(&DataType::Int32).unwrap();
+
+        assert_eq!(stats.min.as_primitive::<Int32Type>().value(0), -1);
+        assert_eq!(stats.max.as_primitive::<Int32Type>().value(0), 9);
+        assert_eq!(stats.null_count, 2);
+        assert_eq!(stats.row_count, 6);
+    }
+
+    #[test]
+    fn test_strings_and_empty_column() {
+        let mut builder = ColumnStatsBuilder::new("lang");
+        builder.update(&StringArray::from(vec!["fr", "en", "es"])).unwrap();
+        let stats = builder.finish(&DataType::Utf8).unwrap();
+        assert_eq!(stats.min.as_string::<i32>().value(0), "en");
+        assert_eq!(stats.max.as_string::<i32>().value(0), "fr");
+
+        let empty = ColumnStatsBuilder::new("lang").finish(&DataType::Utf8).unwrap();
+        assert!(empty.min.is_null(0));
+        assert_eq!(empty.row_count, 0);
+        assert_eq!(empty.to_record_batch().unwrap().num_rows(), 1);
+    }
+}
@@ -0,0 +1,92 @@
+//! Process-wide default object store options (credentials, region, endpoint).
+//!
+//! Options set with [`set_defaults`] apply to every connection opened afterwards, so
+//! they only need configuring once per process. Options given for one connection take
+//! precedence over the defaults key by key. Connections already open keep the options
+//! they were opened with.
+
+use anyhow::{anyhow, Result};
+use lancedb::Connection;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use crate::runtime;
+
+static DEFAULTS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+/// Parse `key=value` pairs, trimming keys and values. Errors name a pair by position
+/// only, since a malformed pair may be a bare secret.
+pub fn parse_pairs<S: AsRef<str>>(pairs: &[S]) -> Result<Vec<(String, String)>> {
+    pairs
+        .iter()
+        .enumerate()
+        .map(|(i, pair)| {
+            let (key, value) = pair
+                .as_ref()
+                .split_once('=')
+                .ok_or_else(|| anyhow!("storage option {} must be key=value", i + 1))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(anyhow!("storage option {} has an empty key", i + 1));
+            }
+            Ok((key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Replace the defaults with `options`; an empty list clears them.
+pub fn set_defaults(options: Vec<(String, String)>) {
+    *DEFAULTS.write().unwrap_or_else(|e| e.into_inner()) = options.into_iter().collect();
+}
+
+/// Keys of the current defaults, sorted.
+pub fn default_keys() -> Vec<String> {
+    DEFAULTS.read().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect()
+}
+
+/// `defaults` overridden key by key by `options`.
+fn merge(defaults: &BTreeMap<String, String>, options: &[(String, String)]) -> BTreeMap<String, String> {
+    let mut merged = defaults.clone();
+    merged.extend(options.iter().cloned());
+    merged
+}
+
+/// Connect to the database at `uri` with the defaults merged with `options`.
+pub fn connect(uri: &str, options: &[(String, String)]) -> Result<Connection> {
+    let merged = merge(&DEFAULTS.read().unwrap_or_else(|e| e.into_inner()), options);
+    Ok(runtime::block_on(lancedb::connect(uri).storage_options(merged).execute())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pairs() {
+        let pairs = parse_pairs(&["aws_region = us-east-1", "token=a=b"]).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("aws_region".to_string(), "us-east-1".to_string()),
+                ("token".to_string(), "a=b".to_string())
+            ]
+        );
+        assert!(parse_pairs(&["=value"]).is_err());
+        let err = parse_pairs(&["a=b", "secret"]).unwrap_err().to_string();
+        assert_eq!(err, "storage option 2 must be key=value");
+        assert!(parse_pairs::<&str>(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_call_options_override_defaults() {
+        let defaults: BTreeMap<String, String> = parse_pairs(&["aws_region=us-east-1", "endpoint=http://a"])
+            .unwrap()
+            .into_iter()
+            .collect();
+        let merged = merge(&defaults, &parse_pairs(&["endpoint=http://b", "timeout=30s"]).unwrap());
+        assert_eq!(merged["aws_region"], "us-east-1");
+        assert_eq!(merged["endpoint"], "http://b");
+        assert_eq!(merged["timeout"], "30s");
+        assert_eq!(defaults["endpoint"], "http://a");
+    }
+}
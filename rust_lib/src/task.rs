@@ -0,0 +1,168 @@
+//! Poll-based handles for work running on the Lance runtime.
+//!
+//! [`submit`] starts a job on the runtime's blocking pool and returns a task id at
+//! once. The host polls the id from its own loop (e.g. between interrupt checks)
+//! and, once the task is ready, takes its Arrow result, which removes the task.
+//! Discarding a running task waits for it to finish, so once [`discard`] returns
+//! nothing the task borrowed is used any more. Ids are process-wide and never reused.
+
+use anyhow::{anyhow, Result};
+use arrow_array::RecordBatch;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Condvar, LazyLock, Mutex};
+
+static TASKS: LazyLock<Mutex<HashMap<u64, TaskState>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+/// Signalled when a discarded task finishes.
+static CANCELLED_DONE: Condvar = Condvar::new();
+
+enum TaskState {
+    Pending,
+    Ready(RecordBatch),
+    Failed(String),
+    /// Discarded while running; the result is dropped when it arrives.
+    Cancelled,
+}
+
+/// What `poll` reports for a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Pending,
+    Ready,
+    Error,
+}
+
+fn tasks() -> std::sync::MutexGuard<'static, HashMap<u64, TaskState>> {
+    TASKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Run `work` on the runtime's blocking pool and return its task id. A panic in
+/// `work` fails the task.
+pub fn submit<F>(work: F) -> u64
+where
+    F: FnOnce() -> Result<RecordBatch> + Send + 'static,
+{
+    let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+    tasks().insert(id, TaskState::Pending);
+    crate::runtime::spawn_blocking(move || {
+        let state = match panic::catch_unwind(AssertUnwindSafe(work)) {
+            Ok(Ok(batch)) => TaskState::Ready(batch),
+            Ok(Err(e)) => TaskState::Failed(format!("{:#}", e)),
+            Err(payload) => TaskState::Failed(format!("task panicked: {}", panic_message(&*payload))),
+        };
+        let mut tasks = tasks();
+        if let Some(slot) = tasks.get_mut(&id) {
+            if matches!(slot, TaskState::Cancelled) {
+                tasks.remove(&id);
+                CANCELLED_DONE.notify_all();
+            } else {
+                *slot = state;
+            }
+        }
+    });
+    id
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Status of a task, or `None` for an unknown (taken or discarded) id.
+pub fn poll(id: u64) -> Option<TaskStatus> {
+    match tasks().get(&id)? {
+        TaskState::Pending => Some(TaskStatus::Pending),
+        TaskState::Ready(_) => Some(TaskStatus::Ready),
+        TaskState::Failed(_) => Some(TaskStatus::Error),
+        TaskState::Cancelled => None,
+    }
+}
+
+/// Remove a finished task and return its result or error. Fails without removing
+/// the task while it is still pending.
+pub fn take_result(id: u64) -> Result<RecordBatch> {
+    let mut tasks = tasks();
+    match tasks.get(&id) {
+        None | Some(TaskState::Cancelled) => Err(anyhow!("unknown task {}", id)),
+        Some(TaskState::Pending) => Err(anyhow!("task {} is still pending", id)),
+        Some(_) => match tasks.remove(&id) {
+            Some(TaskState::Ready(batch)) => Ok(batch),
+            Some(TaskState::Failed(msg)) => Err(anyhow!(msg)),
+            _ => unreachable!(),
+        },
+    }
+}
+
+/// Forget a task. A running task is waited for and its result dropped.
+pub fn discard(id: u64) {
+    let mut tasks = tasks();
+    match tasks.get_mut(&id) {
+        Some(slot @ TaskState::Pending) => *slot = TaskState::Cancelled,
+        Some(TaskState::Cancelled) => {}
+        Some(_) => {
+            tasks.remove(&id);
+            return;
+        }
+        None => return,
+    }
+    while tasks.contains_key(&id) {
+        tasks = CANCELLED_DONE.wait(tasks).unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+    use std::sync::Arc;
+
+    fn wait(id: u64) -> TaskStatus {
+        loop {
+            match poll(id).unwrap() {
+                TaskStatus::Pending => std::thread::sleep(std::time::Duration::from_millis(5)),
+                status => return status,
+            }
+        }
+    }
+
+    #[test]
+    fn test_task_lifecycle() {
+        let ok = submit(|| Ok(RecordBatch::try_from_iter([("x", Arc::new(Int64Array::from(vec![7])) as _)])?));
+        assert_eq!(wait(ok), TaskStatus::Ready);
+        assert_eq!(take_result(ok).unwrap().num_rows(), 1);
+        assert_eq!(poll(ok), None);
+        assert!(take_result(ok).is_err());
+
+        let failed = submit(|| Err(anyhow!("boom")));
+        assert_eq!(wait(failed), TaskStatus::Error);
+        assert_eq!(take_result(failed).unwrap_err().to_string(), "boom");
+
+        let panicked = submit(|| panic!("bad input"));
+        assert_eq!(wait(panicked), TaskStatus::Error);
+        assert_eq!(take_result(panicked).unwrap_err().to_string(), "task panicked: bad input");
+
+        // Discard returns only once the running task is done with what it borrowed
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let finished = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let done = finished.clone();
+        let slow = submit(move || {
+            rx.recv().ok();
+            done.store(true, Ordering::SeqCst);
+            Err(anyhow!("dropped"))
+        });
+        assert!(take_result(slow).unwrap_err().to_string().contains("pending"));
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            drop(tx);
+        });
+        discard(slow);
+        assert!(finished.load(Ordering::SeqCst));
+        assert_eq!(poll(slow), None);
+        release.join().unwrap();
+    }
+}
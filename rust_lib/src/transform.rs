@@ -0,0 +1,255 @@
+//! Query vector preprocessing applied by `search()` before the Lance query.
+//!
+//! A transform is a sequence of steps in text form, e.g. `slice(0,256) | center | normalize`:
+//!
+//! - `slice(start,len)`: keep `len` dimensions starting at `start` (Matryoshka-style truncation).
+//! - `center`: subtract the stored corpus mean.
+//! - `normalize`: scale to unit L2 norm.
+//!
+//! The spec and mean are persisted in the table metadata, so queries are always
+//! processed the way the corpus was when it was indexed.
+
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+use crate::distance;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Slice { start: usize, len: usize },
+    Center,
+    Normalize,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Slice { start, len } => write!(f, "slice({},{})", start, len),
+            Step::Center => write!(f, "center"),
+            Step::Normalize => write!(f, "normalize"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryTransform {
+    pub steps: Vec<Step>,
+    /// Mean subtracted by `center`, in the dimensionality at that step.
+    pub mean: Option<Vec<f32>>,
+}
+
+impl QueryTransform {
+    /// Parse a spec. `mean` is required if the spec contains `center`.
+    pub fn parse(spec: &str, mean: Option<Vec<f32>>) -> Result<Self> {
+        let mut steps = Vec::new();
+        for part in spec.split('|').map(str::trim) {
+            let step = match part {
+                "center" => Step::Center,
+                "normalize" => Step::Normalize,
+                _ => {
+                    let args = part
+                        .strip_prefix("slice(")
+                        .and_then(|rest| rest.strip_suffix(')'))
+                        .ok_or_else(|| anyhow!("invalid query transform step '{}'", part))?;
+                    let (start, len) = args
+                        .split_once(',')
+                        .ok_or_else(|| anyhow!("slice takes (start,len), got '{}'", part))?;
+                    let start = start.trim().parse().map_err(|_| anyhow!("invalid slice start in '{}'", part))?;
+                    let len: usize = len.trim().parse().map_err(|_| anyhow!("invalid slice len in '{}'", part))?;
+                    if len == 0 {
+                        return Err(anyhow!("slice len must be positive"));
+                    }
+                    Step::Slice { start, len }
+                }
+            };
+            steps.push(step);
+        }
+        if steps.contains(&Step::Center) && mean.is_none() {
+            return Err(anyhow!("center requires a stored mean"));
+        }
+        Ok(Self { steps, mean })
+    }
+
+    /// Output dimension for an input of `dim`, or an error if a step cannot apply.
+    pub fn output_dim(&self, dim: usize) -> Result<usize> {
+        let mut len = dim;
+        for step in &self.steps {
+            match step {
+                Step::Slice { start, len: slice_len } => {
+                    let end = Self::slice_end(*start, *slice_len)?;
+                    if end > len {
+                        return Err(anyhow!(
+                            "slice({},{}) needs at least {} dimensions, got {}",
+                            start,
+                            slice_len,
+                            end,
+                            len
+                        ));
+                    }
+                    len = *slice_len;
+                }
+                Step::Center => {
+                    let mean_len = self.mean.as_ref().map_or(0, |m| m.len());
+                    if mean_len != len {
+                        return Err(anyhow!("center mean has {} dimensions, query has {}", mean_len, len));
+                    }
+                }
+                Step::Normalize => {}
+            }
+        }
+        Ok(len)
+    }
+
+    /// Query dimension fixed by the first step that depends on one: the end of a
+    /// slice, or the mean's dimension for `center`. `None` when only `normalize` is
+    /// used, which applies to any dimension.
+    pub fn input_dim(&self) -> Result<Option<usize>> {
+        for step in &self.steps {
+            match step {
+                Step::Slice { start, len } => return Self::slice_end(*start, *len).map(Some),
+                Step::Center => return Ok(self.mean.as_ref().map(Vec::len)),
+                Step::Normalize => {}
+            }
+        }
+        Ok(None)
+    }
+
+    fn slice_end(start: usize, len: usize) -> Result<usize> {
+        start
+            .checked_add(len)
+            .ok_or_else(|| anyhow!("slice({},{}) is out of range", start, len))
+    }
+
+    pub fn apply(&self, query: &[f32]) -> Result<Vec<f32>> {
+        self.output_dim(query.len())?;
+        let mut v = query.to_vec();
+        for step in &self.steps {
+            match step {
+                Step::Slice { start, len } => {
+                    v = v[*start..*start + *len].to_vec();
+                }
+                Step::Center => {
+                    let mean = self.mean.as_deref().unwrap_or_default();
+                    v.iter_mut().zip(mean).for_each(|(x, m)| *x -= m);
+                }
+                Step::Normalize => v = distance::normalize(&v),
+            }
+        }
+        Ok(v)
+    }
+
+    /// Metadata encoding of the mean (comma-separated; Rust float formatting round-trips).
+    pub fn mean_to_string(&self) -> Option<String> {
+        self.mean.as_ref().map(|m| {
+            m.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(",")
+        })
+    }
+
+    pub fn parse_mean(s: &str) -> Result<Vec<f32>> {
+        s.split(',')
+            .map(|x| x.trim().parse::<f32>().map_err(|_| anyhow!("invalid mean component '{}'", x)))
+            .collect()
+    }
+}
+
+impl fmt::Display for QueryTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            write!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Move `positive` away from the mean of `negatives` (flattened, `positive.len()`
+/// values each): `positive - weight * mean(negatives)`. This is the Rocchio-style
+/// adjustment behind "more like A, less like B" queries.
+pub fn exclude_negatives(positive: &[f32], negatives: &[f32], weight: f32) -> Result<Vec<f32>> {
+    let dim = positive.len();
+    if dim == 0 || negatives.len() % dim != 0 {
+        return Err(anyhow!(
+            "negative vectors must have the query dimension {} ({} values given)",
+            dim,
+            negatives.len()
+        ));
+    }
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(anyhow!("negative weight must be a non-negative number, got {}", weight));
+    }
+    if negatives.is_empty() || weight == 0.0 {
+        return Ok(positive.to_vec());
+    }
+    let mean = mean_vector(negatives, dim)?;
+    Ok(positive.iter().zip(&mean).map(|(p, m)| p - weight * m).collect())
+}
+
+/// Element-wise mean of one or more `dim`-sized vectors, flattened.
+pub fn mean_vector(vectors: &[f32], dim: usize) -> Result<Vec<f32>> {
+    if dim == 0 || vectors.is_empty() || vectors.len() % dim != 0 {
+        return Err(anyhow!(
+            "expected one or more vectors of dimension {}, got {} values",
+            dim,
+            vectors.len()
+        ));
+    }
+    let count = (vectors.len() / dim) as f32;
+    let mut mean = vec![0.0f32; dim];
+    for vector in vectors.chunks(dim) {
+        for (m, v) in mean.iter_mut().zip(vector) {
+            *m += v;
+        }
+    }
+    mean.iter_mut().for_each(|m| *m /= count);
+    Ok(mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_center_normalize() {
+        let t = QueryTransform::parse("slice(1,2) | center | normalize", Some(vec![1.0, 1.0])).unwrap();
+        assert_eq!(t.output_dim(4).unwrap(), 2);
+        assert!(t.output_dim(2).is_err());
+        assert_eq!(t.input_dim().unwrap(), Some(3));
+        assert_eq!(QueryTransform::parse("normalize", None).unwrap().input_dim().unwrap(), None);
+        let out = t.apply(&[9.0, 4.0, 5.0, 9.0]).unwrap();
+        assert!((out[0] - 0.6).abs() < 1e-6);
+        assert!((out[1] - 0.8).abs() < 1e-6);
+
+        let round_trip = QueryTransform::parse(
+            &t.to_string(),
+            Some(QueryTransform::parse_mean(&t.mean_to_string().unwrap()).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(round_trip, t);
+    }
+
+    #[test]
+    fn test_exclude_negatives() {
+        let q = exclude_negatives(&[1.0, 1.0], &[1.0, 0.0, 0.0, 0.0], 1.0).unwrap();
+        assert_eq!(q, vec![0.5, 1.0]);
+        assert_eq!(exclude_negatives(&[1.0, 1.0], &[], 1.0).unwrap(), vec![1.0, 1.0]);
+        assert!(exclude_negatives(&[1.0, 1.0], &[1.0], 1.0).is_err());
+        assert!(exclude_negatives(&[1.0, 1.0], &[1.0, 0.0], -1.0).is_err());
+        assert_eq!(mean_vector(&[1.0, 2.0, 3.0, 4.0], 2).unwrap(), vec![2.0, 3.0]);
+        assert!(mean_vector(&[], 2).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(QueryTransform::parse("center", None).is_err());
+        assert!(QueryTransform::parse("slice(0)", None).is_err());
+        assert!(QueryTransform::parse("slice(0,0)", None).is_err());
+        assert!(QueryTransform::parse("whiten", None).is_err());
+
+        let overflow = QueryTransform::parse(&format!("slice({},2)", usize::MAX), None).unwrap();
+        assert!(overflow.output_dim(4).is_err());
+        assert!(overflow.input_dim().is_err());
+        assert!(QueryTransform::parse_mean("1.0,x").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! Row-level expiry through an `expires_at` column.
+//!
+//! When enabled, every search adds `expires_at IS NULL OR expires_at > <now>` to its
+//! filter, so expired rows never surface even before they are deleted. The column
+//! may be a timestamp or an Int64 of milliseconds since the Unix epoch.
+
+use anyhow::{anyhow, Result};
+use arrow_schema::{DataType, Schema, TimeUnit};
+
+pub const EXPIRES_AT: &str = "expires_at";
+
+/// Type of the `expires_at` column, if the schema has a usable one.
+pub fn expiry_type(schema: &Schema) -> Result<DataType> {
+    let field = schema
+        .field_with_name(EXPIRES_AT)
+        .map_err(|_| anyhow!("row expiry needs an {} column", EXPIRES_AT))?;
+    match field.data_type() {
+        DataType::Timestamp(_, _) | DataType::Int64 => Ok(field.data_type().clone()),
+        other => Err(anyhow!(
+            "{} must be a timestamp or Int64 epoch milliseconds, got {}",
+            EXPIRES_AT,
+            other
+        )),
+    }
+}
+
+/// Lance SQL predicate keeping rows that have not expired at `now_ms`.
+pub fn live_predicate(expiry_type: &DataType, now_ms: i64) -> String {
+    format!("({} IS NULL OR {})", EXPIRES_AT, compare_ms(EXPIRES_AT, expiry_type, ">", now_ms))
+}
+
+/// Lance SQL comparison `column <op> <ms>` for a timestamp or Int64 epoch-milliseconds
+/// column. Naive timestamps are compared with a literal of the UTC time. Lance SQL
+/// has no zone-qualified literals and would read a naive one in the column's zone,
+/// so timestamps with a zone are compared on their epoch value.
+pub fn compare_ms(column: &str, column_type: &DataType, op: &str, ms: i64) -> String {
+    match column_type {
+        DataType::Timestamp(_, None) => format!("{} {} TIMESTAMP '{}'", column, op, format_timestamp_ms(ms)),
+        DataType::Timestamp(unit, Some(_)) => {
+            let value = match unit {
+                TimeUnit::Second => ms.div_euclid(1000),
+                TimeUnit::Millisecond => ms,
+                TimeUnit::Microsecond => ms.saturating_mul(1000),
+                TimeUnit::Nanosecond => ms.saturating_mul(1_000_000),
+            };
+            format!("CAST({} AS BIGINT) {} {}", column, op, value)
+        }
+        _ => format!("{} {} {}", column, op, ms),
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS.mmm` (UTC) for milliseconds since the Unix epoch.
+fn format_timestamp_ms(ms: i64) -> String {
+    let days = ms.div_euclid(86_400_000);
+    let day_ms = ms.rem_euclid(86_400_000);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        day_ms / 3_600_000,
+        day_ms / 60_000 % 60,
+        day_ms / 1000 % 60,
+        day_ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_schema::Field;
+
+    #[test]
+    fn test_live_predicate() {
+        assert_eq!(format_timestamp_ms(0), "1970-01-01 00:00:00.000");
+        assert_eq!(format_timestamp_ms(951_782_400_123), "2000-02-29 00:00:00.123");
+        assert_eq!(format_timestamp_ms(1_790_000_000_000), "2026-09-21 14:13:20.000");
+
+        let ts = DataType::Timestamp(TimeUnit::Microsecond, None);
+        assert_eq!(
+            live_predicate(&ts, 0),
+            "(expires_at IS NULL OR expires_at > TIMESTAMP '1970-01-01 00:00:00.000')"
+        );
+        assert_eq!(live_predicate(&DataType::Int64, 5), "(expires_at IS NULL OR expires_at > 5)");
+
+        // Zoned timestamps compare on the epoch value in the column's unit
+        let utc = DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+        assert_eq!(live_predicate(&utc, 5), "(expires_at IS NULL OR CAST(expires_at AS BIGINT) > 5000)");
+        let zoned = DataType::Timestamp(TimeUnit::Second, Some("+05:00".into()));
+        assert_eq!(compare_ms("ts", &zoned, "<", 1999), "CAST(ts AS BIGINT) < 1");
+        let zoned = DataType::Timestamp(TimeUnit::Nanosecond, Some("America/New_York".into()));
+        assert_eq!(compare_ms("ts", &zoned, "<", i64::MAX), format!("CAST(ts AS BIGINT) < {}", i64::MAX));
+
+        let schema = Schema::new(vec![Field::new(EXPIRES_AT, DataType::Utf8, true)]);
+        assert!(expiry_type(&schema).is_err());
+        assert!(expiry_type(&Schema::empty()).is_err());
+    }
+}
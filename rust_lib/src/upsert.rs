@@ -0,0 +1,166 @@
+//! Conflict policies for upserts.
+//!
+//! [`LanceIndex::upsert_batch_arrow`](crate::lance_manager::LanceIndex::upsert_batch_arrow)
+//! matches incoming rows to stored ones by key. What happens to an incoming row whose
+//! key is already stored is its `on_conflict` policy:
+//!
+//! - `replace`: the incoming row replaces the stored one (the default).
+//! - `keep_existing`: the stored row stays; only new keys are written.
+//! - `error`: the upsert fails and writes nothing.
+//! - `keep_newest(<column>)`: whichever row has the larger `column` value stays; the
+//!   incoming row wins ties, and NULL is older than any value.
+//!
+//! A batch repeating a key is refused by default (`duplicates=error`). With
+//! `duplicates=collapse` each key keeps one incoming row, chosen deterministically:
+//! the row with the largest `keep_newest` column (the later row on a tie), or under
+//! the other policies the last row in batch order. Only that row is then matched
+//! against storage.
+//!
+//! Options are given in text form, e.g. `on_conflict=keep_newest(updated_at); duplicates=collapse`.
+
+use anyhow::{anyhow, Result};
+use arrow::array::{make_comparator, DynComparator};
+use arrow_array::Array;
+use arrow_schema::SortOptions;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// What to do with an incoming row whose key is already stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OnConflict {
+    #[default]
+    Replace,
+    KeepExisting,
+    Error,
+    /// Keep the row with the larger value of the column.
+    KeepNewest(String),
+}
+
+impl OnConflict {
+    pub fn parse(policy: &str) -> Result<Self> {
+        match policy {
+            "replace" => Ok(OnConflict::Replace),
+            "keep_existing" => Ok(OnConflict::KeepExisting),
+            "error" => Ok(OnConflict::Error),
+            other => {
+                let column = other
+                    .strip_prefix("keep_newest")
+                    .and_then(|rest| rest.trim().strip_prefix('('))
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty())
+                    .ok_or_else(|| anyhow!("invalid on_conflict policy '{}'", other))?;
+                Ok(OnConflict::KeepNewest(column.to_string()))
+            }
+        }
+    }
+
+    /// The column `keep_newest` compares, if that is the policy.
+    pub fn newest_column(&self) -> Option<&str> {
+        match self {
+            OnConflict::KeepNewest(column) => Some(column),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OnConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnConflict::Replace => write!(f, "replace"),
+            OnConflict::KeepExisting => write!(f, "keep_existing"),
+            OnConflict::Error => write!(f, "error"),
+            OnConflict::KeepNewest(column) => write!(f, "keep_newest({})", column),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpsertOptions {
+    pub on_conflict: OnConflict,
+    /// Keep one row per key of a batch that repeats keys, rather than failing.
+    pub collapse_duplicates: bool,
+}
+
+impl UpsertOptions {
+    /// Parse the text form; an empty spec gives the defaults.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for part in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .map(|(name, value)| (name.trim(), value.trim()))
+                .ok_or_else(|| anyhow!("upsert option '{}' must be <name>=<value>", part))?;
+            match name {
+                "on_conflict" => options.on_conflict = OnConflict::parse(value)?,
+                "duplicates" => match value {
+                    "error" => options.collapse_duplicates = false,
+                    "collapse" => options.collapse_duplicates = true,
+                    other => return Err(anyhow!("invalid duplicates mode '{}' (expected error or collapse)", other)),
+                },
+                other => return Err(anyhow!("unknown upsert option '{}'", other)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// For each row, the row that stands for its key: the last row with the key, or with
+/// `newest`, the row with the largest value (the later row on a tie).
+pub fn winners<K: Hash + Eq>(keys: &[K], newest: Option<&dyn Array>) -> Result<Vec<usize>> {
+    let newer = newest.map(|values| comparator(values, values)).transpose()?;
+    let mut winner: HashMap<&K, usize> = HashMap::with_capacity(keys.len());
+    for (row, key) in keys.iter().enumerate() {
+        winner
+            .entry(key)
+            .and_modify(|best| {
+                let later_wins = match &newer {
+                    Some(cmp) => cmp(row, *best).is_ge(),
+                    None => true,
+                };
+                if later_wins {
+                    *best = row;
+                }
+            })
+            .or_insert(row);
+    }
+    Ok(keys.iter().map(|key| winner[key]).collect())
+}
+
+/// Orders rows of `incoming` against rows of `stored` for `keep_newest`, NULL first.
+pub fn comparator(incoming: &dyn Array, stored: &dyn Array) -> Result<DynComparator> {
+    make_comparator(incoming, stored, SortOptions::default())
+        .map_err(|e| anyhow!("keep_newest cannot compare {} values: {}", incoming.data_type(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(UpsertOptions::parse("").unwrap(), UpsertOptions::default());
+        let options = UpsertOptions::parse("on_conflict = keep_newest( updated_at ); duplicates=collapse").unwrap();
+        assert_eq!(options.on_conflict, OnConflict::KeepNewest("updated_at".to_string()));
+        assert!(options.collapse_duplicates);
+        assert_eq!(options.on_conflict.to_string(), "keep_newest(updated_at)");
+        assert_eq!(UpsertOptions::parse("on_conflict=keep_existing").unwrap().on_conflict, OnConflict::KeepExisting);
+        assert!(UpsertOptions::parse("on_conflict=keep_newest()").is_err());
+        assert!(UpsertOptions::parse("on_conflict=newest").is_err());
+        assert!(UpsertOptions::parse("duplicates=first").is_err());
+        assert!(UpsertOptions::parse("collapse").is_err());
+    }
+
+    #[test]
+    fn test_winners() {
+        let keys = ["a", "b", "a", "a"];
+        assert_eq!(winners(&keys, None).unwrap(), vec![3, 1, 3, 3]);
+
+        let versions = Int64Array::from(vec![Some(5), Some(1), Some(7), None]);
+        assert_eq!(winners(&keys, Some(&versions as &dyn Array)).unwrap(), vec![2, 1, 2, 2]);
+        let tied = Int64Array::from(vec![7, 1, 7, 3]);
+        assert_eq!(winners(&keys, Some(&tied as &dyn Array)).unwrap(), vec![2, 1, 2, 2]);
+    }
+}
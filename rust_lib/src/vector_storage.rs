@@ -0,0 +1,199 @@
+//! Element type of stored vectors.
+//!
+//! Vectors are Float32 by default. A table created with `float16` storage keeps its
+//! declared vector columns as Float16, half the bytes on disk and per scan: vectors
+//! come in as f32 and are narrowed on ingest (an element beyond the f16 range of
+//! ±65504 makes its row invalid, like a NaN), and `get_vector`/`get_all_vectors` widen
+//! them back. Searches take f32 queries either way; Lance converts them to the
+//! column's type. Scans return the stored Float16 column.
+//!
+//! `int8` storage quantizes the `vector` column to one byte per element with a
+//! per-table scale and offset (see [`crate::quantize`]); [`narrow`] and [`widen`]
+//! cannot convert it without them.
+//!
+//! The storage is read back from the table schema on open, so it is not recorded in
+//! the table metadata. bfloat16 has no Arrow type Lance indexes and is not offered.
+
+use anyhow::{anyhow, Result};
+use arrow::compute::cast;
+use arrow_array::{Array, FixedSizeListArray, Float16Array, Float32Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VectorStorage {
+    #[default]
+    Float32,
+    Float16,
+    Int8,
+}
+
+impl VectorStorage {
+    /// Parse a storage name; empty gives the default.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "float32" | "f32" | "float" => Ok(VectorStorage::Float32),
+            "float16" | "f16" | "half" => Ok(VectorStorage::Float16),
+            "int8" | "i8" => Ok(VectorStorage::Int8),
+            "bfloat16" | "bf16" => Err(anyhow!("bfloat16 vector storage is not supported; use float16")),
+            other => Err(anyhow!("unknown vector storage '{}' (expected float32, float16 or int8)", other)),
+        }
+    }
+
+    pub fn element_type(&self) -> DataType {
+        match self {
+            VectorStorage::Float32 => DataType::Float32,
+            VectorStorage::Float16 => DataType::Float16,
+            VectorStorage::Int8 => DataType::Int8,
+        }
+    }
+
+    /// The storage of `schema`'s `vector` column; Float32 when it has none.
+    pub fn of_schema(schema: &Schema) -> Self {
+        match schema.field_with_name("vector").map(Field::data_type) {
+            Ok(DataType::FixedSizeList(item, _)) => match item.data_type() {
+                DataType::Float16 => VectorStorage::Float16,
+                DataType::Int8 => VectorStorage::Int8,
+                _ => VectorStorage::Float32,
+            },
+            _ => VectorStorage::Float32,
+        }
+    }
+}
+
+impl fmt::Display for VectorStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VectorStorage::Float32 => "float32",
+            VectorStorage::Float16 => "float16",
+            VectorStorage::Int8 => "int8",
+        })
+    }
+}
+
+/// Narrow Float32 `vectors` to `storage`. Fails on a finite element the storage
+/// cannot hold, and for int8, which needs the table's quantizer.
+pub fn narrow(vectors: FixedSizeListArray, storage: VectorStorage) -> Result<FixedSizeListArray> {
+    match storage {
+        VectorStorage::Float32 => return Ok(vectors),
+        VectorStorage::Float16 => {}
+        VectorStorage::Int8 => return Err(anyhow!("int8 vectors are quantized with the table's scale and offset")),
+    }
+    let item = Arc::new(Field::new("item", storage.element_type(), true));
+    let size = vectors.value_length();
+    let narrowed = cast(&vectors, &DataType::FixedSizeList(item, size))?;
+    let narrowed = narrowed
+        .as_any()
+        .downcast_ref::<FixedSizeListArray>()
+        .ok_or_else(|| anyhow!("narrowed vectors not FixedSizeList"))?
+        .clone();
+    if let (Some(wide), Some(half)) = (
+        vectors.values().as_any().downcast_ref::<Float32Array>(),
+        narrowed.values().as_any().downcast_ref::<Float16Array>(),
+    ) {
+        let overflow = wide
+            .values()
+            .iter()
+            .zip(half.values())
+            .position(|(w, h)| w.is_finite() && !h.is_finite());
+        if let Some(i) = overflow {
+            return Err(anyhow!(
+                "vector element {} of row {} is outside the {} range",
+                wide.value(i),
+                i / size as usize,
+                storage
+            ));
+        }
+    }
+    Ok(narrowed)
+}
+
+/// `vectors` with Float32 elements, widened if stored narrower. Int8 vectors need
+/// the table's quantizer and fail.
+pub fn widen(vectors: &FixedSizeListArray) -> Result<Cow<'_, FixedSizeListArray>> {
+    match vectors.value_type() {
+        DataType::Float32 => return Ok(Cow::Borrowed(vectors)),
+        DataType::Int8 => return Err(anyhow!("int8 vectors are dequantized with the table's scale and offset")),
+        _ => {}
+    }
+    let item = Arc::new(Field::new("item", DataType::Float32, true));
+    let widened = cast(vectors, &DataType::FixedSizeList(item, vectors.value_length()))?;
+    widened
+        .as_any()
+        .downcast_ref::<FixedSizeListArray>()
+        .map(|list| Cow::Owned(list.clone()))
+        .ok_or_else(|| anyhow!("widened vectors not FixedSizeList"))
+}
+
+/// The schema appended Arrow columns are converted to: `schema` with int8 vectors
+/// as Float32, which are quantized afterwards.
+pub fn ingest_schema(schema: &SchemaRef) -> SchemaRef {
+    if VectorStorage::of_schema(schema) != VectorStorage::Int8 {
+        return schema.clone();
+    }
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| match field.data_type() {
+            DataType::FixedSizeList(_, size) if field.name() == "vector" => {
+                let item = Arc::new(Field::new("item", DataType::Float32, true));
+                field.as_ref().clone().with_data_type(DataType::FixedSizeList(item, *size))
+            }
+            _ => field.as_ref().clone(),
+        })
+        .collect();
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors(values: Vec<f32>) -> FixedSizeListArray {
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        FixedSizeListArray::new(item, 2, Arc::new(Float32Array::from(values)), None)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(VectorStorage::parse("").unwrap(), VectorStorage::Float32);
+        assert_eq!(VectorStorage::parse(" FLOAT16 ").unwrap(), VectorStorage::Float16);
+        assert_eq!(VectorStorage::Float16.to_string(), "float16");
+        assert_eq!(VectorStorage::parse("int8").unwrap(), VectorStorage::Int8);
+        assert!(VectorStorage::parse("bfloat16").unwrap_err().to_string().contains("not supported"));
+        assert!(VectorStorage::parse("int4").is_err());
+    }
+
+    #[test]
+    fn test_narrow_and_widen() {
+        let half = narrow(vectors(vec![1.0, 0.333, -2.5, 65504.0]), VectorStorage::Float16).unwrap();
+        assert_eq!(half.value_type(), DataType::Float16);
+        let wide = widen(&half).unwrap();
+        let values = wide.values().as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(values.value(0), 1.0);
+        assert!((values.value(1) - 0.333).abs() < 1e-3);
+        assert_eq!(values.value(3), 65504.0);
+        assert!(matches!(widen(&vectors(vec![1.0, 2.0])).unwrap(), Cow::Borrowed(_)));
+
+        let err = narrow(vectors(vec![1.0, 2.0, 0.0, 1.0e5]), VectorStorage::Float16).unwrap_err();
+        assert!(err.to_string().contains("row 1"), "{}", err);
+        assert!(narrow(vectors(vec![1.0, 2.0]), VectorStorage::Int8).is_err());
+    }
+
+    #[test]
+    fn test_ingest_schema() {
+        let item = Arc::new(Field::new("item", DataType::Int8, true));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("label", DataType::Int64, false),
+            Field::new("vector", DataType::FixedSizeList(item, 4), false),
+        ]));
+        let ingest = ingest_schema(&schema);
+        assert_eq!(VectorStorage::of_schema(&schema), VectorStorage::Int8);
+        assert_eq!(VectorStorage::of_schema(&ingest), VectorStorage::Float32);
+        assert!(!ingest.field(1).is_nullable());
+        let float = ingest_schema(&ingest);
+        assert!(Arc::ptr_eq(&float, &ingest));
+    }
+}
@@ -0,0 +1,112 @@
+//! Read-your-writes across handles on the same table within one process.
+//!
+//! A LanceDB table handle keeps reading the dataset version it last loaded, so a
+//! write through one handle (e.g. an ingest handle) is invisible to a sibling handle
+//! (e.g. a search handle) until it is reopened. Handles on the same (database, table)
+//! share a [`TableWatch`]: writers bump its generation after each commit, and readers
+//! that see a newer generation than they last synced to check out the latest version
+//! before their next operation. Handles in other processes are not covered.
+//!
+//! Appends buffered by a handle that is dropped with its session open are flushed
+//! where no caller can see the result. A failed flush is recorded per table here,
+//! outliving the handle, and reported by the next write through any handle on it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+
+static WATCHES: LazyLock<Mutex<HashMap<(String, String), Weak<TableWatch>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static LOST_APPENDS: LazyLock<Mutex<HashMap<(String, String), String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Commit counter shared by every handle on one table.
+#[derive(Debug, Default)]
+pub struct TableWatch {
+    generation: AtomicU64,
+}
+
+impl TableWatch {
+    /// Number of commits made through any handle on the table.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Record a commit. Call after the write is durable, so a sibling that sees the
+    /// new generation also sees the write.
+    pub fn bump(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Shared watch for `table_name` in the database at `db_path`.
+///
+/// Local paths are canonicalized so `./db` and `/abs/db` share a watch; other URIs
+/// are compared as given.
+pub fn watch(db_path: &str, table_name: &str) -> Arc<TableWatch> {
+    let key = table_key(db_path, table_name);
+    let mut watches = WATCHES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = watches.get(&key).and_then(Weak::upgrade) {
+        return existing;
+    }
+    watches.retain(|_, w| w.strong_count() > 0);
+    let created = Arc::new(TableWatch::default());
+    watches.insert(key, Arc::downgrade(&created));
+    created
+}
+
+/// Record that buffered appends to `table_name` were lost, described by `message`.
+/// A later record replaces an unreported one.
+pub fn record_lost_appends(db_path: &str, table_name: &str, message: String) {
+    let mut lost = LOST_APPENDS.lock().unwrap_or_else(|e| e.into_inner());
+    lost.insert(table_key(db_path, table_name), message);
+}
+
+/// The unreported loss recorded for `table_name`, if any. Each loss is returned once.
+pub fn take_lost_appends(db_path: &str, table_name: &str) -> Option<String> {
+    let mut lost = LOST_APPENDS.lock().unwrap_or_else(|e| e.into_inner());
+    lost.remove(&table_key(db_path, table_name))
+}
+
+/// Local paths are canonicalized; other URIs are compared as given.
+fn table_key(db_path: &str, table_name: &str) -> (String, String) {
+    let db = std::fs::canonicalize(db_path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| db_path.to_string());
+    (db, table_name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_is_shared_per_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let a = watch(path, "vectors");
+        let b = watch(&format!("{}/.", path), "vectors");
+        let other = watch(path, "other");
+
+        a.bump();
+        assert_eq!(b.generation(), 1);
+        assert_eq!(other.generation(), 0);
+
+        // Dropped watches are not resurrected with a stale count
+        drop((a, b));
+        assert_eq!(watch(path, "vectors").generation(), 0);
+    }
+
+    #[test]
+    fn test_lost_appends_are_reported_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        assert_eq!(take_lost_appends(path, "vectors"), None);
+
+        record_lost_appends(path, "vectors", "2 rows lost".to_string());
+        assert_eq!(take_lost_appends(path, "other"), None);
+        assert_eq!(take_lost_appends(&format!("{}/.", path), "vectors").as_deref(), Some("2 rows lost"));
+        assert_eq!(take_lost_appends(path, "vectors"), None);
+    }
+}
@@ -49,12 +49,180 @@ public:
 	string GetConstraintViolationMessage(VerifyExistenceType verify_type, idx_t failed_index,
 	                                     DataChunk &input) override;
 
-	// ANN search
-	vector<pair<row_t, float>> Search(const float *query, int32_t dimension, int32_t k);
+	// ANN search. predicate is a Lance SQL filter pushed down by the optimizer (empty for none).
+	// model, if set, must match the embedding model recorded for the table.
+	// weights, if non-empty, weighs each stored dimension when ranking hits (0 masks it out); weight_query
+	// also applies them to the query before the ANN search. ef > 0 widens (or narrows) the HNSW candidate list
+	// for this query only; 0 keeps Lance's default.
+	vector<pair<row_t, float>> Search(const float *query, int32_t dimension, int32_t k,
+	                                  const string &predicate = string(), const string &model = string(),
+	                                  const vector<float> &weights = {}, bool weight_query = false, int32_t ef = 0);
+	// Search that also reports the diversity of its hits: their mean pairwise distance and, with column set,
+	// the number of distinct values of that column among them.
+	vector<pair<row_t, float>> SearchDiversity(const float *query, int32_t dimension, int32_t k,
+	                                           const string &predicate, const string &column,
+	                                           LanceSearchDiversity &diversity, const string &model = string(),
+	                                           int32_t ef = 0);
+	// Search through a handle that only sees rows matching scope (a Lance SQL predicate, e.g. "tenant_id = 42").
+	// The scope is enforced by the Rust layer, so no predicate the caller adds can reach other rows.
+	vector<pair<row_t, float>> SearchScoped(const float *query, int32_t dimension, int32_t k, const string &scope,
+	                                        const string &model = string());
+	// Search at a read consistency: latest, session or eventual.
+	vector<pair<row_t, float>> SearchConsistent(const float *query, int32_t dimension, int32_t k,
+	                                            const string &consistency, const string &model = string());
+	// Many queries (row-major, dimension values each) in one call. Hits come grouped by query, nearest first.
+	struct BatchHit {
+		int32_t query_idx;
+		row_t row_id;
+		float distance;
+	};
+	vector<BatchHit> SearchBatch(const float *queries, int32_t dimension, int32_t num_queries, int32_t k);
+	// Up to k hits with min_distance <= distance < max_distance; NaN leaves a side open.
+	vector<pair<row_t, float>> SearchRange(const float *query, int32_t dimension, int32_t k, float min_distance,
+	                                       float max_distance, const string &model = string());
+	// "More like query, less like negatives": negatives holds whole vectors of the query dimension, flattened.
+	vector<pair<row_t, float>> SearchWithNegatives(const float *query, int32_t dimension, int32_t k,
+	                                               const vector<float> &negatives, float weight,
+	                                               const string &model = string());
+	// Best hit per distinct value of dedup_column (a column stored in the index), up to k hits.
+	vector<pair<row_t, float>> SearchDedup(const float *query, int32_t dimension, int32_t k,
+	                                       const string &dedup_column, const string &model = string());
+	// Parent documents of the nearest chunks, best chunk per parent, up to k parents. An empty
+	// parent_column uses the parent column of the chunking stage.
+	struct ParentHit {
+		int64_t parent;
+		row_t row_id;
+		float distance;
+	};
+	vector<ParentHit> SearchParents(const float *query, int32_t dimension, int32_t k, const string &parent_column);
+	// Rows similar to the given rows as a group (centroid query), optionally pushed away from negative rows.
+	// Throws if a row is not in the index.
+	vector<pair<row_t, float>> SearchLikeRows(const vector<row_t> &row_ids, const vector<row_t> &negative_row_ids,
+	                                          float weight, int32_t k);
+	// Approximate search probing a fraction of the partitions. Results carry estimated full-table ranks.
+	struct SampledHit {
+		row_t row_id;
+		float distance;
+		double estimated_rank;
+	};
+	vector<SampledHit> SearchSampled(const float *query, int32_t dimension, int32_t k, double fraction,
+	                                 double &sampled_fraction);
+	// Multi-hop discovery: initial hits plus their nearest neighbors over `hops` rounds, scores decayed per hop
+	struct ExpandedHit {
+		row_t row_id;
+		float distance;
+		int32_t hop;
+		double score;
+	};
+	vector<ExpandedHit> SearchExpand(const float *query, int32_t dimension, int32_t k, int32_t hops, int32_t per_hop,
+	                                 double decay);
+	// Exact ranking of the given rows only. Rows not in the index are ignored.
+	vector<pair<row_t, float>> SearchWithin(const float *query, int32_t dimension, int32_t k,
+	                                        const vector<row_t> &row_ids);
+	// Check that the store is reachable and the table manifest readable
+	LancePingResult Ping();
+	// Warm the local caches for the given rows (all rows when empty) matching filter. Returns rows read.
+	int64_t Prefetch(const vector<row_t> &row_ids, const string &filter);
+	// Search the PCA-reduced column, rescoring k * rescore_factor candidates on the full vectors
+	vector<pair<row_t, float>> SearchPca(const float *query, int32_t dimension, int32_t k, int32_t rescore_factor);
+	// Vector search fused with a full-text search of text (LANCE_FUSION_* strategy); scores are higher-is-better
+	vector<pair<row_t, float>> HybridSearch(const float *query, int32_t dimension, int32_t k, const string &text,
+	                                        const string &text_column, int32_t fusion, float rrf_k, float vector_weight);
+	// Build a full-text index on a string column stored in the table, for HybridSearch
+	void CreateFtsIndex(const string &column);
+	// Build a scalar index on a metadata column stored in the table, so filters on it skip full scans
+	void CreateScalarIndex(const string &column, LanceScalarIndexType index_type);
+	// k-NN graph over a sample of the rows, as (src, dst) row id edges, for graph/visualization tooling
+	vector<LanceGraphEdge> KnnGraph(int32_t k, int64_t sample);
+	// Lance address of every row, with the DuckDB row id its label maps to (-1 when unmapped)
+	struct RowAddress {
+		row_t row_id;
+		LanceRowAddress address;
+	};
+	vector<RowAddress> RowAddresses();
+	//! Returns the fraction of variance kept. index_type < 0 builds no index on the reduced column.
+	double TrainPca(int32_t target_dims, int64_t sample, int32_t index_type);
+	// Rotate stored vectors and queries so PQ sub-vectors carry balanced variance
+	void TrainRotation(int32_t num_sub_vectors, int64_t sample);
 
 	// Build ANN index on the Lance dataset
-	void CreateAnnIndex(int32_t num_partitions, int32_t num_sub_vectors);
-	void CreateHnswIndex(int32_t m, int32_t ef_construction);
+	//! A non-empty metric builds the index with that distance type instead of the index's own.
+	void CreateAnnIndex(int32_t num_partitions, int32_t num_sub_vectors, const string &metric = string());
+	void CreateHnswIndex(int32_t m, int32_t ef_construction, const string &metric = string());
+	void CreateIvfFlatIndex(int32_t num_partitions, int32_t sample_rate, const string &metric = string());
+	//! Rebuild the vector index in the background, swapping it in atomically when done.
+	//! With staging, the index is built on the staging table instead (see CreateStaging).
+	void RebuildIndex(int32_t index_type, int32_t num_partitions, int32_t num_sub_vectors, int32_t m,
+	                  int32_t ef_construction, int32_t sample_rate, const string &metric, bool wait,
+	                  bool staging = false);
+	LanceRebuildStatus GetRebuildStatus(bool staging = false) const;
+
+	//! Copy all rows into a hidden staging table that can be re-indexed while this one serves searches.
+	void CreateStaging();
+	//! Swap the staging table in as the live table. Returns the tag the old version was kept under.
+	//! Throws if rows changed since CreateStaging.
+	string PromoteStaging();
+	//! Rename the Lance table backing this index and reopen it under the new name.
+	void RenameLanceTable(const string &new_name);
+	void SetIndexBuildLimits(int64_t max_memory_bytes);
+	// Rows per search result batch read from Lance; 0 restores the default. Not persisted.
+	void SetReadBatchSize(int32_t rows);
+	// Budget for returning k hits under filters. Not persisted.
+	LanceHitBudget GetHitBudget() const;
+	void SetHitBudget(const LanceHitBudget &budget);
+	// What merges into this index do about the rows they append unindexed (LANCE_MERGE_INDEXING_*). Not persisted.
+	void SetMergeIndexing(int32_t mode);
+	LanceIndexStaleness GetIndexStaleness() const;
+	// Rebuild the recorded max label from the rows; returns it (-1 when none was assigned).
+	int64_t RepairLabelWatermark();
+	// Give the rows of old_labels[i] the label new_labels[i], in one Lance commit. Returns the rows remapped.
+	idx_t RemapLabels(ClientContext &context, const vector<int64_t> &old_labels, const vector<int64_t> &new_labels);
+	// Delete the Lance rows whose key_column value is not among keys, in one Lance commit. Returns the rows deleted.
+	idx_t RetainOnly(ClientContext &context, const string &key_column, const vector<Value> &keys, bool allow_empty);
+	// How the configured refine_factor resolves for a search of k rows.
+	LanceRefinePlan GetRefinePlan(int32_t k) const;
+
+	void SetPipeline(const string &spec);
+	// Metric for exact distances (search_within, rescoring); empty restores the index metric. Not persisted.
+	void SetRescoreMetric(const string &metric);
+	void SetQueryTransform(const string &spec, const vector<float> &mean);
+	void SetQuota(const string &spec);
+	void SetColdStorage(const string &spec);
+	void SetChunking(const string &spec);
+	void SetConstraints(const string &spec);
+	void SetOnInvalid(const string &mode);
+	void SetColumnMatching(const string &mode);
+	vector<LanceReject> GetRejects();
+
+	// Search hit tracking and rows idle for at least idle_ms, as (row_id, cold row) pairs
+	void SetAccessTracking(bool enabled);
+	vector<pair<row_t, LanceColdRow>> GetColdRows(int64_t idle_ms);
+
+	// Log searches under a tag and replay them to measure result drift
+	void SetQueryLog(const string &spec);
+	vector<LanceQueryDrift> ReplayQueries(const string &tag);
+
+	// Hide rows past their expires_at column from searches
+	void SetRowTtl(bool enabled);
+	// Refuse writes while another process holds the table's writer lease
+	void SetWriterLease(int64_t ttl_secs);
+	// Withhold columns (e.g. raw PII text) from unprivileged scans and stats
+	void SetSensitiveColumns(const vector<string> &columns);
+
+	// Centroid drift against baselines tagged on the Lance table
+	void TagDriftBaseline(const string &tag);
+	std::vector<LanceDriftMetric> GetDriftReport(const string &tag) const;
+	// Optimize, compact, prune and verify in one call, per a declarative plan
+	std::vector<LanceMaintenanceStep> RunMaintenance(const string &plan);
+	// Bounded-memory group counts over a column, for exploring the data without exporting it
+	LanceApproxStats GetApproxStats(const string &column, const vector<float> &query, double sample_fraction,
+	                                int32_t max_groups) const;
+
+	// Physically sort the Lance dataset by a column so range filters on it prune fragments
+	void ClusterBy(const string &column, int64_t rows_per_fragment);
+	LancePruningStats GetPruningStats(const string &predicate) const {
+		return rust_handle_ ? LanceDetachedPruningStats(rust_handle_, predicate) : LancePruningStats();
+	}
 
 	int32_t GetDimension() const {
 		return dimension_;
@@ -68,6 +236,20 @@ public:
 	bool HasPendingDeletes() const {
 		return has_pending_deletes_;
 	}
+	std::vector<LanceDiskUsageEntry> GetDiskUsage(bool include_columns) const {
+		return rust_handle_ ? LanceDetachedDiskUsage(rust_handle_, include_columns) : std::vector<LanceDiskUsageEntry>();
+	}
+	// Back the table up into dest, copying only what came after since_version (< 0 for everything)
+	LanceBackupResult Backup(const string &dest, int64_t since_version) const;
+	// Replication: export the current version's manifest; importing one makes this index a read-only replica
+	LanceManifestBlob ExportManifest() const;
+	LanceLabelBitmap LabelBitmap() const;
+	int64_t ImportManifest(const string &blob);
+	// Storage-format versions and whether this build can write the table
+	LanceFormatInfo FormatInfo() const;
+	LanceColumnStats GetColumnStats(const string &column) const {
+		return rust_handle_ ? LanceDetachedColumnStats(rust_handle_, column) : LanceColumnStats {Value(), Value(), 0, 0};
+	}
 
 	friend class PhysicalCreateLanceIndex;
 
@@ -78,6 +260,9 @@ private:
 
 	// Rust Lance handle
 	LanceHandle rust_handle_ = nullptr;
+	// Staging table handle (CreateStaging), and whether rows changed since it was copied
+	LanceHandle staging_handle_ = nullptr;
+	bool staging_stale_ = false;
 
 	// Cached Lance dataset path (generated once, reused)
 	string lance_path_;
@@ -88,6 +273,9 @@ private:
 	// Parameters
 	int32_t dimension_ = 0;
 	string metric_ = "l2";
+	string model_;   // embedding model id (WITH (model = '...')), empty if undeclared
+	string storage_; // vector element type (WITH (storage = 'float16')), empty for float32
+	vector<float> int8_range_; // [min, max] of int8 storage (WITH (int8_range = [-1, 1])), empty to fit
 	int32_t nprobes_ = 20;
 	int32_t refine_factor_ = 1;
 
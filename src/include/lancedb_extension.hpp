@@ -14,7 +14,67 @@ public:
 void RegisterLanceSearchFunction(ExtensionLoader &loader);
 void RegisterLanceCreateAnnIndexFunction(ExtensionLoader &loader);
 void RegisterLanceCreateHnswIndexFunction(ExtensionLoader &loader);
+void RegisterLanceCreateIvfFlatIndexFunction(ExtensionLoader &loader);
+void RegisterLanceCreateFtsIndexFunction(ExtensionLoader &loader);
+void RegisterLanceCreateScalarIndexFunction(ExtensionLoader &loader);
+void RegisterLanceRebuildIndexFunction(ExtensionLoader &loader);
+void RegisterLanceRebuildStatusFunction(ExtensionLoader &loader);
+void RegisterLanceCreateStagingFunction(ExtensionLoader &loader);
+void RegisterLancePromoteStagingFunction(ExtensionLoader &loader);
+void RegisterLanceRenameTableFunction(ExtensionLoader &loader);
+void RegisterLanceSetIndexBuildLimitsFunction(ExtensionLoader &loader);
+void RegisterLanceSetReadBatchSizeFunction(ExtensionLoader &loader);
+void RegisterLanceRefinePlanFunction(ExtensionLoader &loader);
+void RegisterLanceSetHitBudgetFunction(ExtensionLoader &loader);
+void RegisterLanceSetMergeIndexingFunction(ExtensionLoader &loader);
+void RegisterLanceIndexStalenessFunction(ExtensionLoader &loader);
+void RegisterLanceRepairLabelWatermarkFunction(ExtensionLoader &loader);
+void RegisterLanceRemapLabelsFunction(ExtensionLoader &loader);
+void RegisterLanceRetainOnlyFunction(ExtensionLoader &loader);
+void RegisterLancePrefetchFunction(ExtensionLoader &loader);
+void RegisterLancePingFunction(ExtensionLoader &loader);
+void RegisterLanceVectorMathFunctions(ExtensionLoader &loader);
+void RegisterLanceRuntimeConfigFunction(ExtensionLoader &loader);
+void RegisterLanceReadLimitsFunction(ExtensionLoader &loader);
+void RegisterLanceDiskCacheFunction(ExtensionLoader &loader);
+void RegisterLanceSetDefaultStorageOptionsFunction(ExtensionLoader &loader);
+void RegisterLanceHandlesFunction(ExtensionLoader &loader);
+void RegisterLanceInspectFunction(ExtensionLoader &loader);
+void RegisterLanceClusterByFunction(ExtensionLoader &loader);
+void RegisterLanceSetPipelineFunction(ExtensionLoader &loader);
+void RegisterLanceSetRescoreMetricFunction(ExtensionLoader &loader);
+void RegisterLanceSetQueryTransformFunction(ExtensionLoader &loader);
+void RegisterLanceSetQuotaFunction(ExtensionLoader &loader);
+void RegisterLanceSetColdStorageFunction(ExtensionLoader &loader);
+void RegisterLanceSetChunkingFunction(ExtensionLoader &loader);
+void RegisterLanceSetConstraintsFunction(ExtensionLoader &loader);
+void RegisterLanceSetOnInvalidFunction(ExtensionLoader &loader);
+void RegisterLanceSetColumnMatchingFunction(ExtensionLoader &loader);
+void RegisterLanceRejectsFunction(ExtensionLoader &loader);
+void RegisterLanceSetRetentionFunction(ExtensionLoader &loader);
+void RegisterLanceSetAccessTrackingFunction(ExtensionLoader &loader);
+void RegisterLanceSetRowTtlFunction(ExtensionLoader &loader);
+void RegisterLanceSetSensitiveColumnsFunction(ExtensionLoader &loader);
+void RegisterLanceSetWriterLeaseFunction(ExtensionLoader &loader);
+void RegisterLanceTrainPcaFunction(ExtensionLoader &loader);
+void RegisterLanceBackupFunction(ExtensionLoader &loader);
+void RegisterLanceLabelBitmapFunction(ExtensionLoader &loader);
+void RegisterLanceLabelBitmapOps(ExtensionLoader &loader);
+void RegisterLanceExportManifestFunction(ExtensionLoader &loader);
+void RegisterLanceFormatInfoFunction(ExtensionLoader &loader);
+void RegisterLanceImportManifestFunction(ExtensionLoader &loader);
+void RegisterLanceTrainOpqFunction(ExtensionLoader &loader);
+void RegisterLanceColdRowsFunction(ExtensionLoader &loader);
+void RegisterLanceSetQueryLogFunction(ExtensionLoader &loader);
+void RegisterLanceReplayQueriesFunction(ExtensionLoader &loader);
+void RegisterLanceTagDriftBaselineFunction(ExtensionLoader &loader);
+void RegisterLanceDriftReportFunction(ExtensionLoader &loader);
+void RegisterLanceRunMaintenanceFunction(ExtensionLoader &loader);
+void RegisterLanceApproxStatsFunction(ExtensionLoader &loader);
+void RegisterLanceKnnGraphFunction(ExtensionLoader &loader);
+void RegisterLanceRowAddressesFunction(ExtensionLoader &loader);
 void RegisterLanceInfoFunction(ExtensionLoader &loader);
+void RegisterLanceDiskUsageFunction(ExtensionLoader &loader);
 void RegisterLanceOptimizer(DatabaseInstance &db);
 
 } // namespace duckdb
@@ -1,6 +1,9 @@
 #pragma once
 
+#include "duckdb/common/types/value.hpp"
+
 #include <cstdint>
+#include <functional>
 #include <string>
 #include <vector>
 
@@ -8,20 +11,59 @@ namespace duckdb {
 
 typedef void *LanceHandle;
 
-// Create a Lance dataset at db_path. table_name identifies the Lance table within the dataset.
+// Create a Lance dataset at db_path. table_name identifies the Lance table within the dataset. storage is the
+// element type vectors are stored with: "float32" (empty) or "float16", which halves their size; vectors are
+// still passed and read back as floats.
 LanceHandle LanceCreateDetached(const std::string &db_path, int32_t dimension, const std::string &metric,
-                                const std::string &table_name);
-// Create from Arrow schema (multi-column, zero-copy). arrow_schema is an ArrowSchema*.
+                                const std::string &table_name, const std::string &storage = std::string());
+// Create from Arrow schema (multi-column, zero-copy). arrow_schema is an ArrowSchema*. vector_columns
+// (comma-separated) declares the FixedSizeList columns holding vectors, "vector" first; empty declares just
+// "vector". Declared columns need float elements and a sane dimension; a schema without a "vector" column throws
+// rather than one of its list columns being guessed. Declared columns are stored as storage (see
+// LanceCreateDetached).
 LanceHandle LanceCreateDetachedFromArrow(const std::string &db_path, void *arrow_schema, const std::string &metric,
-                                         const std::string &table_name);
-// Open existing Lance dataset, deriving schema from the table.
-LanceHandle LanceOpenDetached(const std::string &db_path, const std::string &table_name, const std::string &metric);
+                                         const std::string &table_name,
+                                         const std::string &vector_columns = std::string(),
+                                         const std::string &storage = std::string());
+// Open existing Lance dataset, deriving schema from the table. A non-empty scope (e.g. "tenant_id = 42") is
+// AND-ed by Rust into every search, scan, count and delete on the handle and cannot be lifted.
+LanceHandle LanceOpenDetached(const std::string &db_path, const std::string &table_name, const std::string &metric,
+                              const std::string &scope = std::string());
 void LanceFreeDetached(LanceHandle handle);
+// What an existing table offers to the index layer, without opening it as an index. kind is "label" (the key
+// column), "vector" (a FixedSizeList column; data_type is its element type), "index" (data_type is the index type,
+// detail its columns) or "problem" (detail says why LanceOpenDetached would fail, e.g. a missing, non-Int64 or
+// NULL-containing label). nullable and dimension are NULL where they do not apply.
+struct LanceCapability {
+	std::string kind;
+	std::string name;
+	std::string data_type;
+	Value nullable;
+	Value dimension;
+	std::string detail;
+};
+std::vector<LanceCapability> LanceInspectTable(const std::string &db_path, const std::string &table_name);
+// Rename a table (and its access sidecar) in a local Lance database. Free handles on it first.
+void LanceRenameTable(const std::string &db_path, const std::string &old_name, const std::string &new_name);
+// Every handle open in this process, oldest first. A handle listed long after its last operation was
+// probably leaked.
+struct LanceHandleInfo {
+	uint64_t id;
+	std::string path;
+	std::string table_name;
+	bool read_only;
+	int64_t buffered_rows;
+	int64_t opened_ms;
+	int64_t last_operation_ms;
+};
+std::vector<LanceHandleInfo> LanceListHandles();
 
 // Check if index has extra columns beyond label + vector.
 bool LanceDetachedHasExtraColumns(LanceHandle handle);
 // Get dimension from the Rust handle.
 int32_t LanceDetachedDimension(LanceHandle handle);
+// Whether a query of dim dimensions is searchable once the table's query transform is applied.
+bool LanceDetachedAcceptsQueryDim(LanceHandle handle, int32_t dim);
 
 // Add single vector. Returns label.
 int64_t LanceDetachedAdd(LanceHandle handle, const float *vector, int32_t dimension);
@@ -30,25 +72,384 @@ int64_t LanceDetachedAdd(LanceHandle handle, const float *vector, int32_t dimens
 int32_t LanceDetachedAddBatch(LanceHandle handle, const float *vectors, int32_t num, int32_t dim,
                               int64_t *out_labels);
 
+// As LanceDetachedAddBatch, but hands the vectors to Lance without a copy; they are freed once Lance is done.
+int32_t LanceDetachedAddBatchOwned(LanceHandle handle, std::vector<float> &&vectors, int32_t num, int32_t dim,
+                                   int64_t *out_labels);
+
+// Buffer appends on the handle so consecutive small ones are written as one Lance append (one version).
+// Buffered rows become visible, and quota rejections are thrown, when the session ends.
+void LanceDetachedBeginAppendSession(LanceHandle handle);
+void LanceDetachedEndAppendSession(LanceHandle handle);
+
 // Add batch via Arrow C Data Interface (multi-column). Returns count. Fills out_labels.
 // Takes ownership of arrow_array (sets release to null); caller must release arrow_schema.
-int32_t LanceDetachedAddBatchArrow(LanceHandle handle, void *arrow_schema, void *arrow_array, int64_t *out_labels);
+// model, if not nullptr, must match the embedding model recorded for the table.
+// token, if not nullptr, is an idempotency token: a retry with the same token adds nothing and returns
+// the labels of the first call. Tokens are remembered for 24 hours. A retry of a call whose outcome was never
+// recorded (it crashed, or its token could not be written) throws instead of adding the rows again.
+// Rows dropped by validation or constraints get label -1; out_rejected, if not nullptr, receives their count.
+// out_status, if not nullptr, selects partial-failure semantics: rows failing validation or a constraint never
+// throw, and out_status receives one LANCE_ROW_* code per row.
+constexpr uint8_t LANCE_ROW_OK = 0;
+// Added by an earlier call with the same token; out_labels holds the label assigned then.
+constexpr uint8_t LANCE_ROW_DUPLICATE = 1;
+// Dropped by validation or a constraint; its label is -1.
+constexpr uint8_t LANCE_ROW_INVALID = 2;
+int32_t LanceDetachedAddBatchArrow(LanceHandle handle, void *arrow_schema, void *arrow_array, int64_t *out_labels,
+                                   const char *model = nullptr, const char *token = nullptr,
+                                   int64_t *out_rejected = nullptr, uint8_t *out_status = nullptr);
+
+// Parent-document search: the k best distinct values of parent_column (nullptr: the chunking stage's
+// parent column), each with its nearest chunk. A NULL parent counts as the row's own label.
+int32_t LanceDetachedSearchParents(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                   const char *parent_column, int32_t nprobes, int32_t refine_factor,
+                                   int64_t *out_parents, int64_t *out_labels, float *out_distances);
+
+// Search with the query moved away from the mean of negative_count negatives (dim floats each, flattened):
+// query - weight * mean(negatives). Other arguments as LanceDetachedSearch.
+int32_t LanceDetachedSearchWithNegatives(LanceHandle handle, const float *query, int32_t dim, const float *negatives,
+                                         int32_t negative_count, float weight, int32_t k, int32_t nprobes,
+                                         int32_t refine_factor, const char *predicate, int64_t *out_labels,
+                                         float *out_distances, const char *model = nullptr);
+
+// Search keeping only the best hit per distinct value of dedup_column (NULL counts as one value),
+// over-fetching internally so up to k distinct values are returned.
+int32_t LanceDetachedSearchDedup(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                 const std::string &dedup_column, int32_t nprobes, int32_t refine_factor,
+                                 const char *predicate, int64_t *out_labels, float *out_distances,
+                                 const char *model = nullptr);
+
+// Search with the centroid of the stored vectors of label_count labels, moved away from the centroid
+// of negative_count negative labels by weight. The labels themselves are excluded from the results.
+int32_t LanceDetachedSearchLikeLabels(LanceHandle handle, const int64_t *labels, int32_t label_count,
+                                      const int64_t *negative_labels, int32_t negative_count, float weight, int32_t k,
+                                      int32_t nprobes, int32_t refine_factor, int64_t *out_labels,
+                                      float *out_distances);
+
+// Approximate search probing only a fraction of the nprobes partitions a full search would, nearest first.
+// out_estimated_ranks receives each hit's estimated rank in the full table and out_fraction the fraction of
+// partitions actually probed (1 without a vector index, when every row is searched).
+int32_t LanceDetachedSearchSampled(LanceHandle handle, const float *query, int32_t dim, int32_t k, double fraction,
+                                   int32_t nprobes, int32_t refine_factor, int64_t *out_labels, float *out_distances,
+                                   double *out_estimated_ranks, double &out_fraction);
+
+// Multi-hop search: the k nearest, then up to `hops` rounds keeping the k best new neighbors of the
+// previous round (per_hop searched around each), ranked by decay^hop * exp(-distance).
+// out_* hold at least k * (hops + 1) entries.
+int32_t LanceDetachedSearchExpand(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t hops,
+                                  int32_t per_hop, double decay, int32_t nprobes, int32_t refine_factor,
+                                  int64_t *out_labels, float *out_distances, int32_t *out_hops, double *out_scores);
+
+// Exact k-NN ranking restricted to label_count labels (an allow-list). out_* hold at least k entries.
+int32_t LanceDetachedSearchWithin(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                  const int64_t *labels, int32_t label_count, int64_t *out_labels,
+                                  float *out_distances);
+
+// Read the rows among label_count labels (all rows when 0) matching predicate (empty for none) so their fragments
+// are in the local disk cache and OS page cache before heavy queries. Returns the number of rows read.
+int64_t LanceDetachedPrefetch(LanceHandle handle, const int64_t *labels, int32_t label_count,
+                              const std::string &predicate);
+
+// k-NN in the PCA-reduced space, rescored on the full vectors. out_* hold at least k entries.
+int32_t LanceDetachedSearchPca(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                               int32_t rescore_factor, int64_t *out_labels, float *out_distances);
+// How lance_hybrid_search fuses the vector and full-text rankings.
+constexpr int32_t LANCE_FUSION_RRF = 0;
+constexpr int32_t LANCE_FUSION_LINEAR = 1;
+constexpr int32_t LANCE_FUSION_MAX_SCORE = 2;
+// Vector k-NN fused with a full-text (BM25) search of text; a null text_column searches every
+// full-text indexed column. rrf_k <= 0 uses the default. out_* hold at least k entries, highest score first.
+int32_t LanceDetachedHybridSearch(LanceHandle handle, const float *query, int32_t dim, const std::string &text,
+                                  const char *text_column, int32_t k, int32_t nprobes, int32_t refine_factor,
+                                  const char *predicate, int32_t fusion, float rrf_k, float vector_weight,
+                                  int64_t *out_labels, float *out_scores);
+// Build (or replace) a full-text index on a string column.
+void LanceDetachedCreateFtsIndex(LanceHandle handle, const std::string &column);
+// Build (or replace) a scalar index on a metadata column, used by filters and search prefilters: BTREE for mostly
+// distinct values, BITMAP for low-cardinality columns, LABEL_LIST for list columns.
+enum class LanceScalarIndexType : int32_t { BTREE = 0, BITMAP = 1, LABEL_LIST = 2 };
+void LanceDetachedCreateScalarIndex(LanceHandle handle, const std::string &column, LanceScalarIndexType index_type);
+// Fit a PCA projection and write the reduced column; index_type < 0 builds no index on it.
+// Returns the fraction of variance kept.
+double LanceDetachedTrainPca(LanceHandle handle, int32_t target_dims, int64_t sample, int32_t index_type);
+// Train an OPQ-style rotation for num_sub_vectors PQ sub-vectors and rewrite the rows rotated.
+void LanceDetachedTrainRotation(LanceHandle handle, int32_t num_sub_vectors, int64_t sample);
+
+// Vector index coverage: unindexed rows are searched by brute force until the index is optimized, and
+// retrain_recommended is set once the table outgrew the rows the index was trained on. Row counts are -1
+// without a vector index.
+struct LanceIndexStaleness {
+	int64_t indexed_rows = -1;
+	int64_t unindexed_rows = -1;
+	bool retrain_recommended = false;
+};
+LanceIndexStaleness LanceDetachedIndexStaleness(LanceHandle handle);
+
+// Rebuild the max label recorded in the table metadata (which spares opens a label scan) from the rows.
+// Returns the max label, -1 when none was ever assigned.
+int64_t LanceDetachedRepairLabelWatermark(LanceHandle handle);
+
+// Relabel rows in one table version. The mapping is an Arrow struct of BIGINT old_label and new_label
+// columns; takes ownership of arrow_array, caller must release arrow_schema. Old labels must exist and
+// new labels must not be used by rows outside the mapping. Returns the number of rows remapped.
+int64_t LanceDetachedRemapLabels(LanceHandle handle, void *arrow_schema, void *arrow_array);
+
+// Delete, in one table version, every row whose key is not in the Arrow struct of one BIGINT or VARCHAR column
+// named after the key column (label or a stored column); rows with a NULL key are deleted. Takes ownership of
+// arrow_array, caller must release arrow_schema. An empty key set deletes every row and throws unless
+// allow_empty. Returns the number of rows deleted.
+int64_t LanceDetachedRetainOnly(LanceHandle handle, void *arrow_schema, void *arrow_array, bool allow_empty);
 
-// Merge live rows from source into target (all in Rust). Returns count of merged rows.
-// Fills out_old_labels and out_new_labels with the mapping.
-int32_t LanceDetachedMerge(LanceHandle target, LanceHandle source, const int64_t *live_source_labels,
-                           int32_t live_count, int64_t *out_old_labels, int64_t *out_new_labels);
+// Declarative schema migration. target_schema (borrowed ArrowSchema) lists the desired data columns without the
+// label, like create_from_arrow. The plan drops, casts, makes nullable and adds columns (step is "drop", "cast",
+// "set_nullable" or "add"; from_type/to_type are empty where they do not apply). Applying it fails without changes
+// if the table schema moved since or a step drops or casts a column a table setting reads; drops are applied last.
+// Handles must be reopened to see the migrated columns.
+struct LanceMigrationStep {
+	std::string step;
+	std::string column;
+	std::string from_type;
+	std::string to_type;
+};
+std::vector<LanceMigrationStep> LanceDetachedSchemaDiff(LanceHandle handle, void *target_schema);
+int64_t LanceDetachedApplyMigration(LanceHandle handle, void *target_schema);
+
+// What a merge into the handle does about the rows it appends unindexed.
+constexpr int32_t LANCE_MERGE_INDEXING_NONE = 0;
+constexpr int32_t LANCE_MERGE_INDEXING_OPTIMIZE = 1;
+constexpr int32_t LANCE_MERGE_INDEXING_BACKGROUND = 2;
+void LanceDetachedSetMergeIndexing(LanceHandle handle, int32_t mode);
+
+// Merge live rows from source into target (all in Rust). Returns the (old_label, new_label) mapping,
+// streamed from Rust so its size need not be known in advance. Columns are matched by name; with strict,
+// any schema difference between the two tables is an error. out_staleness, if given, receives the index
+// coverage after the merge and the target's merge indexing step. With an idempotency token, a retry
+// merges nothing and returns the first call's mapping.
+std::vector<std::pair<int64_t, int64_t>> LanceDetachedMerge(LanceHandle target, LanceHandle source,
+                                                            const int64_t *live_source_labels, int32_t live_count,
+                                                            bool strict = false,
+                                                            LanceIndexStaleness *out_staleness = nullptr,
+                                                            const char *token = nullptr);
 
 // Search. Returns count. Fills out_labels, out_distances.
+// predicate is an optional Lance SQL filter (nullptr for none).
+// model, if not nullptr, must match the embedding model recorded for the table.
+// weights, if not nullptr, holds weights_len per-dimension weights (0 masks a dimension) the hits are rescored
+// with; weight_query also weighs the query before the ANN search.
+// ef > 0 sets how many candidates an HNSW index explores per query (at least k); 0 keeps Lance's default.
 int32_t LanceDetachedSearch(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
-                            int32_t refine_factor, int64_t *out_labels, float *out_distances);
+                            int32_t refine_factor, const char *predicate, int64_t *out_labels, float *out_distances,
+                            const char *model = nullptr, const float *weights = nullptr, int32_t weights_len = 0,
+                            bool weight_query = false, int32_t ef = 0);
+
+// Diversity of one search's hits. mean_pairwise_distance is NaN with fewer than two hits; distinct_values counts
+// the distinct non-NULL values of the chosen column among them, -1 without a column.
+struct LanceSearchDiversity {
+	double mean_pairwise_distance;
+	int64_t distinct_values;
+};
+
+// LanceDetachedSearch that also fills diversity. column (nullptr for none) must not be sensitive.
+int32_t LanceDetachedSearchDiversity(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                     int32_t refine_factor, int32_t ef, const char *predicate, const char *column,
+                                     int64_t *out_labels, float *out_distances, LanceSearchDiversity &diversity,
+                                     const char *model = nullptr);
+// Search at a read consistency: "latest" checks out the latest version first (seeing other processes' commits),
+// "session" (the default) catches up with this process's commits, "eventual" reads the version the handle has.
+int32_t LanceDetachedSearchConsistent(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                      int32_t refine_factor, const std::string &consistency, int64_t *out_labels,
+                                      float *out_distances, const char *model = nullptr);
+// Search num_queries row-major queries in one call, up to concurrency at once (0 for the default). The output
+// buffers hold num_queries * k entries; hits come grouped by query, nearest first, tagged with the query index.
+int32_t LanceDetachedSearchBatch(LanceHandle handle, const float *queries, int32_t dim, int32_t num_queries, int32_t k,
+                                 int32_t nprobes, int32_t refine_factor, int32_t concurrency, int32_t *out_query_idx,
+                                 int64_t *out_labels, float *out_distances);
+// Search for at most k neighbors with lower_bound <= distance < upper_bound; a NaN bound leaves that side open
+// (one is required). Fewer than k hits means every neighbor in range was found.
+int32_t LanceDetachedSearchRange(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                 int32_t refine_factor, float lower_bound, float upper_bound, const char *predicate,
+                                 int64_t *out_labels, float *out_distances, const char *model = nullptr);
+
+// Asynchronous search and append: submit the work to the Lance runtime and return at once, so the caller can
+// overlap Lance I/O with its own work. done runs on a Lance runtime thread with the results, or with a non-empty
+// error (and, for appends, the LANCE_ERR_* code as status). The handle, and for appends the vectors buffer, must
+// stay valid until done has run. Submission errors throw, and done is then never called.
+using LanceSearchDone = std::function<void(std::vector<std::pair<int64_t, float>> results, const std::string &error)>;
+void LanceDetachedSearchAsync(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                              int32_t refine_factor, const char *predicate, LanceSearchDone done);
+using LanceAddDone = std::function<void(std::vector<int64_t> labels, int32_t status, const std::string &error)>;
+void LanceDetachedAddBatchAsync(LanceHandle handle, const float *vectors, int32_t num, int32_t dim, LanceAddDone done);
+
+// Poll-based alternative: submit returns a task id to check from the caller's own loop (e.g. between interrupt
+// checks). Taking a result forgets the task and throws if it failed or is still pending; a task that is no longer
+// wanted must be discarded. The handle must stay valid until the task finishes or is discarded: discarding a
+// running task blocks until it is done. The vectors are copied.
+enum class LanceTaskStatus : int32_t { PENDING = 0, READY = 1, FAILED = 2, UNKNOWN = -1 };
+int64_t LanceDetachedSearchSubmit(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                  int32_t refine_factor, const char *predicate);
+int64_t LanceDetachedAddBatchSubmit(LanceHandle handle, const float *vectors, int32_t num, int32_t dim);
+LanceTaskStatus LanceTaskPoll(int64_t task_id);
+std::vector<std::pair<int64_t, float>> LanceTaskTakeSearchResult(int64_t task_id);
+std::vector<int64_t> LanceTaskTakeAddResult(int64_t task_id);
+void LanceTaskDiscard(int64_t task_id);
+
+// Thread counts of the Lance I/O runtime and of the separate pool for CPU-heavy work (index training, rescoring,
+// reranking). Lance's own index training pool is sized by the LANCE_CPU_THREADS environment variable, which must
+// be set before the extension loads; the CPU count defaults to it. Takes effect only before the first Lance call;
+// afterwards throws unless the counts match the running configuration. 0 keeps the current count. Returns the
+// effective (io_threads, cpu_threads).
+std::pair<int32_t, int32_t> LanceRuntimeConfigure(int32_t io_threads, int32_t cpu_threads);
+
+// Rows and bytes one Lance call collecting a whole result in memory (get_all_vectors, e.g. when merging indexes,
+// or an unordered scan) may read before it throws, so an accidental full read of a huge table fails instead of
+// exhausting memory. 0 lifts a limit, a negative value keeps the current one. Returns the effective
+// (max_rows, max_bytes).
+std::pair<int64_t, int64_t> LanceReadLimitsConfigure(int64_t max_rows, int64_t max_bytes);
+
+// Replace the object store options ("key=value", e.g. "aws_region=us-east-1") every connection opened afterwards
+// starts from; options given for one connection override them key by key. An empty list clears them. Returns the
+// number of options set.
+int32_t LanceSetDefaultStorageOptions(const std::vector<std::string> &pairs);
+
+// Credential source for tables on S3 opened or created afterwards, called on first use and again shortly before
+// the credentials expire, so rotating (e.g. STS) credentials reach open tables. Write NUL-terminated key id,
+// secret key and session token (empty for none) into the buffers, set *expires_at_ms to the epoch-millisecond
+// expiry (0 if none) and return 0. Must be thread-safe; user_data must outlive its registration. A null callback
+// unregisters it.
+typedef int32_t (*LanceCredentialFn)(void *user_data, char *key_id, int32_t key_id_len, char *secret_key,
+                                     int32_t secret_key_len, char *token, int32_t token_len, int64_t *expires_at_ms);
+void LanceRegisterCredentialProvider(LanceCredentialFn callback, void *user_data);
+
+// Local disk cache of data, deletion and index files of tables on object storage, evicting the least recently read
+// files beyond max_bytes. Applies to tables opened afterwards; an empty dir stops caching.
+struct LanceDiskCacheStats {
+	bool enabled = false;
+	int64_t max_bytes = 0;
+	int64_t bytes = 0;
+	int64_t files = 0;
+	int64_t hits = 0;
+	int64_t misses = 0;
+};
+void LanceDiskCacheConfigure(const std::string &dir, int64_t max_bytes);
+LanceDiskCacheStats LanceDiskCacheGetStats();
+
+// k-NN graph over a sample of source rows (all rows when sample is 0); a row is never its own neighbor.
+struct LanceGraphEdge {
+	int64_t src;
+	int64_t dst;
+	float distance;
+};
+std::vector<LanceGraphEdge> LanceDetachedKnnGraph(LanceHandle handle, int32_t k, int64_t sample, int32_t nprobes,
+                                                  int32_t refine_factor);
+
+// Physical address of every row: Lance's _rowid ((fragment_id << 32) | offset) changes when compaction or a
+// merge rewrites fragments; the label does not.
+struct LanceRowAddress {
+	int64_t label;
+	uint64_t lance_row_id;
+	uint32_t fragment_id;
+};
+std::vector<LanceRowAddress> LanceDetachedRowAddresses(LanceHandle handle);
+
+// Streaming search for very large k. Open a cursor, then pull results in chunks until Next returns 0.
+typedef void *LanceSearchCursor;
+LanceSearchCursor LanceDetachedSearchCursorOpen(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                                int32_t nprobes, int32_t refine_factor);
+int32_t LanceSearchCursorNext(LanceSearchCursor cursor, int64_t *out_labels, float *out_distances, int32_t capacity);
+void LanceSearchCursorFree(LanceSearchCursor cursor);
 
 int64_t LanceDetachedCount(LanceHandle handle);
+
+// Reachability of the handle's store and table manifest: the latest table version and the round trip. After a
+// connection error the handle reopens its table before its next operation; reads retry once transparently.
+struct LancePingResult {
+	int64_t version = 0;
+	double elapsed_ms = 0;
+};
+LancePingResult LanceDetachedPing(LanceHandle handle);
 void LanceDetachedDelete(LanceHandle handle, int64_t label);
-void LanceDetachedDeleteBatch(LanceHandle handle, const int64_t *labels, int32_t count);
+// With an idempotency token, a retry of the same delete is a no-op.
+void LanceDetachedDeleteBatch(LanceHandle handle, const int64_t *labels, int32_t count, const char *token = nullptr);
+// Deletes and column updates planned by UPDATE/DELETE, committed in one table version. The optional Arrow
+// batch (ownership of the array passes to Rust) holds a label column plus the columns to set, by name.
+// expected_version >= 0 rejects the change if the table moved past that version.
+struct LanceAppliedChanges {
+	int64_t version = 0;
+	int64_t deleted = 0;
+	int64_t updated = 0;
+};
+LanceAppliedChanges LanceDetachedApplyChanges(LanceHandle handle, const int64_t *deletes, int32_t delete_count,
+                                              void *arrow_schema = nullptr, void *arrow_array = nullptr,
+                                              int64_t expected_version = -1);
 
 void LanceDetachedCreateIndex(LanceHandle handle, int32_t num_partitions, int32_t num_sub_vectors);
 void LanceDetachedCreateHnswIndex(LanceHandle handle, int32_t m, int32_t ef_construction);
+
+// Vector index types for LanceDetachedCreateVectorIndex. Parameters <= 0 use LanceDB defaults;
+// those that do not apply to the type are ignored. sample_rate is training rows per partition.
+// A non-empty metric overrides the handle's distance type; searches through an index built with
+// a different metric than the handle's fail with an error.
+constexpr int32_t LANCE_INDEX_IVF_PQ = 0;
+constexpr int32_t LANCE_INDEX_IVF_HNSW_SQ = 1;
+// 2 is reserved for IVF_SQ, which the bundled Lance cannot build; it fails as an unsupported index type.
+constexpr int32_t LANCE_INDEX_IVF_FLAT = 3;
+void LanceDetachedCreateVectorIndex(LanceHandle handle, int32_t index_type, int32_t num_partitions,
+                                    int32_t num_sub_vectors, int32_t m, int32_t ef_construction,
+                                    int32_t sample_rate, const std::string &metric = std::string());
+
+// Rebuild the vector index on a background thread; the current index serves searches until the
+// new one is committed. wait blocks until the rebuild finishes (and throws if it failed).
+void LanceDetachedRebuildIndex(LanceHandle handle, int32_t index_type, int32_t num_partitions, int32_t num_sub_vectors,
+                               int32_t m, int32_t ef_construction, int32_t sample_rate, const std::string &metric,
+                               bool wait);
+// Latest rebuild: state is idle, running, succeeded or failed. index_type and error are empty when unset.
+struct LanceRebuildStatus {
+	std::string state;
+	std::string index_type;
+	int64_t started_ms;
+	int64_t elapsed_ms;
+	std::string error;
+	//! training or recording while running, empty otherwise
+	std::string phase;
+};
+LanceRebuildStatus LanceDetachedRebuildStatus(LanceHandle handle);
+// Open a read-only handle pinned to the table version handle currently sees (written to out_version if
+// given), so several searches and scans observe one dataset while appends continue. Release it with
+// LanceDetachedReleaseSnapshot, which refuses live handles.
+LanceHandle LanceDetachedBeginReadSnapshot(LanceHandle handle, uint64_t *out_version = nullptr);
+void LanceDetachedReleaseSnapshot(LanceHandle snapshot);
+// Create the hidden staging sibling of the table (replacing any previous one), copying all rows with
+// their labels when copy_rows is set. The returned handle must be freed or passed to LanceDetachedPromoteStaging.
+LanceHandle LanceDetachedCreateStaging(LanceHandle handle, bool copy_rows);
+// Swap the staging table in as the live table, as a commit on it, and reopen handle on it. Takes ownership
+// of staging even when it throws. Returns the tag the previous version of the live table was kept under.
+std::string LanceDetachedPromoteStaging(LanceHandle handle, LanceHandle staging);
+// Cap the training sample of index builds to max_memory_bytes (0 = no cap). Build threads are bounded for the
+// whole process by LANCE_CPU_THREADS, set before the extension loads.
+void LanceDetachedSetIndexBuildLimits(LanceHandle handle, int64_t max_memory_bytes);
+// Rows per batch Lance produces for search results on this handle (0 = Lance's default).
+void LanceDetachedSetReadBatchSize(LanceHandle handle, int32_t rows);
+// What filtered searches spend to return k hits when more rows match (0 disables a step): doubling
+// nprobes up to max_nprobes, then an exact search when at most max_exact_rows rows match, and
+// over-fetching up to max_overfetch * k rows when DuckDB filters the results further.
+struct LanceHitBudget {
+	int32_t max_nprobes = 0;
+	int64_t max_exact_rows = 0;
+	int32_t max_overfetch = 0;
+};
+void LanceDetachedSetHitBudget(LanceHandle handle, const LanceHitBudget &budget);
+LanceHitBudget LanceDetachedGetHitBudget(LanceHandle handle);
+// refine_factor that picks the factor per search from the index's compression ratio and k.
+constexpr int32_t LANCE_REFINE_AUTO = -1;
+// Refine factor a search of k rows runs with for refine_factor (negative = auto, chosen from the
+// index's compression ratio and k) and the candidates it rescores. compression is 0 without an index.
+struct LanceRefinePlan {
+	int64_t factor = 0;
+	int64_t rescored = 0;
+	double compression = 0;
+	bool is_auto = false;
+};
+LanceRefinePlan LanceDetachedRefinePlan(LanceHandle handle, int32_t refine_factor, int32_t k);
 void LanceDetachedCompact(LanceHandle handle);
 
 int32_t LanceDetachedGetVector(LanceHandle handle, int64_t label, float *out_vec, int32_t capacity);
@@ -57,4 +458,241 @@ int32_t LanceDetachedGetVector(LanceHandle handle, int64_t label, float *out_vec
 // Pass nullptr for out_labels/out_vectors to get count first (via out_count).
 int32_t LanceDetachedGetAllVectors(LanceHandle handle, int64_t *out_labels, float *out_vectors, int64_t *out_count);
 
+// On-disk bytes by component (data, deletions, indices, manifests, total) and, optionally, per column.
+struct LanceDiskUsageEntry {
+	std::string component;
+	std::string column; // empty for whole-table components
+	int64_t bytes;
+};
+std::vector<LanceDiskUsageEntry> LanceDetachedDiskUsage(LanceHandle handle, bool include_columns);
+
+// Back the table's files up into the dataset directory dest, copying only what came after since_version
+// (< 0 for a full backup). Chained backups into one dest can be restored at any backed-up version.
+struct LanceBackupResult {
+	int64_t version = 0; // latest version in dest
+	int64_t files = 0;
+	int64_t bytes = 0;
+};
+LanceBackupResult LanceDetachedBackup(LanceHandle handle, const std::string &dest, int64_t since_version = -1);
+
+// Labels of the rows the handle sees as a serialized roaring bitmap (portable 64-bit format), with the table
+// version read and the label count, for checking references and diffing versions without per-label queries.
+struct LanceLabelBitmap {
+	int64_t version = 0;
+	int64_t labels = 0;
+	std::string bitmap;
+};
+LanceLabelBitmap LanceDetachedLabelBitmap(LanceHandle handle);
+// Set operations on serialized label bitmaps.
+enum class LanceBitmapOp : int32_t { AND = 0, OR = 1, AND_NOT = 2, XOR = 3 };
+std::string LanceLabelBitmapCombine(const std::string &a, const std::string &b, LanceBitmapOp op);
+// The labels of a serialized bitmap, ascending.
+std::vector<int64_t> LanceLabelBitmapLabels(const std::string &bitmap);
+
+// Manifest of the version the handle sees, as a blob a read replica on shared storage imports to move its
+// view to that version without listing the store.
+struct LanceManifestBlob {
+	int64_t version = 0;
+	std::string blob;
+};
+LanceManifestBlob LanceDetachedExportManifest(LanceHandle handle);
+// Check the blob against the stored manifest and move the handle to its version; the handle becomes a
+// read-only replica. Returns the version.
+int64_t LanceDetachedImportManifest(LanceHandle handle, const std::string &blob);
+
+// Storage format of the table's current version and whether this build can write it. writer and problems
+// are empty when unknown or none; file_versions is a comma-separated list of data file versions.
+struct LanceFormatInfo {
+	int64_t version = 0;
+	std::string writer;
+	std::string library;
+	std::string storage_format;
+	std::string file_versions;
+	int64_t reader_flags = 0;
+	int64_t writer_flags = 0;
+	bool can_read = false;
+	bool can_write = false;
+	bool written_by_newer = false;
+	std::string problems;
+};
+LanceFormatInfo LanceDetachedFormatInfo(LanceHandle handle);
+
+// Min/max (typed; NULL when the column has no non-null values) and null count of one column.
+struct LanceColumnStats {
+	Value min;
+	Value max;
+	int64_t null_count;
+	int64_t row_count;
+};
+// Sensitive columns need privileged, since min/max expose their values.
+LanceColumnStats LanceDetachedColumnStats(LanceHandle handle, const std::string &column, bool privileged = false);
+// Row count (scaled up from the sample) and distances to the query, if one is given, per value of a
+// column. Groups beyond max_groups are counted together in one row with other set.
+struct LanceGroupStats {
+	Value group_value;
+	bool other;
+	int64_t row_count;
+	Value min_distance;
+	Value avg_distance;
+	Value max_distance;
+};
+struct LanceApproxStats {
+	// Exact unless the groups overflowed max_groups (then a HyperLogLog estimate)
+	double distinct_groups = 0;
+	std::vector<LanceGroupStats> groups;
+};
+LanceApproxStats LanceDetachedApproxStats(LanceHandle handle, const std::string &column, const std::vector<float> &query,
+                                          double sample_fraction, int32_t max_groups, bool privileged = false);
+// Withhold columns from unprivileged scans and stats (replaces the previous list; empty clears it).
+void LanceDetachedSetSensitiveColumns(LanceHandle handle, const std::vector<std::string> &columns);
+
+// Query preprocessing applied before every search, e.g. "slice(0,256) | center | normalize".
+// An empty spec removes it. An empty mean makes center use the mean of the stored vectors.
+void LanceDetachedSetQueryTransform(LanceHandle handle, const std::string &spec, const std::vector<float> &mean);
+
+// Retrieval pipeline run by every search on the table, e.g. "coarse(10) | rescore | rerank(name)".
+// An empty spec removes it.
+void LanceDetachedSetPipeline(LanceHandle handle, const std::string &spec);
+
+// Reranker for rerank(name) stages: fill out_scores[0..n) (smaller ranks first), return 0 on success.
+// Must be thread-safe; user_data must outlive the process's use of the reranker.
+typedef int32_t (*LanceRerankFn)(void *user_data, const float *query, int32_t dim, const int64_t *labels,
+                                 const float *distances, int32_t n, float *out_scores);
+void LanceRegisterReranker(const std::string &name, LanceRerankFn callback, void *user_data);
+
+// Custom distance for exact and rescoring paths (never the ANN index): the distance between a and b
+// (dim values each), smaller is closer. Must be thread-safe; user_data must outlive its use.
+typedef float (*LanceDistanceFn)(void *user_data, const float *a, const float *b, int32_t dim);
+void LanceRegisterDistance(const std::string &name, LanceDistanceFn callback, void *user_data);
+// Vector arithmetic on the search kernels, so results match the search paths exactly (normalize leaves a
+// zero vector zero, like the normalize query transform step). Inputs are dim floats each (average: count
+// vectors, flattened); out must hold dim floats.
+void LanceVectorAdd(const float *a, const float *b, int32_t dim, float *out);
+void LanceVectorSubtract(const float *a, const float *b, int32_t dim, float *out);
+void LanceVectorAverage(const float *vectors, int32_t count, int32_t dim, float *out);
+void LanceVectorNormalize(const float *vector, int32_t dim, float *out);
+float LanceVectorDot(const float *a, const float *b, int32_t dim);
+
+// Metric (built-in or registered) for the handle's exact distances; empty restores the handle's metric.
+void LanceDetachedSetRescoreMetric(LanceHandle handle, const std::string &metric);
+
+// Rewrite the Lance table sorted by column, rows_per_fragment rows per fragment.
+void LanceDetachedClusterBy(LanceHandle handle, const std::string &column, int64_t rows_per_fragment);
+
+// Fragments a pushed-down predicate touches. cluster_by is empty if the table is not clustered.
+struct LancePruningStats {
+	std::string cluster_by;
+	int64_t total_fragments = 0;
+	int64_t matching_fragments = 0;
+};
+LancePruningStats LanceDetachedPruningStats(LanceHandle handle, const std::string &predicate);
+
+// Search hit tracking, buffered in memory and flushed to a sidecar Lance table.
+void LanceDetachedSetAccessTracking(LanceHandle handle, bool enabled);
+void LanceDetachedFlushAccessStats(LanceHandle handle);
+
+// Hide rows whose expires_at column is in the past from every search.
+void LanceDetachedSetRowTtl(LanceHandle handle, bool enabled);
+
+// Require writers to hold an advisory lease expiring ttl_secs after their last write (0 removes it).
+void LanceDetachedSetWriterLease(LanceHandle handle, int64_t ttl_secs);
+
+// Rows not searched within idle_ms, least recently used first. hits is 0 for never-accessed rows.
+struct LanceColdRow {
+	int64_t label;
+	int64_t hits;
+	int64_t last_access_ms;
+};
+std::vector<LanceColdRow> LanceDetachedColdRows(LanceHandle handle, int64_t idle_ms);
+
+// Query log, e.g. "tag=baseline, vectors=full": every search is appended to a sidecar Lance table with its
+// filter, k and returned labels; vectors=hash keeps only a hash of the query vector. An empty spec stops logging.
+void LanceDetachedSetQueryLog(LanceHandle handle, const std::string &spec);
+
+// Logged queries of tag re-run against the current table. overlap is the fraction of the logged results
+// still returned; top_changed is set when the nearest result changed. Hash-only entries are not replayed.
+struct LanceQueryDrift {
+	int64_t logged_ms;
+	std::string query_hash;
+	int64_t logged;
+	int64_t returned;
+	double overlap;
+	bool top_changed;
+};
+std::vector<LanceQueryDrift> LanceDetachedReplayQueries(LanceHandle handle, const std::string &tag);
+
+// Row/size quota, e.g. "max_rows=1000, max_bytes=1073741824, on_exceed=evict_oldest(ts)".
+// on_exceed=evict_lru (least recently searched first) requires access tracking.
+// An empty spec removes it. Appends rejected by the quota throw ConstraintException.
+constexpr int32_t LANCE_ERR_QUOTA_EXCEEDED = -2;
+void LanceDetachedSetQuota(LanceHandle handle, const std::string &spec);
+
+// Cold vector storage, e.g. "column=created_at, after_days=30, precision=f16": compaction moves rows older than
+// after_days (by a timestamp or epoch-ms BIGINT column) into a sidecar table storing their vectors at f16 or int8
+// precision, which searches rank exactly. An empty spec removes the policy; moved rows stay cold.
+void LanceDetachedSetColdStorage(LanceHandle handle, const std::string &spec);
+
+// Ingest constraints on metadata columns, e.g. "author not null; category in ('news', 'blog');
+// score between 0 and 1; on_violation=skip". With on_violation=error (the default) a violating batch throws
+// ConstraintException; skip drops the violating rows and dead_letter drops them into the rejects sidecar.
+// Dropped rows get label -1. An empty spec removes the constraints.
+constexpr int32_t LANCE_ERR_CONSTRAINT_VIOLATED = -3;
+void LanceDetachedSetConstraints(LanceHandle handle, const std::string &spec);
+
+// What multi-column appends do with rows that fail validation (NULL vector, NULL/NaN/infinite elements, values
+// that do not convert to their column type): "error" (the default, throws IOException), "skip" or "dead_letter"
+// (drop them into the rejects sidecar).
+void LanceDetachedSetOnInvalid(LanceHandle handle, const std::string &mode);
+
+// How multi-column appends match incoming columns to the table's: "positional" (the default), "strict" (by name;
+// unknown or missing columns throw IOException) or "lenient" (by name; missing columns are NULL, unknown ignored).
+void LanceDetachedSetColumnMatching(LanceHandle handle, const std::string &mode);
+
+// Rows captured in the rejects sidecar, oldest first. row renders the rejected values as column=value pairs.
+struct LanceReject {
+	int64_t rejected_ms;
+	int64_t source_row;
+	std::string reason;
+	std::string row;
+};
+std::vector<LanceReject> LanceDetachedRejects(LanceHandle handle);
+
+// Embedder for chunking stages: embed the n texts (texts[i] is text_lens[i] UTF-8 bytes, not NUL-terminated)
+// into out_vectors (n * dim floats), return 0 on success. Must be thread-safe; user_data must outlive its use.
+typedef int32_t (*LanceEmbedFn)(void *user_data, const char *const *texts, const int32_t *text_lens, int32_t n,
+                                int32_t dim, float *out_vectors);
+void LanceRegisterEmbedder(const std::string &name, LanceEmbedFn callback, void *user_data);
+// Chunking stage for documents added through the Rust chunked ingestion path, e.g.
+// "column=body, parent=doc_id, unit=tokens, size=256, overlap=32, embedder=name": each document becomes one
+// embedded row per window of body, and its chunks share the first chunk's label in doc_id. Empty removes it.
+void LanceDetachedSetChunking(LanceHandle handle, const std::string &spec);
+
+// Record the embedding model id (with the table dimension) in the table metadata. Empty clears it.
+void LanceDetachedSetEmbeddingModel(LanceHandle handle, const std::string &model);
+
+// Quantize int8 vector storage over [min, max]. Required before rows are added, while the table is empty; rows
+// with elements outside the range are invalid.
+void LanceDetachedSetInt8Range(LanceHandle handle, float min, float max);
+
+// Snapshot the running centroid/norm statistics as a named baseline stored with the table.
+void LanceDetachedTagDriftBaseline(LanceHandle handle, const std::string &tag);
+
+// Current statistics against a tagged baseline, one row per metric.
+struct LanceDriftMetric {
+	std::string metric;
+	double baseline;
+	double current;
+};
+std::vector<LanceDriftMetric> LanceDetachedDriftReport(LanceHandle handle, const std::string &tag);
+
+// Run a maintenance plan (e.g. "optimize; compact(16); prune(7); verify"), one row per step. A failing
+// step is reported with status "failed" and stops the plan; only an invalid plan throws.
+struct LanceMaintenanceStep {
+	std::string step;
+	std::string status;
+	std::string detail;
+	int64_t elapsed_ms;
+};
+std::vector<LanceMaintenanceStep> LanceDetachedRunMaintenance(LanceHandle handle, const std::string &plan);
+
 } // namespace duckdb
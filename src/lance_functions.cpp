@@ -5,13 +5,33 @@
 #include "duckdb/catalog/catalog.hpp"
 #include "duckdb/catalog/catalog_entry/duck_table_entry.hpp"
 #include "duckdb/catalog/catalog_entry/table_catalog_entry.hpp"
+#include "duckdb/common/string_util.hpp"
+#include "duckdb/common/types/timestamp.hpp"
 #include "duckdb/storage/data_table.hpp"
 
 namespace duckdb {
 
+// Look up a bound LANCE index by table and index name.
+static LanceIndex &GetLanceIndex(ClientContext &context, const string &table_name, const string &index_name) {
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", index_name, table_name);
+	}
+	return index_ptr->Cast<LanceIndex>();
+}
+
 // ========================================
-// lance_create_ann_index(table, index, num_partitions, num_sub_vectors)
-// Build IVF_PQ index for large datasets.
+// lance_create_ann_index(table, index, num_partitions, num_sub_vectors, metric := NULL)
+// Build IVF_PQ index for large datasets. metric overrides the index's distance type.
 // ========================================
 
 struct LanceCreateAnnBindData : public TableFunctionData {
@@ -19,6 +39,7 @@ struct LanceCreateAnnBindData : public TableFunctionData {
 	string index_name;
 	int32_t num_partitions;
 	int32_t num_sub_vectors;
+	string metric;
 };
 
 struct LanceCreateAnnState : public GlobalTableFunctionState {
@@ -36,6 +57,11 @@ static unique_ptr<FunctionData> LanceCreateAnnBind(ClientContext &context, Table
 	bind_data->num_partitions = input.inputs[2].GetValue<int32_t>();
 	bind_data->num_sub_vectors = input.inputs[3].GetValue<int32_t>();
 
+	auto it = input.named_parameters.find("metric");
+	if (it != input.named_parameters.end() && !it->second.IsNull()) {
+		bind_data->metric = it->second.GetValue<string>();
+	}
+
 	return_types.push_back(LogicalType::VARCHAR);
 	names.push_back("status");
 	return std::move(bind_data);
@@ -71,7 +97,7 @@ static void LanceCreateAnnScan(ClientContext &context, TableFunctionInput &data,
 	}
 
 	auto &lance_idx = index_ptr->Cast<LanceIndex>();
-	lance_idx.CreateAnnIndex(bind.num_partitions, bind.num_sub_vectors);
+	lance_idx.CreateAnnIndex(bind.num_partitions, bind.num_sub_vectors, bind.metric);
 
 	output.data[0].SetValue(0, Value("ANN index created"));
 	output.SetCardinality(1);
@@ -81,12 +107,13 @@ void RegisterLanceCreateAnnIndexFunction(ExtensionLoader &loader) {
 	TableFunction func("lance_create_ann_index",
 	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER, LogicalType::INTEGER},
 	                   LanceCreateAnnScan, LanceCreateAnnBind, LanceCreateAnnInit);
+	func.named_parameters["metric"] = LogicalType::VARCHAR;
 	loader.RegisterFunction(func);
 }
 
 // ========================================
-// lance_create_hnsw_index(table, index, m, ef_construction)
-// Build IVF_HNSW_SQ index for better recall.
+// lance_create_hnsw_index(table, index, m, ef_construction, metric := NULL)
+// Build IVF_HNSW_SQ index for better recall. metric overrides the index's distance type.
 // ========================================
 
 struct LanceCreateHnswBindData : public TableFunctionData {
@@ -94,6 +121,7 @@ struct LanceCreateHnswBindData : public TableFunctionData {
 	string index_name;
 	int32_t m;
 	int32_t ef_construction;
+	string metric;
 };
 
 struct LanceCreateHnswState : public GlobalTableFunctionState {
@@ -111,6 +139,11 @@ static unique_ptr<FunctionData> LanceCreateHnswBind(ClientContext &context, Tabl
 	bind_data->m = input.inputs[2].GetValue<int32_t>();
 	bind_data->ef_construction = input.inputs[3].GetValue<int32_t>();
 
+	auto it = input.named_parameters.find("metric");
+	if (it != input.named_parameters.end() && !it->second.IsNull()) {
+		bind_data->metric = it->second.GetValue<string>();
+	}
+
 	return_types.push_back(LogicalType::VARCHAR);
 	names.push_back("status");
 	return std::move(bind_data);
@@ -146,7 +179,7 @@ static void LanceCreateHnswScan(ClientContext &context, TableFunctionInput &data
 	}
 
 	auto &lance_idx = index_ptr->Cast<LanceIndex>();
-	lance_idx.CreateHnswIndex(bind.m, bind.ef_construction);
+	lance_idx.CreateHnswIndex(bind.m, bind.ef_construction, bind.metric);
 
 	output.data[0].SetValue(0, Value("HNSW index created"));
 	output.SetCardinality(1);
@@ -156,6 +189,3342 @@ void RegisterLanceCreateHnswIndexFunction(ExtensionLoader &loader) {
 	TableFunction func("lance_create_hnsw_index",
 	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER, LogicalType::INTEGER},
 	                   LanceCreateHnswScan, LanceCreateHnswBind, LanceCreateHnswInit);
+	func.named_parameters["metric"] = LogicalType::VARCHAR;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_create_ivf_flat_index(table, index, num_partitions, sample_rate := 0, metric := NULL)
+// Build IVF_FLAT index (no quantization): probed partitions are searched with exact distances, for full recall
+// on moderate tables at the cost of an index as large as the vectors.
+// ========================================
+
+struct LanceCreateIvfFlatBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t num_partitions;
+	int32_t sample_rate = 0;
+	string metric;
+};
+
+static unique_ptr<FunctionData> LanceCreateIvfFlatBind(ClientContext &context, TableFunctionBindInput &input,
+                                                       vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceCreateIvfFlatBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->num_partitions = input.inputs[2].GetValue<int32_t>();
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "sample_rate") {
+			bind_data->sample_rate = param.second.GetValue<int32_t>();
+		} else if (param.first == "metric") {
+			bind_data->metric = param.second.GetValue<string>();
+		}
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceCreateIvfFlatScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceCreateIvfFlatBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.CreateIvfFlatIndex(bind.num_partitions, bind.sample_rate, bind.metric);
+
+	output.data[0].SetValue(0, Value("IVF_FLAT index created"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceCreateIvfFlatIndexFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_create_ivf_flat_index",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER}, LanceCreateIvfFlatScan,
+	                   LanceCreateIvfFlatBind, LanceCreateAnnInit);
+	func.named_parameters["sample_rate"] = LogicalType::INTEGER;
+	func.named_parameters["metric"] = LogicalType::VARCHAR;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_create_scalar_index(table, index, column, index_type := 'btree')
+// Build (or replace) a Lance scalar index on a column stored in the index, so filters on it (including the
+// prefilter of filtered searches) read the index instead of scanning the column. index_type is btree (mostly
+// distinct values; equality and ranges), bitmap (low-cardinality columns) or label_list (list columns, for
+// membership tests).
+// ========================================
+
+struct LanceCreateScalarIndexBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string column;
+	LanceScalarIndexType index_type = LanceScalarIndexType::BTREE;
+};
+
+static unique_ptr<FunctionData> LanceCreateScalarIndexBind(ClientContext &context, TableFunctionBindInput &input,
+                                                           vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceCreateScalarIndexBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->column = input.inputs[2].GetValue<string>();
+
+	for (auto &kv : input.named_parameters) {
+		if (kv.first == "index_type" && !kv.second.IsNull()) {
+			auto type = StringUtil::Lower(kv.second.GetValue<string>());
+			if (type == "btree") {
+				bind_data->index_type = LanceScalarIndexType::BTREE;
+			} else if (type == "bitmap") {
+				bind_data->index_type = LanceScalarIndexType::BITMAP;
+			} else if (type == "label_list") {
+				bind_data->index_type = LanceScalarIndexType::LABEL_LIST;
+			} else {
+				throw InvalidInputException(
+				    "lance_create_scalar_index: unknown index_type '%s' (expected btree, bitmap or label_list)", type);
+			}
+		}
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceCreateScalarIndexScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceCreateScalarIndexBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).CreateScalarIndex(bind.column, bind.index_type);
+
+	output.data[0].SetValue(0, Value("Scalar index created on " + bind.column));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceCreateScalarIndexFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_create_scalar_index",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceCreateScalarIndexScan,
+	                   LanceCreateScalarIndexBind, LanceCreateAnnInit);
+	func.named_parameters["index_type"] = LogicalType::VARCHAR;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_create_fts_index(table, index, column)
+// Build (or replace) a full-text (BM25) index on a string column stored in the index, searched by
+// lance_hybrid_search.
+// ========================================
+
+struct LanceCreateFtsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string column;
+};
+
+static unique_ptr<FunctionData> LanceCreateFtsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                   vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceCreateFtsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->column = input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceCreateFtsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceCreateFtsBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).CreateFtsIndex(bind.column);
+
+	output.data[0].SetValue(0, Value("Full-text index created on " + bind.column));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceCreateFtsIndexFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_create_fts_index", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceCreateFtsScan, LanceCreateFtsBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_rebuild_index(table, index, index_type, num_partitions := 0, num_sub_vectors := 0, m := 0,
+//                     ef_construction := 0, sample_rate := 0, metric := NULL, wait := false, staging := false)
+// Train a replacement vector index (ivf_pq, ivf_hnsw_sq or ivf_sq) in the background. The current
+// index keeps serving searches until the new one is committed; poll lance_rebuild_status.
+// With staging := true the index is built on the staging table (lance_create_staging).
+// ========================================
+
+struct LanceRebuildIndexBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t index_type;
+	int32_t num_partitions = 0;
+	int32_t num_sub_vectors = 0;
+	int32_t m = 0;
+	int32_t ef_construction = 0;
+	int32_t sample_rate = 0;
+	string metric;
+	bool wait = false;
+	bool staging = false;
+};
+
+static int32_t ParseVectorIndexType(const string &name) {
+	auto lower = StringUtil::Lower(name);
+	if (lower == "ivf_pq") {
+		return LANCE_INDEX_IVF_PQ;
+	}
+	if (lower == "ivf_hnsw_sq") {
+		return LANCE_INDEX_IVF_HNSW_SQ;
+	}
+	if (lower == "ivf_flat") {
+		return LANCE_INDEX_IVF_FLAT;
+	}
+	if (lower == "ivf_sq") {
+		throw NotImplementedException("Unsupported index type 'ivf_sq': the bundled Lance builds SQ only with HNSW "
+		                              "(use ivf_hnsw_sq)");
+	}
+	throw InvalidInputException("Unknown index type '%s' (expected ivf_pq, ivf_hnsw_sq or ivf_flat)", name);
+}
+
+static unique_ptr<FunctionData> LanceRebuildIndexBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRebuildIndexBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->index_type = ParseVectorIndexType(input.inputs[2].GetValue<string>());
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "num_partitions") {
+			bind_data->num_partitions = param.second.GetValue<int32_t>();
+		} else if (param.first == "num_sub_vectors") {
+			bind_data->num_sub_vectors = param.second.GetValue<int32_t>();
+		} else if (param.first == "m") {
+			bind_data->m = param.second.GetValue<int32_t>();
+		} else if (param.first == "ef_construction") {
+			bind_data->ef_construction = param.second.GetValue<int32_t>();
+		} else if (param.first == "sample_rate") {
+			bind_data->sample_rate = param.second.GetValue<int32_t>();
+		} else if (param.first == "metric") {
+			bind_data->metric = param.second.GetValue<string>();
+		} else if (param.first == "wait") {
+			bind_data->wait = param.second.GetValue<bool>();
+		} else if (param.first == "staging") {
+			bind_data->staging = param.second.GetValue<bool>();
+		}
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceRebuildIndexScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRebuildIndexBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.RebuildIndex(bind.index_type, bind.num_partitions, bind.num_sub_vectors, bind.m, bind.ef_construction,
+	                       bind.sample_rate, bind.metric, bind.wait, bind.staging);
+
+	output.data[0].SetValue(0, Value(bind.wait ? "Index rebuilt" : "Rebuild started"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRebuildIndexFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_rebuild_index", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceRebuildIndexScan, LanceRebuildIndexBind, LanceCreateAnnInit);
+	func.named_parameters["num_partitions"] = LogicalType::INTEGER;
+	func.named_parameters["num_sub_vectors"] = LogicalType::INTEGER;
+	func.named_parameters["m"] = LogicalType::INTEGER;
+	func.named_parameters["ef_construction"] = LogicalType::INTEGER;
+	func.named_parameters["sample_rate"] = LogicalType::INTEGER;
+	func.named_parameters["metric"] = LogicalType::VARCHAR;
+	func.named_parameters["wait"] = LogicalType::BOOLEAN;
+	func.named_parameters["staging"] = LogicalType::BOOLEAN;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_rebuild_status(table, index, staging := false)
+// Returns (state, index_type, started, elapsed_ms, error, phase) of the latest lance_rebuild_index.
+// state is idle, running, succeeded or failed; elapsed_ms keeps growing while running. phase is
+// training (Lance trains and commits the index, reporting nothing finer) or recording, NULL unless running.
+// ========================================
+
+struct LanceRebuildStatusBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	bool staging = false;
+};
+
+static unique_ptr<FunctionData> LanceRebuildStatusBind(ClientContext &context, TableFunctionBindInput &input,
+                                                       vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRebuildStatusBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	auto staging = input.named_parameters.find("staging");
+	if (staging != input.named_parameters.end() && !staging->second.IsNull()) {
+		bind_data->staging = staging->second.GetValue<bool>();
+	}
+
+	return_types = {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::TIMESTAMP, LogicalType::BIGINT,
+	                LogicalType::VARCHAR, LogicalType::VARCHAR};
+	names = {"state", "index_type", "started", "elapsed_ms", "error", "phase"};
+	return std::move(bind_data);
+}
+
+static void LanceRebuildStatusScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRebuildStatusBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto status = lance_idx.GetRebuildStatus(bind.staging);
+
+	output.data[0].SetValue(0, Value(status.state));
+	output.data[1].SetValue(0, status.index_type.empty() ? Value() : Value(status.index_type));
+	output.data[2].SetValue(0, status.started_ms == 0 ? Value()
+	                                                  : Value::TIMESTAMP(Timestamp::FromEpochMs(status.started_ms)));
+	output.data[3].SetValue(0, Value::BIGINT(status.elapsed_ms));
+	output.data[4].SetValue(0, status.error.empty() ? Value() : Value(status.error));
+	output.data[5].SetValue(0, status.phase.empty() ? Value() : Value(status.phase));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRebuildStatusFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_rebuild_status", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceRebuildStatusScan,
+	                   LanceRebuildStatusBind, LanceCreateAnnInit);
+	func.named_parameters["staging"] = LogicalType::BOOLEAN;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_create_staging(table, index)
+// Copy the index's rows into a hidden staging table (replacing any previous one). Re-index it with
+// lance_rebuild_index(..., staging := true) while the live table keeps serving, then promote it.
+// ========================================
+
+struct LanceStagingBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LanceCreateStagingBind(ClientContext &context, TableFunctionBindInput &input,
+                                                       vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceStagingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceCreateStagingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceStagingBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.CreateStaging();
+
+	output.data[0].SetValue(0, Value("Staging table created"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceCreateStagingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_create_staging", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceCreateStagingScan,
+	                   LanceCreateStagingBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_promote_staging(table, index)
+// Swap the staging table in as the live Lance table, committed as a new version of it. The previous
+// version is kept under the returned retired_tag. Fails if rows were written or deleted since
+// lance_create_staging.
+// ========================================
+
+static unique_ptr<FunctionData> LancePromoteStagingBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceStagingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("retired_tag");
+	return std::move(bind_data);
+}
+
+static void LancePromoteStagingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceStagingBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	output.data[0].SetValue(0, Value(lance_idx.PromoteStaging()));
+	output.SetCardinality(1);
+}
+
+void RegisterLancePromoteStagingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_promote_staging", {LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LancePromoteStagingScan, LancePromoteStagingBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_rename_table(table, index, new_name)
+// Rename the Lance table backing an index (e.g. to match a renamed DuckDB table). The index keeps
+// working under the new name; a pending staging table is discarded.
+// ========================================
+
+struct LanceRenameTableBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string new_name;
+};
+
+static unique_ptr<FunctionData> LanceRenameTableBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRenameTableBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->new_name = input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceRenameTableScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRenameTableBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.RenameLanceTable(bind.new_name);
+
+	output.data[0].SetValue(0, Value("Table renamed"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRenameTableFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_rename_table", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceRenameTableScan, LanceRenameTableBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_index_build_limits(table, index, max_memory_mb := 0)
+// Cap the training-sample memory of later index builds on this index. 0 removes the cap.
+// The cap lowers the per-partition training sample size; builds fail if even one sample
+// per partition does not fit. Build threads are capped for the process by the LANCE_CPU_THREADS
+// environment variable, which must be set before the extension loads.
+// ========================================
+
+struct LanceSetIndexBuildLimitsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int64_t max_memory_mb = 0;
+};
+
+static unique_ptr<FunctionData> LanceSetIndexBuildLimitsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                             vector<LogicalType> &return_types,
+                                                             vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetIndexBuildLimitsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "max_memory_mb") {
+			bind_data->max_memory_mb = param.second.GetValue<int64_t>();
+		}
+	}
+	if (bind_data->max_memory_mb < 0) {
+		throw InvalidInputException("lance_set_index_build_limits: max_memory_mb must not be negative");
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetIndexBuildLimitsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetIndexBuildLimitsBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetIndexBuildLimits(bind.max_memory_mb * 1024 * 1024);
+
+	output.data[0].SetValue(0, Value("Index build limits set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetIndexBuildLimitsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_index_build_limits", {LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetIndexBuildLimitsScan, LanceSetIndexBuildLimitsBind, LanceCreateAnnInit);
+	func.named_parameters["max_memory_mb"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_read_batch_size(table, index, rows)
+// Rows per batch Lance produces for search results on this index; 0 restores the default.
+// Applies until the index is reloaded.
+// ========================================
+
+struct LanceSetReadBatchSizeBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t rows = 0;
+};
+
+static unique_ptr<FunctionData> LanceSetReadBatchSizeBind(ClientContext &context, TableFunctionBindInput &input,
+                                                          vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetReadBatchSizeBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->rows = input.inputs[2].GetValue<int32_t>();
+	if (bind_data->rows < 0) {
+		throw InvalidInputException("lance_set_read_batch_size: rows must not be negative");
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetReadBatchSizeScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetReadBatchSizeBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetReadBatchSize(bind.rows);
+
+	output.data[0].SetValue(0, Value("Read batch size set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetReadBatchSizeFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_read_batch_size", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER},
+	                   LanceSetReadBatchSizeScan, LanceSetReadBatchSizeBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_hit_budget(table, index, max_nprobes := NULL, max_exact_rows := NULL, max_overfetch := NULL)
+// What filtered searches may spend to return k rows when more match: a short search retries with
+// doubled nprobes up to max_nprobes, then searches exactly if at most max_exact_rows rows match;
+// when DuckDB filters the results further, the scan over-fetches up to max_overfetch * k rows.
+// 0 disables a step; omitted parameters keep their value. Applies until the index is reloaded.
+// ========================================
+
+struct LanceSetHitBudgetBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int64_t max_nprobes = -1;
+	int64_t max_exact_rows = -1;
+	int64_t max_overfetch = -1;
+};
+
+static unique_ptr<FunctionData> LanceSetHitBudgetBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetHitBudgetBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		auto value = param.second.GetValue<int64_t>();
+		if (value < 0) {
+			throw InvalidInputException("lance_set_hit_budget: %s must not be negative", param.first);
+		}
+		if (param.first == "max_nprobes") {
+			bind_data->max_nprobes = value;
+		} else if (param.first == "max_exact_rows") {
+			bind_data->max_exact_rows = value;
+		} else if (param.first == "max_overfetch") {
+			bind_data->max_overfetch = value;
+		}
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetHitBudgetScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetHitBudgetBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto budget = lance_idx.GetHitBudget();
+	if (bind.max_nprobes >= 0) {
+		budget.max_nprobes = static_cast<int32_t>(bind.max_nprobes);
+	}
+	if (bind.max_exact_rows >= 0) {
+		budget.max_exact_rows = bind.max_exact_rows;
+	}
+	if (bind.max_overfetch >= 0) {
+		budget.max_overfetch = static_cast<int32_t>(bind.max_overfetch);
+	}
+	lance_idx.SetHitBudget(budget);
+
+	output.data[0].SetValue(0, Value("Hit budget set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetHitBudgetFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_hit_budget", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceSetHitBudgetScan,
+	                   LanceSetHitBudgetBind, LanceCreateAnnInit);
+	func.named_parameters["max_nprobes"] = LogicalType::INTEGER;
+	func.named_parameters["max_exact_rows"] = LogicalType::BIGINT;
+	func.named_parameters["max_overfetch"] = LogicalType::INTEGER;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_merge_indexing(table, index, mode)
+// What merges into the index (e.g. from parallel index builds) do about the rows they append unindexed:
+// 'none' leaves them to brute-force search until the next optimize, 'optimize' adds them to the vector
+// index before the merge returns, 'background' adds them after it returns. Applies until the index is
+// reloaded.
+// ========================================
+
+struct LanceSetMergeIndexingBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t mode = LANCE_MERGE_INDEXING_NONE;
+};
+
+static unique_ptr<FunctionData> LanceSetMergeIndexingBind(ClientContext &context, TableFunctionBindInput &input,
+                                                          vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetMergeIndexingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	auto mode = StringUtil::Lower(input.inputs[2].GetValue<string>());
+	if (mode == "none") {
+		bind_data->mode = LANCE_MERGE_INDEXING_NONE;
+	} else if (mode == "optimize") {
+		bind_data->mode = LANCE_MERGE_INDEXING_OPTIMIZE;
+	} else if (mode == "background") {
+		bind_data->mode = LANCE_MERGE_INDEXING_BACKGROUND;
+	} else {
+		throw InvalidInputException("lance_set_merge_indexing: mode must be 'none', 'optimize' or 'background'");
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetMergeIndexingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetMergeIndexingBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetMergeIndexing(bind.mode);
+
+	output.data[0].SetValue(0, Value("Merge indexing set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetMergeIndexingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_merge_indexing", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetMergeIndexingScan, LanceSetMergeIndexingBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_index_staleness(table, index)
+// Returns (indexed_rows, unindexed_rows, retrain_recommended) for the vector index: unindexed rows are
+// searched by brute force until the index is optimized, and retrain_recommended is set once the table
+// outgrew the rows the index was trained on. Counts are NULL without a vector index.
+// ========================================
+
+struct LanceIndexStalenessBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LanceIndexStalenessBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceIndexStalenessBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::BOOLEAN};
+	names = {"indexed_rows", "unindexed_rows", "retrain_recommended"};
+	return std::move(bind_data);
+}
+
+static void LanceIndexStalenessScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceIndexStalenessBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto staleness = GetLanceIndex(context, bind.table_name, bind.index_name).GetIndexStaleness();
+	auto count = [](int64_t rows) {
+		return rows < 0 ? Value(LogicalType::BIGINT) : Value::BIGINT(rows);
+	};
+	output.SetValue(0, 0, count(staleness.indexed_rows));
+	output.SetValue(1, 0, count(staleness.unindexed_rows));
+	output.SetValue(2, 0, Value::BOOLEAN(staleness.retrain_recommended));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceIndexStalenessFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_index_staleness", {LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceIndexStalenessScan, LanceIndexStalenessBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_repair_label_watermark(table, index)
+// Rebuilds the max label recorded in the table metadata from a scan of the rows and returns it (NULL when
+// no label was ever assigned). Opens rebuild a missing or stale watermark on their own; this repairs one
+// after out-of-band edits without reopening.
+// ========================================
+
+struct LanceRepairLabelWatermarkBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LanceRepairLabelWatermarkBind(ClientContext &context, TableFunctionBindInput &input,
+                                                              vector<LogicalType> &return_types,
+                                                              vector<string> &names) {
+	auto bind_data = make_uniq<LanceRepairLabelWatermarkBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT};
+	names = {"max_label"};
+	return std::move(bind_data);
+}
+
+static void LanceRepairLabelWatermarkScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRepairLabelWatermarkBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto max_label = GetLanceIndex(context, bind.table_name, bind.index_name).RepairLabelWatermark();
+	output.SetValue(0, 0, max_label < 0 ? Value(LogicalType::BIGINT) : Value::BIGINT(max_label));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRepairLabelWatermarkFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_repair_label_watermark", {LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceRepairLabelWatermarkScan, LanceRepairLabelWatermarkBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_remap_labels(table, index, old_labels, new_labels)
+// Relabel rows in one Lance commit, keeping their row history: the row of old_labels[i] gets
+// new_labels[i]. New labels may be swapped among the remapped rows but not taken from others.
+// ========================================
+
+struct LanceRemapLabelsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	vector<int64_t> old_labels;
+	vector<int64_t> new_labels;
+};
+
+static unique_ptr<FunctionData> LanceRemapLabelsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRemapLabelsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->old_labels.push_back(child.GetValue<int64_t>());
+	}
+	for (auto &child : ListValue::GetChildren(input.inputs[3])) {
+		bind_data->new_labels.push_back(child.GetValue<int64_t>());
+	}
+	if (bind_data->old_labels.size() != bind_data->new_labels.size()) {
+		throw InvalidInputException("lance_remap_labels: old_labels and new_labels differ in length");
+	}
+
+	return_types = {LogicalType::BIGINT};
+	names = {"remapped"};
+	return std::move(bind_data);
+}
+
+static void LanceRemapLabelsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRemapLabelsBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto remapped = lance_idx.RemapLabels(context, bind.old_labels, bind.new_labels);
+	output.SetValue(0, 0, Value::BIGINT(static_cast<int64_t>(remapped)));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRemapLabelsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_remap_labels",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::BIGINT),
+	                    LogicalType::LIST(LogicalType::BIGINT)},
+	                   LanceRemapLabelsScan, LanceRemapLabelsBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_retain_only(table, index, key_column, keys)
+// Delete, in one Lance commit, the index rows whose key_column value (label or a stored column) is not among
+// keys: the primitive for syncing an index to its source of truth. Rows with a NULL key are deleted. An empty
+// keys list deletes every row and is refused unless allow_empty := true. Returns the rows deleted.
+// ========================================
+
+struct LanceRetainOnlyBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string key_column;
+	vector<Value> keys;
+	bool allow_empty = false;
+};
+
+static unique_ptr<FunctionData> LanceRetainOnlyBind(ClientContext &context, TableFunctionBindInput &input,
+                                                    vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRetainOnlyBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->key_column = input.inputs[2].GetValue<string>();
+	if (input.inputs[3].IsNull()) {
+		throw InvalidInputException("lance_retain_only: keys must not be NULL");
+	}
+	bind_data->keys = ListValue::GetChildren(input.inputs[3]);
+	auto it = input.named_parameters.find("allow_empty");
+	if (it != input.named_parameters.end() && !it->second.IsNull()) {
+		bind_data->allow_empty = it->second.GetValue<bool>();
+	}
+
+	return_types = {LogicalType::BIGINT};
+	names = {"deleted"};
+	return std::move(bind_data);
+}
+
+static void LanceRetainOnlyScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRetainOnlyBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto deleted = lance_idx.RetainOnly(context, bind.key_column, bind.keys, bind.allow_empty);
+	output.SetValue(0, 0, Value::BIGINT(static_cast<int64_t>(deleted)));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRetainOnlyFunction(ExtensionLoader &loader) {
+	TableFunctionSet set("lance_retain_only");
+	vector<LogicalType> key_types {LogicalType::BIGINT, LogicalType::VARCHAR};
+	for (auto &key_type : key_types) {
+		TableFunction func({LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR,
+		                    LogicalType::LIST(key_type)},
+		                   LanceRetainOnlyScan, LanceRetainOnlyBind, LanceCreateAnnInit);
+		func.named_parameters["allow_empty"] = LogicalType::BOOLEAN;
+		set.AddFunction(func);
+	}
+	loader.RegisterFunction(set);
+}
+
+// ========================================
+// lance_ping(table, index)
+// Check that the index's store is reachable and its table manifest readable. Returns (version, latency_ms):
+// the latest table version and the round trip. Fails when the store cannot be reached; the index then
+// reopens its table before its next operation.
+// ========================================
+
+struct LancePingBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LancePingBind(ClientContext &context, TableFunctionBindInput &input,
+                                              vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LancePingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT, LogicalType::DOUBLE};
+	names = {"version", "latency_ms"};
+	return std::move(bind_data);
+}
+
+static void LancePingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LancePingBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto ping = GetLanceIndex(context, bind.table_name, bind.index_name).Ping();
+	output.SetValue(0, 0, Value::BIGINT(ping.version));
+	output.SetValue(1, 0, Value::DOUBLE(ping.elapsed_ms));
+	output.SetCardinality(1);
+}
+
+void RegisterLancePingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_ping", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LancePingScan, LancePingBind,
+	                   LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_prefetch(table, index, row_ids := NULL, filter := NULL)
+// Read the given rows (all rows when row_ids is NULL) matching the Lance filter and discard them, warming the
+// local disk cache and OS page cache for their fragments before a heavy join on remote data. Returns
+// (rows_read).
+// ========================================
+
+struct LancePrefetchBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	vector<row_t> row_ids;
+	string filter;
+};
+
+static unique_ptr<FunctionData> LancePrefetchBind(ClientContext &context, TableFunctionBindInput &input,
+                                                  vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LancePrefetchBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "row_ids") {
+			for (auto &child : ListValue::GetChildren(param.second)) {
+				if (!child.IsNull()) {
+					bind_data->row_ids.push_back(child.GetValue<int64_t>());
+				}
+			}
+		} else if (param.first == "filter") {
+			bind_data->filter = param.second.GetValue<string>();
+		}
+	}
+
+	return_types = {LogicalType::BIGINT};
+	names = {"rows_read"};
+	return std::move(bind_data);
+}
+
+static void LancePrefetchScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LancePrefetchBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto rows = GetLanceIndex(context, bind.table_name, bind.index_name).Prefetch(bind.row_ids, bind.filter);
+	output.SetValue(0, 0, Value::BIGINT(rows));
+	output.SetCardinality(1);
+}
+
+void RegisterLancePrefetchFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_prefetch", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LancePrefetchScan,
+	                   LancePrefetchBind, LanceCreateAnnInit);
+	func.named_parameters["row_ids"] = LogicalType::LIST(LogicalType::BIGINT);
+	func.named_parameters["filter"] = LogicalType::VARCHAR;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_refine_plan(table, index, k)
+// Returns (refine_factor, rescored, compression_ratio, auto) for a search of k rows with the index's
+// refine_factor: the factor used, how many candidates are rescored with exact distances, the vector
+// index's compression ratio (NULL without an index built by the extension), and whether the factor
+// was chosen by WITH (refine_factor = 'auto').
+// ========================================
+
+struct LanceRefinePlanBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t k = 0;
+};
+
+static unique_ptr<FunctionData> LanceRefinePlanBind(ClientContext &context, TableFunctionBindInput &input,
+                                                    vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRefinePlanBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->k = input.inputs[2].GetValue<int32_t>();
+	if (bind_data->k <= 0) {
+		throw InvalidInputException("lance_refine_plan: k must be positive");
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::DOUBLE, LogicalType::BOOLEAN};
+	names = {"refine_factor", "rescored", "compression_ratio", "auto"};
+	return std::move(bind_data);
+}
+
+static void LanceRefinePlanScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRefinePlanBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto plan = lance_idx.GetRefinePlan(bind.k);
+
+	output.data[0].SetValue(0, Value::BIGINT(plan.factor));
+	output.data[1].SetValue(0, Value::BIGINT(plan.rescored));
+	output.data[2].SetValue(0, plan.compression > 0 ? Value::DOUBLE(plan.compression) : Value());
+	output.data[3].SetValue(0, Value::BOOLEAN(plan.is_auto));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRefinePlanFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_refine_plan", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER},
+	                   LanceRefinePlanScan, LanceRefinePlanBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_runtime_config(io_threads := 0, cpu_threads := 0)
+// Thread counts of the Lance I/O runtime and CPU pool. Settable only before the first Lance operation in
+// the process; 0 keeps the current count (cpu_threads defaults to LANCE_CPU_THREADS, which sizes Lance's
+// own index training pool and must be set before the extension loads). Returns the effective counts.
+// ========================================
+
+struct LanceRuntimeConfigBindData : public TableFunctionData {
+	int32_t io_threads = 0;
+	int32_t cpu_threads = 0;
+};
+
+static unique_ptr<FunctionData> LanceRuntimeConfigBind(ClientContext &context, TableFunctionBindInput &input,
+                                                       vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRuntimeConfigBindData>();
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "io_threads") {
+			bind_data->io_threads = param.second.GetValue<int32_t>();
+		} else if (param.first == "cpu_threads") {
+			bind_data->cpu_threads = param.second.GetValue<int32_t>();
+		}
+	}
+	if (bind_data->io_threads < 0 || bind_data->cpu_threads < 0) {
+		throw InvalidInputException("lance_runtime_config: thread counts must not be negative");
+	}
+
+	return_types.push_back(LogicalType::INTEGER);
+	names.push_back("io_threads");
+	return_types.push_back(LogicalType::INTEGER);
+	names.push_back("cpu_threads");
+	return std::move(bind_data);
+}
+
+static void LanceRuntimeConfigScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceRuntimeConfigBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto config = LanceRuntimeConfigure(bind.io_threads, bind.cpu_threads);
+	output.SetValue(0, 0, Value::INTEGER(config.first));
+	output.SetValue(1, 0, Value::INTEGER(config.second));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceRuntimeConfigFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_runtime_config", {}, LanceRuntimeConfigScan, LanceRuntimeConfigBind,
+	                   LanceCreateAnnInit);
+	func.named_parameters["io_threads"] = LogicalType::INTEGER;
+	func.named_parameters["cpu_threads"] = LogicalType::INTEGER;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_read_limits(max_rows := NULL, max_bytes := NULL)
+// Process-wide caps on the rows and bytes a single Lance read collecting its whole result in memory may read
+// (e.g. exporting every vector when index merges copy a transaction's rows); such a read fails once over a cap
+// rather than taking the process down. 0 lifts a cap; NULL keeps it. Streaming reads are not capped. Returns the
+// effective (max_rows, max_bytes).
+// ========================================
+
+struct LanceReadLimitsBindData : public TableFunctionData {
+	int64_t max_rows = -1;
+	int64_t max_bytes = -1;
+};
+
+static unique_ptr<FunctionData> LanceReadLimitsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                    vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceReadLimitsBindData>();
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "max_rows") {
+			bind_data->max_rows = param.second.GetValue<int64_t>();
+		} else if (param.first == "max_bytes") {
+			bind_data->max_bytes = param.second.GetValue<int64_t>();
+		}
+		if (param.second.GetValue<int64_t>() < 0) {
+			throw InvalidInputException("lance_read_limits: %s must not be negative", param.first);
+		}
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT};
+	names = {"max_rows", "max_bytes"};
+	return std::move(bind_data);
+}
+
+static void LanceReadLimitsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceReadLimitsBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto limits = LanceReadLimitsConfigure(bind.max_rows, bind.max_bytes);
+	output.SetValue(0, 0, Value::BIGINT(limits.first));
+	output.SetValue(1, 0, Value::BIGINT(limits.second));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceReadLimitsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_read_limits", {}, LanceReadLimitsScan, LanceReadLimitsBind, LanceCreateAnnInit);
+	func.named_parameters["max_rows"] = LogicalType::BIGINT;
+	func.named_parameters["max_bytes"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_default_storage_options(options)
+// Object store options as 'key=value' strings (credentials, region, endpoint) for every Lance table opened or
+// created afterwards in this process, so they are configured once rather than per table. Replaces earlier
+// defaults; an empty list clears them. Returns (options) set; values are never echoed as they may be secrets.
+// ========================================
+
+struct LanceSetDefaultStorageOptionsBindData : public TableFunctionData {
+	vector<string> pairs;
+};
+
+static unique_ptr<FunctionData> LanceSetDefaultStorageOptionsBind(ClientContext &context,
+                                                                  TableFunctionBindInput &input,
+                                                                  vector<LogicalType> &return_types,
+                                                                  vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetDefaultStorageOptionsBindData>();
+	if (!input.inputs[0].IsNull()) {
+		for (auto &child : ListValue::GetChildren(input.inputs[0])) {
+			if (!child.IsNull()) {
+				bind_data->pairs.push_back(child.GetValue<string>());
+			}
+		}
+	}
+
+	return_types = {LogicalType::INTEGER};
+	names = {"options"};
+	return std::move(bind_data);
+}
+
+static void LanceSetDefaultStorageOptionsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetDefaultStorageOptionsBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	output.SetValue(0, 0, Value::INTEGER(LanceSetDefaultStorageOptions(bind.pairs)));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetDefaultStorageOptionsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_default_storage_options", {LogicalType::LIST(LogicalType::VARCHAR)},
+	                   LanceSetDefaultStorageOptionsScan, LanceSetDefaultStorageOptionsBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_disk_cache(directory := NULL, max_mb := 1024)
+// Local disk cache of data and index files of tables on object storage (s3:// etc.), kept across sessions and
+// evicting the least recently read files beyond max_mb. Setting directory configures the cache for tables opened
+// afterwards; an empty directory turns it off. Returns (enabled, max_bytes, bytes, files, hits, misses).
+// ========================================
+
+struct LanceDiskCacheBindData : public TableFunctionData {
+	bool configure = false;
+	string directory;
+	int64_t max_mb = 1024;
+};
+
+static unique_ptr<FunctionData> LanceDiskCacheBind(ClientContext &context, TableFunctionBindInput &input,
+                                                   vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceDiskCacheBindData>();
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "directory") {
+			bind_data->configure = true;
+			bind_data->directory = param.second.GetValue<string>();
+		} else if (param.first == "max_mb") {
+			bind_data->max_mb = param.second.GetValue<int64_t>();
+		}
+	}
+	if (bind_data->max_mb <= 0) {
+		throw InvalidInputException("lance_disk_cache: max_mb must be positive");
+	}
+
+	return_types = {LogicalType::BOOLEAN, LogicalType::BIGINT, LogicalType::BIGINT,
+	                LogicalType::BIGINT,  LogicalType::BIGINT, LogicalType::BIGINT};
+	names = {"enabled", "max_bytes", "bytes", "files", "hits", "misses"};
+	return std::move(bind_data);
+}
+
+static void LanceDiskCacheScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceDiskCacheBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	if (bind.configure) {
+		LanceDiskCacheConfigure(bind.directory, bind.max_mb * 1024 * 1024);
+	}
+	auto stats = LanceDiskCacheGetStats();
+	output.SetValue(0, 0, Value::BOOLEAN(stats.enabled));
+	output.SetValue(1, 0, Value::BIGINT(stats.max_bytes));
+	output.SetValue(2, 0, Value::BIGINT(stats.bytes));
+	output.SetValue(3, 0, Value::BIGINT(stats.files));
+	output.SetValue(4, 0, Value::BIGINT(stats.hits));
+	output.SetValue(5, 0, Value::BIGINT(stats.misses));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceDiskCacheFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_disk_cache", {}, LanceDiskCacheScan, LanceDiskCacheBind, LanceCreateAnnInit);
+	func.named_parameters["directory"] = LogicalType::VARCHAR;
+	func.named_parameters["max_mb"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_handles()
+// Lance handles open in this process: (handle_id, path, table_name, read_only, buffered_rows, opened_at,
+// last_operation_at). read_only marks read snapshots; buffered_rows counts rows held by an open append
+// session. Handles idle for long in a long-lived process are likely leaked.
+// ========================================
+
+struct LanceHandlesState : public GlobalTableFunctionState {
+	std::vector<LanceHandleInfo> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceHandlesBind(ClientContext &context, TableFunctionBindInput &input,
+                                                 vector<LogicalType> &return_types, vector<string> &names) {
+	return_types = {LogicalType::UBIGINT, LogicalType::VARCHAR,   LogicalType::VARCHAR,  LogicalType::BOOLEAN,
+	                LogicalType::BIGINT,  LogicalType::TIMESTAMP, LogicalType::TIMESTAMP};
+	names = {"handle_id",     "path",      "table_name",       "read_only",
+	         "buffered_rows", "opened_at", "last_operation_at"};
+	return make_uniq<TableFunctionData>();
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceHandlesInit(ClientContext &context, TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceHandlesState>();
+	state->rows = LanceListHandles();
+	return std::move(state);
+}
+
+static void LanceHandlesScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceHandlesState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value::UBIGINT(row.id));
+		output.SetValue(1, i, Value(row.path));
+		output.SetValue(2, i, Value(row.table_name));
+		output.SetValue(3, i, Value::BOOLEAN(row.read_only));
+		output.SetValue(4, i, Value::BIGINT(row.buffered_rows));
+		output.SetValue(5, i, Value::TIMESTAMP(Timestamp::FromEpochMs(row.opened_ms)));
+		output.SetValue(6, i, Value::TIMESTAMP(Timestamp::FromEpochMs(row.last_operation_ms)));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceHandlesFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_handles", {}, LanceHandlesScan, LanceHandlesBind, LanceHandlesInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_inspect(path, table_name)
+// What an existing Lance table offers before it is opened as an index: (kind, name, data_type, nullable,
+// dimension, detail). kind is label (the row key), vector (a FixedSizeList column; data_type is its element
+// type), index (data_type is the index type, detail its columns) or problem (detail says why the table
+// cannot be opened as an index, e.g. a missing, non-Int64 or NULL-containing label).
+// ========================================
+
+struct LanceInspectBindData : public TableFunctionData {
+	string path;
+	string table_name;
+};
+
+struct LanceInspectState : public GlobalTableFunctionState {
+	std::vector<LanceCapability> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceInspectBind(ClientContext &context, TableFunctionBindInput &input,
+                                                 vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceInspectBindData>();
+	bind_data->path = input.inputs[0].GetValue<string>();
+	bind_data->table_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR,
+	                LogicalType::BOOLEAN, LogicalType::INTEGER, LogicalType::VARCHAR};
+	names = {"kind", "name", "data_type", "nullable", "dimension", "detail"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceInspectInit(ClientContext &context, TableFunctionInitInput &input) {
+	auto &bind = input.bind_data->Cast<LanceInspectBindData>();
+	auto state = make_uniq<LanceInspectState>();
+	state->rows = LanceInspectTable(bind.path, bind.table_name);
+	return std::move(state);
+}
+
+static void LanceInspectScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceInspectState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value(row.kind));
+		output.SetValue(1, i, Value(row.name));
+		output.SetValue(2, i, row.data_type.empty() ? Value() : Value(row.data_type));
+		output.SetValue(3, i, row.nullable);
+		output.SetValue(4, i, row.dimension);
+		output.SetValue(5, i, row.detail.empty() ? Value() : Value(row.detail));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceInspectFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_inspect", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceInspectScan,
+	                   LanceInspectBind, LanceInspectInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_cluster_by(table, index, column, rows_per_fragment := 65536)
+// Rewrite the Lance dataset sorted by a column so range filters prune fragments.
+// ========================================
+
+struct LanceClusterByBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string column;
+	int64_t rows_per_fragment = 65536;
+};
+
+struct LanceClusterByState : public GlobalTableFunctionState {
+	bool done = false;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceClusterByBind(ClientContext &context, TableFunctionBindInput &input,
+                                                   vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceClusterByBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->column = input.inputs[2].GetValue<string>();
+
+	auto it = input.named_parameters.find("rows_per_fragment");
+	if (it != input.named_parameters.end()) {
+		bind_data->rows_per_fragment = it->second.GetValue<int64_t>();
+		if (bind_data->rows_per_fragment <= 0) {
+			throw InvalidInputException("rows_per_fragment must be positive");
+		}
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceClusterByInit(ClientContext &context,
+                                                               TableFunctionInitInput &input) {
+	return make_uniq<LanceClusterByState>();
+}
+
+static void LanceClusterByScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceClusterByBindData>();
+	auto &state = data.global_state->Cast<LanceClusterByState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.ClusterBy(bind.column, bind.rows_per_fragment);
+
+	output.data[0].SetValue(0, Value("Clustered by " + bind.column));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceClusterByFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_cluster_by", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceClusterByScan, LanceClusterByBind, LanceClusterByInit);
+	func.named_parameters["rows_per_fragment"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_pipeline(table, index, spec)
+// Configure the multi-stage retrieval pipeline, e.g. 'coarse(10) | rescore'. Empty spec removes it.
+// ========================================
+
+struct LanceSetPipelineBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+struct LanceSetPipelineState : public GlobalTableFunctionState {
+	bool done = false;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSetPipelineBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetPipelineBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSetPipelineInit(ClientContext &context,
+                                                                 TableFunctionInitInput &input) {
+	return make_uniq<LanceSetPipelineState>();
+}
+
+static void LanceSetPipelineScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetPipelineBindData>();
+	auto &state = data.global_state->Cast<LanceSetPipelineState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetPipeline(bind.spec);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Pipeline removed" : "Pipeline set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetPipelineFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_pipeline", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetPipelineScan, LanceSetPipelineBind, LanceSetPipelineInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_rescore_metric(table, index, metric)
+// Metric for exact distances (lance_search_within, PCA and pipeline rescoring): a built-in metric or
+// one registered by the host through LanceRegisterDistance. The ANN index keeps its own metric.
+// NULL or empty restores the index metric. Applies until the index is reloaded.
+// ========================================
+
+struct LanceSetRescoreMetricBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string metric;
+};
+
+static unique_ptr<FunctionData> LanceSetRescoreMetricBind(ClientContext &context, TableFunctionBindInput &input,
+                                                          vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetRescoreMetricBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->metric = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetRescoreMetricScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetRescoreMetricBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetRescoreMetric(bind.metric);
+
+	output.data[0].SetValue(0, Value(bind.metric.empty() ? "Rescore metric reset" : "Rescore metric set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetRescoreMetricFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_rescore_metric", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetRescoreMetricScan, LanceSetRescoreMetricBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_query_transform(table, index, spec, mean := NULL)
+// Configure query preprocessing, e.g. 'center | normalize'. Empty spec removes it.
+// ========================================
+
+struct LanceSetQueryTransformBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+	vector<float> mean;
+};
+
+struct LanceSetQueryTransformState : public GlobalTableFunctionState {
+	bool done = false;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSetQueryTransformBind(ClientContext &context, TableFunctionBindInput &input,
+                                                           vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetQueryTransformBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	auto it = input.named_parameters.find("mean");
+	if (it != input.named_parameters.end() && !it->second.IsNull()) {
+		for (auto &child : ListValue::GetChildren(it->second)) {
+			bind_data->mean.push_back(child.GetValue<float>());
+		}
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSetQueryTransformInit(ClientContext &context,
+                                                                       TableFunctionInitInput &input) {
+	return make_uniq<LanceSetQueryTransformState>();
+}
+
+static void LanceSetQueryTransformScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetQueryTransformBindData>();
+	auto &state = data.global_state->Cast<LanceSetQueryTransformState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetQueryTransform(bind.spec, bind.mean);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Query transform removed" : "Query transform set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetQueryTransformFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_query_transform",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceSetQueryTransformScan,
+	                   LanceSetQueryTransformBind, LanceSetQueryTransformInit);
+	func.named_parameters["mean"] = LogicalType::LIST(LogicalType::FLOAT);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_tag_drift_baseline(table, index, tag)
+// Snapshot the current centroid/norm statistics under a name for later drift reports.
+// ========================================
+
+struct LanceDriftBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string tag;
+};
+
+struct LanceTagDriftBaselineState : public GlobalTableFunctionState {
+	bool done = false;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceTagDriftBaselineBind(ClientContext &context, TableFunctionBindInput &input,
+                                                          vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceDriftBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->tag = input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceTagDriftBaselineInit(ClientContext &context,
+                                                                      TableFunctionInitInput &input) {
+	return make_uniq<LanceTagDriftBaselineState>();
+}
+
+static void LanceTagDriftBaselineScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceDriftBindData>();
+	auto &state = data.global_state->Cast<LanceTagDriftBaselineState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.TagDriftBaseline(bind.tag);
+
+	output.data[0].SetValue(0, Value("Baseline '" + bind.tag + "' tagged"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceTagDriftBaselineFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_tag_drift_baseline",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceTagDriftBaselineScan,
+	                   LanceTagDriftBaselineBind, LanceTagDriftBaselineInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_drift_report(table, index, tag)
+// Returns (metric, baseline, current) comparing current statistics to a tagged baseline.
+// ========================================
+
+struct LanceDriftReportState : public GlobalTableFunctionState {
+	std::vector<LanceDriftMetric> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceDriftReportBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceDriftBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->tag = input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	return_types.push_back(LogicalType::DOUBLE);
+	return_types.push_back(LogicalType::DOUBLE);
+	names.push_back("metric");
+	names.push_back("baseline");
+	names.push_back("current");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceDriftReportInit(ClientContext &context,
+                                                                 TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceDriftReportState>();
+	auto &bind = input.bind_data->Cast<LanceDriftBindData>();
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	state->rows = lance_idx.GetDriftReport(bind.tag);
+	return std::move(state);
+}
+
+static void LanceDriftReportScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceDriftReportState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value(row.metric));
+		output.SetValue(1, i, Value::DOUBLE(row.baseline));
+		output.SetValue(2, i, Value::DOUBLE(row.current));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceDriftReportFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_drift_report", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceDriftReportScan, LanceDriftReportBind, LanceDriftReportInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_run_maintenance(table, index, plan)
+// Runs a maintenance plan such as 'optimize; compact(16); prune(7); verify' step by step and returns
+// (step, status, detail, elapsed_ms) per step. Steps: optimize (index new rows), compact(min_fragments),
+// prune(older_than_days) and verify (label consistency). A failed step stops the plan.
+// ========================================
+
+struct LanceRunMaintenanceBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string plan;
+};
+
+struct LanceRunMaintenanceState : public GlobalTableFunctionState {
+	std::vector<LanceMaintenanceStep> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceRunMaintenanceBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRunMaintenanceBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->plan = input.inputs[2].GetValue<string>();
+
+	return_types = {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::BIGINT};
+	names = {"step", "status", "detail", "elapsed_ms"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceRunMaintenanceInit(ClientContext &context,
+                                                                    TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceRunMaintenanceState>();
+	auto &bind = input.bind_data->Cast<LanceRunMaintenanceBindData>();
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	state->rows = lance_idx.RunMaintenance(bind.plan);
+	return std::move(state);
+}
+
+static void LanceRunMaintenanceScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceRunMaintenanceState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value(row.step));
+		output.SetValue(1, i, Value(row.status));
+		output.SetValue(2, i, Value(row.detail));
+		output.SetValue(3, i, Value::BIGINT(row.elapsed_ms));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceRunMaintenanceFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_run_maintenance", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceRunMaintenanceScan, LanceRunMaintenanceBind, LanceRunMaintenanceInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_approx_stats(table, index, column, query := NULL, sample_fraction := 1.0, max_groups := 1000)
+// Per-value row counts of an index column, with min/avg/max distance to `query` per group when
+// given, computed in Lance with bounded memory. Groups beyond max_groups are counted together in
+// one row with other = true; approx_distinct_groups estimates the number of distinct values.
+// ========================================
+
+struct LanceApproxStatsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string column;
+	vector<float> query;
+	double sample_fraction = 1.0;
+	int32_t max_groups = 1000;
+};
+
+struct LanceApproxStatsState : public GlobalTableFunctionState {
+	LanceApproxStats stats;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceApproxStatsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceApproxStatsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->column = input.inputs[2].GetValue<string>();
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "query") {
+			for (auto &child : ListValue::GetChildren(param.second)) {
+				bind_data->query.push_back(child.GetValue<float>());
+			}
+		} else if (param.first == "sample_fraction") {
+			bind_data->sample_fraction = param.second.GetValue<double>();
+		} else if (param.first == "max_groups") {
+			bind_data->max_groups = param.second.GetValue<int32_t>();
+		}
+	}
+	if (bind_data->max_groups <= 0) {
+		throw InvalidInputException("lance_approx_stats: max_groups must be positive");
+	}
+
+	return_types = {LogicalType::VARCHAR, LogicalType::BOOLEAN, LogicalType::BIGINT, LogicalType::DOUBLE,
+	                LogicalType::DOUBLE,  LogicalType::DOUBLE,  LogicalType::BIGINT};
+	names = {"group_value",  "other",        "row_count",           "min_distance",
+	         "avg_distance", "max_distance", "approx_distinct_groups"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceApproxStatsInit(ClientContext &context,
+                                                                 TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceApproxStatsState>();
+	auto &bind = input.bind_data->Cast<LanceApproxStatsBindData>();
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	state->stats = lance_idx.GetApproxStats(bind.column, bind.query, bind.sample_fraction, bind.max_groups);
+	return std::move(state);
+}
+
+static void LanceApproxStatsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceApproxStatsState>();
+	auto &groups = state.stats.groups;
+
+	if (state.position >= groups.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	auto distinct = Value::BIGINT(static_cast<int64_t>(state.stats.distinct_groups + 0.5));
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, groups.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &group = groups[state.position + i];
+		output.SetValue(0, i, group.group_value.DefaultCastAs(LogicalType::VARCHAR));
+		output.SetValue(1, i, Value::BOOLEAN(group.other));
+		output.SetValue(2, i, Value::BIGINT(group.row_count));
+		output.SetValue(3, i, group.min_distance);
+		output.SetValue(4, i, group.avg_distance);
+		output.SetValue(5, i, group.max_distance);
+		output.SetValue(6, i, distinct);
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceApproxStatsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_approx_stats", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceApproxStatsScan, LanceApproxStatsBind, LanceApproxStatsInit);
+	func.named_parameters["query"] = LogicalType::LIST(LogicalType::FLOAT);
+	func.named_parameters["sample_fraction"] = LogicalType::DOUBLE;
+	func.named_parameters["max_groups"] = LogicalType::INTEGER;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_knn_graph(table, index, k, sample := 0)
+// The k-nearest-neighbor graph over `sample` rows (all rows when 0), found through the index.
+// Returns one (src_row_id, dst_row_id, distance) edge per neighbor, ready for UMAP/t-SNE or
+// graph tooling.
+// ========================================
+
+struct LanceKnnGraphBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t k = 0;
+	int64_t sample = 0;
+};
+
+struct LanceKnnGraphState : public GlobalTableFunctionState {
+	vector<LanceGraphEdge> edges;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceKnnGraphBind(ClientContext &context, TableFunctionBindInput &input,
+                                                  vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceKnnGraphBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->k = input.inputs[2].GetValue<int32_t>();
+	if (bind_data->k <= 0) {
+		throw InvalidInputException("lance_knn_graph: k must be positive");
+	}
+	auto sample = input.named_parameters.find("sample");
+	if (sample != input.named_parameters.end() && !sample->second.IsNull()) {
+		bind_data->sample = sample->second.GetValue<int64_t>();
+	}
+	if (bind_data->sample < 0) {
+		throw InvalidInputException("lance_knn_graph: sample must not be negative");
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::FLOAT};
+	names = {"src_row_id", "dst_row_id", "distance"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceKnnGraphInit(ClientContext &context, TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceKnnGraphState>();
+	auto &bind = input.bind_data->Cast<LanceKnnGraphBindData>();
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	state->edges = lance_idx.KnnGraph(bind.k, bind.sample);
+	return std::move(state);
+}
+
+static void LanceKnnGraphScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceKnnGraphState>();
+
+	if (state.position >= state.edges.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.edges.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &edge = state.edges[state.position + i];
+		output.SetValue(0, i, Value::BIGINT(edge.src));
+		output.SetValue(1, i, Value::BIGINT(edge.dst));
+		output.SetValue(2, i, Value::FLOAT(edge.distance));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceKnnGraphFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_knn_graph", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER},
+	                   LanceKnnGraphScan, LanceKnnGraphBind, LanceKnnGraphInit);
+	func.named_parameters["sample"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_row_addresses(table, index)
+// The Lance address of every indexed row: (row_id BIGINT, label BIGINT, lance_row_id UBIGINT,
+// fragment_id UINTEGER). Addresses change when compaction or a merge rewrites fragments; row_id is
+// NULL for labels with no DuckDB row.
+// ========================================
+
+struct LanceRowAddressesBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+struct LanceRowAddressesState : public GlobalTableFunctionState {
+	vector<LanceIndex::RowAddress> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceRowAddressesBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRowAddressesBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::UBIGINT, LogicalType::UINTEGER};
+	names = {"row_id", "label", "lance_row_id", "fragment_id"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceRowAddressesInit(ClientContext &context,
+                                                                  TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceRowAddressesState>();
+	auto &bind = input.bind_data->Cast<LanceRowAddressesBindData>();
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	state->rows = lance_idx.RowAddresses();
+	return std::move(state);
+}
+
+static void LanceRowAddressesScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceRowAddressesState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, row.row_id < 0 ? Value(LogicalType::BIGINT) : Value::BIGINT(row.row_id));
+		output.SetValue(1, i, Value::BIGINT(row.address.label));
+		output.SetValue(2, i, Value::UBIGINT(row.address.lance_row_id));
+		output.SetValue(3, i, Value::UINTEGER(row.address.fragment_id));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceRowAddressesFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_row_addresses", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceRowAddressesScan,
+	                   LanceRowAddressesBind, LanceRowAddressesInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_quota(table, index, spec)
+// Configure a row/size quota, e.g. 'max_rows=1000, on_exceed=evict_oldest(ts)'. Empty spec removes it.
+// ========================================
+
+struct LanceSetQuotaBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+struct LanceSetQuotaState : public GlobalTableFunctionState {
+	bool done = false;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSetQuotaBind(ClientContext &context, TableFunctionBindInput &input,
+                                                  vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetQuotaBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSetQuotaInit(ClientContext &context, TableFunctionInitInput &input) {
+	return make_uniq<LanceSetQuotaState>();
+}
+
+static void LanceSetQuotaScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetQuotaBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetQuota(bind.spec);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Quota removed" : "Quota set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetQuotaFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_quota", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetQuotaScan, LanceSetQuotaBind, LanceSetQuotaInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_cold_storage(table, index, spec)
+// Store old vectors at reduced precision, e.g. 'column=created_at, after_days=30, precision=f16'.
+// Compaction (VACUUM, or a compact step of lance_run_maintenance) moves rows whose time column is older
+// than after_days into a sidecar table storing their vectors as f16 or int8; searches still find them.
+// Empty spec removes the policy.
+// ========================================
+
+struct LanceSetColdStorageBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+static unique_ptr<FunctionData> LanceSetColdStorageBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetColdStorageBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetColdStorageScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetColdStorageBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetColdStorage(bind.spec);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Cold storage removed" : "Cold storage set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetColdStorageFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_cold_storage",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceSetColdStorageScan,
+	                   LanceSetColdStorageBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_chunking(table, index, spec)
+// Configure the chunking stage of documents ingested through the chunked path, e.g.
+// 'column=body, parent=doc_id, unit=tokens, size=256, overlap=32, embedder=minilm'. Empty spec removes it.
+// ========================================
+
+struct LanceSetChunkingBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+static unique_ptr<FunctionData> LanceSetChunkingBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetChunkingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetChunkingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetChunkingBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetChunking(bind.spec);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Chunking removed" : "Chunking set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetChunkingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_chunking", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetChunkingScan, LanceSetChunkingBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_constraints(table, index, spec)
+// Check metadata columns of inserted rows, e.g. 'author not null; category in (news, blog);
+// score between 0 and 1; on_violation=dead_letter'. on_violation is error (reject the insert), skip
+// (drop violating rows from the index) or dead_letter (drop them into lance_rejects). Empty spec removes them.
+// ========================================
+
+struct LanceSetConstraintsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+static unique_ptr<FunctionData> LanceSetConstraintsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetConstraintsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetConstraintsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetConstraintsBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetConstraints(bind.spec);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Constraints removed" : "Constraints set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetConstraintsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_constraints",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceSetConstraintsScan,
+	                   LanceSetConstraintsBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_on_invalid(table, index, mode)
+// What inserts do with rows the index cannot take: a NULL vector, NULL, NaN or infinite elements, or a
+// value that does not convert to its column type. mode is error (reject the insert, the default), skip
+// (leave the rows out of the index) or dead_letter (leave them out and record them in lance_rejects).
+// ========================================
+
+struct LanceSetOnInvalidBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string mode;
+};
+
+static unique_ptr<FunctionData> LanceSetOnInvalidBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetOnInvalidBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->mode = input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetOnInvalidScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetOnInvalidBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetOnInvalid(bind.mode);
+
+	output.data[0].SetValue(0, Value("Invalid rows: " + bind.mode));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetOnInvalidFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_on_invalid",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceSetOnInvalidScan,
+	                   LanceSetOnInvalidBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_column_matching(table, index, mode)
+// How inserts match their columns to the index's stored columns. mode is positional (by position, the
+// default), strict (by name; an unknown or missing column rejects the insert) or lenient (by name; missing
+// nullable columns are stored as NULL and unknown ones are ignored).
+// ========================================
+
+struct LanceSetColumnMatchingBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string mode;
+};
+
+static unique_ptr<FunctionData> LanceSetColumnMatchingBind(ClientContext &context, TableFunctionBindInput &input,
+                                                           vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetColumnMatchingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->mode = input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetColumnMatchingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetColumnMatchingBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetColumnMatching(bind.mode);
+
+	output.data[0].SetValue(0, Value("Column matching: " + bind.mode));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetColumnMatchingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_column_matching",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetColumnMatchingScan, LanceSetColumnMatchingBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_rejects(table, index)
+// Rows dropped by ingest constraints with on_violation=dead_letter, oldest first:
+// (rejected_at, source_row, reason, row). source_row is the row's position in its insert batch and row
+// renders its values as column=value pairs.
+// ========================================
+
+struct LanceRejectsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+struct LanceRejectsState : public GlobalTableFunctionState {
+	vector<LanceReject> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceRejectsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                 vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceRejectsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::TIMESTAMP, LogicalType::BIGINT, LogicalType::VARCHAR, LogicalType::VARCHAR};
+	names = {"rejected_at", "source_row", "reason", "row"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceRejectsInit(ClientContext &context, TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceRejectsState>();
+	auto &bind = input.bind_data->Cast<LanceRejectsBindData>();
+	state->rows = GetLanceIndex(context, bind.table_name, bind.index_name).GetRejects();
+	return std::move(state);
+}
+
+static void LanceRejectsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceRejectsState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value::TIMESTAMP(Timestamp::FromEpochMs(row.rejected_ms)));
+		output.SetValue(1, i, Value::BIGINT(row.source_row));
+		output.SetValue(2, i, Value(row.reason));
+		output.SetValue(3, i, Value(row.row));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceRejectsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_rejects", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceRejectsScan,
+	                   LanceRejectsBind, LanceRejectsInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_retention(table, index, max_rows, order_by := 'label', batch := 1)
+// Keep at most max_rows rows, evicting the oldest by order_by once batch rows are over.
+// Shorthand for a max_rows quota with the evict_oldest policy.
+// ========================================
+
+struct LanceSetRetentionBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+static unique_ptr<FunctionData> LanceSetRetentionBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetRetentionBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	auto max_rows = input.inputs[2].GetValue<int64_t>();
+	if (max_rows <= 0) {
+		throw InvalidInputException("max_rows must be positive");
+	}
+	string order_by = "label";
+	int64_t batch = 1;
+	for (auto &kv : input.named_parameters) {
+		if (kv.first == "order_by") {
+			order_by = kv.second.GetValue<string>();
+		} else if (kv.first == "batch") {
+			batch = kv.second.GetValue<int64_t>();
+			if (batch <= 0) {
+				throw InvalidInputException("batch must be positive");
+			}
+		}
+	}
+	bind_data->spec = "max_rows=" + std::to_string(max_rows) + ", on_exceed=evict_oldest(" + order_by +
+	                  "), evict_batch=" + std::to_string(batch);
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetRetentionScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetRetentionBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetQuota(bind.spec);
+
+	output.data[0].SetValue(0, Value("Retention set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetRetentionFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_retention", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::BIGINT},
+	                   LanceSetRetentionScan, LanceSetRetentionBind, LanceSetQuotaInit);
+	func.named_parameters["order_by"] = LogicalType::VARCHAR;
+	func.named_parameters["batch"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_access_tracking(table, index, enabled)
+// Record search hits per row for usage-aware eviction (evict_lru) and lance_cold_rows.
+// ========================================
+
+struct LanceSetAccessTrackingBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	bool enabled;
+};
+
+static unique_ptr<FunctionData> LanceSetAccessTrackingBind(ClientContext &context, TableFunctionBindInput &input,
+                                                           vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetAccessTrackingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->enabled = input.inputs[2].GetValue<bool>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetAccessTrackingScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetAccessTrackingBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetAccessTracking(bind.enabled);
+
+	output.data[0].SetValue(0, Value(bind.enabled ? "Access tracking enabled" : "Access tracking disabled"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetAccessTrackingFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_access_tracking",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::BOOLEAN}, LanceSetAccessTrackingScan,
+	                   LanceSetAccessTrackingBind, LanceSetQuotaInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_row_ttl(table, index, enabled)
+// Hide rows whose expires_at column (TIMESTAMP, or BIGINT epoch milliseconds) is in the past from
+// every search, even before they are deleted. expires_at must be one of the index's columns.
+// ========================================
+
+static unique_ptr<FunctionData> LanceSetRowTtlBind(ClientContext &context, TableFunctionBindInput &input,
+                                                   vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetAccessTrackingBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->enabled = input.inputs[2].GetValue<bool>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetRowTtlScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetAccessTrackingBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetRowTtl(bind.enabled);
+
+	output.data[0].SetValue(0, Value(bind.enabled ? "Row expiry enabled" : "Row expiry disabled"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetRowTtlFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_row_ttl", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::BOOLEAN},
+	                   LanceSetRowTtlScan, LanceSetRowTtlBind, LanceSetQuotaInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_train_pca(table, index, target_dims, sample := 10000, index_type := NULL)
+// Fit a PCA projection to target_dims on the first `sample` vectors and store each row's projection
+// in a reduced column, searched with lance_search_pca. index_type (ivf_pq, ivf_hnsw_sq) also builds
+// an index on the reduced column. Returns (target_dims INTEGER, explained_variance DOUBLE).
+// ========================================
+
+struct LanceTrainPcaBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t target_dims = 0;
+	int64_t sample = 10000;
+	int32_t index_type = -1;
+};
+
+static unique_ptr<FunctionData> LanceTrainPcaBind(ClientContext &context, TableFunctionBindInput &input,
+                                                  vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceTrainPcaBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->target_dims = input.inputs[2].GetValue<int32_t>();
+	if (bind_data->target_dims <= 0) {
+		throw InvalidInputException("lance_train_pca: target_dims must be positive");
+	}
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "sample") {
+			bind_data->sample = param.second.GetValue<int64_t>();
+		} else if (param.first == "index_type") {
+			bind_data->index_type = ParseVectorIndexType(param.second.GetValue<string>());
+		}
+	}
+
+	return_types = {LogicalType::INTEGER, LogicalType::DOUBLE};
+	names = {"target_dims", "explained_variance"};
+	return std::move(bind_data);
+}
+
+static void LanceTrainPcaScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceTrainPcaBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto explained_variance = lance_idx.TrainPca(bind.target_dims, bind.sample, bind.index_type);
+
+	output.data[0].SetValue(0, Value::INTEGER(bind.target_dims));
+	output.data[1].SetValue(0, Value::DOUBLE(explained_variance));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceTrainPcaFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_train_pca", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER},
+	                   LanceTrainPcaScan, LanceTrainPcaBind, LanceSetQuotaInit);
+	func.named_parameters["sample"] = LogicalType::BIGINT;
+	func.named_parameters["index_type"] = LogicalType::VARCHAR;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_backup(table, index, dest, since_version := NULL)
+// Copy the table's files into the Lance dataset directory dest (a path or object store URI). With
+// since_version, only what later versions added is copied, so nightly backups chained into one dest
+// stay cheap; dest must already hold since_version. Any backed-up version can be restored from dest.
+// Returns (version BIGINT, files BIGINT, bytes BIGINT): the latest version backed up and what was copied.
+// ========================================
+
+struct LanceBackupBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string dest;
+	int64_t since_version = -1;
+};
+
+static unique_ptr<FunctionData> LanceBackupBind(ClientContext &context, TableFunctionBindInput &input,
+                                                vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceBackupBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->dest = input.inputs[2].GetValue<string>();
+	auto it = input.named_parameters.find("since_version");
+	if (it != input.named_parameters.end() && !it->second.IsNull()) {
+		bind_data->since_version = it->second.GetValue<int64_t>();
+		if (bind_data->since_version < 0) {
+			throw InvalidInputException("lance_backup: since_version must not be negative");
+		}
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::BIGINT};
+	names = {"version", "files", "bytes"};
+	return std::move(bind_data);
+}
+
+static void LanceBackupScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceBackupBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	auto result = lance_idx.Backup(bind.dest, bind.since_version);
+
+	output.data[0].SetValue(0, Value::BIGINT(result.version));
+	output.data[1].SetValue(0, Value::BIGINT(result.files));
+	output.data[2].SetValue(0, Value::BIGINT(result.bytes));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceBackupFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_backup", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceBackupScan, LanceBackupBind, LanceSetQuotaInit);
+	func.named_parameters["since_version"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_label_bitmap(table, index)
+// The labels of the index's rows as a roaring bitmap (portable 64-bit serialization), for checking references
+// and diffing versions with lance_label_bitmap_andnot & co. instead of per-label queries. Returns
+// (version BIGINT, labels BIGINT, bitmap BLOB): the table version read and the label count.
+// ========================================
+
+struct LanceLabelBitmapBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LanceLabelBitmapBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceLabelBitmapBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::BLOB};
+	names = {"version", "labels", "bitmap"};
+	return std::move(bind_data);
+}
+
+static void LanceLabelBitmapScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceLabelBitmapBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto bitmap = GetLanceIndex(context, bind.table_name, bind.index_name).LabelBitmap();
+	output.SetValue(0, 0, Value::BIGINT(bitmap.version));
+	output.SetValue(1, 0, Value::BIGINT(bitmap.labels));
+	output.SetValue(2, 0, Value::BLOB(const_data_ptr_cast(bitmap.bitmap.data()), bitmap.bitmap.size()));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceLabelBitmapFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_label_bitmap", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceLabelBitmapScan,
+	                   LanceLabelBitmapBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_export_manifest(table, index)
+// Export the manifest of the table version the index sees as a small blob. A read replica attached to the
+// same object storage passes it to lance_import_manifest to advance to that version without listing the
+// store. Returns (version BIGINT, manifest BLOB).
+// ========================================
+
+struct LanceExportManifestBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LanceExportManifestBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceExportManifestBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT, LogicalType::BLOB};
+	names = {"version", "manifest"};
+	return std::move(bind_data);
+}
+
+static void LanceExportManifestScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceExportManifestBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto blob = GetLanceIndex(context, bind.table_name, bind.index_name).ExportManifest();
+	output.SetValue(0, 0, Value::BIGINT(blob.version));
+	output.SetValue(1, 0, Value::BLOB(const_data_ptr_cast(blob.blob.data()), blob.blob.size()));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceExportManifestFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_export_manifest", {LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceExportManifestScan, LanceExportManifestBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_format_info(table, index)
+// Report the storage format of the table's current version: the manifest version, the Lance release that
+// wrote it, this build's Lance release, the declared storage format and the data file versions in use,
+// the feature flags, and whether this build can read and write the table. A table written with features
+// this build does not know is opened read-only; problems says why.
+// ========================================
+
+struct LanceFormatInfoBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+};
+
+static unique_ptr<FunctionData> LanceFormatInfoBind(ClientContext &context, TableFunctionBindInput &input,
+                                                    vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceFormatInfoBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+
+	return_types = {LogicalType::BIGINT,  LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR,
+	                LogicalType::VARCHAR, LogicalType::BIGINT,  LogicalType::BIGINT,  LogicalType::BOOLEAN,
+	                LogicalType::BOOLEAN, LogicalType::BOOLEAN, LogicalType::VARCHAR};
+	names = {"version",      "writer",   "library",   "storage_format",   "file_versions", "reader_flags",
+	         "writer_flags", "can_read", "can_write", "written_by_newer", "problems"};
+	return std::move(bind_data);
+}
+
+static void LanceFormatInfoScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceFormatInfoBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto info = GetLanceIndex(context, bind.table_name, bind.index_name).FormatInfo();
+	output.SetValue(0, 0, Value::BIGINT(info.version));
+	output.SetValue(1, 0, info.writer.empty() ? Value() : Value(info.writer));
+	output.SetValue(2, 0, Value(info.library));
+	output.SetValue(3, 0, Value(info.storage_format));
+	output.SetValue(4, 0, Value(info.file_versions));
+	output.SetValue(5, 0, Value::BIGINT(info.reader_flags));
+	output.SetValue(6, 0, Value::BIGINT(info.writer_flags));
+	output.SetValue(7, 0, Value::BOOLEAN(info.can_read));
+	output.SetValue(8, 0, Value::BOOLEAN(info.can_write));
+	output.SetValue(9, 0, Value::BOOLEAN(info.written_by_newer));
+	output.SetValue(10, 0, info.problems.empty() ? Value() : Value(info.problems));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceFormatInfoFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_format_info", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceFormatInfoScan,
+	                   LanceFormatInfoBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_import_manifest(table, index, manifest)
+// Move the index's view to the table version of a blob from lance_export_manifest, after checking it against
+// the manifest stored for that version. The index becomes a read-only replica: it stays on the imported
+// version until the next import, which must not go back, and rejects writes. Returns (version).
+// ========================================
+
+struct LanceImportManifestBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string manifest;
+};
+
+static unique_ptr<FunctionData> LanceImportManifestBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceImportManifestBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	if (input.inputs[2].IsNull()) {
+		throw InvalidInputException("lance_import_manifest: manifest must not be NULL");
+	}
+	bind_data->manifest = input.inputs[2].GetValueUnsafe<string>();
+
+	return_types = {LogicalType::BIGINT};
+	names = {"version"};
+	return std::move(bind_data);
+}
+
+static void LanceImportManifestScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceImportManifestBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto version = GetLanceIndex(context, bind.table_name, bind.index_name).ImportManifest(bind.manifest);
+	output.SetValue(0, 0, Value::BIGINT(version));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceImportManifestFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_import_manifest", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::BLOB},
+	                   LanceImportManifestScan, LanceImportManifestBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_train_opq(table, index, num_sub_vectors, sample := 10000)
+// Train an OPQ-style rotation on the first `sample` vectors that spreads variance evenly over
+// num_sub_vectors PQ sub-vectors. Stored vectors are rewritten rotated; later inserts and queries
+// are rotated on the way in. Distances are unchanged, so rebuild the IVF_PQ index with the same
+// num_sub_vectors to improve its recall.
+// ========================================
+
+struct LanceTrainOpqBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int32_t num_sub_vectors = 0;
+	int64_t sample = 10000;
+};
+
+static unique_ptr<FunctionData> LanceTrainOpqBind(ClientContext &context, TableFunctionBindInput &input,
+                                                  vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceTrainOpqBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->num_sub_vectors = input.inputs[2].GetValue<int32_t>();
+	if (bind_data->num_sub_vectors <= 0) {
+		throw InvalidInputException("lance_train_opq: num_sub_vectors must be positive");
+	}
+	auto sample = input.named_parameters.find("sample");
+	if (sample != input.named_parameters.end() && !sample->second.IsNull()) {
+		bind_data->sample = sample->second.GetValue<int64_t>();
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceTrainOpqScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceTrainOpqBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.TrainRotation(bind.num_sub_vectors, bind.sample);
+
+	output.data[0].SetValue(0, Value("Rotation trained; rebuild the IVF_PQ index"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceTrainOpqFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_train_opq", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::INTEGER},
+	                   LanceTrainOpqScan, LanceTrainOpqBind, LanceSetQuotaInit);
+	func.named_parameters["sample"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_writer_lease(table, index, ttl_seconds)
+// Require writers to hold an advisory lease on the table, so a second process ingesting into
+// the same local table fails fast instead of churning on commit conflicts. The lease expires
+// ttl_seconds after its holder's last write; 0 removes the requirement.
+// ========================================
+
+struct LanceSetWriterLeaseBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int64_t ttl_seconds = 0;
+};
+
+static unique_ptr<FunctionData> LanceSetWriterLeaseBind(ClientContext &context, TableFunctionBindInput &input,
+                                                        vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetWriterLeaseBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->ttl_seconds = input.inputs[2].GetValue<int64_t>();
+	if (bind_data->ttl_seconds < 0) {
+		throw InvalidInputException("lance_set_writer_lease: ttl_seconds must not be negative");
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetWriterLeaseScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetWriterLeaseBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetWriterLease(bind.ttl_seconds);
+
+	output.data[0].SetValue(0, Value(bind.ttl_seconds > 0 ? "Writer lease enabled" : "Writer lease disabled"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetWriterLeaseFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_writer_lease", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::BIGINT},
+	                   LanceSetWriterLeaseScan, LanceSetWriterLeaseBind, LanceSetQuotaInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_sensitive_columns(table, index, columns)
+// Mark index columns as non-exportable: unprivileged scans and column stats skip or reject
+// them. The list replaces the previous one; an empty list clears it.
+// ========================================
+
+struct LanceSetSensitiveColumnsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	vector<string> columns;
+};
+
+static unique_ptr<FunctionData> LanceSetSensitiveColumnsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                             vector<LogicalType> &return_types,
+                                                             vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetSensitiveColumnsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->columns.push_back(child.GetValue<string>());
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetSensitiveColumnsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetSensitiveColumnsBindData>();
+	auto &state = data.global_state->Cast<LanceSetQuotaState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	lance_idx.SetSensitiveColumns(bind.columns);
+
+	output.data[0].SetValue(0, Value(bind.columns.empty() ? "Sensitive columns cleared" : "Sensitive columns set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetSensitiveColumnsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_sensitive_columns",
+	                   {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::VARCHAR)},
+	                   LanceSetSensitiveColumnsScan, LanceSetSensitiveColumnsBind, LanceSetQuotaInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_cold_rows(table, index, idle_seconds := 0)
+// Returns (row_id, hits, last_access) for rows not searched within idle_seconds,
+// least recently used first. last_access is NULL for rows never returned by a search.
+// ========================================
+
+struct LanceColdRowsBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	int64_t idle_seconds = 0;
+};
+
+struct LanceColdRowsState : public GlobalTableFunctionState {
+	vector<pair<row_t, LanceColdRow>> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceColdRowsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                  vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceColdRowsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	auto it = input.named_parameters.find("idle_seconds");
+	if (it != input.named_parameters.end()) {
+		bind_data->idle_seconds = it->second.GetValue<int64_t>();
+	}
+
+	return_types.push_back(LogicalType::BIGINT);
+	return_types.push_back(LogicalType::BIGINT);
+	return_types.push_back(LogicalType::TIMESTAMP);
+	names.push_back("row_id");
+	names.push_back("hits");
+	names.push_back("last_access");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceColdRowsInit(ClientContext &context, TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceColdRowsState>();
+	auto &bind = input.bind_data->Cast<LanceColdRowsBindData>();
+	auto &lance_idx = GetLanceIndex(context, bind.table_name, bind.index_name);
+	state->rows = lance_idx.GetColdRows(bind.idle_seconds * 1000);
+	return std::move(state);
+}
+
+static void LanceColdRowsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceColdRowsState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value::BIGINT(row.first));
+		output.SetValue(1, i, Value::BIGINT(row.second.hits));
+		output.SetValue(2, i,
+		                row.second.hits == 0 ? Value(LogicalType::TIMESTAMP)
+		                                     : Value::TIMESTAMP(Timestamp::FromEpochMs(row.second.last_access_ms)));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceColdRowsFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_cold_rows", {LogicalType::VARCHAR, LogicalType::VARCHAR}, LanceColdRowsScan,
+	                   LanceColdRowsBind, LanceColdRowsInit);
+	func.named_parameters["idle_seconds"] = LogicalType::BIGINT;
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_set_query_log(table, index, spec)
+// Log every search of the index for offline evaluation, e.g. 'tag=baseline, vectors=full': the query vector
+// (or only its hash with vectors=hash), filter, k and returned rows are appended to a sidecar Lance table.
+// Empty spec stops logging.
+// ========================================
+
+struct LanceSetQueryLogBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string spec;
+};
+
+static unique_ptr<FunctionData> LanceSetQueryLogBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSetQueryLogBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->spec = input.inputs[2].IsNull() ? string() : input.inputs[2].GetValue<string>();
+
+	return_types.push_back(LogicalType::VARCHAR);
+	names.push_back("status");
+	return std::move(bind_data);
+}
+
+static void LanceSetQueryLogScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &bind = data.bind_data->Cast<LanceSetQueryLogBindData>();
+	auto &state = data.global_state->Cast<LanceCreateAnnState>();
+
+	if (state.done) {
+		output.SetCardinality(0);
+		return;
+	}
+	state.done = true;
+
+	GetLanceIndex(context, bind.table_name, bind.index_name).SetQueryLog(bind.spec);
+
+	output.data[0].SetValue(0, Value(bind.spec.empty() ? "Query log stopped" : "Query log set"));
+	output.SetCardinality(1);
+}
+
+void RegisterLanceSetQueryLogFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_set_query_log", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceSetQueryLogScan, LanceSetQueryLogBind, LanceCreateAnnInit);
+	loader.RegisterFunction(func);
+}
+
+// ========================================
+// lance_replay_queries(table, index, tag)
+// Re-run the searches logged under tag against the index as it is now, with their logged filter and
+// parameters. Returns (logged_at, query_hash, logged, returned, overlap, top_changed) per replayed search:
+// overlap is the fraction of the logged results still returned, top_changed whether the nearest result
+// changed. Searches logged with vectors=hash are not replayed.
+// ========================================
+
+struct LanceReplayQueriesBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	string tag;
+};
+
+struct LanceReplayQueriesState : public GlobalTableFunctionState {
+	vector<LanceQueryDrift> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceReplayQueriesBind(ClientContext &context, TableFunctionBindInput &input,
+                                                       vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceReplayQueriesBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->tag = input.inputs[2].GetValue<string>();
+
+	return_types = {LogicalType::TIMESTAMP, LogicalType::VARCHAR, LogicalType::BIGINT,
+	                LogicalType::BIGINT,    LogicalType::DOUBLE,  LogicalType::BOOLEAN};
+	names = {"logged_at", "query_hash", "logged", "returned", "overlap", "top_changed"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceReplayQueriesInit(ClientContext &context,
+                                                                   TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceReplayQueriesState>();
+	auto &bind = input.bind_data->Cast<LanceReplayQueriesBindData>();
+	state->rows = GetLanceIndex(context, bind.table_name, bind.index_name).ReplayQueries(bind.tag);
+	return std::move(state);
+}
+
+static void LanceReplayQueriesScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceReplayQueriesState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value::TIMESTAMP(Timestamp::FromEpochMs(row.logged_ms)));
+		output.SetValue(1, i, Value(row.query_hash));
+		output.SetValue(2, i, Value::BIGINT(row.logged));
+		output.SetValue(3, i, Value::BIGINT(row.returned));
+		output.SetValue(4, i, Value::DOUBLE(row.overlap));
+		output.SetValue(5, i, Value::BOOLEAN(row.top_changed));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceReplayQueriesFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_replay_queries", {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::VARCHAR},
+	                   LanceReplayQueriesScan, LanceReplayQueriesBind, LanceReplayQueriesInit);
 	loader.RegisterFunction(func);
 }
 
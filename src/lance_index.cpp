@@ -5,6 +5,7 @@
 #include "duckdb/catalog/catalog_entry/duck_index_entry.hpp"
 #include "duckdb/catalog/catalog_entry/duck_table_entry.hpp"
 #include "duckdb/catalog/catalog_entry/table_catalog_entry.hpp"
+#include "duckdb/common/string_util.hpp"
 #include "duckdb/common/types/vector.hpp"
 #include "duckdb/execution/index/fixed_size_allocator.hpp"
 #include "duckdb/main/attached_database.hpp"
@@ -45,6 +46,18 @@ static string SanitizeIndexName(const string &name) {
 	return result;
 }
 
+// WITH (refine_factor = ...) accepts a count or 'auto' (stored as LANCE_REFINE_AUTO)
+static int32_t ParseRefineFactor(const Value &value) {
+	if (value.type().id() == LogicalTypeId::VARCHAR && StringUtil::Lower(value.ToString()) == "auto") {
+		return LANCE_REFINE_AUTO;
+	}
+	auto factor = value.GetValue<int32_t>();
+	if (factor < 0 && factor != LANCE_REFINE_AUTO) {
+		throw InvalidInputException("refine_factor must be 'auto' or a non-negative integer");
+	}
+	return factor;
+}
+
 // Generate a unique temp directory path for in-memory DBs
 static string MakeUniqueTempPath(const string &sanitized_name) {
 	const char *tmp_dir = std::getenv("TMPDIR");
@@ -160,9 +173,23 @@ LanceIndex::LanceIndex(const string &name, IndexConstraintType constraint_type,
 		} else if (kv.first == "nprobes") {
 			nprobes_ = kv.second.GetValue<int32_t>();
 		} else if (kv.first == "refine_factor") {
-			refine_factor_ = kv.second.GetValue<int32_t>();
+			refine_factor_ = ParseRefineFactor(kv.second);
+		} else if (kv.first == "model") {
+			model_ = kv.second.ToString();
+		} else if (kv.first == "storage") {
+			storage_ = kv.second.ToString();
+		} else if (kv.first == "int8_range") {
+			for (auto &bound : ListValue::GetChildren(kv.second)) {
+				int8_range_.push_back(bound.GetValue<float>());
+			}
+			if (int8_range_.size() != 2) {
+				throw InvalidInputException("int8_range must be [min, max]");
+			}
 		}
 	}
+	if (storage_ == "int8" && int8_range_.empty()) {
+		throw InvalidInputException("storage = 'int8' needs int8_range = [min, max]");
+	}
 
 	// Detect dimension from expression type
 	if (!unbound_expressions.empty()) {
@@ -190,6 +217,10 @@ LanceIndex::LanceIndex(const string &name, IndexConstraintType constraint_type,
 }
 
 LanceIndex::~LanceIndex() {
+	if (staging_handle_) {
+		LanceFreeDetached(staging_handle_);
+		staging_handle_ = nullptr;
+	}
 	if (rust_handle_) {
 		LanceFreeDetached(rust_handle_);
 		rust_handle_ = nullptr;
@@ -276,206 +307,1129 @@ ErrorData LanceIndex::Append(IndexLock &lock, DataChunk &entries, Vector &row_id
 		return ErrorData {};
 	}
 
-	DataChunk expr_chunk;
-	expr_chunk.Initialize(Allocator::DefaultAllocator(), logical_types);
-	ExecuteExpressions(entries, expr_chunk);
+	DataChunk expr_chunk;
+	expr_chunk.Initialize(Allocator::DefaultAllocator(), logical_types);
+	ExecuteExpressions(entries, expr_chunk);
+
+	if (!rust_handle_) {
+		auto lance_path = GetLancePath();
+		table_name_ = SanitizeIndexName(name);
+		if (has_extra_columns_) {
+			// Build ArrowSchema for multi-column table creation
+			vector<LogicalType> col_types;
+			vector<string> col_names;
+			col_names.push_back("vector");
+			col_types.push_back(expr_chunk.data[0].GetType());
+			for (idx_t i = 0; i < extra_column_names_.size(); i++) {
+				col_names.push_back(extra_column_names_[i]);
+				col_types.push_back(extra_column_types_[i]);
+			}
+
+			ArrowSchema create_schema;
+			memset(&create_schema, 0, sizeof(ArrowSchema));
+			auto db_shared = db.GetDatabase().shared_from_this();
+			auto temp_ctx = make_shared_ptr<ClientContext>(db_shared);
+			auto client_props = temp_ctx->GetClientProperties();
+			ArrowConverter::ToArrowSchema(&create_schema, col_types, col_names, client_props);
+
+			rust_handle_ = LanceCreateDetachedFromArrow(lance_path, &create_schema, metric_, table_name_, "", storage_);
+
+			if (create_schema.release) {
+				create_schema.release(&create_schema);
+			}
+		} else {
+			rust_handle_ = LanceCreateDetached(lance_path, dimension_, metric_, table_name_, storage_);
+		}
+		if (!model_.empty()) {
+			LanceDetachedSetEmbeddingModel(rust_handle_, model_);
+		}
+		if (!int8_range_.empty()) {
+			LanceDetachedSetInt8Range(rust_handle_, int8_range_[0], int8_range_[1]);
+		}
+	}
+
+	UnifiedVectorFormat rowid_format;
+	row_identifiers.ToUnifiedFormat(count, rowid_format);
+	auto rowid_data = reinterpret_cast<row_t *>(rowid_format.data);
+
+	vector<int64_t> labels(count);
+	int32_t n;
+
+	if (has_extra_columns_) {
+		// Arrow C Data Interface path: zero-copy via ArrowConverter with temp ClientContext
+		idx_t data_col_count = expr_chunk.ColumnCount();
+		vector<LogicalType> arrow_types;
+		vector<string> col_names;
+		col_names.push_back("vector");
+		arrow_types.push_back(expr_chunk.data[0].GetType());
+		for (idx_t i = 0; i < extra_column_names_.size(); i++) {
+			col_names.push_back(extra_column_names_[i]);
+			arrow_types.push_back(expr_chunk.data[i + 1].GetType());
+		}
+
+		DataChunk arrow_chunk;
+		arrow_chunk.Initialize(Allocator::DefaultAllocator(), arrow_types);
+		for (idx_t i = 0; i < arrow_types.size(); i++) {
+			arrow_chunk.data[i].Reference(expr_chunk.data[i]);
+		}
+		arrow_chunk.SetCardinality(count);
+
+		ArrowSchema arrow_schema;
+		ArrowArray arrow_array;
+		memset(&arrow_schema, 0, sizeof(ArrowSchema));
+		memset(&arrow_array, 0, sizeof(ArrowArray));
+
+		// Create temporary ClientContext for Arrow conversion (BoundIndex::Append has no ClientContext)
+		auto db_shared = db.GetDatabase().shared_from_this();
+		auto temp_ctx = make_shared_ptr<ClientContext>(db_shared);
+		auto client_props = temp_ctx->GetClientProperties();
+
+		ArrowConverter::ToArrowSchema(&arrow_schema, arrow_types, col_names, client_props);
+		unordered_map<idx_t, const shared_ptr<ArrowTypeExtensionData>> ext_types;
+		ArrowConverter::ToArrowArray(arrow_chunk, &arrow_array, client_props, ext_types);
+
+		n = LanceDetachedAddBatchArrow(rust_handle_, &arrow_schema, &arrow_array, labels.data(),
+		                               model_.empty() ? nullptr : model_.c_str());
+
+		// Release schema (Rust consumed the array)
+		if (arrow_schema.release) {
+			arrow_schema.release(&arrow_schema);
+		}
+	} else {
+		// Vector-only fast path
+		auto &vec_col = expr_chunk.data[0];
+		auto &array_child = ArrayVector::GetEntry(vec_col);
+		auto child_data = FlatVector::GetData<float>(array_child);
+
+		n = LanceDetachedAddBatch(rust_handle_, child_data, static_cast<int32_t>(count), dimension_, labels.data());
+	}
+
+	for (idx_t i = 0; i < static_cast<idx_t>(n); i++) {
+		auto row_idx = rowid_format.sel->get_index(i);
+		auto row_id = rowid_data[row_idx];
+		auto label = labels[i];
+		// Dropped by an ingest constraint
+		if (label < 0) {
+			continue;
+		}
+
+		if (static_cast<idx_t>(label) >= label_to_rowid_.size()) {
+			label_to_rowid_.resize(label + 1, -1);
+		}
+		label_to_rowid_[label] = row_id;
+		rowid_to_label_[row_id] = label;
+	}
+
+	staging_stale_ = true;
+	is_dirty_ = true;
+	return ErrorData {};
+}
+
+ErrorData LanceIndex::Insert(IndexLock &lock, DataChunk &data, Vector &row_ids) {
+	return Append(lock, data, row_ids);
+}
+
+void LanceIndex::Delete(IndexLock &lock, DataChunk &entries, Vector &row_identifiers) {
+	auto count = entries.size();
+	if (count == 0) {
+		return;
+	}
+
+	UnifiedVectorFormat rowid_format;
+	row_identifiers.ToUnifiedFormat(count, rowid_format);
+	auto rowid_data = reinterpret_cast<row_t *>(rowid_format.data);
+
+	// Collect labels to delete in a single batch
+	vector<int64_t> labels_to_delete;
+	labels_to_delete.reserve(count);
+
+	for (idx_t i = 0; i < count; i++) {
+		auto row_idx = rowid_format.sel->get_index(i);
+		auto row_id = rowid_data[row_idx];
+
+		auto it = rowid_to_label_.find(row_id);
+		if (it != rowid_to_label_.end()) {
+			auto label = it->second;
+			labels_to_delete.push_back(label);
+			// Mark slot as deleted in forward mapping
+			if (label >= 0 && static_cast<idx_t>(label) < label_to_rowid_.size()) {
+				label_to_rowid_[label] = static_cast<row_t>(-1);
+			}
+			rowid_to_label_.erase(it);
+		}
+	}
+
+	if (rust_handle_ && !labels_to_delete.empty()) {
+		LanceDetachedDeleteBatch(rust_handle_, labels_to_delete.data(), static_cast<int32_t>(labels_to_delete.size()));
+		has_pending_deletes_ = true;
+		staging_stale_ = true;
+	}
+
+	is_dirty_ = true;
+}
+
+void LanceIndex::CommitDrop(IndexLock &lock) {
+	if (staging_handle_) {
+		LanceFreeDetached(staging_handle_);
+		staging_handle_ = nullptr;
+	}
+	if (rust_handle_) {
+		LanceFreeDetached(rust_handle_);
+		rust_handle_ = nullptr;
+	}
+	// Clean up Lance directory
+	auto lance_path = GetLancePath();
+	auto &fs = FileSystem::GetFileSystem(db.GetDatabase());
+	fs.RemoveDirectory(lance_path);
+}
+
+// ========================================
+// Search
+// ========================================
+
+vector<pair<row_t, float>> LanceIndex::Search(const float *query, int32_t dimension, int32_t k,
+                                              const string &predicate, const string &model,
+                                              const vector<float> &weights, bool weight_query, int32_t ef) {
+	// A query transform (e.g. slice) may accept queries of another dimension
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	// Lance already excludes deleted vectors (Delete() calls LanceDetachedDeleteBatch),
+	// so no C++ tombstone filtering or retry loop needed.
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearch(rust_handle_, query, dimension, k, nprobes_, refine_factor_,
+	                             predicate.empty() ? nullptr : predicate.c_str(), labels.data(), distances.data(),
+	                             model.empty() ? nullptr : model.c_str(), weights.empty() ? nullptr : weights.data(),
+	                             static_cast<int32_t>(weights.size()), weight_query, ef);
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchDiversity(const float *query, int32_t dimension, int32_t k,
+                                                       const string &predicate, const string &column,
+                                                       LanceSearchDiversity &diversity, const string &model,
+                                                       int32_t ef) {
+	diversity = {NAN, column.empty() ? -1 : 0};
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchDiversity(rust_handle_, query, dimension, k, nprobes_, refine_factor_, ef,
+	                                      predicate.empty() ? nullptr : predicate.c_str(),
+	                                      column.empty() ? nullptr : column.c_str(), labels.data(), distances.data(),
+	                                      diversity, model.empty() ? nullptr : model.c_str());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchScoped(const float *query, int32_t dimension, int32_t k,
+                                                    const string &scope, const string &model) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	auto scoped = LanceOpenDetached(GetLancePath(), table_name_, metric_, scope);
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	int32_t n;
+	try {
+		n = LanceDetachedSearch(scoped, query, dimension, k, nprobes_, refine_factor_, nullptr, labels.data(),
+		                        distances.data(), model.empty() ? nullptr : model.c_str());
+	} catch (...) {
+		LanceFreeDetached(scoped);
+		throw;
+	}
+	LanceFreeDetached(scoped);
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchConsistent(const float *query, int32_t dimension, int32_t k,
+                                                        const string &consistency, const string &model) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchConsistent(rust_handle_, query, dimension, k, nprobes_, refine_factor_, consistency,
+	                                       labels.data(), distances.data(), model.empty() ? nullptr : model.c_str());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<LanceIndex::BatchHit> LanceIndex::SearchBatch(const float *queries, int32_t dimension, int32_t num_queries,
+                                                     int32_t k) {
+	if (!rust_handle_ || num_queries <= 0 || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	auto capacity = static_cast<idx_t>(num_queries) * k;
+	vector<int32_t> query_idx(capacity);
+	vector<int64_t> labels(capacity);
+	vector<float> distances(capacity);
+	auto n = LanceDetachedSearchBatch(rust_handle_, queries, dimension, num_queries, k, nprobes_, refine_factor_, 0,
+	                                  query_idx.data(), labels.data(), distances.data());
+
+	vector<BatchHit> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.push_back({query_idx[i], label_to_rowid_[label], distances[i]});
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchRange(const float *query, int32_t dimension, int32_t k,
+                                                   float min_distance, float max_distance, const string &model) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchRange(rust_handle_, query, dimension, k, nprobes_, refine_factor_, min_distance,
+	                                  max_distance, nullptr, labels.data(), distances.data(),
+	                                  model.empty() ? nullptr : model.c_str());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchWithNegatives(const float *query, int32_t dimension, int32_t k,
+                                                           const vector<float> &negatives, float weight,
+                                                           const string &model) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+	if (negatives.size() % dimension != 0) {
+		throw InvalidInputException("Negative vectors must have the query dimension %d", dimension);
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchWithNegatives(rust_handle_, query, dimension, negatives.data(),
+	                                          static_cast<int32_t>(negatives.size() / dimension), weight, k, nprobes_,
+	                                          refine_factor_, nullptr, labels.data(), distances.data(),
+	                                          model.empty() ? nullptr : model.c_str());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchDedup(const float *query, int32_t dimension, int32_t k,
+                                                   const string &dedup_column, const string &model) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchDedup(rust_handle_, query, dimension, k, dedup_column, nprobes_, refine_factor_,
+	                                  nullptr, labels.data(), distances.data(), model.empty() ? nullptr : model.c_str());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<LanceIndex::ParentHit> LanceIndex::SearchParents(const float *query, int32_t dimension, int32_t k,
+                                                        const string &parent_column) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> parents(k);
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchParents(rust_handle_, query, dimension, k,
+	                                    parent_column.empty() ? nullptr : parent_column.c_str(), nprobes_,
+	                                    refine_factor_, parents.data(), labels.data(), distances.data());
+
+	vector<ParentHit> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.push_back(ParentHit {parents[i], label_to_rowid_[label], distances[i]});
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchLikeRows(const vector<row_t> &row_ids,
+                                                      const vector<row_t> &negative_row_ids, float weight,
+                                                      int32_t k) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	auto to_labels = [&](const vector<row_t> &ids) {
+		vector<int64_t> labels;
+		labels.reserve(ids.size());
+		for (auto row_id : ids) {
+			auto it = rowid_to_label_.find(row_id);
+			if (it == rowid_to_label_.end()) {
+				throw InvalidInputException("Row %lld is not in the Lance index", static_cast<long long>(row_id));
+			}
+			labels.push_back(it->second);
+		}
+		return labels;
+	};
+	auto positives = to_labels(row_ids);
+	auto negatives = to_labels(negative_row_ids);
+	if (k <= 0) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchLikeLabels(rust_handle_, positives.data(), static_cast<int32_t>(positives.size()),
+	                                       negatives.data(), static_cast<int32_t>(negatives.size()), weight, k,
+	                                       nprobes_, refine_factor_, labels.data(), distances.data());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<LanceIndex::SampledHit> LanceIndex::SearchSampled(const float *query, int32_t dimension, int32_t k,
+                                                         double fraction, double &sampled_fraction) {
+	sampled_fraction = fraction;
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	vector<double> ranks(k);
+	auto n = LanceDetachedSearchSampled(rust_handle_, query, dimension, k, fraction, nprobes_, refine_factor_,
+	                                    labels.data(), distances.data(), ranks.data(), sampled_fraction);
+
+	vector<SampledHit> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.push_back({label_to_rowid_[label], distances[i], ranks[i]});
+		}
+	}
+	return results;
+}
+
+vector<LanceIndex::ExpandedHit> LanceIndex::SearchExpand(const float *query, int32_t dimension, int32_t k,
+                                                         int32_t hops, int32_t per_hop, double decay) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension) || k <= 0) {
+		return {};
+	}
+
+	auto capacity = static_cast<idx_t>(k) * (static_cast<idx_t>(hops) + 1);
+	vector<int64_t> labels(capacity);
+	vector<float> distances(capacity);
+	vector<int32_t> hop_counts(capacity);
+	vector<double> scores(capacity);
+	auto n = LanceDetachedSearchExpand(rust_handle_, query, dimension, k, hops, per_hop, decay, nprobes_,
+	                                   refine_factor_, labels.data(), distances.data(), hop_counts.data(),
+	                                   scores.data());
+
+	vector<ExpandedHit> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.push_back({label_to_rowid_[label], distances[i], hop_counts[i], scores[i]});
+		}
+	}
+	return results;
+}
+
+LancePingResult LanceIndex::Ping() {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedPing(rust_handle_);
+}
+
+int64_t LanceIndex::Prefetch(const vector<row_t> &row_ids, const string &filter) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	vector<int64_t> labels;
+	labels.reserve(row_ids.size());
+	for (auto row_id : row_ids) {
+		auto it = rowid_to_label_.find(row_id);
+		if (it != rowid_to_label_.end()) {
+			labels.push_back(it->second);
+		}
+	}
+	// None of the given rows is indexed: nothing to read, not the whole table
+	if (!row_ids.empty() && labels.empty()) {
+		return 0;
+	}
+	return LanceDetachedPrefetch(rust_handle_, labels.data(), static_cast<int32_t>(labels.size()), filter);
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchWithin(const float *query, int32_t dimension, int32_t k,
+                                                    const vector<row_t> &row_ids) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension)) {
+		return {};
+	}
+
+	vector<int64_t> candidates;
+	candidates.reserve(row_ids.size());
+	for (auto row_id : row_ids) {
+		auto it = rowid_to_label_.find(row_id);
+		if (it != rowid_to_label_.end()) {
+			candidates.push_back(it->second);
+		}
+	}
+	if (candidates.empty() || k <= 0) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchWithin(rust_handle_, query, dimension, k, candidates.data(),
+	                                   static_cast<int32_t>(candidates.size()), labels.data(), distances.data());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::SearchPca(const float *query, int32_t dimension, int32_t k,
+                                                 int32_t rescore_factor) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension) || k <= 0) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> distances(k);
+	auto n = LanceDetachedSearchPca(rust_handle_, query, dimension, k, nprobes_, rescore_factor, labels.data(),
+	                                distances.data());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], distances[i]);
+		}
+	}
+	return results;
+}
+
+vector<pair<row_t, float>> LanceIndex::HybridSearch(const float *query, int32_t dimension, int32_t k,
+                                                    const string &text, const string &text_column, int32_t fusion,
+                                                    float rrf_k, float vector_weight) {
+	if (!rust_handle_ || !LanceDetachedAcceptsQueryDim(rust_handle_, dimension) || k <= 0) {
+		return {};
+	}
+
+	vector<int64_t> labels(k);
+	vector<float> scores(k);
+	auto n = LanceDetachedHybridSearch(rust_handle_, query, dimension, text,
+	                                   text_column.empty() ? nullptr : text_column.c_str(), k, nprobes_,
+	                                   refine_factor_, nullptr, fusion, rrf_k, vector_weight, labels.data(),
+	                                   scores.data());
+
+	vector<pair<row_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		auto label = labels[i];
+		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
+			results.emplace_back(label_to_rowid_[label], scores[i]);
+		}
+	}
+	return results;
+}
+
+void LanceIndex::CreateFtsIndex(const string &column) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedCreateFtsIndex(rust_handle_, column);
+}
+
+void LanceIndex::CreateScalarIndex(const string &column, LanceScalarIndexType index_type) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedCreateScalarIndex(rust_handle_, column, index_type);
+}
+
+vector<LanceGraphEdge> LanceIndex::KnnGraph(int32_t k, int64_t sample) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	auto is_mapped = [&](int64_t label) {
+		return label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size());
+	};
+	vector<LanceGraphEdge> edges;
+	for (auto &edge : LanceDetachedKnnGraph(rust_handle_, k, sample, nprobes_, refine_factor_)) {
+		if (is_mapped(edge.src) && is_mapped(edge.dst)) {
+			edges.push_back({label_to_rowid_[edge.src], label_to_rowid_[edge.dst], edge.distance});
+		}
+	}
+	return edges;
+}
+
+vector<LanceIndex::RowAddress> LanceIndex::RowAddresses() {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	vector<RowAddress> rows;
+	for (auto &address : LanceDetachedRowAddresses(rust_handle_)) {
+		auto mapped = address.label >= 0 && address.label < static_cast<int64_t>(label_to_rowid_.size());
+		rows.push_back({mapped ? label_to_rowid_[address.label] : row_t(-1), address});
+	}
+	return rows;
+}
+
+LanceBackupResult LanceIndex::Backup(const string &dest, int64_t since_version) const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedBackup(rust_handle_, dest, since_version);
+}
+
+LanceManifestBlob LanceIndex::ExportManifest() const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedExportManifest(rust_handle_);
+}
+
+LanceFormatInfo LanceIndex::FormatInfo() const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedFormatInfo(rust_handle_);
+}
+
+LanceLabelBitmap LanceIndex::LabelBitmap() const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedLabelBitmap(rust_handle_);
+}
+
+int64_t LanceIndex::ImportManifest(const string &blob) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedImportManifest(rust_handle_, blob);
+}
+
+double LanceIndex::TrainPca(int32_t target_dims, int64_t sample, int32_t index_type) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedTrainPca(rust_handle_, target_dims, sample, index_type);
+}
+
+void LanceIndex::TrainRotation(int32_t num_sub_vectors, int64_t sample) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedTrainRotation(rust_handle_, num_sub_vectors, sample);
+}
+
+void LanceIndex::CreateAnnIndex(int32_t num_partitions, int32_t num_sub_vectors, const string &metric) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedCreateVectorIndex(rust_handle_, LANCE_INDEX_IVF_PQ, num_partitions, num_sub_vectors, 0, 0, 0, metric);
+}
+
+void LanceIndex::CreateHnswIndex(int32_t m, int32_t ef_construction, const string &metric) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedCreateVectorIndex(rust_handle_, LANCE_INDEX_IVF_HNSW_SQ, 0, 0, m, ef_construction, 0, metric);
+}
+
+void LanceIndex::CreateIvfFlatIndex(int32_t num_partitions, int32_t sample_rate, const string &metric) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedCreateVectorIndex(rust_handle_, LANCE_INDEX_IVF_FLAT, num_partitions, 0, 0, 0, sample_rate, metric);
+}
+
+void LanceIndex::RebuildIndex(int32_t index_type, int32_t num_partitions, int32_t num_sub_vectors, int32_t m,
+                              int32_t ef_construction, int32_t sample_rate, const string &metric, bool wait,
+                              bool staging) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	if (staging && !staging_handle_) {
+		throw InvalidInputException("No staging table; call lance_create_staging first");
+	}
+	LanceDetachedRebuildIndex(staging ? staging_handle_ : rust_handle_, index_type, num_partitions, num_sub_vectors,
+	                          m, ef_construction, sample_rate, metric, wait);
+}
+
+LanceRebuildStatus LanceIndex::GetRebuildStatus(bool staging) const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	if (staging && !staging_handle_) {
+		throw InvalidInputException("No staging table; call lance_create_staging first");
+	}
+	return LanceDetachedRebuildStatus(staging ? staging_handle_ : rust_handle_);
+}
+
+void LanceIndex::CreateStaging() {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	if (staging_handle_) {
+		LanceFreeDetached(staging_handle_);
+		staging_handle_ = nullptr;
+	}
+	// Labels are copied unchanged, so label_to_rowid_ stays valid for the staging rows
+	staging_handle_ = LanceDetachedCreateStaging(rust_handle_, true);
+	staging_stale_ = false;
+}
+
+string LanceIndex::PromoteStaging() {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	if (!staging_handle_) {
+		throw InvalidInputException("No staging table; call lance_create_staging first");
+	}
+	if (staging_stale_) {
+		throw InvalidInputException("Rows changed since the staging table was created; call lance_create_staging again");
+	}
+	auto staging = staging_handle_;
+	staging_handle_ = nullptr;
+	auto retired = LanceDetachedPromoteStaging(rust_handle_, staging);
+	has_pending_deletes_ = false;
+	is_dirty_ = true;
+	return retired;
+}
+
+void LanceIndex::RenameLanceTable(const string &new_name) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	if (staging_handle_) {
+		LanceFreeDetached(staging_handle_);
+		staging_handle_ = nullptr;
+	}
+	auto lance_path = GetLancePath();
+	LanceFreeDetached(rust_handle_);
+	rust_handle_ = nullptr;
+	try {
+		LanceRenameTable(lance_path, table_name_, new_name);
+	} catch (...) {
+		rust_handle_ = LanceOpenDetached(lance_path, table_name_, metric_);
+		throw;
+	}
+	rust_handle_ = LanceOpenDetached(lance_path, new_name, metric_);
+	table_name_ = new_name;
+	is_dirty_ = true;
+}
+
+void LanceIndex::SetIndexBuildLimits(int64_t max_memory_bytes) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetIndexBuildLimits(rust_handle_, max_memory_bytes);
+}
+
+void LanceIndex::SetReadBatchSize(int32_t rows) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetReadBatchSize(rust_handle_, rows);
+}
+
+LanceHitBudget LanceIndex::GetHitBudget() const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedGetHitBudget(rust_handle_);
+}
+
+void LanceIndex::SetHitBudget(const LanceHitBudget &budget) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetHitBudget(rust_handle_, budget);
+}
+
+void LanceIndex::SetMergeIndexing(int32_t mode) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetMergeIndexing(rust_handle_, mode);
+}
 
+LanceIndexStaleness LanceIndex::GetIndexStaleness() const {
 	if (!rust_handle_) {
-		auto lance_path = GetLancePath();
-		table_name_ = SanitizeIndexName(name);
-		if (has_extra_columns_) {
-			// Build ArrowSchema for multi-column table creation
-			vector<LogicalType> col_types;
-			vector<string> col_names;
-			col_names.push_back("vector");
-			col_types.push_back(expr_chunk.data[0].GetType());
-			for (idx_t i = 0; i < extra_column_names_.size(); i++) {
-				col_names.push_back(extra_column_names_[i]);
-				col_types.push_back(extra_column_types_[i]);
-			}
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedIndexStaleness(rust_handle_);
+}
 
-			ArrowSchema create_schema;
-			memset(&create_schema, 0, sizeof(ArrowSchema));
-			auto db_shared = db.GetDatabase().shared_from_this();
-			auto temp_ctx = make_shared_ptr<ClientContext>(db_shared);
-			auto client_props = temp_ctx->GetClientProperties();
-			ArrowConverter::ToArrowSchema(&create_schema, col_types, col_names, client_props);
+int64_t LanceIndex::RepairLabelWatermark() {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedRepairLabelWatermark(rust_handle_);
+}
 
-			rust_handle_ = LanceCreateDetachedFromArrow(lance_path, &create_schema, metric_, table_name_);
+idx_t LanceIndex::RemapLabels(ClientContext &context, const vector<int64_t> &old_labels,
+                              const vector<int64_t> &new_labels) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	if (old_labels.size() != new_labels.size()) {
+		throw InvalidInputException("lance_remap_labels: old_labels and new_labels differ in length");
+	}
+	auto count = old_labels.size();
+	if (count == 0) {
+		return 0;
+	}
 
-			if (create_schema.release) {
-				create_schema.release(&create_schema);
-			}
-		} else {
-			rust_handle_ = LanceCreateDetached(lance_path, dimension_, metric_, table_name_);
+	vector<LogicalType> arrow_types {LogicalType::BIGINT, LogicalType::BIGINT};
+	vector<string> col_names {"old_label", "new_label"};
+	DataChunk mapping;
+	mapping.Initialize(Allocator::DefaultAllocator(), arrow_types, count);
+	memcpy(FlatVector::GetData<int64_t>(mapping.data[0]), old_labels.data(), count * sizeof(int64_t));
+	memcpy(FlatVector::GetData<int64_t>(mapping.data[1]), new_labels.data(), count * sizeof(int64_t));
+	mapping.SetCardinality(count);
+
+	ArrowSchema arrow_schema;
+	ArrowArray arrow_array;
+	memset(&arrow_schema, 0, sizeof(ArrowSchema));
+	memset(&arrow_array, 0, sizeof(ArrowArray));
+	auto client_props = context.GetClientProperties();
+	ArrowConverter::ToArrowSchema(&arrow_schema, arrow_types, col_names, client_props);
+	unordered_map<idx_t, const shared_ptr<ArrowTypeExtensionData>> ext_types;
+	ArrowConverter::ToArrowArray(mapping, &arrow_array, client_props, ext_types);
+
+	int64_t n;
+	try {
+		n = LanceDetachedRemapLabels(rust_handle_, &arrow_schema, &arrow_array);
+	} catch (...) {
+		if (arrow_schema.release) {
+			arrow_schema.release(&arrow_schema);
 		}
+		throw;
+	}
+	// Release schema (Rust consumed the array)
+	if (arrow_schema.release) {
+		arrow_schema.release(&arrow_schema);
 	}
 
-	UnifiedVectorFormat rowid_format;
-	row_identifiers.ToUnifiedFormat(count, rowid_format);
-	auto rowid_data = reinterpret_cast<row_t *>(rowid_format.data);
-
-	vector<int64_t> labels(count);
-	int32_t n;
-
-	if (has_extra_columns_) {
-		// Arrow C Data Interface path: zero-copy via ArrowConverter with temp ClientContext
-		idx_t data_col_count = expr_chunk.ColumnCount();
-		vector<LogicalType> arrow_types;
-		vector<string> col_names;
-		col_names.push_back("vector");
-		arrow_types.push_back(expr_chunk.data[0].GetType());
-		for (idx_t i = 0; i < extra_column_names_.size(); i++) {
-			col_names.push_back(extra_column_names_[i]);
-			arrow_types.push_back(expr_chunk.data[i + 1].GetType());
+	// Old labels are cleared before new ones are set, so swapped labels end up on the right rows
+	vector<row_t> row_ids(count, static_cast<row_t>(-1));
+	for (idx_t i = 0; i < count; i++) {
+		auto old_label = old_labels[i];
+		if (old_label >= 0 && static_cast<idx_t>(old_label) < label_to_rowid_.size()) {
+			row_ids[i] = label_to_rowid_[old_label];
+			label_to_rowid_[old_label] = static_cast<row_t>(-1);
 		}
-
-		DataChunk arrow_chunk;
-		arrow_chunk.Initialize(Allocator::DefaultAllocator(), arrow_types);
-		for (idx_t i = 0; i < arrow_types.size(); i++) {
-			arrow_chunk.data[i].Reference(expr_chunk.data[i]);
+	}
+	for (idx_t i = 0; i < count; i++) {
+		if (row_ids[i] == static_cast<row_t>(-1)) {
+			continue;
 		}
-		arrow_chunk.SetCardinality(count);
-
-		ArrowSchema arrow_schema;
-		ArrowArray arrow_array;
-		memset(&arrow_schema, 0, sizeof(ArrowSchema));
-		memset(&arrow_array, 0, sizeof(ArrowArray));
-
-		// Create temporary ClientContext for Arrow conversion (BoundIndex::Append has no ClientContext)
-		auto db_shared = db.GetDatabase().shared_from_this();
-		auto temp_ctx = make_shared_ptr<ClientContext>(db_shared);
-		auto client_props = temp_ctx->GetClientProperties();
-
-		ArrowConverter::ToArrowSchema(&arrow_schema, arrow_types, col_names, client_props);
-		unordered_map<idx_t, const shared_ptr<ArrowTypeExtensionData>> ext_types;
-		ArrowConverter::ToArrowArray(arrow_chunk, &arrow_array, client_props, ext_types);
-
-		n = LanceDetachedAddBatchArrow(rust_handle_, &arrow_schema, &arrow_array, labels.data());
+		auto new_label = new_labels[i];
+		if (static_cast<idx_t>(new_label) >= label_to_rowid_.size()) {
+			label_to_rowid_.resize(new_label + 1, -1);
+		}
+		label_to_rowid_[new_label] = row_ids[i];
+		rowid_to_label_[row_ids[i]] = new_label;
+	}
+	staging_stale_ = true;
+	is_dirty_ = true;
+	return static_cast<idx_t>(n);
+}
 
-		// Release schema (Rust consumed the array)
+idx_t LanceIndex::RetainOnly(ClientContext &context, const string &key_column, const vector<Value> &keys,
+                             bool allow_empty) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	auto count = keys.size();
+	auto key_type = keys.empty() ? LogicalType::BIGINT : keys[0].type();
+	vector<LogicalType> arrow_types {key_type};
+	vector<string> col_names {key_column};
+	DataChunk key_chunk;
+	key_chunk.Initialize(Allocator::DefaultAllocator(), arrow_types, MaxValue<idx_t>(count, 1));
+	for (idx_t i = 0; i < count; i++) {
+		key_chunk.SetValue(0, i, keys[i]);
+	}
+	key_chunk.SetCardinality(count);
+
+	ArrowSchema arrow_schema;
+	ArrowArray arrow_array;
+	memset(&arrow_schema, 0, sizeof(ArrowSchema));
+	memset(&arrow_array, 0, sizeof(ArrowArray));
+	auto client_props = context.GetClientProperties();
+	ArrowConverter::ToArrowSchema(&arrow_schema, arrow_types, col_names, client_props);
+	unordered_map<idx_t, const shared_ptr<ArrowTypeExtensionData>> ext_types;
+	ArrowConverter::ToArrowArray(key_chunk, &arrow_array, client_props, ext_types);
+
+	int64_t n;
+	try {
+		n = LanceDetachedRetainOnly(rust_handle_, &arrow_schema, &arrow_array, allow_empty);
+	} catch (...) {
 		if (arrow_schema.release) {
 			arrow_schema.release(&arrow_schema);
 		}
-	} else {
-		// Vector-only fast path
-		auto &vec_col = expr_chunk.data[0];
-		auto &array_child = ArrayVector::GetEntry(vec_col);
-		auto child_data = FlatVector::GetData<float>(array_child);
-
-		n = LanceDetachedAddBatch(rust_handle_, child_data, static_cast<int32_t>(count), dimension_, labels.data());
+		throw;
+	}
+	// Release schema (Rust consumed the array)
+	if (arrow_schema.release) {
+		arrow_schema.release(&arrow_schema);
 	}
+	staging_stale_ = true;
+	is_dirty_ = true;
+	return static_cast<idx_t>(n);
+}
 
-	for (idx_t i = 0; i < static_cast<idx_t>(n); i++) {
-		auto row_idx = rowid_format.sel->get_index(i);
-		auto row_id = rowid_data[row_idx];
-		auto label = labels[i];
+LanceRefinePlan LanceIndex::GetRefinePlan(int32_t k) const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedRefinePlan(rust_handle_, refine_factor_, k);
+}
 
-		if (static_cast<idx_t>(label) >= label_to_rowid_.size()) {
-			label_to_rowid_.resize(label + 1, -1);
-		}
-		label_to_rowid_[label] = row_id;
-		rowid_to_label_[row_id] = label;
+void LanceIndex::SetPipeline(const string &spec) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
 	}
+	LanceDetachedSetPipeline(rust_handle_, spec);
+}
 
-	is_dirty_ = true;
-	return ErrorData {};
+void LanceIndex::SetRescoreMetric(const string &metric) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetRescoreMetric(rust_handle_, metric);
 }
 
-ErrorData LanceIndex::Insert(IndexLock &lock, DataChunk &data, Vector &row_ids) {
-	return Append(lock, data, row_ids);
+void LanceIndex::SetQueryTransform(const string &spec, const vector<float> &mean) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetQueryTransform(rust_handle_, spec, mean);
 }
 
-void LanceIndex::Delete(IndexLock &lock, DataChunk &entries, Vector &row_identifiers) {
-	auto count = entries.size();
-	if (count == 0) {
-		return;
+void LanceIndex::SetQuota(const string &spec) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
 	}
+	LanceDetachedSetQuota(rust_handle_, spec);
+}
 
-	UnifiedVectorFormat rowid_format;
-	row_identifiers.ToUnifiedFormat(count, rowid_format);
-	auto rowid_data = reinterpret_cast<row_t *>(rowid_format.data);
+void LanceIndex::SetColdStorage(const string &spec) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetColdStorage(rust_handle_, spec);
+}
 
-	// Collect labels to delete in a single batch
-	vector<int64_t> labels_to_delete;
-	labels_to_delete.reserve(count);
+void LanceIndex::SetChunking(const string &spec) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetChunking(rust_handle_, spec);
+}
 
-	for (idx_t i = 0; i < count; i++) {
-		auto row_idx = rowid_format.sel->get_index(i);
-		auto row_id = rowid_data[row_idx];
+void LanceIndex::SetConstraints(const string &spec) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetConstraints(rust_handle_, spec);
+}
 
-		auto it = rowid_to_label_.find(row_id);
-		if (it != rowid_to_label_.end()) {
-			auto label = it->second;
-			labels_to_delete.push_back(label);
-			// Mark slot as deleted in forward mapping
-			if (label >= 0 && static_cast<idx_t>(label) < label_to_rowid_.size()) {
-				label_to_rowid_[label] = static_cast<row_t>(-1);
-			}
-			rowid_to_label_.erase(it);
-		}
+void LanceIndex::SetOnInvalid(const string &mode) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
 	}
+	LanceDetachedSetOnInvalid(rust_handle_, mode);
+}
 
-	if (rust_handle_ && !labels_to_delete.empty()) {
-		LanceDetachedDeleteBatch(rust_handle_, labels_to_delete.data(), static_cast<int32_t>(labels_to_delete.size()));
-		has_pending_deletes_ = true;
+void LanceIndex::SetColumnMatching(const string &mode) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
 	}
+	LanceDetachedSetColumnMatching(rust_handle_, mode);
+}
 
-	is_dirty_ = true;
+vector<LanceReject> LanceIndex::GetRejects() {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedRejects(rust_handle_);
 }
 
-void LanceIndex::CommitDrop(IndexLock &lock) {
-	if (rust_handle_) {
-		LanceFreeDetached(rust_handle_);
-		rust_handle_ = nullptr;
+void LanceIndex::SetAccessTracking(bool enabled) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
 	}
-	// Clean up Lance directory
-	auto lance_path = GetLancePath();
-	auto &fs = FileSystem::GetFileSystem(db.GetDatabase());
-	fs.RemoveDirectory(lance_path);
+	LanceDetachedSetAccessTracking(rust_handle_, enabled);
 }
 
-// ========================================
-// Search
-// ========================================
+void LanceIndex::SetRowTtl(bool enabled) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetRowTtl(rust_handle_, enabled);
+}
 
-vector<pair<row_t, float>> LanceIndex::Search(const float *query, int32_t dimension, int32_t k,
-                                              const string &predicate) {
-	if (!rust_handle_ || dimension != dimension_) {
-		return {};
+void LanceIndex::SetWriterLease(int64_t ttl_secs) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
 	}
+	LanceDetachedSetWriterLease(rust_handle_, ttl_secs);
+}
 
-	// Lance already excludes deleted vectors (Delete() calls LanceDetachedDeleteBatch),
-	// so no C++ tombstone filtering or retry loop needed.
-	vector<int64_t> labels(k);
-	vector<float> distances(k);
-	auto n = LanceDetachedSearch(rust_handle_, query, dimension, k, nprobes_, refine_factor_,
-	                             predicate.empty() ? nullptr : predicate.c_str(), labels.data(), distances.data());
+void LanceIndex::SetSensitiveColumns(const vector<string> &columns) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetSensitiveColumns(rust_handle_, std::vector<std::string>(columns.begin(), columns.end()));
+}
 
-	vector<pair<row_t, float>> results;
-	results.reserve(n);
-	for (int32_t i = 0; i < n; i++) {
-		auto label = labels[i];
-		if (label >= 0 && label < static_cast<int64_t>(label_to_rowid_.size())) {
-			results.emplace_back(label_to_rowid_[label], distances[i]);
+vector<pair<row_t, LanceColdRow>> LanceIndex::GetColdRows(int64_t idle_ms) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	vector<pair<row_t, LanceColdRow>> result;
+	for (auto &row : LanceDetachedColdRows(rust_handle_, idle_ms)) {
+		if (row.label >= 0 && row.label < static_cast<int64_t>(label_to_rowid_.size())) {
+			result.emplace_back(label_to_rowid_[row.label], row);
 		}
 	}
+	return result;
+}
 
-	return results;
+void LanceIndex::SetQueryLog(const string &spec) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedSetQueryLog(rust_handle_, spec);
+}
+
+vector<LanceQueryDrift> LanceIndex::ReplayQueries(const string &tag) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedReplayQueries(rust_handle_, tag);
+}
+
+void LanceIndex::TagDriftBaseline(const string &tag) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	LanceDetachedTagDriftBaseline(rust_handle_, tag);
+}
+
+std::vector<LanceDriftMetric> LanceIndex::GetDriftReport(const string &tag) const {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedDriftReport(rust_handle_, tag);
+}
+
+std::vector<LanceMaintenanceStep> LanceIndex::RunMaintenance(const string &plan) {
+	if (!rust_handle_) {
+		throw IOException("Lance index not initialized");
+	}
+	return LanceDetachedRunMaintenance(rust_handle_, plan);
 }
 
-void LanceIndex::CreateAnnIndex(int32_t num_partitions, int32_t num_sub_vectors) {
+LanceApproxStats LanceIndex::GetApproxStats(const string &column, const vector<float> &query, double sample_fraction,
+                                            int32_t max_groups) const {
 	if (!rust_handle_) {
 		throw IOException("Lance index not initialized");
 	}
-	LanceDetachedCreateIndex(rust_handle_, num_partitions, num_sub_vectors);
+	return LanceDetachedApproxStats(rust_handle_, column, query, sample_fraction, max_groups);
 }
 
-void LanceIndex::CreateHnswIndex(int32_t m, int32_t ef_construction) {
+void LanceIndex::ClusterBy(const string &column, int64_t rows_per_fragment) {
 	if (!rust_handle_) {
 		throw IOException("Lance index not initialized");
 	}
-	LanceDetachedCreateHnswIndex(rust_handle_, m, ef_construction);
+	LanceDetachedClusterBy(rust_handle_, column, rows_per_fragment);
 }
 
 void LanceIndex::CreateScalarIndex(const string &column, const string &index_type) {
@@ -642,10 +1596,7 @@ bool LanceIndex::MergeIndexes(IndexLock &state, BoundIndex &other_index) {
 
 		if (!live_labels.empty()) {
 			auto count = static_cast<int32_t>(live_labels.size());
-			vector<int64_t> out_old_labels(count);
-			vector<int64_t> out_new_labels(count);
-			auto n = LanceDetachedMerge(rust_handle_, other.rust_handle_, live_labels.data(), count,
-			                            out_old_labels.data(), out_new_labels.data());
+			auto mapping = LanceDetachedMerge(rust_handle_, other.rust_handle_, live_labels.data(), count);
 
 			// Build old_label→rowid map from the other index for lookup
 			unordered_map<int64_t, row_t> old_label_to_rowid;
@@ -653,9 +1604,9 @@ bool LanceIndex::MergeIndexes(IndexLock &state, BoundIndex &other_index) {
 				old_label_to_rowid[live_labels[i]] = live_rowids[i];
 			}
 
-			for (int32_t i = 0; i < n; i++) {
-				auto old_label = out_old_labels[i];
-				auto new_label = out_new_labels[i];
+			for (auto &entry : mapping) {
+				auto old_label = entry.first;
+				auto new_label = entry.second;
 				auto it = old_label_to_rowid.find(old_label);
 				if (it == old_label_to_rowid.end()) {
 					continue;
@@ -706,7 +1657,7 @@ bool LanceIndex::MergeIndexes(IndexLock &state, BoundIndex &other_index) {
 		if (!live_rowids.empty()) {
 			auto num = static_cast<int32_t>(live_rowids.size());
 			vector<int64_t> new_labels(num);
-			LanceDetachedAddBatch(rust_handle_, live_vectors.data(), num, dimension_, new_labels.data());
+			LanceDetachedAddBatchOwned(rust_handle_, std::move(live_vectors), num, dimension_, new_labels.data());
 
 			for (idx_t i = 0; i < static_cast<idx_t>(num); i++) {
 				auto row_id = live_rowids[i];
@@ -721,6 +1672,7 @@ bool LanceIndex::MergeIndexes(IndexLock &state, BoundIndex &other_index) {
 		}
 	}
 
+	staging_stale_ = true;
 	is_dirty_ = true;
 	return true;
 }
@@ -780,6 +1732,8 @@ struct CreateLanceGlobalSinkState : public GlobalSinkState {
 	string metric = "l2";
 	int32_t nprobes = 20;
 	int32_t refine_factor = 1;
+	string model;
+	string storage;
 	string lance_path;
 	string table_name;
 
@@ -821,7 +1775,11 @@ unique_ptr<GlobalSinkState> PhysicalCreateLanceIndex::GetGlobalSinkState(ClientC
 		} else if (kv.first == "nprobes") {
 			state->nprobes = kv.second.GetValue<int32_t>();
 		} else if (kv.first == "refine_factor") {
-			state->refine_factor = kv.second.GetValue<int32_t>();
+			state->refine_factor = ParseRefineFactor(kv.second);
+		} else if (kv.first == "model") {
+			state->model = kv.second.ToString();
+		} else if (kv.first == "storage") {
+			state->storage = kv.second.ToString();
 		}
 	}
 
@@ -869,14 +1827,21 @@ unique_ptr<GlobalSinkState> PhysicalCreateLanceIndex::GetGlobalSinkState(ClientC
 		auto client_props = context.GetClientProperties();
 		ArrowConverter::ToArrowSchema(&create_schema, col_types, col_names, client_props);
 
-		state->rust_handle = LanceCreateDetachedFromArrow(state->lance_path, &create_schema, state->metric, sanitized);
+		state->rust_handle = LanceCreateDetachedFromArrow(state->lance_path, &create_schema, state->metric, sanitized,
+		                                                  "", state->storage);
 
 		if (create_schema.release) {
 			create_schema.release(&create_schema);
 		}
 	} else {
-		state->rust_handle = LanceCreateDetached(state->lance_path, state->dimension, state->metric, sanitized);
+		state->rust_handle =
+		    LanceCreateDetached(state->lance_path, state->dimension, state->metric, sanitized, state->storage);
+	}
+	if (!state->model.empty()) {
+		LanceDetachedSetEmbeddingModel(state->rust_handle, state->model);
 	}
+	// Sink appends one chunk at a time; write them as a few large appends instead of one version per chunk
+	LanceDetachedBeginAppendSession(state->rust_handle);
 	return std::move(state);
 }
 
@@ -930,7 +1895,8 @@ SinkResultType PhysicalCreateLanceIndex::Sink(ExecutionContext &context, DataChu
 		unordered_map<idx_t, const shared_ptr<ArrowTypeExtensionData>> ext_types;
 		ArrowConverter::ToArrowArray(arrow_chunk, &arrow_array, client_props, ext_types);
 
-		n = LanceDetachedAddBatchArrow(state.rust_handle, &arrow_schema, &arrow_array, labels.data());
+		n = LanceDetachedAddBatchArrow(state.rust_handle, &arrow_schema, &arrow_array, labels.data(),
+		                               state.model.empty() ? nullptr : state.model.c_str());
 
 		// Release schema (Rust consumed the array)
 		if (arrow_schema.release) {
@@ -949,6 +1915,10 @@ SinkResultType PhysicalCreateLanceIndex::Sink(ExecutionContext &context, DataChu
 	for (idx_t i = 0; i < static_cast<idx_t>(n); i++) {
 		auto row_idx = rowid_format.sel->get_index(i);
 		auto row_id = rowid_data[row_idx];
+		// Dropped by an ingest constraint; the labels of the other rows stay consecutive
+		if (labels[i] < 0) {
+			continue;
+		}
 		state.label_to_rowid.push_back(row_id);
 		state.rowid_to_label[row_id] = labels[i];
 	}
@@ -959,6 +1929,7 @@ SinkResultType PhysicalCreateLanceIndex::Sink(ExecutionContext &context, DataChu
 SinkFinalizeType PhysicalCreateLanceIndex::Finalize(Pipeline &pipeline, Event &event, ClientContext &context,
                                                     OperatorSinkFinalizeInput &input) const {
 	auto &state = input.global_state.Cast<CreateLanceGlobalSinkState>();
+	LanceDetachedEndAppendSession(state.rust_handle);
 
 	auto &storage = table.GetStorage();
 	if (!storage.IsMainTable()) {
@@ -969,6 +1940,12 @@ SinkFinalizeType PhysicalCreateLanceIndex::Finalize(Pipeline &pipeline, Event &e
 	options["metric"] = Value(state.metric);
 	options["nprobes"] = Value::INTEGER(state.nprobes);
 	options["refine_factor"] = Value::INTEGER(state.refine_factor);
+	if (!state.model.empty()) {
+		options["model"] = Value(state.model);
+	}
+	if (!state.storage.empty()) {
+		options["storage"] = Value(state.storage);
+	}
 
 	auto index = make_uniq<LanceIndex>(info->index_name, info->constraint_type, storage_ids,
 	                                   TableIOManager::Get(storage), unbound_expressions, storage.db, options);
@@ -979,6 +1956,7 @@ SinkFinalizeType PhysicalCreateLanceIndex::Finalize(Pipeline &pipeline, Event &e
 	index->metric_ = state.metric;
 	index->nprobes_ = state.nprobes;
 	index->refine_factor_ = state.refine_factor;
+	index->model_ = state.model;
 	index->label_to_rowid_ = std::move(state.label_to_rowid);
 	index->rowid_to_label_ = std::move(state.rowid_to_label);
 	index->table_name_ = std::move(state.table_name);
@@ -0,0 +1,80 @@
+#include "lancedb_extension.hpp"
+#include "rust_ffi.hpp"
+
+#include "duckdb/common/vector_operations/binary_executor.hpp"
+#include "duckdb/function/scalar_function.hpp"
+
+#include <cstring>
+
+namespace duckdb {
+
+// ========================================
+// lance_label_bitmap_and(a, b), lance_label_bitmap_or(a, b), lance_label_bitmap_andnot(a, b),
+// lance_label_bitmap_xor(a, b), lance_label_bitmap_labels(bitmap)
+// Set operations on label bitmaps from lance_label_bitmap, e.g. the labels deleted between two versions are
+// lance_label_bitmap_andnot(before, after). lance_label_bitmap_labels lists a bitmap's labels ascending as
+// BIGINT[]. NULL inputs give NULL.
+// ========================================
+
+static void BitmapOpFunction(LanceBitmapOp op, DataChunk &args, Vector &result) {
+	BinaryExecutor::Execute<string_t, string_t, string_t>(
+	    args.data[0], args.data[1], result, args.size(), [&](string_t a, string_t b) {
+		    auto combined = LanceLabelBitmapCombine(a.GetString(), b.GetString(), op);
+		    return StringVector::AddStringOrBlob(result, combined);
+	    });
+}
+
+static void BitmapAndFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	BitmapOpFunction(LanceBitmapOp::AND, args, result);
+}
+
+static void BitmapOrFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	BitmapOpFunction(LanceBitmapOp::OR, args, result);
+}
+
+static void BitmapAndNotFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	BitmapOpFunction(LanceBitmapOp::AND_NOT, args, result);
+}
+
+static void BitmapXorFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	BitmapOpFunction(LanceBitmapOp::XOR, args, result);
+}
+
+static void BitmapLabelsFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	auto count = args.size();
+	UnifiedVectorFormat format;
+	args.data[0].ToUnifiedFormat(count, format);
+	auto bitmaps = UnifiedVectorFormat::GetData<string_t>(format);
+
+	for (idx_t row = 0; row < count; row++) {
+		auto idx = format.sel->get_index(row);
+		if (!format.validity.RowIsValid(idx)) {
+			FlatVector::SetNull(result, row, true);
+			continue;
+		}
+		auto labels = LanceLabelBitmapLabels(bitmaps[idx].GetString());
+		auto offset = ListVector::GetListSize(result);
+		ListVector::Reserve(result, offset + labels.size());
+		auto &child = ListVector::GetEntry(result);
+		if (!labels.empty()) {
+			memcpy(FlatVector::GetData<int64_t>(child) + offset, labels.data(), labels.size() * sizeof(int64_t));
+		}
+		ListVector::SetListSize(result, offset + labels.size());
+		FlatVector::GetData<list_entry_t>(result)[row] = list_entry_t(offset, labels.size());
+	}
+	if (args.AllConstant()) {
+		result.SetVectorType(VectorType::CONSTANT_VECTOR);
+	}
+}
+
+void RegisterLanceLabelBitmapOps(ExtensionLoader &loader) {
+	auto blob = LogicalType::BLOB;
+	loader.RegisterFunction(ScalarFunction("lance_label_bitmap_and", {blob, blob}, blob, BitmapAndFunction));
+	loader.RegisterFunction(ScalarFunction("lance_label_bitmap_or", {blob, blob}, blob, BitmapOrFunction));
+	loader.RegisterFunction(ScalarFunction("lance_label_bitmap_andnot", {blob, blob}, blob, BitmapAndNotFunction));
+	loader.RegisterFunction(ScalarFunction("lance_label_bitmap_xor", {blob, blob}, blob, BitmapXorFunction));
+	loader.RegisterFunction(ScalarFunction("lance_label_bitmap_labels", {blob},
+	                                       LogicalType::LIST(LogicalType::BIGINT), BitmapLabelsFunction));
+}
+
+} // namespace duckdb
@@ -114,4 +114,103 @@ void RegisterLanceInfoFunction(ExtensionLoader &loader) {
 	loader.RegisterFunction(func);
 }
 
+// ========================================
+// lance_disk_usage(table, columns := false)
+// Returns (index_name, component, column_name, bytes) for every LANCE index on the table.
+// ========================================
+
+struct LanceDiskUsageBindData : public TableFunctionData {
+	string table_name;
+	bool include_columns = false;
+};
+
+struct LanceDiskUsageRow {
+	string index_name;
+	LanceDiskUsageEntry entry;
+};
+
+struct LanceDiskUsageState : public GlobalTableFunctionState {
+	vector<LanceDiskUsageRow> rows;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceDiskUsageBind(ClientContext &context, TableFunctionBindInput &input,
+                                                   vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceDiskUsageBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	auto it = input.named_parameters.find("columns");
+	if (it != input.named_parameters.end()) {
+		bind_data->include_columns = it->second.GetValue<bool>();
+	}
+
+	return_types.push_back(LogicalType::VARCHAR);
+	return_types.push_back(LogicalType::VARCHAR);
+	return_types.push_back(LogicalType::VARCHAR);
+	return_types.push_back(LogicalType::BIGINT);
+	names.push_back("index_name");
+	names.push_back("component");
+	names.push_back("column_name");
+	names.push_back("bytes");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceDiskUsageInit(ClientContext &context,
+                                                               TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceDiskUsageState>();
+	auto &bind = input.bind_data->Cast<LanceDiskUsageBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+	indexes.Scan([&](Index &idx) {
+		if (idx.GetIndexType() != LanceIndex::TYPE_NAME) {
+			return false;
+		}
+		auto &lance_idx = idx.Cast<LanceIndex>();
+		for (auto &entry : lance_idx.GetDiskUsage(bind.include_columns)) {
+			state->rows.push_back(LanceDiskUsageRow {idx.GetIndexName(), std::move(entry)});
+		}
+		return false;
+	});
+
+	return std::move(state);
+}
+
+static void LanceDiskUsageScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceDiskUsageState>();
+
+	if (state.position >= state.rows.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.rows.size() - state.position);
+
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &row = state.rows[state.position + i];
+		output.SetValue(0, i, Value(row.index_name));
+		output.SetValue(1, i, Value(row.entry.component));
+		output.SetValue(2, i, row.entry.column.empty() ? Value(LogicalType::VARCHAR) : Value(row.entry.column));
+		output.SetValue(3, i, Value::BIGINT(row.entry.bytes));
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+void RegisterLanceDiskUsageFunction(ExtensionLoader &loader) {
+	TableFunction func("lance_disk_usage", {LogicalType::VARCHAR}, LanceDiskUsageScan, LanceDiskUsageBind,
+	                   LanceDiskUsageInit);
+	func.named_parameters["columns"] = LogicalType::BOOLEAN;
+	loader.RegisterFunction(func);
+}
+
 } // namespace duckdb
@@ -9,6 +9,8 @@
 #include "duckdb/catalog/catalog_entry/duck_table_entry.hpp"
 #include "duckdb/catalog/catalog_entry/table_catalog_entry.hpp"
 #include "duckdb/common/types/value.hpp"
+#include "duckdb/common/unordered_set.hpp"
+#include "duckdb/main/client_config.hpp"
 #include "duckdb/optimizer/optimizer_extension.hpp"
 #include "duckdb/planner/expression/bound_comparison_expression.hpp"
 #include "duckdb/planner/expression/bound_conjunction_expression.hpp"
@@ -40,6 +42,9 @@ struct LanceIndexScanBindData : public TableFunctionData {
 	idx_t vector_size = 0;
 	idx_t limit = 100;
 	string predicate;
+	// DuckDB filters the results further (predicates that could not be pushed down), so the scan
+	// over-fetches and tops up until the LIMIT above it is satisfied.
+	bool postfiltered = false;
 };
 
 struct LanceIndexScanGlobalState : public GlobalTableFunctionState {
@@ -47,6 +52,19 @@ struct LanceIndexScanGlobalState : public GlobalTableFunctionState {
 	idx_t offset = 0;
 	vector<StorageIndex> storage_ids;
 
+	// Top-up state of a postfiltered scan: rows requested by the last search, whether it returned
+	// fewer (no more rows reachable), the over-fetch cap and the rows already emitted (skipped when a
+	// wider search returns them again)
+	LanceIndex *index = nullptr;
+	idx_t fetched = 0;
+	bool exhausted = false;
+	idx_t max_fetch = 0;
+	unordered_set<row_t> emitted;
+
+	// Collected only when profiling (EXPLAIN ANALYZE), since it costs a filter scan
+	bool has_pruning_stats = false;
+	LancePruningStats pruning_stats;
+
 	idx_t MaxThreads() const override {
 		return 1;
 	}
@@ -74,22 +92,57 @@ static unique_ptr<GlobalTableFunctionState> LanceIndexScanInit(ClientContext &co
 	auto idx_ptr = indexes.Find(bind_data.index_name);
 	if (idx_ptr) {
 		auto &lance_idx = idx_ptr->Cast<LanceIndex>();
+		state->fetched = bind_data.limit;
+		if (bind_data.postfiltered) {
+			auto overfetch = MaxValue<idx_t>(1, static_cast<idx_t>(lance_idx.GetHitBudget().max_overfetch));
+			state->index = &lance_idx;
+			state->max_fetch = bind_data.limit * overfetch;
+			state->fetched = MinValue<idx_t>(bind_data.limit * 2, state->max_fetch);
+		}
 		state->results = lance_idx.Search(bind_data.query_vector.get(), static_cast<int32_t>(bind_data.vector_size),
-		                                  static_cast<int32_t>(bind_data.limit), bind_data.predicate);
+		                                  static_cast<int32_t>(state->fetched), bind_data.predicate);
+		state->exhausted = state->results.size() < state->fetched;
+		if (!bind_data.predicate.empty() && ClientConfig::GetConfig(context).enable_profiler) {
+			state->pruning_stats = lance_idx.GetPruningStats(bind_data.predicate);
+			state->has_pruning_stats = true;
+		}
 	}
 
 	return std::move(state);
 }
 
+// Search again for twice as many rows, keeping only rows not emitted yet. Returns false once the
+// over-fetch cap is reached or the last search already returned every row it could.
+static bool LanceIndexScanTopUp(const LanceIndexScanBindData &bind_data, LanceIndexScanGlobalState &state) {
+	if (!state.index || state.exhausted || state.fetched >= state.max_fetch) {
+		return false;
+	}
+	state.fetched = MinValue<idx_t>(state.fetched * 2, state.max_fetch);
+	auto wider = state.index->Search(bind_data.query_vector.get(), static_cast<int32_t>(bind_data.vector_size),
+	                                 static_cast<int32_t>(state.fetched), bind_data.predicate);
+	state.exhausted = wider.size() < state.fetched;
+	state.results.clear();
+	for (auto &result : wider) {
+		if (!state.emitted.count(result.first)) {
+			state.results.push_back(result);
+		}
+	}
+	state.offset = 0;
+	return true;
+}
+
 static void LanceIndexScanScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
 	auto &bind_data = data.bind_data->Cast<LanceIndexScanBindData>();
 	auto &state = data.global_state->Cast<LanceIndexScanGlobalState>();
 
-	auto remaining = state.results.size() - state.offset;
-	if (remaining == 0) {
-		output.SetCardinality(0);
-		return;
+	while (state.offset == state.results.size()) {
+		// Only asked again when the LIMIT above still wants rows after DuckDB's filter
+		if (!LanceIndexScanTopUp(bind_data, state)) {
+			output.SetCardinality(0);
+			return;
+		}
 	}
+	auto remaining = state.results.size() - state.offset;
 
 	auto batch_size = MinValue<idx_t>(remaining, STANDARD_VECTOR_SIZE);
 
@@ -97,6 +150,9 @@ static void LanceIndexScanScan(ClientContext &context, TableFunctionInput &data,
 	auto row_ids_data = FlatVector::GetData<row_t>(row_ids_vec);
 	for (idx_t i = 0; i < batch_size; i++) {
 		row_ids_data[i] = state.results[state.offset + i].first;
+		if (state.index) {
+			state.emitted.insert(row_ids_data[i]);
+		}
 	}
 
 	auto &storage = bind_data.table_entry->GetStorage();
@@ -108,6 +164,30 @@ static void LanceIndexScanScan(ClientContext &context, TableFunctionInput &data,
 	// Note: storage.Fetch() already sets the correct cardinality (skipping MVCC-invisible rows).
 }
 
+static InsertionOrderPreservingMap<string> LanceIndexScanToString(TableFunctionToStringInput &input) {
+	InsertionOrderPreservingMap<string> result;
+	auto &bind_data = input.bind_data->Cast<LanceIndexScanBindData>();
+	result["Index"] = bind_data.index_name;
+	result["Limit"] = std::to_string(bind_data.limit);
+	if (!bind_data.predicate.empty()) {
+		result["Lance Filter"] = bind_data.predicate;
+	}
+	return result;
+}
+
+static InsertionOrderPreservingMap<string> LanceIndexScanDynamicToString(TableFunctionDynamicToStringInput &input) {
+	InsertionOrderPreservingMap<string> result;
+	auto &state = input.global_state->Cast<LanceIndexScanGlobalState>();
+	if (state.has_pruning_stats) {
+		auto &stats = state.pruning_stats;
+		result["Clustered By"] = stats.cluster_by.empty() ? "(none)" : stats.cluster_by;
+		result["Fragments Scanned"] =
+		    std::to_string(stats.matching_fragments) + "/" + std::to_string(stats.total_fragments);
+		result["Fragments Pruned"] = std::to_string(stats.total_fragments - stats.matching_fragments);
+	}
+	return result;
+}
+
 // ========================================
 // Expression-to-Lance predicate converter
 // ========================================
@@ -580,11 +660,14 @@ private:
 			if (!filter_fully_pushed) {
 				// Keep remaining predicates in the FILTER node
 				filter_ptr->expressions = std::move(remaining);
+				bind_data->postfiltered = true;
 			}
 		}
 
 		// Create the replacement table function
 		TableFunction scan_func("lance_index_scan", {}, LanceIndexScanScan, LanceIndexScanBind, LanceIndexScanInit);
+		scan_func.to_string = LanceIndexScanToString;
+		scan_func.dynamic_to_string = LanceIndexScanDynamicToString;
 
 		// Reuse the original GET's table_index so column references from above still resolve
 		auto new_get =
@@ -594,18 +677,18 @@ private:
 
 		// Replace the LIMIT → ORDER → [FILTER →] [PROJ →] GET subtree
 		if (filter_ptr && !filter_fully_pushed) {
-			// Partial pushdown: keep FILTER, replace its child subtree with new GET
+			// Partial pushdown: keep FILTER, replace its child subtree with new GET. The LIMIT stays
+			// so the over-fetching scan stops once k rows pass the filter; the scan is distance-ordered
+			// and the FILTER preserves order, so ORDER goes.
 			if (has_projection) {
-				// FILTER → PROJ → GET  →  FILTER → PROJ → new GET
+				// LIMIT → ORDER → FILTER → PROJ → GET  →  LIMIT → FILTER → PROJ → new GET
 				auto *proj_node = filter_ptr->children[0].get();
 				proj_node->children[0] = std::move(new_get);
-				// Move FILTER (with PROJ child) out to replace LIMIT
-				op = std::move(order_op.children[0]);
 			} else {
-				// FILTER → GET  →  FILTER → new GET
+				// LIMIT → ORDER → FILTER → GET  →  LIMIT → FILTER → new GET
 				filter_ptr->children[0] = std::move(new_get);
-				op = std::move(order_op.children[0]);
 			}
+			op->children[0] = std::move(order_op.children[0]);
 		} else if (has_projection) {
 			// LIMIT → ORDER → [FILTER →] PROJ → GET  →  PROJ → new GET
 			unique_ptr<LogicalOperator> proj;
@@ -5,13 +5,39 @@
 #include "duckdb/catalog/catalog.hpp"
 #include "duckdb/catalog/catalog_entry/duck_table_entry.hpp"
 #include "duckdb/catalog/catalog_entry/table_catalog_entry.hpp"
+#include "duckdb/common/string_util.hpp"
 #include "duckdb/storage/data_table.hpp"
 
+#include <cmath>
+
 namespace duckdb {
 
 // ========================================
-// lance_search(table, index, query_vec, k)
-// Returns (row_id BIGINT, distance FLOAT)
+// lance_search(table, index, query_vec, k, model := NULL, negatives := NULL, negative_weight := 1.0,
+//              dedup_column := NULL, weights := NULL, weight_query := false, min_distance := NULL,
+//              max_distance := NULL, consistency := NULL, ef := NULL, diversity := false,
+//              diversity_column := NULL, scope := NULL)
+// Returns (row_id BIGINT, distance FLOAT). model, if given, must match the index's embedding model.
+// negatives (a list of vectors) turns the search into "more like query, less like these": the query
+// is moved to query - negative_weight * mean(negatives) before searching.
+// dedup_column (a column stored in the index, e.g. a document id) keeps only the best hit per distinct
+// value; the search over-fetches internally so k distinct values are returned when the table has them.
+// weights (one per stored dimension, 0 masks a dimension out) down-weights noisy dimensions: hits are rescored
+// with the weighted metric, and weight_query also weighs the query before the ANN search.
+// min_distance/max_distance make it a range search: only hits with min_distance <= distance < max_distance,
+// at most k of them (fewer than k means every neighbor in range was returned), e.g. all rows within a radius
+// for dedup. Distances are in the index metric (squared for l2).
+// consistency trades freshness for latency: 'latest' checks out the latest table version first (seeing commits
+// from other processes), 'session' (the default) sees this process's commits, 'eventual' reads whatever version
+// the index already has.
+// ef sets how many candidates an HNSW index explores for this query (at least k; Lance's default is about 1.5 * k):
+// raise it for recall, lower it for latency, without rebuilding the index. Other index types ignore it.
+// diversity adds mean_pairwise_distance DOUBLE (the mean index-metric distance between every pair of hits, NULL
+// with fewer than two) and distinct_values BIGINT (distinct non-NULL values of diversity_column among the hits,
+// NULL without one), repeated on every row, so A/B tests can track how varied the results are. diversity_column
+// implies diversity.
+// scope (a Lance SQL predicate over stored columns, e.g. 'tenant_id = 42') searches through a handle that only sees
+// the matching rows, for per-tenant queries that cannot reach other tenants' rows.
 // ========================================
 
 struct LanceSearchBindData : public TableFunctionData {
@@ -19,11 +45,27 @@ struct LanceSearchBindData : public TableFunctionData {
 	string index_name;
 	vector<float> query;
 	int32_t k;
+	string model;
+	vector<float> negatives;
+	float negative_weight = 1.0f;
+	string dedup_column;
+	vector<float> weights;
+	bool weight_query = false;
+	float min_distance = NAN;
+	float max_distance = NAN;
+	string consistency;
+	int32_t ef = 0;
+	bool diversity = false;
+	string diversity_column;
+	string scope;
 };
 
 struct LanceSearchState : public GlobalTableFunctionState {
 	vector<row_t> row_ids;
 	vector<float> distances;
+	// Set for lance_search(..., diversity := true); the scan then fills two more columns
+	bool with_diversity = false;
+	LanceSearchDiversity diversity;
 	idx_t position = 0;
 	idx_t MaxThreads() const override {
 		return 1;
@@ -44,10 +86,92 @@ static unique_ptr<FunctionData> LanceSearchBind(ClientContext &context, TableFun
 
 	bind_data->k = input.inputs[3].GetValue<int32_t>();
 
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "model") {
+			bind_data->model = param.second.GetValue<string>();
+		} else if (param.first == "negatives") {
+			for (auto &negative : ListValue::GetChildren(param.second)) {
+				auto &values = ListValue::GetChildren(negative);
+				if (values.size() != bind_data->query.size()) {
+					throw InvalidInputException("lance_search: negative vectors must have the query dimension %d",
+					                            bind_data->query.size());
+				}
+				for (auto &value : values) {
+					bind_data->negatives.push_back(value.GetValue<float>());
+				}
+			}
+		} else if (param.first == "negative_weight") {
+			bind_data->negative_weight = param.second.GetValue<float>();
+		} else if (param.first == "dedup_column") {
+			bind_data->dedup_column = param.second.GetValue<string>();
+		} else if (param.first == "weights") {
+			for (auto &value : ListValue::GetChildren(param.second)) {
+				bind_data->weights.push_back(value.GetValue<float>());
+			}
+		} else if (param.first == "weight_query") {
+			bind_data->weight_query = param.second.GetValue<bool>();
+		} else if (param.first == "min_distance") {
+			bind_data->min_distance = param.second.GetValue<float>();
+		} else if (param.first == "max_distance") {
+			bind_data->max_distance = param.second.GetValue<float>();
+		} else if (param.first == "consistency") {
+			bind_data->consistency = param.second.GetValue<string>();
+		} else if (param.first == "ef") {
+			bind_data->ef = param.second.GetValue<int32_t>();
+			if (bind_data->ef < bind_data->k) {
+				throw InvalidInputException("lance_search: ef must be at least k (%d)", bind_data->k);
+			}
+		} else if (param.first == "diversity") {
+			bind_data->diversity = param.second.GetValue<bool>();
+		} else if (param.first == "diversity_column") {
+			bind_data->diversity_column = param.second.GetValue<string>();
+			bind_data->diversity = true;
+		} else if (param.first == "scope") {
+			bind_data->scope = param.second.GetValue<string>();
+		}
+	}
+	auto ranged = !std::isnan(bind_data->min_distance) || !std::isnan(bind_data->max_distance);
+	if (ranged && (!bind_data->dedup_column.empty() || !bind_data->negatives.empty() || !bind_data->weights.empty())) {
+		throw InvalidInputException(
+		    "lance_search: min_distance/max_distance cannot be combined with dedup_column, negatives or weights");
+	}
+	if (!bind_data->consistency.empty() && (ranged || !bind_data->dedup_column.empty() ||
+	                                        !bind_data->negatives.empty() || !bind_data->weights.empty())) {
+		throw InvalidInputException("lance_search: consistency cannot be combined with other search options");
+	}
+	if (!bind_data->dedup_column.empty() && !bind_data->negatives.empty()) {
+		throw InvalidInputException("lance_search: dedup_column cannot be combined with negatives");
+	}
+	if (!bind_data->weights.empty() && (!bind_data->dedup_column.empty() || !bind_data->negatives.empty())) {
+		throw InvalidInputException("lance_search: weights cannot be combined with dedup_column or negatives");
+	}
+	if (bind_data->ef > 0 && (ranged || !bind_data->consistency.empty() || !bind_data->dedup_column.empty() ||
+	                          !bind_data->negatives.empty() || !bind_data->weights.empty())) {
+		throw InvalidInputException("lance_search: ef cannot be combined with other search options");
+	}
+	if (bind_data->diversity && (ranged || !bind_data->consistency.empty() || !bind_data->dedup_column.empty() ||
+	                             !bind_data->negatives.empty() || !bind_data->weights.empty())) {
+		throw InvalidInputException("lance_search: diversity cannot be combined with other search options");
+	}
+	if (!bind_data->scope.empty() && (ranged || !bind_data->consistency.empty() || !bind_data->dedup_column.empty() ||
+	                                  !bind_data->negatives.empty() || !bind_data->weights.empty() ||
+	                                  bind_data->ef > 0 || bind_data->diversity)) {
+		throw InvalidInputException("lance_search: scope cannot be combined with other search options");
+	}
+
 	return_types.push_back(LogicalType::BIGINT);
 	return_types.push_back(LogicalType::FLOAT);
 	names.push_back("row_id");
 	names.push_back("distance");
+	if (bind_data->diversity) {
+		return_types.push_back(LogicalType::DOUBLE);
+		return_types.push_back(LogicalType::BIGINT);
+		names.push_back("mean_pairwise_distance");
+		names.push_back("distinct_values");
+	}
 	return std::move(bind_data);
 }
 
@@ -69,10 +193,155 @@ static unique_ptr<GlobalTableFunctionState> LanceSearchInit(ClientContext &conte
 		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
 	}
 
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	auto dimension = static_cast<int32_t>(bind.query.size());
+	vector<pair<row_t, float>> results;
+	if (!bind.scope.empty()) {
+		results = lance_idx.SearchScoped(bind.query.data(), dimension, bind.k, bind.scope, bind.model);
+	} else if (!bind.dedup_column.empty()) {
+		results = lance_idx.SearchDedup(bind.query.data(), dimension, bind.k, bind.dedup_column, bind.model);
+	} else if (!bind.consistency.empty()) {
+		results = lance_idx.SearchConsistent(bind.query.data(), dimension, bind.k, bind.consistency, bind.model);
+	} else if (!std::isnan(bind.min_distance) || !std::isnan(bind.max_distance)) {
+		results = lance_idx.SearchRange(bind.query.data(), dimension, bind.k, bind.min_distance, bind.max_distance,
+		                                bind.model);
+	} else if (!bind.negatives.empty()) {
+		results = lance_idx.SearchWithNegatives(bind.query.data(), dimension, bind.k, bind.negatives,
+		                                        bind.negative_weight, bind.model);
+	} else if (bind.diversity) {
+		state->with_diversity = true;
+		results = lance_idx.SearchDiversity(bind.query.data(), dimension, bind.k, string(), bind.diversity_column,
+		                                    state->diversity, bind.model, bind.ef);
+	} else {
+		results = lance_idx.Search(bind.query.data(), dimension, bind.k, string(), bind.model, bind.weights,
+		                           bind.weight_query, bind.ef);
+	}
+
+	for (auto &result : results) {
+		state->row_ids.push_back(result.first);
+		state->distances.push_back(result.second);
+	}
+
+	return std::move(state);
+}
+
+// ========================================
+// lance_search_within(table, index, query_vec, k, row_ids)
+// Exact k-NN ranking of the given rows only, e.g. candidates already narrowed by joins.
+// Returns (row_id BIGINT, distance FLOAT); row ids not in the index are ignored.
+// ========================================
+
+struct LanceSearchWithinBindData : public LanceSearchBindData {
+	vector<row_t> row_ids;
+};
+
+static unique_ptr<FunctionData> LanceSearchWithinBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchWithinBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->query.push_back(child.GetValue<float>());
+	}
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+	for (auto &child : ListValue::GetChildren(input.inputs[4])) {
+		if (!child.IsNull()) {
+			bind_data->row_ids.push_back(child.GetValue<int64_t>());
+		}
+	}
+
+	return_types.push_back(LogicalType::BIGINT);
+	return_types.push_back(LogicalType::FLOAT);
+	names.push_back("row_id");
+	names.push_back("distance");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSearchWithinInit(ClientContext &context,
+                                                                  TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchState>();
+	auto &bind = input.bind_data->Cast<LanceSearchWithinBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
 	auto &lance_idx = index_ptr->Cast<LanceIndex>();
 	auto results =
-	    lance_idx.Search(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k);
+	    lance_idx.SearchWithin(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k, bind.row_ids);
+	for (auto &result : results) {
+		state->row_ids.push_back(result.first);
+		state->distances.push_back(result.second);
+	}
+
+	return std::move(state);
+}
+
+// ========================================
+// lance_search_pca(table, index, query_vec, k, rescore_factor := 4)
+// Searches the PCA-reduced column trained by lance_train_pca and rescores the k * rescore_factor
+// nearest candidates on the full vectors. Returns (row_id BIGINT, distance FLOAT).
+// ========================================
+
+struct LanceSearchPcaBindData : public LanceSearchBindData {
+	int32_t rescore_factor = 4;
+};
+
+static unique_ptr<FunctionData> LanceSearchPcaBind(ClientContext &context, TableFunctionBindInput &input,
+                                                   vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchPcaBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->query.push_back(child.GetValue<float>());
+	}
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+	auto it = input.named_parameters.find("rescore_factor");
+	if (it != input.named_parameters.end() && !it->second.IsNull()) {
+		bind_data->rescore_factor = it->second.GetValue<int32_t>();
+		if (bind_data->rescore_factor < 1) {
+			throw InvalidInputException("lance_search_pca: rescore_factor must be at least 1");
+		}
+	}
+
+	return_types.push_back(LogicalType::BIGINT);
+	return_types.push_back(LogicalType::FLOAT);
+	names.push_back("row_id");
+	names.push_back("distance");
+	return std::move(bind_data);
+}
 
+static unique_ptr<GlobalTableFunctionState> LanceSearchPcaInit(ClientContext &context, TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchState>();
+	auto &bind = input.bind_data->Cast<LanceSearchPcaBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	auto results = lance_idx.SearchPca(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k,
+	                                   bind.rescore_factor);
 	for (auto &result : results) {
 		state->row_ids.push_back(result.first);
 		state->distances.push_back(result.second);
@@ -81,6 +350,602 @@ static unique_ptr<GlobalTableFunctionState> LanceSearchInit(ClientContext &conte
 	return std::move(state);
 }
 
+// ========================================
+// lance_hybrid_search(table, index, query_vec, text, k, text_column := NULL, fusion := 'rrf', rrf_k := 60,
+//                     vector_weight := 0.5)
+// Vector k-NN fused with a full-text (BM25) search of text over the columns indexed by
+// lance_create_fts_index (only text_column when given). fusion is rrf (reciprocal rank fusion),
+// linear (vector_weight * vector + (1 - vector_weight) * text, both min-max normalized) or max (the
+// larger normalized score). Returns (row_id BIGINT, score FLOAT), higher is better.
+// ========================================
+
+struct LanceHybridSearchBindData : public LanceSearchBindData {
+	string text;
+	string text_column;
+	int32_t fusion = LANCE_FUSION_RRF;
+	float rrf_k = 0;
+	float vector_weight = 0.5f;
+};
+
+static int32_t ParseFusion(const string &fusion) {
+	auto lower = StringUtil::Lower(fusion);
+	if (lower == "rrf") {
+		return LANCE_FUSION_RRF;
+	}
+	if (lower == "linear") {
+		return LANCE_FUSION_LINEAR;
+	}
+	if (lower == "max") {
+		return LANCE_FUSION_MAX_SCORE;
+	}
+	throw InvalidInputException("lance_hybrid_search: unknown fusion '%s' (expected rrf, linear or max)", fusion);
+}
+
+static unique_ptr<FunctionData> LanceHybridSearchBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceHybridSearchBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->query.push_back(child.GetValue<float>());
+	}
+	bind_data->text = input.inputs[3].GetValue<string>();
+	bind_data->k = input.inputs[4].GetValue<int32_t>();
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "text_column") {
+			bind_data->text_column = param.second.GetValue<string>();
+		} else if (param.first == "fusion") {
+			bind_data->fusion = ParseFusion(param.second.GetValue<string>());
+		} else if (param.first == "rrf_k") {
+			bind_data->rrf_k = param.second.GetValue<float>();
+		} else if (param.first == "vector_weight") {
+			bind_data->vector_weight = param.second.GetValue<float>();
+		}
+	}
+	if (bind_data->vector_weight < 0 || bind_data->vector_weight > 1) {
+		throw InvalidInputException("lance_hybrid_search: vector_weight must be between 0 and 1");
+	}
+
+	return_types.push_back(LogicalType::BIGINT);
+	return_types.push_back(LogicalType::FLOAT);
+	names.push_back("row_id");
+	names.push_back("score");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceHybridSearchInit(ClientContext &context,
+                                                                  TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchState>();
+	auto &bind = input.bind_data->Cast<LanceHybridSearchBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	auto results = lance_idx.HybridSearch(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k,
+	                                      bind.text, bind.text_column, bind.fusion, bind.rrf_k, bind.vector_weight);
+	for (auto &result : results) {
+		state->row_ids.push_back(result.first);
+		state->distances.push_back(result.second);
+	}
+
+	return std::move(state);
+}
+
+// ========================================
+// lance_search_like(table, index, row_ids, k, negatives := NULL, negative_weight := 1.0)
+// "Find items similar to this basket": searches with the centroid of the given rows' vectors,
+// optionally moved away from the centroid of the negative rows. The given rows are not returned.
+// Returns (row_id BIGINT, distance FLOAT).
+// ========================================
+
+struct LanceSearchLikeBindData : public TableFunctionData {
+	string table_name;
+	string index_name;
+	vector<row_t> row_ids;
+	vector<row_t> negative_row_ids;
+	float negative_weight = 1.0f;
+	int32_t k;
+};
+
+static vector<row_t> RowIdList(const Value &list) {
+	vector<row_t> row_ids;
+	for (auto &child : ListValue::GetChildren(list)) {
+		if (!child.IsNull()) {
+			row_ids.push_back(child.GetValue<int64_t>());
+		}
+	}
+	return row_ids;
+}
+
+static unique_ptr<FunctionData> LanceSearchLikeBind(ClientContext &context, TableFunctionBindInput &input,
+                                                    vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchLikeBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->row_ids = RowIdList(input.inputs[2]);
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+	if (bind_data->row_ids.empty()) {
+		throw InvalidInputException("lance_search_like: row_ids must not be empty");
+	}
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "negatives") {
+			bind_data->negative_row_ids = RowIdList(param.second);
+		} else if (param.first == "negative_weight") {
+			bind_data->negative_weight = param.second.GetValue<float>();
+		}
+	}
+
+	return_types.push_back(LogicalType::BIGINT);
+	return_types.push_back(LogicalType::FLOAT);
+	names.push_back("row_id");
+	names.push_back("distance");
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSearchLikeInit(ClientContext &context,
+                                                                TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchState>();
+	auto &bind = input.bind_data->Cast<LanceSearchLikeBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	auto results = lance_idx.SearchLikeRows(bind.row_ids, bind.negative_row_ids, bind.negative_weight, bind.k);
+	for (auto &result : results) {
+		state->row_ids.push_back(result.first);
+		state->distances.push_back(result.second);
+	}
+
+	return std::move(state);
+}
+
+static unique_ptr<NodeStatistics> LanceSearchLikeCardinality(ClientContext &context,
+                                                             const FunctionData *bind_data_p) {
+	auto &bind = bind_data_p->Cast<LanceSearchLikeBindData>();
+	return make_uniq<NodeStatistics>(bind.k, bind.k);
+}
+
+// ========================================
+// lance_search_sample(table, index, query_vec, k, sample_fraction)
+// Fast approximate search probing only sample_fraction of the IVF partitions a full search would.
+// Returns (row_id BIGINT, distance FLOAT, estimated_rank DOUBLE, sample_fraction DOUBLE,
+// approximate BOOLEAN); estimated_rank extrapolates each hit's rank to the full table.
+// ========================================
+
+struct LanceSearchSampleBindData : public LanceSearchBindData {
+	double fraction;
+};
+
+struct LanceSearchSampleState : public GlobalTableFunctionState {
+	vector<LanceIndex::SampledHit> hits;
+	double sampled_fraction = 0;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSearchSampleBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchSampleBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->query.push_back(child.GetValue<float>());
+	}
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+	bind_data->fraction = input.inputs[4].GetValue<double>();
+	if (!(bind_data->fraction > 0 && bind_data->fraction <= 1)) {
+		throw InvalidInputException("lance_search_sample: sample_fraction must be in (0, 1]");
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::FLOAT, LogicalType::DOUBLE, LogicalType::DOUBLE,
+	                LogicalType::BOOLEAN};
+	names = {"row_id", "distance", "estimated_rank", "sample_fraction", "approximate"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSearchSampleInit(ClientContext &context,
+                                                                  TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchSampleState>();
+	auto &bind = input.bind_data->Cast<LanceSearchSampleBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	state->hits = lance_idx.SearchSampled(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k,
+	                                      bind.fraction, state->sampled_fraction);
+	return std::move(state);
+}
+
+static void LanceSearchSampleScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceSearchSampleState>();
+
+	if (state.position >= state.hits.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.hits.size() - state.position);
+
+	auto rowid_data = FlatVector::GetData<int64_t>(output.data[0]);
+	auto dist_data = FlatVector::GetData<float>(output.data[1]);
+	auto rank_data = FlatVector::GetData<double>(output.data[2]);
+	auto fraction_data = FlatVector::GetData<double>(output.data[3]);
+	auto approximate_data = FlatVector::GetData<bool>(output.data[4]);
+
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &hit = state.hits[state.position + i];
+		rowid_data[i] = hit.row_id;
+		dist_data[i] = hit.distance;
+		rank_data[i] = hit.estimated_rank;
+		fraction_data[i] = state.sampled_fraction;
+		approximate_data[i] = true;
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+// ========================================
+// lance_search_expand(table, index, query_vec, k, hops := 1, per_hop := 5, decay := 0.5)
+// Graph-expansion retrieval: the k nearest rows, then `hops` rounds that search around each row
+// found in the previous round for per_hop neighbors and keep the k best new ones. Returns
+// (row_id BIGINT, distance FLOAT, hop INTEGER, score DOUBLE), deduplicated and ranked by
+// score = decay^hop * exp(-distance), so related clusters one-hop ANN misses can surface.
+// ========================================
+
+struct LanceSearchExpandBindData : public LanceSearchBindData {
+	int32_t hops = 1;
+	int32_t per_hop = 5;
+	double decay = 0.5;
+};
+
+struct LanceSearchExpandState : public GlobalTableFunctionState {
+	vector<LanceIndex::ExpandedHit> hits;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSearchExpandBind(ClientContext &context, TableFunctionBindInput &input,
+                                                      vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchExpandBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->query.push_back(child.GetValue<float>());
+	}
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+
+	for (auto &param : input.named_parameters) {
+		if (param.second.IsNull()) {
+			continue;
+		}
+		if (param.first == "hops") {
+			bind_data->hops = param.second.GetValue<int32_t>();
+		} else if (param.first == "per_hop") {
+			bind_data->per_hop = param.second.GetValue<int32_t>();
+		} else if (param.first == "decay") {
+			bind_data->decay = param.second.GetValue<double>();
+		}
+	}
+	if (bind_data->hops < 0 || bind_data->per_hop < 0) {
+		throw InvalidInputException("lance_search_expand: hops and per_hop must not be negative");
+	}
+	if (!(bind_data->decay > 0 && bind_data->decay <= 1)) {
+		throw InvalidInputException("lance_search_expand: decay must be in (0, 1]");
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::FLOAT, LogicalType::INTEGER, LogicalType::DOUBLE};
+	names = {"row_id", "distance", "hop", "score"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSearchExpandInit(ClientContext &context,
+                                                                  TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchExpandState>();
+	auto &bind = input.bind_data->Cast<LanceSearchExpandBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	state->hits = lance_idx.SearchExpand(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k,
+	                                     bind.hops, bind.per_hop, bind.decay);
+	return std::move(state);
+}
+
+static void LanceSearchExpandScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceSearchExpandState>();
+
+	if (state.position >= state.hits.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.hits.size() - state.position);
+
+	auto rowid_data = FlatVector::GetData<int64_t>(output.data[0]);
+	auto dist_data = FlatVector::GetData<float>(output.data[1]);
+	auto hop_data = FlatVector::GetData<int32_t>(output.data[2]);
+	auto score_data = FlatVector::GetData<double>(output.data[3]);
+
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &hit = state.hits[state.position + i];
+		rowid_data[i] = hit.row_id;
+		dist_data[i] = hit.distance;
+		hop_data[i] = hit.hop;
+		score_data[i] = hit.score;
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+static unique_ptr<NodeStatistics> LanceSearchExpandCardinality(ClientContext &context,
+                                                               const FunctionData *bind_data_p) {
+	auto &bind = bind_data_p->Cast<LanceSearchExpandBindData>();
+	auto max_rows = static_cast<idx_t>(bind.k) * (static_cast<idx_t>(bind.hops) + 1);
+	return make_uniq<NodeStatistics>(bind.k, max_rows);
+}
+
+// ========================================
+// lance_search_parents(table_name, index_name, query_vector, k, parent_column := NULL)
+// Parent-document retrieval over chunked rows: searches the chunks and returns the k best distinct
+// parents as (parent_id BIGINT, row_id BIGINT, distance FLOAT), where row_id is the parent's nearest
+// chunk. parent_column defaults to the parent column of the chunking stage (lance_set_chunking); a
+// chunk with a NULL parent is its own parent.
+// ========================================
+
+struct LanceSearchParentsBindData : public LanceSearchBindData {
+	string parent_column;
+};
+
+struct LanceSearchParentsState : public GlobalTableFunctionState {
+	vector<LanceIndex::ParentHit> hits;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSearchParentsBind(ClientContext &context, TableFunctionBindInput &input,
+                                                       vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchParentsBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	for (auto &child : ListValue::GetChildren(input.inputs[2])) {
+		bind_data->query.push_back(child.GetValue<float>());
+	}
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+
+	for (auto &param : input.named_parameters) {
+		if (param.first == "parent_column" && !param.second.IsNull()) {
+			bind_data->parent_column = param.second.GetValue<string>();
+		}
+	}
+
+	return_types = {LogicalType::BIGINT, LogicalType::BIGINT, LogicalType::FLOAT};
+	names = {"parent_id", "row_id", "distance"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSearchParentsInit(ClientContext &context,
+                                                                   TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchParentsState>();
+	auto &bind = input.bind_data->Cast<LanceSearchParentsBindData>();
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	state->hits = lance_idx.SearchParents(bind.query.data(), static_cast<int32_t>(bind.query.size()), bind.k,
+	                                      bind.parent_column);
+	return std::move(state);
+}
+
+static void LanceSearchParentsScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceSearchParentsState>();
+
+	if (state.position >= state.hits.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.hits.size() - state.position);
+
+	auto parent_data = FlatVector::GetData<int64_t>(output.data[0]);
+	auto rowid_data = FlatVector::GetData<int64_t>(output.data[1]);
+	auto dist_data = FlatVector::GetData<float>(output.data[2]);
+
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &hit = state.hits[state.position + i];
+		parent_data[i] = hit.parent;
+		rowid_data[i] = hit.row_id;
+		dist_data[i] = hit.distance;
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+// ========================================
+// lance_search_batch(table_name, index_name, queries, k)
+// Many queries in one call: queries is a FLOAT[][] of query vectors, searched concurrently on the Lance
+// runtime. Returns (query_idx INTEGER, row_id BIGINT, distance FLOAT), query_idx being the 0-based
+// position of the query in the list, grouped by query and nearest first.
+// ========================================
+
+struct LanceSearchBatchBindData : public LanceSearchBindData {
+	int32_t num_queries = 0;
+};
+
+struct LanceSearchBatchState : public GlobalTableFunctionState {
+	vector<LanceIndex::BatchHit> hits;
+	idx_t position = 0;
+	idx_t MaxThreads() const override {
+		return 1;
+	}
+};
+
+static unique_ptr<FunctionData> LanceSearchBatchBind(ClientContext &context, TableFunctionBindInput &input,
+                                                     vector<LogicalType> &return_types, vector<string> &names) {
+	auto bind_data = make_uniq<LanceSearchBatchBindData>();
+	bind_data->table_name = input.inputs[0].GetValue<string>();
+	bind_data->index_name = input.inputs[1].GetValue<string>();
+	bind_data->k = input.inputs[3].GetValue<int32_t>();
+
+	idx_t dimension = 0;
+	for (auto &query : ListValue::GetChildren(input.inputs[2])) {
+		if (query.IsNull()) {
+			throw InvalidInputException("lance_search_batch: query %d is NULL", bind_data->num_queries);
+		}
+		auto &values = ListValue::GetChildren(query);
+		if (bind_data->num_queries == 0) {
+			dimension = values.size();
+		} else if (values.size() != dimension) {
+			throw InvalidInputException("lance_search_batch: query %d has %d values, expected %d",
+			                            bind_data->num_queries, values.size(), dimension);
+		}
+		for (auto &value : values) {
+			bind_data->query.push_back(value.GetValue<float>());
+		}
+		bind_data->num_queries++;
+	}
+
+	return_types = {LogicalType::INTEGER, LogicalType::BIGINT, LogicalType::FLOAT};
+	names = {"query_idx", "row_id", "distance"};
+	return std::move(bind_data);
+}
+
+static unique_ptr<GlobalTableFunctionState> LanceSearchBatchInit(ClientContext &context,
+                                                                 TableFunctionInitInput &input) {
+	auto state = make_uniq<LanceSearchBatchState>();
+	auto &bind = input.bind_data->Cast<LanceSearchBatchBindData>();
+	if (bind.num_queries == 0) {
+		return std::move(state);
+	}
+
+	auto &catalog = Catalog::GetCatalog(context, "");
+	auto &table_entry = catalog.GetEntry<TableCatalogEntry>(context, DEFAULT_SCHEMA, bind.table_name);
+	auto &duck_table = table_entry.Cast<DuckTableEntry>();
+	auto &storage = duck_table.GetStorage();
+	auto &table_info = *storage.GetDataTableInfo();
+	auto &indexes = table_info.GetIndexes();
+
+	indexes.Bind(context, table_info, LanceIndex::TYPE_NAME);
+
+	auto index_ptr = indexes.Find(bind.index_name);
+	if (!index_ptr) {
+		throw InvalidInputException("Index '%s' not found on table '%s'", bind.index_name, bind.table_name);
+	}
+
+	auto &lance_idx = index_ptr->Cast<LanceIndex>();
+	auto dimension = static_cast<int32_t>(bind.query.size() / bind.num_queries);
+	state->hits = lance_idx.SearchBatch(bind.query.data(), dimension, bind.num_queries, bind.k);
+	return std::move(state);
+}
+
+static void LanceSearchBatchScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
+	auto &state = data.global_state->Cast<LanceSearchBatchState>();
+
+	if (state.position >= state.hits.size()) {
+		output.SetCardinality(0);
+		return;
+	}
+
+	idx_t chunk_size = MinValue<idx_t>(STANDARD_VECTOR_SIZE, state.hits.size() - state.position);
+
+	auto query_data = FlatVector::GetData<int32_t>(output.data[0]);
+	auto rowid_data = FlatVector::GetData<int64_t>(output.data[1]);
+	auto dist_data = FlatVector::GetData<float>(output.data[2]);
+
+	for (idx_t i = 0; i < chunk_size; i++) {
+		auto &hit = state.hits[state.position + i];
+		query_data[i] = hit.query_idx;
+		rowid_data[i] = hit.row_id;
+		dist_data[i] = hit.distance;
+	}
+
+	state.position += chunk_size;
+	output.SetCardinality(chunk_size);
+}
+
+static unique_ptr<NodeStatistics> LanceSearchBatchCardinality(ClientContext &context,
+                                                              const FunctionData *bind_data_p) {
+	auto &bind = bind_data_p->Cast<LanceSearchBatchBindData>();
+	auto max = static_cast<idx_t>(bind.num_queries) * bind.k;
+	return make_uniq<NodeStatistics>(max, max);
+}
+
 static void LanceSearchScan(ClientContext &context, TableFunctionInput &data, DataChunk &output) {
 	auto &state = data.global_state->Cast<LanceSearchState>();
 
@@ -98,6 +963,14 @@ static void LanceSearchScan(ClientContext &context, TableFunctionInput &data, Da
 		rowid_data[i] = state.row_ids[state.position + i];
 		dist_data[i] = state.distances[state.position + i];
 	}
+	if (state.with_diversity) {
+		auto mean_pairwise = state.diversity.mean_pairwise_distance;
+		auto distinct = state.diversity.distinct_values;
+		for (idx_t i = 0; i < chunk_size; i++) {
+			output.SetValue(2, i, std::isnan(mean_pairwise) ? Value(LogicalType::DOUBLE) : Value::DOUBLE(mean_pairwise));
+			output.SetValue(3, i, distinct < 0 ? Value(LogicalType::BIGINT) : Value::BIGINT(distinct));
+		}
+	}
 
 	state.position += chunk_size;
 	output.SetCardinality(chunk_size);
@@ -114,7 +987,87 @@ void RegisterLanceSearchFunction(ExtensionLoader &loader) {
 	    {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT), LogicalType::INTEGER},
 	    LanceSearchScan, LanceSearchBind, LanceSearchInit);
 	func.cardinality = LanceSearchCardinality;
+	func.named_parameters["model"] = LogicalType::VARCHAR;
+	func.named_parameters["negatives"] = LogicalType::LIST(LogicalType::LIST(LogicalType::FLOAT));
+	func.named_parameters["negative_weight"] = LogicalType::FLOAT;
+	func.named_parameters["dedup_column"] = LogicalType::VARCHAR;
+	func.named_parameters["weights"] = LogicalType::LIST(LogicalType::FLOAT);
+	func.named_parameters["weight_query"] = LogicalType::BOOLEAN;
+	func.named_parameters["min_distance"] = LogicalType::FLOAT;
+	func.named_parameters["max_distance"] = LogicalType::FLOAT;
+	func.named_parameters["consistency"] = LogicalType::VARCHAR;
+	func.named_parameters["ef"] = LogicalType::INTEGER;
+	func.named_parameters["diversity"] = LogicalType::BOOLEAN;
+	func.named_parameters["diversity_column"] = LogicalType::VARCHAR;
+	func.named_parameters["scope"] = LogicalType::VARCHAR;
 	loader.RegisterFunction(func);
+
+	TableFunction within_func("lance_search_within",
+	                          {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT),
+	                           LogicalType::INTEGER, LogicalType::LIST(LogicalType::BIGINT)},
+	                          LanceSearchScan, LanceSearchWithinBind, LanceSearchWithinInit);
+	within_func.cardinality = LanceSearchCardinality;
+	loader.RegisterFunction(within_func);
+
+	TableFunction pca_func("lance_search_pca",
+	                       {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT),
+	                        LogicalType::INTEGER},
+	                       LanceSearchScan, LanceSearchPcaBind, LanceSearchPcaInit);
+	pca_func.cardinality = LanceSearchCardinality;
+	pca_func.named_parameters["rescore_factor"] = LogicalType::INTEGER;
+	loader.RegisterFunction(pca_func);
+
+	TableFunction hybrid_func("lance_hybrid_search",
+	                          {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT),
+	                           LogicalType::VARCHAR, LogicalType::INTEGER},
+	                          LanceSearchScan, LanceHybridSearchBind, LanceHybridSearchInit);
+	hybrid_func.cardinality = LanceSearchCardinality;
+	hybrid_func.named_parameters["text_column"] = LogicalType::VARCHAR;
+	hybrid_func.named_parameters["fusion"] = LogicalType::VARCHAR;
+	hybrid_func.named_parameters["rrf_k"] = LogicalType::FLOAT;
+	hybrid_func.named_parameters["vector_weight"] = LogicalType::FLOAT;
+	loader.RegisterFunction(hybrid_func);
+
+	TableFunction like_func("lance_search_like",
+	                        {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::BIGINT),
+	                         LogicalType::INTEGER},
+	                        LanceSearchScan, LanceSearchLikeBind, LanceSearchLikeInit);
+	like_func.cardinality = LanceSearchLikeCardinality;
+	like_func.named_parameters["negatives"] = LogicalType::LIST(LogicalType::BIGINT);
+	like_func.named_parameters["negative_weight"] = LogicalType::FLOAT;
+	loader.RegisterFunction(like_func);
+
+	TableFunction sample_func("lance_search_sample",
+	                          {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT),
+	                           LogicalType::INTEGER, LogicalType::DOUBLE},
+	                          LanceSearchSampleScan, LanceSearchSampleBind, LanceSearchSampleInit);
+	sample_func.cardinality = LanceSearchCardinality;
+	loader.RegisterFunction(sample_func);
+
+	TableFunction expand_func("lance_search_expand",
+	                          {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT),
+	                           LogicalType::INTEGER},
+	                          LanceSearchExpandScan, LanceSearchExpandBind, LanceSearchExpandInit);
+	expand_func.cardinality = LanceSearchExpandCardinality;
+	expand_func.named_parameters["hops"] = LogicalType::INTEGER;
+	expand_func.named_parameters["per_hop"] = LogicalType::INTEGER;
+	expand_func.named_parameters["decay"] = LogicalType::DOUBLE;
+	loader.RegisterFunction(expand_func);
+
+	TableFunction parents_func("lance_search_parents",
+	                           {LogicalType::VARCHAR, LogicalType::VARCHAR, LogicalType::LIST(LogicalType::FLOAT),
+	                            LogicalType::INTEGER},
+	                           LanceSearchParentsScan, LanceSearchParentsBind, LanceSearchParentsInit);
+	parents_func.cardinality = LanceSearchCardinality;
+	parents_func.named_parameters["parent_column"] = LogicalType::VARCHAR;
+	loader.RegisterFunction(parents_func);
+
+	TableFunction batch_func("lance_search_batch",
+	                         {LogicalType::VARCHAR, LogicalType::VARCHAR,
+	                          LogicalType::LIST(LogicalType::LIST(LogicalType::FLOAT)), LogicalType::INTEGER},
+	                         LanceSearchBatchScan, LanceSearchBatchBind, LanceSearchBatchInit);
+	batch_func.cardinality = LanceSearchBatchCardinality;
+	loader.RegisterFunction(batch_func);
 }
 
 } // namespace duckdb
@@ -0,0 +1,195 @@
+#include "lancedb_extension.hpp"
+#include "rust_ffi.hpp"
+
+#include "duckdb/function/scalar_function.hpp"
+
+#include <cstring>
+
+namespace duckdb {
+
+// ========================================
+// lance_vector_add(a, b), lance_vector_sub(a, b), lance_vector_avg(vectors), lance_vector_normalize(v),
+// lance_vector_dot(a, b)
+// Vector arithmetic on FLOAT[] values, computed by the same kernels as the search paths. NULL inputs give
+// NULL; lance_vector_avg skips NULL vectors like AVG. normalize leaves a zero vector zero.
+// ========================================
+
+// Row-wise access to a FLOAT[] vector.
+struct FloatListInput {
+	FloatListInput(Vector &lists, idx_t count) {
+		lists.ToUnifiedFormat(count, list_format);
+		auto &child = ListVector::GetEntry(lists);
+		child.ToUnifiedFormat(ListVector::GetListSize(lists), child_format);
+	}
+
+	// Copy row's floats into out; false for a NULL list.
+	bool Get(idx_t row, vector<float> &out) const {
+		auto idx = list_format.sel->get_index(row);
+		if (!list_format.validity.RowIsValid(idx)) {
+			return false;
+		}
+		auto entry = UnifiedVectorFormat::GetData<list_entry_t>(list_format)[idx];
+		auto values = UnifiedVectorFormat::GetData<float>(child_format);
+		out.resize(entry.length);
+		for (idx_t i = 0; i < entry.length; i++) {
+			auto child_idx = child_format.sel->get_index(entry.offset + i);
+			if (!child_format.validity.RowIsValid(child_idx)) {
+				throw InvalidInputException("vector elements must not be NULL");
+			}
+			out[i] = values[child_idx];
+		}
+		return true;
+	}
+
+	UnifiedVectorFormat list_format;
+	UnifiedVectorFormat child_format;
+};
+
+static void SetFloatList(Vector &result, idx_t row, const vector<float> &values) {
+	auto offset = ListVector::GetListSize(result);
+	ListVector::Reserve(result, offset + values.size());
+	auto &child = ListVector::GetEntry(result);
+	if (!values.empty()) {
+		memcpy(FlatVector::GetData<float>(child) + offset, values.data(), values.size() * sizeof(float));
+	}
+	ListVector::SetListSize(result, offset + values.size());
+	FlatVector::GetData<list_entry_t>(result)[row] = list_entry_t(offset, values.size());
+}
+
+static void CheckSameDimension(const char *name, const vector<float> &a, const vector<float> &b) {
+	if (a.size() != b.size()) {
+		throw InvalidInputException("%s: dimension mismatch (%llu vs %llu)", name, a.size(), b.size());
+	}
+}
+
+static void FinishResult(DataChunk &args, Vector &result) {
+	if (args.AllConstant()) {
+		result.SetVectorType(VectorType::CONSTANT_VECTOR);
+	}
+}
+
+typedef void (*LanceBinaryVectorKernel)(const float *a, const float *b, int32_t dim, float *out);
+
+static void BinaryVectorFunction(const char *name, LanceBinaryVectorKernel kernel, DataChunk &args,
+                                 Vector &result) {
+	auto count = args.size();
+	FloatListInput left(args.data[0], count);
+	FloatListInput right(args.data[1], count);
+	vector<float> a, b, out;
+	for (idx_t row = 0; row < count; row++) {
+		if (!left.Get(row, a) || !right.Get(row, b)) {
+			FlatVector::SetNull(result, row, true);
+			continue;
+		}
+		CheckSameDimension(name, a, b);
+		out.resize(a.size());
+		if (!a.empty()) {
+			kernel(a.data(), b.data(), NumericCast<int32_t>(a.size()), out.data());
+		}
+		SetFloatList(result, row, out);
+	}
+	FinishResult(args, result);
+}
+
+static void VectorAddFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	BinaryVectorFunction("lance_vector_add", LanceVectorAdd, args, result);
+}
+
+static void VectorSubFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	BinaryVectorFunction("lance_vector_sub", LanceVectorSubtract, args, result);
+}
+
+static void VectorNormalizeFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	auto count = args.size();
+	FloatListInput input(args.data[0], count);
+	vector<float> v, out;
+	for (idx_t row = 0; row < count; row++) {
+		if (!input.Get(row, v)) {
+			FlatVector::SetNull(result, row, true);
+			continue;
+		}
+		out.resize(v.size());
+		if (!v.empty()) {
+			LanceVectorNormalize(v.data(), NumericCast<int32_t>(v.size()), out.data());
+		}
+		SetFloatList(result, row, out);
+	}
+	FinishResult(args, result);
+}
+
+static void VectorDotFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	auto count = args.size();
+	FloatListInput left(args.data[0], count);
+	FloatListInput right(args.data[1], count);
+	auto out = FlatVector::GetData<float>(result);
+	vector<float> a, b;
+	for (idx_t row = 0; row < count; row++) {
+		if (!left.Get(row, a) || !right.Get(row, b)) {
+			FlatVector::SetNull(result, row, true);
+			continue;
+		}
+		CheckSameDimension("lance_vector_dot", a, b);
+		out[row] = a.empty() ? 0 : LanceVectorDot(a.data(), b.data(), NumericCast<int32_t>(a.size()));
+	}
+	FinishResult(args, result);
+}
+
+static void VectorAvgFunction(DataChunk &args, ExpressionState &state, Vector &result) {
+	auto count = args.size();
+	auto &lists = args.data[0];
+	UnifiedVectorFormat lists_format;
+	lists.ToUnifiedFormat(count, lists_format);
+	auto entries = UnifiedVectorFormat::GetData<list_entry_t>(lists_format);
+	FloatListInput vectors(ListVector::GetEntry(lists), ListVector::GetListSize(lists));
+
+	vector<float> flat, v, out;
+	for (idx_t row = 0; row < count; row++) {
+		auto idx = lists_format.sel->get_index(row);
+		if (!lists_format.validity.RowIsValid(idx)) {
+			FlatVector::SetNull(result, row, true);
+			continue;
+		}
+		auto entry = entries[idx];
+		flat.clear();
+		idx_t n = 0;
+		idx_t dim = 0;
+		for (idx_t i = 0; i < entry.length; i++) {
+			if (!vectors.Get(entry.offset + i, v)) {
+				continue;
+			}
+			if (n == 0) {
+				dim = v.size();
+			} else if (v.size() != dim) {
+				throw InvalidInputException("lance_vector_avg: dimension mismatch (%llu vs %llu)", dim, v.size());
+			}
+			flat.insert(flat.end(), v.begin(), v.end());
+			n++;
+		}
+		if (n == 0) {
+			FlatVector::SetNull(result, row, true);
+			continue;
+		}
+		out.resize(dim);
+		if (dim > 0) {
+			LanceVectorAverage(flat.data(), NumericCast<int32_t>(n), NumericCast<int32_t>(dim), out.data());
+		}
+		SetFloatList(result, row, out);
+	}
+	FinishResult(args, result);
+}
+
+void RegisterLanceVectorMathFunctions(ExtensionLoader &loader) {
+	auto vector_type = LogicalType::LIST(LogicalType::FLOAT);
+	loader.RegisterFunction(
+	    ScalarFunction("lance_vector_add", {vector_type, vector_type}, vector_type, VectorAddFunction));
+	loader.RegisterFunction(
+	    ScalarFunction("lance_vector_sub", {vector_type, vector_type}, vector_type, VectorSubFunction));
+	loader.RegisterFunction(
+	    ScalarFunction("lance_vector_avg", {LogicalType::LIST(vector_type)}, vector_type, VectorAvgFunction));
+	loader.RegisterFunction(
+	    ScalarFunction("lance_vector_normalize", {vector_type}, vector_type, VectorNormalizeFunction));
+	loader.RegisterFunction(
+	    ScalarFunction("lance_vector_dot", {vector_type, vector_type}, LogicalType::FLOAT, VectorDotFunction));
+}
+
+} // namespace duckdb
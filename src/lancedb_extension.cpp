@@ -24,7 +24,69 @@ static void LoadInternal(ExtensionLoader &loader) {
 	RegisterLanceSearchFunction(loader);
 	RegisterLanceCreateAnnIndexFunction(loader);
 	RegisterLanceCreateHnswIndexFunction(loader);
+	RegisterLanceCreateIvfFlatIndexFunction(loader);
+	RegisterLanceCreateFtsIndexFunction(loader);
+	RegisterLanceCreateScalarIndexFunction(loader);
+	RegisterLanceRebuildIndexFunction(loader);
+	RegisterLanceRebuildStatusFunction(loader);
+	RegisterLanceCreateStagingFunction(loader);
+	RegisterLancePromoteStagingFunction(loader);
+	RegisterLanceRenameTableFunction(loader);
+	RegisterLanceSetIndexBuildLimitsFunction(loader);
+	RegisterLanceSetReadBatchSizeFunction(loader);
+	RegisterLanceRefinePlanFunction(loader);
+	RegisterLanceSetHitBudgetFunction(loader);
+	RegisterLanceSetMergeIndexingFunction(loader);
+	RegisterLanceIndexStalenessFunction(loader);
+	RegisterLanceRepairLabelWatermarkFunction(loader);
+	RegisterLanceRemapLabelsFunction(loader);
+	RegisterLanceRetainOnlyFunction(loader);
+	RegisterLancePrefetchFunction(loader);
+	RegisterLancePingFunction(loader);
+	RegisterLanceRuntimeConfigFunction(loader);
+	RegisterLanceReadLimitsFunction(loader);
+	RegisterLanceDiskCacheFunction(loader);
+	RegisterLanceSetDefaultStorageOptionsFunction(loader);
+	RegisterLanceHandlesFunction(loader);
+	RegisterLanceInspectFunction(loader);
+	RegisterLanceClusterByFunction(loader);
+	RegisterLanceSetPipelineFunction(loader);
+	RegisterLanceSetRescoreMetricFunction(loader);
+	RegisterLanceSetQueryTransformFunction(loader);
+	RegisterLanceSetQuotaFunction(loader);
+	RegisterLanceSetColdStorageFunction(loader);
+	RegisterLanceSetChunkingFunction(loader);
+	RegisterLanceSetConstraintsFunction(loader);
+	RegisterLanceSetOnInvalidFunction(loader);
+	RegisterLanceSetColumnMatchingFunction(loader);
+	RegisterLanceRejectsFunction(loader);
+	RegisterLanceSetRetentionFunction(loader);
+	RegisterLanceSetAccessTrackingFunction(loader);
+	RegisterLanceSetRowTtlFunction(loader);
+	RegisterLanceSetSensitiveColumnsFunction(loader);
+	RegisterLanceSetWriterLeaseFunction(loader);
+	RegisterLanceTrainPcaFunction(loader);
+	RegisterLanceTrainOpqFunction(loader);
+	RegisterLanceColdRowsFunction(loader);
+	RegisterLanceSetQueryLogFunction(loader);
+	RegisterLanceReplayQueriesFunction(loader);
+	RegisterLanceTagDriftBaselineFunction(loader);
+	RegisterLanceDriftReportFunction(loader);
+	RegisterLanceRunMaintenanceFunction(loader);
+	RegisterLanceApproxStatsFunction(loader);
+	RegisterLanceKnnGraphFunction(loader);
+	RegisterLanceRowAddressesFunction(loader);
 	RegisterLanceInfoFunction(loader);
+	RegisterLanceDiskUsageFunction(loader);
+	RegisterLanceBackupFunction(loader);
+	RegisterLanceLabelBitmapFunction(loader);
+	RegisterLanceLabelBitmapOps(loader);
+	RegisterLanceExportManifestFunction(loader);
+	RegisterLanceFormatInfoFunction(loader);
+	RegisterLanceImportManifestFunction(loader);
+
+	// Register scalar functions
+	RegisterLanceVectorMathFunctions(loader);
 
 	// Register optimizer
 	RegisterLanceOptimizer(db);
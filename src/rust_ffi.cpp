@@ -1,55 +1,380 @@
 // Rust LanceDB FFI wrapper for DuckDB extension
 
 #include "rust_ffi.hpp"
+#include "duckdb/common/arrow/arrow.hpp"
 #include "duckdb/common/exception.hpp"
+#include "duckdb/common/types/timestamp.hpp"
+#include <cstring>
+#include <memory>
 #include <string>
 
 extern "C" {
 
 void *lance_create_detached(const char *db_path, int32_t dimension, const char *metric, const char *table_name,
-                            char *err_buf, int err_buf_len);
+                            const char *storage, char *err_buf, int err_buf_len);
 void *lance_create_detached_from_arrow(const char *db_path, void *arrow_schema, const char *metric,
                                        const char *table_name, char *err_buf, int err_buf_len);
+void *lance_create_detached_from_arrow_with_vectors(const char *db_path, void *arrow_schema, const char *metric,
+                                                    const char *table_name, const char *vector_columns,
+                                                    const char *storage, char *err_buf, int err_buf_len);
 void *lance_open_detached(const char *db_path, const char *table_name, const char *metric, char *err_buf,
                           int err_buf_len);
+void *lance_open_detached_scoped(const char *db_path, const char *table_name, const char *metric, const char *scope,
+                                 char *err_buf, int err_buf_len);
 void lance_free_detached(void *handle);
+int64_t lance_inspect_table(const char *db_path, const char *table_name, void *out_schema, void *out_array,
+                            char *err_buf, int err_buf_len);
+int32_t lance_rename_table(const char *db_path, const char *old_name, const char *new_name, char *err_buf,
+                           int err_buf_len);
+int32_t lance_list_handles(void *out_schema, void *out_array, char *err_buf, int err_buf_len);
 int32_t lance_detached_has_extra_columns(void *handle);
 int32_t lance_detached_dimension(void *handle);
+int32_t lance_detached_accepts_query_dim(void *handle, int32_t dim);
 int64_t lance_detached_add(void *handle, const float *vector, int32_t dimension, char *err_buf, int err_buf_len);
 int32_t lance_detached_add_batch(void *handle, const float *vectors, int32_t num, int32_t dim, int64_t *out_labels,
                                  char *err_buf, int err_buf_len);
-int32_t lance_detached_add_batch_arrow(void *handle, void *arrow_schema, void *arrow_array, int64_t *out_labels,
-                                       char *err_buf, int err_buf_len);
+int32_t lance_detached_add_batch_owned(void *handle, const float *vectors, int32_t num, int32_t dim,
+                                       void (*release)(void *), void *release_ctx, int64_t *out_labels, char *err_buf,
+                                       int err_buf_len);
+int32_t lance_detached_begin_append_session(void *handle, char *err_buf, int err_buf_len);
+int32_t lance_detached_end_append_session(void *handle, char *err_buf, int err_buf_len);
+int32_t lance_detached_add_batch_arrow(void *handle, void *arrow_schema, void *arrow_array, const char *model,
+                                       const char *token, int64_t *out_labels, int64_t *out_rejected,
+                                       uint8_t *out_status, char *err_buf, int err_buf_len);
 int32_t lance_detached_merge(void *target_handle, void *source_handle, const int64_t *live_source_labels,
-                             int32_t live_count, int64_t *out_old_labels, int64_t *out_new_labels, char *err_buf,
+                             int32_t live_count, int32_t strict, const char *token, void *out_stream, int64_t *out_indexed_rows,
+                             int64_t *out_unindexed_rows, int32_t *out_retrain_recommended, char *err_buf,
                              int err_buf_len);
+int32_t lance_detached_set_merge_indexing(void *handle, int32_t mode, char *err_buf, int err_buf_len);
+int32_t lance_detached_index_staleness(void *handle, int64_t *out_indexed_rows, int64_t *out_unindexed_rows,
+                                       char *err_buf, int err_buf_len);
+int32_t lance_detached_repair_label_watermark(void *handle, int64_t *out_max_label, char *err_buf, int err_buf_len);
+int64_t lance_detached_remap_labels(void *handle, void *arrow_schema, void *arrow_array, char *err_buf,
+                                    int err_buf_len);
+int64_t lance_detached_retain_only(void *handle, void *arrow_schema, void *arrow_array, int32_t allow_empty,
+                                   char *err_buf, int err_buf_len);
+int64_t lance_detached_schema_diff(void *handle, void *target_schema, void *out_schema, void *out_array,
+                                   char *err_buf, int err_buf_len);
+int64_t lance_detached_apply_migration(void *handle, void *target_schema, char *err_buf, int err_buf_len);
 int32_t lance_detached_search(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
-                              int32_t refine_factor, int64_t *out_labels, float *out_distances, char *err_buf,
-                              int err_buf_len);
+                              int32_t refine_factor, int32_t ef, const char *predicate, const char *model,
+                              const float *weights, int32_t weights_len, int32_t weight_query, int32_t privileged,
+                              int64_t *out_labels, float *out_distances, char *err_buf, int err_buf_len);
+int32_t lance_detached_search_diversity(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                        int32_t refine_factor, int32_t ef, const char *predicate, const char *model,
+                                        const char *column, int32_t privileged, int64_t *out_labels,
+                                        float *out_distances, double *out_mean_pairwise, int64_t *out_distinct,
+                                        char *err_buf, int err_buf_len);
+int32_t lance_detached_search_consistent(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                         int32_t refine_factor, const char *predicate, const char *model,
+                                         const char *consistency, int32_t privileged, int64_t *out_labels,
+                                         float *out_distances, char *err_buf, int err_buf_len);
+int32_t lance_detached_search_batch(void *handle, const float *queries, int32_t dim, int32_t num_queries, int32_t k,
+                                    int32_t nprobes, int32_t refine_factor, const char *predicate, int32_t concurrency,
+                                    int32_t privileged, int32_t *out_query_idx, int64_t *out_labels,
+                                    float *out_distances, char *err_buf, int err_buf_len);
+int32_t lance_detached_search_range(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                    int32_t refine_factor, float lower_bound, float upper_bound, const char *predicate,
+                                    const char *model, int32_t privileged, int64_t *out_labels, float *out_distances,
+                                    char *err_buf, int err_buf_len);
+int32_t lance_detached_search_with_negatives(void *handle, const float *query, int32_t dim, const float *negatives,
+                                             int32_t negative_count, float weight, int32_t k, int32_t nprobes,
+                                             int32_t refine_factor, const char *predicate, const char *model,
+                                             int32_t privileged, int64_t *out_labels, float *out_distances,
+                                             char *err_buf, int err_buf_len);
+int32_t lance_detached_search_dedup(void *handle, const float *query, int32_t dim, int32_t k, const char *dedup_column,
+                                    int32_t nprobes, int32_t refine_factor, const char *predicate, const char *model,
+                                    int32_t privileged, int64_t *out_labels, float *out_distances, char *err_buf,
+                                    int err_buf_len);
+int32_t lance_detached_search_parents(void *handle, const float *query, int32_t dim, int32_t k,
+                                      const char *parent_column, int32_t nprobes, int32_t refine_factor,
+                                      const char *predicate, const char *model, int32_t privileged,
+                                      int64_t *out_parents, int64_t *out_labels, float *out_distances, char *err_buf,
+                                      int err_buf_len);
+int32_t lance_detached_search_like_labels(void *handle, const int64_t *labels, int32_t label_count,
+                                          const int64_t *negative_labels, int32_t negative_count, float weight,
+                                          int32_t k, int32_t nprobes, int32_t refine_factor, const char *predicate,
+                                          int32_t privileged, int64_t *out_labels, float *out_distances, char *err_buf,
+                                          int err_buf_len);
+int32_t lance_detached_search_sampled(void *handle, const float *query, int32_t dim, int32_t k, double fraction,
+                                      int32_t nprobes, int32_t refine_factor, const char *predicate, int32_t privileged,
+                                      int64_t *out_labels, float *out_distances, double *out_estimated_ranks,
+                                      double *out_fraction, char *err_buf, int err_buf_len);
+int32_t lance_detached_search_expand(void *handle, const float *query, int32_t dim, int32_t k, int32_t hops,
+                                     int32_t per_hop, double decay, int32_t nprobes, int32_t refine_factor,
+                                     int64_t *out_labels, float *out_distances, int32_t *out_hops, double *out_scores,
+                                     char *err_buf, int err_buf_len);
+int64_t lance_detached_prefetch(void *handle, const int64_t *labels, int32_t label_count, const char *predicate,
+                                char *err_buf, int err_buf_len);
+int32_t lance_detached_search_within(void *handle, const float *query, int32_t dim, int32_t k, const int64_t *labels,
+                                     int32_t label_count, int64_t *out_labels, float *out_distances, char *err_buf,
+                                     int err_buf_len);
+int32_t lance_detached_search_pca(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                  int32_t rescore_factor, int64_t *out_labels, float *out_distances, char *err_buf,
+                                  int err_buf_len);
+int32_t lance_detached_train_pca(void *handle, int32_t target_dims, int64_t sample, int32_t index_type,
+                                 double *out_explained_variance, char *err_buf, int err_buf_len);
+int32_t lance_detached_hybrid_search(void *handle, const float *query, int32_t dim, const char *text,
+                                     const char *text_column, int32_t k, int32_t nprobes, int32_t refine_factor,
+                                     const char *predicate, int32_t fusion, float rrf_k, float vector_weight,
+                                     int32_t privileged, int64_t *out_labels, float *out_scores, char *err_buf,
+                                     int err_buf_len);
+int32_t lance_detached_create_fts_index(void *handle, const char *column, char *err_buf, int err_buf_len);
+int32_t lance_detached_create_scalar_index(void *handle, const char *column, int32_t index_type, char *err_buf,
+                                           int err_buf_len);
+int32_t lance_detached_train_rotation(void *handle, int32_t num_sub_vectors, int64_t sample, char *err_buf,
+                                      int err_buf_len);
+int64_t lance_detached_row_addresses(void *handle, void *out_stream, char *err_buf, int err_buf_len);
+int64_t lance_detached_knn_graph(void *handle, int32_t k, int64_t sample, int32_t nprobes, int32_t refine_factor,
+                                 void *out_stream, char *err_buf, int err_buf_len);
+void *lance_detached_search_cursor_open(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                        int32_t refine_factor, char *err_buf, int err_buf_len);
+int32_t lance_search_cursor_next(void *cursor, int64_t *out_labels, float *out_distances, int32_t capacity,
+                                 char *err_buf, int err_buf_len);
+void lance_search_cursor_free(void *cursor);
 int64_t lance_detached_count(void *handle, char *err_buf, int err_buf_len);
+int32_t lance_detached_ping(void *handle, int64_t *out_version, double *out_elapsed_ms, char *err_buf,
+                            int err_buf_len);
 int32_t lance_detached_delete(void *handle, int64_t label, char *err_buf, int err_buf_len);
-int32_t lance_detached_delete_batch(void *handle, const int64_t *labels, int32_t count, char *err_buf,
-                                    int err_buf_len);
+int32_t lance_detached_delete_batch(void *handle, const int64_t *labels, int32_t count, const char *token,
+                                    char *err_buf, int err_buf_len);
+int64_t lance_detached_apply_changes(void *handle, const int64_t *deletes, int32_t delete_count, void *arrow_schema,
+                                     void *arrow_array, int64_t expected_version, int64_t *out_deleted,
+                                     int64_t *out_updated, char *err_buf, int err_buf_len);
 int32_t lance_detached_create_index(void *handle, int32_t num_partitions, int32_t num_sub_vectors, char *err_buf,
                                     int err_buf_len);
 int32_t lance_detached_create_hnsw_index(void *handle, int32_t m, int32_t ef_construction, char *err_buf,
                                           int err_buf_len);
+int32_t lance_detached_create_vector_index(void *handle, int32_t index_type, int32_t num_partitions,
+                                           int32_t num_sub_vectors, int32_t m, int32_t ef_construction,
+                                           int32_t sample_rate, const char *metric, char *err_buf,
+                                           int err_buf_len);
+int32_t lance_detached_rebuild_index(void *handle, int32_t index_type, int32_t num_partitions, int32_t num_sub_vectors,
+                                     int32_t m, int32_t ef_construction, int32_t sample_rate, const char *metric,
+                                     int32_t wait, char *err_buf, int err_buf_len);
+int32_t lance_detached_rebuild_status(void *handle, void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+void *lance_detached_begin_read_snapshot(void *handle, uint64_t *out_version, char *err_buf, int err_buf_len);
+int32_t lance_detached_release_snapshot(void *snapshot, char *err_buf, int err_buf_len);
+void *lance_detached_create_staging(void *handle, int32_t copy_rows, char *err_buf, int err_buf_len);
+int32_t lance_detached_promote_staging(void *handle, void *staging_handle, char *out_retired, int32_t out_retired_len,
+                                       char *err_buf, int err_buf_len);
+int32_t lance_detached_set_read_batch_size(void *handle, int32_t rows, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_hit_budget(void *handle, int32_t max_nprobes, int64_t max_exact_rows, int32_t max_overfetch,
+                                      char *err_buf, int err_buf_len);
+int32_t lance_detached_hit_budget(void *handle, int32_t *out_max_nprobes, int64_t *out_max_exact_rows,
+                                  int32_t *out_max_overfetch, char *err_buf, int err_buf_len);
+int32_t lance_detached_refine_plan(void *handle, int32_t refine_factor, int32_t k, int64_t *out_factor,
+                                   int64_t *out_rescored, double *out_compression, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_index_build_limits(void *handle, int64_t max_memory_bytes, char *err_buf, int err_buf_len);
 int32_t lance_detached_compact(void *handle, char *err_buf, int err_buf_len);
 int32_t lance_detached_get_vector(void *handle, int64_t label, float *out_vec, int32_t capacity, char *err_buf,
                                   int err_buf_len);
 int32_t lance_detached_get_all_vectors(void *handle, int64_t *out_labels, float *out_vectors, int64_t *out_count,
                                        char *err_buf, int err_buf_len);
+int32_t lance_detached_disk_usage(void *handle, int32_t include_columns, void *out_schema, void *out_array,
+                                  char *err_buf, int err_buf_len);
+int32_t lance_detached_backup(void *handle, const char *dest, int64_t since_version, int64_t *out_version,
+                              int64_t *out_files, int64_t *out_bytes, char *err_buf, int err_buf_len);
+int32_t lance_detached_label_bitmap(void *handle, void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+int32_t lance_label_bitmap_combine(const uint8_t *a, int64_t a_len, const uint8_t *b, int64_t b_len, int32_t op,
+                                   void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+int64_t lance_label_bitmap_labels(const uint8_t *bitmap, int64_t bitmap_len, void *out_schema, void *out_array,
+                                  char *err_buf, int err_buf_len);
+int32_t lance_detached_export_manifest(void *handle, void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+int32_t lance_detached_format_info(void *handle, void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+int64_t lance_detached_import_manifest(void *handle, const uint8_t *blob, int64_t blob_len, char *err_buf,
+                                       int err_buf_len);
+int32_t lance_detached_column_stats(void *handle, const char *column, int32_t privileged, void *out_schema,
+                                    void *out_array, char *err_buf, int err_buf_len);
+int32_t lance_detached_approx_stats(void *handle, const char *column, const float *query, int32_t dim,
+                                    double sample_fraction, int32_t max_groups, int32_t privileged,
+                                    double *out_distinct_groups, void *out_schema, void *out_array, char *err_buf,
+                                    int err_buf_len);
+int32_t lance_detached_set_sensitive_columns(void *handle, const char *columns, char *err_buf, int err_buf_len);
+int32_t lance_detached_cluster_by(void *handle, const char *column, int64_t rows_per_fragment, char *err_buf,
+                                  int err_buf_len);
+int32_t lance_detached_set_query_transform(void *handle, const char *spec, const float *mean, int32_t mean_len,
+                                           char *err_buf, int err_buf_len);
+int32_t lance_detached_set_pipeline(void *handle, const char *spec, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_access_tracking(void *handle, int32_t enabled, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_row_ttl(void *handle, int32_t enabled, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_writer_lease(void *handle, int64_t ttl_secs, char *err_buf, int err_buf_len);
+int32_t lance_detached_flush_access_stats(void *handle, char *err_buf, int err_buf_len);
+int32_t lance_detached_cold_rows(void *handle, int64_t idle_ms, void *out_schema, void *out_array, char *err_buf,
+                                 int err_buf_len);
+int32_t lance_detached_set_query_log(void *handle, const char *spec, char *err_buf, int err_buf_len);
+int32_t lance_detached_replay_queries(void *handle, const char *tag, void *out_schema, void *out_array, char *err_buf,
+                                      int err_buf_len);
+int32_t lance_detached_set_quota(void *handle, const char *spec, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_cold_storage(void *handle, const char *spec, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_constraints(void *handle, const char *spec, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_on_invalid(void *handle, const char *mode, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_column_matching(void *handle, const char *mode, char *err_buf, int err_buf_len);
+int64_t lance_detached_rejects(void *handle, void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+int32_t lance_register_embedder(const char *name, duckdb::LanceEmbedFn callback, void *user_data, char *err_buf,
+                                int err_buf_len);
+int32_t lance_detached_set_chunking(void *handle, const char *spec, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_embedding_model(void *handle, const char *model, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_int8_range(void *handle, float min, float max, char *err_buf, int err_buf_len);
+int32_t lance_detached_tag_drift_baseline(void *handle, const char *tag, char *err_buf, int err_buf_len);
+int32_t lance_detached_drift_report(void *handle, const char *tag, void *out_schema, void *out_array, char *err_buf,
+                                   int err_buf_len);
+int32_t lance_detached_run_maintenance(void *handle, const char *plan, void *out_schema, void *out_array,
+                                       char *err_buf, int err_buf_len);
+int32_t lance_detached_search_async(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                    int32_t refine_factor, const char *predicate, int32_t privileged,
+                                    void (*callback)(void *, const int64_t *, const float *, int32_t, const char *),
+                                    void *user_data, char *err_buf, int err_buf_len);
+int32_t lance_detached_add_batch_async(void *handle, const float *vectors, int32_t num, int32_t dim,
+                                       void (*callback)(void *, const int64_t *, int32_t, const char *),
+                                       void *user_data, char *err_buf, int err_buf_len);
+int64_t lance_detached_search_submit(void *handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                     int32_t refine_factor, const char *predicate, int32_t privileged, char *err_buf,
+                                     int err_buf_len);
+int64_t lance_detached_add_batch_submit(void *handle, const float *vectors, int32_t num, int32_t dim, char *err_buf,
+                                        int err_buf_len);
+int32_t lance_task_poll(int64_t task_id);
+int32_t lance_task_take_result(int64_t task_id, void *out_schema, void *out_array, char *err_buf, int err_buf_len);
+void lance_task_discard(int64_t task_id);
+int32_t lance_runtime_configure(int32_t io_threads, int32_t cpu_threads, int32_t *out_io_threads,
+                                int32_t *out_cpu_threads, char *err_buf, int err_buf_len);
+int32_t lance_read_limits_configure(int64_t max_rows, int64_t max_bytes, int64_t *out_max_rows,
+                                    int64_t *out_max_bytes);
+int32_t lance_set_default_storage_options(const char *const *pairs, int32_t count, char *err_buf, int err_buf_len);
+int32_t lance_register_credential_provider(duckdb::LanceCredentialFn callback, void *user_data, char *err_buf,
+                                           int err_buf_len);
+int32_t lance_disk_cache_configure(const char *dir, int64_t max_bytes, char *err_buf, int err_buf_len);
+int32_t lance_disk_cache_stats(int64_t *out_max_bytes, int64_t *out_bytes, int64_t *out_files, int64_t *out_hits,
+                               int64_t *out_misses);
+int32_t lance_register_reranker(const char *name, duckdb::LanceRerankFn callback, void *user_data, char *err_buf,
+                                int err_buf_len);
+int32_t lance_register_distance(const char *name, duckdb::LanceDistanceFn callback, void *user_data, char *err_buf,
+                                int err_buf_len);
+int32_t lance_vector_add(const float *a, const float *b, int32_t dim, float *out, char *err_buf, int err_buf_len);
+int32_t lance_vector_subtract(const float *a, const float *b, int32_t dim, float *out, char *err_buf, int err_buf_len);
+int32_t lance_vector_average(const float *vectors, int32_t count, int32_t dim, float *out, char *err_buf,
+                             int err_buf_len);
+int32_t lance_vector_normalize(const float *vector, int32_t dim, float *out, char *err_buf, int err_buf_len);
+int32_t lance_vector_dot(const float *a, const float *b, int32_t dim, float *out, char *err_buf, int err_buf_len);
+int32_t lance_detached_set_rescore_metric(void *handle, const char *metric, char *err_buf, int err_buf_len);
+int32_t lance_detached_pruning_stats(void *handle, const char *predicate, int64_t *out_total_fragments,
+                                     int64_t *out_matching_fragments, char *out_cluster_by, int out_cluster_by_len,
+                                     char *err_buf, int err_buf_len);
 }
 
 namespace duckdb {
 
 constexpr int ERR_BUF_LEN = 2048;
 
+// ========================================
+// Arrow result helpers (flat struct arrays exported by Rust)
+// ========================================
+
+// Read a Utf8 child value. Returns empty string for nulls.
+static std::string ArrowStringAt(const ArrowArray &child, int64_t row) {
+	auto idx = child.offset + row;
+	auto validity = static_cast<const uint8_t *>(child.buffers[0]);
+	if (validity && !(validity[idx / 8] & (1 << (idx % 8)))) {
+		return std::string();
+	}
+	auto offsets = static_cast<const int32_t *>(child.buffers[1]);
+	auto data = static_cast<const char *>(child.buffers[2]);
+	return std::string(data + offsets[idx], offsets[idx + 1] - offsets[idx]);
+}
+
+static int64_t ArrowInt64At(const ArrowArray &child, int64_t row) {
+	return static_cast<const int64_t *>(child.buffers[1])[child.offset + row];
+}
+
+template <class T>
+static T ArrowPrimitiveAt(const ArrowArray &child, int64_t row) {
+	return static_cast<const T *>(child.buffers[1])[child.offset + row];
+}
+
+// Decode one value of a scalar child into a DuckDB Value, using its Arrow format string.
+static Value ArrowValueAt(const ArrowSchema &schema, const ArrowArray &child, int64_t row) {
+	auto idx = child.offset + row;
+	auto validity = static_cast<const uint8_t *>(child.buffers[0]);
+	if (validity && !(validity[idx / 8] & (1 << (idx % 8)))) {
+		return Value();
+	}
+	std::string format(schema.format);
+	if (format == "c") {
+		return Value::TINYINT(ArrowPrimitiveAt<int8_t>(child, row));
+	} else if (format == "s") {
+		return Value::SMALLINT(ArrowPrimitiveAt<int16_t>(child, row));
+	} else if (format == "i") {
+		return Value::INTEGER(ArrowPrimitiveAt<int32_t>(child, row));
+	} else if (format == "l") {
+		return Value::BIGINT(ArrowPrimitiveAt<int64_t>(child, row));
+	} else if (format == "C") {
+		return Value::UTINYINT(ArrowPrimitiveAt<uint8_t>(child, row));
+	} else if (format == "S") {
+		return Value::USMALLINT(ArrowPrimitiveAt<uint16_t>(child, row));
+	} else if (format == "I") {
+		return Value::UINTEGER(ArrowPrimitiveAt<uint32_t>(child, row));
+	} else if (format == "L") {
+		return Value::UBIGINT(ArrowPrimitiveAt<uint64_t>(child, row));
+	} else if (format == "f") {
+		return Value::FLOAT(ArrowPrimitiveAt<float>(child, row));
+	} else if (format == "g") {
+		return Value::DOUBLE(ArrowPrimitiveAt<double>(child, row));
+	} else if (format == "b") {
+		auto bits = static_cast<const uint8_t *>(child.buffers[1]);
+		return Value::BOOLEAN((bits[idx / 8] >> (idx % 8)) & 1);
+	} else if (format == "u") {
+		return Value(ArrowStringAt(child, row));
+	} else if (format == "tdD") {
+		return Value::DATE(date_t(ArrowPrimitiveAt<int32_t>(child, row)));
+	} else if (format == "tdm") {
+		return Value::DATE(date_t(static_cast<int32_t>(ArrowPrimitiveAt<int64_t>(child, row) / 86400000)));
+	} else if (format.rfind("tss", 0) == 0) {
+		return Value::TIMESTAMP(Timestamp::FromEpochSeconds(ArrowPrimitiveAt<int64_t>(child, row)));
+	} else if (format.rfind("tsm", 0) == 0) {
+		return Value::TIMESTAMP(Timestamp::FromEpochMs(ArrowPrimitiveAt<int64_t>(child, row)));
+	} else if (format.rfind("tsu", 0) == 0) {
+		return Value::TIMESTAMP(Timestamp::FromEpochMicroSeconds(ArrowPrimitiveAt<int64_t>(child, row)));
+	} else if (format.rfind("tsn", 0) == 0) {
+		return Value::TIMESTAMP(Timestamp::FromEpochNanoSeconds(ArrowPrimitiveAt<int64_t>(child, row)));
+	}
+	throw IOException("Lance column_stats: unsupported Arrow format '" + format + "'");
+}
+
+// Releases an exported Arrow schema/array pair when it goes out of scope.
+struct ArrowExportGuard {
+	ArrowSchema schema;
+	ArrowArray array;
+	ArrowExportGuard() {
+		memset(&schema, 0, sizeof(ArrowSchema));
+		memset(&array, 0, sizeof(ArrowArray));
+	}
+	~ArrowExportGuard() {
+		if (array.release) {
+			array.release(&array);
+		}
+		if (schema.release) {
+			schema.release(&schema);
+		}
+	}
+};
+
+struct ArrowStreamGuard {
+	ArrowArrayStream stream;
+	ArrowStreamGuard() {
+		memset(&stream, 0, sizeof(ArrowArrayStream));
+	}
+	~ArrowStreamGuard() {
+		if (stream.release) {
+			stream.release(&stream);
+		}
+	}
+};
+
 LanceHandle LanceCreateDetached(const std::string &db_path, int32_t dimension, const std::string &metric,
-                                const std::string &table_name) {
+                                const std::string &table_name, const std::string &storage) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	auto handle = lance_create_detached(db_path.c_str(), dimension, metric.c_str(), table_name.c_str(), err_buf,
-	                                    ERR_BUF_LEN);
+	auto handle = lance_create_detached(db_path.c_str(), dimension, metric.c_str(), table_name.c_str(),
+	                                    storage.c_str(), err_buf, ERR_BUF_LEN);
 	if (!handle) {
 		throw IOException("Lance create: " + std::string(err_buf));
 	}
@@ -57,19 +382,28 @@ LanceHandle LanceCreateDetached(const std::string &db_path, int32_t dimension, c
 }
 
 LanceHandle LanceCreateDetachedFromArrow(const std::string &db_path, void *arrow_schema, const std::string &metric,
-                                         const std::string &table_name) {
+                                         const std::string &table_name, const std::string &vector_columns,
+                                         const std::string &storage) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	auto handle = lance_create_detached_from_arrow(db_path.c_str(), arrow_schema, metric.c_str(), table_name.c_str(),
-	                                               err_buf, ERR_BUF_LEN);
+	auto handle = vector_columns.empty() && storage.empty()
+	                  ? lance_create_detached_from_arrow(db_path.c_str(), arrow_schema, metric.c_str(),
+	                                                     table_name.c_str(), err_buf, ERR_BUF_LEN)
+	                  : lance_create_detached_from_arrow_with_vectors(db_path.c_str(), arrow_schema, metric.c_str(),
+	                                                                  table_name.c_str(), vector_columns.c_str(),
+	                                                                  storage.c_str(), err_buf, ERR_BUF_LEN);
 	if (!handle) {
 		throw IOException("Lance create_from_arrow: " + std::string(err_buf));
 	}
 	return handle;
 }
 
-LanceHandle LanceOpenDetached(const std::string &db_path, const std::string &table_name, const std::string &metric) {
+LanceHandle LanceOpenDetached(const std::string &db_path, const std::string &table_name, const std::string &metric,
+                              const std::string &scope) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	auto handle = lance_open_detached(db_path.c_str(), table_name.c_str(), metric.c_str(), err_buf, ERR_BUF_LEN);
+	auto handle = scope.empty() ? lance_open_detached(db_path.c_str(), table_name.c_str(), metric.c_str(), err_buf,
+	                                                  ERR_BUF_LEN)
+	                            : lance_open_detached_scoped(db_path.c_str(), table_name.c_str(), metric.c_str(),
+	                                                         scope.c_str(), err_buf, ERR_BUF_LEN);
 	if (!handle) {
 		throw IOException("Lance open: " + std::string(err_buf));
 	}
@@ -80,6 +414,63 @@ void LanceFreeDetached(LanceHandle handle) {
 	lance_free_detached(handle);
 }
 
+std::vector<LanceCapability> LanceInspectTable(const std::string &db_path, const std::string &table_name) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int64_t n = lance_inspect_table(db_path.c_str(), table_name.c_str(), &exported.schema, &exported.array, err_buf,
+	                                ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance inspect: " + std::string(err_buf));
+	}
+
+	std::vector<LanceCapability> capabilities;
+	capabilities.reserve(n);
+	for (int64_t i = 0; i < n; i++) {
+		LanceCapability capability;
+		capability.kind = ArrowStringAt(*exported.array.children[0], i);
+		capability.name = ArrowStringAt(*exported.array.children[1], i);
+		capability.data_type = ArrowStringAt(*exported.array.children[2], i);
+		capability.nullable = ArrowValueAt(*exported.schema.children[3], *exported.array.children[3], i);
+		capability.dimension = ArrowValueAt(*exported.schema.children[4], *exported.array.children[4], i);
+		capability.detail = ArrowStringAt(*exported.array.children[5], i);
+		capabilities.push_back(std::move(capability));
+	}
+	return capabilities;
+}
+
+void LanceRenameTable(const std::string &db_path, const std::string &old_name, const std::string &new_name) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_rename_table(db_path.c_str(), old_name.c_str(), new_name.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance rename_table: " + std::string(err_buf));
+	}
+}
+
+std::vector<LanceHandleInfo> LanceListHandles() {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_list_handles(&exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance list_handles: " + std::string(err_buf));
+	}
+
+	std::vector<LanceHandleInfo> handles;
+	handles.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceHandleInfo info;
+		info.id = ArrowPrimitiveAt<uint64_t>(*exported.array.children[0], i);
+		info.path = ArrowStringAt(*exported.array.children[1], i);
+		info.table_name = ArrowStringAt(*exported.array.children[2], i);
+		info.read_only =
+		    ArrowValueAt(*exported.schema.children[3], *exported.array.children[3], i).GetValue<bool>();
+		info.buffered_rows = ArrowInt64At(*exported.array.children[4], i);
+		info.opened_ms = ArrowInt64At(*exported.array.children[5], i);
+		info.last_operation_ms = ArrowInt64At(*exported.array.children[6], i);
+		handles.push_back(std::move(info));
+	}
+	return handles;
+}
+
 bool LanceDetachedHasExtraColumns(LanceHandle handle) {
 	return lance_detached_has_extra_columns(handle) != 0;
 }
@@ -88,11 +479,23 @@ int32_t LanceDetachedDimension(LanceHandle handle) {
 	return lance_detached_dimension(handle);
 }
 
+bool LanceDetachedAcceptsQueryDim(LanceHandle handle, int32_t dim) {
+	return lance_detached_accepts_query_dim(handle, dim) != 0;
+}
+
+// Appends rejected by a table quota or ingest constraint surface as constraint errors, others as I/O errors.
+[[noreturn]] static void ThrowAppendError(const std::string &op, int64_t rc, const char *err_buf) {
+	if (rc == LANCE_ERR_QUOTA_EXCEEDED || rc == LANCE_ERR_CONSTRAINT_VIOLATED) {
+		throw ConstraintException("Lance " + op + ": " + std::string(err_buf));
+	}
+	throw IOException("Lance " + op + ": " + std::string(err_buf));
+}
+
 int64_t LanceDetachedAdd(LanceHandle handle, const float *vector, int32_t dimension) {
 	char err_buf[ERR_BUF_LEN] = {0};
 	int64_t label = lance_detached_add(handle, vector, dimension, err_buf, ERR_BUF_LEN);
 	if (label < 0) {
-		throw IOException("Lance add: " + std::string(err_buf));
+		ThrowAppendError("add", label, err_buf);
 	}
 	return label;
 }
@@ -102,107 +505,1357 @@ int32_t LanceDetachedAddBatch(LanceHandle handle, const float *vectors, int32_t
 	char err_buf[ERR_BUF_LEN] = {0};
 	int32_t n = lance_detached_add_batch(handle, vectors, num, dim, out_labels, err_buf, ERR_BUF_LEN);
 	if (n < 0) {
-		throw IOException("Lance add_batch: " + std::string(err_buf));
+		ThrowAppendError("add_batch", n, err_buf);
+	}
+	return n;
+}
+
+void LanceDetachedBeginAppendSession(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_begin_append_session(handle, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance begin_append_session: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedEndAppendSession(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_end_append_session(handle, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		ThrowAppendError("end_append_session", rc, err_buf);
+	}
+}
+
+static void ReleaseOwnedVectors(void *release_ctx) {
+	delete static_cast<std::vector<float> *>(release_ctx);
+}
+
+int32_t LanceDetachedAddBatchOwned(LanceHandle handle, std::vector<float> &&vectors, int32_t num, int32_t dim,
+                                   int64_t *out_labels) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	// Lance releases the vectors through ReleaseOwnedVectors, also on failure
+	auto owned = new std::vector<float>(std::move(vectors));
+	int32_t n = lance_detached_add_batch_owned(handle, owned->data(), num, dim, ReleaseOwnedVectors, owned, out_labels,
+	                                           err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		ThrowAppendError("add_batch", n, err_buf);
 	}
 	return n;
 }
 
-int32_t LanceDetachedAddBatchArrow(LanceHandle handle, void *arrow_schema, void *arrow_array, int64_t *out_labels) {
+int32_t LanceDetachedAddBatchArrow(LanceHandle handle, void *arrow_schema, void *arrow_array, int64_t *out_labels,
+                                   const char *model, const char *token, int64_t *out_rejected,
+                                   uint8_t *out_status) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t n = lance_detached_add_batch_arrow(handle, arrow_schema, arrow_array, out_labels, err_buf, ERR_BUF_LEN);
+	int32_t n = lance_detached_add_batch_arrow(handle, arrow_schema, arrow_array, model, token, out_labels,
+	                                           out_rejected, out_status, err_buf, ERR_BUF_LEN);
 	if (n < 0) {
-		throw IOException("Lance add_batch_arrow: " + std::string(err_buf));
+		ThrowAppendError("add_batch_arrow", n, err_buf);
 	}
 	return n;
 }
 
-int32_t LanceDetachedMerge(LanceHandle target, LanceHandle source, const int64_t *live_source_labels,
-                           int32_t live_count, int64_t *out_old_labels, int64_t *out_new_labels) {
+std::vector<std::pair<int64_t, int64_t>> LanceDetachedMerge(LanceHandle target, LanceHandle source,
+                                                            const int64_t *live_source_labels, int32_t live_count,
+                                                            bool strict, LanceIndexStaleness *out_staleness,
+                                                            const char *token) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t n = lance_detached_merge(target, source, live_source_labels, live_count, out_old_labels, out_new_labels,
+	ArrowStreamGuard stream;
+	LanceIndexStaleness staleness;
+	int32_t retrain = 0;
+	int32_t n = lance_detached_merge(target, source, live_source_labels, live_count, strict ? 1 : 0, token,
+	                                 &stream.stream, &staleness.indexed_rows, &staleness.unindexed_rows, &retrain,
 	                                 err_buf, ERR_BUF_LEN);
 	if (n < 0) {
-		throw IOException("Lance merge: " + std::string(err_buf));
+		ThrowAppendError("merge", n, err_buf);
+	}
+
+	std::vector<std::pair<int64_t, int64_t>> mapping;
+	mapping.reserve(n);
+	while (true) {
+		ArrowExportGuard batch;
+		if (stream.stream.get_next(&stream.stream, &batch.array) != 0) {
+			auto error = stream.stream.get_last_error(&stream.stream);
+			throw IOException("Lance merge: " + std::string(error ? error : "failed to read label mapping"));
+		}
+		if (!batch.array.release) {
+			break;
+		}
+		for (int64_t i = 0; i < batch.array.length; i++) {
+			mapping.emplace_back(ArrowInt64At(*batch.array.children[0], i), ArrowInt64At(*batch.array.children[1], i));
+		}
+	}
+	if (out_staleness) {
+		staleness.retrain_recommended = retrain != 0;
+		*out_staleness = staleness;
+	}
+	return mapping;
+}
+
+void LanceDetachedSetMergeIndexing(LanceHandle handle, int32_t mode) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_detached_set_merge_indexing(handle, mode, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance set_merge_indexing: " + std::string(err_buf));
+	}
+}
+
+LanceIndexStaleness LanceDetachedIndexStaleness(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	LanceIndexStaleness staleness;
+	int32_t rc = lance_detached_index_staleness(handle, &staleness.indexed_rows, &staleness.unindexed_rows, err_buf,
+	                                            ERR_BUF_LEN);
+	if (rc < 0) {
+		throw IOException("Lance index_staleness: " + std::string(err_buf));
+	}
+	staleness.retrain_recommended = rc == 1;
+	return staleness;
+}
+
+int64_t LanceDetachedRepairLabelWatermark(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t max_label = -1;
+	if (lance_detached_repair_label_watermark(handle, &max_label, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance repair_label_watermark: " + std::string(err_buf));
+	}
+	return max_label;
+}
+
+int64_t LanceDetachedRemapLabels(LanceHandle handle, void *arrow_schema, void *arrow_array) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t n = lance_detached_remap_labels(handle, arrow_schema, arrow_array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance remap_labels: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int64_t LanceDetachedRetainOnly(LanceHandle handle, void *arrow_schema, void *arrow_array, bool allow_empty) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t n = lance_detached_retain_only(handle, arrow_schema, arrow_array, allow_empty ? 1 : 0, err_buf,
+	                                       ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance retain_only: " + std::string(err_buf));
+	}
+	return n;
+}
+
+std::vector<LanceMigrationStep> LanceDetachedSchemaDiff(LanceHandle handle, void *target_schema) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int64_t n = lance_detached_schema_diff(handle, target_schema, &exported.schema, &exported.array, err_buf,
+	                                       ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance schema_diff: " + std::string(err_buf));
+	}
+
+	std::vector<LanceMigrationStep> steps;
+	steps.reserve(n);
+	for (int64_t i = 0; i < n; i++) {
+		LanceMigrationStep step;
+		step.step = ArrowStringAt(*exported.array.children[0], i);
+		step.column = ArrowStringAt(*exported.array.children[1], i);
+		step.from_type = ArrowStringAt(*exported.array.children[2], i);
+		step.to_type = ArrowStringAt(*exported.array.children[3], i);
+		steps.push_back(std::move(step));
+	}
+	return steps;
+}
+
+int64_t LanceDetachedApplyMigration(LanceHandle handle, void *target_schema) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t n = lance_detached_apply_migration(handle, target_schema, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance apply_migration: " + std::string(err_buf));
 	}
 	return n;
 }
 
 int32_t LanceDetachedSearch(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
-                            int32_t refine_factor, int64_t *out_labels, float *out_distances) {
+                            int32_t refine_factor, const char *predicate, int64_t *out_labels, float *out_distances,
+                            const char *model, const float *weights, int32_t weights_len, bool weight_query,
+                            int32_t ef) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t n = lance_detached_search(handle, query, dim, k, nprobes, refine_factor, out_labels, out_distances,
-	                                  err_buf, ERR_BUF_LEN);
+	int32_t n = lance_detached_search(handle, query, dim, k, nprobes, refine_factor, ef, predicate, model, weights,
+	                                  weights_len, weight_query ? 1 : 0, 0, out_labels, out_distances, err_buf,
+	                                  ERR_BUF_LEN);
 	if (n < 0) {
 		throw IOException("Lance search: " + std::string(err_buf));
 	}
 	return n;
 }
 
-int64_t LanceDetachedCount(LanceHandle handle) {
+int32_t LanceDetachedSearchDiversity(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                     int32_t refine_factor, int32_t ef, const char *predicate, const char *column,
+                                     int64_t *out_labels, float *out_distances, LanceSearchDiversity &diversity,
+                                     const char *model) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int64_t n = lance_detached_count(handle, err_buf, ERR_BUF_LEN);
+	int32_t n = lance_detached_search_diversity(handle, query, dim, k, nprobes, refine_factor, ef, predicate, model,
+	                                            column, 0, out_labels, out_distances, &diversity.mean_pairwise_distance,
+	                                            &diversity.distinct_values, err_buf, ERR_BUF_LEN);
 	if (n < 0) {
-		throw IOException("Lance count: " + std::string(err_buf));
+		throw IOException("Lance search: " + std::string(err_buf));
 	}
 	return n;
 }
 
-void LanceDetachedDelete(LanceHandle handle, int64_t label) {
+int32_t LanceDetachedSearchConsistent(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                      int32_t refine_factor, const std::string &consistency, int64_t *out_labels,
+                                      float *out_distances, const char *model) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t rc = lance_detached_delete(handle, label, err_buf, ERR_BUF_LEN);
+	int32_t n = lance_detached_search_consistent(handle, query, dim, k, nprobes, refine_factor, nullptr, model,
+	                                             consistency.c_str(), 0, out_labels, out_distances, err_buf,
+	                                             ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchBatch(LanceHandle handle, const float *queries, int32_t dim, int32_t num_queries, int32_t k,
+                                 int32_t nprobes, int32_t refine_factor, int32_t concurrency, int32_t *out_query_idx,
+                                 int64_t *out_labels, float *out_distances) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_batch(handle, queries, dim, num_queries, k, nprobes, refine_factor, nullptr,
+	                                        concurrency, 0, out_query_idx, out_labels, out_distances, err_buf,
+	                                        ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_batch: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchRange(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                 int32_t refine_factor, float lower_bound, float upper_bound, const char *predicate,
+                                 int64_t *out_labels, float *out_distances, const char *model) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_range(handle, query, dim, k, nprobes, refine_factor, lower_bound, upper_bound,
+	                                        predicate, model, 0, out_labels, out_distances, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_range: " + std::string(err_buf));
+	}
+	return n;
+}
+
+// Completion trampolines: user_data is the heap-allocated callback, owned by the call until it completes
+static void SearchAsyncDone(void *user_data, const int64_t *labels, const float *distances, int32_t n,
+                            const char *error) {
+	std::unique_ptr<LanceSearchDone> done(static_cast<LanceSearchDone *>(user_data));
+	std::vector<std::pair<int64_t, float>> results;
+	for (int32_t i = 0; i < n; i++) {
+		results.emplace_back(labels[i], distances[i]);
+	}
+	(*done)(std::move(results), error ? std::string(error) : std::string());
+}
+
+static void AddBatchAsyncDone(void *user_data, const int64_t *labels, int32_t n, const char *error) {
+	std::unique_ptr<LanceAddDone> done(static_cast<LanceAddDone *>(user_data));
+	std::vector<int64_t> result;
+	if (n > 0) {
+		result.assign(labels, labels + n);
+	}
+	(*done)(std::move(result), n < 0 ? n : 0, error ? std::string(error) : std::string());
+}
+
+void LanceDetachedSearchAsync(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                              int32_t refine_factor, const char *predicate, LanceSearchDone done) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	auto callback = new LanceSearchDone(std::move(done));
+	int32_t rc = lance_detached_search_async(handle, query, dim, k, nprobes, refine_factor, predicate, 0,
+	                                         SearchAsyncDone, callback, err_buf, ERR_BUF_LEN);
 	if (rc != 0) {
-		throw IOException("Lance delete: " + std::string(err_buf));
+		delete callback;
+		throw IOException("Lance search_async: " + std::string(err_buf));
 	}
 }
 
-void LanceDetachedDeleteBatch(LanceHandle handle, const int64_t *labels, int32_t count) {
+void LanceDetachedAddBatchAsync(LanceHandle handle, const float *vectors, int32_t num, int32_t dim, LanceAddDone done) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t rc = lance_detached_delete_batch(handle, labels, count, err_buf, ERR_BUF_LEN);
+	auto callback = new LanceAddDone(std::move(done));
+	int32_t rc =
+	    lance_detached_add_batch_async(handle, vectors, num, dim, AddBatchAsyncDone, callback, err_buf, ERR_BUF_LEN);
 	if (rc != 0) {
-		throw IOException("Lance delete_batch: " + std::string(err_buf));
+		delete callback;
+		throw IOException("Lance add_batch_async: " + std::string(err_buf));
 	}
 }
 
-void LanceDetachedCreateIndex(LanceHandle handle, int32_t num_partitions, int32_t num_sub_vectors) {
+int64_t LanceDetachedSearchSubmit(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                                  int32_t refine_factor, const char *predicate) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t rc = lance_detached_create_index(handle, num_partitions, num_sub_vectors, err_buf, ERR_BUF_LEN);
+	int64_t task_id = lance_detached_search_submit(handle, query, dim, k, nprobes, refine_factor, predicate, 0, err_buf,
+	                                               ERR_BUF_LEN);
+	if (task_id < 0) {
+		throw IOException("Lance search_submit: " + std::string(err_buf));
+	}
+	return task_id;
+}
+
+int64_t LanceDetachedAddBatchSubmit(LanceHandle handle, const float *vectors, int32_t num, int32_t dim) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t task_id = lance_detached_add_batch_submit(handle, vectors, num, dim, err_buf, ERR_BUF_LEN);
+	if (task_id < 0) {
+		throw IOException("Lance add_batch_submit: " + std::string(err_buf));
+	}
+	return task_id;
+}
+
+LanceTaskStatus LanceTaskPoll(int64_t task_id) {
+	return static_cast<LanceTaskStatus>(lance_task_poll(task_id));
+}
+
+std::vector<std::pair<int64_t, float>> LanceTaskTakeSearchResult(int64_t task_id) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_task_take_result(task_id, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance task: " + std::string(err_buf));
+	}
+	std::vector<std::pair<int64_t, float>> results;
+	results.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		results.emplace_back(ArrowInt64At(*exported.array.children[0], i),
+		                     ArrowPrimitiveAt<float>(*exported.array.children[1], i));
+	}
+	return results;
+}
+
+std::vector<int64_t> LanceTaskTakeAddResult(int64_t task_id) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_task_take_result(task_id, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance task: " + std::string(err_buf));
+	}
+	std::vector<int64_t> labels;
+	labels.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		labels.push_back(ArrowInt64At(*exported.array.children[0], i));
+	}
+	return labels;
+}
+
+void LanceTaskDiscard(int64_t task_id) {
+	lance_task_discard(task_id);
+}
+
+std::pair<int32_t, int32_t> LanceRuntimeConfigure(int32_t io_threads, int32_t cpu_threads) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t effective_io = 0;
+	int32_t effective_cpu = 0;
+	int32_t rc = lance_runtime_configure(io_threads, cpu_threads, &effective_io, &effective_cpu, err_buf, ERR_BUF_LEN);
 	if (rc != 0) {
-		throw IOException("Lance create_index: " + std::string(err_buf));
+		throw IOException("Lance runtime_configure: " + std::string(err_buf));
 	}
+	return {effective_io, effective_cpu};
 }
 
-void LanceDetachedCreateHnswIndex(LanceHandle handle, int32_t m, int32_t ef_construction) {
+std::pair<int64_t, int64_t> LanceReadLimitsConfigure(int64_t max_rows, int64_t max_bytes) {
+	int64_t effective_rows = 0;
+	int64_t effective_bytes = 0;
+	lance_read_limits_configure(max_rows, max_bytes, &effective_rows, &effective_bytes);
+	return {effective_rows, effective_bytes};
+}
+
+int32_t LanceSetDefaultStorageOptions(const std::vector<std::string> &pairs) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t rc = lance_detached_create_hnsw_index(handle, m, ef_construction, err_buf, ERR_BUF_LEN);
+	std::vector<const char *> pair_ptrs;
+	pair_ptrs.reserve(pairs.size());
+	for (auto &pair : pairs) {
+		pair_ptrs.push_back(pair.c_str());
+	}
+	int32_t n = lance_set_default_storage_options(pair_ptrs.data(), static_cast<int32_t>(pair_ptrs.size()), err_buf,
+	                                              ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance set_default_storage_options: " + std::string(err_buf));
+	}
+	return n;
+}
+
+void LanceRegisterCredentialProvider(LanceCredentialFn callback, void *user_data) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_register_credential_provider(callback, user_data, err_buf, ERR_BUF_LEN);
 	if (rc != 0) {
-		throw IOException("Lance create_hnsw_index: " + std::string(err_buf));
+		throw IOException("Lance register_credential_provider: " + std::string(err_buf));
 	}
 }
 
-void LanceDetachedCompact(LanceHandle handle) {
+void LanceDiskCacheConfigure(const std::string &dir, int64_t max_bytes) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t rc = lance_detached_compact(handle, err_buf, ERR_BUF_LEN);
+	int32_t rc = lance_disk_cache_configure(dir.c_str(), max_bytes, err_buf, ERR_BUF_LEN);
 	if (rc != 0) {
-		throw IOException("Lance compact: " + std::string(err_buf));
+		throw IOException("Lance disk_cache_configure: " + std::string(err_buf));
 	}
 }
 
-int32_t LanceDetachedGetVector(LanceHandle handle, int64_t label, float *out_vec, int32_t capacity) {
+LanceDiskCacheStats LanceDiskCacheGetStats() {
+	LanceDiskCacheStats stats;
+	stats.enabled = lance_disk_cache_stats(&stats.max_bytes, &stats.bytes, &stats.files, &stats.hits,
+	                                       &stats.misses) == 1;
+	return stats;
+}
+
+int32_t LanceDetachedSearchWithNegatives(LanceHandle handle, const float *query, int32_t dim, const float *negatives,
+                                         int32_t negative_count, float weight, int32_t k, int32_t nprobes,
+                                         int32_t refine_factor, const char *predicate, int64_t *out_labels,
+                                         float *out_distances, const char *model) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t dim = lance_detached_get_vector(handle, label, out_vec, capacity, err_buf, ERR_BUF_LEN);
-	if (dim < 0) {
-		throw IOException("Lance get_vector: " + std::string(err_buf));
+	int32_t n = lance_detached_search_with_negatives(handle, query, dim, negatives, negative_count, weight, k, nprobes,
+	                                                 refine_factor, predicate, model, 0, out_labels, out_distances,
+	                                                 err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_with_negatives: " + std::string(err_buf));
 	}
-	return dim;
+	return n;
 }
 
-int32_t LanceDetachedGetAllVectors(LanceHandle handle, int64_t *out_labels, float *out_vectors, int64_t *out_count) {
+int32_t LanceDetachedSearchDedup(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                 const std::string &dedup_column, int32_t nprobes, int32_t refine_factor,
+                                 const char *predicate, int64_t *out_labels, float *out_distances, const char *model) {
 	char err_buf[ERR_BUF_LEN] = {0};
-	int32_t n = lance_detached_get_all_vectors(handle, out_labels, out_vectors, out_count, err_buf, ERR_BUF_LEN);
+	int32_t n = lance_detached_search_dedup(handle, query, dim, k, dedup_column.c_str(), nprobes, refine_factor,
+	                                        predicate, model, 0, out_labels, out_distances, err_buf, ERR_BUF_LEN);
 	if (n < 0) {
-		throw IOException("Lance get_all_vectors: " + std::string(err_buf));
+		throw IOException("Lance search_dedup: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchParents(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                   const char *parent_column, int32_t nprobes, int32_t refine_factor,
+                                   int64_t *out_parents, int64_t *out_labels, float *out_distances) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_parents(handle, query, dim, k, parent_column, nprobes, refine_factor, nullptr,
+	                                          nullptr, 0, out_parents, out_labels, out_distances, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_parents: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchLikeLabels(LanceHandle handle, const int64_t *labels, int32_t label_count,
+                                      const int64_t *negative_labels, int32_t negative_count, float weight, int32_t k,
+                                      int32_t nprobes, int32_t refine_factor, int64_t *out_labels,
+                                      float *out_distances) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_like_labels(handle, labels, label_count, negative_labels, negative_count, weight,
+	                                              k, nprobes, refine_factor, nullptr, 0, out_labels, out_distances,
+	                                              err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_like_labels: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchSampled(LanceHandle handle, const float *query, int32_t dim, int32_t k, double fraction,
+                                   int32_t nprobes, int32_t refine_factor, int64_t *out_labels, float *out_distances,
+                                   double *out_estimated_ranks, double &out_fraction) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_sampled(handle, query, dim, k, fraction, nprobes, refine_factor, nullptr, 0,
+	                                          out_labels, out_distances, out_estimated_ranks, &out_fraction, err_buf,
+	                                          ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_sampled: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchExpand(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t hops,
+                                  int32_t per_hop, double decay, int32_t nprobes, int32_t refine_factor,
+                                  int64_t *out_labels, float *out_distances, int32_t *out_hops, double *out_scores) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_expand(handle, query, dim, k, hops, per_hop, decay, nprobes, refine_factor,
+	                                         out_labels, out_distances, out_hops, out_scores, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_expand: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedSearchWithin(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                  const int64_t *labels, int32_t label_count, int64_t *out_labels,
+                                  float *out_distances) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_within(handle, query, dim, k, labels, label_count, out_labels, out_distances,
+	                                         err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_within: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int64_t LanceDetachedPrefetch(LanceHandle handle, const int64_t *labels, int32_t label_count,
+                              const std::string &predicate) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t rows = lance_detached_prefetch(handle, labels, label_count, predicate.c_str(), err_buf, ERR_BUF_LEN);
+	if (rows < 0) {
+		throw IOException("Lance prefetch: " + std::string(err_buf));
+	}
+	return rows;
+}
+
+int32_t LanceDetachedSearchPca(LanceHandle handle, const float *query, int32_t dim, int32_t k, int32_t nprobes,
+                               int32_t rescore_factor, int64_t *out_labels, float *out_distances) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_search_pca(handle, query, dim, k, nprobes, rescore_factor, out_labels, out_distances,
+	                                      err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_pca: " + std::string(err_buf));
+	}
+	return n;
+}
+
+int32_t LanceDetachedHybridSearch(LanceHandle handle, const float *query, int32_t dim, const std::string &text,
+                                  const char *text_column, int32_t k, int32_t nprobes, int32_t refine_factor,
+                                  const char *predicate, int32_t fusion, float rrf_k, float vector_weight,
+                                  int64_t *out_labels, float *out_scores) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_hybrid_search(handle, query, dim, text.c_str(), text_column, k, nprobes, refine_factor,
+	                                         predicate, fusion, rrf_k, vector_weight, 0, out_labels, out_scores,
+	                                         err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance hybrid_search: " + std::string(err_buf));
 	}
 	return n;
 }
 
+void LanceDetachedCreateFtsIndex(LanceHandle handle, const std::string &column) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_detached_create_fts_index(handle, column.c_str(), err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance create_fts_index: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedCreateScalarIndex(LanceHandle handle, const std::string &column, LanceScalarIndexType index_type) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_detached_create_scalar_index(handle, column.c_str(), static_cast<int32_t>(index_type), err_buf,
+	                                       ERR_BUF_LEN) != 0) {
+		throw IOException("Lance create_scalar_index: " + std::string(err_buf));
+	}
+}
+
+double LanceDetachedTrainPca(LanceHandle handle, int32_t target_dims, int64_t sample, int32_t index_type) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	double explained_variance = 0;
+	int32_t rc =
+	    lance_detached_train_pca(handle, target_dims, sample, index_type, &explained_variance, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance train_pca: " + std::string(err_buf));
+	}
+	return explained_variance;
+}
+
+void LanceDetachedTrainRotation(LanceHandle handle, int32_t num_sub_vectors, int64_t sample) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_detached_train_rotation(handle, num_sub_vectors, sample, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance train_rotation: " + std::string(err_buf));
+	}
+}
+
+std::vector<LanceGraphEdge> LanceDetachedKnnGraph(LanceHandle handle, int32_t k, int64_t sample, int32_t nprobes,
+                                                  int32_t refine_factor) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowStreamGuard stream;
+	int64_t n =
+	    lance_detached_knn_graph(handle, k, sample, nprobes, refine_factor, &stream.stream, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance knn_graph: " + std::string(err_buf));
+	}
+
+	std::vector<LanceGraphEdge> edges;
+	edges.reserve(n);
+	while (true) {
+		ArrowExportGuard batch;
+		if (stream.stream.get_next(&stream.stream, &batch.array) != 0) {
+			auto error = stream.stream.get_last_error(&stream.stream);
+			throw IOException("Lance knn_graph: " + std::string(error ? error : "failed to read edges"));
+		}
+		if (!batch.array.release) {
+			break;
+		}
+		for (int64_t i = 0; i < batch.array.length; i++) {
+			edges.push_back({ArrowInt64At(*batch.array.children[0], i), ArrowInt64At(*batch.array.children[1], i),
+			                 ArrowPrimitiveAt<float>(*batch.array.children[2], i)});
+		}
+	}
+	return edges;
+}
+
+std::vector<LanceRowAddress> LanceDetachedRowAddresses(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowStreamGuard stream;
+	int64_t n = lance_detached_row_addresses(handle, &stream.stream, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance row_addresses: " + std::string(err_buf));
+	}
+
+	std::vector<LanceRowAddress> addresses;
+	addresses.reserve(n);
+	while (true) {
+		ArrowExportGuard batch;
+		if (stream.stream.get_next(&stream.stream, &batch.array) != 0) {
+			auto error = stream.stream.get_last_error(&stream.stream);
+			throw IOException("Lance row_addresses: " + std::string(error ? error : "failed to read addresses"));
+		}
+		if (!batch.array.release) {
+			break;
+		}
+		for (int64_t i = 0; i < batch.array.length; i++) {
+			addresses.push_back({ArrowInt64At(*batch.array.children[0], i),
+			                     ArrowPrimitiveAt<uint64_t>(*batch.array.children[1], i),
+			                     ArrowPrimitiveAt<uint32_t>(*batch.array.children[2], i)});
+		}
+	}
+	return addresses;
+}
+
+LanceSearchCursor LanceDetachedSearchCursorOpen(LanceHandle handle, const float *query, int32_t dim, int32_t k,
+                                                int32_t nprobes, int32_t refine_factor) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	auto cursor =
+	    lance_detached_search_cursor_open(handle, query, dim, k, nprobes, refine_factor, err_buf, ERR_BUF_LEN);
+	if (!cursor) {
+		throw IOException("Lance search_cursor_open: " + std::string(err_buf));
+	}
+	return cursor;
+}
+
+int32_t LanceSearchCursorNext(LanceSearchCursor cursor, int64_t *out_labels, float *out_distances, int32_t capacity) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_search_cursor_next(cursor, out_labels, out_distances, capacity, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance search_cursor_next: " + std::string(err_buf));
+	}
+	return n;
+}
+
+void LanceSearchCursorFree(LanceSearchCursor cursor) {
+	lance_search_cursor_free(cursor);
+}
+
+int64_t LanceDetachedCount(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t n = lance_detached_count(handle, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance count: " + std::string(err_buf));
+	}
+	return n;
+}
+
+LancePingResult LanceDetachedPing(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	LancePingResult result;
+	int32_t rc = lance_detached_ping(handle, &result.version, &result.elapsed_ms, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance ping: " + std::string(err_buf));
+	}
+	return result;
+}
+
+void LanceDetachedDelete(LanceHandle handle, int64_t label) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_delete(handle, label, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance delete: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedDeleteBatch(LanceHandle handle, const int64_t *labels, int32_t count, const char *token) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_delete_batch(handle, labels, count, token, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance delete_batch: " + std::string(err_buf));
+	}
+}
+
+LanceAppliedChanges LanceDetachedApplyChanges(LanceHandle handle, const int64_t *deletes, int32_t delete_count,
+                                              void *arrow_schema, void *arrow_array, int64_t expected_version) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	LanceAppliedChanges applied;
+	applied.version = lance_detached_apply_changes(handle, deletes, delete_count, arrow_schema, arrow_array,
+	                                               expected_version, &applied.deleted, &applied.updated, err_buf,
+	                                               ERR_BUF_LEN);
+	if (applied.version < 0) {
+		throw IOException("Lance apply_changes: " + std::string(err_buf));
+	}
+	return applied;
+}
+
+void LanceDetachedCreateIndex(LanceHandle handle, int32_t num_partitions, int32_t num_sub_vectors) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_create_index(handle, num_partitions, num_sub_vectors, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance create_index: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedCreateHnswIndex(LanceHandle handle, int32_t m, int32_t ef_construction) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_create_hnsw_index(handle, m, ef_construction, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance create_hnsw_index: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedCreateVectorIndex(LanceHandle handle, int32_t index_type, int32_t num_partitions,
+                                    int32_t num_sub_vectors, int32_t m, int32_t ef_construction,
+                                    int32_t sample_rate, const std::string &metric) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_create_vector_index(handle, index_type, num_partitions, num_sub_vectors, m,
+	                                                ef_construction, sample_rate, metric.c_str(), err_buf,
+	                                                ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance create_vector_index: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedRebuildIndex(LanceHandle handle, int32_t index_type, int32_t num_partitions, int32_t num_sub_vectors,
+                               int32_t m, int32_t ef_construction, int32_t sample_rate, const std::string &metric,
+                               bool wait) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_rebuild_index(handle, index_type, num_partitions, num_sub_vectors, m, ef_construction,
+	                                          sample_rate, metric.c_str(), wait ? 1 : 0, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance rebuild_index: " + std::string(err_buf));
+	}
+}
+
+LanceRebuildStatus LanceDetachedRebuildStatus(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_detached_rebuild_status(handle, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n != 1) {
+		throw IOException("Lance rebuild_status: " + std::string(err_buf));
+	}
+
+	LanceRebuildStatus status;
+	status.state = ArrowStringAt(*exported.array.children[0], 0);
+	status.index_type = ArrowStringAt(*exported.array.children[1], 0);
+	status.started_ms = ArrowInt64At(*exported.array.children[2], 0);
+	status.elapsed_ms = ArrowInt64At(*exported.array.children[3], 0);
+	status.error = ArrowStringAt(*exported.array.children[4], 0);
+	status.phase = ArrowStringAt(*exported.array.children[5], 0);
+	return status;
+}
+
+LanceHandle LanceDetachedBeginReadSnapshot(LanceHandle handle, uint64_t *out_version) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	auto snapshot = lance_detached_begin_read_snapshot(handle, out_version, err_buf, ERR_BUF_LEN);
+	if (!snapshot) {
+		throw IOException("Lance begin_read_snapshot: " + std::string(err_buf));
+	}
+	return snapshot;
+}
+
+void LanceDetachedReleaseSnapshot(LanceHandle snapshot) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_detached_release_snapshot(snapshot, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance release_snapshot: " + std::string(err_buf));
+	}
+}
+
+LanceHandle LanceDetachedCreateStaging(LanceHandle handle, bool copy_rows) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	auto staging = lance_detached_create_staging(handle, copy_rows ? 1 : 0, err_buf, ERR_BUF_LEN);
+	if (!staging) {
+		throw IOException("Lance create_staging: " + std::string(err_buf));
+	}
+	return staging;
+}
+
+std::string LanceDetachedPromoteStaging(LanceHandle handle, LanceHandle staging) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	char retired[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_promote_staging(handle, staging, retired, ERR_BUF_LEN, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance promote_staging: " + std::string(err_buf));
+	}
+	return std::string(retired);
+}
+
+void LanceDetachedSetIndexBuildLimits(LanceHandle handle, int64_t max_memory_bytes) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_index_build_limits(handle, max_memory_bytes, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_index_build_limits: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetReadBatchSize(LanceHandle handle, int32_t rows) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_read_batch_size(handle, rows, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_read_batch_size: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetHitBudget(LanceHandle handle, const LanceHitBudget &budget) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_hit_budget(handle, budget.max_nprobes, budget.max_exact_rows, budget.max_overfetch,
+	                                           err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_hit_budget: " + std::string(err_buf));
+	}
+}
+
+LanceHitBudget LanceDetachedGetHitBudget(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	LanceHitBudget budget;
+	int32_t rc = lance_detached_hit_budget(handle, &budget.max_nprobes, &budget.max_exact_rows, &budget.max_overfetch,
+	                                       err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance hit_budget: " + std::string(err_buf));
+	}
+	return budget;
+}
+
+LanceRefinePlan LanceDetachedRefinePlan(LanceHandle handle, int32_t refine_factor, int32_t k) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	LanceRefinePlan plan;
+	int32_t rc = lance_detached_refine_plan(handle, refine_factor, k, &plan.factor, &plan.rescored,
+	                                        &plan.compression, err_buf, ERR_BUF_LEN);
+	if (rc < 0) {
+		throw IOException("Lance refine_plan: " + std::string(err_buf));
+	}
+	plan.is_auto = rc == 1;
+	return plan;
+}
+
+void LanceDetachedCompact(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_compact(handle, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance compact: " + std::string(err_buf));
+	}
+}
+
+int32_t LanceDetachedGetVector(LanceHandle handle, int64_t label, float *out_vec, int32_t capacity) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t dim = lance_detached_get_vector(handle, label, out_vec, capacity, err_buf, ERR_BUF_LEN);
+	if (dim < 0) {
+		throw IOException("Lance get_vector: " + std::string(err_buf));
+	}
+	return dim;
+}
+
+int32_t LanceDetachedGetAllVectors(LanceHandle handle, int64_t *out_labels, float *out_vectors, int64_t *out_count) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t n = lance_detached_get_all_vectors(handle, out_labels, out_vectors, out_count, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance get_all_vectors: " + std::string(err_buf));
+	}
+	return n;
+}
+
+LanceBackupResult LanceDetachedBackup(LanceHandle handle, const std::string &dest, int64_t since_version) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	LanceBackupResult result;
+	if (lance_detached_backup(handle, dest.c_str(), since_version, &result.version, &result.files, &result.bytes,
+	                          err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance backup: " + std::string(err_buf));
+	}
+	return result;
+}
+
+LanceLabelBitmap LanceDetachedLabelBitmap(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	if (lance_detached_label_bitmap(handle, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN) != 1) {
+		throw IOException("Lance label_bitmap: " + std::string(err_buf));
+	}
+
+	LanceLabelBitmap bitmap;
+	bitmap.version = ArrowInt64At(*exported.array.children[0], 0);
+	bitmap.labels = ArrowInt64At(*exported.array.children[1], 0);
+	bitmap.bitmap = ArrowStringAt(*exported.array.children[2], 0);
+	return bitmap;
+}
+
+std::string LanceLabelBitmapCombine(const std::string &a, const std::string &b, LanceBitmapOp op) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	if (lance_label_bitmap_combine(reinterpret_cast<const uint8_t *>(a.data()), static_cast<int64_t>(a.size()),
+	                               reinterpret_cast<const uint8_t *>(b.data()), static_cast<int64_t>(b.size()),
+	                               static_cast<int32_t>(op), &exported.schema, &exported.array, err_buf,
+	                               ERR_BUF_LEN) != 1) {
+		throw IOException("Lance label_bitmap_combine: " + std::string(err_buf));
+	}
+	return ArrowStringAt(*exported.array.children[1], 0);
+}
+
+std::vector<int64_t> LanceLabelBitmapLabels(const std::string &bitmap) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int64_t n = lance_label_bitmap_labels(reinterpret_cast<const uint8_t *>(bitmap.data()),
+	                                      static_cast<int64_t>(bitmap.size()), &exported.schema, &exported.array,
+	                                      err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance label_bitmap_labels: " + std::string(err_buf));
+	}
+
+	std::vector<int64_t> labels;
+	labels.reserve(n);
+	for (int64_t i = 0; i < n; i++) {
+		labels.push_back(ArrowInt64At(*exported.array.children[0], i));
+	}
+	return labels;
+}
+
+LanceManifestBlob LanceDetachedExportManifest(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	if (lance_detached_export_manifest(handle, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN) != 1) {
+		throw IOException("Lance export_manifest: " + std::string(err_buf));
+	}
+
+	LanceManifestBlob blob;
+	blob.version = ArrowInt64At(*exported.array.children[0], 0);
+	// Binary shares the Utf8 layout
+	blob.blob = ArrowStringAt(*exported.array.children[1], 0);
+	return blob;
+}
+
+LanceFormatInfo LanceDetachedFormatInfo(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	if (lance_detached_format_info(handle, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN) != 1) {
+		throw IOException("Lance format_info: " + std::string(err_buf));
+	}
+
+	auto &columns = exported.array.children;
+	auto flag = [&](int64_t col) {
+		return ArrowValueAt(*exported.schema.children[col], *columns[col], 0).GetValue<bool>();
+	};
+	LanceFormatInfo info;
+	info.version = ArrowInt64At(*columns[0], 0);
+	info.writer = ArrowStringAt(*columns[1], 0);
+	info.library = ArrowStringAt(*columns[2], 0);
+	info.storage_format = ArrowStringAt(*columns[3], 0);
+	info.file_versions = ArrowStringAt(*columns[4], 0);
+	info.reader_flags = ArrowInt64At(*columns[5], 0);
+	info.writer_flags = ArrowInt64At(*columns[6], 0);
+	info.can_read = flag(7);
+	info.can_write = flag(8);
+	info.written_by_newer = flag(9);
+	info.problems = ArrowStringAt(*columns[10], 0);
+	return info;
+}
+
+int64_t LanceDetachedImportManifest(LanceHandle handle, const std::string &blob) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int64_t version = lance_detached_import_manifest(handle, reinterpret_cast<const uint8_t *>(blob.data()),
+	                                                 static_cast<int64_t>(blob.size()), err_buf, ERR_BUF_LEN);
+	if (version < 0) {
+		throw IOException("Lance import_manifest: " + std::string(err_buf));
+	}
+	return version;
+}
+
+std::vector<LanceDiskUsageEntry> LanceDetachedDiskUsage(LanceHandle handle, bool include_columns) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_detached_disk_usage(handle, include_columns ? 1 : 0, &exported.schema, &exported.array, err_buf,
+	                                      ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance disk_usage: " + std::string(err_buf));
+	}
+
+	std::vector<LanceDiskUsageEntry> entries;
+	entries.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceDiskUsageEntry entry;
+		entry.component = ArrowStringAt(*exported.array.children[0], i);
+		entry.column = ArrowStringAt(*exported.array.children[1], i);
+		entry.bytes = ArrowInt64At(*exported.array.children[2], i);
+		entries.push_back(std::move(entry));
+	}
+	return entries;
+}
+
+LanceColumnStats LanceDetachedColumnStats(LanceHandle handle, const std::string &column, bool privileged) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_detached_column_stats(handle, column.c_str(), privileged ? 1 : 0, &exported.schema,
+	                                        &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance column_stats: " + std::string(err_buf));
+	}
+
+	LanceColumnStats stats;
+	stats.min = ArrowValueAt(*exported.schema.children[0], *exported.array.children[0], 0);
+	stats.max = ArrowValueAt(*exported.schema.children[1], *exported.array.children[1], 0);
+	stats.null_count = ArrowInt64At(*exported.array.children[2], 0);
+	stats.row_count = ArrowInt64At(*exported.array.children[3], 0);
+	return stats;
+}
+
+LanceApproxStats LanceDetachedApproxStats(LanceHandle handle, const std::string &column, const std::vector<float> &query,
+                                          double sample_fraction, int32_t max_groups, bool privileged) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	LanceApproxStats stats;
+	int32_t n = lance_detached_approx_stats(handle, column.c_str(), query.empty() ? nullptr : query.data(),
+	                                        static_cast<int32_t>(query.size()), sample_fraction, max_groups,
+	                                        privileged ? 1 : 0, &stats.distinct_groups, &exported.schema,
+	                                        &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance approx_stats: " + std::string(err_buf));
+	}
+
+	stats.groups.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceGroupStats group;
+		group.group_value = ArrowValueAt(*exported.schema.children[0], *exported.array.children[0], i);
+		group.other = ArrowValueAt(*exported.schema.children[1], *exported.array.children[1], i).GetValue<bool>();
+		group.row_count = ArrowInt64At(*exported.array.children[2], i);
+		group.min_distance = ArrowValueAt(*exported.schema.children[3], *exported.array.children[3], i);
+		group.avg_distance = ArrowValueAt(*exported.schema.children[4], *exported.array.children[4], i);
+		group.max_distance = ArrowValueAt(*exported.schema.children[5], *exported.array.children[5], i);
+		stats.groups.push_back(std::move(group));
+	}
+	return stats;
+}
+
+void LanceDetachedSetSensitiveColumns(LanceHandle handle, const std::vector<std::string> &columns) {
+	std::string list;
+	for (auto &column : columns) {
+		if (!list.empty()) {
+			list += ",";
+		}
+		list += column;
+	}
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_sensitive_columns(handle, list.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_sensitive_columns: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedClusterBy(LanceHandle handle, const std::string &column, int64_t rows_per_fragment) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_cluster_by(handle, column.c_str(), rows_per_fragment, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance cluster_by: " + std::string(err_buf));
+	}
+}
+
+LancePruningStats LanceDetachedPruningStats(LanceHandle handle, const std::string &predicate) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	char cluster_by[ERR_BUF_LEN] = {0};
+	LancePruningStats stats;
+	int32_t rc = lance_detached_pruning_stats(handle, predicate.c_str(), &stats.total_fragments,
+	                                          &stats.matching_fragments, cluster_by, ERR_BUF_LEN, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance pruning_stats: " + std::string(err_buf));
+	}
+	stats.cluster_by = cluster_by;
+	return stats;
+}
+
+void LanceDetachedSetPipeline(LanceHandle handle, const std::string &spec) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_pipeline(handle, spec.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_pipeline: " + std::string(err_buf));
+	}
+}
+
+void LanceRegisterReranker(const std::string &name, LanceRerankFn callback, void *user_data) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_register_reranker(name.c_str(), callback, user_data, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance register_reranker: " + std::string(err_buf));
+	}
+}
+
+void LanceRegisterDistance(const std::string &name, LanceDistanceFn callback, void *user_data) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_register_distance(name.c_str(), callback, user_data, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance register_distance: " + std::string(err_buf));
+	}
+}
+
+void LanceVectorAdd(const float *a, const float *b, int32_t dim, float *out) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_vector_add(a, b, dim, out, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance vector_add: " + std::string(err_buf));
+	}
+}
+
+void LanceVectorSubtract(const float *a, const float *b, int32_t dim, float *out) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_vector_subtract(a, b, dim, out, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance vector_subtract: " + std::string(err_buf));
+	}
+}
+
+void LanceVectorAverage(const float *vectors, int32_t count, int32_t dim, float *out) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_vector_average(vectors, count, dim, out, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance vector_average: " + std::string(err_buf));
+	}
+}
+
+void LanceVectorNormalize(const float *vector, int32_t dim, float *out) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	if (lance_vector_normalize(vector, dim, out, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance vector_normalize: " + std::string(err_buf));
+	}
+}
+
+float LanceVectorDot(const float *a, const float *b, int32_t dim) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	float dot = 0;
+	if (lance_vector_dot(a, b, dim, &dot, err_buf, ERR_BUF_LEN) != 0) {
+		throw IOException("Lance vector_dot: " + std::string(err_buf));
+	}
+	return dot;
+}
+
+void LanceDetachedSetRescoreMetric(LanceHandle handle, const std::string &metric) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_rescore_metric(handle, metric.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_rescore_metric: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetQueryTransform(LanceHandle handle, const std::string &spec, const std::vector<float> &mean) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_query_transform(handle, spec.c_str(), mean.empty() ? nullptr : mean.data(),
+	                                                static_cast<int32_t>(mean.size()), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_query_transform: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetEmbeddingModel(LanceHandle handle, const std::string &model) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_embedding_model(handle, model.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_embedding_model: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetInt8Range(LanceHandle handle, float min, float max) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_int8_range(handle, min, max, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_int8_range: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedTagDriftBaseline(LanceHandle handle, const std::string &tag) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_tag_drift_baseline(handle, tag.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance tag_drift_baseline: " + std::string(err_buf));
+	}
+}
+
+std::vector<LanceDriftMetric> LanceDetachedDriftReport(LanceHandle handle, const std::string &tag) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n =
+	    lance_detached_drift_report(handle, tag.c_str(), &exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance drift_report: " + std::string(err_buf));
+	}
+
+	std::vector<LanceDriftMetric> metrics;
+	metrics.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceDriftMetric m;
+		m.metric = ArrowStringAt(*exported.array.children[0], i);
+		m.baseline = ArrowPrimitiveAt<double>(*exported.array.children[1], i);
+		m.current = ArrowPrimitiveAt<double>(*exported.array.children[2], i);
+		metrics.push_back(std::move(m));
+	}
+	return metrics;
+}
+
+std::vector<LanceMaintenanceStep> LanceDetachedRunMaintenance(LanceHandle handle, const std::string &plan) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_detached_run_maintenance(handle, plan.c_str(), &exported.schema, &exported.array, err_buf,
+	                                           ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance run_maintenance: " + std::string(err_buf));
+	}
+
+	std::vector<LanceMaintenanceStep> steps;
+	steps.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceMaintenanceStep step;
+		step.step = ArrowStringAt(*exported.array.children[0], i);
+		step.status = ArrowStringAt(*exported.array.children[1], i);
+		step.detail = ArrowStringAt(*exported.array.children[2], i);
+		step.elapsed_ms = ArrowInt64At(*exported.array.children[3], i);
+		steps.push_back(std::move(step));
+	}
+	return steps;
+}
+
+void LanceDetachedSetColdStorage(LanceHandle handle, const std::string &spec) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_cold_storage(handle, spec.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_cold_storage: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetQuota(LanceHandle handle, const std::string &spec) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_quota(handle, spec.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_quota: " + std::string(err_buf));
+	}
+}
+
+void LanceRegisterEmbedder(const std::string &name, LanceEmbedFn callback, void *user_data) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_register_embedder(name.c_str(), callback, user_data, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance register_embedder: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetChunking(LanceHandle handle, const std::string &spec) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_chunking(handle, spec.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_chunking: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetConstraints(LanceHandle handle, const std::string &spec) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_constraints(handle, spec.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_constraints: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetOnInvalid(LanceHandle handle, const std::string &mode) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_on_invalid(handle, mode.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_on_invalid: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetColumnMatching(LanceHandle handle, const std::string &mode) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_column_matching(handle, mode.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_column_matching: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetAccessTracking(LanceHandle handle, bool enabled) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_access_tracking(handle, enabled ? 1 : 0, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_access_tracking: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetRowTtl(LanceHandle handle, bool enabled) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_row_ttl(handle, enabled ? 1 : 0, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_row_ttl: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedSetWriterLease(LanceHandle handle, int64_t ttl_secs) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_writer_lease(handle, ttl_secs, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_writer_lease: " + std::string(err_buf));
+	}
+}
+
+void LanceDetachedFlushAccessStats(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_flush_access_stats(handle, err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance flush_access_stats: " + std::string(err_buf));
+	}
+}
+
+std::vector<LanceColdRow> LanceDetachedColdRows(LanceHandle handle, int64_t idle_ms) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_detached_cold_rows(handle, idle_ms, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance cold_rows: " + std::string(err_buf));
+	}
+
+	std::vector<LanceColdRow> rows;
+	rows.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceColdRow row;
+		row.label = ArrowInt64At(*exported.array.children[0], i);
+		row.hits = ArrowInt64At(*exported.array.children[1], i);
+		row.last_access_ms = ArrowInt64At(*exported.array.children[2], i);
+		rows.push_back(row);
+	}
+	return rows;
+}
+
+void LanceDetachedSetQueryLog(LanceHandle handle, const std::string &spec) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	int32_t rc = lance_detached_set_query_log(handle, spec.c_str(), err_buf, ERR_BUF_LEN);
+	if (rc != 0) {
+		throw IOException("Lance set_query_log: " + std::string(err_buf));
+	}
+}
+
+std::vector<LanceQueryDrift> LanceDetachedReplayQueries(LanceHandle handle, const std::string &tag) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int32_t n = lance_detached_replay_queries(handle, tag.c_str(), &exported.schema, &exported.array, err_buf,
+	                                          ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance replay_queries: " + std::string(err_buf));
+	}
+
+	auto &columns = exported.array.children;
+	std::vector<LanceQueryDrift> rows;
+	rows.reserve(n);
+	for (int32_t i = 0; i < n; i++) {
+		LanceQueryDrift row;
+		row.logged_ms = ArrowInt64At(*columns[0], i);
+		row.query_hash = ArrowStringAt(*columns[1], i);
+		row.logged = ArrowInt64At(*columns[2], i);
+		row.returned = ArrowInt64At(*columns[3], i);
+		row.overlap = ArrowValueAt(*exported.schema.children[4], *columns[4], i).GetValue<double>();
+		row.top_changed = ArrowValueAt(*exported.schema.children[5], *columns[5], i).GetValue<bool>();
+		rows.push_back(row);
+	}
+	return rows;
+}
+
+std::vector<LanceReject> LanceDetachedRejects(LanceHandle handle) {
+	char err_buf[ERR_BUF_LEN] = {0};
+	ArrowExportGuard exported;
+	int64_t n = lance_detached_rejects(handle, &exported.schema, &exported.array, err_buf, ERR_BUF_LEN);
+	if (n < 0) {
+		throw IOException("Lance rejects: " + std::string(err_buf));
+	}
+
+	std::vector<LanceReject> rows;
+	rows.reserve(n);
+	for (int64_t i = 0; i < n; i++) {
+		LanceReject row;
+		row.rejected_ms = ArrowInt64At(*exported.array.children[0], i);
+		row.source_row = ArrowInt64At(*exported.array.children[1], i);
+		row.reason = ArrowStringAt(*exported.array.children[2], i);
+		row.row = ArrowStringAt(*exported.array.children[3], i);
+		rows.push_back(std::move(row));
+	}
+	return rows;
+}
+
 } // namespace duckdb